@@ -0,0 +1,116 @@
+// Animated tiles: the game loads the 32 characters 0x1C0-0x1DF of every overworld map from an
+// animated graphics sheet, and swaps in the sheet's next frames while the map is shown (e.g. for
+// water). A project tile assigned to an animated slot holds the first frame of that character,
+// and is exported as a reference to it. Which sheet is loaded depends on the map: Death Mountain
+// maps have their own, and all others share the default one. So an animated tile can only be
+// used in areas of maps that load its bank, and in an area, each slot can only hold one graphic.
+use std::{fmt, ops::Range};
+
+use serde::{Deserialize, Serialize};
+
+use crate::state::Area;
+
+// Characters of a map's graphics that the game animates.
+pub const ANIMATED_CHARS: Range<u16> = 0x1C0..0x1E0;
+pub const ANIMATED_SLOT_COUNT: u8 = 32;
+
+// Maps that load the Death Mountain animated graphics (instead of the default ones).
+const DEATH_MOUNTAIN_MAPS: [u8; 6] = [0x03, 0x05, 0x07, 0x43, 0x45, 0x47];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum AnimatedBank {
+    Default,
+    DeathMountain,
+}
+
+impl AnimatedBank {
+    pub const ALL: [AnimatedBank; 2] = [AnimatedBank::Default, AnimatedBank::DeathMountain];
+
+    // The bank loaded for a (parent) map.
+    pub fn for_map(map_id: u8) -> Self {
+        if DEATH_MOUNTAIN_MAPS.contains(&map_id) {
+            AnimatedBank::DeathMountain
+        } else {
+            AnimatedBank::Default
+        }
+    }
+
+    // The bank loaded for an area, from the vanilla map that it replaces. Areas that don't
+    // replace a map are assumed to use the default bank.
+    pub fn for_area(area: &Area) -> Self {
+        area.vanilla_map_id
+            .map(AnimatedBank::for_map)
+            .unwrap_or(AnimatedBank::Default)
+    }
+
+    // Graphics sheet holding the bank's first frames.
+    pub fn sheet(self) -> u16 {
+        match self {
+            AnimatedBank::Default => 0x5B,
+            AnimatedBank::DeathMountain => 0x59,
+        }
+    }
+}
+
+impl fmt::Display for AnimatedBank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnimatedBank::Default => write!(f, "Default"),
+            AnimatedBank::DeathMountain => write!(f, "Death Mountain"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct AnimatedSlot {
+    pub bank: AnimatedBank,
+    // Index among the animated characters (0-31):
+    pub slot: u8,
+}
+
+impl AnimatedSlot {
+    // The character of the map's graphics that the slot is loaded into.
+    pub fn gfx_char(self) -> u16 {
+        ANIMATED_CHARS.start + self.slot as u16
+    }
+
+    // The slot `offset` places after this one in the same bank, if there is one.
+    pub fn offset(self, offset: usize) -> Option<Self> {
+        let slot = self.slot as usize + offset;
+        (slot < ANIMATED_SLOT_COUNT as usize).then_some(AnimatedSlot {
+            bank: self.bank,
+            slot: slot as u8,
+        })
+    }
+}
+
+impl fmt::Display for AnimatedSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.bank, self.slot)
+    }
+}
+
+// A choice of a tile's animated slot (or none), for picking in the editor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AnimationChoice(pub Option<AnimatedSlot>);
+
+impl AnimationChoice {
+    pub fn all() -> Vec<AnimationChoice> {
+        let mut choices = vec![AnimationChoice(None)];
+        for bank in AnimatedBank::ALL {
+            for slot in 0..ANIMATED_SLOT_COUNT {
+                choices.push(AnimationChoice(Some(AnimatedSlot { bank, slot })));
+            }
+        }
+        choices
+    }
+}
+
+impl fmt::Display for AnimationChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            None => write!(f, "Static"),
+            Some(slot) => write!(f, "{}", slot),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+// Screen annotations: an optional number per screen of an area (e.g. difficulty or enemy
+// density), drawn over the area and exported as JSON for tools outside the editor, such as a
+// hack's spawn-table generator. They're kept in the area's JSON, so each theme has its own.
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::state::{Area, AreaId, AreaName, EditorState, ThemeName};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScreenAnnotation {
+    // X and Y position of the screen within the area, in screen counts:
+    pub screen: (u8, u8),
+    pub value: u8,
+}
+
+// The annotation of a screen of the area, if any.
+pub fn annotation(area: &Area, screen: (u8, u8)) -> Option<u8> {
+    area.annotations
+        .iter()
+        .find(|a| a.screen == screen)
+        .map(|a| a.value)
+}
+
+// Set or clear the annotation of a screen, keeping them in row-major order of the screens.
+pub fn set_annotation(area: &mut Area, screen: (u8, u8), value: Option<u8>) {
+    area.annotations.retain(|a| a.screen != screen);
+    if let Some(value) = value {
+        area.annotations.push(ScreenAnnotation { screen, value });
+        area.annotations.sort_by_key(|a| (a.screen.1, a.screen.0));
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnnotationRecord {
+    pub area: AreaName,
+    pub theme: ThemeName,
+    pub screen: (u8, u8),
+    // Index of the overworld screen (as in $8A) and the quadrant of it covered by the screen,
+    // for areas imported from the ROM:
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub map_screen: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quadrant: Option<(u8, u8)>,
+    pub value: u8,
+}
+
+fn area_records(area: &Area) -> Vec<AnnotationRecord> {
+    area.annotations
+        .iter()
+        .filter(|a| a.screen.0 < area.size.0 && a.screen.1 < area.size.1)
+        .map(|a| {
+            let (sx, sy) = a.screen;
+            let map_screen = area
+                .vanilla_map_id
+                .map(|parent| parent as u32 + (sx / 2) as u32 + (sy / 2) as u32 * 8)
+                .filter(|&s| s <= 0xFF);
+            AnnotationRecord {
+                area: area.name.clone(),
+                theme: area.theme.clone(),
+                screen: a.screen,
+                map_screen: map_screen.map(|s| s as u8),
+                quadrant: map_screen.map(|_| (sx % 2, sy % 2)),
+                value: a.value,
+            }
+        })
+        .collect()
+}
+
+// Annotations of every area in every theme, including unsaved changes.
+pub fn annotation_records(state: &EditorState) -> Result<Vec<AnnotationRecord>> {
+    let mut out = vec![];
+    for theme in &state.theme_names {
+        for area_name in &state.area_names {
+            let area = state.area_copy(&AreaId {
+                area: area_name.clone(),
+                theme: theme.clone(),
+            })?;
+            out.extend(area_records(&area));
+        }
+    }
+    Ok(out)
+}
+
+pub fn export_annotations(state: &EditorState, path: &Path) -> Result<()> {
+    let records = annotation_records(state)?;
+    let json = serde_json::to_string_pretty(&records)?;
+    std::fs::write(path, json + "\n")
+        .with_context(|| format!("Unable to write {}", path.display()))?;
+    info!(
+        "Exported {} screen annotations to {}",
+        records.len(),
+        path.display()
+    );
+    Ok(())
+}
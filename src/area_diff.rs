@@ -0,0 +1,107 @@
+// Comparing an area with another version of it: the version saved on disk (e.g. for reviewing a
+// modification made outside the editor), or the same area in another theme. Tiles are compared
+// by palette, tile index, and flip; screens missing from the other version count as changed.
+use std::fmt;
+
+use anyhow::{Context, Result};
+
+use crate::{
+    persist::load_area,
+    state::{Area, AreaId, EditorState, ThemeName, TileCoord},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiffBase {
+    Saved,
+    Theme(ThemeName),
+}
+
+impl fmt::Display for DiffBase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiffBase::Saved => write!(f, "Saved version"),
+            DiffBase::Theme(theme) => write!(f, "Theme {}", theme),
+        }
+    }
+}
+
+// An area being compared, with the version it's compared against.
+pub struct AreaDiff {
+    pub area_id: AreaId,
+    pub base: DiffBase,
+    pub base_area: Area,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScreenChanges {
+    pub position: (u8, u8),
+    pub tiles: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AreaChanges {
+    // Coordinates of the changed 8x8 tiles, in row-major order within each screen:
+    pub tiles: Vec<(TileCoord, TileCoord)>,
+    // Screens with any changed tiles:
+    pub screens: Vec<ScreenChanges>,
+    pub size_changed: bool,
+}
+
+impl AreaDiff {
+    pub fn new(state: &EditorState, area_id: &AreaId, base: DiffBase) -> Result<Self> {
+        let base_area = match &base {
+            DiffBase::Saved => load_area(state, area_id).with_context(|| {
+                format!(
+                    "Unable to load the saved version of {} ({})",
+                    area_id.area, area_id.theme
+                )
+            })?,
+            DiffBase::Theme(theme) => state.area_copy(&AreaId {
+                area: area_id.area.clone(),
+                theme: theme.clone(),
+            })?,
+        };
+        Ok(AreaDiff {
+            area_id: area_id.clone(),
+            base,
+            base_area,
+        })
+    }
+}
+
+pub fn compare_areas(area: &Area, base: &Area) -> AreaChanges {
+    let mut changes = AreaChanges {
+        size_changed: area.size != base.size,
+        ..AreaChanges::default()
+    };
+    for screen in &area.screens {
+        let base_screen = base.screens.iter().find(|s| s.position == screen.position);
+        let mut count = 0;
+        for y in 0..32 {
+            for x in 0..32 {
+                let changed = match base_screen {
+                    Some(b) => {
+                        screen.palettes[y][x] != b.palettes[y][x]
+                            || screen.tiles[y][x] != b.tiles[y][x]
+                            || screen.flips[y][x] != b.flips[y][x]
+                    }
+                    None => true,
+                };
+                if changed {
+                    count += 1;
+                    changes.tiles.push((
+                        screen.position.0 as TileCoord * 32 + x as TileCoord,
+                        screen.position.1 as TileCoord * 32 + y as TileCoord,
+                    ));
+                }
+            }
+        }
+        if count > 0 {
+            changes.screens.push(ScreenChanges {
+                position: screen.position,
+                tiles: count,
+            });
+        }
+    }
+    changes
+}
@@ -0,0 +1,148 @@
+// Area manifest: a list of areas to create in one pass (in every theme), e.g. to scaffold the
+// whole world layout at the start of a project.
+//
+// CSV input has one area per line, as `name,size,vanilla_map_id,bg_color`, where `size` is
+// the width and height in screens (e.g. `2x2`), `vanilla_map_id` is the map index (in decimal
+// or as $XX/0xXX hex), and `bg_color` is a 24-bit hex color (e.g. `#488038`, snapped to the
+// nearest 5-bit color) or a SNES color word (e.g. `$1CE9`). The last two fields may be left
+// empty or omitted. Blank lines, lines starting with '#', and a header line are ignored. JSON
+// input is an array of objects with the same fields, with `size` as `[width, height]`.
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::{
+    helpers::{parse_rgb_hex, parse_snes_color},
+    state::{AreaName, ColorRGB},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AreaManifestEntry {
+    pub name: AreaName,
+    pub size: (u8, u8),
+    pub vanilla_map_id: Option<u8>,
+    // The BG color of the current area is used if not given.
+    pub bg_color: Option<ColorRGB>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MapRef {
+    Index(u8),
+    Text(String),
+}
+
+#[derive(Deserialize)]
+struct ManifestRecord {
+    name: String,
+    size: (u8, u8),
+    vanilla_map_id: Option<MapRef>,
+    bg_color: Option<String>,
+}
+
+fn parse_map_id(s: &str) -> Result<u8> {
+    let hex = s
+        .strip_prefix('$')
+        .or_else(|| s.strip_prefix("0x"))
+        .or_else(|| s.strip_prefix("0X"));
+    let map_id = match hex {
+        Some(h) => u8::from_str_radix(h, 16),
+        None => s.parse::<u8>(),
+    };
+    map_id.context(format!("invalid map index: {}", s))
+}
+
+fn parse_size(s: &str) -> Result<(u8, u8)> {
+    let (w, h) = s
+        .split_once(['x', 'X'])
+        .context(format!("invalid size (expected e.g. 2x2): {}", s))?;
+    let parse = |x: &str| x.trim().parse::<u8>().ok();
+    match (parse(w), parse(h)) {
+        (Some(w), Some(h)) => Ok((w, h)),
+        _ => bail!("invalid size (expected e.g. 2x2): {}", s),
+    }
+}
+
+fn parse_bg_color(s: &str) -> Result<ColorRGB> {
+    let color = if s.starts_with('#') {
+        parse_rgb_hex(s)
+    } else {
+        parse_snes_color(s)
+    };
+    color.context(format!("invalid color: {}", s))
+}
+
+fn entry(record: ManifestRecord) -> Result<AreaManifestEntry> {
+    let name = record.name.trim().to_string();
+    if name.is_empty() {
+        bail!("empty area name");
+    }
+    let (w, h) = record.size;
+    if !(1..=8).contains(&w) || !(1..=8).contains(&h) {
+        bail!("{}: size must be from 1x1 to 8x8 screens", name);
+    }
+    let vanilla_map_id = match record.vanilla_map_id {
+        Some(MapRef::Index(i)) => Some(i),
+        Some(MapRef::Text(s)) if !s.trim().is_empty() => Some(parse_map_id(s.trim())?),
+        _ => None,
+    };
+    let bg_color = match record.bg_color {
+        Some(s) if !s.trim().is_empty() => Some(parse_bg_color(s.trim())?),
+        _ => None,
+    };
+    Ok(AreaManifestEntry {
+        name,
+        size: record.size,
+        vanilla_map_id,
+        bg_color,
+    })
+}
+
+fn parse_csv(data: &str) -> Result<Vec<ManifestRecord>> {
+    let mut out = vec![];
+    for (i, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(|x| x.trim()).collect();
+        if !(2..=4).contains(&fields.len()) {
+            bail!("line {}: expected 2 to 4 fields", i + 1);
+        }
+        let size = parse_size(fields[1]);
+        if i == 0 && size.is_err() {
+            // Header line
+            continue;
+        }
+        out.push(ManifestRecord {
+            name: fields[0].to_string(),
+            size: size.context(format!("line {}", i + 1))?,
+            vanilla_map_id: fields.get(2).map(|x| MapRef::Text(x.to_string())),
+            bg_color: fields.get(3).map(|x| x.to_string()),
+        });
+    }
+    Ok(out)
+}
+
+pub fn parse_area_manifest(data: &str, is_json: bool) -> Result<Vec<AreaManifestEntry>> {
+    let records: Vec<ManifestRecord> = if is_json {
+        serde_json::from_str(data)?
+    } else {
+        parse_csv(data)?
+    };
+    let entries = records.into_iter().map(entry).collect::<Result<Vec<_>>>()?;
+    if entries.is_empty() {
+        bail!("The manifest doesn't list any areas.");
+    }
+    Ok(entries)
+}
+
+pub fn load_area_manifest(path: &Path) -> Result<Vec<AreaManifestEntry>> {
+    let data =
+        std::fs::read_to_string(path).context(format!("unable to read {}", path.display()))?;
+    let is_json = path
+        .extension()
+        .is_some_and(|x| x.eq_ignore_ascii_case("json"));
+    parse_area_manifest(&data, is_json).context(format!("unable to load {}", path.display()))
+}
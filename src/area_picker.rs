@@ -0,0 +1,143 @@
+// Area picker: a popup for switching areas by typing part of an area's name, listing the
+// matching areas with their sizes and thumbnails (the saved area PNGs). The query is matched
+// fuzzily (its characters must appear in the name in order, not necessarily together), with
+// the best matches listed first. The arrow keys move through the list, and Enter opens the
+// highlighted area.
+use std::{fs::File, path::Path};
+
+use anyhow::Result;
+use iced::widget::image::Handle;
+
+use crate::{
+    area_raster::SCREEN_PIXELS,
+    dirty::{area_unexported, area_unsaved},
+    persist::area_png_path,
+    state::{AreaId, AreaName, AreaPosition, EditorState},
+};
+
+#[derive(Clone, Debug)]
+pub struct AreaPickerEntry {
+    pub name: AreaName,
+    // X and Y dimensions, measured in number of screens (None if unknown, e.g. with the PNG
+    // missing):
+    pub size: Option<(u8, u8)>,
+    pub thumbnail: Option<Handle>,
+    // Whether the area has unsaved changes, or saved changes not yet exported (see `dirty`):
+    pub unsaved: bool,
+    pub unexported: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct AreaPicker {
+    pub position: AreaPosition,
+    pub entries: Vec<AreaPickerEntry>,
+    pub query: String,
+    // Index of the highlighted area among the matches:
+    pub selected: usize,
+}
+
+// Size of an area from the dimensions of its PNG, which is quicker than reading its JSON.
+fn png_area_size(path: &Path) -> Option<(u8, u8)> {
+    let reader = png::Decoder::new(File::open(path).ok()?).read_info().ok()?;
+    let info = reader.info();
+    Some((
+        (info.width as usize / SCREEN_PIXELS) as u8,
+        (info.height as usize / SCREEN_PIXELS) as u8,
+    ))
+}
+
+// Score of a name matching the query, or None if it doesn't match. Matched characters score
+// more when they follow the previous match or start a word, and less after a gap.
+pub fn fuzzy_score(query: &str, name: &str) -> Option<i32> {
+    let query: Vec<char> = query
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut qi = 0;
+    let mut prev_match: Option<usize> = None;
+    for (i, &c) in name.iter().enumerate() {
+        if qi == query.len() {
+            break;
+        }
+        if c != query[qi] {
+            continue;
+        }
+        score += 1;
+        if i == 0 || !name[i - 1].is_alphanumeric() {
+            score += 3;
+        }
+        match prev_match {
+            Some(p) if p + 1 == i => score += 5,
+            Some(p) => score -= (i - p - 1).min(3) as i32,
+            None => {}
+        }
+        prev_match = Some(i);
+        qi += 1;
+    }
+    (qi == query.len()).then_some(score)
+}
+
+impl AreaPicker {
+    pub fn load(state: &EditorState, position: AreaPosition) -> Result<Self> {
+        let theme = &state.area_id(position).theme;
+        let mut entries = vec![];
+        for name in &state.area_names {
+            let area_id = AreaId {
+                area: name.clone(),
+                theme: theme.clone(),
+            };
+            let png_path = area_png_path(state, &area_id)?;
+            let size = match state.areas.get(&area_id) {
+                Some(area) => Some(area.size),
+                None => png_area_size(&png_path),
+            };
+            entries.push(AreaPickerEntry {
+                name: name.clone(),
+                size,
+                thumbnail: png_path.exists().then(|| Handle::from_path(&png_path)),
+                unsaved: area_unsaved(state, &area_id),
+                unexported: area_unexported(state, &area_id),
+            });
+        }
+        let current = &state.area(position).name;
+        let selected = entries.iter().position(|e| &e.name == current).unwrap_or(0);
+        Ok(AreaPicker {
+            position,
+            entries,
+            query: String::new(),
+            selected,
+        })
+    }
+
+    // Areas matching the query, best matches first (and otherwise in the project's order).
+    pub fn matches(&self) -> Vec<&AreaPickerEntry> {
+        let mut scored: Vec<(i32, &AreaPickerEntry)> = self
+            .entries
+            .iter()
+            .filter_map(|e| Some((fuzzy_score(&self.query, &e.name)?, e)))
+            .collect();
+        scored.sort_by_key(|&(score, _)| -score);
+        scored.into_iter().map(|(_, e)| e).collect()
+    }
+
+    pub fn selected_entry(&self) -> Option<&AreaPickerEntry> {
+        self.matches().get(self.selected).copied()
+    }
+
+    pub fn set_query(&mut self, query: &str) {
+        self.query = query.to_string();
+        self.selected = 0;
+    }
+
+    // Move the highlight up (negative) or down the list, stopping at its ends.
+    pub fn move_selection(&mut self, delta: isize) {
+        let len = self.matches().len();
+        if len == 0 {
+            return;
+        }
+        self.selected = self.selected.saturating_add_signed(delta).min(len - 1);
+    }
+}
@@ -0,0 +1,158 @@
+// Gameplay properties of an area besides its tiles: the music and ambient sound played in it,
+// the palette set that it loads, and flags for custom code. The music and palette set are read
+// from the ROM's tables for the area's parent map when importing, and written back to them when
+// exporting. The game has no table of per-area flags, so the flags are kept in the project, for
+// tools and patches outside the editor.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::import::{Constants, Rom, SnesAddr};
+
+// The stages of the game with their own Light World music (the Dark World has a single stage):
+pub const MUSIC_STAGES: [&str; 4] = ["Start", "Zelda rescued", "Master Sword", "Agahnim defeated"];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AreaMusic {
+    // Music track (0-15), and ambient sound effect (0-15, e.g. rain):
+    pub track: u8,
+    pub ambient: u8,
+}
+
+impl AreaMusic {
+    // The music tables have the track in the low nibble, and the ambient sound in the high one.
+    pub fn from_byte(b: u8) -> Self {
+        AreaMusic {
+            track: b & 0x0F,
+            ambient: b >> 4,
+        }
+    }
+
+    pub fn to_byte(self) -> u8 {
+        (self.ambient & 0x0F) << 4 | self.track & 0x0F
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AreaProperties {
+    // Music for each stage of the game (see `MUSIC_STAGES`), or none for areas that the music
+    // tables don't cover:
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub music: Vec<AreaMusic>,
+    // Index of the palette set (aux and animated palettes) loaded by the area:
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub palette_set: Option<u8>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub flags: u8,
+}
+
+fn is_zero(x: &u8) -> bool {
+    *x == 0
+}
+
+// A change to one of the main area's properties, from the area's properties panel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AreaPropertyEdit {
+    MusicTrack(usize, u8),
+    Ambient(usize, u8),
+    PaletteSet(u8),
+    Flags(u8),
+}
+
+impl AreaProperties {
+    pub fn is_empty(&self) -> bool {
+        *self == AreaProperties::default()
+    }
+
+    // The properties with an edit applied, for an area of the given parent map.
+    pub fn edited(&self, vanilla_map_id: Option<u8>, edit: AreaPropertyEdit) -> AreaProperties {
+        let mut properties = self.clone();
+        properties
+            .music
+            .resize(music_stage_count(vanilla_map_id), AreaMusic::default());
+        match edit {
+            AreaPropertyEdit::MusicTrack(stage, track) => {
+                if let Some(m) = properties.music.get_mut(stage) {
+                    m.track = track;
+                }
+            }
+            AreaPropertyEdit::Ambient(stage, ambient) => {
+                if let Some(m) = properties.music.get_mut(stage) {
+                    m.ambient = ambient;
+                }
+            }
+            AreaPropertyEdit::PaletteSet(palette_set) => properties.palette_set = Some(palette_set),
+            AreaPropertyEdit::Flags(flags) => properties.flags = flags,
+        }
+        properties
+    }
+}
+
+// Number of stages with their own music for areas of the given parent map.
+pub fn music_stage_count(vanilla_map_id: Option<u8>) -> usize {
+    match vanilla_map_id {
+        Some(0..0x40) => MUSIC_STAGES.len(),
+        Some(0x40..0x80) => 1,
+        _ => 0,
+    }
+}
+
+// Addresses of the music table entries of a parent map, for each stage of the game.
+pub fn music_addrs(constants: &Constants, parent: usize) -> Vec<SnesAddr> {
+    let Some(addr) = constants.music_addr else {
+        return vec![];
+    };
+    let count = music_stage_count(u8::try_from(parent).ok()) as u32;
+    (0..count)
+        .map(|stage| match parent {
+            0..0x40 => addr + (stage * 0x40 + parent as u32),
+            _ => addr + (0x100 + parent as u32 - 0x40),
+        })
+        .collect()
+}
+
+// Address of the palette set index of a parent map.
+pub fn palette_set_addr(constants: &Constants, parent: usize) -> Option<SnesAddr> {
+    match parent {
+        0x88 => None,
+        0x80.. => Some(constants.special_map_pal_set_addr + (parent as u32 - 0x80)),
+        _ => Some(constants.map_aux_pal_set_addr + parent as u32),
+    }
+}
+
+pub fn read_properties(rom: &Rom, constants: &Constants, parent: usize) -> Result<AreaProperties> {
+    let music = music_addrs(constants, parent)
+        .into_iter()
+        .map(|addr| Ok(AreaMusic::from_byte(rom.read_u8(addr.into())?)))
+        .collect::<Result<_>>()?;
+    let palette_set = match palette_set_addr(constants, parent) {
+        Some(addr) => Some(rom.read_u8(addr.into())?),
+        None => None,
+    };
+    Ok(AreaProperties {
+        music,
+        palette_set,
+        flags: 0,
+    })
+}
+
+// Write the properties into the ROM's tables for a parent map. Music for stages that the map
+// doesn't have (or missing music, e.g. for an area not imported from the ROM) is skipped.
+pub fn write_properties(
+    rom: &mut Rom,
+    constants: &Constants,
+    parent: usize,
+    properties: &AreaProperties,
+) -> Result<()> {
+    for (addr, music) in music_addrs(constants, parent)
+        .into_iter()
+        .zip(&properties.music)
+    {
+        rom.write_u8(addr.into(), music.to_byte())?;
+    }
+    if let (Some(addr), Some(palette_set)) =
+        (palette_set_addr(constants, parent), properties.palette_set)
+    {
+        rom.write_u8(addr.into(), palette_set)?;
+    }
+    Ok(())
+}
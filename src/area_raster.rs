@@ -0,0 +1,384 @@
+// Rasters of an area's screens for the area views, cached so that redrawing an area only
+// re-rasterizes the screens whose contents changed (e.g. the one being brushed on), and the
+// others keep their image handles (and so their uploaded textures). The cache key of a screen is
+// a hash of everything its raster depends on: its tiles, the graphics and colors of the palettes
+// they use, the area's BG color, and the highlighting settings.
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+};
+
+use hashbrown::{HashMap, HashSet};
+use iced::advanced::image::Handle;
+
+use crate::{
+    area_shapes::AreaCell,
+    helpers::{alpha_blend, collision_color, scale_color},
+    state::{Area, AreaId, ColorIdx, Flip, Palette, PaletteId, TileCoord, TileIdx},
+};
+
+pub const SCREEN_PIXELS: usize = 256;
+// Screen rasters have a pixel of transparent padding around them, since Iced's "nearest
+// neighbor" filter results in the edge pixels having the wrong size:
+pub const RASTER_SIZE: usize = SCREEN_PIXELS + 2;
+
+// What the area view highlights, besides the tiles themselves.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RasterHighlights {
+    // Uses of the selected tile, or of the selected color:
+    pub identify_tile: bool,
+    pub identify_color: bool,
+    pub palette_idx: usize,
+    pub tile_idx: Option<TileIdx>,
+    pub color_idx: Option<ColorIdx>,
+    // Tint tiles with the color of their collision type:
+    pub show_collision: bool,
+}
+
+pub struct ScreenRasterizer<'a> {
+    pub area: &'a Area,
+    pub palettes: &'a [Palette],
+    pub palettes_id_idx_map: &'a HashMap<PaletteId, usize>,
+    pub highlights: RasterHighlights,
+}
+
+impl ScreenRasterizer<'_> {
+    pub fn key(&self, screen_idx: usize) -> u64 {
+        let screen = &self.area.screens[screen_idx];
+        let mut hasher = DefaultHasher::new();
+        self.area.bg_color.hash(&mut hasher);
+        // Only the selection being identified matters:
+        let h = &self.highlights;
+        (h.identify_tile, h.identify_color, h.show_collision).hash(&mut hasher);
+        if h.identify_tile || h.identify_color {
+            h.palette_idx.hash(&mut hasher);
+        }
+        if h.identify_tile {
+            h.tile_idx.hash(&mut hasher);
+        }
+        if h.identify_color {
+            h.color_idx.hash(&mut hasher);
+        }
+        screen.position.hash(&mut hasher);
+        screen.palettes.hash(&mut hasher);
+        screen.tiles.hash(&mut hasher);
+        screen.flips.hash(&mut hasher);
+        let mut palettes_seen = HashSet::new();
+        let mut tiles_seen = HashSet::new();
+        for (palette_row, tile_row) in screen.palettes.iter().zip(&screen.tiles) {
+            for (&palette_id, &tile_idx) in palette_row.iter().zip(tile_row) {
+                let Some(&palette_idx) = self.palettes_id_idx_map.get(&palette_id) else {
+                    continue;
+                };
+                let palette = &self.palettes[palette_idx];
+                if palettes_seen.insert(palette_id) {
+                    (palette_id, palette_idx, palette.colors).hash(&mut hasher);
+                }
+                if tiles_seen.insert((palette_id, tile_idx)) {
+                    (palette_id, tile_idx, palette.tiles.get(tile_idx as usize)).hash(&mut hasher);
+                }
+            }
+        }
+        hasher.finish()
+    }
+
+    // RGBA pixels of the screen (with padding), `RASTER_SIZE` pixels square.
+    pub fn rasterize(&self, screen_idx: usize) -> Vec<u8> {
+        let screen = &self.area.screens[screen_idx];
+        let h = &self.highlights;
+        let row_stride = RASTER_SIZE * 4;
+        let mut data = vec![0; RASTER_SIZE * row_stride];
+        let mut color_bytes: HashMap<usize, [[u8; 3]; 16]> = HashMap::new();
+        for ty in 0..32 {
+            for tx in 0..32 {
+                let palette_id = screen.palettes[ty][tx];
+                let Some(&palette_idx) = self.palettes_id_idx_map.get(&palette_id) else {
+                    // TODO: draw some indicator of the broken tile (due to invalid palette reference)
+                    continue;
+                };
+                let palette = &self.palettes[palette_idx];
+                let tile_idx = screen.tiles[ty][tx];
+                let Some(&tile) = palette.tiles.get(tile_idx as usize) else {
+                    continue;
+                };
+                let cb = color_bytes.entry(palette_idx).or_insert_with(|| {
+                    let mut colors = palette.colors;
+                    colors[0] = self.area.bg_color;
+                    colors.map(|[r, g, b]| [scale_color(r), scale_color(g), scale_color(b)])
+                });
+                let flip = screen.flips[ty][tx];
+                let tile = flip.apply_to_tile(tile);
+                let illegal_flip = match flip {
+                    Flip::None => false,
+                    Flip::Horizontal => !tile.h_flippable,
+                    Flip::Vertical => !tile.v_flippable,
+                    Flip::Both => !tile.h_flippable || !tile.v_flippable,
+                };
+                let identify_tile =
+                    h.identify_tile && h.palette_idx == palette_idx && h.tile_idx == Some(tile_idx);
+                // Tint with the collision type's color (leaving the default type 0 untinted):
+                let collision = (h.show_collision && tile.collision != 0)
+                    .then(|| collision_color(tile.collision));
+                let mut tile_addr = (ty * 8 + 1) * row_stride + (tx * 8 + 1) * 4;
+                for py in 0..8 {
+                    let mut addr = tile_addr;
+                    for px in 0..8 {
+                        let color_idx = tile.pixels[py][px];
+                        let mut color = cb[color_idx as usize];
+                        let identify_color = h.identify_color
+                            && h.color_idx == Some(color_idx)
+                            && h.palette_idx == palette_idx;
+
+                        if illegal_flip && !h.identify_tile && !h.identify_color {
+                            let red_highlight = [255, 0, 0];
+                            color = alpha_blend(color, red_highlight, 0.5);
+                        }
+
+                        let pink_highlight = [255, 105, 180];
+                        if identify_tile {
+                            color = alpha_blend(color, pink_highlight, 0.5);
+                        } else if identify_color {
+                            color = pink_highlight;
+                        }
+                        if let Some(collision) = collision {
+                            color = alpha_blend(color, collision, 0.5);
+                        }
+                        data[addr..(addr + 3)].copy_from_slice(&color);
+                        data[addr + 3] = 255;
+                        addr += 4;
+                    }
+                    tile_addr += row_stride;
+                }
+            }
+        }
+        data
+    }
+}
+
+impl ScreenRasterizer<'_> {
+    pub fn overlay_key(&self, screen_idx: usize) -> u64 {
+        let screen = &self.area.screens[screen_idx];
+        let mut hasher = DefaultHasher::new();
+        screen.overlay.hash(&mut hasher);
+        let mut tiles_seen = HashSet::new();
+        for t in &screen.overlay {
+            if !tiles_seen.insert((t.palette, t.tile)) {
+                continue;
+            }
+            let Some(&palette_idx) = self.palettes_id_idx_map.get(&t.palette) else {
+                continue;
+            };
+            let palette = &self.palettes[palette_idx];
+            (palette.colors, palette.tiles.get(t.tile as usize)).hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    // RGBA pixels of the screen's overlay (with padding), transparent where it has no tiles and
+    // for color 0 of its tiles.
+    pub fn rasterize_overlay(&self, screen_idx: usize) -> Vec<u8> {
+        let screen = &self.area.screens[screen_idx];
+        let row_stride = RASTER_SIZE * 4;
+        let mut data = vec![0; RASTER_SIZE * row_stride];
+        for t in &screen.overlay {
+            let Some(&palette_idx) = self.palettes_id_idx_map.get(&t.palette) else {
+                continue;
+            };
+            let palette = &self.palettes[palette_idx];
+            let Some(&tile) = palette.tiles.get(t.tile as usize) else {
+                continue;
+            };
+            let tile = t.flip.apply_to_tile(tile);
+            let mut tile_addr = (t.y as usize * 8 + 1) * row_stride + (t.x as usize * 8 + 1) * 4;
+            for py in 0..8 {
+                let mut addr = tile_addr;
+                for px in 0..8 {
+                    let color_idx = tile.pixels[py][px] as usize;
+                    if color_idx != 0 {
+                        let [r, g, b] = palette.colors[color_idx];
+                        data[addr..addr + 4].copy_from_slice(&[
+                            scale_color(r),
+                            scale_color(g),
+                            scale_color(b),
+                            255,
+                        ]);
+                    }
+                    addr += 4;
+                }
+                tile_addr += row_stride;
+            }
+        }
+        data
+    }
+}
+
+// An image of some cells of an area, e.g. for previewing brushing them.
+pub struct CellsRaster {
+    // Position of the top-left cell:
+    pub x: TileCoord,
+    pub y: TileCoord,
+    // Size in pixels, with a pixel of padding around the cells:
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<u8>,
+}
+
+impl ScreenRasterizer<'_> {
+    // RGBA pixels of the cells' bounding box, transparent outside of the cells.
+    pub fn rasterize_cells(&self, cells: &[AreaCell]) -> Option<CellsRaster> {
+        let x0 = cells.iter().map(|c| c.x).min()?;
+        let y0 = cells.iter().map(|c| c.y).min()?;
+        let x1 = cells.iter().map(|c| c.x).max()?;
+        let y1 = cells.iter().map(|c| c.y).max()?;
+        let width = (x1 - x0 + 1) as usize * 8 + 2;
+        let height = (y1 - y0 + 1) as usize * 8 + 2;
+        let row_stride = width * 4;
+        let mut data = vec![0; height * row_stride];
+        for cell in cells {
+            let Some(&palette_idx) = self.palettes_id_idx_map.get(&cell.palette) else {
+                continue;
+            };
+            let palette = &self.palettes[palette_idx];
+            let Some(&tile) = palette.tiles.get(cell.tile as usize) else {
+                continue;
+            };
+            let mut colors = palette.colors;
+            colors[0] = self.area.bg_color;
+            let tile = cell.flip.apply_to_tile(tile);
+            let mut tile_addr = ((cell.y - y0) as usize * 8 + 1) * row_stride
+                + ((cell.x - x0) as usize * 8 + 1) * 4;
+            for py in 0..8 {
+                let mut addr = tile_addr;
+                for px in 0..8 {
+                    let [r, g, b] = colors[tile.pixels[py][px] as usize];
+                    data[addr..addr + 4].copy_from_slice(&[
+                        scale_color(r),
+                        scale_color(g),
+                        scale_color(b),
+                        255,
+                    ]);
+                    addr += 4;
+                }
+                tile_addr += row_stride;
+            }
+        }
+        Some(CellsRaster {
+            x: x0,
+            y: y0,
+            width,
+            height,
+            data,
+        })
+    }
+}
+
+// Screen images of an area view, reused while their cache keys are unchanged.
+#[derive(Default)]
+pub struct ScreenRasterCache {
+    area_id: Option<AreaId>,
+    generation: u64,
+    screens: HashMap<usize, (u64, Handle)>,
+    overlays: HashMap<usize, (u64, Handle)>,
+}
+
+// The caches live in the state of the area view widgets, so they're cleared (on their next draw)
+// by advancing a global generation, and counted globally for the memory report:
+static CACHE_GENERATION: AtomicU64 = AtomicU64::new(0);
+static CACHED_SCREENS: AtomicUsize = AtomicUsize::new(0);
+
+pub fn clear_screen_raster_caches() {
+    CACHE_GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+// Number of screen images held by all the area views' caches.
+pub fn cached_screen_count() -> usize {
+    CACHED_SCREENS.load(Ordering::Relaxed)
+}
+
+impl ScreenRasterCache {
+    // The image of a screen, re-rasterizing it if it changed since it was last drawn.
+    pub fn get(&mut self, rasterizer: &ScreenRasterizer, screen_idx: usize) -> Handle {
+        self.check_area(rasterizer);
+        let key = rasterizer.key(screen_idx);
+        if let Some((cached_key, handle)) = self.screens.get(&screen_idx) {
+            if *cached_key == key {
+                return handle.clone();
+            }
+        }
+        let handle = Handle::from_rgba(
+            RASTER_SIZE as u32,
+            RASTER_SIZE as u32,
+            rasterizer.rasterize(screen_idx),
+        );
+        if self
+            .screens
+            .insert(screen_idx, (key, handle.clone()))
+            .is_none()
+        {
+            CACHED_SCREENS.fetch_add(1, Ordering::Relaxed);
+        }
+        handle
+    }
+
+    // The image of a screen's overlay, or None if it has no overlay tiles.
+    pub fn get_overlay(
+        &mut self,
+        rasterizer: &ScreenRasterizer,
+        screen_idx: usize,
+    ) -> Option<Handle> {
+        if rasterizer.area.screens[screen_idx].overlay.is_empty() {
+            return None;
+        }
+        self.check_area(rasterizer);
+        let key = rasterizer.overlay_key(screen_idx);
+        if let Some((cached_key, handle)) = self.overlays.get(&screen_idx) {
+            if *cached_key == key {
+                return Some(handle.clone());
+            }
+        }
+        let handle = Handle::from_rgba(
+            RASTER_SIZE as u32,
+            RASTER_SIZE as u32,
+            rasterizer.rasterize_overlay(screen_idx),
+        );
+        if self
+            .overlays
+            .insert(screen_idx, (key, handle.clone()))
+            .is_none()
+        {
+            CACHED_SCREENS.fetch_add(1, Ordering::Relaxed);
+        }
+        Some(handle)
+    }
+
+    fn check_area(&mut self, rasterizer: &ScreenRasterizer) {
+        let area_id = rasterizer.area.id();
+        let generation = CACHE_GENERATION.load(Ordering::Relaxed);
+        if self.area_id.as_ref() != Some(&area_id) || self.generation != generation {
+            self.clear();
+            self.area_id = Some(area_id);
+            self.generation = generation;
+        }
+    }
+
+    fn clear(&mut self) {
+        CACHED_SCREENS.fetch_sub(self.screens.len() + self.overlays.len(), Ordering::Relaxed);
+        self.screens.clear();
+        self.overlays.clear();
+    }
+
+    // Number of screens with a cached image.
+    pub fn len(&self) -> usize {
+        self.screens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.screens.is_empty()
+    }
+}
+
+impl Drop for ScreenRasterCache {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
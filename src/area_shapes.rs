@@ -0,0 +1,261 @@
+// Shapes drawn onto an area with the Rectangle, Line, and Fill tools, using the brush's tiles.
+//
+// Rectangles and fills repeat the brush as a pattern, aligned to where the shape starts, while
+// lines stamp the whole brush at each point along the line (as if brushing along it).
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use hashbrown::{hash_map::Entry, HashMap};
+use iced::Point;
+
+use crate::state::{Area, Flip, PaletteId, TileBlock, TileCoord, TileIdx};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AreaShape {
+    Rectangle {
+        start: Point<TileCoord>,
+        end: Point<TileCoord>,
+    },
+    Line {
+        start: Point<TileCoord>,
+        end: Point<TileCoord>,
+    },
+    // The contiguous region of matching tiles containing the point (tiles with the same
+    // palette, tile index, and flip, or only the same palette when painting palettes only):
+    Fill(Point<TileCoord>),
+}
+
+// The contents of an 8x8 tile position in an area.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AreaCell {
+    pub x: TileCoord,
+    pub y: TileCoord,
+    pub palette: PaletteId,
+    pub tile: TileIdx,
+    pub flip: Flip,
+}
+
+impl AreaCell {
+    pub fn get(area: &Area, x: TileCoord, y: TileCoord) -> Result<Self> {
+        Ok(AreaCell {
+            x,
+            y,
+            palette: area.get_palette(x, y)?,
+            tile: area.get_tile(x, y)?,
+            flip: area.get_flip(x, y)?,
+        })
+    }
+
+    pub fn set(&self, area: &mut Area) -> Result<()> {
+        area.set_palette(self.x, self.y, self.palette)?;
+        area.set_tile(self.x, self.y, self.tile)?;
+        area.set_flip(self.x, self.y, self.flip)?;
+        Ok(())
+    }
+}
+
+// Points of a line between two positions (inclusive), using Bresenham's algorithm.
+pub fn line_points(start: Point<TileCoord>, end: Point<TileCoord>) -> Vec<Point<TileCoord>> {
+    let (x0, y0) = (start.x as i32, start.y as i32);
+    let (x1, y1) = (end.x as i32, end.y as i32);
+    let dx = (x1 - x0).abs();
+    let dy = -(y1 - y0).abs();
+    let sx = if x0 < x1 { 1 } else { -1 };
+    let sy = if y0 < y1 { 1 } else { -1 };
+    let (mut x, mut y) = (x0, y0);
+    let mut err = dx + dy;
+    let mut out = vec![];
+    loop {
+        out.push(Point::new(x as TileCoord, y as TileCoord));
+        if x == x1 && y == y1 {
+            return out;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+    }
+}
+
+// The new contents of a position, from the given cell of the brush (if not masked out).
+fn brush_cell(
+    area: &Area,
+    block: &TileBlock,
+    palette_only: bool,
+    (x, y): (TileCoord, TileCoord),
+    (bx, by): (usize, usize),
+) -> Option<AreaCell> {
+    if !block.covers(bx, by) {
+        return None;
+    }
+    let mut cell = AreaCell::get(area, x, y).ok()?;
+    cell.palette = block.palettes[by][bx];
+    if !palette_only {
+        cell.tile = block.tiles[by][bx];
+        cell.flip = block.flips[by][bx];
+    }
+    Some(cell)
+}
+
+// The positions changed by brushing with the block's top-left corner at the given position, with
+// their new contents.
+pub fn brush_cells(
+    area: &Area,
+    coords: Point<TileCoord>,
+    block: &TileBlock,
+    palette_only: bool,
+) -> Vec<AreaCell> {
+    let mut cells = vec![];
+    for by in 0..block.size.1 as usize {
+        for bx in 0..block.size.0 as usize {
+            let (x, y) = (coords.x + bx as TileCoord, coords.y + by as TileCoord);
+            cells.extend(brush_cell(area, block, palette_only, (x, y), (bx, by)));
+        }
+    }
+    cells
+}
+
+// The positions changed by drawing the shape, with their new contents.
+pub fn shape_cells(
+    area: &Area,
+    shape: AreaShape,
+    block: &TileBlock,
+    palette_only: bool,
+) -> Vec<AreaCell> {
+    let (w, h) = (block.size.0 as i32, block.size.1 as i32);
+    if w == 0 || h == 0 {
+        return vec![];
+    }
+    let width = area.size.0 as TileCoord * 32;
+    let height = area.size.1 as TileCoord * 32;
+    // The brush repeated as a pattern, with its top-left corner at `origin`:
+    let pattern = |x: TileCoord, y: TileCoord, origin: Point<TileCoord>| {
+        let bx = (x as i32 - origin.x as i32).rem_euclid(w) as usize;
+        let by = (y as i32 - origin.y as i32).rem_euclid(h) as usize;
+        brush_cell(area, block, palette_only, (x, y), (bx, by))
+    };
+    match shape {
+        AreaShape::Rectangle { start, end } => {
+            let origin = Point::new(start.x.min(end.x), start.y.min(end.y));
+            let x1 = start.x.max(end.x).min(width - 1);
+            let y1 = start.y.max(end.y).min(height - 1);
+            let mut cells = vec![];
+            for y in origin.y..=y1 {
+                for x in origin.x..=x1 {
+                    cells.extend(pattern(x, y, origin));
+                }
+            }
+            cells
+        }
+        AreaShape::Line { start, end } => {
+            let mut cells: Vec<AreaCell> = vec![];
+            let mut cell_idx: HashMap<(TileCoord, TileCoord), usize> = HashMap::new();
+            for p in line_points(start, end) {
+                for by in 0..h as usize {
+                    for bx in 0..w as usize {
+                        let (x, y) = (p.x + bx as TileCoord, p.y + by as TileCoord);
+                        if x >= width || y >= height {
+                            continue;
+                        }
+                        let Some(cell) = brush_cell(area, block, palette_only, (x, y), (bx, by))
+                        else {
+                            continue;
+                        };
+                        // Later stamps overwrite earlier ones where they overlap:
+                        match cell_idx.entry((x, y)) {
+                            Entry::Occupied(e) => cells[*e.get()] = cell,
+                            Entry::Vacant(e) => {
+                                e.insert(cells.len());
+                                cells.push(cell);
+                            }
+                        }
+                    }
+                }
+            }
+            cells
+        }
+        AreaShape::Fill(p) => fill_region(area, p, palette_only)
+            .into_iter()
+            .filter_map(|q| pattern(q.x, q.y, p))
+            .collect(),
+    }
+}
+
+// The contiguous region of matching tiles containing the point (tiles with the same palette, tile
+// index, and flip, or only the same palette if `palette_only`), starting with the point.
+pub fn fill_region(area: &Area, p: Point<TileCoord>, palette_only: bool) -> Vec<Point<TileCoord>> {
+    let width = area.size.0 as TileCoord * 32;
+    let height = area.size.1 as TileCoord * 32;
+    let Ok(seed) = AreaCell::get(area, p.x, p.y) else {
+        return vec![];
+    };
+    let matches = |c: AreaCell| {
+        c.palette == seed.palette && (palette_only || (c.tile, c.flip) == (seed.tile, seed.flip))
+    };
+    let mut visited = vec![false; width as usize * height as usize];
+    let mut queue = VecDeque::from([(p.x, p.y)]);
+    visited[p.y as usize * width as usize + p.x as usize] = true;
+    let mut points = vec![];
+    while let Some((x, y)) = queue.pop_front() {
+        points.push(Point::new(x, y));
+        let neighbors = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ];
+        for (x1, y1) in neighbors {
+            if x1 >= width || y1 >= height {
+                continue;
+            }
+            let i = y1 as usize * width as usize + x1 as usize;
+            if visited[i] {
+                continue;
+            }
+            if let Ok(c) = AreaCell::get(area, x1, y1) {
+                if matches(c) {
+                    visited[i] = true;
+                    queue.push_back((x1, y1));
+                }
+            }
+        }
+    }
+    points
+}
+
+// The positions of an area as a brush: the block bounding them, with a mask leaving out the
+// positions in between (if any).
+pub fn region_block(area: &Area, points: &[Point<TileCoord>]) -> Result<TileBlock> {
+    let Some(left) = points.iter().map(|p| p.x).min() else {
+        return Ok(TileBlock::default());
+    };
+    let right = points.iter().map(|p| p.x).max().unwrap();
+    let top = points.iter().map(|p| p.y).min().unwrap();
+    let bottom = points.iter().map(|p| p.y).max().unwrap();
+    let (w, h) = ((right - left + 1) as usize, (bottom - top + 1) as usize);
+    let mut block = TileBlock {
+        size: (w as TileCoord, h as TileCoord),
+        palettes: vec![vec![0; w]; h],
+        tiles: vec![vec![0; w]; h],
+        flips: vec![vec![Flip::None; w]; h],
+        mask: None,
+    };
+    let mut mask = vec![vec![false; w]; h];
+    for p in points {
+        let cell = AreaCell::get(area, p.x, p.y)?;
+        let (x, y) = ((p.x - left) as usize, (p.y - top) as usize);
+        block.palettes[y][x] = cell.palette;
+        block.tiles[y][x] = cell.tile;
+        block.flips[y][x] = cell.flip;
+        mask[y][x] = true;
+    }
+    if mask.iter().flatten().any(|&c| !c) {
+        block.mask = Some(mask);
+    }
+    Ok(block)
+}
@@ -0,0 +1,64 @@
+// Scrolling an area view automatically while selecting or brushing with the cursor near (or past)
+// the edge of the view, so that regions larger than the view can be selected. The area canvas
+// starts and stops the panning as the cursor moves, and a timer (subscribed to while panning)
+// scrolls the view and continues the selection or brush stroke at the tile now under the cursor.
+use std::time::Duration;
+
+use iced::{Point, Size, Vector};
+
+use crate::state::AreaPosition;
+
+// How often the view is scrolled while panning:
+pub const AUTO_PAN_INTERVAL: Duration = Duration::from_millis(30);
+// Distance from the edge of the view (in logical pixels) within which the view is panned:
+const EDGE_MARGIN: f32 = 32.0;
+// Scrolling per tick (in logical pixels) with the cursor at or past the edge of the view:
+const MAX_SPEED: f32 = 24.0;
+
+// What is continued at the cursor as the view pans.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PanAction {
+    Select,
+    Brush,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AutoPan {
+    pub position: AreaPosition,
+    pub action: PanAction,
+    // Scrolling per tick, in logical pixels:
+    pub velocity: Vector,
+    // Position of the cursor relative to the top-left corner of the view, and the view's size:
+    pub cursor: Point,
+    pub viewport: Size,
+}
+
+// Speed along one axis: faster the closer the cursor is to the edge.
+fn edge_speed(p: f32, size: f32) -> f32 {
+    let margin = EDGE_MARGIN.min(size / 4.0);
+    if p < margin {
+        -MAX_SPEED * ((margin - p) / margin).min(1.0)
+    } else if p > size - margin {
+        MAX_SPEED * ((p - (size - margin)) / margin).min(1.0)
+    } else {
+        0.0
+    }
+}
+
+// Panning velocity for the cursor at the given position in the view.
+pub fn edge_velocity(cursor: Point, viewport: Size) -> Vector {
+    Vector::new(
+        edge_speed(cursor.x, viewport.width),
+        edge_speed(cursor.y, viewport.height),
+    )
+}
+
+// The scroll offset after a tick of panning, kept within the content.
+pub fn panned_scroll(scroll: Vector, velocity: Vector, content: Size, viewport: Size) -> Vector {
+    let max_x = (content.width - viewport.width).max(0.0);
+    let max_y = (content.height - viewport.height).max(0.0);
+    Vector::new(
+        (scroll.x + velocity.x).clamp(0.0, max_x),
+        (scroll.y + velocity.y).clamp(0.0, max_y),
+    )
+}
@@ -0,0 +1,59 @@
+// CLI for regenerating the area PNGs of the current project, e.g. after editing the
+// project's JSON files by hand or with other tools.
+
+use anyhow::{bail, Result};
+use clap::Parser;
+use log::info;
+use z3_overworld_editor::{
+    persist::{rebuild_area_pngs, RebuildScope},
+    state,
+};
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// Only rebuild the PNGs of this area
+    #[arg(long)]
+    area: Option<String>,
+    /// Only rebuild the PNGs of this theme
+    #[arg(long)]
+    theme: Option<String>,
+    /// Only rebuild PNGs that are missing or older than their area's JSON
+    #[arg(long)]
+    stale: bool,
+    /// Also rebuild the palettes' color and tile PNGs
+    #[arg(long)]
+    palettes: bool,
+}
+
+pub fn main() -> Result<()> {
+    env_logger::Builder::from_env(
+        env_logger::Env::default().default_filter_or("rebuild_pngs=info,z3_overworld_editor=info"),
+    )
+    .format_timestamp_millis()
+    .init();
+
+    let args = Args::parse();
+    let mut state = state::get_initial_state()?;
+    if state.global_config.project_dir.is_none() {
+        bail!("No project is open; open one in the editor first.");
+    }
+    if let Some(area) = &args.area {
+        if !state.area_names.contains(area) {
+            bail!("Unknown area: {}", area);
+        }
+    }
+    if let Some(theme) = &args.theme {
+        if !state.theme_names.contains(theme) {
+            bail!("Unknown theme: {}", theme);
+        }
+    }
+    let scope = RebuildScope {
+        area: args.area,
+        theme: args.theme,
+        only_stale: args.stale,
+        palettes: args.palettes,
+    };
+    let count = rebuild_area_pngs(&mut state, &scope)?;
+    info!("Done: {} PNGs written", count);
+    Ok(())
+}
@@ -0,0 +1,44 @@
+// CLI for refreshing the map data of the current project from an updated base ROM,
+// without touching areas that have been edited since the last import.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use clap::Parser;
+use log::{info, warn};
+use z3_overworld_editor::{
+    import::{ImportMode, Importer},
+    state,
+};
+
+#[derive(Parser, Debug)]
+struct Args {
+    #[arg(long)]
+    rom: PathBuf,
+}
+
+pub fn main() -> Result<()> {
+    env_logger::Builder::from_env(
+        env_logger::Env::default().default_filter_or("reimport_maps=info,z3_overworld_editor=info"),
+    )
+    .format_timestamp_millis()
+    .init();
+
+    let args = Args::parse();
+    let mut state = state::get_initial_state()?;
+    if state.global_config.project_dir.is_none() {
+        bail!("No project is open; open one in the editor first.");
+    }
+    let report = Importer::import(&mut state, &args.rom, ImportMode::MapsOnly)?;
+    info!("Added: {:?}", report.added);
+    info!("Updated: {:?}", report.updated);
+    info!("Unchanged: {}", report.unchanged.len());
+    info!("Kept (edited in project): {:?}", report.kept);
+    if !report.dropped.is_empty() {
+        info!("Dropped (by import rules): {:?}", report.dropped);
+    }
+    for name in &report.conflicts {
+        warn!("Conflict (edited in both project and ROM): {}", name);
+    }
+    Ok(())
+}
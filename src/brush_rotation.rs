@@ -0,0 +1,111 @@
+// Rotation of the brush by 90 degrees. The SNES can only flip tiles, not rotate them, so each
+// rotated tile is replaced by a tile of the same palette with the rotated graphics (placed with
+// a flip that the tile allows, if need be). A brush with a tile that has no such counterpart
+// can't be rotated.
+use anyhow::{bail, Context, Result};
+use hashbrown::HashMap;
+use itertools::Itertools;
+
+use crate::{
+    state::{ColorIdx, EditorState, Flip, PaletteId, TileBlock, TileCoord, TileIdx},
+    tile_dedup::flip_allowed,
+};
+
+const FLIPS: [Flip; 4] = [Flip::None, Flip::Horizontal, Flip::Vertical, Flip::Both];
+
+pub fn rotate_pixels(pixels: [[ColorIdx; 8]; 8]) -> [[ColorIdx; 8]; 8] {
+    let mut out = [[0; 8]; 8];
+    for (y, row) in out.iter_mut().enumerate() {
+        for (x, c) in row.iter_mut().enumerate() {
+            *c = pixels[7 - x][y];
+        }
+    }
+    out
+}
+
+// The tile and flip that place the graphics of the given placement rotated clockwise, if any.
+fn rotated_tile(
+    state: &EditorState,
+    palette_id: PaletteId,
+    tile_idx: TileIdx,
+    flip: Flip,
+) -> Result<Option<(TileIdx, Flip)>> {
+    let palette = state
+        .palettes_id_idx_map
+        .get(&palette_id)
+        .map(|&idx| &state.palettes[idx])
+        .with_context(|| format!("Palette {} not found", palette_id))?;
+    let tile = palette
+        .tiles
+        .get(tile_idx as usize)
+        .with_context(|| format!("Tile {} not found in palette {}", tile_idx, palette_id))?;
+    let rotated = rotate_pixels(flip.apply_to_pixels(tile.pixels));
+    // The tile itself is preferred, for symmetric graphics:
+    let candidates = std::iter::once(tile_idx).chain(0..palette.tiles.len() as TileIdx);
+    for idx in candidates {
+        let t = &palette.tiles[idx as usize];
+        for f in FLIPS {
+            if flip_allowed(t, f) && f.apply_to_pixels(t.pixels) == rotated {
+                return Ok(Some((idx, f)));
+            }
+        }
+    }
+    Ok(None)
+}
+
+// The brush rotated clockwise by 90 degrees.
+pub fn rotated_block(state: &EditorState, block: &TileBlock) -> Result<TileBlock> {
+    let (w, h) = (block.size.0 as usize, block.size.1 as usize);
+    let mut out = TileBlock {
+        size: (h as TileCoord, w as TileCoord),
+        palettes: vec![vec![0; h]; w],
+        tiles: vec![vec![0; h]; w],
+        flips: vec![vec![Flip::None; h]; w],
+        mask: block.mask.as_ref().map(|_| vec![vec![true; h]; w]),
+    };
+    let mut cache: HashMap<(PaletteId, TileIdx, Flip), Option<(TileIdx, Flip)>> = HashMap::new();
+    let mut missing = vec![];
+    for y in 0..h {
+        for x in 0..w {
+            // The cell at (x, y) moves to (h - 1 - y, x):
+            let (nx, ny) = (h - 1 - y, x);
+            if let (Some(mask), Some(out_mask)) = (&block.mask, &mut out.mask) {
+                out_mask[ny][nx] = mask[y][x];
+            }
+            let palette_id = block.palettes[y][x];
+            out.palettes[ny][nx] = palette_id;
+            if !block.covers(x, y) {
+                continue;
+            }
+            let key = (palette_id, block.tiles[y][x], block.flips[y][x]);
+            let rotated = match cache.get(&key) {
+                Some(&r) => r,
+                None => {
+                    let r = rotated_tile(state, key.0, key.1, key.2)?;
+                    cache.insert(key, r);
+                    r
+                }
+            };
+            match rotated {
+                Some((tile_idx, flip)) => {
+                    out.tiles[ny][nx] = tile_idx;
+                    out.flips[ny][nx] = flip;
+                }
+                None => missing.push((palette_id, block.tiles[y][x])),
+            }
+        }
+    }
+    if !missing.is_empty() {
+        let missing = missing.into_iter().unique().collect_vec();
+        bail!(
+            "Unable to rotate the brush: no rotated graphics for {} tile(s) ({})",
+            missing.len(),
+            missing
+                .iter()
+                .take(5)
+                .map(|(p, t)| format!("palette {} tile {}", p, t))
+                .join(", ")
+        );
+    }
+    Ok(out)
+}
@@ -0,0 +1,180 @@
+// Opt-in session recording, to help reproduce reported editor bugs: the sequence of messages
+// processed by the editor is logged to a file, which can be bundled together with the global
+// config and the current area into a zip file to attach to a bug report.
+//
+// Messages are logged in their debug format, with paths under the project directory or the
+// user's home directory replaced by placeholders. Frequent messages that don't affect the
+// editor's state in a meaningful way (mouse movement, timers) are skipped.
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use anyhow::{Context, Result};
+use flate2::{write::DeflateEncoder, Compression};
+use iced::{keyboard, Event};
+use log::info;
+
+use crate::{message::Message, state::EditorState};
+
+pub struct SessionRecorder {
+    pub path: PathBuf,
+    writer: BufWriter<File>,
+    start_time: Instant,
+    replacements: Vec<(String, &'static str)>,
+}
+
+// Whether the message is worth recording (as opposed to e.g. timers and mouse movement).
+fn is_recorded(message: &Message) -> bool {
+    match message {
+        Message::Event(Event::Keyboard(
+            keyboard::Event::KeyPressed { .. } | keyboard::Event::KeyReleased { .. },
+        )) => true,
+        Message::Event(_) => false,
+        Message::SaveProject
+        | Message::CheckWatcher
+        | Message::SetWindowGeometry { .. }
+        | Message::SetScaleFactor(_)
+        | Message::ProjectLoadProgress { .. }
+        | Message::AreaScrolled { .. }
+        | Message::AutoPanTick => false,
+        _ => true,
+    }
+}
+
+impl SessionRecorder {
+    pub fn start(state: &EditorState) -> Result<Self> {
+        let path = session_log_path(state)?;
+        let file =
+            File::create(&path).with_context(|| format!("Unable to create {}", path.display()))?;
+        info!("Recording session to {}", path.display());
+        Ok(SessionRecorder {
+            path,
+            writer: BufWriter::new(file),
+            start_time: Instant::now(),
+            replacements: path_replacements(state),
+        })
+    }
+
+    pub fn record(&mut self, message: &Message) -> Result<()> {
+        if !is_recorded(message) {
+            return Ok(());
+        }
+        let line = sanitize(&self.replacements, &format!("{:?}", message));
+        let elapsed = self.start_time.elapsed().as_millis();
+        writeln!(self.writer, "{:>9} {}", elapsed, line)?;
+        // Flush right away, so the log is complete even if the editor crashes:
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+// User-specific paths, and the placeholders to replace them with (most specific first).
+fn path_replacements(state: &EditorState) -> Vec<(String, &'static str)> {
+    let mut replacements = vec![];
+    if let Some(project_dir) = &state.global_config.project_dir {
+        replacements.push((project_dir.display().to_string(), "<project>"));
+    }
+    if let Some(dirs) = directories::UserDirs::new() {
+        replacements.push((dirs.home_dir().display().to_string(), "<home>"));
+    }
+    replacements
+}
+
+pub fn sanitize(replacements: &[(String, &'static str)], s: &str) -> String {
+    let mut s = s.to_string();
+    for (from, to) in replacements {
+        if !from.is_empty() {
+            s = s.replace(from, to);
+        }
+    }
+    s
+}
+
+fn session_log_path(state: &EditorState) -> Result<PathBuf> {
+    let dir = state
+        .global_config_path
+        .parent()
+        .context("invalid global config path")?;
+    Ok(dir.join("session.log"))
+}
+
+// Write a zip file with the session log (if recording), the global config, and the current
+// main area, with user-specific paths removed.
+pub fn save_bug_report(state: &EditorState, path: &Path) -> Result<()> {
+    let mut entries: Vec<(String, Vec<u8>)> = vec![];
+    let replacements = path_replacements(state);
+    let config = sanitize(
+        &replacements,
+        &serde_json::to_string_pretty(&state.global_config)?,
+    );
+    let area = state.main_area();
+    let area_json = serde_json::to_string(area)?;
+    let area_name = format!("{}-{}.json", area.name, area.theme);
+    entries.push(("config.json".to_string(), config.into_bytes()));
+    entries.push((area_name, area_json.into_bytes()));
+    if let Some(recorder) = &state.session_recorder {
+        entries.push(("session.log".to_string(), std::fs::read(&recorder.path)?));
+    }
+    write_zip(path, &entries)?;
+    info!("Saved bug report to {}", path.display());
+    Ok(())
+}
+
+// Date of the entries' timestamps (1980-01-01, the earliest date the zip format supports):
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+// Write a zip archive with the given (deflate-compressed) files.
+fn write_zip(path: &Path, entries: &[(String, Vec<u8>)]) -> Result<()> {
+    let mut out: Vec<u8> = vec![];
+    let mut central_directory: Vec<u8> = vec![];
+    for (name, data) in entries {
+        let mut encoder = DeflateEncoder::new(vec![], Compression::default());
+        encoder.write_all(data)?;
+        let compressed = encoder.finish()?;
+        let crc = crc32fast::hash(data);
+        let offset = out.len() as u32;
+
+        // Fields shared between the local header and the central directory header:
+        let mut common: Vec<u8> = vec![];
+        common.extend(20u16.to_le_bytes()); // Version needed to extract (2.0)
+        common.extend(0x0800u16.to_le_bytes()); // Flags: UTF-8 file name
+        common.extend(8u16.to_le_bytes()); // Compression method: deflate
+        common.extend(0u16.to_le_bytes()); // Modification time
+        common.extend(DOS_DATE.to_le_bytes());
+        common.extend(crc.to_le_bytes());
+        common.extend((compressed.len() as u32).to_le_bytes());
+        common.extend((data.len() as u32).to_le_bytes());
+        common.extend((name.len() as u16).to_le_bytes());
+        common.extend(0u16.to_le_bytes()); // Extra field length
+
+        out.extend(0x04034b50u32.to_le_bytes());
+        out.extend(&common);
+        out.extend(name.as_bytes());
+        out.extend(&compressed);
+
+        central_directory.extend(0x02014b50u32.to_le_bytes());
+        central_directory.extend(20u16.to_le_bytes()); // Version made by
+        central_directory.extend(&common);
+        central_directory.extend(0u16.to_le_bytes()); // File comment length
+        central_directory.extend(0u16.to_le_bytes()); // Disk number
+        central_directory.extend(0u16.to_le_bytes()); // Internal attributes
+        central_directory.extend(0u32.to_le_bytes()); // External attributes
+        central_directory.extend(offset.to_le_bytes());
+        central_directory.extend(name.as_bytes());
+    }
+    let central_directory_offset = out.len() as u32;
+    out.extend(&central_directory);
+    out.extend(0x06054b50u32.to_le_bytes());
+    out.extend(0u16.to_le_bytes()); // Disk number
+    out.extend(0u16.to_le_bytes()); // Disk with the central directory
+    out.extend((entries.len() as u16).to_le_bytes());
+    out.extend((entries.len() as u16).to_le_bytes());
+    out.extend((central_directory.len() as u32).to_le_bytes());
+    out.extend(central_directory_offset.to_le_bytes());
+    out.extend(0u16.to_le_bytes()); // Comment length
+    std::fs::write(path, out).with_context(|| format!("Unable to write {}", path.display()))?;
+    Ok(())
+}
@@ -0,0 +1,92 @@
+// Camera-locked screens: screens that the camera stays within instead of scrolling across the
+// rest of the area, like single-screen rooms (e.g. the Master Sword grove). They're marked on the
+// screens in the area's JSON, outlined in the area views, and exported as an assembly table for
+// the hack's camera handling.
+//
+// The table has a byte per overworld screen (as in $8A), with a bit for each locked 256x256
+// quadrant of it: bit 0 for the top-left, 1 for the top-right, 2 for the bottom-left, and 3 for
+// the bottom-right. Only areas imported from the ROM can be matched up with the overworld screens.
+use std::{fmt::Write, path::Path};
+
+use anyhow::{Context, Result};
+use log::info;
+
+use crate::state::{Area, AreaName, EditorState, ThemeName};
+
+// Number of overworld screens: the light world, dark world, and special areas.
+pub const MAP_SCREEN_COUNT: usize = 0xA0;
+
+pub fn is_camera_locked(area: &Area, screen: (u8, u8)) -> bool {
+    area.screens
+        .iter()
+        .any(|s| s.position == screen && s.camera_locked)
+}
+
+pub struct CameraLockTable {
+    pub theme: ThemeName,
+    pub bits: Vec<u8>,
+    // Areas with locked screens that aren't from the ROM, so which aren't in the table:
+    pub skipped: Vec<AreaName>,
+}
+
+// The table of the camera-locked screens of a theme's areas.
+pub fn camera_lock_table(state: &EditorState, theme: &ThemeName) -> Result<CameraLockTable> {
+    let mut bits = vec![0; MAP_SCREEN_COUNT];
+    let mut skipped = vec![];
+    for area in state.theme_areas(theme)? {
+        let locked = area.screens.iter().filter(|s| s.camera_locked);
+        let Some(parent) = area.vanilla_map_id else {
+            if locked.count() > 0 {
+                skipped.push(area.name.clone());
+            }
+            continue;
+        };
+        for screen in locked {
+            let (sx, sy) = screen.position;
+            let map_screen = parent as usize + (sx / 2) as usize + (sy / 2) as usize * 8;
+            if let Some(b) = bits.get_mut(map_screen) {
+                *b |= 1 << (sx % 2 + 2 * (sy % 2));
+            }
+        }
+    }
+    Ok(CameraLockTable {
+        theme: theme.clone(),
+        bits,
+        skipped,
+    })
+}
+
+impl CameraLockTable {
+    pub fn to_asm(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "; Camera-locked screens of theme {}.", self.theme).unwrap();
+        writeln!(
+            out,
+            "; A byte per overworld screen, with bits 0-3 set for its locked quadrants"
+        )
+        .unwrap();
+        writeln!(out, "; (top-left, top-right, bottom-left, bottom-right).").unwrap();
+        for name in &self.skipped {
+            writeln!(
+                out,
+                "; Skipped {}, which isn't imported from the ROM.",
+                name
+            )
+            .unwrap();
+        }
+        writeln!(out, "CameraLockedScreens:").unwrap();
+        for (i, row) in self.bits.chunks(16).enumerate() {
+            let bytes: Vec<String> = row.iter().map(|b| format!("${:02X}", b)).collect();
+            writeln!(out, "    db {} ; ${:02X}", bytes.join(", "), i * 16).unwrap();
+        }
+        out
+    }
+}
+
+pub fn export_camera_locks(state: &EditorState, theme: &ThemeName, path: &Path) -> Result<()> {
+    let table = camera_lock_table(state, theme)?;
+    std::fs::write(path, table.to_asm())
+        .with_context(|| format!("Unable to write {}", path.display()))?;
+    info!("Exported camera-locked screens to {}", path.display());
+    Ok(())
+}
@@ -0,0 +1,270 @@
+// Copying a selection of tiles through the system clipboard, so that it can be pasted into
+// another project (or another running instance of the editor). The clipboard holds the selection
+// as JSON in the same form as a library stamp, carrying the colors and graphics of the tiles it
+// uses, so that pasting doesn't depend on the palettes of the source project.
+//
+// A selection can also be copied as human-readable JSON that refers to the project's own
+// palettes, with a line per row of tiles, for sharing snippets as text (e.g. in code review):
+//
+//     {
+//       "format": "z3-overworld-editor/selection",
+//       "version": 1,
+//       "source": "My Hack",
+//       "palettes": {"0": "Default"},
+//       "rows": [
+//         ["0:$01", "0:$02 H", null],
+//         ["0:$11", "0:$12", "0:$12 HV"]
+//       ]
+//     }
+//
+// Each cell is a palette ID and a tile index (in hex), followed by its flip if any ("H", "V",
+// or "HV"). Cells outside of the brush shape are null.
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    library::{selection_stamp, source_name, stamp_messages, StampPalette},
+    message::Message,
+    state::{EditorState, Flip, PaletteId, TileBlock, TileCoord, TileIdx},
+};
+
+// Identifies clipboard text as tiles copied from the editor:
+pub const CLIPBOARD_FORMAT: &str = "z3-overworld-editor/tiles";
+pub const CLIPBOARD_VERSION: u32 = 1;
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ClipboardTiles {
+    pub format: String,
+    pub version: u32,
+    // Project that the tiles were copied from, for reference:
+    #[serde(default)]
+    pub source: String,
+    // As for a library stamp, the block's palette IDs are indices into `palettes`, and its tile
+    // indices are indices into the tiles of the corresponding palette:
+    pub palettes: Vec<StampPalette>,
+    pub block: TileBlock,
+}
+
+impl ClipboardTiles {
+    pub fn from_selection(state: &EditorState) -> Result<Self> {
+        let s = &state.selected_tile_block;
+        if s.size.0 == 0 || s.size.1 == 0 {
+            bail!("Select some tiles to copy");
+        }
+        let (palettes, block) = selection_stamp(state)?;
+        Ok(ClipboardTiles {
+            format: CLIPBOARD_FORMAT.to_string(),
+            version: CLIPBOARD_VERSION,
+            source: source_name(state),
+            palettes,
+            block,
+        })
+    }
+
+    pub fn to_text(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let value: serde_json::Value =
+            serde_json::from_str(text.trim()).context("Clipboard doesn't contain copied tiles")?;
+        if value.get("format").and_then(|f| f.as_str()) != Some(CLIPBOARD_FORMAT) {
+            bail!("Clipboard doesn't contain copied tiles");
+        }
+        let tiles: ClipboardTiles =
+            serde_json::from_value(value).context("Unable to read copied tiles")?;
+        if tiles.version > CLIPBOARD_VERSION {
+            bail!(
+                "Copied tiles are from a newer version of the editor (format version {})",
+                tiles.version
+            );
+        }
+        let b = &tiles.block;
+        let (w, h) = (b.size.0 as usize, b.size.1 as usize);
+        let fits = |row_lens: Vec<usize>| row_lens.len() == h && row_lens.iter().all(|&n| n == w);
+        if !fits(b.palettes.iter().map(Vec::len).collect())
+            || !fits(b.tiles.iter().map(Vec::len).collect())
+            || !fits(b.flips.iter().map(Vec::len).collect())
+            || b.mask
+                .as_ref()
+                .is_some_and(|m| !fits(m.iter().map(Vec::len).collect()))
+        {
+            bail!("Copied tiles have an inconsistent size");
+        }
+        Ok(tiles)
+    }
+
+    // The (undoable) edits that add the tiles to the project, merging them into palettes with
+    // matching colors or else adding new palettes, after which the tiles become the brush.
+    pub fn paste_messages(&self, state: &EditorState) -> Result<Vec<Message>> {
+        let name = if self.source.is_empty() {
+            "Pasted"
+        } else {
+            &self.source
+        };
+        stamp_messages(state, name, &self.palettes, &self.block)
+    }
+}
+
+pub const SELECTION_FORMAT: &str = "z3-overworld-editor/selection";
+pub const SELECTION_VERSION: u32 = 1;
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct SelectionJson {
+    pub format: String,
+    pub version: u32,
+    #[serde(default)]
+    pub source: String,
+    // Names of the palettes used, for reference (they're not checked when pasting):
+    #[serde(default)]
+    pub palettes: BTreeMap<PaletteId, String>,
+    pub rows: Vec<Vec<Option<String>>>,
+}
+
+fn format_cell(palette_id: PaletteId, tile_idx: TileIdx, flip: Flip) -> String {
+    let flip = match flip {
+        Flip::None => "",
+        Flip::Horizontal => " H",
+        Flip::Vertical => " V",
+        Flip::Both => " HV",
+    };
+    format!("{}:${:02X}{}", palette_id, tile_idx, flip)
+}
+
+fn parse_cell(cell: &str) -> Result<(PaletteId, TileIdx, Flip)> {
+    let parse = || -> Option<(PaletteId, TileIdx, Flip)> {
+        let (palette, rest) = cell.trim().split_once(':')?;
+        let mut parts = rest.split_whitespace();
+        let tile = parts.next()?;
+        let tile = tile.strip_prefix('$').unwrap_or(tile);
+        let flip = match parts.next() {
+            None => Flip::None,
+            Some("H") => Flip::Horizontal,
+            Some("V") => Flip::Vertical,
+            Some("HV") => Flip::Both,
+            Some(_) => return None,
+        };
+        if parts.next().is_some() {
+            return None;
+        }
+        Some((
+            palette.trim().parse().ok()?,
+            TileIdx::from_str_radix(tile, 16).ok()?,
+            flip,
+        ))
+    };
+    parse().with_context(|| {
+        format!(
+            "Invalid cell {:?} (expected a palette ID and tile, such as \"0:$1F H\")",
+            cell
+        )
+    })
+}
+
+impl SelectionJson {
+    pub fn from_selection(state: &EditorState) -> Result<Self> {
+        let s = &state.selected_tile_block;
+        if s.size.0 == 0 || s.size.1 == 0 {
+            bail!("Select some tiles to copy");
+        }
+        let mut palettes = BTreeMap::new();
+        let mut rows = vec![];
+        for y in 0..s.size.1 as usize {
+            let mut row = vec![];
+            for x in 0..s.size.0 as usize {
+                if !s.covers(x, y) {
+                    row.push(None);
+                    continue;
+                }
+                let palette_id = s.palettes[y][x];
+                if let Some(&idx) = state.palettes_id_idx_map.get(&palette_id) {
+                    palettes.insert(palette_id, state.palettes[idx].name.clone());
+                }
+                row.push(Some(format_cell(palette_id, s.tiles[y][x], s.flips[y][x])));
+            }
+            rows.push(row);
+        }
+        Ok(SelectionJson {
+            format: SELECTION_FORMAT.to_string(),
+            version: SELECTION_VERSION,
+            source: source_name(state),
+            palettes,
+            rows,
+        })
+    }
+
+    // Pretty-printed, but with each row of cells on one line.
+    pub fn to_text(&self) -> Result<String> {
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| Ok(format!("    {}", serde_json::to_string(row)?)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(format!(
+            "{{\n  \"format\": {},\n  \"version\": {},\n  \"source\": {},\n  \"palettes\": {},\n  \"rows\": [\n{}\n  ]\n}}\n",
+            serde_json::to_string(&self.format)?,
+            self.version,
+            serde_json::to_string(&self.source)?,
+            serde_json::to_string(&self.palettes)?,
+            rows.join(",\n")
+        ))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(text.trim())
+            .context("Clipboard doesn't contain a JSON selection")?;
+        if value.get("format").and_then(|f| f.as_str()) != Some(SELECTION_FORMAT) {
+            bail!("Clipboard doesn't contain a JSON selection");
+        }
+        let selection: SelectionJson =
+            serde_json::from_value(value).context("Unable to read JSON selection")?;
+        if selection.version > SELECTION_VERSION {
+            bail!(
+                "JSON selection is from a newer version of the editor (format version {})",
+                selection.version
+            );
+        }
+        Ok(selection)
+    }
+
+    // The selection as a brush, which must only use palettes of the project.
+    pub fn to_block(&self, state: &EditorState) -> Result<TileBlock> {
+        let h = self.rows.len();
+        let w = self.rows.first().map(Vec::len).unwrap_or(0);
+        if w == 0 || self.rows.iter().any(|row| row.len() != w) {
+            bail!("JSON selection rows must all have the same (non-zero) number of cells");
+        }
+        let mut block = TileBlock {
+            size: (w as TileCoord, h as TileCoord),
+            palettes: vec![vec![0; w]; h],
+            tiles: vec![vec![0; w]; h],
+            flips: vec![vec![Flip::None; w]; h],
+            mask: None,
+        };
+        let mut mask = vec![vec![true; w]; h];
+        for (y, row) in self.rows.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                let Some(cell) = cell else {
+                    mask[y][x] = false;
+                    continue;
+                };
+                let (palette_id, tile_idx, flip) = parse_cell(cell)?;
+                if !state.palettes_id_idx_map.contains_key(&palette_id) {
+                    bail!(
+                        "JSON selection uses palette {}, which isn't in this project",
+                        palette_id
+                    );
+                }
+                block.palettes[y][x] = palette_id;
+                block.tiles[y][x] = tile_idx;
+                block.flips[y][x] = flip;
+            }
+        }
+        if mask.iter().flatten().any(|&c| !c) {
+            block.mask = Some(mask);
+        }
+        Ok(block)
+    }
+}
@@ -0,0 +1,189 @@
+// Pre-export check of a single area: simulate building the vanilla map16 (tile16) and
+// map32 (tile32) structures from the area's 8x8 tiles, to report whether the area can
+// be represented within the ROM's limits before attempting a full export. Animated tiles are
+// checked against the animated graphics that the area's map loads.
+use hashbrown::{hash_map::Entry, HashMap, HashSet};
+use itertools::Itertools;
+
+use crate::{
+    animated_tiles::{AnimatedBank, AnimatedSlot, ANIMATED_SLOT_COUNT},
+    state::{Area, EditorState, Flip, PaletteCategory, PaletteId, Tile, TileIdx},
+};
+
+// Number of map16 entries in the vanilla ROM (shared by all areas).
+pub const TILE16_BUDGET: usize = 0xEA8;
+// Number of map32 entries in the vanilla ROM (shared by all areas).
+pub const TILE32_BUDGET: usize = 0x22A0;
+// BG palette rows available to overworld graphics (rows 0-1 are used by the HUD).
+pub const PALETTE_ROW_BUDGET: usize = 6;
+// HUD palette rows, which overworld graphics can also use (but which are shared by all maps).
+pub const HUD_PALETTE_ROW_BUDGET: usize = 2;
+// 8x8 character slots in VRAM for overworld BG graphics, besides the animated ones.
+pub const CHAR_BUDGET: usize = 0x200 - ANIMATED_SLOT_COUNT as usize;
+
+// Maximum number of problem locations to list individually.
+const MAX_LISTED_PROBLEMS: usize = 20;
+
+#[derive(Clone, Debug, Default)]
+pub struct CompileReport {
+    pub area_name: String,
+    pub theme: String,
+    pub tile16_count: usize,
+    pub tile32_count: usize,
+    pub palette_count: usize,
+    pub hud_palette_count: usize,
+    pub char_count: usize,
+    // Animated slots used, and uses of them that the game can't show (see `animated_tiles`):
+    pub animated_slot_count: usize,
+    pub animated_problems: Vec<String>,
+    pub animated_problem_count: usize,
+    pub illegal_flip_count: usize,
+    // Locations that can't be converted at all (invalid palette or tile references):
+    pub errors: Vec<String>,
+    pub error_count: usize,
+}
+
+impl CompileReport {
+    pub fn is_ok(&self) -> bool {
+        self.error_count == 0
+            && self.animated_problem_count == 0
+            && self.tile16_count <= TILE16_BUDGET
+            && self.tile32_count <= TILE32_BUDGET
+            && self.palette_count <= PALETTE_ROW_BUDGET
+            && self.hud_palette_count <= HUD_PALETTE_ROW_BUDGET
+            && self.char_count <= CHAR_BUDGET
+    }
+}
+
+// An 8x8 tile reference, as it would appear in a map16 entry.
+pub type TileRef = (PaletteId, TileIdx, Flip);
+
+// The graphic held by an animated slot, and where it was first used.
+type SlotUse = ([[u8; 8]; 8], (u16, u16));
+
+// Character data is shared between palettes and flips, so each distinct graphic is counted once,
+// in a canonical flip orientation.
+pub fn canonical_pixels(pixels: [[u8; 8]; 8]) -> [[u8; 8]; 8] {
+    [Flip::None, Flip::Horizontal, Flip::Vertical, Flip::Both]
+        .into_iter()
+        .map(|f| f.apply_to_pixels(pixels))
+        .min()
+        .unwrap()
+}
+
+pub fn check_area(state: &EditorState, area: &Area) -> CompileReport {
+    let mut report = CompileReport {
+        area_name: area.name.clone(),
+        theme: area.theme.clone(),
+        ..CompileReport::default()
+    };
+    let width = area.size.0 as u16 * 32;
+    let height = area.size.1 as u16 * 32;
+
+    let mut palettes: HashSet<PaletteId> = HashSet::new();
+    let mut hud_palettes: HashSet<PaletteId> = HashSet::new();
+    let mut chars: HashSet<[[u8; 8]; 8]> = HashSet::new();
+    let bank = AnimatedBank::for_area(area);
+    let mut animated: HashMap<AnimatedSlot, SlotUse> = HashMap::new();
+    let mut tile16s: HashSet<[TileRef; 4]> = HashSet::new();
+    let mut tile32s: HashSet<[[TileRef; 4]; 4]> = HashSet::new();
+
+    let mut get_ref = |x: u16, y: u16, report: &mut CompileReport| -> Option<TileRef> {
+        let palette_id = area.get_palette(x, y).ok()?;
+        let tile_idx = area.get_tile(x, y).ok()?;
+        let flip = area.get_flip(x, y).ok()?;
+        let palette = state
+            .palettes_id_idx_map
+            .get(&palette_id)
+            .map(|&idx| &state.palettes[idx]);
+        let tile = palette
+            .and_then(|p| p.tiles.get(tile_idx as usize))
+            .map(Tile::exported);
+        let (Some(palette), Some(tile)) = (palette, tile) else {
+            report.error_count += 1;
+            if report.errors.len() < MAX_LISTED_PROBLEMS {
+                report.errors.push(format!(
+                    "Tile ({}, {}): palette {} tile {} does not exist",
+                    x, y, palette_id, tile_idx
+                ));
+            }
+            return None;
+        };
+        let illegal_flip = match flip {
+            Flip::None => false,
+            Flip::Horizontal => !tile.h_flippable,
+            Flip::Vertical => !tile.v_flippable,
+            Flip::Both => !tile.h_flippable || !tile.v_flippable,
+        };
+        if illegal_flip {
+            report.illegal_flip_count += 1;
+        }
+        if palette.category == PaletteCategory::HUD {
+            hud_palettes.insert(palette_id);
+        } else {
+            palettes.insert(palette_id);
+        }
+        let canonical = canonical_pixels(tile.pixels);
+        match tile.animated {
+            None => {
+                chars.insert(canonical);
+            }
+            Some(slot) => {
+                let problem = if slot.bank != bank {
+                    Some(format!(
+                        "Tile ({}, {}): animated slot {}, but the map loads the {} bank",
+                        x, y, slot, bank
+                    ))
+                } else {
+                    match animated.entry(slot) {
+                        Entry::Vacant(e) => {
+                            e.insert((canonical, (x, y)));
+                            None
+                        }
+                        Entry::Occupied(e) if e.get().0 != canonical => Some(format!(
+                            "Tile ({}, {}): animated slot {} holds a different graphic at ({}, {})",
+                            x,
+                            y,
+                            slot,
+                            e.get().1 .0,
+                            e.get().1 .1
+                        )),
+                        Entry::Occupied(_) => None,
+                    }
+                };
+                if let Some(problem) = problem {
+                    report.animated_problem_count += 1;
+                    if report.animated_problems.len() < MAX_LISTED_PROBLEMS {
+                        report.animated_problems.push(problem);
+                    }
+                }
+            }
+        }
+        Some((palette_id, tile_idx, flip))
+    };
+
+    for (y32, x32) in (0..height / 4).cartesian_product(0..width / 4) {
+        let mut tile32: [[TileRef; 4]; 4] = Default::default();
+        for (i, (dy, dx)) in [(0, 0), (0, 2), (2, 0), (2, 2)].into_iter().enumerate() {
+            let mut tile16: [TileRef; 4] = Default::default();
+            for (j, (ey, ex)) in [(0, 0), (0, 1), (1, 0), (1, 1)].into_iter().enumerate() {
+                let x = x32 * 4 + dx + ex;
+                let y = y32 * 4 + dy + ey;
+                if let Some(r) = get_ref(x, y, &mut report) {
+                    tile16[j] = r;
+                }
+            }
+            tile16s.insert(tile16);
+            tile32[i] = tile16;
+        }
+        tile32s.insert(tile32);
+    }
+
+    report.tile16_count = tile16s.len();
+    report.tile32_count = tile32s.len();
+    report.palette_count = palettes.len();
+    report.hud_palette_count = hud_palettes.len();
+    report.char_count = chars.len();
+    report.animated_slot_count = animated.len();
+    report
+}
@@ -0,0 +1,188 @@
+// The game's "LZ2" compression format, used for overworld map data (among other things).
+//
+// The data is a sequence of commands, each starting with a header byte `CCCLLLLL` giving
+// the command and its length (L + 1, up to 32 bytes of output). A header of `111CCCLL`
+// instead starts an extended command, with the length (L + 1, up to 1024) continuing in
+// the next byte. The data ends with $FF.
+//
+// Graphics use little-endian addresses for the repeat command, while map data uses
+// big-endian addresses. This is the one implementation of the format, both for reading the ROM
+// on import and for writing data back on export.
+use anyhow::{bail, Context, Result};
+
+const CMD_COPY: u8 = 0; // Copy the following bytes
+const CMD_BYTE_FILL: u8 = 1; // Repeat a single byte
+const CMD_WORD_FILL: u8 = 2; // Alternate between two bytes
+const CMD_INCREASING_FILL: u8 = 3; // A byte, followed by increasing values
+const CMD_REPEAT: u8 = 4; // Copy earlier output, from an absolute address
+const END_MARKER: u8 = 0xFF;
+
+const MAX_SHORT_LEN: usize = 32;
+const MAX_LEN: usize = 1024;
+
+fn push_header(out: &mut Vec<u8>, cmd: u8, len: usize) {
+    let l = len - 1;
+    if len <= MAX_SHORT_LEN {
+        out.push((cmd << 5) | l as u8);
+    } else {
+        out.push(0xE0 | (cmd << 2) | (l >> 8) as u8);
+        out.push((l & 0xFF) as u8);
+    }
+}
+
+fn header_size(len: usize) -> usize {
+    if len <= MAX_SHORT_LEN {
+        1
+    } else {
+        2
+    }
+}
+
+fn flush_literals(out: &mut Vec<u8>, data: &[u8]) {
+    for chunk in data.chunks(MAX_LEN) {
+        push_header(out, CMD_COPY, chunk.len());
+        out.extend_from_slice(chunk);
+    }
+}
+
+// The longest run starting at `i` for each command, with the bytes of its argument.
+fn candidates(data: &[u8], i: usize, big_endian_offset: bool) -> [(u8, usize, Vec<u8>); 4] {
+    let rest = &data[i..(i + MAX_LEN).min(data.len())];
+    let byte_len = rest.iter().take_while(|&&b| b == rest[0]).count();
+    let word_len = if rest.len() >= 2 {
+        (0..rest.len())
+            .take_while(|&k| rest[k] == rest[k % 2])
+            .count()
+    } else {
+        0
+    };
+    let inc_len = (0..rest.len())
+        .take_while(|&k| rest[k] == rest[0].wrapping_add(k as u8))
+        .count();
+    let mut repeat = (0, 0);
+    for start in 0..i.min(0x10000) {
+        // The source may overlap the output being produced:
+        let len = (0..rest.len())
+            .take_while(|&k| data[start + k] == rest[k])
+            .count();
+        if len > repeat.1 {
+            repeat = (start, len);
+        }
+    }
+    let offset = [(repeat.0 & 0xFF) as u8, (repeat.0 >> 8) as u8];
+    let offset = if big_endian_offset {
+        vec![offset[1], offset[0]]
+    } else {
+        offset.to_vec()
+    };
+    [
+        (CMD_BYTE_FILL, byte_len, vec![rest[0]]),
+        (CMD_WORD_FILL, word_len, rest[..2.min(rest.len())].to_vec()),
+        (CMD_INCREASING_FILL, inc_len, vec![rest[0]]),
+        (CMD_REPEAT, repeat.1, offset),
+    ]
+}
+
+// Compress the data greedily: at each position, use whichever command saves the most
+// bytes compared to copying the data as-is.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    compress_with(data, false)
+}
+
+pub fn compress_with(data: &[u8], big_endian_offset: bool) -> Vec<u8> {
+    let mut out = vec![];
+    let mut literal_start = 0;
+    let mut i = 0;
+    while i < data.len() {
+        let best = candidates(data, i, big_endian_offset)
+            .into_iter()
+            .filter(|(_, len, _)| *len > 0)
+            .max_by_key(|(_, len, arg)| *len as isize - (arg.len() + header_size(*len)) as isize);
+        match best {
+            Some((cmd, len, arg)) if len > arg.len() + header_size(len) => {
+                flush_literals(&mut out, &data[literal_start..i]);
+                push_header(&mut out, cmd, len);
+                out.extend_from_slice(&arg);
+                i += len;
+                literal_start = i;
+            }
+            _ => {
+                i += 1;
+            }
+        }
+    }
+    flush_literals(&mut out, &data[literal_start..]);
+    out.push(END_MARKER);
+    out
+}
+
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>> {
+    decompress_with(data, false)
+}
+
+pub fn decompress_with(data: &[u8], big_endian_offset: bool) -> Result<Vec<u8>> {
+    Ok(decompress_prefix(data, big_endian_offset)?.0)
+}
+
+// Decompress data that may be followed by other data (e.g. further compressed blocks), also
+// returning the length of the compressed data, including its end marker.
+pub fn decompress_prefix(data: &[u8], big_endian_offset: bool) -> Result<(Vec<u8>, usize)> {
+    let mut out: Vec<u8> = vec![];
+    let mut pos = 0;
+    let next = |pos: &mut usize| -> Result<u8> {
+        let b = *data
+            .get(*pos)
+            .context("unexpected end of compressed data")?;
+        *pos += 1;
+        Ok(b)
+    };
+    loop {
+        let header = next(&mut pos)?;
+        if header == END_MARKER {
+            return Ok((out, pos));
+        }
+        let (cmd, len) = if header >> 5 == 7 {
+            let low = next(&mut pos)?;
+            (
+                (header >> 2) & 7,
+                (((header & 3) as usize) << 8 | low as usize) + 1,
+            )
+        } else {
+            (header >> 5, (header & 0x1F) as usize + 1)
+        };
+        match cmd {
+            CMD_COPY => {
+                for _ in 0..len {
+                    out.push(next(&mut pos)?);
+                }
+            }
+            CMD_BYTE_FILL => {
+                let b = next(&mut pos)?;
+                out.extend(std::iter::repeat_n(b, len));
+            }
+            CMD_WORD_FILL => {
+                let w = [next(&mut pos)?, next(&mut pos)?];
+                out.extend((0..len).map(|k| w[k % 2]));
+            }
+            CMD_INCREASING_FILL => {
+                let b = next(&mut pos)?;
+                out.extend((0..len).map(|k| b.wrapping_add(k as u8)));
+            }
+            CMD_REPEAT => {
+                let (b0, b1) = (next(&mut pos)? as usize, next(&mut pos)? as usize);
+                let addr = if big_endian_offset {
+                    b0 << 8 | b1
+                } else {
+                    b0 | b1 << 8
+                };
+                for k in 0..len {
+                    let b = *out
+                        .get(addr + k)
+                        .context("repeat refers past end of output")?;
+                    out.push(b);
+                }
+            }
+            _ => bail!("invalid compression command {}", cmd),
+        }
+    }
+}
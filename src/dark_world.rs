@@ -0,0 +1,110 @@
+// Generating a dark world area from a light world area, as a starting point for designing the
+// two worlds in parallel. The dark world area (for the map 0x40 above the light world one) gets
+// a copy of the light world layout, with each palette replaced by its dark world counterpart.
+// If the project already has an area for the dark world map, its layout is replaced, keeping
+// its name and background color.
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Context, Result};
+
+use crate::state::{Area, AreaId, AreaName, ColorRGB, EditorState, PaletteId};
+
+// Offset from a light world map ID to the corresponding dark world map:
+pub const DARK_WORLD_OFFSET: u8 = 0x40;
+
+// Background color of new dark world areas, as used on import for ROMs without custom colors:
+const DARK_WORLD_BG_COLOR: ColorRGB = [18, 17, 10];
+
+// The dark world map ID for a light world area.
+pub fn dark_world_map_id(light_area: &Area) -> Result<u8> {
+    match light_area.vanilla_map_id {
+        Some(id) if id < DARK_WORLD_OFFSET => Ok(id + DARK_WORLD_OFFSET),
+        Some(id) => bail!("Map {:02X} is not in the light world.", id),
+        None => bail!("Area {} has no vanilla map.", light_area.name),
+    }
+}
+
+// The area for the given map in the main area's theme, if there is one.
+pub fn find_area_for_map(state: &EditorState, map_id: u8) -> Result<Option<AreaName>> {
+    for area_name in &state.area_names {
+        let area = state.area_copy(&AreaId {
+            area: area_name.clone(),
+            theme: state.main_area_id.theme.clone(),
+        })?;
+        if area.vanilla_map_id == Some(map_id) {
+            return Ok(Some(area_name.clone()));
+        }
+    }
+    Ok(None)
+}
+
+// Name for a new dark world area, following the import's naming of areas.
+pub fn dark_world_area_name(map_id: u8) -> AreaName {
+    format!("{:02X} Dark World", map_id)
+}
+
+// The dark world version of a light world area. The name and background color are taken from
+// the existing dark world area, if any.
+pub fn make_dark_world_area(
+    light_area: &Area,
+    existing: Option<&Area>,
+    name: &AreaName,
+    mapping: &BTreeMap<PaletteId, PaletteId>,
+) -> Result<Area> {
+    let map_id = dark_world_map_id(light_area)?;
+    let mut area = light_area.clone();
+    area.name = name.clone();
+    area.vanilla_map_id = Some(map_id);
+    area.bg_color = existing.map(|a| a.bg_color).unwrap_or(DARK_WORLD_BG_COLOR);
+    area.modified = true;
+    for screen in &mut area.screens {
+        for row in &mut screen.palettes {
+            for id in row {
+                if let Some(&dark_id) = mapping.get(id) {
+                    *id = dark_id;
+                }
+            }
+        }
+    }
+    Ok(area)
+}
+
+// The dark world area to generate from the given light world area, for each theme.
+pub fn dark_world_areas(
+    state: &EditorState,
+    light_area_name: &AreaName,
+    mapping: &BTreeMap<PaletteId, PaletteId>,
+) -> Result<Vec<Area>> {
+    let light_area = state.area_copy(&AreaId {
+        area: light_area_name.clone(),
+        theme: state.main_area_id.theme.clone(),
+    })?;
+    let map_id = dark_world_map_id(&light_area)?;
+    let existing_name = find_area_for_map(state, map_id)?;
+    let name = existing_name
+        .clone()
+        .unwrap_or_else(|| dark_world_area_name(map_id));
+    let mut areas = vec![];
+    for theme in &state.theme_names {
+        let light_area = state
+            .area_copy(&AreaId {
+                area: light_area_name.clone(),
+                theme: theme.clone(),
+            })
+            .with_context(|| format!("Unable to load area {} ({})", light_area_name, theme))?;
+        let existing = match &existing_name {
+            Some(name) => Some(state.area_copy(&AreaId {
+                area: name.clone(),
+                theme: theme.clone(),
+            })?),
+            None => None,
+        };
+        areas.push(make_dark_world_area(
+            &light_area,
+            existing.as_ref(),
+            &name,
+            mapping,
+        )?);
+    }
+    Ok(areas)
+}
@@ -0,0 +1,140 @@
+// Tracking of unsaved and unexported changes, for showing them in the area and palette pickers
+// and in the status bar, and for asking whether to save or discard them when the editor is
+// closed. Changes are unsaved while the area or palette is marked modified, and unexported from
+// when they are saved until the next ROM export of their theme (palettes are shared by all
+// themes, so their changes count as exported by an export of any theme). Unexported changes are
+// recorded in the project metadata, so that they're remembered between sessions.
+use crate::state::{AreaId, AreaName, EditorState, PaletteId, ThemeName};
+
+// Key of an area in the project metadata's unexported areas.
+pub fn area_key(area_id: &AreaId) -> String {
+    format!("{}/{}", area_id.area, area_id.theme)
+}
+
+pub fn area_unsaved(state: &EditorState, area_id: &AreaId) -> bool {
+    state.areas.get(area_id).is_some_and(|a| a.modified)
+}
+
+pub fn area_unexported(state: &EditorState, area_id: &AreaId) -> bool {
+    state
+        .project_metadata
+        .unexported_areas
+        .contains(&area_key(area_id))
+}
+
+// Loaded areas with unsaved changes, in sorted order.
+pub fn unsaved_areas(state: &EditorState) -> Vec<AreaId> {
+    let mut areas: Vec<AreaId> = state
+        .areas
+        .iter()
+        .filter(|(_, a)| a.modified)
+        .map(|(id, _)| id.clone())
+        .collect();
+    areas.sort_by(|a, b| (&a.area, &a.theme).cmp(&(&b.area, &b.theme)));
+    areas
+}
+
+pub fn unsaved_palette_count(state: &EditorState) -> usize {
+    state.palettes.iter().filter(|p| p.modified).count()
+}
+
+// Areas of a theme with saved changes that haven't been exported to a ROM.
+pub fn unexported_areas(state: &EditorState, theme: &ThemeName) -> Vec<AreaName> {
+    state
+        .area_names
+        .iter()
+        .filter(|name| {
+            area_unexported(
+                state,
+                &AreaId {
+                    area: (*name).clone(),
+                    theme: theme.clone(),
+                },
+            )
+        })
+        .cloned()
+        .collect()
+}
+
+pub fn has_unsaved_changes(state: &EditorState) -> bool {
+    state.project_metadata.modified
+        || unsaved_palette_count(state) > 0
+        || !unsaved_areas(state).is_empty()
+}
+
+// Record that an area's changes have been saved, and so are yet to be exported.
+pub fn mark_area_unexported(state: &mut EditorState, area_id: &AreaId) {
+    if state
+        .project_metadata
+        .unexported_areas
+        .insert(area_key(area_id))
+    {
+        state.project_metadata.modified = true;
+    }
+}
+
+pub fn mark_palette_unexported(state: &mut EditorState, palette_id: PaletteId) {
+    if state
+        .project_metadata
+        .unexported_palettes
+        .insert(palette_id)
+    {
+        state.project_metadata.modified = true;
+    }
+}
+
+// Record that the saved changes to a theme's areas (and to the palettes) have been exported.
+pub fn mark_exported(state: &mut EditorState, theme: &ThemeName) {
+    let suffix = format!("/{}", theme);
+    let metadata = &mut state.project_metadata;
+    let count = metadata.unexported_areas.len();
+    metadata
+        .unexported_areas
+        .retain(|key| !key.ends_with(&suffix));
+    if metadata.unexported_areas.len() != count || !metadata.unexported_palettes.is_empty() {
+        metadata.unexported_palettes.clear();
+        metadata.modified = true;
+    }
+}
+
+// Summary of the changes for the status bar, e.g. "2 areas and 1 palette unsaved".
+pub fn status_summary(state: &EditorState) -> String {
+    let plural = |n: usize, what: &str| {
+        if n == 1 {
+            format!("1 {}", what)
+        } else {
+            format!("{} {}s", n, what)
+        }
+    };
+    let theme = &state.main_area().theme;
+    let unsaved_areas = unsaved_areas(state).len();
+    let unsaved_palettes = unsaved_palette_count(state);
+    let mut parts = vec![];
+    if unsaved_areas > 0 || unsaved_palettes > 0 {
+        let mut what = vec![];
+        if unsaved_areas > 0 {
+            what.push(plural(unsaved_areas, "area"));
+        }
+        if unsaved_palettes > 0 {
+            what.push(plural(unsaved_palettes, "palette"));
+        }
+        parts.push(format!("{} unsaved", what.join(" and ")));
+    } else if state.project_metadata.modified {
+        parts.push("Project settings unsaved".to_string());
+    } else {
+        parts.push("All changes saved".to_string());
+    }
+    let unexported = unexported_areas(state, theme).len();
+    if unexported > 0 {
+        parts.push(format!(
+            "{} not exported ({})",
+            plural(unexported, "area"),
+            theme
+        ));
+    }
+    let unexported = state.project_metadata.unexported_palettes.len();
+    if unexported > 0 {
+        parts.push(format!("{} not exported", plural(unexported, "palette")));
+    }
+    parts.join(", ")
+}
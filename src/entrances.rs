@@ -0,0 +1,61 @@
+// Entrances, holes, and exits of overworld areas, shown as markers over the area. Entrances and
+// holes lead into the underworld (to one of the game's entrance IDs), while exits are where the
+// player comes out of a room. Only their positions are edited for now; the rest is kept as
+// imported from the ROM.
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::state::TileCoord;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EntranceKind {
+    Entrance,
+    Hole,
+    Exit,
+}
+
+impl fmt::Display for EntranceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntranceKind::Entrance => write!(f, "Entrance"),
+            EntranceKind::Hole => write!(f, "Hole"),
+            EntranceKind::Exit => write!(f, "Exit"),
+        }
+    }
+}
+
+// Width and height of a marker, in 8x8 tiles (the size of the 16x16 tile that triggers an
+// entrance).
+pub const MARKER_SIZE: TileCoord = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Entrance {
+    pub kind: EntranceKind,
+    // Position of the marker's top-left corner within the area, in 8x8 tiles:
+    pub position: (TileCoord, TileCoord),
+    // The entrance ID that an entrance or hole leads to, or the room ID that an exit leads
+    // out of:
+    pub target: u16,
+}
+
+impl Entrance {
+    pub fn contains(&self, x: TileCoord, y: TileCoord) -> bool {
+        let (x0, y0) = self.position;
+        (x0..x0 + MARKER_SIZE).contains(&x) && (y0..y0 + MARKER_SIZE).contains(&y)
+    }
+
+    // Short label drawn on the marker, e.g. "E12" for entrance $12.
+    pub fn label(&self) -> String {
+        match self.kind {
+            EntranceKind::Entrance => format!("E{:02X}", self.target),
+            EntranceKind::Hole => format!("H{:02X}", self.target),
+            EntranceKind::Exit => format!("X{:02X}", self.target),
+        }
+    }
+}
+
+// The marker at the given position, if any (the last one drawn, if they overlap).
+pub fn entrance_at(entrances: &[Entrance], x: TileCoord, y: TileCoord) -> Option<usize> {
+    entrances.iter().rposition(|e| e.contains(x, y))
+}
@@ -0,0 +1,716 @@
+// Export of a theme's areas and palettes back into a ROM, the reverse of `import`.
+//
+// Each area is written in place of the vanilla map it was imported from, so it must keep its
+// vanilla map ID and size. Graphics sheets aren't written: every tile of an exported area must
+// match graphics loaded for its map in the base ROM, as is the case for tiles imported from the
+// ROM (or borrowed from its graphics sheets). Areas that can't be exported are left as they are
+// in the base ROM, and listed in the report with the reason.
+// Tiles assigned to an animated slot (see `animated_tiles`) are written as references to the
+// slot's character, and other tiles never use the animated characters. The music and palette set
+// of each exported area (see `area_properties`) are written into the tables of its map.
+//
+// Palettes given a position in the theme's palette slot assignment (see `palette_slots`) are
+// written into the palette row that the position selects for the map. Since maps share palette
+// rows, an area is skipped if it needs a row to hold a different palette than an area exported
+// before it.
+//
+// New 16x16 and 32x32 tiles take the place of table entries that are no longer used by any map or
+// overlay, and the compressed map data is repacked into the space taken by the original data.
+use std::{
+    collections::BTreeMap,
+    hash::Hash,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use hashbrown::{HashMap, HashSet};
+use log::{info, warn};
+
+use crate::{
+    animated_tiles::{AnimatedBank, ANIMATED_CHARS},
+    area_properties::{write_properties, AreaProperties},
+    compression::compress_with,
+    helpers::snes_color_word,
+    import::{
+        decompress_at, is_pal_high, map_palette_slot, palette_slots, read_palette,
+        rom_folder::ensure_rom_file, Constants, PaletteSlot, PcAddr, Rom, RomInfo, RomOverworld,
+        RomPaletteIds, SnesAddr, Tile16Idx, Tile32, Tile8, PARENT_MAP_COUNT,
+    },
+    palette_slots::{load_assignment, PaletteAssignment, RowPosition},
+    persist::load_area,
+    state::{Area, AreaId, AreaName, ColorRGB, EditorState, Flip, PaletteId, ThemeName},
+};
+
+// Summary of an export, for reporting in the editor.
+#[derive(Clone, Debug, Default)]
+pub struct ExportReport {
+    pub path: PathBuf,
+    pub exported: Vec<AreaName>,
+    // Areas left as they are in the base ROM, with the reason:
+    pub skipped: Vec<(AreaName, String)>,
+    // Palette rows whose colors were changed:
+    pub palettes_written: usize,
+    pub tiles16_added: usize,
+    pub tiles32_added: usize,
+    // Bytes of compressed map data, and the space available for it:
+    pub map_data_size: usize,
+    pub map_data_space: usize,
+    // Layout differences from the ROM that the project was imported from:
+    pub rom_differences: Vec<String>,
+}
+
+// A 16x16 tile, as four VRAM tilemap words.
+type Tile16Words = [u16; 4];
+
+// A 32x32 tile, as the contents of its four 16x16 tiles.
+type Tile32Words = [Tile16Words; 4];
+
+// The 32x32 tiles of a map (16x16, in row-major order), with the map's index.
+type MapTiles32 = (usize, Vec<Tile32Words>);
+
+// The palette rows used by an area, with the palette that each must hold.
+type PaletteRows = Vec<(PaletteSlot, PaletteId)>;
+
+const TILE32_OFFSETS: [(usize, usize); 4] = [(0, 0), (2, 0), (0, 2), (2, 2)];
+const TILE16_OFFSETS: [(usize, usize); 4] = [(0, 0), (1, 0), (0, 1), (1, 1)];
+
+// Size of a LoROM bank in the ROM file. Compressed data can't cross from one bank to the next.
+const BANK_SIZE: u32 = 0x8000;
+
+// A table of 16x16 or 32x32 tiles in the ROM. New entries are added in place of entries that
+// aren't used by any map, and identical entries are shared.
+struct TileTable<T> {
+    entries: Vec<T>,
+    index_by_entry: HashMap<T, usize>,
+    used: Vec<bool>,
+    next_free: usize,
+    added: usize,
+}
+
+impl<T: Copy + Eq + Hash> TileTable<T> {
+    fn new(entries: Vec<T>) -> Self {
+        let mut index_by_entry = HashMap::new();
+        for (i, &t) in entries.iter().enumerate() {
+            index_by_entry.entry(t).or_insert(i);
+        }
+        TileTable {
+            used: vec![false; entries.len()],
+            entries,
+            index_by_entry,
+            next_free: 0,
+            added: 0,
+        }
+    }
+
+    fn get_or_add(&mut self, t: T) -> Option<usize> {
+        if let Some(&i) = self.index_by_entry.get(&t) {
+            self.used[i] = true;
+            return Some(i);
+        }
+        while *self.used.get(self.next_free)? {
+            self.next_free += 1;
+        }
+        let i = self.next_free;
+        let old = self.entries[i];
+        if self.index_by_entry.get(&old) == Some(&i) {
+            self.index_by_entry.remove(&old);
+        }
+        self.entries[i] = t;
+        self.index_by_entry.insert(t, i);
+        self.used[i] = true;
+        self.added += 1;
+        Some(i)
+    }
+}
+
+// The project palette for each palette row of the ROM, as recorded on import.
+fn rom_palette_ids(state: &EditorState, constants: &Constants, theme: &ThemeName) -> RomPaletteIds {
+    if let Some(names) = state.project_metadata.rom_palettes.get(theme) {
+        return RomPaletteIds::from_names(constants, names);
+    }
+    // For projects imported before the rows were recorded, go by the names that palettes are
+    // given on import:
+    let mut ids = HashMap::new();
+    for (slot, _, _) in palette_slots(constants) {
+        let name = slot.to_string();
+        let themed_name = format!("{} ({})", name, theme);
+        let pal = state
+            .palettes
+            .iter()
+            .find(|p| p.name == themed_name)
+            .or_else(|| state.palettes.iter().find(|p| p.name == name));
+        if let Some(pal) = pal {
+            ids.insert(slot, pal.id);
+        }
+    }
+    RomPaletteIds(ids)
+}
+
+pub struct Exporter<'a> {
+    state: &'a EditorState,
+    theme: ThemeName,
+    constants: Constants,
+    rom: Rom,
+    overworld: RomOverworld,
+    palette_ids: RomPaletteIds,
+    assignment: PaletteAssignment,
+    // Palette rows used by the areas exported so far, with their palette and the area:
+    row_claims: HashMap<PaletteSlot, (PaletteId, AreaName)>,
+    report: ExportReport,
+}
+
+impl<'a> Exporter<'a> {
+    pub fn export(
+        state: &'a EditorState,
+        base_rom_path: &Path,
+        path: &Path,
+        theme: &ThemeName,
+    ) -> Result<ExportReport> {
+        info!(
+            "Exporting theme {} to ROM at {} (based on {})",
+            theme,
+            path.display(),
+            base_rom_path.display()
+        );
+        ensure_rom_file(base_rom_path)?;
+        let data = std::fs::read(base_rom_path)
+            .with_context(|| format!("Unable to read ROM at {}", base_rom_path.display()))?;
+        let rom = Rom::new(data);
+        let constants = Constants::auto(&rom)?;
+        let overworld = RomOverworld::read(&rom, &constants)?;
+        let palette_ids = rom_palette_ids(state, &constants, theme);
+        let assignment = load_assignment(state, theme)?;
+        let mut exporter = Exporter {
+            state,
+            theme: theme.clone(),
+            constants,
+            rom,
+            overworld,
+            palette_ids,
+            assignment,
+            row_claims: HashMap::new(),
+            report: ExportReport {
+                path: path.to_owned(),
+                ..ExportReport::default()
+            },
+        };
+        exporter.check_base_rom();
+        exporter.export_palettes()?;
+        exporter.export_maps()?;
+        std::fs::write(path, &exporter.rom.data)
+            .with_context(|| format!("Unable to write ROM to {}", path.display()))?;
+        info!(
+            "Exported {} areas ({} skipped)",
+            exporter.report.exported.len(),
+            exporter.report.skipped.len()
+        );
+        Ok(exporter.report)
+    }
+
+    fn check_base_rom(&mut self) {
+        let Some(base) = &self.state.project_metadata.base_rom else {
+            return;
+        };
+        let info = RomInfo::new(&self.rom, &self.constants);
+        self.report.rom_differences = base.layout_differences(&info);
+        for d in &self.report.rom_differences {
+            warn!("ROM layout differs from the project's base ROM: {}", d);
+        }
+    }
+
+    fn export_palettes(&mut self) -> Result<()> {
+        for (slot, addr, size) in palette_slots(&self.constants) {
+            if let Some(&id) = self.palette_ids.0.get(&slot) {
+                self.write_palette(slot, addr, size, id)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Write the colors of a palette to a palette row, if they differ from what's there.
+    fn write_palette(
+        &mut self,
+        slot: PaletteSlot,
+        addr: PcAddr,
+        size: usize,
+        id: PaletteId,
+    ) -> Result<()> {
+        let Some(&idx) = self.state.palettes_id_idx_map.get(&id) else {
+            warn!("Palette {} (for {}) not found", id, slot);
+            return Ok(());
+        };
+        let colors = &self.state.palettes[idx].colors;
+        if read_palette(&self.rom, addr, size)?[1..=size] == colors[1..=size] {
+            return Ok(());
+        }
+        for (i, &c) in colors[1..=size].iter().enumerate() {
+            self.rom
+                .write_u16(addr + i as u32 * 2, snes_color_word(c))?;
+        }
+        self.report.palettes_written += 1;
+        Ok(())
+    }
+
+    // Write the palettes placed in rows by the palette slot assignment.
+    fn export_assigned_palettes(&mut self) -> Result<()> {
+        for (slot, addr, size) in palette_slots(&self.constants) {
+            let Some(&(id, _)) = self.row_claims.get(&slot) else {
+                continue;
+            };
+            if self.palette_ids.0.get(&slot) != Some(&id) {
+                self.write_palette(slot, addr, size, id)?;
+            }
+        }
+        Ok(())
+    }
+
+    // The reason an area can't be exported, if it needs a palette row to hold a different palette
+    // than an area exported before it.
+    fn find_row_conflict(&self, rows: &PaletteRows) -> Option<String> {
+        rows.iter()
+            .find_map(|(slot, id)| match self.row_claims.get(slot) {
+                Some((other_id, other_area)) if other_id != id => Some(format!(
+                    "palette row {} is already used for another palette by {}",
+                    slot, other_area
+                )),
+                _ => None,
+            })
+    }
+
+    // Convert an area to the 32x32 tiles of each map that it covers, with the palette rows that
+    // it uses.
+    fn convert_area(&self, area: &Area, parent: usize) -> Result<(Vec<MapTiles32>, PaletteRows)> {
+        let map_parents = &self.overworld.map_parents;
+        let size = if parent % 8 <= 6 && map_parents.get(parent + 1) == Some(&(parent as u16)) {
+            2
+        } else {
+            1
+        };
+        if area.size != (size * 2, size * 2) {
+            bail!(
+                "size is {}x{} screens, but the map in the ROM is {}x{}",
+                area.size.0,
+                area.size.1,
+                size * 2,
+                size * 2
+            );
+        }
+        let gfx_idxs = self.overworld.area_gfx(parent);
+        let pal = &self.overworld.map_palettes[parent];
+
+        // Graphics characters loaded for the map, by their pixels (as displayed with the flip),
+        // whether they use the upper half of the palettes, and their collision type. Animated
+        // characters are left out, since their graphics change; tiles only use them through
+        // their animated slot.
+        let bank = AnimatedBank::for_map(parent as u8);
+        let mut chars = HashMap::new();
+        for flip in [Flip::None, Flip::Horizontal, Flip::Vertical, Flip::Both] {
+            for (c, &tiles8_idx) in gfx_idxs.iter().enumerate() {
+                if ANIMATED_CHARS.contains(&(c as u16)) {
+                    continue;
+                }
+                let pixels = flip.apply_to_pixels(self.overworld.tiles8[tiles8_idx as usize]);
+                let key = (
+                    pixels,
+                    is_pal_high(c as u16 / 64),
+                    self.overworld.tile_types[c],
+                );
+                chars.entry(key).or_insert((c as u16, flip));
+            }
+        }
+
+        // Palette indices (0-7, and whether in the upper half) that select each project palette,
+        // by the assignment where there is one, and otherwise by the palettes in the base ROM:
+        let mut pal_indices: HashMap<PaletteId, Vec<(u8, bool)>> = HashMap::new();
+        let assigned = self.assignment.get(&area.name).cloned().unwrap_or_default();
+        for (position, &id) in &assigned {
+            pal_indices
+                .entry(id)
+                .or_default()
+                .push((position.pal_idx, position.pal_high));
+        }
+        for pal_high in [false, true] {
+            for pal_idx in 0..8 {
+                if assigned.contains_key(&RowPosition { pal_idx, pal_high }) {
+                    continue;
+                }
+                if let Some(id) = self.palette_ids.get(pal, pal_idx, pal_high) {
+                    pal_indices.entry(id).or_default().push((pal_idx, pal_high));
+                }
+            }
+        }
+        let mut rows = vec![];
+        for id in area.get_unique_palettes() {
+            for &(pal_idx, pal_high) in pal_indices.get(&id).into_iter().flatten() {
+                if pal_idx >= 2 {
+                    if let Some(slot) = map_palette_slot(pal, pal_idx, pal_high) {
+                        rows.push((slot, id));
+                    }
+                }
+            }
+        }
+
+        let tile8_word = |x: usize, y: usize| -> Result<u16> {
+            let (x, y) = (x as u16, y as u16);
+            let pal_id = area.get_palette(x, y)?;
+            let tile_idx = area.get_tile(x, y)?;
+            let flip = area.get_flip(x, y)?;
+            let palette = &self.state.palettes[*self
+                .state
+                .palettes_id_idx_map
+                .get(&pal_id)
+                .context("palette not found")?];
+            let tile = palette
+                .tiles
+                .get(tile_idx as usize)
+                .context("tile not found")?
+                .exported();
+            let pixels = flip.apply_to_pixels(tile.pixels);
+            let pal_indices = pal_indices.get(&pal_id).with_context(|| {
+                format!(
+                    "palette {} at ({}, {}) isn't loaded for this map in the ROM",
+                    palette.name, x, y
+                )
+            })?;
+            if let Some(slot) = tile.animated {
+                if slot.bank != bank {
+                    bail!(
+                        "tile {} of palette {} at ({}, {}) is animated from the {} bank, but this map loads the {} bank",
+                        tile_idx, palette.name, x, y, slot.bank, bank
+                    );
+                }
+                let gfx_char = slot.gfx_char();
+                let char_pixels = self.overworld.tiles8[gfx_idxs[gfx_char as usize] as usize];
+                let flip = [Flip::None, Flip::Horizontal, Flip::Vertical, Flip::Both]
+                    .into_iter()
+                    .find(|f| f.apply_to_pixels(char_pixels) == pixels)
+                    .filter(|_| self.overworld.tile_types[gfx_char as usize] == tile.collision);
+                let pal_high = is_pal_high(gfx_char / 64);
+                let pal_idx = pal_indices
+                    .iter()
+                    .find(|&&(_, high)| high == pal_high)
+                    .map(|&(pal_idx, _)| pal_idx);
+                let (Some(flip), Some(pal_idx)) = (flip, pal_idx) else {
+                    bail!(
+                        "tile {} of palette {} at ({}, {}) doesn't match the graphics of animated slot {}",
+                        tile_idx,
+                        palette.name,
+                        x,
+                        y,
+                        slot
+                    );
+                };
+                let t8 = Tile8 {
+                    gfx_char,
+                    pal_idx,
+                    priority: tile.priority,
+                    flip,
+                };
+                return Ok(t8.to_vram_tilemap_word());
+            }
+            for &(pal_idx, pal_high) in pal_indices {
+                if let Some(&(gfx_char, flip)) = chars.get(&(pixels, pal_high, tile.collision)) {
+                    let t8 = Tile8 {
+                        gfx_char,
+                        pal_idx,
+                        priority: tile.priority,
+                        flip,
+                    };
+                    return Ok(t8.to_vram_tilemap_word());
+                }
+            }
+            bail!(
+                "tile {} of palette {} at ({}, {}) doesn't match any graphics loaded for this map",
+                tile_idx,
+                palette.name,
+                x,
+                y
+            );
+        };
+
+        let mut maps = vec![];
+        for my in 0..size as usize {
+            for mx in 0..size as usize {
+                let mut tiles32 = vec![];
+                for ty in 0..16 {
+                    for tx in 0..16 {
+                        let mut t32: Tile32Words = Default::default();
+                        for (i, (x32, y32)) in TILE32_OFFSETS.into_iter().enumerate() {
+                            for (j, (x16, y16)) in TILE16_OFFSETS.into_iter().enumerate() {
+                                let x = mx * 64 + tx * 4 + x32 + x16;
+                                let y = my * 64 + ty * 4 + y32 + y16;
+                                t32[i][j] = tile8_word(x, y)?;
+                            }
+                        }
+                        tiles32.push(t32);
+                    }
+                }
+                maps.push((parent + my * 8 + mx, tiles32));
+            }
+        }
+        Ok((maps, rows))
+    }
+
+    fn export_maps(&mut self) -> Result<()> {
+        let state = self.state;
+        let mut new_maps: BTreeMap<usize, Vec<Tile32Words>> = BTreeMap::new();
+        let mut area_by_parent: HashMap<usize, AreaName> = HashMap::new();
+        let mut bg_colors: Vec<(usize, ColorRGB)> = vec![];
+        let mut properties: Vec<(usize, AreaProperties)> = vec![];
+        for area_name in &state.area_names {
+            let area_id = AreaId {
+                area: area_name.clone(),
+                theme: self.theme.clone(),
+            };
+            // Use the area in memory if loaded, since it may have unsaved changes:
+            let loaded;
+            let area = match state.areas.get(&area_id) {
+                Some(area) => area,
+                None => {
+                    loaded = load_area(state, &area_id)?;
+                    &loaded
+                }
+            };
+            let Some(map_id) = area.vanilla_map_id else {
+                let reason = "no vanilla map to replace".to_string();
+                self.report.skipped.push((area_name.clone(), reason));
+                continue;
+            };
+            let parent = map_id as usize;
+            if parent >= PARENT_MAP_COUNT {
+                let reason = format!("map {:02X} can't be the parent of an area", parent);
+                self.report.skipped.push((area_name.clone(), reason));
+                continue;
+            }
+            if self.overworld.map_parents[parent] as usize != parent {
+                let reason = format!("map {:02X} is not the top-left map of an area", parent);
+                self.report.skipped.push((area_name.clone(), reason));
+                continue;
+            }
+            if let Some(other) = area_by_parent.get(&parent) {
+                let reason = format!("map {:02X} is already used by {}", parent, other);
+                self.report.skipped.push((area_name.clone(), reason));
+                continue;
+            }
+            match self.convert_area(area, parent) {
+                Ok((maps, rows)) => {
+                    if let Some(reason) = self.find_row_conflict(&rows) {
+                        warn!("Skipping area {}: {}", area_name, reason);
+                        self.report.skipped.push((area_name.clone(), reason));
+                        continue;
+                    }
+                    for (slot, id) in rows {
+                        self.row_claims.insert(slot, (id, area_name.clone()));
+                    }
+                    new_maps.extend(maps);
+                    area_by_parent.insert(parent, area_name.clone());
+                    bg_colors.push((parent, area.bg_color));
+                    properties.push((parent, area.properties.clone()));
+                    self.report.exported.push(area_name.clone());
+                }
+                Err(e) => {
+                    warn!("Skipping area {}: {:#}", area_name, e);
+                    self.report
+                        .skipped
+                        .push((area_name.clone(), format!("{:#}", e)));
+                }
+            }
+        }
+
+        self.export_assigned_palettes()?;
+        let new_indices = self.allocate_tiles(&new_maps)?;
+        self.write_map_data(&new_indices)?;
+
+        if let Some(addr) = self.constants.custom_bg_colors_addr {
+            for (parent, color) in bg_colors {
+                let addr = (addr + parent as u32 * 2).into();
+                self.rom.write_u16(addr, snes_color_word(color))?;
+            }
+        }
+        for (parent, properties) in properties {
+            write_properties(&mut self.rom, &self.constants, parent, &properties)?;
+        }
+        Ok(())
+    }
+
+    // Find or add the 16x16 and 32x32 tiles of the new maps, writing the updated tables to the
+    // ROM. Returns the 32x32 tile indices of each new map.
+    fn allocate_tiles(
+        &mut self,
+        new_maps: &BTreeMap<usize, Vec<Tile32Words>>,
+    ) -> Result<BTreeMap<usize, Vec<u16>>> {
+        let overworld = &self.overworld;
+        let mut tiles16 = TileTable::new(
+            overworld
+                .tiles16
+                .iter()
+                .map(|t| t.map(|t8| t8.to_vram_tilemap_word()))
+                .collect(),
+        );
+        let mut tiles32: TileTable<Tile32> = TileTable::new(overworld.tiles32.clone());
+
+        // Entries used by the maps that are kept must stay as they are:
+        for (map_idx, map) in overworld.map_tiles.iter().enumerate() {
+            if new_maps.contains_key(&map_idx) {
+                continue;
+            }
+            for &t32_idx in map.iter().flatten() {
+                tiles32.used[t32_idx as usize] = true;
+                for &t16_idx in &overworld.tiles32[t32_idx as usize] {
+                    tiles16.used[t16_idx as usize] = true;
+                }
+            }
+        }
+        // So must the 16x16 tiles drawn by overlays, along with the 32x32 tiles holding them:
+        let overlay_tiles16: HashSet<Tile16Idx> = overworld
+            .overlays
+            .iter()
+            .map(|&(_, _, t16_idx)| t16_idx)
+            .collect();
+        for &t16_idx in &overlay_tiles16 {
+            tiles16.used[t16_idx as usize] = true;
+        }
+        for (t32_idx, t32) in overworld.tiles32.iter().enumerate() {
+            if t32.iter().any(|t16_idx| overlay_tiles16.contains(t16_idx)) {
+                tiles32.used[t32_idx] = true;
+            }
+        }
+
+        let mut new_indices = BTreeMap::new();
+        for (&map_idx, map) in new_maps {
+            let mut indices = vec![];
+            for t32 in map {
+                let mut t32_entry: Tile32 = [0; 4];
+                for (i, &t16) in t32.iter().enumerate() {
+                    t32_entry[i] = tiles16
+                        .get_or_add(t16)
+                        .context("Not enough room in the ROM for more 16x16 tiles.")?
+                        as u16;
+                }
+                let t32_idx = tiles32
+                    .get_or_add(t32_entry)
+                    .context("Not enough room in the ROM for more 32x32 tiles.")?;
+                indices.push(t32_idx as u16);
+            }
+            new_indices.insert(map_idx, indices);
+        }
+        self.report.tiles16_added = tiles16.added;
+        self.report.tiles32_added = tiles32.added;
+
+        for (i, words) in tiles16.entries.iter().enumerate() {
+            let addr = self.constants.tiles16_addr + i as u32 * 8;
+            for (j, &w) in words.iter().enumerate() {
+                self.rom.write_u16((addr + j as u32 * 2).into(), w)?;
+            }
+        }
+
+        // The 32x32 tiles are stored as a table for each quadrant, in groups of 4 entries: the
+        // low bytes of the 4 entries' 16x16 tile indices, followed by their high nibbles.
+        let quadrant_base_addrs: [PcAddr; 4] = [
+            self.constants.tiles32_tl_addr.into(),
+            self.constants.tiles32_tr_addr.into(),
+            self.constants.tiles32_bl_addr.into(),
+            self.constants.tiles32_br_addr.into(),
+        ];
+        for (group, entries) in tiles32.entries.chunks_exact(4).enumerate() {
+            for (quadrant, base_addr) in quadrant_base_addrs.into_iter().enumerate() {
+                let v = entries.iter().map(|t| t[quadrant]).collect::<Vec<u16>>();
+                let bytes = [
+                    v[0] as u8,
+                    v[1] as u8,
+                    v[2] as u8,
+                    v[3] as u8,
+                    ((v[0] >> 8) << 4 | v[1] >> 8) as u8,
+                    ((v[2] >> 8) << 4 | v[3] >> 8) as u8,
+                ];
+                self.rom.write_n(base_addr + group as u32 * 6, &bytes)?;
+            }
+        }
+        Ok(new_indices)
+    }
+
+    // Compress the new maps and repack all the map data (with the maps that are kept) into the
+    // space taken by the original data.
+    fn write_map_data(&mut self, new_indices: &BTreeMap<usize, Vec<u16>>) -> Result<()> {
+        let map_cnt = self.constants.map_cnt;
+        let mut space: Vec<(u32, u32)> = vec![];
+        // Compressed high and low bytes of each map's 32x32 tile indices:
+        let mut streams: Vec<[Vec<u8>; 2]> = vec![];
+        for i in 0..map_cnt {
+            let mut map_streams: [Vec<u8>; 2] = Default::default();
+            for (k, table_addr) in [self.constants.map_high_addr, self.constants.map_low_addr]
+                .into_iter()
+                .enumerate()
+            {
+                let addr: PcAddr = SnesAddr(self.rom.read_u24((table_addr + i * 3).into())?).into();
+                let (_, end) = decompress_at(&self.rom, addr, true)?;
+                space.push((addr.0, end.0));
+                map_streams[k] = match new_indices.get(&(i as usize)) {
+                    Some(indices) => {
+                        let bytes: Vec<u8> = if k == 0 {
+                            indices.iter().map(|&x| (x >> 8) as u8).collect()
+                        } else {
+                            indices.iter().map(|&x| x as u8).collect()
+                        };
+                        compress_with(&bytes, true)
+                    }
+                    None => self.rom.read_n(addr, (end.0 - addr.0) as usize)?.to_vec(),
+                };
+            }
+            streams.push(map_streams);
+        }
+
+        // Free space, merging adjacent ranges, and split at bank boundaries:
+        space.sort();
+        let mut ranges: Vec<(u32, u32)> = vec![];
+        for (start, end) in space {
+            match ranges.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => ranges.push((start, end)),
+            }
+        }
+        let mut free: Vec<(u32, u32)> = vec![];
+        for (mut start, end) in ranges {
+            while start < end {
+                let bank_end = (start / BANK_SIZE + 1) * BANK_SIZE;
+                free.push((start, end.min(bank_end)));
+                start = bank_end;
+            }
+        }
+        self.report.map_data_space = free.iter().map(|(s, e)| (e - s) as usize).sum();
+
+        // Place the largest streams first, sharing identical ones:
+        let mut unique: Vec<&Vec<u8>> = streams.iter().flatten().collect();
+        unique.sort_by_key(|s| std::cmp::Reverse(s.len()));
+        let mut addr_by_stream: HashMap<&Vec<u8>, PcAddr> = HashMap::new();
+        for stream in unique {
+            if addr_by_stream.contains_key(stream) {
+                continue;
+            }
+            let len = stream.len() as u32;
+            let Some(range) = free.iter_mut().find(|(s, e)| e - s >= len) else {
+                bail!(
+                    "Not enough room in the ROM for the compressed map data ({} bytes available)",
+                    self.report.map_data_space
+                );
+            };
+            let addr = PcAddr(range.0);
+            range.0 += len;
+            self.rom.write_n(addr, stream)?;
+            addr_by_stream.insert(stream, addr);
+            self.report.map_data_size += stream.len();
+        }
+
+        for (i, map_streams) in streams.iter().enumerate() {
+            let i = i as u32;
+            for (stream, table_addr) in map_streams
+                .iter()
+                .zip([self.constants.map_high_addr, self.constants.map_low_addr])
+            {
+                let addr = SnesAddr::from(addr_by_stream[stream]);
+                self.rom.write_u24((table_addr + i * 3).into(), addr.0)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,276 @@
+// Reloading project files that were changed outside the editor (e.g. by git, or by hand), file by
+// file. The watcher records which files changed, and the area and palette JSONs among them that
+// differ from the editor's copy are listed in a dialogue, with a choice for each of reloading it
+// from disk or keeping the editor's version (which is then saved over it), and the differences can
+// be shown for each (by screen for areas, by color and tile for palettes). The other areas and
+// palettes are left as they are, along with any unsaved changes to them. Areas that aren't open
+// need no choice, since they're read from disk when opened.
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::{
+    area_diff::{compare_areas, AreaChanges},
+    persist::{load_area, load_json},
+    state::{AreaId, ColorRGB, EditorState, Palette},
+    update::update_palette_order,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChangedFile {
+    Area(AreaId),
+    // Palette, by name:
+    Palette(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReloadChoice {
+    Reload,
+    KeepMine,
+}
+
+// How a changed file differs from the editor's copy.
+#[derive(Clone, Debug)]
+pub enum ChangeDiff {
+    Area {
+        changes: AreaChanges,
+        size: (u8, u8),
+        disk_size: (u8, u8),
+    },
+    Palette {
+        // Colors of the editor's copy (empty for a palette added outside the editor) and the file:
+        colors: Vec<ColorRGB>,
+        disk_colors: Vec<ColorRGB>,
+        // Indices of the tiles that differ, including any only in one of them:
+        tiles: Vec<usize>,
+        tile_count: usize,
+        disk_tile_count: usize,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct ExternalChange {
+    pub path: PathBuf,
+    pub file: ChangedFile,
+    // Whether the editor's copy has unsaved changes, which reloading would discard:
+    pub unsaved: bool,
+    // How the file differs from the editor's copy, or why it can't be loaded:
+    pub summary: String,
+    pub loadable: bool,
+    pub choice: ReloadChoice,
+    // The differences, for a loadable file, and whether they're shown in the dialogue:
+    pub diff: Option<ChangeDiff>,
+    pub show_diff: bool,
+}
+
+impl ExternalChange {
+    fn new(
+        path: &Path,
+        file: ChangedFile,
+        unsaved: bool,
+        summary: String,
+        diff: Option<ChangeDiff>,
+    ) -> Self {
+        let loadable = diff.is_some();
+        // Changes are kept by default where reloading would lose something:
+        let choice = if unsaved || !loadable {
+            ReloadChoice::KeepMine
+        } else {
+            ReloadChoice::Reload
+        };
+        ExternalChange {
+            path: path.to_owned(),
+            file,
+            unsaved,
+            summary,
+            loadable,
+            choice,
+            diff,
+            show_diff: false,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match &self.file {
+            ChangedFile::Area(area_id) => format!("Area {} ({})", area_id.area, area_id.theme),
+            ChangedFile::Palette(name) => format!("Palette {}", name),
+        }
+    }
+}
+
+// The area or palette that a changed path holds (None for other files, e.g. PNGs, which are
+// generated from the JSONs).
+pub fn changed_file(path: &Path) -> Option<ChangedFile> {
+    if path.extension()? != "json" {
+        return None;
+    }
+    let stem = path.file_stem()?.to_str()?.to_owned();
+    let dir = path.parent()?;
+    let dir_name = dir.file_name()?.to_str()?;
+    if dir_name == "Palettes" {
+        Some(ChangedFile::Palette(stem))
+    } else if dir.parent()?.file_name()? == "Areas" {
+        Some(ChangedFile::Area(AreaId {
+            area: dir_name.to_owned(),
+            theme: stem,
+        }))
+    } else {
+        None
+    }
+}
+
+fn same<T: Serialize>(a: &T, b: &T) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+fn area_change(state: &EditorState, path: &Path, area_id: &AreaId) -> Option<ExternalChange> {
+    let area = state.areas.get(area_id)?;
+    let (summary, diff) = match load_area(state, area_id) {
+        Ok(disk_area) if same(area, &disk_area) => return None,
+        Ok(disk_area) => {
+            let changes = compare_areas(area, &disk_area);
+            let mut summary = if changes.tiles.is_empty() {
+                "Area properties differ".to_string()
+            } else {
+                format!("{} tiles differ", changes.tiles.len())
+            };
+            if changes.size_changed {
+                summary += &format!(" (size {}x{} on disk)", disk_area.size.0, disk_area.size.1);
+            }
+            let diff = ChangeDiff::Area {
+                changes,
+                size: area.size,
+                disk_size: disk_area.size,
+            };
+            (summary, Some(diff))
+        }
+        Err(e) => (format!("Can't be loaded: {:#}", e), None),
+    };
+    Some(ExternalChange::new(
+        path,
+        ChangedFile::Area(area_id.clone()),
+        area.modified,
+        summary,
+        diff,
+    ))
+}
+
+// Indices of the tiles that differ between the editor's copy of a palette (if any) and the file.
+fn changed_tiles(pal: Option<&Palette>, disk_palette: &Palette) -> Vec<usize> {
+    let tile_count = pal.map_or(0, |p| p.tiles.len());
+    (0..tile_count.max(disk_palette.tiles.len()))
+        .filter(|&i| pal.and_then(|p| p.tiles.get(i)) != disk_palette.tiles.get(i))
+        .collect()
+}
+
+fn palette_diff(pal: Option<&Palette>, disk_palette: &Palette) -> ChangeDiff {
+    ChangeDiff::Palette {
+        colors: pal.map(|p| p.colors.to_vec()).unwrap_or_default(),
+        disk_colors: disk_palette.colors.to_vec(),
+        tiles: changed_tiles(pal, disk_palette),
+        tile_count: pal.map_or(0, |p| p.tiles.len()),
+        disk_tile_count: disk_palette.tiles.len(),
+    }
+}
+
+fn palette_change(state: &EditorState, path: &Path, name: &str) -> Option<ExternalChange> {
+    let file = ChangedFile::Palette(name.to_owned());
+    let disk_palette = load_json::<Palette>(path);
+    let Some(pal) = state.palettes.iter().find(|p| p.name == name) else {
+        // A palette added outside the editor:
+        return disk_palette.ok().map(|disk_palette| {
+            let diff = palette_diff(None, &disk_palette);
+            ExternalChange::new(path, file, false, "New palette".to_string(), Some(diff))
+        });
+    };
+    let (summary, diff) = match disk_palette {
+        Ok(disk_palette) if same(pal, &disk_palette) => return None,
+        Ok(disk_palette) => {
+            let colors = pal
+                .colors
+                .iter()
+                .zip(&disk_palette.colors)
+                .filter(|(a, b)| a != b)
+                .count();
+            let tiles = changed_tiles(Some(pal), &disk_palette).len();
+            let summary = if colors == 0 && tiles == 0 {
+                "Palette properties differ".to_string()
+            } else {
+                format!("{} colors and {} tiles differ", colors, tiles)
+            };
+            (summary, Some(palette_diff(Some(pal), &disk_palette)))
+        }
+        Err(e) => (format!("Can't be loaded: {:#}", e), None),
+    };
+    Some(ExternalChange::new(path, file, pal.modified, summary, diff))
+}
+
+// The changes to resolve for the given changed paths, added to the pending ones (replacing any
+// for the same files, which may have changed again).
+pub fn pending_changes(
+    state: &EditorState,
+    pending: &[ExternalChange],
+    paths: impl IntoIterator<Item = PathBuf>,
+) -> Vec<ExternalChange> {
+    let mut changes = pending.to_vec();
+    for path in paths {
+        let Some(file) = changed_file(&path) else {
+            continue;
+        };
+        changes.retain(|c| c.file != file);
+        let change = match &file {
+            ChangedFile::Area(area_id) => area_change(state, &path, area_id),
+            ChangedFile::Palette(name) => palette_change(state, &path, name),
+        };
+        changes.extend(change);
+    }
+    changes
+}
+
+// Reload or keep each changed file as chosen, returning whether anything was reloaded.
+pub fn apply_changes(state: &mut EditorState, changes: &[ExternalChange]) -> Result<bool> {
+    let mut reloaded = false;
+    for change in changes {
+        match (&change.file, change.choice) {
+            (ChangedFile::Area(area_id), ReloadChoice::Reload) => {
+                let area = load_area(state, area_id)?;
+                if let Some(a) = state.areas.get_mut(area_id) {
+                    *a = area;
+                }
+                if state
+                    .area_diff
+                    .as_ref()
+                    .is_some_and(|d| &d.area_id == area_id)
+                {
+                    state.area_diff = None;
+                }
+                reloaded = true;
+            }
+            (ChangedFile::Area(area_id), ReloadChoice::KeepMine) => {
+                // Saved over the changed file:
+                if let Some(a) = state.areas.get_mut(area_id) {
+                    a.modified = true;
+                }
+            }
+            (ChangedFile::Palette(name), ReloadChoice::Reload) => {
+                let mut palette: Palette = load_json(&change.path)?;
+                palette.name = name.clone();
+                match state.palettes.iter().position(|p| &p.name == name) {
+                    Some(idx) => state.palettes[idx] = palette,
+                    None => state.palettes.push(palette),
+                }
+                reloaded = true;
+            }
+            (ChangedFile::Palette(name), ReloadChoice::KeepMine) => {
+                if let Some(p) = state.palettes.iter_mut().find(|p| &p.name == name) {
+                    p.modified = true;
+                }
+            }
+        }
+    }
+    if reloaded {
+        update_palette_order(state);
+    }
+    Ok(reloaded)
+}
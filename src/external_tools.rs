@@ -0,0 +1,116 @@
+// External tools: commands registered in the project (e.g. "Run asar build" or "Launch
+// emulator") that can be run from the editor's Tools panel. The command is split into arguments
+// like a shell would (with quotes grouping words, but without any other shell syntax), and then
+// the placeholders {project}, {area}, and {theme} in each argument are replaced by the project
+// folder and the main area's name and theme. The command runs in a folder relative to the
+// project, on a background thread, with each line of its output sent back to be shown in the
+// panel's console (and logged).
+use std::{
+    io::{BufRead, BufReader, Read},
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use anyhow::{bail, Context, Result};
+use iced::{futures::channel::mpsc, Task};
+use serde::{Deserialize, Serialize};
+
+use crate::{message::Message, state::AreaId};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExternalTool {
+    pub name: String,
+    pub command: String,
+    // Working folder, relative to the project folder (which is used if it's empty):
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub working_dir: String,
+}
+
+// Lines of tool output kept in the console, beyond which the oldest are dropped:
+pub const MAX_CONSOLE_LINES: usize = 1000;
+
+// Split a command into its arguments, at whitespace outside of double or single quotes.
+pub fn split_command(command: &str) -> Result<Vec<String>> {
+    let mut args = vec![];
+    let mut arg: Option<String> = None;
+    let mut quote: Option<char> = None;
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => arg.get_or_insert_with(String::new).push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                arg.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => args.extend(arg.take()),
+            (None, c) => arg.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        bail!("unterminated quote in command: {}", command);
+    }
+    args.extend(arg);
+    Ok(args)
+}
+
+pub fn expand_placeholders(arg: &str, project_dir: &Path, area_id: &AreaId) -> String {
+    arg.replace("{project}", &project_dir.display().to_string())
+        .replace("{area}", &area_id.area)
+        .replace("{theme}", &area_id.theme)
+}
+
+// The program and arguments to run for the tool, with placeholders replaced.
+pub fn tool_args(tool: &ExternalTool, project_dir: &Path, area_id: &AreaId) -> Result<Vec<String>> {
+    let args: Vec<String> = split_command(&tool.command)?
+        .iter()
+        .map(|arg| expand_placeholders(arg, project_dir, area_id))
+        .collect();
+    if args.is_empty() {
+        bail!("tool {} has no command", tool.name);
+    }
+    Ok(args)
+}
+
+// Send each line read from a stream of the tool's output.
+fn forward_lines(stream: impl Read + Send + 'static, sender: mpsc::UnboundedSender<Message>) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            let _ = sender.unbounded_send(Message::ExternalToolOutput(line));
+        }
+    });
+}
+
+// Start running the tool, reporting its output with `Message::ExternalToolOutput` and its exit
+// with `Message::ExternalToolFinished`.
+pub fn run(tool: &ExternalTool, project_dir: &Path, area_id: &AreaId) -> Result<Task<Message>> {
+    let args = tool_args(tool, project_dir, area_id)?;
+    let working_dir = project_dir.join(&tool.working_dir);
+    let mut child = Command::new(&args[0])
+        .args(&args[1..])
+        .current_dir(&working_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Unable to run {} in {}", args[0], working_dir.display()))?;
+
+    let (sender, receiver) = mpsc::unbounded();
+    if let Some(stdout) = child.stdout.take() {
+        forward_lines(stdout, sender.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward_lines(stderr, sender.clone());
+    }
+    let name = tool.name.clone();
+    std::thread::spawn(move || {
+        let result = child
+            .wait()
+            .map(|status| status.code())
+            .map_err(|e| e.to_string());
+        let _ = sender.unbounded_send(Message::ExternalToolFinished { name, result });
+    });
+    Ok(Task::run(receiver, |message| message))
+}
@@ -0,0 +1,159 @@
+// Detection of tiles that could make use of flipping: tiles that look the same when
+// flipped (so flipped placements can be allowed), and tiles that are flipped copies of
+// another tile in the same palette (so their placements can refer to the original).
+use hashbrown::HashMap;
+
+use crate::{
+    message::Message,
+    state::{Flip, Palette, PaletteId, Tile, TileIdx},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FlipSuggestion {
+    // Set the flippable flags for the axes along which the tile is symmetric:
+    Symmetric {
+        palette_id: PaletteId,
+        tile_idx: TileIdx,
+        h: bool,
+        v: bool,
+    },
+    // Replace placements of the tile with the original tile, flipped:
+    Duplicate {
+        palette_id: PaletteId,
+        tile_idx: TileIdx,
+        original: TileIdx,
+        flip: Flip,
+    },
+}
+
+impl FlipSuggestion {
+    pub fn palette_id(&self) -> PaletteId {
+        match *self {
+            FlipSuggestion::Symmetric { palette_id, .. } => palette_id,
+            FlipSuggestion::Duplicate { palette_id, .. } => palette_id,
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match *self {
+            FlipSuggestion::Symmetric { tile_idx, h, v, .. } => {
+                let axes = match (h, v) {
+                    (true, true) => "horizontally and vertically",
+                    (true, false) => "horizontally",
+                    _ => "vertically",
+                };
+                format!("Tile {} is symmetric: allow flipping {}", tile_idx, axes)
+            }
+            FlipSuggestion::Duplicate {
+                tile_idx,
+                original,
+                flip,
+                ..
+            } => format!(
+                "Tile {} is tile {} flipped ({:?}): use tile {} instead",
+                tile_idx, original, flip, original
+            ),
+        }
+    }
+
+    // Messages to apply the suggestion.
+    pub fn messages(&self, palette: &Palette) -> Vec<Message> {
+        let mut out = vec![];
+        let (palette_id, tile_idx, h, v) = match *self {
+            FlipSuggestion::Symmetric {
+                palette_id,
+                tile_idx,
+                h,
+                v,
+            } => (palette_id, tile_idx, h, v),
+            FlipSuggestion::Duplicate {
+                palette_id,
+                tile_idx,
+                original,
+                flip,
+            } => {
+                out.push(Message::MergeFlippedTile {
+                    palette_id,
+                    tile_idx,
+                    original,
+                    flip,
+                });
+                let h = matches!(flip, Flip::Horizontal | Flip::Both);
+                let v = matches!(flip, Flip::Vertical | Flip::Both);
+                (palette_id, original, h, v)
+            }
+        };
+        let tile = &palette.tiles[tile_idx as usize];
+        if h && !tile.h_flippable {
+            out.push(Message::SetTileHFlippable {
+                palette_id,
+                tile_idx,
+                h_flippable: true,
+            });
+        }
+        if v && !tile.v_flippable {
+            out.push(Message::SetTileVFlippable {
+                palette_id,
+                tile_idx,
+                v_flippable: true,
+            });
+        }
+        out
+    }
+}
+
+// The parts of a tile that must match for it to be considered a copy of another.
+fn tile_key(tile: &Tile, flip: Flip) -> (bool, u8, [[u8; 8]; 8]) {
+    (
+        tile.priority,
+        tile.collision,
+        flip.apply_to_pixels(tile.pixels),
+    )
+}
+
+pub fn find_flip_suggestions(palette: &Palette) -> Vec<FlipSuggestion> {
+    let mut out = vec![];
+    let mut originals: HashMap<(bool, u8, [[u8; 8]; 8]), TileIdx> = HashMap::new();
+    for (i, tile) in palette.tiles.iter().enumerate() {
+        let tile_idx = i as TileIdx;
+        // Tiles of a single color (e.g. unused blank tiles) aren't interesting:
+        if tile
+            .pixels
+            .iter()
+            .flatten()
+            .all(|&c| c == tile.pixels[0][0])
+        {
+            continue;
+        }
+        let key = tile_key(tile, Flip::None);
+        if originals.contains_key(&key) {
+            // An exact copy, which isn't something flipping can help with.
+            continue;
+        }
+        let duplicate = [Flip::Horizontal, Flip::Vertical, Flip::Both]
+            .into_iter()
+            .find_map(|flip| originals.get(&tile_key(tile, flip)).map(|&x| (x, flip)));
+        if let Some((original, flip)) = duplicate {
+            out.push(FlipSuggestion::Duplicate {
+                palette_id: palette.id,
+                tile_idx,
+                original,
+                flip,
+            });
+            continue;
+        }
+        originals.insert(key, tile_idx);
+
+        let h = !tile.h_flippable && tile.pixels == Flip::Horizontal.apply_to_pixels(tile.pixels);
+        let v = !tile.v_flippable && tile.pixels == Flip::Vertical.apply_to_pixels(tile.pixels);
+        if h || v {
+            out.push(FlipSuggestion::Symmetric {
+                palette_id: palette.id,
+                tile_idx,
+                h,
+                v,
+            });
+        }
+    }
+    out
+}
@@ -0,0 +1,161 @@
+// Playtest heatmap: visit counts per overworld screen, loaded from logs produced
+// outside the editor (e.g. by emulator Lua scripts), for display over the areas.
+//
+// CSV input has one record per line, either `screen,count` or `screen,x,y,count`,
+// where `screen` is the overworld screen index (the value of $8A, in decimal or
+// as $XX/0xXX hex) and x, y are optional pixel coordinates within that 512x512 screen.
+// Blank lines, lines starting with '#', and a header line are ignored. JSON input is
+// an array of objects with the same fields. Counts for repeated entries are summed.
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use hashbrown::HashMap;
+use serde::Deserialize;
+
+use crate::state::Area;
+
+pub struct Heatmap {
+    pub path: PathBuf,
+    // Counts keyed by overworld screen index and 256x256 quadrant within the screen
+    // (matching the size of the editor's screens).
+    counts: HashMap<(u8, u8, u8), u64>,
+    max_count: u64,
+}
+
+pub struct ScreenHeat {
+    pub position: (u8, u8),
+    pub count: u64,
+    // Count relative to the most-visited screen in the heatmap, from 0 to 1.
+    pub intensity: f32,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ScreenRef {
+    Index(u8),
+    Text(String),
+}
+
+#[derive(Deserialize)]
+struct HeatRecord {
+    screen: ScreenRef,
+    x: Option<u16>,
+    y: Option<u16>,
+    count: u64,
+}
+
+fn parse_screen(s: &str) -> Result<u8> {
+    let s = s.trim();
+    let hex = s
+        .strip_prefix('$')
+        .or_else(|| s.strip_prefix("0x"))
+        .or_else(|| s.strip_prefix("0X"));
+    let screen = match hex {
+        Some(h) => u8::from_str_radix(h, 16),
+        None => s.parse::<u8>(),
+    };
+    screen.context(format!("invalid screen index: {}", s))
+}
+
+fn parse_csv(data: &str) -> Result<Vec<HeatRecord>> {
+    let mut out = vec![];
+    for (i, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(|x| x.trim()).collect();
+        let record = match fields[..] {
+            [screen, count] => (screen, None, None, count),
+            [screen, x, y, count] => (screen, Some(x), Some(y), count),
+            _ => bail!("line {}: expected 2 or 4 fields", i + 1),
+        };
+        if i == 0 && record.3.parse::<u64>().is_err() {
+            // Header line
+            continue;
+        }
+        let parse_coord = |c: Option<&str>| -> Result<Option<u16>> {
+            c.map(|c| c.parse::<u16>())
+                .transpose()
+                .context(format!("line {}: invalid coordinate", i + 1))
+        };
+        out.push(HeatRecord {
+            screen: ScreenRef::Text(record.0.to_string()),
+            x: parse_coord(record.1)?,
+            y: parse_coord(record.2)?,
+            count: record
+                .3
+                .parse()
+                .context(format!("line {}: invalid count", i + 1))?,
+        });
+    }
+    Ok(out)
+}
+
+impl Heatmap {
+    pub fn load(path: &Path) -> Result<Heatmap> {
+        let data =
+            std::fs::read_to_string(path).context(format!("unable to read {}", path.display()))?;
+        let is_json = path
+            .extension()
+            .is_some_and(|x| x.eq_ignore_ascii_case("json"));
+        let records: Vec<HeatRecord> = if is_json {
+            serde_json::from_str(&data)?
+        } else {
+            parse_csv(&data)?
+        };
+
+        let mut counts: HashMap<(u8, u8, u8), u64> = HashMap::new();
+        for r in records {
+            let screen = match r.screen {
+                ScreenRef::Index(i) => i,
+                ScreenRef::Text(s) => parse_screen(&s)?,
+            };
+            match (r.x, r.y) {
+                (Some(x), Some(y)) => {
+                    let quadrant = (((x >> 8) & 1) as u8, ((y >> 8) & 1) as u8);
+                    *counts.entry((screen, quadrant.0, quadrant.1)).or_default() += r.count;
+                }
+                _ => {
+                    // Without a position, the whole screen counts as visited:
+                    for (qx, qy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                        *counts.entry((screen, qx, qy)).or_default() += r.count;
+                    }
+                }
+            }
+        }
+        let max_count = counts.values().copied().max().unwrap_or(0);
+        Ok(Heatmap {
+            path: path.to_owned(),
+            counts,
+            max_count,
+        })
+    }
+
+    // Heat for each screen of the area. Only areas imported from the ROM can be
+    // matched up with the overworld screen indices in the logs.
+    pub fn area_heat(&self, area: &Area) -> Vec<ScreenHeat> {
+        let Some(parent) = area.vanilla_map_id else {
+            return vec![];
+        };
+        let mut out = vec![];
+        for screen in &area.screens {
+            let (sx, sy) = screen.position;
+            let map_screen = parent as u32 + (sx / 2) as u32 + (sy / 2) as u32 * 8;
+            if map_screen > 0xFF {
+                continue;
+            }
+            let key = (map_screen as u8, sx % 2, sy % 2);
+            let count = self.counts.get(&key).copied().unwrap_or(0);
+            if count == 0 {
+                continue;
+            }
+            out.push(ScreenHeat {
+                position: (sx, sy),
+                count,
+                intensity: count as f32 / self.max_count as f32,
+            });
+        }
+        out
+    }
+}
@@ -1,9 +1,68 @@
-use crate::state::ColorRGB;
+use std::time::SystemTime;
+
+use crate::state::{CollisionType, ColorRGB};
 
 pub fn scale_color(c: u8) -> u8 {
     ((c as u16) * 255 / 31) as u8
 }
 
+// A color as the SNES stores it: a BGR555 word, as written in ASM source (e.g. "$7FFF").
+pub fn snes_color_word([r, g, b]: ColorRGB) -> u16 {
+    (b as u16) << 10 | (g as u16) << 5 | r as u16
+}
+
+pub fn format_snes_color(color: ColorRGB) -> String {
+    format!("${:04X}", snes_color_word(color))
+}
+
+// Parse a BGR555 word in hex, with or without a "$" or "0x" prefix.
+pub fn parse_snes_color(text: &str) -> Option<ColorRGB> {
+    let text = text.trim();
+    let digits = text
+        .strip_prefix('$')
+        .or_else(|| text.strip_prefix("0x"))
+        .or_else(|| text.strip_prefix("0X"))
+        .unwrap_or(text);
+    if digits.is_empty() || digits.len() > 4 {
+        return None;
+    }
+    let word = u16::from_str_radix(digits, 16).ok()?;
+    if word > 0x7FFF {
+        return None;
+    }
+    Some([
+        (word & 31) as u8,
+        ((word >> 5) & 31) as u8,
+        ((word >> 10) & 31) as u8,
+    ])
+}
+
+// A color in 24-bit hex, as in "#F8F8F8".
+pub fn format_rgb_hex([r, g, b]: ColorRGB) -> String {
+    format!(
+        "#{:02X}{:02X}{:02X}",
+        scale_color(r),
+        scale_color(g),
+        scale_color(b)
+    )
+}
+
+// Parse a 24-bit color in hex, with or without a "#" prefix, snapped to the nearest 5-bit
+// components.
+pub fn parse_rgb_hex(text: &str) -> Option<ColorRGB> {
+    let text = text.trim();
+    let digits = text.strip_prefix('#').unwrap_or(text);
+    if digits.len() != 6 || !digits.is_ascii() {
+        return None;
+    }
+    let mut color = [0; 3];
+    for (i, c) in color.iter_mut().enumerate() {
+        let byte = u8::from_str_radix(&digits[i * 2..i * 2 + 2], 16).ok()?;
+        *c = ((byte as u16 * 31 + 127) / 255) as u8;
+    }
+    Some(color)
+}
+
 pub fn alpha_blend(bg: ColorRGB, fg: ColorRGB, alpha: f32) -> ColorRGB {
     let gamma = 2.2;
     let mut out: ColorRGB = [0, 0, 0];
@@ -15,3 +74,56 @@ pub fn alpha_blend(bg: ColorRGB, fg: ColorRGB, alpha: f32) -> ColorRGB {
     }
     out
 }
+
+// Color (8-bit RGB) for showing a collision type, spreading the types around the color wheel
+// so that nearby values are easy to tell apart.
+pub fn collision_color(collision: CollisionType) -> ColorRGB {
+    let hue = (collision as f32 * 0.618034).fract() * 6.0;
+    let x = 1.0 - (hue % 2.0 - 1.0).abs();
+    let (r, g, b) = match hue as u8 {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    [(r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8]
+}
+
+// Stable 64-bit FNV-1a hash, used for content-addressed file names (unlike
+// Rust's DefaultHasher, it is guaranteed not to change between releases).
+pub fn content_hash(data: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for &b in data {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+pub fn format_age(time: SystemTime) -> String {
+    let secs = SystemTime::now()
+        .duration_since(time)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    if secs < 60 {
+        format!("{} seconds ago", secs)
+    } else if secs < 3600 {
+        format!("{} minutes ago", secs / 60)
+    } else if secs < 86400 {
+        format!("{} hours ago", secs / 3600)
+    } else {
+        format!("{} days ago", secs / 86400)
+    }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{} bytes", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
@@ -1,19 +1,35 @@
-use anyhow::{bail, ensure, Result};
+pub mod rom_folder;
+
+use anyhow::{bail, ensure, Context, Result};
 use hashbrown::{hash_map::Entry, HashMap};
 use itertools::Itertools;
 use log::{info, warn};
+use serde::{Deserialize, Serialize};
 use std::{
+    collections::{BTreeMap, BTreeSet},
     fmt::Display,
     ops::{Add, AddAssign},
-    path::Path,
+    path::{Path, PathBuf},
+    sync::Arc,
 };
 
 use crate::{
-    persist::{load_area, load_project, save_area_json, save_area_png, save_project},
+    animated_tiles::{AnimatedBank, AnimatedSlot, ANIMATED_CHARS, ANIMATED_SLOT_COUNT},
+    area_properties::read_properties,
+    camera_locks::is_camera_locked,
+    compression::decompress_prefix,
+    entrances::{Entrance, EntranceKind},
+    helpers::{content_hash, scale_color},
+    import_rules::ImportRules,
+    persist::{
+        area_json_path, load_area, load_project, save_area_json, save_area_png, save_project,
+    },
+    secrets::Secret,
     state::{
-        Area, AreaId, AreaName, ColorRGB, ColorValue, EditorState, Flip, Palette, PaletteId,
-        Screen, Tile, TileIdx,
+        Area, AreaId, AreaName, ColorRGB, ColorValue, EditorState, Flip, OverlayTile, Palette,
+        PaletteCategory, PaletteId, ProjectMetadata, Screen, Tile, TileCoord, TileIdx,
     },
+    travel::{TravelKind, TravelPoint, FLUTE_SPOT_CNT, WHIRLPOOL_CNT},
     update::update_palette_order,
 };
 
@@ -21,10 +37,10 @@ use crate::{
 // with "PC" addresses (byte index into the ROM file). So we use type-safe wrappers
 // to make these harder to mess up:
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
-struct PcAddr(u32);
+pub struct PcAddr(pub u32);
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
-struct SnesAddr(u32);
+pub struct SnesAddr(pub u32);
 
 macro_rules! impl_add {
     ($target_type:ident, $other_type:ident) => {
@@ -88,42 +104,137 @@ impl From<SnesAddr> for PcAddr {
     }
 }
 
+impl From<PcAddr> for SnesAddr {
+    fn from(addr: PcAddr) -> Self {
+        SnesAddr(addr.0 << 1 & 0x7F0000 | 0x8000 | addr.0 & 0x7FFF)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RomVariant {
+    JP,
+    US,
+    ZScream,
+}
+
+impl Display for RomVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RomVariant::JP => write!(f, "JP"),
+            RomVariant::US => write!(f, "US"),
+            RomVariant::ZScream => write!(f, "ZScream"),
+        }
+    }
+}
+
+// The ROM that a project was imported from, to detect re-importing or exporting against a
+// base ROM with a different layout.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RomInfo {
+    pub variant: RomVariant,
+    pub hash: u64,
+    // SNES address (or entry count) of each table, keyed by name:
+    pub tables: BTreeMap<String, u32>,
+}
+
+impl RomInfo {
+    pub fn new(rom: &Rom, constants: &Constants) -> Self {
+        RomInfo {
+            variant: constants.variant,
+            hash: content_hash(&rom.data),
+            tables: constants.tables(),
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let data = std::fs::read(path)
+            .with_context(|| format!("Unable to read ROM at {}", path.display()))?;
+        let rom = Rom::new(data);
+        let constants = Constants::auto(&rom)?;
+        Ok(Self::new(&rom, &constants))
+    }
+
+    // Description of each difference in layout from the other ROM, e.g. tables that moved.
+    pub fn layout_differences(&self, other: &RomInfo) -> Vec<String> {
+        let mut out = vec![];
+        if self.variant != other.variant {
+            out.push(format!("ROM format: {} -> {}", self.variant, other.variant));
+        }
+        let names: BTreeSet<&String> = self.tables.keys().chain(other.tables.keys()).collect();
+        let fmt = |name: &str, value: Option<&u32>| match value {
+            None => "none".to_string(),
+            Some(x) if name.ends_with("_cnt") => x.to_string(),
+            Some(x) => format!("${:06X}", x),
+        };
+        for name in names {
+            let (a, b) = (self.tables.get(name), other.tables.get(name));
+            if a != b {
+                out.push(format!("{}: {} -> {}", name, fmt(name, a), fmt(name, b)));
+            }
+        }
+        out
+    }
+}
+
 // Addresses of where certain data is located in the ROM. Many of these vary
 // between JP and US versions.
-struct Constants {
-    hud_palettes_addr: SnesAddr,
-    main_palettes_addr: SnesAddr,
-    aux_palettes_addr: SnesAddr,
-    animated_palettes_addr: SnesAddr,
-    gfx_bank_addr: SnesAddr,
-    gfx_high_addr: SnesAddr,
-    gfx_low_addr: SnesAddr,
-    tiles16_addr: SnesAddr,
-    tiles16_cnt: u32,
-    tiles32_tl_addr: SnesAddr,
-    tiles32_tr_addr: SnesAddr,
-    tiles32_bl_addr: SnesAddr,
-    tiles32_br_addr: SnesAddr,
-    tiles32_cnt: u32,
-    map_high_addr: SnesAddr,
-    map_low_addr: SnesAddr,
-    map_cnt: u32,
-    custom_map_main_pal_set_addr: Option<SnesAddr>,
-    map_aux_pal_set_addr: SnesAddr,
-    special_map_pal_set_addr: SnesAddr,
-    pal_set_addr: SnesAddr,
-    global_gfx_set_addr: SnesAddr,
-    local_gfx_set_addr: SnesAddr,
-    map_gfx_set_addr: SnesAddr,
-    custom_gfx_set_addr: Option<SnesAddr>,
-    special_gfx_set_addr: SnesAddr,
-    tile_types: SnesAddr,
-    custom_bg_colors_addr: Option<SnesAddr>,
+pub struct Constants {
+    pub variant: RomVariant,
+    pub hud_palettes_addr: SnesAddr,
+    pub main_palettes_addr: SnesAddr,
+    pub aux_palettes_addr: SnesAddr,
+    pub animated_palettes_addr: SnesAddr,
+    pub gfx_bank_addr: SnesAddr,
+    pub gfx_high_addr: SnesAddr,
+    pub gfx_low_addr: SnesAddr,
+    pub tiles16_addr: SnesAddr,
+    pub tiles16_cnt: u32,
+    pub tiles32_tl_addr: SnesAddr,
+    pub tiles32_tr_addr: SnesAddr,
+    pub tiles32_bl_addr: SnesAddr,
+    pub tiles32_br_addr: SnesAddr,
+    pub tiles32_cnt: u32,
+    pub map_high_addr: SnesAddr,
+    pub map_low_addr: SnesAddr,
+    pub map_cnt: u32,
+    pub custom_map_main_pal_set_addr: Option<SnesAddr>,
+    pub map_aux_pal_set_addr: SnesAddr,
+    pub special_map_pal_set_addr: SnesAddr,
+    pub pal_set_addr: SnesAddr,
+    pub global_gfx_set_addr: SnesAddr,
+    pub local_gfx_set_addr: SnesAddr,
+    pub map_gfx_set_addr: SnesAddr,
+    pub custom_gfx_set_addr: Option<SnesAddr>,
+    pub special_gfx_set_addr: SnesAddr,
+    pub tile_types: SnesAddr,
+    pub custom_bg_colors_addr: Option<SnesAddr>,
+    // Tables of overworld entrances (map, position, and entrance ID of each) and holes
+    // (position, map, and entrance ID), each table following the previous one:
+    pub entrances_addr: SnesAddr,
+    pub entrance_cnt: u32,
+    pub holes_addr: SnesAddr,
+    pub hole_cnt: u32,
+    // Tables of overworld exits (room, map, and then camera and player positions), if known:
+    pub exits_addr: Option<SnesAddr>,
+    pub exit_cnt: u32,
+    // Table of pointers (within its bank) to each map's list of secrets (position and item,
+    // ending with $FFFF):
+    pub secrets_addr: SnesAddr,
+    pub secrets_map_cnt: u32,
+    // Table of pointers (within its bank) to the routine that draws each map's overlay, if known:
+    pub overlays_addr: Option<SnesAddr>,
+    // Tables of each map's music (track and ambient sound) for the four stages of the Light
+    // World, followed by the Dark World's table, if known:
+    pub music_addr: Option<SnesAddr>,
+    // Tables of the flute spots' and whirlpools' destinations (map, and then camera and player
+    // positions, in the same layout as the exits), if known:
+    pub travel_addr: Option<SnesAddr>,
 }
 
 impl Constants {
     fn jp() -> Self {
         Constants {
+            variant: RomVariant::JP,
             hud_palettes_addr: SnesAddr(0x1BD660),
             main_palettes_addr: SnesAddr(0x1BE6C8),
             aux_palettes_addr: SnesAddr(0x1BE86C),
@@ -152,11 +263,23 @@ impl Constants {
             special_gfx_set_addr: SnesAddr(0x02E585), // appears incorrect in ZS?
             tile_types: SnesAddr(0x0FFD94),
             custom_bg_colors_addr: None,
+            entrances_addr: SnesAddr(0x1BB96F),
+            entrance_cnt: 0x81,
+            holes_addr: SnesAddr(0x1BB800),
+            hole_cnt: 0x13,
+            exits_addr: None,
+            exit_cnt: 0x4F,
+            secrets_addr: SnesAddr(0x1BC2F9),
+            secrets_map_cnt: 0x80,
+            overlays_addr: None,
+            music_addr: None,
+            travel_addr: None,
         }
     }
 
     fn us() -> Self {
         Constants {
+            variant: RomVariant::US,
             hud_palettes_addr: SnesAddr(0x1BD660),
             main_palettes_addr: SnesAddr(0x1BE6C8),
             aux_palettes_addr: SnesAddr(0x1BE86C),
@@ -185,13 +308,73 @@ impl Constants {
             special_gfx_set_addr: SnesAddr(0x02E821),
             tile_types: SnesAddr(0x0E9459),
             custom_bg_colors_addr: None,
+            entrances_addr: SnesAddr(0x1BB96F),
+            entrance_cnt: 0x81,
+            holes_addr: SnesAddr(0x1BB800),
+            hole_cnt: 0x13,
+            exits_addr: Some(SnesAddr(0x02DD8A)),
+            exit_cnt: 0x4F,
+            secrets_addr: SnesAddr(0x1BC2F9),
+            secrets_map_cnt: 0x80,
+            overlays_addr: Some(SnesAddr(0x0EF664)),
+            music_addr: Some(SnesAddr(0x02C303)),
+            travel_addr: Some(SnesAddr(0x02EAE5)),
         }
     }
 
-    fn auto(rom: &Rom) -> Result<Self> {
+    // (The entrance tables aren't included, so that projects imported before they were read
+    // don't see them as moved.)
+    fn tables(&self) -> BTreeMap<String, u32> {
+        let addrs = [
+            ("hud_palettes_addr", Some(self.hud_palettes_addr)),
+            ("main_palettes_addr", Some(self.main_palettes_addr)),
+            ("aux_palettes_addr", Some(self.aux_palettes_addr)),
+            ("animated_palettes_addr", Some(self.animated_palettes_addr)),
+            ("gfx_bank_addr", Some(self.gfx_bank_addr)),
+            ("gfx_high_addr", Some(self.gfx_high_addr)),
+            ("gfx_low_addr", Some(self.gfx_low_addr)),
+            ("tiles16_addr", Some(self.tiles16_addr)),
+            ("tiles32_tl_addr", Some(self.tiles32_tl_addr)),
+            ("tiles32_tr_addr", Some(self.tiles32_tr_addr)),
+            ("tiles32_bl_addr", Some(self.tiles32_bl_addr)),
+            ("tiles32_br_addr", Some(self.tiles32_br_addr)),
+            ("map_high_addr", Some(self.map_high_addr)),
+            ("map_low_addr", Some(self.map_low_addr)),
+            (
+                "custom_map_main_pal_set_addr",
+                self.custom_map_main_pal_set_addr,
+            ),
+            ("map_aux_pal_set_addr", Some(self.map_aux_pal_set_addr)),
+            (
+                "special_map_pal_set_addr",
+                Some(self.special_map_pal_set_addr),
+            ),
+            ("pal_set_addr", Some(self.pal_set_addr)),
+            ("global_gfx_set_addr", Some(self.global_gfx_set_addr)),
+            ("local_gfx_set_addr", Some(self.local_gfx_set_addr)),
+            ("map_gfx_set_addr", Some(self.map_gfx_set_addr)),
+            ("custom_gfx_set_addr", self.custom_gfx_set_addr),
+            ("special_gfx_set_addr", Some(self.special_gfx_set_addr)),
+            ("tile_types", Some(self.tile_types)),
+            ("custom_bg_colors_addr", self.custom_bg_colors_addr),
+        ];
+        let counts = [
+            ("tiles16_cnt", self.tiles16_cnt),
+            ("tiles32_cnt", self.tiles32_cnt),
+            ("map_cnt", self.map_cnt),
+        ];
+        addrs
+            .into_iter()
+            .filter_map(|(name, addr)| Some((name.to_string(), addr?.0)))
+            .chain(counts.into_iter().map(|(name, x)| (name.to_string(), x)))
+            .collect()
+    }
+
+    pub fn auto(rom: &Rom) -> Result<Self> {
         if rom.read_u24(SnesAddr(0x008865).into())? == 0xBD8000 {
             info!("ZScream ROM format detected.");
             let mut constants = Constants::us();
+            constants.variant = RomVariant::ZScream;
             constants.tiles16_addr = SnesAddr(0xBD8000);
             constants.tiles16_cnt = 4096;
             constants.tiles32_tr_addr = SnesAddr(0x048000);
@@ -226,16 +409,16 @@ impl Constants {
 }
 
 #[derive(Clone)]
-struct Rom {
+pub struct Rom {
     pub data: Vec<u8>,
 }
 
 #[derive(Copy, Clone, Debug)]
-struct Tile8 {
-    gfx_char: u16, // Index into area-loaded graphics tiles (0-1023)
-    pal_idx: u8,   // Index into area-loaded palettes (0-7)
-    priority: bool,
-    flip: Flip,
+pub struct Tile8 {
+    pub gfx_char: u16, // Index into area-loaded graphics tiles (0-1023)
+    pub pal_idx: u8,   // Index into area-loaded palettes (0-7)
+    pub priority: bool,
+    pub flip: Flip,
 }
 
 impl Tile8 {
@@ -253,285 +436,584 @@ impl Tile8 {
             },
         }
     }
+
+    pub fn to_vram_tilemap_word(self) -> u16 {
+        self.gfx_char & 0x3FF
+            | (self.pal_idx as u16 & 7) << 10
+            | (self.priority as u16) << 13
+            | (self.flip as u16) << 14
+    }
 }
 
-type Tile16 = [Tile8; 4];
+pub type Tile16 = [Tile8; 4];
 
 // Index into Importer::tiles16
-type Tile16Idx = u16;
+pub type Tile16Idx = u16;
 
-type Tile32 = [Tile16Idx; 4];
+pub type Tile32 = [Tile16Idx; 4];
 
 // Index into Importer::tiles32
-type Tile32Idx = u16;
+pub type Tile32Idx = u16;
 
-type MapIdx = u16;
+pub type MapIdx = u16;
 
 #[derive(Debug)]
-struct MapPalettes {
-    main: u8,
-    aux1: u8,
-    aux2: u8,
-    animated: u8,
+pub struct MapPalettes {
+    pub main: u8,
+    pub aux1: u8,
+    pub aux2: u8,
+    pub animated: u8,
 }
 
-pub struct Importer<'a> {
-    state: &'a mut EditorState,
-    constants: Constants,
-    rom: Rom,
-    theme: String,
-    area_name_by_map_id: HashMap<u8, AreaName>,
-    hud_palette_ids: Vec<[PaletteId; 2]>,
-    main_palette_ids: Vec<[PaletteId; 5]>,
-    aux_palette_ids: Vec<[PaletteId; 3]>,
-    animated_palette_ids: Vec<PaletteId>,
-    tiles8: Vec<[[u8; 8]; 8]>, // 3bpp tile color indices (0-7)
-    tiles16: Vec<Tile16>,
-    tiles32: Vec<Tile32>,
-    map_tiles: Vec<[[Tile32Idx; 16]; 16]>,
-    map_parents: Vec<MapIdx>,
-    map_palettes: Vec<MapPalettes>,
-    map_gfx: Vec<[u8; 8]>,
-    tile_types: Vec<u8>,
-    pal_bg_color: HashMap<PaletteId, ColorRGB>,
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ImportMode {
+    // Import everything, overwriting existing areas of the current theme:
+    #[default]
+    Full,
+    // Refresh only the map data (e.g. after rebasing onto an updated base ROM),
+    // keeping any areas that have been edited in the project since the last import:
+    MapsOnly,
+    // Update the colors of the palettes previously imported from the ROM's palette rows,
+    // leaving their tiles and the areas as they are:
+    PalettesOnly,
+    // Add the tiles used by the ROM's maps that the palettes don't have yet, leaving the areas
+    // as they are:
+    GraphicsOnly,
 }
 
-impl Rom {
-    pub fn new(data: Vec<u8>) -> Self {
-        Rom { data }
+impl ImportMode {
+    pub const ALL: [ImportMode; 4] = [
+        ImportMode::Full,
+        ImportMode::MapsOnly,
+        ImportMode::PalettesOnly,
+        ImportMode::GraphicsOnly,
+    ];
+
+    fn imports_areas(self) -> bool {
+        matches!(self, ImportMode::Full | ImportMode::MapsOnly)
     }
+}
 
-    pub fn read_u8(&self, addr: PcAddr) -> Result<u8> {
-        ensure!(
-            (addr.0 as usize) < self.data.len(),
-            "read_u8 address out of bounds"
-        );
-        Ok(self.data[addr.0 as usize] as u8)
+impl Display for ImportMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ImportMode::Full => "Everything",
+            ImportMode::MapsOnly => "Maps only",
+            ImportMode::PalettesOnly => "Palettes only",
+            ImportMode::GraphicsOnly => "Graphics only",
+        };
+        write!(f, "{}", s)
     }
+}
 
-    pub fn read_u16(&self, addr: PcAddr) -> Result<u16> {
-        ensure!(
-            addr.0 as usize + 1 < self.data.len(),
-            "read_u16 address out of bounds"
-        );
-        let b0 = self.data[addr.0 as usize] as u16;
-        let b1 = self.data[addr.0 as usize + 1] as u16;
-        Ok(b0 | b1 << 8)
+// Parse a list of map IDs to import, in hex, separated by commas or spaces, with ranges written
+// like "00-07". An empty list means all maps.
+pub fn parse_map_ids(s: &str) -> Result<Option<BTreeSet<u8>>> {
+    let mut ids = BTreeSet::new();
+    for part in s.split([',', ' ']).filter(|p| !p.is_empty()) {
+        let parse = |x: &str| {
+            u8::from_str_radix(x.trim(), 16).with_context(|| format!("invalid map ID: {}", x))
+        };
+        match part.split_once('-') {
+            Some((start, end)) => ids.extend(parse(start)?..=parse(end)?),
+            None => {
+                ids.insert(parse(part)?);
+            }
+        }
     }
+    Ok((!ids.is_empty()).then_some(ids))
+}
 
-    pub fn read_u24(&self, addr: PcAddr) -> Result<u32> {
-        ensure!(
-            addr.0 as usize + 2 < self.data.len(),
-            "read_u24 address out of bounds"
-        );
-        let b0 = self.data[addr.0 as usize] as u32;
-        let b1 = self.data[addr.0 as usize + 1] as u32;
-        let b2 = self.data[addr.0 as usize + 2] as u32;
-        Ok(b0 | b1 << 8 | b2 << 16)
+// Summary of what happened to each area during an import.
+#[derive(Clone, Debug, Default)]
+pub struct ImportReport {
+    pub added: Vec<AreaName>,
+    pub updated: Vec<AreaName>,
+    pub unchanged: Vec<AreaName>,
+    // Edited in the project but not in the ROM:
+    pub kept: Vec<AreaName>,
+    // Edited in both the project and the ROM (project version is kept):
+    pub conflicts: Vec<AreaName>,
+    // Left out by the project's import rules:
+    pub dropped: Vec<AreaName>,
+    // Palettes whose colors were changed:
+    pub palettes_updated: Vec<String>,
+    // Number of tiles added to each palette:
+    pub tiles_added: Vec<(String, usize)>,
+    // Layout differences from the ROM that the project was previously imported from:
+    pub rom_differences: Vec<String>,
+}
+
+// A graphics sheet as stored in the ROM: 64 3bpp tiles (color indices 0-7).
+pub type GraphicsSheet = Vec<[[u8; 8]; 8]>;
+
+// Number of tiles per row when displaying a graphics sheet.
+pub const GRAPHICS_SHEET_COLS: usize = 16;
+
+// State of the dialogue for borrowing graphics sheets from another ROM,
+// without otherwise importing anything from it.
+pub struct BorrowGraphics {
+    pub path: PathBuf,
+    pub sheets: Vec<GraphicsSheet>,
+    pub selected: BTreeSet<usize>,
+    // Palette that the tiles will be added to, also used for the previews:
+    pub palette_id: PaletteId,
+    pub previews: Vec<iced::widget::image::Handle>,
+}
+
+impl BorrowGraphics {
+    // Render each sheet using the colors of the target palette.
+    pub fn update_previews(&mut self, colors: &[ColorRGB; 16]) {
+        let rows = 64 / GRAPHICS_SHEET_COLS;
+        let width = GRAPHICS_SHEET_COLS * 8;
+        self.previews = self
+            .sheets
+            .iter()
+            .map(|sheet| {
+                let mut data: Vec<u8> = vec![0; rows * 8 * width * 4];
+                for (i, tile) in sheet.iter().enumerate() {
+                    let x0 = (i % GRAPHICS_SHEET_COLS) * 8;
+                    let y0 = (i / GRAPHICS_SHEET_COLS) * 8;
+                    for y in 0..8 {
+                        for x in 0..8 {
+                            let [r, g, b] = colors[tile[y][x] as usize];
+                            let addr = ((y0 + y) * width + x0 + x) * 4;
+                            data[addr..(addr + 4)].copy_from_slice(&[
+                                scale_color(r),
+                                scale_color(g),
+                                scale_color(b),
+                                255,
+                            ]);
+                        }
+                    }
+                }
+                iced::widget::image::Handle::from_rgba(width as u32, (rows * 8) as u32, data)
+            })
+            .collect();
     }
 
-    pub fn read_n(&self, addr: PcAddr, n: usize) -> Result<&[u8]> {
-        ensure!(
-            addr.0 as usize + n <= self.data.len(),
-            "read_n address out of bounds"
-        );
-        Ok(&self.data[addr.0 as usize..(addr.0 as usize + n)])
+    // Tiles for the selected sheets, in order.
+    pub fn selected_tiles(&self) -> Vec<Tile> {
+        self.selected
+            .iter()
+            .flat_map(|&i| &self.sheets[i])
+            .map(|&pixels| Tile {
+                pixels,
+                ..Tile::default()
+            })
+            .collect()
     }
 }
 
-impl<'a> Importer<'a> {
-    pub fn import(state: &'a mut EditorState, path: &Path) -> Result<()> {
-        info!("Importing from ROM at {}", path.display());
-        let mut importer = Self::new(state, path)?;
-        importer.import_all()?;
-        Ok(())
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PaletteGroup {
+    HUD,
+    Main,
+    Aux,
+    Animated,
+}
+
+impl From<PaletteGroup> for PaletteCategory {
+    fn from(group: PaletteGroup) -> Self {
+        match group {
+            PaletteGroup::HUD => PaletteCategory::HUD,
+            PaletteGroup::Main => PaletteCategory::Main,
+            PaletteGroup::Aux => PaletteCategory::Aux,
+            PaletteGroup::Animated => PaletteCategory::Animated,
+        }
     }
+}
 
-    fn new(state: &'a mut EditorState, path: &Path) -> Result<Self> {
-        let rom_bytes = std::fs::read(path)?;
-        let rom = Rom::new(rom_bytes);
-        let theme = state.main_area().theme.clone();
-        Ok(Self {
-            state,
-            constants: Constants::auto(&rom)?,
-            rom,
-            theme,
-            area_name_by_map_id: HashMap::new(),
-            hud_palette_ids: vec![],
-            main_palette_ids: vec![],
-            aux_palette_ids: vec![],
-            animated_palette_ids: vec![],
-            tiles8: vec![],
-            tiles16: vec![],
-            tiles32: vec![],
-            map_tiles: vec![],
-            map_parents: vec![],
-            map_palettes: vec![],
-            map_gfx: vec![],
-            tile_types: vec![],
-            pal_bg_color: HashMap::new(),
-        })
+// A row of colors in one of the ROM's palette tables: the palette set, and the row within it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PaletteSlot {
+    pub group: PaletteGroup,
+    pub set: u8,
+    pub row: u8,
+}
+
+impl Display for PaletteSlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} {:x}-{}", self.group, self.set, self.row)
     }
+}
 
-    fn import_all(&mut self) -> Result<()> {
-        let starting_palette_id = self.state.palettes.iter().map(|x| x.id).max().unwrap() + 1;
-        self.load_area_names()?;
-        self.load_tile_types()?;
-        self.import_all_palettes(starting_palette_id)?;
-        self.load_graphics()?;
-        self.load_16x16_tiles()?;
-        self.load_32x32_tiles()?;
-        self.load_map_tiles()?;
-        self.load_map_parents()?;
-        self.load_map_palettes()?;
-        self.load_map_gfx()?;
-        self.load_areas()?;
-        self.ensure_palette_full_rows()?;
-        self.assign_bg_colors()?;
-        save_project(self.state)?;
-        load_project(self.state)?;
-        for area_name in &self.state.area_names.clone() {
-            let area_id = AreaId {
-                theme: self.theme.clone(),
-                area: area_name.clone(),
-            };
-            self.state.load_area(&area_id)?;
-            save_area_png(self.state, &area_id)?;
-            self.state.areas.remove(&area_id);
+// Position of a map's top-left corner within its world, in pixels.
+fn map_origin(map: usize) -> (u32, u32) {
+    ((map % 8) as u32 * 512, (map % 64 / 8) as u32 * 512)
+}
+
+// Position of a 16x16 tile within its map, in pixels, from its VRAM tilemap offset (as used
+// for entrances, holes, and secrets).
+fn tile16_pixels(pos: u16) -> (u32, u32) {
+    (((pos >> 1) % 64) as u32 * 16, (pos >> 7) as u32 * 16)
+}
+
+// Each palette row in the ROM, with its address and number of colors.
+pub fn palette_slots(constants: &Constants) -> Vec<(PaletteSlot, PcAddr, usize)> {
+    let palette_groups = [
+        (PaletteGroup::HUD, constants.hud_palettes_addr, 1, 2, 15),
+        (PaletteGroup::Main, constants.main_palettes_addr, 6, 5, 7),
+        (PaletteGroup::Aux, constants.aux_palettes_addr, 20, 3, 7),
+        (
+            PaletteGroup::Animated,
+            constants.animated_palettes_addr,
+            14,
+            1,
+            7,
+        ),
+    ];
+    let mut out = vec![];
+    for (group, base_addr, cnt_pal, cnt_rows, size) in palette_groups {
+        let base_addr: PcAddr = base_addr.into();
+        for i in 0..cnt_pal {
+            for j in 0..cnt_rows {
+                let addr = base_addr + ((i * cnt_rows + j) * size) * 2;
+                let slot = PaletteSlot {
+                    group,
+                    set: i as u8,
+                    row: j as u8,
+                };
+                out.push((slot, addr, size as usize));
+            }
         }
-        load_project(self.state)?; // Load again to open the first room.
-        Ok(())
     }
+    out
+}
+
+// Read a palette row, as colors 1 to `size` of a palette.
+pub fn read_palette(rom: &Rom, addr: PcAddr, size: usize) -> Result<[ColorRGB; 16]> {
+    let mut colors = [[0, 0, 0]; 16];
+    for i in 0..size {
+        let c = rom.read_u16(addr + i as u32 * 2)?;
+        let r = c & 31;
+        let g = (c >> 5) & 31;
+        let b = (c >> 10) & 31;
+        colors[i + 1] = [r as ColorValue, g as ColorValue, b as ColorValue];
+    }
+    Ok(colors)
+}
 
-    fn load_area_names(&mut self) -> Result<()> {
-        for area_name in &self.state.area_names {
-            let area_id = AreaId {
-                area: area_name.clone(),
-                theme: self.theme.clone(),
-            };
-            let area = load_area(self.state, &area_id)?;
-            if let Some(id) = area.vanilla_map_id {
-                self.area_name_by_map_id.insert(id, area_name.clone());
+// Graphics sheets whose tiles use the upper half of the palettes (the aux palettes).
+pub fn is_pal_high(gfx_sheet: u16) -> bool {
+    [0, 3, 4, 5].contains(&gfx_sheet)
+}
+
+// The project palette that each palette row of the ROM was imported as.
+// The palette row loaded for a tile's palette index (0-7), given the area's palettes and whether
+// its graphics sheet uses the upper half of the palettes.
+pub fn map_palette_slot(pal: &MapPalettes, pal_idx: u8, pal_high: bool) -> Option<PaletteSlot> {
+    let (group, set, row) = match (pal_idx, pal_high) {
+        (p @ (0 | 1), _) => (PaletteGroup::HUD, 0, p),
+        (p @ 2..=6, false) => (PaletteGroup::Main, pal.main, p - 2),
+        (7, false) => (PaletteGroup::Animated, pal.animated, 0),
+        (p @ 2..=4, true) => (PaletteGroup::Aux, pal.aux1, p - 2),
+        (p @ 5..=7, true) => (PaletteGroup::Aux, pal.aux2, p - 5),
+        _ => return None,
+    };
+    Some(PaletteSlot { group, set, row })
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RomPaletteIds(pub HashMap<PaletteSlot, PaletteId>);
+
+impl RomPaletteIds {
+    // The palette of an 8x8 tile, given the area's palettes, the tile's palette index (0-7)
+    // and whether its graphics sheet uses the upper half of the palettes.
+    pub fn get(&self, pal: &MapPalettes, pal_idx: u8, pal_high: bool) -> Option<PaletteId> {
+        self.0
+            .get(&map_palette_slot(pal, pal_idx, pal_high)?)
+            .copied()
+    }
+
+    pub fn to_names(&self) -> BTreeMap<String, PaletteId> {
+        self.0
+            .iter()
+            .map(|(slot, &id)| (slot.to_string(), id))
+            .collect()
+    }
+
+    pub fn from_names(constants: &Constants, names: &BTreeMap<String, PaletteId>) -> Self {
+        let mut ids = HashMap::new();
+        for (slot, _, _) in palette_slots(constants) {
+            if let Some(&id) = names.get(&slot.to_string()) {
+                ids.insert(slot, id);
             }
         }
-        Ok(())
+        RomPaletteIds(ids)
     }
+}
 
-    fn load_palette(&mut self, addr: PcAddr, size: usize) -> Result<[ColorRGB; 16]> {
-        let mut colors = [[0, 0, 0]; 16];
-        for i in 0..size {
-            let c = self.rom.read_u16(addr + i as u32 * 2)?;
-            let r = c & 31;
-            let g = (c >> 5) & 31;
-            let b = (c >> 10) & 31;
-            colors[i + 1] = [r as ColorValue, g as ColorValue, b as ColorValue];
-        }
-        Ok(colors)
+// The overworld data of a ROM, in the game's own formats.
+#[derive(Default)]
+pub struct RomOverworld {
+    pub tiles8: Vec<[[u8; 8]; 8]>, // 3bpp tile color indices (0-7)
+    pub tiles16: Vec<Tile16>,
+    pub tiles32: Vec<Tile32>,
+    pub map_tiles: Vec<[[Tile32Idx; 16]; 16]>,
+    pub map_parents: Vec<MapIdx>,
+    pub map_palettes: Vec<MapPalettes>,
+    pub map_gfx: Vec<[u8; 8]>,
+    pub tile_types: Vec<u8>,
+    // Entrances, holes, and exits, by the parent map of the area that they're in:
+    pub entrances: Vec<(MapIdx, Entrance)>,
+    // Secrets, by the parent map of the area that they're in:
+    pub secrets: Vec<(MapIdx, Secret)>,
+    // Flute spots and whirlpool destinations, by the parent map of the area that they're in:
+    pub travel: Vec<(MapIdx, TravelPoint)>,
+    // Overlay tiles, by the parent map of the area that they're in, with their positions in the
+    // area in 16x16 tiles:
+    pub overlays: Vec<(MapIdx, (TileCoord, TileCoord), Tile16Idx)>,
+}
+
+impl RomOverworld {
+    pub fn read(rom: &Rom, constants: &Constants) -> Result<Self> {
+        let mut overworld = RomOverworld::default();
+        overworld.load_tile_types(rom, constants)?;
+        overworld.load_graphics(rom, constants)?;
+        overworld.load_16x16_tiles(rom, constants)?;
+        overworld.load_32x32_tiles(rom, constants)?;
+        overworld.load_map_tiles(rom, constants)?;
+        overworld.load_map_parents(constants)?;
+        overworld.load_map_palettes(rom, constants)?;
+        overworld.load_map_gfx(rom, constants)?;
+        overworld.load_entrances(rom, constants)?;
+        overworld.load_secrets(rom, constants)?;
+        overworld.load_travel(rom, constants)?;
+        overworld.load_overlays(rom, constants)?;
+        Ok(overworld)
     }
 
-    fn import_all_palettes(&mut self, mut next_id: PaletteId) -> Result<()> {
-        let palette_groups = [
-            ("HUD", self.constants.hud_palettes_addr, 1, 2, 15),
-            ("Main", self.constants.main_palettes_addr, 6, 5, 7),
-            ("Aux", self.constants.aux_palettes_addr, 20, 3, 7),
-            ("Animated", self.constants.animated_palettes_addr, 14, 1, 7),
-        ];
+    // The entrances in the area of a parent map, of the given size (in maps).
+    pub fn area_entrances(&self, parent: usize, size: (u8, u8)) -> Vec<Entrance> {
+        let limit = (size.0 as TileCoord * 64, size.1 as TileCoord * 64);
+        self.entrances
+            .iter()
+            .filter(|&&(p, e)| {
+                p as usize == parent && e.position.0 < limit.0 && e.position.1 < limit.1
+            })
+            .map(|&(_, e)| e)
+            .collect()
+    }
 
-        let mut pal_by_colors: HashMap<[ColorRGB; 16], PaletteId> = HashMap::new();
-        for pal in &self.state.palettes {
-            let mut colors = pal.colors;
-            colors[0] = [0, 0, 0];
-            let _ = pal_by_colors.try_insert(colors, pal.id);
+    fn load_entrances(&mut self, rom: &Rom, constants: &Constants) -> Result<()> {
+        // Holes' positions are offset by 0x400:
+        let (base, n) = (constants.entrances_addr, constants.entrance_cnt);
+        for i in 0..n {
+            let map = rom.read_u16((base + i * 2).into())?;
+            let pos = rom.read_u16((base + n * 2 + i * 2).into())?;
+            let id = rom.read_u8((base + n * 4 + i).into())?;
+            if pos != 0xFFFF {
+                self.add_entrance(EntranceKind::Entrance, map, tile16_pixels(pos), id as u16);
+            }
+        }
+        let (base, n) = (constants.holes_addr, constants.hole_cnt);
+        for i in 0..n {
+            let pos = rom.read_u16((base + i * 2).into())?;
+            let map = rom.read_u16((base + n * 2 + i * 2).into())?;
+            let id = rom.read_u8((base + n * 4 + i).into())?;
+            let pos = pos.wrapping_add(0x400);
+            self.add_entrance(EntranceKind::Hole, map, tile16_pixels(pos), id as u16);
+        }
+        if let Some(base) = constants.exits_addr {
+            // Exit positions are of the player, in pixels within the world:
+            let n = constants.exit_cnt;
+            for i in 0..n {
+                let room = rom.read_u16((base + i * 2).into())?;
+                let map = rom.read_u8((base + n * 2 + i).into())?;
+                let y = rom.read_u16((base + n * 9 + i * 2).into())?;
+                let x = rom.read_u16((base + n * 11 + i * 2).into())?;
+                let origin = map_origin(map as usize);
+                let offset = (
+                    (x as u32).wrapping_sub(origin.0) % 4096,
+                    (y as u32).wrapping_sub(origin.1) % 4096,
+                );
+                self.add_entrance(EntranceKind::Exit, map as u16, offset, room);
+            }
         }
+        Ok(())
+    }
 
-        for (group_name, base_addr, cnt_pal, cnt_rows, size) in palette_groups {
-            let base_addr: PcAddr = base_addr.into();
-            for i in 0..cnt_pal {
-                let mut palette_ids: Vec<PaletteId> = vec![];
-                for j in 0..cnt_rows {
-                    let addr = base_addr + ((i * cnt_rows + j) * size) * 2;
-                    let colors = self.load_palette(addr, size as usize)?;
+    // The parent map of the area containing a position (a pixel offset from the top-left of
+    // a map), and the position within the area in 8x8 tiles.
+    fn area_position(
+        &self,
+        map: u16,
+        offset: (u32, u32),
+    ) -> Option<(MapIdx, (TileCoord, TileCoord))> {
+        let &parent = self.map_parents.get(map as usize)?;
+        let (map_x, map_y) = map_origin(map as usize);
+        let (parent_x, parent_y) = map_origin(parent as usize);
+        let x = map_x + offset.0 - parent_x;
+        let y = map_y + offset.1 - parent_y;
+        Some((parent, ((x / 8) as TileCoord, (y / 8) as TileCoord)))
+    }
 
-                    if let Some(&id) = pal_by_colors.get(&colors) {
-                        palette_ids.push(id);
-                    } else {
-                        let mut name = format!("{} {:x}-{}", group_name, i, j);
-                        if self.theme != "Base" {
-                            name += &format!(" ({})", self.theme);
-                        }
-                        self.state.palettes.push(Palette {
-                            modified: true,
-                            name,
-                            id: next_id,
-                            colors,
-                            tiles: vec![],
-                        });
-                        pal_by_colors.insert(colors, next_id);
-                        palette_ids.push(next_id);
-                        next_id += 1;
-                    }
-                }
-                match group_name {
-                    "HUD" => {
-                        self.hud_palette_ids.push(palette_ids.try_into().unwrap());
-                    }
-                    "Main" => {
-                        self.main_palette_ids.push(palette_ids.try_into().unwrap());
-                    }
-                    "Aux" => {
-                        self.aux_palette_ids.push(palette_ids.try_into().unwrap());
-                    }
-                    "Animated" => {
-                        self.animated_palette_ids.push(palette_ids[0]);
-                    }
-                    _ => panic!("unexpected group_name"),
+    // Add an entrance at the given pixel offset from the top-left of its map.
+    fn add_entrance(&mut self, kind: EntranceKind, map: u16, offset: (u32, u32), target: u16) {
+        let Some((parent, position)) = self.area_position(map, offset) else {
+            warn!("Skipping {} on unknown map {:02X}", kind, map);
+            return;
+        };
+        self.entrances.push((
+            parent,
+            Entrance {
+                kind,
+                position,
+                target,
+            },
+        ));
+    }
+
+    // The secrets in the area of a parent map, of the given size (in maps).
+    pub fn area_secrets(&self, parent: usize, size: (u8, u8)) -> Vec<Secret> {
+        let limit = (size.0 as TileCoord * 64, size.1 as TileCoord * 64);
+        self.secrets
+            .iter()
+            .filter(|&&(p, s)| {
+                p as usize == parent && s.position.0 < limit.0 && s.position.1 < limit.1
+            })
+            .map(|&(_, s)| s)
+            .collect()
+    }
+
+    // The flute spots and whirlpool destinations in the area of a parent map, of the given size
+    // (in maps).
+    pub fn area_travel(&self, parent: usize, size: (u8, u8)) -> Vec<TravelPoint> {
+        let limit = (size.0 as TileCoord * 64, size.1 as TileCoord * 64);
+        self.travel
+            .iter()
+            .filter(|&&(p, t)| {
+                p as usize == parent && t.position.0 < limit.0 && t.position.1 < limit.1
+            })
+            .map(|&(_, t)| t)
+            .collect()
+    }
+
+    fn load_travel(&mut self, rom: &Rom, constants: &Constants) -> Result<()> {
+        let Some(base) = constants.travel_addr else {
+            return Ok(());
+        };
+        // Positions are of the player, in pixels within the world (like the exits'):
+        let n = (FLUTE_SPOT_CNT + WHIRLPOOL_CNT) as u32;
+        for i in 0..n {
+            let map = rom.read_u16((base + i * 2).into())?;
+            let y = rom.read_u16((base + n * 8 + i * 2).into())?;
+            let x = rom.read_u16((base + n * 10 + i * 2).into())?;
+            let origin = map_origin(map as usize);
+            let offset = (
+                (x as u32).wrapping_sub(origin.0) % 4096,
+                (y as u32).wrapping_sub(origin.1) % 4096,
+            );
+            let (kind, slot) = if i < FLUTE_SPOT_CNT as u32 {
+                (TravelKind::Flute, i as u8)
+            } else {
+                (TravelKind::Whirlpool, (i - FLUTE_SPOT_CNT as u32) as u8)
+            };
+            let Some((parent, position)) = self.area_position(map, offset) else {
+                warn!("Skipping {} {} on unknown map {:02X}", kind, slot, map);
+                continue;
+            };
+            self.travel.push((
+                parent,
+                TravelPoint {
+                    kind,
+                    slot,
+                    position,
+                },
+            ));
+        }
+        Ok(())
+    }
+
+    fn load_secrets(&mut self, rom: &Rom, constants: &Constants) -> Result<()> {
+        let base = constants.secrets_addr;
+        let bank = base.0 & 0xFF0000;
+        for map in 0..constants.secrets_map_cnt {
+            let ptr = rom.read_u16((base + map * 2).into())?;
+            let mut addr = SnesAddr(bank | ptr as u32);
+            // (A bound on the list length, in case of a missing terminator.)
+            for _ in 0..0x100 {
+                let pos = rom.read_u16(addr.into())?;
+                if pos == 0xFFFF {
+                    break;
                 }
+                let item = rom.read_u8((addr + 2).into())?;
+                addr += 3;
+                let Some((parent, position)) =
+                    self.area_position(map as u16, tile16_pixels(pos & 0x1FFF))
+                else {
+                    warn!("Skipping secret on unknown map {:02X}", map);
+                    continue;
+                };
+                self.secrets.push((parent, Secret { position, item }));
             }
         }
-        update_palette_order(self.state);
         Ok(())
     }
 
-    fn load_graphics(&mut self) -> Result<()> {
-        let rom = &self.rom;
-        let gfx_bank = rom.read_u16(self.constants.gfx_bank_addr.into())?;
-        let gfx_high = rom.read_u16(self.constants.gfx_high_addr.into())?;
-        let gfx_low = rom.read_u16(self.constants.gfx_low_addr.into())?;
-
-        for i in 0..113 {
-            let bank = rom.read_u8(SnesAddr::from_bank_offset(0x00, gfx_bank + i).into())?;
-            let high = rom.read_u8(SnesAddr::from_bank_offset(0x00, gfx_high + i).into())?;
-            let low = rom.read_u8(SnesAddr::from_bank_offset(0x00, gfx_low + i).into())?;
-            let addr = SnesAddr::from_bytes(bank, high, low);
-            let data = decompress(rom, addr.into(), false)?;
-            if data.len() != 0x600 {
-                bail!("Unexpected graphics sheet length: {}", data.len());
-            }
+    // The overlay tiles in the area of a parent map, of the given size (in maps).
+    pub fn area_overlay(
+        &self,
+        parent: usize,
+        size: (u8, u8),
+    ) -> Vec<((TileCoord, TileCoord), Tile16Idx)> {
+        let limit = (size.0 as TileCoord * 32, size.1 as TileCoord * 32);
+        self.overlays
+            .iter()
+            .filter(|&&(p, (x, y), _)| p as usize == parent && x < limit.0 && y < limit.1)
+            .map(|&(_, position, tile16)| (position, tile16))
+            .collect()
+    }
 
-            for j in 0..64 {
-                let mut tile: [[u8; 8]; 8] = [[0; 8]; 8];
-                for y in 0..8 {
-                    for x in 0..8 {
-                        let c0 = (data[j * 24 + y * 2] >> (7 - x)) & 1;
-                        let c1 = (data[j * 24 + y * 2 + 1] >> (7 - x)) & 1;
-                        let c2 = (data[j * 24 + y + 16] >> (7 - x)) & 1;
-                        let c = c0 | (c1 << 1) | (c2 << 2);
-                        tile[y][x] = c;
-                    }
+    fn load_overlays(&mut self, rom: &Rom, constants: &Constants) -> Result<()> {
+        let Some(base) = constants.overlays_addr else {
+            return Ok(());
+        };
+        let bank = base.0 & 0xFF0000;
+        // Only the overlays of parent maps are drawn (covering the whole area):
+        for map in 0..0x80 {
+            if self.map_parents.get(map as usize) != Some(&map) {
+                continue;
+            }
+            let ptr = rom.read_u16((base + map as u32 * 2).into())?;
+            for (position, tile16) in read_overlay(rom, SnesAddr(bank | ptr as u32))? {
+                if (tile16 as usize) < self.tiles16.len() {
+                    self.overlays.push((map, position, tile16));
                 }
-                self.tiles8.push(tile);
             }
         }
         Ok(())
     }
 
-    fn load_16x16_tiles(&mut self) -> Result<()> {
-        for i in 0..self.constants.tiles16_cnt {
-            let addr = self.constants.tiles16_addr + i * 8;
-            let tl = self.rom.read_u16(addr.into())?;
-            let tr = self.rom.read_u16((addr + 2).into())?;
-            let bl = self.rom.read_u16((addr + 4).into())?;
-            let br = self.rom.read_u16((addr + 6).into())?;
+    // Indices into `tiles8` of the graphics loaded for the area (512 tiles, from 8 sheets).
+    pub fn area_gfx(&self, parent: usize) -> Vec<u16> {
+        let mut gfx_idxs: Vec<u16> = vec![];
+        for idx in self.map_gfx[parent] {
+            gfx_idxs.extend((idx as u16 * 64)..((idx + 1) as u16 * 64));
+        }
+        let animated_gfx = AnimatedBank::for_map(parent as u8).sheet();
+        let animated_range = ANIMATED_CHARS.start as usize..ANIMATED_CHARS.end as usize;
+        gfx_idxs[animated_range].copy_from_slice(
+            &((animated_gfx * 64)..(animated_gfx * 64 + ANIMATED_SLOT_COUNT as u16)).collect_vec(),
+        );
+        gfx_idxs
+    }
+
+    fn load_graphics(&mut self, rom: &Rom, constants: &Constants) -> Result<()> {
+        for sheet in read_graphics_sheets(rom, constants)? {
+            self.tiles8.extend(sheet);
+        }
+        Ok(())
+    }
+
+    fn load_16x16_tiles(&mut self, rom: &Rom, constants: &Constants) -> Result<()> {
+        for i in 0..constants.tiles16_cnt {
+            let addr = constants.tiles16_addr + i * 8;
+            let tl = rom.read_u16(addr.into())?;
+            let tr = rom.read_u16((addr + 2).into())?;
+            let bl = rom.read_u16((addr + 4).into())?;
+            let br = rom.read_u16((addr + 6).into())?;
             self.tiles16.push([
                 Tile8::from_vram_tilemap_word(tl),
                 Tile8::from_vram_tilemap_word(tr),
@@ -542,16 +1024,15 @@ impl<'a> Importer<'a> {
         Ok(())
     }
 
-    fn load_32x32_tiles(&mut self) -> Result<()> {
-        let rom = &self.rom;
+    fn load_32x32_tiles(&mut self, rom: &Rom, constants: &Constants) -> Result<()> {
         let quadrant_base_addrs: [PcAddr; 4] = [
-            self.constants.tiles32_tl_addr.into(),
-            self.constants.tiles32_tr_addr.into(),
-            self.constants.tiles32_bl_addr.into(),
-            self.constants.tiles32_br_addr.into(),
+            constants.tiles32_tl_addr.into(),
+            constants.tiles32_tr_addr.into(),
+            constants.tiles32_bl_addr.into(),
+            constants.tiles32_br_addr.into(),
         ];
         let mut offset = 0;
-        let tiles32_size = self.constants.tiles32_cnt * 6 / 4;
+        let tiles32_size = constants.tiles32_cnt * 6 / 4;
         while offset < tiles32_size {
             for i in 0..4 {
                 let mut quadrant_idxs = vec![];
@@ -590,13 +1071,12 @@ impl<'a> Importer<'a> {
         Ok(())
     }
 
-    fn load_map_tiles(&mut self) -> Result<()> {
-        let rom = &self.rom;
-        for i in 0..self.constants.map_cnt {
-            let high_addr = SnesAddr(rom.read_u24((self.constants.map_high_addr + i * 3).into())?);
+    fn load_map_tiles(&mut self, rom: &Rom, constants: &Constants) -> Result<()> {
+        for i in 0..constants.map_cnt {
+            let high_addr = SnesAddr(rom.read_u24((constants.map_high_addr + i * 3).into())?);
             let high_data = decompress(rom, high_addr.into(), true)?;
 
-            let low_addr = SnesAddr(rom.read_u24((self.constants.map_low_addr + i * 3).into())?);
+            let low_addr = SnesAddr(rom.read_u24((constants.map_low_addr + i * 3).into())?);
             let low_data = decompress(rom, low_addr.into(), true)?;
 
             ensure!(high_data.len() == 256);
@@ -607,11 +1087,11 @@ impl<'a> Importer<'a> {
                 for x in 0..16 {
                     let j = y * 16 + x;
                     let mut tile32_idx = (high_data[j] as u16) << 8 | low_data[j] as u16;
-                    if (tile32_idx as u32) >= self.constants.tiles32_cnt {
+                    if (tile32_idx as u32) >= constants.tiles32_cnt {
                         // This happens in the US ROM (TODO: look into why).
                         info!(
                             "World block ${:X} (x={}, y={}): tile32 index {} out of bounds ({})",
-                            i, x, y, tile32_idx, self.constants.tiles32_cnt
+                            i, x, y, tile32_idx, constants.tiles32_cnt
                         );
                         tile32_idx = 0;
                     }
@@ -624,8 +1104,8 @@ impl<'a> Importer<'a> {
         Ok(())
     }
 
-    fn load_map_parents(&mut self) -> Result<()> {
-        let mut parents: Vec<MapIdx> = (0..self.constants.map_cnt as MapIdx).collect();
+    fn load_map_parents(&mut self, constants: &Constants) -> Result<()> {
+        let mut parents: Vec<MapIdx> = (0..constants.map_cnt as MapIdx).collect();
 
         // Large areas:
         for i in [0, 3, 5, 24, 27, 30, 48, 53] {
@@ -652,11 +1132,10 @@ impl<'a> Importer<'a> {
         Ok(())
     }
 
-    fn load_map_palettes(&mut self) -> Result<()> {
-        let rom = &self.rom;
-        for i in 0..self.constants.map_cnt as usize {
+    fn load_map_palettes(&mut self, rom: &Rom, constants: &Constants) -> Result<()> {
+        for i in 0..constants.map_cnt as usize {
             let parent = self.map_parents[i];
-            let main = if let Some(main_pal_addr) = self.constants.custom_map_main_pal_set_addr {
+            let main = if let Some(main_pal_addr) = constants.custom_map_main_pal_set_addr {
                 rom.read_u8((main_pal_addr + parent as u32).into())?
             } else {
                 match i {
@@ -672,18 +1151,16 @@ impl<'a> Importer<'a> {
             let pal_set = if i == 0x88 {
                 0
             } else if parent >= 0x80 {
-                rom.read_u8(
-                    (self.constants.special_map_pal_set_addr + (parent as u32 - 0x80)).into(),
-                )?
+                rom.read_u8((constants.special_map_pal_set_addr + (parent as u32 - 0x80)).into())?
             } else {
-                rom.read_u8((self.constants.map_aux_pal_set_addr + parent as u32).into())?
+                rom.read_u8((constants.map_aux_pal_set_addr + parent as u32).into())?
             };
             let prev_pal_set = if parent >= 1 {
-                rom.read_u8((self.constants.map_aux_pal_set_addr + (parent - 1) as u32).into())?
+                rom.read_u8((constants.map_aux_pal_set_addr + (parent - 1) as u32).into())?
             } else {
                 0
             };
-            let pal_set_addr = self.constants.pal_set_addr + pal_set as u32 * 4;
+            let pal_set_addr = constants.pal_set_addr + pal_set as u32 * 4;
             let mut aux1 = rom.read_u8(pal_set_addr.into())?;
             let mut aux2 = rom.read_u8((pal_set_addr + 1).into())?;
             let mut animated = rom.read_u8((pal_set_addr + 2).into())?;
@@ -695,12 +1172,12 @@ impl<'a> Importer<'a> {
                 info!(
                     "{:x}: {} {} {}",
                     parent,
-                    self.constants.pal_set_addr,
+                    constants.pal_set_addr,
                     prev_pal_set,
-                    self.constants.pal_set_addr + prev_pal_set as u32 * 4 + 1
+                    constants.pal_set_addr + prev_pal_set as u32 * 4 + 1
                 );
-                aux2 = rom
-                    .read_u8((self.constants.pal_set_addr + prev_pal_set as u32 * 4 + 1).into())?;
+                aux2 =
+                    rom.read_u8((constants.pal_set_addr + prev_pal_set as u32 * 4 + 1).into())?;
             }
             if animated >= 14 {
                 warn!("{:02X}: out-of-range animated: {}", i, animated);
@@ -717,13 +1194,12 @@ impl<'a> Importer<'a> {
         Ok(())
     }
 
-    fn load_map_gfx(&mut self) -> Result<()> {
-        let rom = &self.rom;
-        let global_gfx_set_addr = self.constants.global_gfx_set_addr;
-        let local_gfx_set_addr = self.constants.local_gfx_set_addr;
-        let map_gfx_set_addr = self.constants.map_gfx_set_addr;
-        let special_gfx_set_addr = self.constants.special_gfx_set_addr;
-        for i in 0..self.constants.map_cnt as usize {
+    fn load_map_gfx(&mut self, rom: &Rom, constants: &Constants) -> Result<()> {
+        let global_gfx_set_addr = constants.global_gfx_set_addr;
+        let local_gfx_set_addr = constants.local_gfx_set_addr;
+        let map_gfx_set_addr = constants.map_gfx_set_addr;
+        let special_gfx_set_addr = constants.special_gfx_set_addr;
+        for i in 0..constants.map_cnt as usize {
             let parent = self.map_parents[i];
             let global_idx = match parent {
                 0x40..0x80 => 0x21, // Dark World
@@ -733,7 +1209,7 @@ impl<'a> Importer<'a> {
             let mut gfx: Vec<u8> = rom
                 .read_n((global_gfx_set_addr + global_idx * 8).into(), 8)?
                 .to_owned();
-            if let Some(custom_gfx_set_addr) = self.constants.custom_gfx_set_addr {
+            if let Some(custom_gfx_set_addr) = constants.custom_gfx_set_addr {
                 let local_gfx = rom
                     .read_n((custom_gfx_set_addr + parent as u32 * 8).into(), 8)?
                     .to_owned();
@@ -767,68 +1243,509 @@ impl<'a> Importer<'a> {
         Ok(())
     }
 
-    fn load_tile_types(&mut self) -> Result<()> {
-        self.tile_types = self
-            .rom
-            .read_n(self.constants.tile_types.into(), 512)?
-            .to_owned();
+    fn load_tile_types(&mut self, rom: &Rom, constants: &Constants) -> Result<()> {
+        self.tile_types = rom.read_n(constants.tile_types.into(), 512)?.to_owned();
         Ok(())
     }
+}
 
-    fn load_areas(&mut self) -> Result<()> {
-        let tile32_offsets = [(0, 0), (2, 0), (0, 2), (2, 2)];
-        let tile16_offsets = [(0, 0), (1, 0), (0, 1), (1, 1)];
+// The contents of a ROM to import. Decompressing the overworld data takes a while on large ROMs,
+// so this is read on a background thread before the import starts.
+pub struct RomData {
+    rom: Rom,
+    constants: Constants,
+    overworld: RomOverworld,
+}
 
-        let mut tile_lookup: Vec<HashMap<Tile, (TileIdx, Flip)>> =
-            vec![HashMap::new(); self.state.palettes.len()];
+impl RomData {
+    // Read a ROM file, or the ROM of a ROM folder (see `rom_folder`).
+    pub fn read(path: &Path) -> Result<Self> {
+        if path.is_dir() {
+            return rom_folder::read_folder(path);
+        }
+        let rom = Rom::new(std::fs::read(path)?);
+        let constants = Constants::auto(&rom)?;
+        let overworld = RomOverworld::read(&rom, &constants)?;
+        Ok(RomData {
+            rom,
+            constants,
+            overworld,
+        })
+    }
+}
+
+impl std::fmt::Debug for RomData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RomData({} bytes)", self.rom.data.len())
+    }
+}
+
+// Number of overworld maps that can be the parent of an area.
+pub const PARENT_MAP_COUNT: usize = 0x82;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportStage {
+    ReadingRom,
+    Palettes,
+    Areas,
+    Saving,
+    Pngs,
+}
 
-        fn strip_tile(mut tile: Tile) -> Tile {
-            // We clear these fields when looking up matching tiles, since these
-            // fields will vary over the course of processing.
-            tile.h_flippable = false;
-            tile.v_flippable = false;
-            tile
+impl Display for ImportStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ImportStage::ReadingRom => "Reading ROM",
+            ImportStage::Palettes => "Importing palettes",
+            ImportStage::Areas => "Importing areas",
+            ImportStage::Saving => "Saving project",
+            ImportStage::Pngs => "Rendering area PNGs",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+// An import into the project, done a step at a time (see `Importer::step`) so that progress can
+// be shown in between.
+pub struct Importer {
+    mode: ImportMode,
+    report: ImportReport,
+    data: Arc<RomData>,
+    theme: String,
+    area_name_by_map_id: HashMap<u8, AreaName>,
+    rules: ImportRules,
+    palette_ids: RomPaletteIds,
+    pal_bg_color: HashMap<PaletteId, ColorRGB>,
+    // The project's tiles for each palette (by index), with their flips, for reusing them:
+    tile_lookup: Vec<HashMap<Tile, (TileIdx, Flip)>>,
+    // Number of tiles in each palette before the areas were imported:
+    tile_counts: Vec<usize>,
+    // Parent maps to import (None for all of them):
+    map_ids: Option<BTreeSet<u8>>,
+    // For a preview, the parts of the project to put back once it's done:
+    preview: Option<ImportPreviewBackup>,
+    stage: ImportStage,
+    // The next parent map to import, or area to render, in the current stage:
+    next: usize,
+}
+
+// The parts of the project that a preview of an import changes (in memory only, since a preview
+// stops before anything is saved).
+struct ImportPreviewBackup {
+    palettes: Vec<Palette>,
+    area_names: Vec<AreaName>,
+    metadata: ProjectMetadata,
+}
+
+impl ImportPreviewBackup {
+    fn restore(self, state: &mut EditorState) {
+        state.palettes = self.palettes;
+        state.area_names = self.area_names;
+        state.project_metadata = self.metadata;
+        update_palette_order(state);
+    }
+}
+
+impl Rom {
+    pub fn new(data: Vec<u8>) -> Self {
+        Rom { data }
+    }
+
+    pub fn read_u8(&self, addr: PcAddr) -> Result<u8> {
+        ensure!(
+            (addr.0 as usize) < self.data.len(),
+            "read_u8 address out of bounds"
+        );
+        Ok(self.data[addr.0 as usize] as u8)
+    }
+
+    pub fn read_u16(&self, addr: PcAddr) -> Result<u16> {
+        ensure!(
+            addr.0 as usize + 1 < self.data.len(),
+            "read_u16 address out of bounds"
+        );
+        let b0 = self.data[addr.0 as usize] as u16;
+        let b1 = self.data[addr.0 as usize + 1] as u16;
+        Ok(b0 | b1 << 8)
+    }
+
+    pub fn read_u24(&self, addr: PcAddr) -> Result<u32> {
+        ensure!(
+            addr.0 as usize + 2 < self.data.len(),
+            "read_u24 address out of bounds"
+        );
+        let b0 = self.data[addr.0 as usize] as u32;
+        let b1 = self.data[addr.0 as usize + 1] as u32;
+        let b2 = self.data[addr.0 as usize + 2] as u32;
+        Ok(b0 | b1 << 8 | b2 << 16)
+    }
+
+    pub fn read_n(&self, addr: PcAddr, n: usize) -> Result<&[u8]> {
+        ensure!(
+            addr.0 as usize + n <= self.data.len(),
+            "read_n address out of bounds"
+        );
+        Ok(&self.data[addr.0 as usize..(addr.0 as usize + n)])
+    }
+
+    pub fn write_u8(&mut self, addr: PcAddr, x: u8) -> Result<()> {
+        self.write_n(addr, &[x])
+    }
+
+    pub fn write_u16(&mut self, addr: PcAddr, x: u16) -> Result<()> {
+        self.write_n(addr, &x.to_le_bytes())
+    }
+
+    pub fn write_u24(&mut self, addr: PcAddr, x: u32) -> Result<()> {
+        ensure!(x < 0x1000000, "write_u24 value out of range");
+        self.write_n(addr, &x.to_le_bytes()[..3])
+    }
+
+    pub fn write_n(&mut self, addr: PcAddr, data: &[u8]) -> Result<()> {
+        ensure!(
+            addr.0 as usize + data.len() <= self.data.len(),
+            "write_n address out of bounds"
+        );
+        self.data[addr.0 as usize..(addr.0 as usize + data.len())].copy_from_slice(data);
+        Ok(())
+    }
+}
+
+// We clear these fields when looking up matching tiles, since these
+// fields will vary over the course of processing.
+fn strip_tile(mut tile: Tile) -> Tile {
+    tile.h_flippable = false;
+    tile.v_flippable = false;
+    tile
+}
+
+impl Importer {
+    // Import the whole ROM at once.
+    pub fn import(state: &mut EditorState, path: &Path, mode: ImportMode) -> Result<ImportReport> {
+        info!("Importing from ROM at {} ({:?})", path.display(), mode);
+        let data = Arc::new(RomData::read(path)?);
+        let mut importer = Self::new(state, data, mode)?;
+        while !importer.step(state)? {}
+        Ok(importer.report)
+    }
+
+    pub fn new(state: &EditorState, data: Arc<RomData>, mode: ImportMode) -> Result<Self> {
+        let theme = state.main_area().theme.clone();
+        let rules = ImportRules::load(state)?;
+        let slots: Vec<PaletteSlot> = palette_slots(&data.constants)
+            .into_iter()
+            .map(|(slot, _, _)| slot)
+            .collect();
+        rules.check_palette_rows(&slots)?;
+        Ok(Self {
+            mode,
+            report: ImportReport::default(),
+            data,
+            theme,
+            area_name_by_map_id: HashMap::new(),
+            rules,
+            palette_ids: RomPaletteIds::default(),
+            pal_bg_color: HashMap::new(),
+            tile_lookup: vec![],
+            tile_counts: vec![],
+            map_ids: None,
+            preview: None,
+            stage: ImportStage::Palettes,
+            next: 0,
+        })
+    }
+
+    // Import only the areas of the given parent maps.
+    pub fn only_maps(mut self, map_ids: Option<BTreeSet<u8>>) -> Self {
+        self.map_ids = map_ids;
+        self
+    }
+
+    // Only work out what the import would change, for the report, leaving the project as it is.
+    pub fn preview(mut self, state: &EditorState) -> Self {
+        self.preview = Some(ImportPreviewBackup {
+            palettes: state.palettes.clone(),
+            area_names: state.area_names.clone(),
+            metadata: state.project_metadata.clone(),
+        });
+        self
+    }
+
+    // Stop the import before it's finished, putting back the project if it was a preview.
+    pub fn abandon(self, state: &mut EditorState) {
+        if let Some(backup) = self.preview {
+            backup.restore(state);
         }
+    }
 
-        for palette_idx in 0..self.state.palettes.len() {
-            for (idx, tile) in self.state.palettes[palette_idx].tiles.iter().enumerate() {
-                for flip in [Flip::None, Flip::Horizontal, Flip::Vertical, Flip::Both] {
-                    tile_lookup[palette_idx].insert(
-                        strip_tile(flip.apply_to_tile(tile.clone())),
-                        (idx as TileIdx, flip),
-                    );
+    // Do the next part of the import: all of the palettes, one parent map, saving the project,
+    // or one area's PNG. Returns whether the import is finished.
+    pub fn step(&mut self, state: &mut EditorState) -> Result<bool> {
+        match self.stage {
+            ImportStage::ReadingRom | ImportStage::Palettes => {
+                self.stage = ImportStage::Areas;
+                if self.mode == ImportMode::PalettesOnly {
+                    self.update_palette_colors(state)?;
+                    self.next = PARENT_MAP_COUNT;
+                } else {
+                    self.load_area_names(state)?;
+                    if self.mode == ImportMode::MapsOnly {
+                        // The palettes may have been edited since they were imported (so that
+                        // they no longer match the ROM's by color), so use the recorded ones:
+                        self.palette_ids = self.recorded_palette_ids(state)?;
+                    } else {
+                        let starting_palette_id =
+                            state.palettes.iter().map(|x| x.id).max().unwrap() + 1;
+                        self.import_all_palettes(state, starting_palette_id)?;
+                    }
+                    self.load_tile_lookup(state);
+                    self.tile_counts = state.palettes.iter().map(|p| p.tiles.len()).collect();
+                    self.next = 0;
                 }
             }
+            ImportStage::Areas if self.next < PARENT_MAP_COUNT => {
+                self.import_area(state, self.next)?;
+                self.next += 1;
+            }
+            ImportStage::Areas => {
+                for (pal, &count) in state.palettes.iter().zip(&self.tile_counts) {
+                    if pal.tiles.len() > count {
+                        let added = pal.tiles.len() - count;
+                        self.report.tiles_added.push((pal.name.clone(), added));
+                    }
+                }
+                if let Some(backup) = self.preview.take() {
+                    backup.restore(state);
+                    return Ok(true);
+                }
+                self.ensure_palette_full_rows(state)?;
+                if self.mode == ImportMode::Full {
+                    self.assign_bg_colors(state)?;
+                }
+                self.record_base_rom(state);
+                self.stage = ImportStage::Saving;
+            }
+            ImportStage::Saving => {
+                save_project(state)?;
+                load_project(state)?;
+                self.stage = ImportStage::Pngs;
+                self.next = 0;
+            }
+            ImportStage::Pngs => {
+                let Some(area_name) = state.area_names.get(self.next).cloned() else {
+                    load_project(state)?; // Load again to open the first room.
+                    return Ok(true);
+                };
+                let area_id = AreaId {
+                    theme: self.theme.clone(),
+                    area: area_name,
+                };
+                state.load_area(&area_id)?;
+                save_area_png(state, &area_id)?;
+                state.areas.remove(&area_id);
+                self.next += 1;
+            }
+        }
+        Ok(false)
+    }
+
+    // The current stage, and the fraction of it done so far.
+    pub fn progress(&self, state: &EditorState) -> (ImportStage, f32) {
+        let fraction = match self.stage {
+            ImportStage::Areas => self.next as f32 / PARENT_MAP_COUNT as f32,
+            ImportStage::Pngs => self.next as f32 / state.area_names.len().max(1) as f32,
+            _ => 0.0,
+        };
+        (self.stage, fraction)
+    }
+
+    pub fn into_report(self) -> ImportReport {
+        self.report
+    }
+
+    fn record_base_rom(&mut self, state: &mut EditorState) {
+        let info = RomInfo::new(&self.data.rom, &self.data.constants);
+        let metadata = &mut state.project_metadata;
+        if let Some(base) = &metadata.base_rom {
+            self.report.rom_differences = base.layout_differences(&info);
+            for d in &self.report.rom_differences {
+                warn!("ROM layout differs from previous import: {}", d);
+            }
         }
+        metadata.base_rom = Some(info);
+        metadata.modified = true;
+    }
 
-        for parent in 0..=0x81 {
-            if self.map_parents[parent] as usize != parent {
+    fn load_area_names(&mut self, state: &mut EditorState) -> Result<()> {
+        for area_name in &state.area_names {
+            let area_id = AreaId {
+                area: area_name.clone(),
+                theme: self.theme.clone(),
+            };
+            let area = load_area(state, &area_id)
+                .with_context(|| format!("Unable to load area {}/{}", area_name, self.theme))?;
+            if let Some(id) = area.vanilla_map_id {
+                self.area_name_by_map_id.insert(id, area_name.clone());
+            }
+        }
+        Ok(())
+    }
+
+    fn import_all_palettes(
+        &mut self,
+        state: &mut EditorState,
+        mut next_id: PaletteId,
+    ) -> Result<()> {
+        let mut pal_by_colors: HashMap<[ColorRGB; 16], PaletteId> = HashMap::new();
+        for pal in &state.palettes {
+            let mut colors = pal.colors;
+            colors[0] = [0, 0, 0];
+            let _ = pal_by_colors.try_insert(colors, pal.id);
+        }
+
+        let slots = palette_slots(&self.data.constants);
+        let slot_list: Vec<PaletteSlot> = slots.iter().map(|&(slot, _, _)| slot).collect();
+        let mut merged = vec![];
+        for (slot, addr, size) in slots {
+            if let Some(target) = self.rules.merge_target(&slot, &slot_list) {
+                merged.push((slot, target));
                 continue;
             }
-            let world_idx = parent / 64;
-            let _block_y = (parent / 8) % 8;
-            let block_x = parent % 8;
-            let size = if block_x <= 6 && self.map_parents[parent + 1] as usize == parent {
-                (2, 2)
+            let colors = read_palette(&self.data.rom, addr, size)?;
+            let id = if let Some(&id) = pal_by_colors.get(&colors) {
+                id
             } else {
-                (1, 1)
+                let mut name = slot.to_string();
+                if self.theme != "Base" {
+                    name += &format!(" ({})", self.theme);
+                }
+                state.palettes.push(Palette {
+                    modified: true,
+                    name,
+                    id: next_id,
+                    category: slot.group.into(),
+                    group: Some(format!("{:?}", slot.group)),
+                    colors,
+                    tiles: vec![],
+                });
+                pal_by_colors.insert(colors, next_id);
+                next_id += 1;
+                next_id - 1
             };
-            let mut gfx_idxs: Vec<u16> = vec![];
-            for idx in self.map_gfx[parent] {
-                gfx_idxs.extend((idx as u16 * 64)..((idx + 1) as u16 * 64));
+            self.palette_ids.0.insert(slot, id);
+        }
+        // Merged rows are imported as the palette of the row they're merged into:
+        for (slot, target) in merged {
+            let id = self.palette_ids.0[&target];
+            self.palette_ids.0.insert(slot, id);
+        }
+        // Remember where each palette came from, for exporting back to the ROM:
+        let metadata = &mut state.project_metadata;
+        metadata
+            .rom_palettes
+            .insert(self.theme.clone(), self.palette_ids.to_names());
+        metadata.modified = true;
+        update_palette_order(state);
+        Ok(())
+    }
+
+    // The palettes that the ROM's palette rows were imported as, in an earlier full import.
+    fn recorded_palette_ids(&self, state: &EditorState) -> Result<RomPaletteIds> {
+        let names = state
+            .project_metadata
+            .rom_palettes
+            .get(&self.theme)
+            .context("The project has no record of which palettes were imported from the ROM")?;
+        Ok(RomPaletteIds::from_names(&self.data.constants, names))
+    }
+
+    // Update the colors of the palettes that the ROM's palette rows were imported as.
+    fn update_palette_colors(&mut self, state: &mut EditorState) -> Result<()> {
+        let palette_ids = self.recorded_palette_ids(state)?;
+        let slots = palette_slots(&self.data.constants);
+        let slot_list: Vec<PaletteSlot> = slots.iter().map(|&(slot, _, _)| slot).collect();
+        for (slot, addr, size) in slots {
+            if self.rules.merge_target(&slot, &slot_list).is_some() {
+                continue;
             }
-            let animated_gfx = if [0x03, 0x05, 0x07, 0x43, 0x45, 0x47].contains(&parent) {
-                0x59
-            } else {
-                0x5B
+            let Some(&id) = palette_ids.0.get(&slot) else {
+                continue;
+            };
+            let Some(pal) = state.palettes.iter_mut().find(|p| p.id == id) else {
+                continue;
             };
-            gfx_idxs[0x1C0..0x1E0]
-                .copy_from_slice(&((animated_gfx * 64)..(animated_gfx * 64 + 32)).collect_vec());
+            // Color 0 isn't in the ROM's rows (it's the background color), so keep the project's:
+            let colors = read_palette(&self.data.rom, addr, size)?;
+            if pal.colors[1..=size] != colors[1..=size] {
+                pal.colors[1..=size].copy_from_slice(&colors[1..=size]);
+                pal.modified = true;
+                if !self.report.palettes_updated.contains(&pal.name) {
+                    self.report.palettes_updated.push(pal.name.clone());
+                }
+            }
+        }
+        Ok(())
+    }
 
-            let pal = &self.map_palettes[parent];
-            let bg_color = if let Some(custom_bg_colors_addr) = self.constants.custom_bg_colors_addr
-            {
+    fn load_tile_lookup(&mut self, state: &EditorState) {
+        self.tile_lookup = vec![HashMap::new(); state.palettes.len()];
+        for palette_idx in 0..state.palettes.len() {
+            for (idx, tile) in state.palettes[palette_idx].tiles.iter().enumerate() {
+                for flip in [Flip::None, Flip::Horizontal, Flip::Vertical, Flip::Both] {
+                    self.tile_lookup[palette_idx].insert(
+                        strip_tile(flip.apply_to_tile(tile.clone())),
+                        (idx as TileIdx, flip),
+                    );
+                }
+            }
+        }
+    }
+
+    // Import the area of a parent map (doing nothing for other maps).
+    fn import_area(&mut self, state: &mut EditorState, parent: usize) -> Result<()> {
+        let tile32_offsets = [(0, 0), (2, 0), (0, 2), (2, 2)];
+        let tile16_offsets = [(0, 0), (1, 0), (0, 1), (1, 1)];
+
+        if self.data.overworld.map_parents[parent] as usize != parent {
+            return Ok(());
+        }
+        if self
+            .map_ids
+            .as_ref()
+            .is_some_and(|ids| !ids.contains(&(parent as u8)))
+        {
+            return Ok(());
+        }
+        if self.rules.is_dropped(parent as u8) {
+            if let Some(name) = self.area_name_by_map_id.get(&(parent as u8)) {
+                self.report.dropped.push(name.clone());
+            } else {
+                self.report.dropped.push(format!("{:02X}", parent));
+            }
+            return Ok(());
+        }
+        let mut tile_lookup = std::mem::take(&mut self.tile_lookup);
+        // When only refreshing maps, tiles added for an area that ends up being kept
+        // must be rolled back, so take a snapshot to restore in that case:
+        let snapshot = match self.mode {
+            ImportMode::MapsOnly => Some((state.palettes.clone(), tile_lookup.clone())),
+            _ => None,
+        };
+        let world_idx = parent / 64;
+        let _block_y = (parent / 8) % 8;
+        let block_x = parent % 8;
+        let size = if block_x <= 6 && self.data.overworld.map_parents[parent + 1] as usize == parent
+        {
+            (2, 2)
+        } else {
+            (1, 1)
+        };
+        let gfx_idxs = self.data.overworld.area_gfx(parent);
+
+        let bg_color =
+            if let Some(custom_bg_colors_addr) = self.data.constants.custom_bg_colors_addr {
                 let c = self
+                    .data
                     .rom
                     .read_u16((custom_bg_colors_addr + parent as u32 * 2).into())?;
                 [
@@ -844,175 +1761,285 @@ impl<'a> Importer<'a> {
                 }
             };
 
-            let area_name = match self.area_name_by_map_id.get(&(parent as u8)) {
-                Some(name) => name.clone(),
-                None => match world_idx {
-                    0 => format!("{:02X} Light World", parent),
-                    1 => format!("{:02X} Dark World", parent),
-                    2 => format!("{:02X} Special World", parent),
-                    _ => bail!("unexpected world_idx: {}", world_idx),
-                },
-            };
-            let mut area: Area = Area {
-                modified: false,
-                name: area_name,
-                theme: self.theme.clone(),
-                vanilla_map_id: Some(parent as u8),
-                bg_color,
-                size: (size.0 * 2, size.1 * 2),
-                screens: vec![],
-            };
-            self.state.area_names.push(area.name.clone());
-            for y in 0..size.1 * 2 {
-                for x in 0..size.0 * 2 {
-                    area.screens.push(Screen {
-                        position: (x, y),
-                        palettes: [[0; 32]; 32],
-                        tiles: [[0; 32]; 32],
-                        flips: [[Flip::None; 32]; 32],
-                    });
-                }
+        let world_name = match world_idx {
+            0 => "Light World",
+            1 => "Dark World",
+            2 => "Special World",
+            _ => bail!("unexpected world_idx: {}", world_idx),
+        };
+        let area_name = match (
+            self.area_name_by_map_id.get(&(parent as u8)),
+            self.rules.area_name(parent as u8),
+        ) {
+            (Some(name), _) | (None, Some(name)) => name.clone(),
+            (None, None) => format!("{:02X} {}", parent, world_name),
+        };
+        // Group imported areas by world, unless already assigned to a group.
+        let metadata = &mut state.project_metadata;
+        if self.mode.imports_areas() && !metadata.area_groups.contains_key(&area_name) {
+            metadata
+                .area_groups
+                .insert(area_name.clone(), world_name.to_string());
+            metadata.modified = true;
+        }
+        let mut area: Area = Area {
+            modified: false,
+            name: area_name,
+            theme: self.theme.clone(),
+            vanilla_map_id: Some(parent as u8),
+            bg_color,
+            size: (size.0 * 2, size.1 * 2),
+            screens: vec![],
+            entrances: self.data.overworld.area_entrances(parent, size),
+            secrets: self.data.overworld.area_secrets(parent, size),
+            travel: self.data.overworld.area_travel(parent, size),
+            annotations: vec![],
+            properties: read_properties(&self.data.rom, &self.data.constants, parent)?,
+        };
+        state.area_names.push(area.name.clone());
+        for y in 0..size.1 * 2 {
+            for x in 0..size.0 * 2 {
+                area.screens.push(Screen {
+                    position: (x, y),
+                    palettes: [[0; 32]; 32],
+                    tiles: [[0; 32]; 32],
+                    flips: [[Flip::None; 32]; 32],
+                    camera_locked: false,
+                    overlay: vec![],
+                });
             }
-            for my in 0..size.1 as usize {
-                for mx in 0..size.0 as usize {
-                    let map_idx = parent + my as usize * 8 + mx as usize;
-                    let tiles = &self.map_tiles[map_idx];
-                    for ty in 0..16 {
-                        for tx in 0..16 {
-                            let t32_idx = tiles[ty][tx];
-                            let t32 = self.tiles32[t32_idx as usize];
-                            for i in 0..4 {
-                                let t16_idx = t32[i];
-                                let t16 = self.tiles16[t16_idx as usize];
-                                for j in 0..4 {
-                                    let t8 = t16[j];
-                                    let x = mx * 64
-                                        + tx * 4
-                                        + tile32_offsets[i].0
-                                        + tile16_offsets[j].0;
-                                    let y = my * 64
-                                        + ty * 4
-                                        + tile32_offsets[i].1
-                                        + tile16_offsets[j].1;
-                                    let tiles8_idx = gfx_idxs[t8.gfx_char as usize];
-                                    let gfx_sheet = t8.gfx_char / 64;
-                                    ensure!(gfx_sheet < 8);
-                                    let pal_high = [0, 3, 4, 5].contains(&gfx_sheet);
-                                    let pal_id = match (t8.pal_idx, pal_high) {
-                                        (p @ (0 | 1), _) => self.hud_palette_ids[0][p as usize],
-                                        (p @ 2..=6, false) => {
-                                            self.main_palette_ids[pal.main as usize][p as usize - 2]
-                                        }
-                                        (7, false) => {
-                                            self.animated_palette_ids[pal.animated as usize]
-                                        }
-                                        (p @ 2..=4, true) => {
-                                            self.aux_palette_ids[pal.aux1 as usize][p as usize - 2]
-                                        }
-                                        (p @ 5..=7, true) => {
-                                            self.aux_palette_ids[pal.aux2 as usize][p as usize - 5]
-                                        }
-                                        _ => {
-                                            bail!("unexpected palette: {} {}", t8.pal_idx, pal_high)
-                                        }
-                                    };
-                                    let palette_idx = self.state.palettes_id_idx_map[&pal_id];
-                                    let collision = self.tile_types[t8.gfx_char as usize];
-                                    let pixels =
-                                        t8.flip.apply_to_pixels(self.tiles8[tiles8_idx as usize]);
-                                    let tile = Tile {
-                                        priority: t8.priority,
-                                        h_flippable: false,
-                                        v_flippable: false,
-                                        collision,
-                                        pixels,
-                                    };
-                                    let (tile_idx, flip) = match tile_lookup[palette_idx].get(&tile)
-                                    {
-                                        Some(x) => *x,
-                                        None => {
-                                            let idx = self.state.palettes[palette_idx].tiles.len()
-                                                as TileIdx;
-                                            self.state.palettes[palette_idx].tiles.push(tile);
-                                            for flip in [
-                                                Flip::None,
-                                                Flip::Horizontal,
-                                                Flip::Vertical,
-                                                Flip::Both,
-                                            ] {
-                                                tile_lookup[palette_idx].insert(
-                                                    strip_tile(flip.apply_to_tile(tile.clone())),
-                                                    (idx, flip),
-                                                );
-                                            }
-                                            (idx, Flip::None)
-                                        }
-                                    };
-
-                                    match flip {
-                                        Flip::None => {}
-                                        Flip::Horizontal => {
-                                            self.state.palettes[palette_idx].tiles
-                                                [tile_idx as usize]
-                                                .h_flippable = true;
-                                        }
-                                        Flip::Vertical => {
-                                            self.state.palettes[palette_idx].tiles
-                                                [tile_idx as usize]
-                                                .v_flippable = true;
-                                        }
-                                        Flip::Both => {
-                                            self.state.palettes[palette_idx].tiles
-                                                [tile_idx as usize]
-                                                .h_flippable = true;
-                                            self.state.palettes[palette_idx].tiles
-                                                [tile_idx as usize]
-                                                .v_flippable = true;
-                                        }
-                                    }
-
-                                    area.set_tile(x as u16, y as u16, tile_idx as u16).unwrap();
-                                    area.set_palette(x as u16, y as u16, pal_id).unwrap();
-                                    area.set_flip(x as u16, y as u16, flip).unwrap();
-
-                                    match self.pal_bg_color.entry(pal_id) {
-                                        Entry::Occupied(mut occupied_entry) => {
-                                            if occupied_entry.get() != &bg_color {
-                                                // Use black as a marker of ambiguous BG color
-                                                occupied_entry.insert([0, 0, 0]);
-                                            }
-                                        }
-                                        Entry::Vacant(vacant_entry) => {
-                                            vacant_entry.insert(bg_color);
-                                        }
-                                    }
-                                }
+        }
+        for my in 0..size.1 as usize {
+            for mx in 0..size.0 as usize {
+                let map_idx = parent + my * 8 + mx;
+                let tiles = self.data.overworld.map_tiles[map_idx];
+                for (ty, row) in tiles.iter().enumerate() {
+                    for (tx, &t32_idx) in row.iter().enumerate() {
+                        let t32 = self.data.overworld.tiles32[t32_idx as usize];
+                        for i in 0..4 {
+                            let t16_idx = t32[i];
+                            let t16 = self.data.overworld.tiles16[t16_idx as usize];
+                            for j in 0..4 {
+                                let t8 = t16[j];
+                                let x =
+                                    mx * 64 + tx * 4 + tile32_offsets[i].0 + tile16_offsets[j].0;
+                                let y =
+                                    my * 64 + ty * 4 + tile32_offsets[i].1 + tile16_offsets[j].1;
+                                let (pal_id, tile_idx, flip) = self.import_tile8(
+                                    state,
+                                    &mut tile_lookup,
+                                    parent,
+                                    &gfx_idxs,
+                                    bg_color,
+                                    t8,
+                                )?;
+                                area.set_tile(x as u16, y as u16, tile_idx).unwrap();
+                                area.set_palette(x as u16, y as u16, pal_id).unwrap();
+                                area.set_flip(x as u16, y as u16, flip).unwrap();
                             }
                         }
                     }
                 }
             }
-            self.state
-                .set_area(crate::state::AreaPosition::Main, area)?;
-            save_area_json(self.state, &self.state.main_area_id.clone())?;
         }
+        for ((x16, y16), t16_idx) in self.data.overworld.area_overlay(parent, size) {
+            let t16 = self.data.overworld.tiles16[t16_idx as usize];
+            for (j, t8) in t16.into_iter().enumerate() {
+                let tiles8_idx = gfx_idxs[t8.gfx_char as usize];
+                // Transparent tiles don't need to be placed:
+                if self.data.overworld.tiles8[tiles8_idx as usize] == [[0; 8]; 8] {
+                    continue;
+                }
+                let (palette, tile, flip) =
+                    self.import_tile8(state, &mut tile_lookup, parent, &gfx_idxs, bg_color, t8)?;
+                let x = x16 * 2 + tile16_offsets[j].0 as TileCoord;
+                let y = y16 * 2 + tile16_offsets[j].1 as TileCoord;
+                let tile = OverlayTile {
+                    x: 0,
+                    y: 0,
+                    palette,
+                    tile,
+                    flip,
+                };
+                area.set_overlay(x, y, Some(tile))?;
+            }
+        }
+        // Only the tiles are kept when the areas aren't being imported:
+        let store = self.mode.imports_areas() && self.should_store_area(state, &area)?;
+        if !store {
+            if let Some((palettes, lookup)) = snapshot {
+                state.palettes = palettes;
+                tile_lookup = lookup;
+            }
+        } else if self.preview.is_none() {
+            // Screen annotations and camera locks aren't in the ROM, so keep the project's:
+            if let Ok(current) = load_area(state, &area.id()) {
+                for screen in &mut area.screens {
+                    screen.camera_locked = is_camera_locked(&current, screen.position);
+                }
+                area.annotations = current.annotations;
+            }
+            let key = format!("{}/{}", area.name, area.theme);
+            let hash = area.map_hash();
+            state.set_area(crate::state::AreaPosition::Main, area)?;
+            save_area_json(state, &state.main_area_id.clone())?;
+            let metadata = &mut state.project_metadata;
+            metadata.imported_area_hashes.insert(key, hash);
+            metadata.modified = true;
+        }
+        self.tile_lookup = tile_lookup;
         Ok(())
     }
 
-    fn ensure_palette_full_rows(&mut self) -> Result<()> {
-        for pal in &mut self.state.palettes {
-            let size = ((pal.tiles.len() + 15) / 16 * 16).max(16);
-            pal.tiles.resize(size, Tile::default());
+    // The project tile that places an 8x8 tile of the area of a parent map (adding it to its
+    // palette if it's new), as the tile's palette, index, and flip.
+    fn import_tile8(
+        &mut self,
+        state: &mut EditorState,
+        tile_lookup: &mut [HashMap<Tile, (TileIdx, Flip)>],
+        parent: usize,
+        gfx_idxs: &[u16],
+        bg_color: ColorRGB,
+        t8: Tile8,
+    ) -> Result<(PaletteId, TileIdx, Flip)> {
+        let pal = &self.data.overworld.map_palettes[parent];
+        let tiles8_idx = gfx_idxs[t8.gfx_char as usize];
+        let gfx_sheet = t8.gfx_char / 64;
+        ensure!(gfx_sheet < 8);
+        let pal_high = is_pal_high(gfx_sheet);
+        let pal_id = self
+            .palette_ids
+            .get(pal, t8.pal_idx, pal_high)
+            .context("palette not found")?;
+        let palette_idx = *state
+            .palettes_id_idx_map
+            .get(&pal_id)
+            .with_context(|| format!("palette {} not found", pal_id))?;
+        let collision = self.data.overworld.tile_types[t8.gfx_char as usize];
+        let pixels = t8
+            .flip
+            .apply_to_pixels(self.data.overworld.tiles8[tiles8_idx as usize]);
+        // Characters swapped by the animation are the first frames of their animated slots:
+        let animated = ANIMATED_CHARS.contains(&t8.gfx_char).then(|| AnimatedSlot {
+            bank: AnimatedBank::for_map(parent as u8),
+            slot: (t8.gfx_char - ANIMATED_CHARS.start) as u8,
+        });
+        let tile = Tile {
+            priority: t8.priority,
+            h_flippable: false,
+            v_flippable: false,
+            collision,
+            animated,
+            editor_only: false,
+            pixels,
+        };
+        let (tile_idx, flip) = match tile_lookup[palette_idx].get(&tile) {
+            Some(x) => *x,
+            None => {
+                let idx = state.palettes[palette_idx].tiles.len() as TileIdx;
+                state.palettes[palette_idx].tiles.push(tile);
+                for flip in [Flip::None, Flip::Horizontal, Flip::Vertical, Flip::Both] {
+                    tile_lookup[palette_idx]
+                        .insert(strip_tile(flip.apply_to_tile(tile)), (idx, flip));
+                }
+                (idx, Flip::None)
+            }
+        };
+
+        let pal = &mut state.palettes[palette_idx];
+        let tile = &mut pal.tiles[tile_idx as usize];
+        let flippable = (tile.h_flippable, tile.v_flippable);
+        match flip {
+            Flip::None => {}
+            Flip::Horizontal => {
+                tile.h_flippable = true;
+            }
+            Flip::Vertical => {
+                tile.v_flippable = true;
+            }
+            Flip::Both => {
+                tile.h_flippable = true;
+                tile.v_flippable = true;
+            }
+        }
+        if (tile.h_flippable, tile.v_flippable) != flippable {
             pal.modified = true;
         }
+
+        match self.pal_bg_color.entry(pal_id) {
+            Entry::Occupied(mut occupied_entry) => {
+                if occupied_entry.get() != &bg_color {
+                    // Use black as a marker of ambiguous BG color
+                    occupied_entry.insert([0, 0, 0]);
+                }
+            }
+            Entry::Vacant(vacant_entry) => {
+                vacant_entry.insert(bg_color);
+            }
+        }
+        Ok((pal_id, tile_idx, flip))
+    }
+
+    // Decide whether a newly imported area should overwrite the project's version,
+    // recording the outcome in the import report.
+    fn should_store_area(&mut self, state: &EditorState, area: &Area) -> Result<bool> {
+        let key = format!("{}/{}", area.name, area.theme);
+        if !area_json_path(state, &area.id())?.exists() {
+            self.report.added.push(area.name.clone());
+            return Ok(true);
+        }
+        // An area that can't be read (e.g. corrupt JSON) stops the import, rather than being
+        // overwritten:
+        let current =
+            load_area(state, &area.id()).with_context(|| format!("Unable to load area {}", key))?;
+        let new_hash = area.map_hash();
+        let current_hash = current.map_hash();
+        if self.mode == ImportMode::Full {
+            if current_hash == new_hash {
+                self.report.unchanged.push(area.name.clone());
+            } else {
+                self.report.updated.push(area.name.clone());
+            }
+            return Ok(true);
+        }
+        let imported_hash = state
+            .project_metadata
+            .imported_area_hashes
+            .get(&key)
+            .copied();
+        if current_hash == new_hash {
+            self.report.unchanged.push(area.name.clone());
+            Ok(false)
+        } else if Some(current_hash) == imported_hash {
+            // Not edited in the project since the last import, so take the ROM's version:
+            self.report.updated.push(area.name.clone());
+            Ok(true)
+        } else if Some(new_hash) == imported_hash {
+            self.report.kept.push(area.name.clone());
+            Ok(false)
+        } else {
+            warn!("Area {} was modified in both the project and ROM.", key);
+            self.report.conflicts.push(area.name.clone());
+            Ok(false)
+        }
+    }
+
+    fn ensure_palette_full_rows(&mut self, state: &mut EditorState) -> Result<()> {
+        for pal in &mut state.palettes {
+            let size = pal.tiles.len().div_ceil(16).max(1) * 16;
+            if size != pal.tiles.len() {
+                pal.tiles.resize(size, Tile::default());
+                pal.modified = true;
+            }
+        }
         Ok(())
     }
 
-    fn assign_bg_colors(&mut self) -> Result<()> {
+    fn assign_bg_colors(&mut self, state: &mut EditorState) -> Result<()> {
         // If a given BG color is consistently used with a palette, then assign
         // it to color 0 of the palette. This won't have any effect in-game, but
         // it helps with rendering the tileset more accurately in the editor.
-        for p in &mut self.state.palettes {
+        for p in &mut state.palettes {
             if let Some(&c) = self.pal_bg_color.get(&p.id) {
                 p.colors[0] = c;
             }
@@ -1021,71 +2048,151 @@ impl<'a> Importer<'a> {
     }
 }
 
-fn decompress(rom: &Rom, mut addr: PcAddr, big_endian_offset: bool) -> Result<Vec<u8>> {
-    let mut out: Vec<u8> = Vec::new();
-    loop {
-        let byte = rom.read_u8(addr)? as isize;
-        addr += 1;
-        if byte == 0xFF {
-            return Ok(out);
-        }
-        let mut block_type = byte >> 5;
-        let size: usize;
-        if block_type != 7 {
-            size = ((byte & 0x1F) + 1) as usize;
-        } else {
-            size = (((byte & 3) << 8 | rom.read_u8(addr)? as isize) + 1) as usize;
-            addr += 1;
-            block_type = (byte >> 2) & 7;
-        }
-
-        match block_type {
-            0 => {
-                // Raw block
-                out.extend(rom.read_n(addr, size)?);
-                addr += size as u32;
+// The tiles placed by an overlay routine, with their positions in 16x16 tiles. The routines load
+// 16x16 tile numbers into A (incrementing it for runs of consecutive tiles) and store them into
+// the map buffer at $7E2000, which holds the area's 64x64 16x16 tiles. Only the instructions
+// used by the vanilla routines are understood, and reading stops at any other.
+pub fn read_overlay(rom: &Rom, addr: SnesAddr) -> Result<Vec<((TileCoord, TileCoord), Tile16Idx)>> {
+    let bank = addr.0 & 0xFF0000;
+    let mut addr = addr;
+    let (mut a, mut x) = (0u16, 0u16);
+    let mut out = vec![];
+    let mut store = |offset: u16, a: u16| {
+        let i = (offset & 0x1FFF) / 2;
+        out.push(((i % 64, i / 64), a));
+    };
+    // (A bound on the routine's length, in case of a loop.)
+    for _ in 0..0x1000 {
+        let operand = || rom.read_u16((addr + 1).into());
+        match rom.read_u8(addr.into())? {
+            // LDA #imm
+            0xA9 => {
+                a = operand()?;
+                addr += 3;
             }
-            1 => {
-                // Byte-level RLE block
-                let value = rom.read_u8(addr)?;
-                addr += 1;
-                out.extend(&vec![value; size]);
+            // LDX #imm
+            0xA2 => {
+                x = operand()?;
+                addr += 3;
             }
-            2 => {
-                // Word-level RLE block
-                let b0 = rom.read_u8(addr)?;
-                let b1 = rom.read_u8(addr + 1)?;
-                addr += 2;
-                out.extend(&[b0, b1].repeat(size >> 1));
-                if size & 1 == 1 {
-                    out.push(b0);
-                }
+            // STA abs
+            0x8D => {
+                store(operand()?, a);
+                addr += 3;
+            }
+            // STA abs,X
+            0x9D => {
+                store(operand()?.wrapping_add(x), a);
+                addr += 3;
             }
-            3 => {
-                // Incrementing sequence
-                let mut b = rom.read_u8(addr)?;
+            // STA long
+            0x8F => {
+                store(operand()?, a);
+                addr += 4;
+            }
+            // STA long,X
+            0x9F => {
+                store(operand()?.wrapping_add(x), a);
+                addr += 4;
+            }
+            // INC A
+            0x1A => {
+                a = a.wrapping_add(1);
                 addr += 1;
-                for _ in 0..size {
-                    out.push(b);
-                    b = b.wrapping_add(1);
-                }
             }
-            4 => {
-                // Copy earlier output, with absolute offset:
-                let offset = if big_endian_offset {
-                    (rom.read_u8(addr)? as usize) << 8 | rom.read_u8(addr + 1)? as usize
-                } else {
-                    rom.read_u16(addr)? as usize
-                };
-                assert!(offset < out.len());
-                addr += 2;
-                for i in offset..(offset + size) {
-                    out.push(out[i]);
-                }
+            // NOP
+            0xEA => addr += 1,
+            // JMP abs
+            0x4C => addr = SnesAddr(bank | operand()? as u32),
+            // RTS, RTL
+            0x60 | 0x6B => return Ok(out),
+            op => {
+                warn!(
+                    "Unknown instruction {:02X} in overlay routine at {}",
+                    op, addr
+                );
+                return Ok(out);
             }
-            _ => {
-                bail!("Unexpected/impossible block type: {block_type}");
+        }
+    }
+    warn!("Overlay routine at {} didn't return", addr);
+    Ok(out)
+}
+
+fn read_graphics_sheets(rom: &Rom, constants: &Constants) -> Result<Vec<GraphicsSheet>> {
+    let gfx_bank = rom.read_u16(constants.gfx_bank_addr.into())?;
+    let gfx_high = rom.read_u16(constants.gfx_high_addr.into())?;
+    let gfx_low = rom.read_u16(constants.gfx_low_addr.into())?;
+
+    let mut sheets = vec![];
+    for i in 0..113 {
+        let bank = rom.read_u8(SnesAddr::from_bank_offset(0x00, gfx_bank + i).into())?;
+        let high = rom.read_u8(SnesAddr::from_bank_offset(0x00, gfx_high + i).into())?;
+        let low = rom.read_u8(SnesAddr::from_bank_offset(0x00, gfx_low + i).into())?;
+        let addr = SnesAddr::from_bytes(bank, high, low);
+        let data = decompress(rom, addr.into(), false)?;
+        if data.len() != 0x600 {
+            bail!("Unexpected graphics sheet length: {}", data.len());
+        }
+
+        let mut sheet: GraphicsSheet = vec![];
+        for j in 0..64 {
+            let mut tile: [[u8; 8]; 8] = [[0; 8]; 8];
+            for (y, row) in tile.iter_mut().enumerate() {
+                for (x, pixel) in row.iter_mut().enumerate() {
+                    let c0 = (data[j * 24 + y * 2] >> (7 - x)) & 1;
+                    let c1 = (data[j * 24 + y * 2 + 1] >> (7 - x)) & 1;
+                    let c2 = (data[j * 24 + y + 16] >> (7 - x)) & 1;
+                    *pixel = c0 | (c1 << 1) | (c2 << 2);
+                }
             }
+            sheet.push(tile);
         }
+        sheets.push(sheet);
+    }
+    Ok(sheets)
+}
+
+// Load just the graphics sheets from a ROM.
+pub fn load_graphics_sheets(path: &Path) -> Result<Vec<GraphicsSheet>> {
+    info!("Loading graphics sheets from ROM at {}", path.display());
+    let rom = Rom::new(std::fs::read(path)?);
+    let constants = Constants::auto(&rom)?;
+    read_graphics_sheets(&rom, &constants)
+}
+
+fn decompress(rom: &Rom, addr: PcAddr, big_endian_offset: bool) -> Result<Vec<u8>> {
+    Ok(decompress_at(rom, addr, big_endian_offset)?.0)
+}
+
+// Decompress data from the ROM (see `compression`), also returning the address following the
+// compressed data.
+pub fn decompress_at(
+    rom: &Rom,
+    addr: PcAddr,
+    big_endian_offset: bool,
+) -> Result<(Vec<u8>, PcAddr)> {
+    let data = rom
+        .data
+        .get(addr.0 as usize..)
+        .context("compressed data address out of bounds")?;
+    let (out, len) = decompress_prefix(data, big_endian_offset)
+        .with_context(|| format!("Unable to decompress data at {}", addr))?;
+    Ok((out, addr + len as u32))
+}
+
+// Layout differences between the project's base ROM (from when it was imported) and the
+// given ROM, e.g. before exporting against it.
+pub fn check_base_rom(state: &EditorState, path: &Path) -> Result<Vec<String>> {
+    rom_folder::ensure_rom_file(path)?;
+    let info = RomInfo::read(path)?;
+    let base = state
+        .project_metadata
+        .base_rom
+        .as_ref()
+        .context("The project has no record of the ROM it was imported from.")?;
+    if base.hash != info.hash {
+        info!("ROM differs from the one the project was imported from.");
     }
+    Ok(base.layout_differences(&info))
 }
@@ -0,0 +1,135 @@
+// Importing from a ROM folder: a ROM along with dumps of its 16x16 tile definitions and palettes
+// that replace the ROM's own, so that custom map16 definitions and palettes kept outside the ROM
+// are imported with it. The folder holds:
+//
+// - the ROM (a single .sfc or .smc file),
+// - `Map16.bin`: the 16x16 tile definitions, laid out as the table at `tiles16_addr`: 8 bytes per
+//   tile, its four 8x8 tiles (top-left, top-right, bottom-left, bottom-right) as little-endian
+//   VRAM tilemap words (see `Tile8::to_vram_tilemap_word`),
+// - `Palettes/HUD.bin`, `Main.bin`, `Aux.bin` and `Animated.bin`: the palette rows of each group,
+//   in the order of `palette_slots` (by set, then row), each row being its colors (from color 1)
+//   as little-endian 15-bit BGR words, as in the vanilla palette tables.
+//
+// Files may be shorter than the full table, replacing just its first entries. The files are plain
+// dumps of the ROM tables, not any other editor's project format.
+//
+// The files that are present replace the corresponding data in a copy of the ROM, which is then
+// imported as usual.
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, ensure, Context, Result};
+use log::info;
+
+use super::{palette_slots, Constants, PaletteGroup, PcAddr, Rom, RomData, RomOverworld};
+
+const ROM_EXTENSIONS: [&str; 2] = ["sfc", "smc"];
+
+// The ROM in a ROM folder.
+pub fn find_rom(dir: &Path) -> Result<PathBuf> {
+    let mut roms = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_rom = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| ROM_EXTENSIONS.contains(&e.to_lowercase().as_str()));
+        if is_rom {
+            roms.push(path);
+        }
+    }
+    match roms.len() {
+        0 => bail!("No ROM (.sfc or .smc file) in {}", dir.display()),
+        1 => Ok(roms.remove(0)),
+        _ => bail!("More than one ROM in {}", dir.display()),
+    }
+}
+
+// The contents of a file in the folder, if it's present, checking that it's a whole number of
+// `unit`-byte entries, up to `max_len` bytes.
+fn read_folder_file(path: &Path, max_len: usize, unit: usize) -> Result<Option<Vec<u8>>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = std::fs::read(path)?;
+    ensure!(
+        data.len() % unit == 0 && data.len() <= max_len,
+        "{} has the wrong size ({} bytes, expected a multiple of {} up to {})",
+        path.display(),
+        data.len(),
+        unit,
+        max_len
+    );
+    info!("Using {} ({} bytes)", path.display(), data.len());
+    Ok(Some(data))
+}
+
+fn palette_group_file(group: PaletteGroup) -> &'static str {
+    match group {
+        PaletteGroup::HUD => "HUD.bin",
+        PaletteGroup::Main => "Main.bin",
+        PaletteGroup::Aux => "Aux.bin",
+        PaletteGroup::Animated => "Animated.bin",
+    }
+}
+
+// Replace the ROM's map16 definitions and palettes with those in the folder.
+pub fn apply_folder_files(rom: &mut Rom, constants: &Constants, dir: &Path) -> Result<()> {
+    let map16_len = constants.tiles16_cnt as usize * 8;
+    if let Some(data) = read_folder_file(&dir.join("Map16.bin"), map16_len, 8)? {
+        rom.write_n(constants.tiles16_addr.into(), &data)?;
+    }
+    let slots = palette_slots(constants);
+    for group in [
+        PaletteGroup::HUD,
+        PaletteGroup::Main,
+        PaletteGroup::Aux,
+        PaletteGroup::Animated,
+    ] {
+        // The file holds the group's rows one after another, but they aren't necessarily stored
+        // that way in the ROM, so each row is written at its own address:
+        let rows: Vec<(PcAddr, usize)> = slots
+            .iter()
+            .filter(|(s, _, _)| s.group == group)
+            .map(|&(_, addr, size)| (addr, size * 2))
+            .collect();
+        let path = dir.join("Palettes").join(palette_group_file(group));
+        let max_len = rows.iter().map(|&(_, len)| len).sum();
+        let Some(data) = read_folder_file(&path, max_len, 2)? else {
+            continue;
+        };
+        let mut offset = 0;
+        for (addr, len) in rows {
+            let len = len.min(data.len() - offset);
+            rom.write_n(addr, &data[offset..offset + len])?;
+            offset += len;
+        }
+    }
+    Ok(())
+}
+
+// Check that a path is a ROM file, and not a ROM folder, for uses that need the ROM as it is (e.g.
+// exporting against it).
+pub fn ensure_rom_file(path: &Path) -> Result<()> {
+    ensure!(
+        !path.is_dir(),
+        "{} is a ROM folder; select its ROM file instead.",
+        path.display()
+    );
+    Ok(())
+}
+
+// Read the ROM of a ROM folder, with the folder's data in place of the ROM's.
+pub fn read_folder(dir: &Path) -> Result<RomData> {
+    let rom_path = find_rom(dir)?;
+    info!("Reading ROM folder's ROM at {}", rom_path.display());
+    let mut rom = Rom::new(std::fs::read(&rom_path)?);
+    let constants = Constants::auto(&rom)?;
+    apply_folder_files(&mut rom, &constants, dir)
+        .with_context(|| format!("Unable to read ROM folder at {}", dir.display()))?;
+    let overworld = RomOverworld::read(&rom, &constants)?;
+    Ok(RomData {
+        rom,
+        constants,
+        overworld,
+    })
+}
@@ -0,0 +1,152 @@
+// Rules for customizing ROM imports, read from a JSON file that the project points to, so that
+// re-importing (e.g. by each member of a team) gives the same result every time:
+//
+//     {
+//         "rename_areas": { "00": "Lost Woods", "1B": "Hyrule Castle" },
+//         "merge_palettes": { "Main 1-0": "Main 0-0" },
+//         "drop_maps": ["80", "81"]
+//     }
+//
+// Maps are identified by the (hex) ID of their parent map, as in the default area names, and
+// palette rows by their names in the project metadata (e.g. "Main 0-2").
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    import::PaletteSlot,
+    persist::load_json,
+    state::{AreaName, EditorState},
+};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ImportRules {
+    // Names for the areas imported from the given maps, in place of the defaults (such as
+    // "1B Light World"). Areas already in the project keep their names.
+    #[serde(default)]
+    pub rename_areas: BTreeMap<String, AreaName>,
+    // Palette rows to import as the same palette as another row, rather than as their own
+    // palette. Tiles using a merged row then use the colors of the row it's merged into.
+    #[serde(default)]
+    pub merge_palettes: BTreeMap<String, String>,
+    // Maps to leave out of the import. Any areas already in the project for them are kept.
+    #[serde(default)]
+    pub drop_maps: Vec<String>,
+}
+
+// Parse a map ID written in hex, with or without a "$" or "0x" prefix.
+pub fn parse_map_id(s: &str) -> Result<u8> {
+    let digits = s
+        .trim()
+        .trim_start_matches('$')
+        .trim_start_matches("0x")
+        .trim_start_matches("0X");
+    let id = u8::from_str_radix(digits, 16).with_context(|| format!("Invalid map ID: {}", s))?;
+    if id > 0x81 {
+        bail!("Invalid map ID: {} (maps go up to 81)", s);
+    }
+    Ok(id)
+}
+
+impl ImportRules {
+    pub fn read(path: &Path) -> Result<Self> {
+        let rules: ImportRules = load_json(path)
+            .with_context(|| format!("Unable to read import rules from {}", path.display()))?;
+        rules
+            .check()
+            .with_context(|| format!("Invalid import rules in {}", path.display()))?;
+        Ok(rules)
+    }
+
+    // The rules for the current project, if it has any.
+    pub fn load(state: &EditorState) -> Result<Self> {
+        match rules_path(state) {
+            Some(path) => Self::read(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    // Check the parts of the rules that don't depend on the ROM.
+    pub fn check(&self) -> Result<()> {
+        let mut names = BTreeSet::new();
+        for (map, name) in &self.rename_areas {
+            parse_map_id(map)?;
+            if name.trim().is_empty() {
+                bail!("Empty area name for map {}", map);
+            }
+            if !names.insert(name) {
+                bail!("More than one map is renamed to {}", name);
+            }
+        }
+        for map in &self.drop_maps {
+            parse_map_id(map)?;
+        }
+        for (row, target) in &self.merge_palettes {
+            if row == target {
+                bail!("Palette row {} is merged into itself", row);
+            }
+            if self.merge_palettes.contains_key(target) {
+                bail!(
+                    "Palette row {} is merged into {}, which is itself merged",
+                    row,
+                    target
+                );
+            }
+        }
+        Ok(())
+    }
+
+    // Check that the palette rows named in the rules exist in the ROM.
+    pub fn check_palette_rows(&self, slots: &[PaletteSlot]) -> Result<()> {
+        let names: BTreeSet<String> = slots.iter().map(|s| s.to_string()).collect();
+        for name in self.merge_palettes.iter().flat_map(|(a, b)| [a, b]) {
+            if !names.contains(name) {
+                bail!("Unknown palette row in import rules: {}", name);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn area_name(&self, map_id: u8) -> Option<&AreaName> {
+        self.rename_areas
+            .iter()
+            .find(|(map, _)| parse_map_id(map).ok() == Some(map_id))
+            .map(|(_, name)| name)
+    }
+
+    pub fn is_dropped(&self, map_id: u8) -> bool {
+        self.drop_maps
+            .iter()
+            .any(|map| parse_map_id(map).ok() == Some(map_id))
+    }
+
+    // The palette row that the given row is merged into, if any.
+    pub fn merge_target(&self, slot: &PaletteSlot, slots: &[PaletteSlot]) -> Option<PaletteSlot> {
+        let target = self.merge_palettes.get(&slot.to_string())?;
+        slots.iter().find(|s| &s.to_string() == target).copied()
+    }
+}
+
+// Location of the project's import rules file. It's stored relative to the project directory
+// when inside it, so that the setting can be shared along with the project.
+pub fn rules_path(state: &EditorState) -> Option<PathBuf> {
+    let path = state.project_metadata.import_rules.as_ref()?;
+    match &state.global_config.project_dir {
+        Some(project_dir) => Some(project_dir.join(path)),
+        None => Some(path.clone()),
+    }
+}
+
+pub fn set_rules_path(state: &mut EditorState, path: Option<&Path>) {
+    let path = path.map(|path| match &state.global_config.project_dir {
+        Some(project_dir) => path.strip_prefix(project_dir).unwrap_or(path).to_owned(),
+        None => path.to_owned(),
+    });
+    state.project_metadata.import_rules = path;
+    state.project_metadata.modified = true;
+}
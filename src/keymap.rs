@@ -0,0 +1,264 @@
+// Keyboard shortcuts: the actions that can be bound to keys, and the active keymap (the default
+// bindings, with any overrides from the global config). The Help dialogue and the printable cheat
+// sheet are generated from the active keymap, so they always show the current bindings.
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    palette_sheet::{RgbImage, CHAR_WIDTH, LINE_HEIGHT},
+    state::{GlobalConfig, ZOOM_PRESETS},
+};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KeyAction {
+    SelectTool,
+    BrushTool,
+    RectangleTool,
+    LineTool,
+    FillTool,
+    MagicWandTool,
+    CollisionTool,
+    ToggleCollision,
+    ToggleEntrances,
+    ToggleSecrets,
+    ToggleSpritePreview,
+    ToggleGrid,
+    ToggleRulers,
+    ToggleMinimap,
+    FlipHorizontal,
+    FlipVertical,
+    RotateBrush,
+    TilesetView,
+    AreaView,
+    MetatileView,
+    ToggleSidePanel,
+    WorldMap,
+    ZoomOut,
+    ZoomIn,
+    // Index into `ZOOM_PRESETS`:
+    ZoomPreset(usize),
+    UndoPreview,
+    Copy,
+    Paste,
+    RebuildProject,
+}
+
+impl KeyAction {
+    pub fn name(self) -> String {
+        match self {
+            KeyAction::SelectTool => "Select tool".to_string(),
+            KeyAction::BrushTool => "Brush tool".to_string(),
+            KeyAction::RectangleTool => "Rectangle tool".to_string(),
+            KeyAction::LineTool => "Line tool".to_string(),
+            KeyAction::FillTool => "Fill tool".to_string(),
+            KeyAction::MagicWandTool => "Magic wand tool".to_string(),
+            KeyAction::CollisionTool => "Collision tool".to_string(),
+            KeyAction::ToggleCollision => "Collision toggle".to_string(),
+            KeyAction::ToggleEntrances => "Entrances toggle".to_string(),
+            KeyAction::ToggleSecrets => "Secrets toggle".to_string(),
+            KeyAction::ToggleSpritePreview => "Sprite preview toggle".to_string(),
+            KeyAction::ToggleGrid => "Grid toggle".to_string(),
+            KeyAction::ToggleRulers => "Rulers toggle".to_string(),
+            KeyAction::ToggleMinimap => "Minimap toggle".to_string(),
+            KeyAction::FlipHorizontal => "Horizontal flip".to_string(),
+            KeyAction::FlipVertical => "Vertical flip".to_string(),
+            KeyAction::RotateBrush => "Rotation".to_string(),
+            KeyAction::TilesetView => "Tileset view".to_string(),
+            KeyAction::AreaView => "Area view".to_string(),
+            KeyAction::MetatileView => "Metatile view".to_string(),
+            KeyAction::ToggleSidePanel => "Side panel toggle".to_string(),
+            KeyAction::WorldMap => "World map".to_string(),
+            KeyAction::ZoomOut => "Zoom out".to_string(),
+            KeyAction::ZoomIn => "Zoom in".to_string(),
+            KeyAction::ZoomPreset(i) => {
+                format!("Zoom {}x", ZOOM_PRESETS.get(i).copied().unwrap_or(0.0))
+            }
+            KeyAction::UndoPreview => "Undo preview".to_string(),
+            KeyAction::Copy => "Copy".to_string(),
+            KeyAction::Paste => "Paste".to_string(),
+            KeyAction::RebuildProject => "Rebuild project".to_string(),
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            KeyAction::SelectTool => "copy tiles, colors, pixels",
+            KeyAction::BrushTool => "paste tiles, colors, pixels",
+            KeyAction::RectangleTool => "drag to fill a rectangle with the brush",
+            KeyAction::LineTool => "drag to brush along a straight line",
+            KeyAction::FillTool => "fill a region of matching tiles with the brush",
+            KeyAction::MagicWandTool => "select a region of matching tiles as a shaped brush",
+            KeyAction::CollisionTool => "paint collision types (right-click picks)",
+            KeyAction::ToggleCollision => "show/hide collision types of tiles",
+            KeyAction::ToggleEntrances => "show/hide entrance, hole, and exit markers",
+            KeyAction::ToggleSecrets => "show/hide markers of items hidden under objects",
+            KeyAction::ToggleSpritePreview => {
+                "show/hide a sample sprite at the cursor, behind tiles with priority"
+            }
+            KeyAction::ToggleGrid => "show/hide 16x16 tile grid",
+            KeyAction::ToggleRulers => "show/hide rulers (click them to place guides)",
+            KeyAction::ToggleMinimap => "show/hide minimap of large areas (click it to scroll)",
+            KeyAction::FlipHorizontal => "flip selection horizontally",
+            KeyAction::FlipVertical => "flip selection vertically",
+            KeyAction::RotateBrush => {
+                "rotate selection clockwise (using tiles with rotated graphics)"
+            }
+            KeyAction::TilesetView => "show palettes/tilesets in side panel",
+            KeyAction::AreaView => "show secondary area in side panel",
+            KeyAction::MetatileView => "show 16x16/32x32 metatiles in side panel",
+            KeyAction::ToggleSidePanel => "hide/show side panel",
+            KeyAction::WorldMap => "show/hide all areas of the theme",
+            KeyAction::ZoomOut => "zoom out area views",
+            KeyAction::ZoomIn => "zoom in area views",
+            KeyAction::ZoomPreset(_) => "jump to a preset zoom level",
+            KeyAction::UndoPreview => "hold to show the result of undoing",
+            KeyAction::Copy => "copy selected tiles to the clipboard",
+            KeyAction::Paste => "brush with tiles copied from any project",
+            KeyAction::RebuildProject => "rebuild the project from the ROM",
+        }
+    }
+}
+
+// A key (as the character it types) bound to an action. An empty key leaves the action unbound,
+// for removing a default binding in the config.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub key: String,
+    #[serde(default)]
+    pub ctrl: bool,
+    pub action: KeyAction,
+}
+
+impl KeyBinding {
+    fn new(key: &str, ctrl: bool, action: KeyAction) -> Self {
+        KeyBinding {
+            key: key.to_string(),
+            ctrl,
+            action,
+        }
+    }
+
+    // The key as shown in the Help dialogue and cheat sheet (e.g. "^C" for Ctrl+C).
+    pub fn label(&self) -> String {
+        if self.ctrl {
+            format!("^{}", self.key.to_uppercase())
+        } else {
+            self.key.clone()
+        }
+    }
+}
+
+pub fn default_keymap() -> Vec<KeyBinding> {
+    let mut keymap = vec![
+        KeyBinding::new("s", false, KeyAction::SelectTool),
+        KeyBinding::new("b", false, KeyAction::BrushTool),
+        KeyBinding::new("r", false, KeyAction::RectangleTool),
+        KeyBinding::new("l", false, KeyAction::LineTool),
+        KeyBinding::new("f", false, KeyAction::FillTool),
+        KeyBinding::new("q", false, KeyAction::MagicWandTool),
+        KeyBinding::new("c", false, KeyAction::CollisionTool),
+        KeyBinding::new("o", false, KeyAction::ToggleCollision),
+        KeyBinding::new("e", false, KeyAction::ToggleEntrances),
+        KeyBinding::new("i", false, KeyAction::ToggleSecrets),
+        KeyBinding::new("k", false, KeyAction::ToggleSpritePreview),
+        KeyBinding::new("g", false, KeyAction::ToggleGrid),
+        KeyBinding::new("u", false, KeyAction::ToggleRulers),
+        KeyBinding::new("n", false, KeyAction::ToggleMinimap),
+        KeyBinding::new("h", false, KeyAction::FlipHorizontal),
+        KeyBinding::new("v", false, KeyAction::FlipVertical),
+        KeyBinding::new("x", false, KeyAction::RotateBrush),
+        KeyBinding::new("t", false, KeyAction::TilesetView),
+        KeyBinding::new("a", false, KeyAction::AreaView),
+        KeyBinding::new("m", false, KeyAction::MetatileView),
+        KeyBinding::new("p", false, KeyAction::ToggleSidePanel),
+        KeyBinding::new("w", false, KeyAction::WorldMap),
+        KeyBinding::new("-", false, KeyAction::ZoomOut),
+        KeyBinding::new("=", false, KeyAction::ZoomIn),
+    ];
+    for i in 0..ZOOM_PRESETS.len() {
+        keymap.push(KeyBinding::new(
+            &(i + 1).to_string(),
+            false,
+            KeyAction::ZoomPreset(i),
+        ));
+    }
+    keymap.extend([
+        KeyBinding::new("z", false, KeyAction::UndoPreview),
+        KeyBinding::new("c", true, KeyAction::Copy),
+        KeyBinding::new("v", true, KeyAction::Paste),
+        KeyBinding::new("r", true, KeyAction::RebuildProject),
+    ]);
+    keymap
+}
+
+// The default keymap with the config's bindings applied. Configured bindings replace the default
+// bindings of their action, and take their keys from any other action bound to them.
+pub fn active_keymap(config: &GlobalConfig) -> Vec<KeyBinding> {
+    let configured = &config.key_bindings;
+    let is_configured = |action: KeyAction| configured.iter().any(|b| b.action == action);
+    let mut placed: HashSet<KeyAction> = HashSet::new();
+    let mut keymap = vec![];
+    for binding in default_keymap() {
+        if !is_configured(binding.action) {
+            let taken = configured
+                .iter()
+                .any(|b| b.key == binding.key && b.ctrl == binding.ctrl);
+            if !taken {
+                keymap.push(binding);
+            }
+        } else if placed.insert(binding.action) {
+            keymap.extend(
+                configured
+                    .iter()
+                    .filter(|b| b.action == binding.action && !b.key.is_empty())
+                    .cloned(),
+            );
+        }
+    }
+    keymap
+}
+
+// The action bound to the given key, if any.
+pub fn key_action(keymap: &[KeyBinding], key: &str, ctrl: bool) -> Option<KeyAction> {
+    keymap
+        .iter()
+        .find(|b| b.key == key && b.ctrl == ctrl)
+        .map(|b| b.action)
+}
+
+// Whether the key is bound to the action (e.g. for handling the release of a held key).
+pub fn is_bound(keymap: &[KeyBinding], key: &str, action: KeyAction) -> bool {
+    keymap.iter().any(|b| b.key == key && b.action == action)
+}
+
+const MARGIN: usize = 16;
+const KEY_COLUMN_WIDTH: usize = 6 * CHAR_WIDTH;
+const LINE_SPACING: usize = LINE_HEIGHT + 4;
+const SHEET_WIDTH: usize = 2 * MARGIN + KEY_COLUMN_WIDTH + 72 * CHAR_WIDTH;
+
+const BACKGROUND: [u8; 3] = [255, 255, 255];
+const TEXT_COLOR: [u8; 3] = [0, 0, 0];
+const KEY_COLOR: [u8; 3] = [0, 64, 160];
+
+// A printable sheet listing the bindings of the keymap, one per line.
+pub fn render_cheat_sheet(keymap: &[KeyBinding]) -> RgbImage {
+    let height = 2 * MARGIN + (keymap.len() + 2) * LINE_SPACING;
+    let mut image = RgbImage::new(SHEET_WIDTH, height, BACKGROUND);
+    image.draw_text(MARGIN, MARGIN, "Z3 Overworld Editor shortcuts", TEXT_COLOR);
+    for (i, binding) in keymap.iter().enumerate() {
+        let y = MARGIN + (i + 2) * LINE_SPACING;
+        image.draw_text(MARGIN, y, &binding.label(), KEY_COLOR);
+        image.draw_text(
+            MARGIN + KEY_COLUMN_WIDTH,
+            y,
+            &format!(
+                "{}: {}",
+                binding.action.name(),
+                binding.action.description()
+            ),
+            TEXT_COLOR,
+        );
+    }
+    image
+}
@@ -0,0 +1,139 @@
+// In-game area name labels: preview area names as they would be drawn with the game's
+// font, and generate a name table that can be included in the hack's ASM.
+//
+// The font is a run of consecutive tiles in one of the project's palettes (e.g. borrowed
+// from the ROM's graphics sheets), one per character of `charset`.
+use serde::{Deserialize, Serialize};
+
+use crate::state::{AreaName, PaletteId, TileIdx};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LabelFont {
+    pub palette_id: PaletteId,
+    pub start_tile: TileIdx,
+    pub charset: String,
+    // Characters are two tiles high, with the lower half in the next row of the tileset
+    // (as with the game's dialogue font):
+    #[serde(default)]
+    pub double_height: bool,
+    // Value of the first character of `charset` in the game's text encoding:
+    #[serde(default)]
+    pub first_code: u8,
+    // Maximum label width, in characters:
+    pub max_width: u8,
+}
+
+impl Default for LabelFont {
+    fn default() -> Self {
+        LabelFont {
+            palette_id: 0,
+            start_tile: 0,
+            charset: "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 -'.!?".to_string(),
+            double_height: false,
+            first_code: 0,
+            max_width: 16,
+        }
+    }
+}
+
+// A single setting of the label font, for editing.
+#[derive(Clone, Debug)]
+pub enum LabelFontField {
+    Palette(PaletteId),
+    StartTile(TileIdx),
+    Charset(String),
+    DoubleHeight(bool),
+    FirstCode(u8),
+    MaxWidth(u8),
+}
+
+// Value written after each name in the name table.
+pub const NAME_TERMINATOR: u8 = 0xFF;
+
+impl LabelFont {
+    pub fn set(&mut self, field: &LabelFontField) {
+        match field {
+            &LabelFontField::Palette(x) => self.palette_id = x,
+            &LabelFontField::StartTile(x) => self.start_tile = x,
+            LabelFontField::Charset(x) => self.charset = x.clone(),
+            &LabelFontField::DoubleHeight(x) => self.double_height = x,
+            &LabelFontField::FirstCode(x) => self.first_code = x,
+            &LabelFontField::MaxWidth(x) => self.max_width = x,
+        }
+    }
+
+    // Index of the character within the charset. Lowercase letters fall back to
+    // uppercase if the font doesn't have them.
+    pub fn char_index(&self, c: char) -> Option<usize> {
+        self.charset.chars().position(|x| x == c).or_else(|| {
+            self.charset
+                .chars()
+                .position(|x| x == c.to_ascii_uppercase())
+        })
+    }
+
+    // Tiles (top half) for each character of the label, or None if not in the font.
+    pub fn label_tiles(&self, label: &str) -> Vec<Option<TileIdx>> {
+        label
+            .chars()
+            .map(|c| {
+                self.char_index(c)
+                    .map(|i| self.start_tile.saturating_add(i as TileIdx))
+            })
+            .collect()
+    }
+
+    // Description of why the label can't be displayed as-is, if so.
+    pub fn label_problem(&self, label: &str) -> Option<String> {
+        let mut missing: Vec<char> = label
+            .chars()
+            .filter(|&c| self.char_index(c).is_none())
+            .collect();
+        missing.dedup();
+        let len = label.chars().count();
+        if !missing.is_empty() {
+            Some(format!(
+                "Characters not in font: {}",
+                missing.into_iter().collect::<String>()
+            ))
+        } else if len > self.max_width as usize {
+            Some(format!(
+                "Too long: {} characters (max {})",
+                len, self.max_width
+            ))
+        } else {
+            None
+        }
+    }
+
+    fn label_codes(&self, label: &str) -> Vec<u8> {
+        label
+            .chars()
+            .filter_map(|c| self.char_index(c))
+            .map(|i| self.first_code.wrapping_add(i as u8))
+            .collect()
+    }
+
+    // Assembly source (asar syntax) with a pointer table followed by the encoded name of
+    // each area, each terminated by NAME_TERMINATOR. Characters missing from the font are
+    // skipped.
+    pub fn name_table_asm(&self, area_names: &[AreaName]) -> String {
+        let mut out =
+            String::from("; Area name table generated by Z3 Overworld Editor. Do not edit.\n\n");
+        out += "AreaNameTable:\n";
+        for i in 0..area_names.len() {
+            out += &format!("    dw AreaName{}\n", i);
+        }
+        for (i, name) in area_names.iter().enumerate() {
+            let codes = self
+                .label_codes(name)
+                .into_iter()
+                .chain([NAME_TERMINATOR])
+                .map(|c| format!("${:02X}", c))
+                .collect::<Vec<_>>()
+                .join(", ");
+            out += &format!("\nAreaName{}: ; {}\n    db {}\n", i, name, codes);
+        }
+        out
+    }
+}
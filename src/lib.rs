@@ -1,8 +1,67 @@
+pub mod animated_tiles;
+pub mod annotations;
+pub mod area_diff;
+pub mod area_manifest;
+pub mod area_picker;
+pub mod area_properties;
+pub mod area_raster;
+pub mod area_shapes;
+pub mod auto_pan;
+pub mod brush_rotation;
+pub mod bug_report;
+pub mod camera_locks;
+pub mod clipboard;
+pub mod compile_check;
+pub mod compression;
+pub mod dark_world;
+pub mod dirty;
+pub mod entrances;
+pub mod export;
+pub mod external_changes;
+pub mod external_tools;
+pub mod flip_analysis;
+pub mod heatmap;
 pub mod helpers;
 pub mod import;
+pub mod import_rules;
+pub mod keymap;
+pub mod labels;
+pub mod library;
+pub mod macros;
+pub mod map_compression;
+pub mod memory_report;
 pub mod message;
+pub mod metatiles;
+pub mod overlays;
+pub mod palette_adjust;
+pub mod palette_groups;
+pub mod palette_replace;
+pub mod palette_sheet;
+pub mod palette_slots;
+pub mod panes;
 pub mod persist;
+pub mod project_load;
+pub mod project_rebuild;
+pub mod project_stats;
+pub mod ramps;
+pub mod renames;
+pub mod rom_import;
+pub mod scripting;
+pub mod secrets;
+pub mod sprite_preview;
+pub mod stamps;
 pub mod state;
+pub mod test_rom;
+pub mod theme_sync;
+pub mod tile_dedup;
+pub mod tile_graphics;
+pub mod tile_table;
+pub mod travel;
 pub mod undo;
 pub mod update;
+pub mod usages;
+pub mod validation;
 pub mod view;
+pub mod vram_usage;
+pub mod window_state;
+pub mod world_map;
@@ -0,0 +1,492 @@
+// Library of assets shared between projects: palettes (colors only), tilesets (a palette's
+// colors together with its tiles), and stamps (a block of tiles as used for brushing, together
+// with the graphics it needs). The library is a user-level folder set in the global config,
+// holding each item as a JSON file with a PNG thumbnail alongside it.
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use iced::widget::image::Handle;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helpers::scale_color,
+    message::Message,
+    persist::{load_json, save_json},
+    state::{ColorRGB, EditorState, Palette, PaletteCategory, PaletteId, Tile, TileBlock, TileIdx},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LibraryKind {
+    Palette,
+    Tileset,
+    Stamp,
+}
+
+impl std::fmt::Display for LibraryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LibraryKind::Palette => write!(f, "Palette"),
+            LibraryKind::Tileset => write!(f, "Tileset"),
+            LibraryKind::Stamp => write!(f, "Stamp"),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct StampPalette {
+    pub colors: [ColorRGB; 16],
+    pub tiles: Vec<Tile>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum LibraryAsset {
+    Palette {
+        colors: [ColorRGB; 16],
+    },
+    Tileset {
+        colors: [ColorRGB; 16],
+        tiles: Vec<Tile>,
+    },
+    // The block's palette IDs are indices into `palettes`, and its tile indices are indices
+    // into the tiles of the corresponding stamp palette:
+    Stamp {
+        palettes: Vec<StampPalette>,
+        block: TileBlock,
+    },
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct LibraryItem {
+    #[serde(skip_serializing, skip_deserializing)]
+    pub name: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // Project that the item was published from, for reference:
+    #[serde(default)]
+    pub source: String,
+    pub asset: LibraryAsset,
+    // Loaded along with the item (rather than from its path when drawn), so that a thumbnail
+    // replaced by publishing over an item is shown updated:
+    #[serde(skip_serializing, skip_deserializing)]
+    pub thumbnail: Option<Handle>,
+}
+
+impl LibraryItem {
+    pub fn kind(&self) -> LibraryKind {
+        match self.asset {
+            LibraryAsset::Palette { .. } => LibraryKind::Palette,
+            LibraryAsset::Tileset { .. } => LibraryKind::Tileset,
+            LibraryAsset::Stamp { .. } => LibraryKind::Stamp,
+        }
+    }
+
+    // Whether the item's name, kind, or one of its tags contains the filter text.
+    pub fn matches(&self, filter: &str) -> bool {
+        let filter = filter.trim().to_lowercase();
+        filter.is_empty()
+            || self.name.to_lowercase().contains(&filter)
+            || self.kind().to_string().to_lowercase().contains(&filter)
+            || self.tags.iter().any(|t| t.to_lowercase().contains(&filter))
+    }
+}
+
+// Tags are entered as a comma-separated list.
+pub fn parse_tags(s: &str) -> Vec<String> {
+    let mut tags: Vec<String> = vec![];
+    for tag in s.split(',').map(|t| t.trim()) {
+        if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+pub fn library_dir(state: &EditorState) -> Result<&Path> {
+    state
+        .global_config
+        .library_dir
+        .as_deref()
+        .context("No library folder is set up")
+}
+
+fn item_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.json", name))
+}
+
+pub fn thumbnail_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.png", name))
+}
+
+pub fn source_name(state: &EditorState) -> String {
+    state
+        .global_config
+        .project_dir
+        .as_ref()
+        .and_then(|p| p.file_name())
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+pub fn new_item(
+    state: &EditorState,
+    kind: LibraryKind,
+    name: &str,
+    tags: Vec<String>,
+) -> Result<LibraryItem> {
+    let palette = &state.palettes[state.palette_idx];
+    let asset = match kind {
+        LibraryKind::Palette => LibraryAsset::Palette {
+            colors: palette.colors,
+        },
+        LibraryKind::Tileset => LibraryAsset::Tileset {
+            colors: palette.colors,
+            tiles: palette.tiles.clone(),
+        },
+        LibraryKind::Stamp => stamp_asset(state)?,
+    };
+    Ok(LibraryItem {
+        name: name.trim().to_string(),
+        tags,
+        source: source_name(state),
+        asset,
+        thumbnail: None,
+    })
+}
+
+fn stamp_asset(state: &EditorState) -> Result<LibraryAsset> {
+    let s = &state.selected_tile_block;
+    if s.size.0 == 0 || s.size.1 == 0 {
+        bail!("Select some tiles to publish as a stamp");
+    }
+    let (palettes, block) = selection_stamp(state)?;
+    Ok(LibraryAsset::Stamp { palettes, block })
+}
+
+// Make a stamp from the current selection, keeping only the tiles that it uses.
+pub fn selection_stamp(state: &EditorState) -> Result<(Vec<StampPalette>, TileBlock)> {
+    let s = &state.selected_tile_block;
+    let mut palette_ids: Vec<PaletteId> = vec![];
+    let mut palettes: Vec<StampPalette> = vec![];
+    let mut block = s.clone();
+    for y in 0..s.size.1 as usize {
+        for x in 0..s.size.0 as usize {
+            let palette_id = s.palettes[y][x];
+            let &palette_idx = state
+                .palettes_id_idx_map
+                .get(&palette_id)
+                .context("palette not found")?;
+            let pal = &state.palettes[palette_idx];
+            let tile = *pal
+                .tiles
+                .get(s.tiles[y][x] as usize)
+                .context("tile not found")?;
+            let i = match palette_ids.iter().position(|&id| id == palette_id) {
+                Some(i) => i,
+                None => {
+                    palette_ids.push(palette_id);
+                    palettes.push(StampPalette {
+                        colors: pal.colors,
+                        tiles: vec![],
+                    });
+                    palettes.len() - 1
+                }
+            };
+            let tiles = &mut palettes[i].tiles;
+            let tile_idx = match tiles.iter().position(|t| *t == tile) {
+                Some(j) => j,
+                None => {
+                    tiles.push(tile);
+                    tiles.len() - 1
+                }
+            };
+            block.palettes[y][x] = i as PaletteId;
+            block.tiles[y][x] = tile_idx as TileIdx;
+        }
+    }
+    Ok((palettes, block))
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        bail!("Library item name is empty");
+    }
+    if name.contains(['/', '\\', ':']) || name.starts_with('.') {
+        bail!("Invalid library item name: {}", name);
+    }
+    Ok(())
+}
+
+pub fn publish(state: &EditorState, item: &LibraryItem) -> Result<()> {
+    validate_name(&item.name)?;
+    let dir = library_dir(state)?;
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Unable to create library folder {}", dir.display()))?;
+    save_json(&item_path(dir, &item.name), item)?;
+    save_thumbnail(&thumbnail_path(dir, &item.name), &item.asset)?;
+    info!("Published {} to library", item.name);
+    Ok(())
+}
+
+// All items in the library, sorted by name. Items that can't be read are skipped.
+pub fn load_library(state: &EditorState) -> Result<Vec<LibraryItem>> {
+    let dir = library_dir(state)?;
+    let mut items = vec![];
+    let pattern = format!(
+        "{}/*.json",
+        glob::Pattern::escape(&dir.display().to_string())
+    );
+    for entry in glob::glob(&pattern)? {
+        let path = entry?;
+        let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        match load_json::<LibraryItem>(&path) {
+            Ok(mut item) => {
+                item.name = name.to_string();
+                item.thumbnail = std::fs::read(thumbnail_path(dir, name))
+                    .ok()
+                    .map(Handle::from_bytes);
+                items.push(item);
+            }
+            Err(e) => warn!("Skipping library item {}: {:#}", path.display(), e),
+        }
+    }
+    items.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(items)
+}
+
+pub fn delete_item(state: &EditorState, name: &str) -> Result<()> {
+    validate_name(name)?;
+    let dir = library_dir(state)?;
+    std::fs::remove_file(item_path(dir, name))?;
+    let thumbnail = thumbnail_path(dir, name);
+    if thumbnail.exists() {
+        std::fs::remove_file(thumbnail)?;
+    }
+    info!("Deleted {} from library", name);
+    Ok(())
+}
+
+fn unused_palette_id(state: &EditorState, reserved: &[PaletteId]) -> PaletteId {
+    (0..=PaletteId::MAX)
+        .find(|id| !state.palettes_id_idx_map.contains_key(id) && !reserved.contains(id))
+        .unwrap_or(PaletteId::MAX)
+}
+
+fn unused_palette_name(state: &EditorState, name: &str, reserved: &[String]) -> String {
+    let taken =
+        |n: &str| state.palettes.iter().any(|p| p.name == n) || reserved.iter().any(|r| r == n);
+    if !taken(name) {
+        return name.to_string();
+    }
+    (2..)
+        .map(|i| format!("{} {}", name, i))
+        .find(|n| !taken(n))
+        .unwrap()
+}
+
+fn full_rows(mut tiles: Vec<Tile>) -> Vec<Tile> {
+    let size = (tiles.len().div_ceil(16) * 16).max(16);
+    tiles.resize(size, Tile::default());
+    tiles
+}
+
+// The (undoable) edits that bring the item into the project:
+// - a palette replaces the colors of the current palette,
+// - a tileset is added as a new palette,
+// - a stamp is added as described for `stamp_messages`.
+pub fn import_messages(state: &EditorState, item: &LibraryItem) -> Result<Vec<Message>> {
+    match &item.asset {
+        LibraryAsset::Palette { colors } => {
+            let mut pal = state.palettes[state.palette_idx].clone();
+            pal.colors = *colors;
+            Ok(vec![Message::ReplacePalette(pal)])
+        }
+        LibraryAsset::Tileset { colors, tiles } => Ok(vec![Message::RestorePalette(Palette {
+            modified: true,
+            name: unused_palette_name(state, &item.name, &[]),
+            id: unused_palette_id(state, &[]),
+            category: PaletteCategory::Custom,
+            group: None,
+            colors: *colors,
+            tiles: full_rows(tiles.clone()),
+        })]),
+        LibraryAsset::Stamp { palettes, block } => {
+            stamp_messages(state, &item.name, palettes, block)
+        }
+    }
+}
+
+// The edits that bring a stamp into the project: its tiles are added to the palettes with
+// matching colors (or new palettes named after `name`, if there are none), after which the stamp
+// becomes the brush.
+pub fn stamp_messages(
+    state: &EditorState,
+    name: &str,
+    palettes: &[StampPalette],
+    block: &TileBlock,
+) -> Result<Vec<Message>> {
+    let mut messages = vec![];
+    let mut new_ids: Vec<PaletteId> = vec![];
+    let mut new_names: Vec<String> = vec![];
+    let mut block = block.clone();
+    // Project palette ID and tile indices for each stamp palette:
+    let mut tile_maps: Vec<(PaletteId, Vec<TileIdx>)> = vec![];
+    for stamp_pal in palettes {
+        let existing = state.palettes.iter().find(|p| p.colors == stamp_pal.colors);
+        let mut pal = match existing {
+            Some(p) => p.clone(),
+            None => {
+                let name = unused_palette_name(state, name, &new_names);
+                let id = unused_palette_id(state, &new_ids);
+                new_names.push(name.clone());
+                new_ids.push(id);
+                Palette {
+                    modified: true,
+                    name,
+                    id,
+                    category: PaletteCategory::Custom,
+                    group: None,
+                    colors: stamp_pal.colors,
+                    tiles: vec![],
+                }
+            }
+        };
+        let mut tile_map = vec![];
+        for tile in &stamp_pal.tiles {
+            let idx = match pal.tiles.iter().position(|t| t == tile) {
+                Some(i) => i,
+                None => {
+                    pal.tiles.push(*tile);
+                    pal.tiles.len() - 1
+                }
+            };
+            tile_map.push(idx as TileIdx);
+        }
+        tile_maps.push((pal.id, tile_map));
+        match existing {
+            Some(p) => {
+                if p.tiles != pal.tiles {
+                    pal.tiles = full_rows(pal.tiles);
+                    messages.push(Message::ReplacePalette(pal));
+                }
+            }
+            None => {
+                pal.tiles = full_rows(pal.tiles);
+                messages.push(Message::RestorePalette(pal));
+            }
+        }
+    }
+    for y in 0..block.size.1 as usize {
+        for x in 0..block.size.0 as usize {
+            let (id, tile_map) = tile_maps
+                .get(block.palettes[y][x] as usize)
+                .context("invalid stamp palette")?;
+            block.palettes[y][x] = *id;
+            block.tiles[y][x] = *tile_map
+                .get(block.tiles[y][x] as usize)
+                .context("invalid stamp tile")?;
+        }
+    }
+    messages.push(Message::SetBrush(block));
+    Ok(messages)
+}
+
+// Thumbnails are drawn at one pixel per pixel, and scaled up when shown in the library.
+fn render_thumbnail(asset: &LibraryAsset) -> (usize, usize, Vec<u8>) {
+    let to_bytes = |colors: &[ColorRGB; 16]| -> Vec<[u8; 3]> {
+        colors
+            .iter()
+            .map(|&[r, g, b]| [scale_color(r), scale_color(g), scale_color(b)])
+            .collect()
+    };
+    match asset {
+        LibraryAsset::Palette { colors } => {
+            // Two rows of eight 8x8 swatches:
+            let color_bytes = to_bytes(colors);
+            let (width, height) = (64, 16);
+            let mut data = Vec::with_capacity(width * height * 3);
+            for y in 0..height {
+                for x in 0..width {
+                    data.extend(color_bytes[y / 8 * 8 + x / 8]);
+                }
+            }
+            (width, height, data)
+        }
+        LibraryAsset::Tileset { colors, tiles } => {
+            let color_bytes = to_bytes(colors);
+            let num_rows = tiles.len().div_ceil(16);
+            let (width, height) = (16 * 8, num_rows.max(1) * 8);
+            let mut data = vec![0; width * height * 3];
+            for (i, tile) in tiles.iter().enumerate() {
+                draw_tile(
+                    &mut data,
+                    width,
+                    (i % 16) * 8,
+                    (i / 16) * 8,
+                    tile,
+                    &color_bytes,
+                );
+            }
+            (width, height, data)
+        }
+        LibraryAsset::Stamp { palettes, block } => {
+            let color_bytes: Vec<Vec<[u8; 3]>> =
+                palettes.iter().map(|p| to_bytes(&p.colors)).collect();
+            let (width, height) = (block.size.0 as usize * 8, block.size.1 as usize * 8);
+            let mut data = vec![0; width * height * 3];
+            for y in 0..block.size.1 as usize {
+                for x in 0..block.size.0 as usize {
+                    if !block.covers(x, y) {
+                        continue;
+                    }
+                    let pal_idx = block.palettes[y][x] as usize;
+                    let Some(tile) = palettes
+                        .get(pal_idx)
+                        .and_then(|p| p.tiles.get(block.tiles[y][x] as usize))
+                    else {
+                        continue;
+                    };
+                    let tile = block.flips[y][x].apply_to_tile(*tile);
+                    draw_tile(&mut data, width, x * 8, y * 8, &tile, &color_bytes[pal_idx]);
+                }
+            }
+            (width, height, data)
+        }
+    }
+}
+
+fn draw_tile(
+    data: &mut [u8],
+    width: usize,
+    x0: usize,
+    y0: usize,
+    tile: &Tile,
+    color_bytes: &[[u8; 3]],
+) {
+    for py in 0..8 {
+        for px in 0..8 {
+            let addr = ((y0 + py) * width + x0 + px) * 3;
+            let color = color_bytes[tile.pixels[py][px] as usize];
+            data[addr..addr + 3].copy_from_slice(&color);
+        }
+    }
+}
+
+pub fn save_thumbnail(path: &Path, asset: &LibraryAsset) -> Result<()> {
+    let (width, height, data) = render_thumbnail(asset);
+    let file =
+        File::create(path).with_context(|| format!("Unable to create {}", path.display()))?;
+    let w = BufWriter::new(file);
+    let mut encoder = png::Encoder::new(w, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&data)?;
+    Ok(())
+}
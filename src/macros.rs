@@ -0,0 +1,210 @@
+// Edit macros: a recorded sequence of edits (brush stamps and tile property changes) that
+// can be replayed elsewhere, e.g. to repeat the same structural change in several areas.
+//
+// Brush stamps are stored relative to the top-left-most stamp of the recording, and are
+// replayed relative to a chosen location in the target area.
+use iced::Point;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    message::Message,
+    state::{
+        AreaId, AreaPosition, CollisionType, PaletteId, TileBlock, TileCoord, TileIdx,
+        TileProperties,
+    },
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum MacroStep {
+    Brush {
+        offset: (TileCoord, TileCoord),
+        selection: TileBlock,
+        palette_only: bool,
+    },
+    TilePriority {
+        palette_id: PaletteId,
+        tile_idx: TileIdx,
+        priority: bool,
+    },
+    TileCollision {
+        palette_id: PaletteId,
+        tile_idx: TileIdx,
+        collision: CollisionType,
+    },
+    TileHFlippable {
+        palette_id: PaletteId,
+        tile_idx: TileIdx,
+        h_flippable: bool,
+    },
+    TileVFlippable {
+        palette_id: PaletteId,
+        tile_idx: TileIdx,
+        v_flippable: bool,
+    },
+    TileProperties {
+        palette_id: PaletteId,
+        properties: Vec<(TileIdx, TileProperties)>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EditMacro {
+    pub name: String,
+    pub steps: Vec<MacroStep>,
+}
+
+// Whether the message is an edit that can be included in a macro.
+pub fn is_recordable(message: &Message) -> bool {
+    matches!(
+        message,
+        Message::AreaBrush { .. }
+            | Message::SetTilePriority { .. }
+            | Message::SetTileCollision { .. }
+            | Message::SetTileHFlippable { .. }
+            | Message::SetTileVFlippable { .. }
+            | Message::SetTileProperties { .. }
+    )
+}
+
+impl EditMacro {
+    // Build a macro from recorded messages (as accepted by `is_recordable`).
+    pub fn from_messages(name: String, messages: &[Message]) -> Self {
+        let (min_x, min_y) = messages
+            .iter()
+            .filter_map(|m| match m {
+                Message::AreaBrush { coords, .. } => Some((coords.x, coords.y)),
+                _ => None,
+            })
+            .fold((TileCoord::MAX, TileCoord::MAX), |(x0, y0), (x, y)| {
+                (x0.min(x), y0.min(y))
+            });
+        let steps = messages
+            .iter()
+            .filter_map(|m| match m.clone() {
+                Message::AreaBrush {
+                    coords,
+                    selection,
+                    palette_only,
+                    ..
+                } => Some(MacroStep::Brush {
+                    offset: (coords.x - min_x, coords.y - min_y),
+                    selection,
+                    palette_only,
+                }),
+                Message::SetTilePriority {
+                    palette_id,
+                    tile_idx,
+                    priority,
+                } => Some(MacroStep::TilePriority {
+                    palette_id,
+                    tile_idx,
+                    priority,
+                }),
+                Message::SetTileCollision {
+                    palette_id,
+                    tile_idx,
+                    collision,
+                } => Some(MacroStep::TileCollision {
+                    palette_id,
+                    tile_idx,
+                    collision,
+                }),
+                Message::SetTileHFlippable {
+                    palette_id,
+                    tile_idx,
+                    h_flippable,
+                } => Some(MacroStep::TileHFlippable {
+                    palette_id,
+                    tile_idx,
+                    h_flippable,
+                }),
+                Message::SetTileVFlippable {
+                    palette_id,
+                    tile_idx,
+                    v_flippable,
+                } => Some(MacroStep::TileVFlippable {
+                    palette_id,
+                    tile_idx,
+                    v_flippable,
+                }),
+                Message::SetTileProperties {
+                    palette_id,
+                    properties,
+                } => Some(MacroStep::TileProperties {
+                    palette_id,
+                    properties,
+                }),
+                _ => None,
+            })
+            .collect();
+        EditMacro { name, steps }
+    }
+
+    // Messages to replay the macro in the given area, with brush stamps relative to `coords`.
+    pub fn messages(
+        &self,
+        position: AreaPosition,
+        area_id: &AreaId,
+        coords: Point<TileCoord>,
+    ) -> Vec<Message> {
+        self.steps
+            .iter()
+            .map(|step| match step.clone() {
+                MacroStep::Brush {
+                    offset,
+                    selection,
+                    palette_only,
+                } => Message::AreaBrush {
+                    position,
+                    area_id: area_id.clone(),
+                    coords: Point::new(coords.x + offset.0, coords.y + offset.1),
+                    selection,
+                    palette_only,
+                },
+                MacroStep::TilePriority {
+                    palette_id,
+                    tile_idx,
+                    priority,
+                } => Message::SetTilePriority {
+                    palette_id,
+                    tile_idx,
+                    priority,
+                },
+                MacroStep::TileCollision {
+                    palette_id,
+                    tile_idx,
+                    collision,
+                } => Message::SetTileCollision {
+                    palette_id,
+                    tile_idx,
+                    collision,
+                },
+                MacroStep::TileHFlippable {
+                    palette_id,
+                    tile_idx,
+                    h_flippable,
+                } => Message::SetTileHFlippable {
+                    palette_id,
+                    tile_idx,
+                    h_flippable,
+                },
+                MacroStep::TileVFlippable {
+                    palette_id,
+                    tile_idx,
+                    v_flippable,
+                } => Message::SetTileVFlippable {
+                    palette_id,
+                    tile_idx,
+                    v_flippable,
+                },
+                MacroStep::TileProperties {
+                    palette_id,
+                    properties,
+                } => Message::SetTileProperties {
+                    palette_id,
+                    properties,
+                },
+            })
+            .collect()
+    }
+}
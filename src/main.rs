@@ -1,9 +1,11 @@
 use std::time::Duration;
 
-use z3_overworld_editor::{message, state, update, view};
+use z3_overworld_editor::{
+    auto_pan::AUTO_PAN_INTERVAL, message, project_load, state, update, view, window_state,
+};
 
 use anyhow::Result;
-use iced::{Size, Subscription, Task, Theme};
+use iced::{window, Subscription, Task, Theme};
 use message::Message;
 use state::EditorState;
 
@@ -14,12 +16,19 @@ fn theme(_state: &EditorState) -> Theme {
     }
 }
 
-fn subscription(_state: &EditorState) -> Subscription<Message> {
-    Subscription::batch(vec![
+fn subscription(state: &EditorState) -> Subscription<Message> {
+    let mut subscriptions = vec![
         iced::window::close_requests().map(Message::WindowClose),
-        iced::time::every(Duration::from_secs(1)).map(|_| Message::SaveProject),
+        iced::time::every(Duration::from_secs(30)).map(|_| Message::CheckWatcher),
         iced::event::listen().map(Message::Event),
-    ])
+    ];
+    if !state.global_config.manual_save {
+        subscriptions.push(iced::time::every(Duration::from_secs(1)).map(|_| Message::SaveProject));
+    }
+    if state.auto_pan.is_some() {
+        subscriptions.push(iced::time::every(AUTO_PAN_INTERVAL).map(|_| Message::AutoPanTick));
+    }
+    Subscription::batch(subscriptions)
 }
 
 pub fn main() -> Result<()> {
@@ -28,21 +37,26 @@ pub fn main() -> Result<()> {
     )
     .format_timestamp_millis()
     .init();
-    let editor_state = state::get_initial_state()?;
-    let initial_task = match &editor_state.global_config.project_dir {
+    let mut editor_state = state::get_startup_state()?;
+    // The project is set once it has loaded:
+    let mut initial_task = match editor_state.global_config.project_dir.take() {
         None => Task::perform(view::open_project(), Message::ProjectOpened),
-        Some(_) => Task::none(),
+        Some(dir) => project_load::start(&mut editor_state, dir),
     };
+    initial_task = Task::batch([initial_task, update::scale_factor_task()]);
+    let saved_window = editor_state.global_config.window;
+    if saved_window.is_some_and(|w| w.maximized) {
+        initial_task = Task::batch([
+            initial_task,
+            window::get_latest().and_then(|id| window::maximize(id, true)),
+        ]);
+    }
     iced::application("Z3 Overworld Editor", update::update, view::view)
         .font(iced_fonts::REQUIRED_FONT_BYTES)
         .font(iced_fonts::BOOTSTRAP_FONT_BYTES)
         .theme(theme)
-        .exit_on_close_request(false)
         .subscription(subscription)
-        .window_size(Size {
-            width: 1440.0,
-            height: 960.0,
-        })
+        .window(window_state::initial_settings(saved_window))
         .run_with(|| (editor_state, initial_task))?;
     Ok(())
 }
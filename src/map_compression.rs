@@ -0,0 +1,113 @@
+// Estimate of how much ROM space each area's map data would take once compressed.
+//
+// In the game, each 512x512 screen of the overworld is stored as a 16x16 grid of tile32
+// indices, with the low bytes and high bytes of the indices compressed separately. Areas
+// with little repetition compress poorly, and may not fit in the space available even if
+// they are within the tile16/tile32 budgets.
+use anyhow::Result;
+use hashbrown::HashMap;
+use itertools::Itertools;
+
+use crate::{
+    compile_check::TileRef,
+    compression::compress,
+    persist::load_area,
+    state::{Area, AreaId, EditorState, ThemeName},
+};
+
+// Space for the compressed map data of all screens, approximately as in the vanilla ROM.
+pub const MAP_DATA_SPACE: usize = 0x10000;
+// Number of screens sharing the space (Light World, Dark World, and special areas).
+pub const VANILLA_SCREEN_COUNT: usize = 160;
+// Tile32 grid size of one screen.
+const SCREEN_TILE32S: u16 = 16;
+
+type Tile32 = [TileRef; 16];
+
+#[derive(Clone, Debug)]
+pub struct AreaCompression {
+    pub area_name: String,
+    pub screen_count: usize,
+    pub compressed_size: usize,
+}
+
+impl AreaCompression {
+    // The area's share of the space, in proportion to its number of screens.
+    pub fn budget(&self) -> usize {
+        self.screen_count * MAP_DATA_SPACE / VANILLA_SCREEN_COUNT
+    }
+
+    pub fn over_budget(&self) -> bool {
+        self.compressed_size > self.budget()
+    }
+}
+
+fn get_tile32(area: &Area, x32: u16, y32: u16) -> Tile32 {
+    let mut out: Tile32 = Default::default();
+    for (i, (dy, dx)) in (0..4).cartesian_product(0..4).enumerate() {
+        let x = x32 * 4 + dx;
+        let y = y32 * 4 + dy;
+        out[i] = (
+            area.get_palette(x, y).unwrap_or_default(),
+            area.get_tile(x, y).unwrap_or_default(),
+            area.get_flip(x, y).unwrap_or_default(),
+        );
+    }
+    out
+}
+
+// Compressed size of the area's map data. Tile32s are numbered in order of first use, in
+// `tile32_indices`, which is shared between areas so that numbering continues across them
+// as it would in an export.
+pub fn estimate_area(area: &Area, tile32_indices: &mut HashMap<Tile32, u16>) -> AreaCompression {
+    let width32 = area.size.0 as u16 * 8;
+    let height32 = area.size.1 as u16 * 8;
+    let screens_x = width32.div_ceil(SCREEN_TILE32S);
+    let screens_y = height32.div_ceil(SCREEN_TILE32S);
+    let mut compressed_size = 0;
+    for (sy, sx) in (0..screens_y).cartesian_product(0..screens_x) {
+        let mut low = vec![];
+        let mut high = vec![];
+        for (y, x) in (0..SCREEN_TILE32S).cartesian_product(0..SCREEN_TILE32S) {
+            let x32 = sx * SCREEN_TILE32S + x;
+            let y32 = sy * SCREEN_TILE32S + y;
+            let idx = if x32 < width32 && y32 < height32 {
+                let tile32 = get_tile32(area, x32, y32);
+                let next_idx = tile32_indices.len() as u16;
+                *tile32_indices.entry(tile32).or_insert(next_idx)
+            } else {
+                0
+            };
+            low.push((idx & 0xFF) as u8);
+            high.push((idx >> 8) as u8);
+        }
+        compressed_size += compress(&low).len() + compress(&high).len();
+    }
+    AreaCompression {
+        area_name: area.name.clone(),
+        screen_count: (screens_x * screens_y) as usize,
+        compressed_size,
+    }
+}
+
+// Estimate for every area of the theme, including unsaved edits to loaded areas.
+pub fn estimate_theme(state: &EditorState, theme: &ThemeName) -> Result<Vec<AreaCompression>> {
+    let mut tile32_indices = HashMap::new();
+    let mut out = vec![];
+    for area_name in &state.area_names {
+        let area_id = AreaId {
+            area: area_name.clone(),
+            theme: theme.clone(),
+        };
+        let loaded;
+        let area = match state.areas.get(&area_id) {
+            Some(area) => area,
+            None => {
+                loaded = load_area(state, &area_id)?;
+                &loaded
+            }
+        };
+        out.push(estimate_area(area, &mut tile32_indices));
+    }
+    Ok(out)
+}
@@ -0,0 +1,149 @@
+// Summary of what the editor is holding in memory (loaded areas, undo history, cached images),
+// compared with the size of the project on disk, for keeping long editing sessions in check on
+// machines with little RAM. Sizes are estimates from the data's layout, not measured allocations.
+use std::{mem::size_of, path::Path};
+
+use crate::{
+    area_raster::{cached_screen_count, clear_screen_raster_caches, RASTER_SIZE},
+    message::Message,
+    state::{
+        Area, EditorState, Flip, Palette, PaletteId, Screen, SidePanelView, Tile, TileBlock,
+        TileIdx, TileUsage,
+    },
+};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    pub loaded_areas: usize,
+    // Loaded areas other than the main and side areas (e.g. left over from batch operations):
+    pub unused_areas: usize,
+    pub area_bytes: u64,
+    pub palette_bytes: u64,
+    pub undo_entries: usize,
+    pub redo_entries: usize,
+    pub undo_bytes: u64,
+    pub cached_screens: usize,
+    pub cached_image_bytes: u64,
+    pub project_bytes: u64,
+}
+
+impl MemoryReport {
+    pub fn total_bytes(&self) -> u64 {
+        self.area_bytes + self.palette_bytes + self.undo_bytes + self.cached_image_bytes
+    }
+}
+
+fn area_bytes(area: &Area) -> u64 {
+    (size_of::<Area>() + area.screens.capacity() * size_of::<Screen>()) as u64
+}
+
+fn palette_bytes(palette: &Palette) -> u64 {
+    (size_of::<Palette>() + palette.tiles.capacity() * size_of::<Tile>()) as u64
+}
+
+fn block_bytes(block: &TileBlock) -> u64 {
+    let cell = size_of::<PaletteId>() + size_of::<TileIdx>() + size_of::<Flip>();
+    (block.size.0 as usize * block.size.1 as usize * cell) as u64
+}
+
+// Estimated size of a message kept in the undo history, counting the data that the messages
+// restoring larger changes carry.
+pub fn message_bytes(message: &Message) -> u64 {
+    let data = match message {
+        Message::Batch(messages) => messages.iter().map(message_bytes).sum(),
+        Message::RestoreArea { areas, .. } | Message::RestoreTheme { areas, .. } => {
+            areas.iter().map(area_bytes).sum()
+        }
+        Message::RestoreSnapshot(snapshot) => {
+            snapshot.areas.iter().map(area_bytes).sum::<u64>()
+                + snapshot.palettes.iter().map(palette_bytes).sum::<u64>()
+        }
+        Message::ImportTiles { tiles, .. } | Message::RestoreTileRow(_, tiles) => {
+            (tiles.len() * size_of::<Tile>()) as u64
+        }
+        Message::TilesetBrush { selected_gfx, .. } => {
+            (selected_gfx.iter().flatten().count() * size_of::<Tile>()) as u64
+        }
+        Message::SetTileUsages { usages, .. } => (usages.len() * size_of::<TileUsage>()) as u64,
+        Message::AreaBrush { selection, .. } | Message::AreaShape { selection, .. } => {
+            block_bytes(selection)
+        }
+        _ => 0,
+    };
+    size_of::<Message>() as u64 + data
+}
+
+// Total size of the project's files, leaving out hidden ones (e.g. a Git repository).
+pub fn dir_bytes(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    let mut total = 0;
+    for entry in entries.flatten() {
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if metadata.is_dir() {
+            total += dir_bytes(&entry.path());
+        } else {
+            total += metadata.len();
+        }
+    }
+    total
+}
+
+// The report is cheap enough to make on every redraw, except for the size of the project on disk,
+// which is measured once when the report is opened.
+pub fn memory_report(state: &EditorState, project_bytes: u64) -> MemoryReport {
+    let cached_screens = cached_screen_count();
+    MemoryReport {
+        loaded_areas: state.areas.len(),
+        unused_areas: state
+            .areas
+            .keys()
+            .filter(|&id| *id != state.main_area_id && *id != state.side_area_id)
+            .count(),
+        area_bytes: state.areas.values().map(area_bytes).sum(),
+        palette_bytes: state.palettes.iter().map(palette_bytes).sum(),
+        undo_entries: state.undo_stack.len(),
+        redo_entries: state.redo_stack.len(),
+        undo_bytes: state
+            .undo_stack
+            .iter()
+            .chain(&state.redo_stack)
+            .map(|(m, r)| message_bytes(m) + message_bytes(r))
+            .sum(),
+        cached_screens,
+        cached_image_bytes: (cached_screens * RASTER_SIZE * RASTER_SIZE * 4) as u64,
+        project_bytes,
+    }
+}
+
+// Images that are rebuilt when they're needed again: the area views' screen images, and the
+// stamp previews (while the Stamps panel isn't showing them).
+pub fn clear_caches(state: &mut EditorState) {
+    clear_screen_raster_caches();
+    if !matches!(state.side_panel_view, SidePanelView::Stamps) {
+        state.stamps.clear();
+    }
+}
+
+// Release the memory that collections hold beyond their contents, e.g. after large edits were
+// undone or areas unloaded.
+pub fn compact_memory(state: &mut EditorState) {
+    state.areas.shrink_to_fit();
+    for area in state.areas.values_mut() {
+        area.screens.shrink_to_fit();
+    }
+    for palette in &mut state.palettes {
+        palette.tiles.shrink_to_fit();
+    }
+    state.undo_stack.shrink_to_fit();
+    state.redo_stack.shrink_to_fit();
+    state.tool_console.shrink_to_fit();
+    state.update_timings.shrink_to_fit();
+    state.stamps.shrink_to_fit();
+}
@@ -1,10 +1,41 @@
-use std::path::PathBuf;
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
 
-use iced::Point;
+use iced::{
+    widget::{pane_grid, text_editor},
+    Point, Vector,
+};
 
-use crate::state::{
-    AreaId, AreaPosition, CollisionType, ColorIdx, ColorRGB, ColorValue, Focus, Palette, PaletteId,
-    PaletteIdx, PixelCoord, Tile, TileBlock, TileCoord, TileIdx,
+use crate::{
+    area_diff::DiffBase,
+    area_manifest::AreaManifestEntry,
+    area_properties::{AreaProperties, AreaPropertyEdit},
+    area_shapes::{AreaCell, AreaShape},
+    auto_pan::AutoPan,
+    external_changes::ReloadChoice,
+    external_tools::ExternalTool,
+    import::{ImportMode, RomData},
+    labels::{LabelFont, LabelFontField},
+    library::LibraryKind,
+    macros::EditMacro,
+    metatiles::{Metatile, MetatileSize},
+    overlays::OverlayCell,
+    palette_adjust::{PaletteAdjustChange, PaletteColors},
+    palette_slots::{PaletteAssignment, RowPosition},
+    persist::{ProjectFiles, RebuildScope, RestoreOption},
+    project_stats::StatsFormat,
+    ramps::ColorRamp,
+    secrets::Secret,
+    state::{
+        Area, AreaId, AreaName, AreaPosition, CollisionType, ColorIdx, ColorRGB, ColorValue, Flip,
+        Focus, Guide, Layer, Palette, PaletteCategory, PaletteId, PaletteIdx, PickListMenu,
+        PixelCoord, ProjectSnapshot, ThemeName, Tile, TileBlock, TileCoord, TileIdx,
+        TileProperties, TilePropertyChange, TileUsage, UnsavedChangesAction,
+    },
+    tile_graphics::GraphicsFormat,
+    tile_table::TileTableFormat,
+    travel::TravelPoint,
+    validation::{IssueLocation, ProjectIssue},
+    world_map::WorldPosition,
 };
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -16,25 +47,146 @@ pub enum SelectionSource {
 #[derive(Debug, Clone)]
 pub enum Message {
     Nothing,
+    // Several changes applied (and undone) together:
+    Batch(Vec<Message>),
     Event(iced::Event),
+    Undo,
+    Redo,
+    // Start (true) or end (false) previewing the result of undoing the last change:
+    PreviewUndo(bool),
     Focus(Focus),
     WindowClose(iced::window::Id),
+    SetWindowGeometry {
+        position: Option<iced::Point>,
+        size: Option<iced::Size>,
+        maximized: bool,
+    },
     SaveProject,
+    CheckWatcher,
     OpenProject,
     ModifiedReload,
+    SetReloadChoice(usize, ReloadChoice),
+    ToggleReloadDiff(usize),
+    ApplyExternalChanges,
+    ReloadAreaFromDisk,
+    DiscardChangesDialogue,
+    DiscardChanges,
+    SaveAndContinue(UnsavedChangesAction),
+    DiscardAndContinue(UnsavedChangesAction),
+    RestoreFile {
+        path: PathBuf,
+        option: RestoreOption,
+    },
+    SkipFile(PathBuf),
+    OpenFileLocation(PathBuf),
     RebuildProjectDialogue,
-    RebuildProject,
+    SetRebuildScope(RebuildScope),
+    StartRebuildProject,
+    RebuildProjectProgress(usize),
+    RebuildProjectFinished(Result<usize, String>),
+    CancelRebuildProject,
     ProjectOpened(Option<PathBuf>),
+    ProjectLoadProgress {
+        dir: PathBuf,
+        palettes: usize,
+        areas: usize,
+    },
+    ProjectLoaded {
+        dir: PathBuf,
+        files: Result<Box<ProjectFiles>, String>,
+    },
+    CancelProjectLoad,
     SettingsDialogue,
+    MemoryDialogue,
+    ClearCaches,
+    CompactMemory,
+    UnloadUnusedAreas,
+    ExportStatistics(StatsFormat),
+    ScriptDialogue,
+    EditScript(text_editor::Action),
+    RunScript,
     HelpDialogue,
+    ExportCheatSheet,
+    ExportCheatSheetTo(Option<PathBuf>),
+    StartTutorial,
+    SetTutorialStep(usize),
+    CloseTutorial,
+    ToggleSidePanel,
+    SetThreePaneLayout(bool),
+    PaneResized(pane_grid::ResizeEvent),
     SetPixelSize(f32),
+    SetPerDisplayZoom(bool),
+    SetRecordSession(bool),
+    SetManualSave(bool),
+    SetRecordRenames(bool),
+    RenameHistoryDialogue,
+    SaveBugReport,
+    SaveBugReportTo(Option<PathBuf>),
+    SetScaleFactor(f32),
     SetGridAlpha(f32),
+    SetOverlayOpacity(f32),
     CloseDialogue,
-    ImportDialogue,
+    ContextMenu(PickListMenu),
+    ImportDialogue(ImportMode),
+    ImportRomFolderDialogue,
     ImportConfirm(Option<PathBuf>),
+    SetImportMode(ImportMode),
+    SetImportMaps(String),
+    PreviewImport,
     ImportROMProgress,
     ImportROM,
+    ImportROMRead(Result<Arc<RomData>, String>),
+    ImportROMStep,
+    CancelImportROM,
+    SelectImportRules,
+    ImportRulesSelected(Option<PathBuf>),
+    ClearImportRules,
+    ExportDialogue,
+    ExportBaseROMSelected(Option<PathBuf>),
+    ExportROMTo(Option<PathBuf>),
+    ExportROMProgress,
+    ExportROM,
+    PaletteSlotsDialogue,
+    SelectPaletteSlotsArea(AreaName),
+    SolvePaletteSlots(ThemeName),
+    SetPaletteAssignment {
+        theme: ThemeName,
+        assignment: PaletteAssignment,
+    },
+    SetPaletteSlot {
+        theme: ThemeName,
+        area: AreaName,
+        palette_id: PaletteId,
+        // None to leave the palette where the base ROM has it:
+        position: Option<RowPosition>,
+    },
+    BorrowGraphicsDialogue,
+    BorrowGraphicsROMOpened(Option<PathBuf>),
+    ToggleBorrowSheet(usize),
+    SetBorrowPalette(String),
+    ImportTiles {
+        palette_id: PaletteId,
+        tiles: Vec<Tile>,
+    },
+    OpenHeatmap,
+    HeatmapOpened(Option<PathBuf>),
+    ClearHeatmap,
     SelectPalette(String),
+    SetPaletteFilter(String),
+    SetPaletteGroupFilter(String),
+    PaletteGroupsDialogue,
+    SetNewPaletteGroupName(String),
+    EditPaletteGroupName {
+        group: String,
+        name: String,
+    },
+    // New group of each palette:
+    SetPaletteGroups(Vec<(PaletteId, Option<String>)>),
+    MovePaletteGroup {
+        group: String,
+        up: bool,
+    },
+    SetPaletteGroupOrder(Vec<String>),
     AddPaletteDialogue,
     SetAddPaletteName(String),
     SetAddPaletteID(PaletteId),
@@ -42,25 +194,73 @@ pub enum Message {
         name: String,
         id: PaletteId,
     },
+    DuplicatePaletteDialogue,
     DeletePaletteDialogue,
     DeletePalette(PaletteId),
     RestorePalette(Palette),
+    SetPaletteCategory {
+        palette_id: PaletteId,
+        category: PaletteCategory,
+    },
     RenamePaletteDialogue,
     SetRenamePaletteName(String),
     RenamePalette {
         id: PaletteId,
         name: String,
     },
+    PaletteHistoryDialogue,
+    ReplacePalette(Palette),
     HideModal,
+    DismissSlowUpdateWarning,
     SelectColor(PaletteIdx, ColorIdx),
     BrushColor {
         palette_id: PaletteId,
         color_idx: ColorIdx,
         color: ColorRGB,
     },
-    ChangeRed(ColorValue),
-    ChangeGreen(ColorValue),
-    ChangeBlue(ColorValue),
+    SetSnesColorText(String),
+    CopySnesColor,
+    PasteSnesColor,
+    PastedSnesColor(Option<String>),
+    PickColorHsv {
+        hue: f32,
+        saturation: f32,
+        value: f32,
+    },
+    SetRgbHexText(String),
+    ToggleEyedropper,
+    // Pick the selected color from a pixel of an area (in pixels from its top-left corner):
+    PickAreaColor {
+        position: AreaPosition,
+        pixel: Point<u16>,
+    },
+    SwapColors {
+        palette_id: PaletteId,
+        color_idx_1: ColorIdx,
+        color_idx_2: ColorIdx,
+    },
+    AdjustPaletteDialogue,
+    SetPaletteAdjust(PaletteAdjustChange),
+    AdjustPaletteHSV {
+        palette_id: PaletteId,
+        hue: i16,
+        saturation: i16,
+        brightness: i16,
+    },
+    CopyPaletteColors {
+        palette_id: PaletteId,
+        source_id: PaletteId,
+    },
+    InterpolatePalettes {
+        palette_id: PaletteId,
+        from: PaletteId,
+        to: PaletteId,
+        amount: u8,
+    },
+    SetPaletteColors {
+        palette_id: PaletteId,
+        colors: PaletteColors,
+    },
     AddTileRow(PaletteId),
     DeleteTileRow(PaletteId),
     RestoreTileRow(PaletteId, Vec<Tile>),
@@ -79,11 +279,255 @@ pub enum Message {
         tile_idx: TileIdx,
         h_flippable: bool,
     },
+    SetSelectedTilesProperty(TilePropertyChange),
+    SetTileProperties {
+        palette_id: PaletteId,
+        properties: Vec<(TileIdx, TileProperties)>,
+    },
     SetTileVFlippable {
         palette_id: PaletteId,
         tile_idx: TileIdx,
         v_flippable: bool,
     },
+    FlipSuggestionsDialogue,
+    ToggleFlipSuggestion(usize),
+    SetAllFlipSuggestions(bool),
+    ApplyFlipSuggestions,
+    DuplicateTilesDialogue(PaletteId),
+    ApplyDuplicateTiles,
+    MergeFlippedTile {
+        palette_id: PaletteId,
+        tile_idx: TileIdx,
+        original: TileIdx,
+        flip: Flip,
+    },
+    SetTileUsages {
+        palette_id: PaletteId,
+        usages: Vec<TileUsage>,
+    },
+    FindTileUsages,
+    TileUsagesFound {
+        palette_id: PaletteId,
+        tile_idx: TileIdx,
+        usages: Vec<TileUsage>,
+    },
+    GoToTileUsage(TileUsage),
+    CloseTileUsages,
+    ValidateProject,
+    ValidationFinished(Vec<ProjectIssue>),
+    GoToIssue(IssueLocation),
+    CloseIssues,
+    ShowAreaDiff(DiffBase),
+    CloseAreaDiff,
+    ShowPaletteReplace,
+    SetPaletteReplaceFrom(PaletteId),
+    SetPaletteReplaceTo(PaletteId),
+    ClosePaletteReplace,
+    ReplaceAreaPalette {
+        area_id: AreaId,
+        from: PaletteId,
+        to: PaletteId,
+    },
+    SetAreaPalettes {
+        area_id: AreaId,
+        palette_id: PaletteId,
+        cells: Vec<(TileCoord, TileCoord)>,
+    },
+    GoToDiffScreen((u8, u8)),
+    ToggleMacroRecording,
+    MacrosDialogue,
+    SetMacroReplayCoords(TileCoord, TileCoord),
+    ReplayMacro {
+        macro_idx: usize,
+        area_id: AreaId,
+        coords: Point<TileCoord>,
+    },
+    DeleteMacro(usize),
+    SetMacroName(usize, String),
+    SetMacros(Vec<EditMacro>),
+    ColorRampsDialogue,
+    SetColorRampRange {
+        start: ColorIdx,
+        len: u8,
+    },
+    SelectRampShade(Option<(usize, usize)>),
+    AddColorRamp {
+        palette_id: PaletteId,
+        start: ColorIdx,
+        len: u8,
+    },
+    DeleteColorRamp(usize),
+    SetColorRampName(usize, String),
+    SetColorRamps(Vec<ColorRamp>),
+    ShowMetatiles(bool),
+    ShowStamps(bool),
+    SetStampName(String),
+    SaveStamp,
+    LoadStamp(String),
+    DeleteStamp(String),
+    AddMetatile(MetatileSize),
+    SelectMetatile(Option<usize>),
+    SetMetatileName(usize, String),
+    SetMetatileCell {
+        metatile_idx: usize,
+        x: TileCoord,
+        y: TileCoord,
+        palette_id: PaletteId,
+        tile_idx: TileIdx,
+        flip: Flip,
+    },
+    DeleteMetatile(usize),
+    SetMetatiles(Vec<Metatile>),
+    StampMetatile(usize),
+    ExportPaletteDialogue,
+    SetExportPaletteLabeled(bool),
+    SetExportPaletteTileTable(Option<TileTableFormat>),
+    ExportPalette,
+    ExportPaletteTo(Option<PathBuf>),
+    TileGraphicsDialogue,
+    SetTileGraphicsFormat(GraphicsFormat),
+    ExportTileGraphics,
+    ExportTileGraphicsTo(Option<PathBuf>),
+    ImportTileGraphics,
+    ImportTileGraphicsFrom(Option<PathBuf>),
+    LibraryDialogue,
+    PickLibraryDir,
+    LibraryDirPicked(Option<PathBuf>),
+    SetLibraryFilter(String),
+    SetLibraryPublishName(String),
+    SetLibraryPublishTags(String),
+    PublishToLibrary(LibraryKind),
+    ImportFromLibrary(String),
+    DeleteFromLibrary(String),
+    SetBrush(TileBlock),
+    ToggleCollisionOverlay,
+    ToggleEntrances,
+    MoveEntrance {
+        position: AreaPosition,
+        area_id: AreaId,
+        idx: usize,
+        coords: Point<TileCoord>,
+    },
+    ToggleSecrets,
+    ToggleSpritePreview,
+    SetEditLayer(Layer),
+    ShowSecrets(bool),
+    InsertSecret {
+        position: AreaPosition,
+        area_id: AreaId,
+        idx: usize,
+        secret: Secret,
+    },
+    RemoveSecret {
+        position: AreaPosition,
+        area_id: AreaId,
+        idx: usize,
+    },
+    SetSecret {
+        position: AreaPosition,
+        area_id: AreaId,
+        idx: usize,
+        secret: Secret,
+    },
+    ToggleTravel,
+    TravelPointDialogue {
+        position: AreaPosition,
+        idx: usize,
+    },
+    SetTravelPoint {
+        position: AreaPosition,
+        area_id: AreaId,
+        idx: usize,
+        point: TravelPoint,
+    },
+    // Move the travel point being edited in the dialogue to a position (in 8x8 tiles):
+    EditTravelPosition(TileCoord, TileCoord),
+    ToggleAnnotations,
+    ShowAnnotations(bool),
+    SetScreenAnnotation {
+        position: AreaPosition,
+        area_id: AreaId,
+        screen: (u8, u8),
+        value: Option<u8>,
+    },
+    ExportAnnotations,
+    ExportAnnotationsTo(Option<PathBuf>),
+    SetCameraLocked {
+        position: AreaPosition,
+        area_id: AreaId,
+        screen: (u8, u8),
+        locked: bool,
+    },
+    ExportCameraLocks,
+    ExportCameraLocksTo(Option<PathBuf>),
+    ShowExternalTools(bool),
+    AddExternalTool,
+    SetExternalTool(usize, ExternalTool),
+    DeleteExternalTool(usize),
+    SetExternalTools(Vec<ExternalTool>),
+    RunExternalTool(usize),
+    ExternalToolOutput(String),
+    ExternalToolFinished {
+        name: String,
+        // Exit code (none if the tool was killed by a signal), or the error waiting for it:
+        result: Result<Option<i32>, String>,
+    },
+    // Export the main area's theme into a test ROM and run it in the emulator:
+    RunTestRom,
+    BuildTestRom,
+    LaunchTestRom,
+    // The emulator exited (with an exit code, as for external tools):
+    TestRunFinished(Result<Option<i32>, String>),
+    PickEmulator,
+    EmulatorPicked(Option<PathBuf>),
+    PickTestBaseRom,
+    TestBaseRomPicked(Option<PathBuf>),
+    ClearToolConsole,
+    SetCollisionBrush(CollisionType),
+    AreaScrolled {
+        position: AreaPosition,
+        offset: Vector,
+    },
+    ScrollAreaTo {
+        position: AreaPosition,
+        offset: Vector,
+    },
+    SetAutoPan(Option<AutoPan>),
+    AutoPanTick,
+    // Zoom an area view by a number of wheel steps, keeping the point under the cursor (in
+    // logical pixels from the top-left of the area) in place:
+    ZoomArea {
+        position: AreaPosition,
+        steps: f32,
+        anchor: Vector,
+    },
+    ToggleLinkedScroll,
+    ToggleSideDifferences,
+    ToggleGuide {
+        area: AreaName,
+        guide: Guide,
+    },
+    CopyTiles,
+    PasteTiles,
+    PastedTiles(Option<String>),
+    CopySelectionJson,
+    PasteSelectionJson,
+    PastedSelectionJson(Option<String>),
+    SetRampColor {
+        ramp_idx: usize,
+        shade: usize,
+        color: ColorRGB,
+    },
+    SubscribeColorRamp {
+        ramp_idx: usize,
+        palette_id: PaletteId,
+        start: ColorIdx,
+    },
+    UnsubscribeColorRamp {
+        ramp_idx: usize,
+        palette_id: PaletteId,
+        start: ColorIdx,
+    },
     TilesetBrush {
         palette_id: PaletteId,
         coords: Point<TileCoord>,
@@ -96,6 +540,11 @@ pub enum Message {
         coords: Point<PixelCoord>,
         color_idx: ColorIdx,
     },
+    // A stroke across the graphics of several tiles, setting each pixel's color:
+    SetTilePixels {
+        palette_id: PaletteId,
+        pixels: Vec<(TileIdx, Point<PixelCoord>, ColorIdx)>,
+    },
     SelectArea(AreaPosition, String),
     AddAreaDialogue,
     SetAddAreaName(String),
@@ -105,12 +554,18 @@ pub enum Message {
         name: String,
         size: (u8, u8),
     },
+    OpenAreaManifest,
+    AreaManifestOpened(Option<PathBuf>),
+    // Create one area of a manifest (see `area_manifest`), then the next one while the
+    // dialogue showing the progress is open:
+    CreateManifestArea(AreaManifestEntry),
     EditAreaDialogue,
     SetEditAreaName(String),
     EditArea {
         old_name: String,
         new_name: String,
     },
+    ToggleBGColorEditor,
     EditAreaBGRed(ColorValue),
     EditAreaBGGreen(ColorValue),
     EditAreaBGBlue(ColorValue),
@@ -118,12 +573,79 @@ pub enum Message {
         area_id: AreaId,
         color: ColorRGB,
     },
+    EditAreaProperty(AreaPropertyEdit),
+    SetAreaProperties {
+        area_id: AreaId,
+        properties: AreaProperties,
+    },
+    BGColorsDialogue,
+    SelectBGColor {
+        theme: ThemeName,
+        color: ColorRGB,
+    },
+    SetBGColorsColor(ColorRGB),
+    SetBGColorColumn,
+    CopyBGColorRow(AreaName),
+    SetBGColors(Vec<(AreaId, ColorRGB)>),
     DeleteAreaDialogue,
     DeleteArea(String),
+    RestoreArea {
+        // The area in each theme:
+        areas: Vec<Area>,
+        group: Option<String>,
+        world_position: Option<WorldPosition>,
+    },
+    DarkWorldDialogue,
+    SetDarkWorldPalette {
+        light: PaletteId,
+        dark: PaletteId,
+    },
+    GenerateDarkWorldArea {
+        light_area: AreaName,
+        mapping: BTreeMap<PaletteId, PaletteId>,
+    },
+    DuplicateAreaDialogue,
+    SetDuplicateAreaName(String),
+    DuplicateArea {
+        old_name: String,
+        new_name: String,
+    },
+    CompileCheckArea,
+    CompressionEstimate,
+    // Check the screens of the main area (or of all areas of its theme) against the VRAM budgets:
+    VramUsage(bool),
+    GoToAreaScreen(AreaId, (u8, u8)),
+    AreaLabelsDialogue,
+    SetLabelPreviewText(String),
+    SetLabelFont(Option<LabelFont>),
+    SetLabelFontField(LabelFontField),
+    ExportAreaNameTable,
+    AreaPickerDialogue(AreaPosition),
+    SetAreaPickerQuery(String),
+    PickArea(String),
+    PickSelectedArea,
+    AreaListDialogue(AreaPosition),
+    SetNewAreaGroupName(String),
+    ToggleAreaGroup(String),
+    DragArea(String),
+    ReleaseOnArea(String),
+    ReleaseOnAreaGroup(String),
+    SetAreaGroup {
+        area: String,
+        group: Option<String>,
+    },
+    OpenWorldMap(ThemeName),
+    CloseWorldMap,
+    SetWorldMapPosition {
+        area: AreaName,
+        position: Option<WorldPosition>,
+    },
+    OpenWorldMapArea(AreaName),
     SelectTheme(AreaPosition, String),
     AddThemeDialogue,
     SetAddThemeName(String),
     AddTheme(String),
+    DuplicateThemeDialogue,
     RenameThemeDialogue,
     SetRenameThemeName(String),
     RenameTheme {
@@ -132,6 +654,11 @@ pub enum Message {
     },
     DeleteThemeDialogue,
     DeleteTheme(String),
+    RestoreTheme {
+        theme: ThemeName,
+        areas: Vec<Area>,
+    },
+    RestoreSnapshot(Box<ProjectSnapshot>),
     StartTileSelection(Point<TileCoord>, SelectionSource),
     ProgressTileSelection(Point<TileCoord>),
     EndTileSelection(Point<TileCoord>),
@@ -142,8 +669,41 @@ pub enum Message {
         selection: TileBlock,
         palette_only: bool,
     },
+    AreaShape {
+        position: AreaPosition,
+        area_id: AreaId,
+        shape: AreaShape,
+        selection: TileBlock,
+        palette_only: bool,
+    },
+    SetAreaCells {
+        position: AreaPosition,
+        area_id: AreaId,
+        cells: Vec<AreaCell>,
+    },
+    // Set positions of an area's overlay (from brush strokes and shapes on the overlay layer):
+    SetOverlayCells {
+        position: AreaPosition,
+        area_id: AreaId,
+        cells: Vec<OverlayCell>,
+    },
+    // Pair the main area's theme with another for synced brushing (or stop syncing):
+    SetThemeSync(Option<ThemeName>),
+    // Set cells of an area without showing it (e.g. mirroring a brush stroke into a synced theme):
+    MirrorAreaCells {
+        area_id: AreaId,
+        cells: Vec<AreaCell>,
+    },
+    // Select the region of matching tiles containing the point, as a masked brush:
+    SelectRegion(AreaPosition, Point<TileCoord>),
+    ToggleBrushMask(TileCoord, TileCoord),
+    ClearBrushMask,
     OpenTile {
         palette_id: PaletteId,
         tile_idx: TileIdx,
     },
+    OpenTileGraphics {
+        palette_id: PaletteId,
+        tile_idx: TileIdx,
+    },
 }
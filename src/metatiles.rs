@@ -0,0 +1,95 @@
+// Metatiles: named 16x16 and 32x32 blocks of 8x8 tiles, like the composite tiles that the game
+// builds its maps from (see `import`). They're kept in the project metadata, edited in the side
+// panel, and stamped into areas with the brush, snapped to their own grid so that the area's
+// blocks line up with them.
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::state::{Flip, PaletteId, TileBlock, TileCoord, TileIdx};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetatileSize {
+    Tile16,
+    Tile32,
+}
+
+impl MetatileSize {
+    // Width and height in 8x8 tiles.
+    pub fn tiles(self) -> TileCoord {
+        match self {
+            MetatileSize::Tile16 => 2,
+            MetatileSize::Tile32 => 4,
+        }
+    }
+}
+
+impl std::fmt::Display for MetatileSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetatileSize::Tile16 => write!(f, "16x16"),
+            MetatileSize::Tile32 => write!(f, "32x32"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metatile {
+    pub name: String,
+    pub size: MetatileSize,
+    // Always the metatile's size, without a mask:
+    pub block: TileBlock,
+}
+
+fn crop<T: Clone>(rows: &[Vec<T>], n: usize) -> Vec<Vec<T>> {
+    rows[..n].iter().map(|r| r[..n].to_vec()).collect()
+}
+
+impl Metatile {
+    // A metatile of the top-left cells of a brush, which must be at least as large.
+    pub fn from_brush(name: String, size: MetatileSize, brush: &TileBlock) -> Result<Self> {
+        let n = size.tiles() as usize;
+        if (brush.size.0 as usize) < n || (brush.size.1 as usize) < n {
+            bail!(
+                "A {} metatile needs a brush of at least {}x{} tiles",
+                size,
+                n,
+                n
+            );
+        }
+        Ok(Metatile {
+            name,
+            size,
+            block: TileBlock {
+                size: (n as TileCoord, n as TileCoord),
+                palettes: crop(&brush.palettes, n),
+                tiles: crop(&brush.tiles, n),
+                flips: crop(&brush.flips, n),
+                mask: None,
+            },
+        })
+    }
+
+    pub fn set_cell(
+        &mut self,
+        x: TileCoord,
+        y: TileCoord,
+        palette_id: PaletteId,
+        tile_idx: TileIdx,
+        flip: Flip,
+    ) -> Result<()> {
+        let n = self.size.tiles();
+        if x >= n || y >= n {
+            bail!(
+                "cell ({}, {}) is outside of the {} metatile",
+                x,
+                y,
+                self.size
+            );
+        }
+        let (x, y) = (x as usize, y as usize);
+        self.block.palettes[y][x] = palette_id;
+        self.block.tiles[y][x] = tile_idx;
+        self.block.flips[y][x] = flip;
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+// Editing of the overlay layer of areas (the second background layer, e.g. the Lost Woods canopy):
+// while it's the layer being edited, brush strokes and shapes are drawn onto the overlay instead of
+// the main layer. Only the positions of the overlay with tiles are stored, so brushing with tiles
+// that are fully transparent (color 0) erases the overlay.
+use anyhow::Result;
+
+use crate::{
+    area_shapes::{brush_cells, shape_cells, AreaCell},
+    message::Message,
+    state::{Area, EditorState, Flip, Layer, OverlayTile, TileCoord, TileIdx},
+};
+
+// Tile index standing for positions without an overlay tile, in `overlay_view`:
+const NO_TILE: TileIdx = TileIdx::MAX;
+
+// The contents of a position of an area's overlay.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OverlayCell {
+    pub x: TileCoord,
+    pub y: TileCoord,
+    pub tile: Option<OverlayTile>,
+}
+
+impl OverlayCell {
+    pub fn get(area: &Area, x: TileCoord, y: TileCoord) -> Result<Self> {
+        Ok(OverlayCell {
+            x,
+            y,
+            tile: area.get_overlay(x, y)?,
+        })
+    }
+
+    pub fn set(&self, area: &mut Area) -> Result<()> {
+        area.set_overlay(self.x, self.y, self.tile)
+    }
+}
+
+// A copy of the area's layout with its overlay in place of its main layer, for working out the
+// positions that brush strokes and shapes change (e.g. the extent of a fill) with the same code.
+fn overlay_view(area: &Area) -> Area {
+    let mut view = Area {
+        name: area.name.clone(),
+        theme: area.theme.clone(),
+        size: area.size,
+        screens: area.screens.clone(),
+        ..Area::default()
+    };
+    for screen in &mut view.screens {
+        screen.palettes = [[0; 32]; 32];
+        screen.tiles = [[NO_TILE; 32]; 32];
+        screen.flips = [[Flip::None; 32]; 32];
+        for t in std::mem::take(&mut screen.overlay) {
+            let (x, y) = (t.x as usize, t.y as usize);
+            screen.palettes[y][x] = t.palette;
+            screen.tiles[y][x] = t.tile;
+            screen.flips[y][x] = t.flip;
+        }
+    }
+    view
+}
+
+fn overlay_cell(state: &EditorState, cell: AreaCell) -> OverlayCell {
+    let transparent = state
+        .palettes_id_idx_map
+        .get(&cell.palette)
+        .and_then(|&idx| state.palettes[idx].tiles.get(cell.tile as usize))
+        .is_some_and(|t| t.pixels == [[0; 8]; 8]);
+    OverlayCell {
+        x: cell.x,
+        y: cell.y,
+        tile: (cell.tile != NO_TILE && !transparent).then_some(OverlayTile {
+            x: 0,
+            y: 0,
+            palette: cell.palette,
+            tile: cell.tile,
+            flip: cell.flip,
+        }),
+    }
+}
+
+// The message to send in place of the given one: a brush stroke or shape drawn onto the overlay,
+// when it's the layer being edited, or otherwise the message itself.
+pub fn layer_message(state: &EditorState, message: Message) -> Message {
+    if state.edit_layer != Layer::Overlay {
+        return message;
+    }
+    let (position, area_id, cells) = match &message {
+        Message::AreaBrush {
+            position,
+            area_id,
+            coords,
+            selection,
+            palette_only,
+        } => {
+            let Some(area) = state.areas.get(area_id) else {
+                return message;
+            };
+            let view = overlay_view(area);
+            let cells = brush_cells(&view, *coords, selection, *palette_only);
+            (position, area_id, cells)
+        }
+        Message::AreaShape {
+            position,
+            area_id,
+            shape,
+            selection,
+            palette_only,
+        } => {
+            let Some(area) = state.areas.get(area_id) else {
+                return message;
+            };
+            let view = overlay_view(area);
+            let cells = shape_cells(&view, *shape, selection, *palette_only);
+            (position, area_id, cells)
+        }
+        _ => return message,
+    };
+    Message::SetOverlayCells {
+        position: *position,
+        area_id: area_id.clone(),
+        cells: cells.into_iter().map(|c| overlay_cell(state, c)).collect(),
+    }
+}
@@ -0,0 +1,130 @@
+// Bulk operations on the 16 colors of a palette: shifting hue/saturation/brightness, and
+// blending between two palettes (e.g. for making seasonal or darker variants of a palette).
+use crate::state::{ColorRGB, ColorValue, PaletteId};
+
+pub type PaletteColors = [ColorRGB; 16];
+
+// Settings of the palette adjustment dialogue.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PaletteAdjust {
+    // Hue rotation in degrees:
+    pub hue: i16,
+    // Change of saturation and brightness, in percent of their current values:
+    pub saturation: i16,
+    pub brightness: i16,
+    pub copy_source: Option<PaletteId>,
+    pub blend_from: Option<PaletteId>,
+    pub blend_to: Option<PaletteId>,
+    // Position between `blend_from` (0) and `blend_to` (100):
+    pub blend_amount: u8,
+}
+
+impl Default for PaletteAdjust {
+    fn default() -> Self {
+        PaletteAdjust {
+            hue: 0,
+            saturation: 0,
+            brightness: 0,
+            copy_source: None,
+            blend_from: None,
+            blend_to: None,
+            blend_amount: 50,
+        }
+    }
+}
+
+// Change of one setting of the palette adjustment dialogue.
+#[derive(Clone, Copy, Debug)]
+pub enum PaletteAdjustChange {
+    Hue(i16),
+    Saturation(i16),
+    Brightness(i16),
+    CopySource(Option<PaletteId>),
+    BlendFrom(Option<PaletteId>),
+    BlendTo(Option<PaletteId>),
+    BlendAmount(u8),
+}
+
+impl PaletteAdjust {
+    pub fn apply(&mut self, change: PaletteAdjustChange) {
+        match change {
+            PaletteAdjustChange::Hue(x) => self.hue = x,
+            PaletteAdjustChange::Saturation(x) => self.saturation = x,
+            PaletteAdjustChange::Brightness(x) => self.brightness = x,
+            PaletteAdjustChange::CopySource(x) => self.copy_source = x,
+            PaletteAdjustChange::BlendFrom(x) => self.blend_from = x,
+            PaletteAdjustChange::BlendTo(x) => self.blend_to = x,
+            PaletteAdjustChange::BlendAmount(x) => self.blend_amount = x,
+        }
+    }
+}
+
+const MAX_VALUE: f32 = 31.0;
+
+pub fn rgb_to_hsv(color: ColorRGB) -> (f32, f32, f32) {
+    let [r, g, b] = color.map(|c| c as f32 / MAX_VALUE);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+    let hue = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let saturation = if max == 0.0 { 0.0 } else { delta / max };
+    (hue, saturation, max)
+}
+
+// The nearest color with 5-bit components.
+pub fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> ColorRGB {
+    let c = value * saturation;
+    let h = hue.rem_euclid(360.0) / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u8 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = value - c;
+    [r, g, b].map(|v| ((v + m) * MAX_VALUE).round().clamp(0.0, MAX_VALUE) as ColorValue)
+}
+
+// Rotate the hue and scale the saturation and brightness of each color.
+pub fn shift_hsv(
+    colors: &PaletteColors,
+    hue: i16,
+    saturation: i16,
+    brightness: i16,
+) -> PaletteColors {
+    let saturation_scale = 1.0 + saturation as f32 / 100.0;
+    let brightness_scale = 1.0 + brightness as f32 / 100.0;
+    colors.map(|color| {
+        let (h, s, v) = rgb_to_hsv(color);
+        hsv_to_rgb(
+            h + hue as f32,
+            (s * saturation_scale).clamp(0.0, 1.0),
+            (v * brightness_scale).clamp(0.0, 1.0),
+        )
+    })
+}
+
+// Blend each color of `from` with the corresponding color of `to`, by `amount` percent.
+pub fn interpolate(from: &PaletteColors, to: &PaletteColors, amount: u8) -> PaletteColors {
+    let t = amount.min(100) as f32 / 100.0;
+    let mut out = [[0; 3]; 16];
+    for i in 0..16 {
+        for j in 0..3 {
+            let a = from[i][j] as f32;
+            let b = to[i][j] as f32;
+            out[i][j] = (a + (b - a) * t).round() as ColorValue;
+        }
+    }
+    out
+}
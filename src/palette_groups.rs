@@ -0,0 +1,147 @@
+// Palette groups: each palette can be put in a named group (stored in its JSON), for finding it
+// among the many palettes of an imported project. Imported palettes are grouped by the game's
+// palette group (HUD, Main, Aux, or Animated). The palette panel lists the palettes matching a
+// filter text and group, and a dialogue reorders groups, renames them, and moves palettes
+// between them in bulk.
+use crate::state::{EditorState, Palette, PaletteId};
+
+pub const UNGROUPED_PALETTE_GROUP: &str = "Ungrouped";
+// Choice of the group filter that shows all groups:
+pub const ALL_PALETTE_GROUPS: &str = "All groups";
+
+pub fn palette_group(palette: &Palette) -> &str {
+    palette.group.as_deref().unwrap_or(UNGROUPED_PALETTE_GROUP)
+}
+
+// The group that a palette should be stored with, from a group name entered by the user.
+pub fn normalized_group(group: &str) -> Option<String> {
+    let group = group.trim();
+    if group.is_empty() || group == UNGROUPED_PALETTE_GROUP {
+        None
+    } else {
+        Some(group.to_string())
+    }
+}
+
+// The groups that palettes are in, in the project's order, followed by any others in sorted
+// order, and with ungrouped palettes last.
+pub fn palette_group_names(state: &EditorState) -> Vec<String> {
+    let mut groups: Vec<String> = state
+        .palettes
+        .iter()
+        .filter_map(|p| p.group.clone())
+        .collect();
+    groups.sort();
+    groups.dedup();
+    let order = &state.project_metadata.palette_group_order;
+    let rank = |g: &String| order.iter().position(|o| o == g).unwrap_or(order.len());
+    groups.sort_by_key(|g| rank(g));
+    if state.palettes.iter().any(|p| p.group.is_none()) {
+        groups.push(UNGROUPED_PALETTE_GROUP.to_string());
+    }
+    groups
+}
+
+pub fn group_size(state: &EditorState, group: &str) -> usize {
+    state
+        .palettes
+        .iter()
+        .filter(|p| palette_group(p) == group)
+        .count()
+}
+
+// Whether a palette is listed, given the filter text (matched against its ID, name, and group,
+// ignoring case) and group.
+pub fn palette_shown(state: &EditorState, palette: &Palette) -> bool {
+    if let Some(group) = &state.palette_group_filter {
+        if palette_group(palette) != group {
+            return false;
+        }
+    }
+    let filter = state.palette_filter.trim().to_lowercase();
+    filter.is_empty()
+        || format!(
+            "{}: {} {}",
+            palette.id,
+            palette.name,
+            palette_group(palette)
+        )
+        .to_lowercase()
+        .contains(&filter)
+}
+
+pub fn shown_palettes(state: &EditorState) -> Vec<&Palette> {
+    state
+        .palettes
+        .iter()
+        .filter(|p| palette_shown(state, p))
+        .collect()
+}
+
+// Group changes moving the given palettes to a group.
+pub fn moved_to_group(
+    palettes: &[&Palette],
+    group: Option<String>,
+) -> Vec<(PaletteId, Option<String>)> {
+    palettes
+        .iter()
+        .filter(|p| p.group != group)
+        .map(|p| (p.id, group.clone()))
+        .collect()
+}
+
+// The group order after moving a group up or down by one place.
+pub fn moved_group_order(state: &EditorState, group: &str, up: bool) -> Vec<String> {
+    let mut order: Vec<String> = palette_group_names(state)
+        .into_iter()
+        .filter(|g| g != UNGROUPED_PALETTE_GROUP)
+        .collect();
+    let Some(i) = order.iter().position(|g| g == group) else {
+        return order;
+    };
+    let j = if up {
+        i.checked_sub(1)
+    } else {
+        Some(i + 1).filter(|&j| j < order.len())
+    };
+    if let Some(j) = j {
+        order.swap(i, j);
+    }
+    order
+}
+
+// Apply group changes to the palettes, returning the changes that undo them. A group whose
+// palettes all move to a new group (i.e. a renamed group) keeps its place in the order.
+pub fn set_palette_groups(
+    state: &mut EditorState,
+    changes: &[(PaletteId, Option<String>)],
+) -> Vec<(PaletteId, Option<String>)> {
+    let old_groups = palette_group_names(state);
+    let mut reverse = vec![];
+    for (id, group) in changes {
+        let Some(&idx) = state.palettes_id_idx_map.get(id) else {
+            continue;
+        };
+        let palette = &mut state.palettes[idx];
+        reverse.push((*id, palette.group.clone()));
+        palette.group = group.clone();
+        palette.modified = true;
+    }
+    let new_groups = palette_group_names(state);
+    let removed: Vec<&String> = old_groups
+        .iter()
+        .filter(|g| !new_groups.contains(g))
+        .collect();
+    let added: Vec<&String> = new_groups
+        .iter()
+        .filter(|g| !old_groups.contains(g))
+        .collect();
+    if let ([removed], [added]) = (removed.as_slice(), added.as_slice()) {
+        let order = &mut state.project_metadata.palette_group_order;
+        if let Some(g) = order.iter_mut().find(|g| g == removed) {
+            *g = added.to_string();
+            state.project_metadata.modified = true;
+        }
+    }
+    reverse
+}
@@ -0,0 +1,46 @@
+// Replacing the uses of one palette with another in a single area (rather than across the
+// project), e.g. while reorganizing the palettes of one map. The tiles that would change are
+// highlighted in the area while the replacement is being set up.
+use crate::state::{Area, AreaId, Palette, PaletteId, TileCoord};
+
+#[derive(Clone, Debug)]
+pub struct PaletteReplace {
+    pub area_id: AreaId,
+    pub from: Option<PaletteId>,
+    pub to: Option<PaletteId>,
+}
+
+// Positions of the tiles in the area that use the palette.
+pub fn palette_cells(area: &Area, palette_id: PaletteId) -> Vec<(TileCoord, TileCoord)> {
+    let mut cells = vec![];
+    for screen in &area.screens {
+        let (sx, sy) = (
+            screen.position.0 as TileCoord,
+            screen.position.1 as TileCoord,
+        );
+        for y in 0..32 {
+            for x in 0..32 {
+                if screen.palettes[y][x] == palette_id {
+                    cells.push((sx * 32 + x as TileCoord, sy * 32 + y as TileCoord));
+                }
+            }
+        }
+    }
+    cells
+}
+
+// How many of the cells have a tile index beyond the end of the palette, so that they would show
+// a missing tile after switching to it.
+pub fn missing_tile_count(
+    area: &Area,
+    cells: &[(TileCoord, TileCoord)],
+    palette: &Palette,
+) -> usize {
+    cells
+        .iter()
+        .filter(|&&(x, y)| {
+            area.get_tile(x, y)
+                .is_ok_and(|t| t as usize >= palette.tiles.len())
+        })
+        .count()
+}
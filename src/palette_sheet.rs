@@ -0,0 +1,169 @@
+// Reference sheets of a palette's colors, for sharing with collaborators working outside the
+// editor: a header with the palette's name and ID, and each color swatch labeled with its
+// index, RGB values (0-31), and SNES color value.
+use crate::{helpers::scale_color, state::Palette};
+
+// Glyphs of a 3x5 pixel font, one row per byte (bit 2 is the leftmost pixel):
+fn glyph(c: char) -> [u8; 5] {
+    match c.to_ascii_uppercase() {
+        '0' => [7, 5, 5, 5, 7],
+        '1' => [2, 6, 2, 2, 7],
+        '2' => [7, 1, 7, 4, 7],
+        '3' => [7, 1, 7, 1, 7],
+        '4' => [5, 5, 7, 1, 1],
+        '5' => [7, 4, 7, 1, 7],
+        '6' => [7, 4, 7, 5, 7],
+        '7' => [7, 1, 1, 1, 1],
+        '8' => [7, 5, 7, 5, 7],
+        '9' => [7, 5, 7, 1, 7],
+        'A' => [2, 5, 7, 5, 5],
+        'B' => [6, 5, 6, 5, 6],
+        'C' => [3, 4, 4, 4, 3],
+        'D' => [6, 5, 5, 5, 6],
+        'E' => [7, 4, 6, 4, 7],
+        'F' => [7, 4, 6, 4, 4],
+        'G' => [3, 4, 5, 5, 3],
+        'H' => [5, 5, 7, 5, 5],
+        'I' => [7, 2, 2, 2, 7],
+        'J' => [1, 1, 1, 5, 2],
+        'K' => [5, 5, 6, 5, 5],
+        'L' => [4, 4, 4, 4, 7],
+        'M' => [5, 7, 7, 5, 5],
+        'N' => [6, 5, 5, 5, 5],
+        'O' => [2, 5, 5, 5, 2],
+        'P' => [6, 5, 6, 4, 4],
+        'Q' => [2, 5, 5, 6, 3],
+        'R' => [6, 5, 6, 5, 5],
+        'S' => [3, 4, 2, 1, 6],
+        'T' => [7, 2, 2, 2, 2],
+        'U' => [5, 5, 5, 5, 7],
+        'V' => [5, 5, 5, 5, 2],
+        'W' => [5, 5, 7, 7, 5],
+        'X' => [5, 5, 2, 5, 5],
+        'Y' => [5, 5, 2, 2, 2],
+        'Z' => [7, 1, 2, 4, 7],
+        ' ' => [0, 0, 0, 0, 0],
+        '-' => [0, 0, 7, 0, 0],
+        '_' => [0, 0, 0, 0, 7],
+        '.' => [0, 0, 0, 0, 2],
+        ',' => [0, 0, 0, 2, 4],
+        ':' => [0, 2, 0, 2, 0],
+        '(' => [1, 2, 2, 2, 1],
+        ')' => [4, 2, 2, 2, 4],
+        '$' => [3, 6, 2, 3, 6],
+        '#' => [5, 7, 5, 7, 5],
+        '/' => [1, 1, 2, 4, 4],
+        '^' => [2, 5, 0, 0, 0],
+        '=' => [0, 7, 0, 7, 0],
+        '+' => [0, 2, 7, 2, 0],
+        _ => [6, 1, 2, 0, 2], // '?'
+    }
+}
+
+const FONT_SCALE: usize = 2;
+pub const CHAR_WIDTH: usize = 4 * FONT_SCALE;
+pub const LINE_HEIGHT: usize = 7 * FONT_SCALE;
+
+const MARGIN: usize = 8;
+const COLUMNS: usize = 8;
+const CELL_WIDTH: usize = 96;
+const SWATCH_HEIGHT: usize = 48;
+const CELL_HEIGHT: usize = SWATCH_HEIGHT + 3 * LINE_HEIGHT + MARGIN;
+const HEADER_HEIGHT: usize = LINE_HEIGHT + MARGIN;
+
+const BACKGROUND: [u8; 3] = [32, 32, 32];
+const TEXT_COLOR: [u8; 3] = [240, 240, 240];
+
+// An RGB image, 3 bytes per pixel.
+pub struct RgbImage {
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<u8>,
+}
+
+impl RgbImage {
+    pub fn new(width: usize, height: usize, color: [u8; 3]) -> Self {
+        RgbImage {
+            width,
+            height,
+            data: color.repeat(width * height),
+        }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 3] {
+        let i = (y * self.width + x) * 3;
+        [self.data[i], self.data[i + 1], self.data[i + 2]]
+    }
+
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: [u8; 3]) {
+        for y1 in y..(y + height).min(self.height) {
+            for x1 in x..(x + width).min(self.width) {
+                let i = (y1 * self.width + x1) * 3;
+                self.data[i..i + 3].copy_from_slice(&color);
+            }
+        }
+    }
+
+    // Draw text with its top-left corner at the given position, clipped to the image.
+    pub fn draw_text(&mut self, x: usize, y: usize, s: &str, color: [u8; 3]) {
+        for (i, c) in s.chars().enumerate() {
+            let x0 = x + i * CHAR_WIDTH;
+            if x0 >= self.width {
+                break;
+            }
+            for (row, bits) in glyph(c).into_iter().enumerate() {
+                for col in 0..3 {
+                    if bits & (4 >> col) != 0 {
+                        self.fill_rect(
+                            x0 + col * FONT_SCALE,
+                            y + row * FONT_SCALE,
+                            FONT_SCALE,
+                            FONT_SCALE,
+                            color,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Position of the top-left corner of the swatch for the given color index.
+pub fn swatch_position(color_idx: usize) -> (usize, usize) {
+    (
+        MARGIN + (color_idx % COLUMNS) * CELL_WIDTH,
+        MARGIN + HEADER_HEIGHT + (color_idx / COLUMNS) * CELL_HEIGHT,
+    )
+}
+
+pub fn render_palette_sheet(palette: &Palette) -> RgbImage {
+    let width = 2 * MARGIN + COLUMNS * CELL_WIDTH;
+    let height = MARGIN + HEADER_HEIGHT + 16 / COLUMNS * CELL_HEIGHT;
+    let mut image = RgbImage::new(width, height, BACKGROUND);
+    image.draw_text(
+        MARGIN,
+        MARGIN,
+        &format!("{} (ID {})", palette.name, palette.id),
+        TEXT_COLOR,
+    );
+    for (i, &[r, g, b]) in palette.colors.iter().enumerate() {
+        let (x, y) = swatch_position(i);
+        let color = [scale_color(r), scale_color(g), scale_color(b)];
+        image.fill_rect(x, y, CELL_WIDTH - MARGIN, SWATCH_HEIGHT, color);
+        let snes_color = (b as u16) << 10 | (g as u16) << 5 | r as u16;
+        let labels = [
+            if i == 0 {
+                "0 (CLEAR)".to_string()
+            } else {
+                i.to_string()
+            },
+            format!("R{} G{} B{}", r, g, b),
+            format!("${:04X}", snes_color),
+        ];
+        for (j, label) in labels.iter().enumerate() {
+            let text_y = y + SWATCH_HEIGHT + FONT_SCALE + j * LINE_HEIGHT;
+            image.draw_text(x, text_y, label, TEXT_COLOR);
+        }
+    }
+    image
+}
@@ -0,0 +1,383 @@
+// Assignment of the palettes of each area to the palette positions that the game loads for its
+// map, saved for each theme in the project (in "PaletteSlots/<theme>.json") for the export to
+// use. For example:
+//
+//     {
+//         "Lost Woods": { "Main 0": 3, "Aux1 2": 12, "Animated": 20 },
+//         "Kakariko Village": { "Main 0": 3, "Main 1": 15 }
+//     }
+//
+// Without an assignment, an area can only use the palettes that the ROM already loads for its
+// map. The solver picks positions by coloring the graph of palettes that appear together in an
+// area or in neighboring areas (which are shown together during screen transitions), so that
+// such palettes get different positions. Where it picks poorly, individual areas can be
+// adjusted by hand.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use anyhow::{bail, Context, Result};
+use heuristic_graph_coloring::VecVecGraph;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    import::PaletteGroup,
+    persist::{load_json, save_json},
+    state::{Area, AreaId, AreaName, EditorState, PaletteCategory, PaletteId, ThemeName},
+};
+
+// A position among the palettes loaded for a map: the palette index (2-7) that tiles select it
+// with, and whether it's in the upper half, which is used by the aux graphics sheets. The HUD
+// palettes (indices 0-1) are the same for every map, so they aren't assigned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct RowPosition {
+    pub pal_idx: u8,
+    pub pal_high: bool,
+}
+
+impl RowPosition {
+    pub const ALL: [RowPosition; 12] = [
+        RowPosition::low(2),
+        RowPosition::low(3),
+        RowPosition::low(4),
+        RowPosition::low(5),
+        RowPosition::low(6),
+        RowPosition::low(7),
+        RowPosition::high(2),
+        RowPosition::high(3),
+        RowPosition::high(4),
+        RowPosition::high(5),
+        RowPosition::high(6),
+        RowPosition::high(7),
+    ];
+
+    const fn low(pal_idx: u8) -> Self {
+        RowPosition {
+            pal_idx,
+            pal_high: false,
+        }
+    }
+
+    const fn high(pal_idx: u8) -> Self {
+        RowPosition {
+            pal_idx,
+            pal_high: true,
+        }
+    }
+
+    // The group of ROM palette rows that the position is loaded from.
+    pub fn group(self) -> PaletteGroup {
+        match (self.pal_idx, self.pal_high) {
+            (7, false) => PaletteGroup::Animated,
+            (_, false) => PaletteGroup::Main,
+            (_, true) => PaletteGroup::Aux,
+        }
+    }
+
+    pub fn in_group(group: PaletteGroup) -> Vec<RowPosition> {
+        Self::ALL
+            .into_iter()
+            .filter(|p| p.group() == group)
+            .collect()
+    }
+
+    // Positions that can hold a palette of the given category.
+    pub fn for_category(category: PaletteCategory) -> Vec<RowPosition> {
+        category_group(category)
+            .map(Self::in_group)
+            .unwrap_or_default()
+    }
+}
+
+impl std::fmt::Display for RowPosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.pal_idx, self.pal_high) {
+            (7, false) => write!(f, "Animated"),
+            (p, false) => write!(f, "Main {}", p - 2),
+            (p @ 2..=4, true) => write!(f, "Aux1 {}", p - 2),
+            (p, true) => write!(f, "Aux2 {}", p - 5),
+        }
+    }
+}
+
+impl From<RowPosition> for String {
+    fn from(position: RowPosition) -> Self {
+        position.to_string()
+    }
+}
+
+impl TryFrom<String> for RowPosition {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::ALL
+            .into_iter()
+            .find(|p| p.to_string() == s)
+            .ok_or_else(|| format!("Invalid palette position: {}", s))
+    }
+}
+
+// The group of positions for palettes of the given category. Custom palettes are placed with the
+// main palettes.
+pub fn category_group(category: PaletteCategory) -> Option<PaletteGroup> {
+    match category {
+        PaletteCategory::HUD => None,
+        PaletteCategory::Main | PaletteCategory::Custom => Some(PaletteGroup::Main),
+        PaletteCategory::Aux => Some(PaletteGroup::Aux),
+        PaletteCategory::Animated => Some(PaletteGroup::Animated),
+    }
+}
+
+// The palette at each position, for each area of a theme.
+pub type PaletteAssignment = BTreeMap<AreaName, BTreeMap<RowPosition, PaletteId>>;
+
+pub fn assignment_path(state: &EditorState, theme: &ThemeName) -> Result<PathBuf> {
+    let project_dir = state
+        .global_config
+        .project_dir
+        .as_ref()
+        .context("Project directory not set.")?;
+    Ok(project_dir
+        .join("PaletteSlots")
+        .join(format!("{}.json", theme)))
+}
+
+// The saved assignment for the theme, which is empty if there isn't one.
+pub fn load_assignment(state: &EditorState, theme: &ThemeName) -> Result<PaletteAssignment> {
+    let path = assignment_path(state, theme)?;
+    if !path.exists() {
+        return Ok(PaletteAssignment::new());
+    }
+    load_json(&path)
+        .with_context(|| format!("Unable to read palette positions from {}", path.display()))
+}
+
+pub fn save_assignment(
+    state: &EditorState,
+    theme: &ThemeName,
+    assignment: &PaletteAssignment,
+) -> Result<()> {
+    save_json(&assignment_path(state, theme)?, assignment)
+}
+
+// Check that each palette in the assignment can be placed at its position.
+pub fn check_assignment(state: &EditorState, assignment: &PaletteAssignment) -> Result<()> {
+    for (area_name, positions) in assignment {
+        for (position, id) in positions {
+            let Some(&idx) = state.palettes_id_idx_map.get(id) else {
+                bail!("Unknown palette {} in area {}", id, area_name);
+            };
+            let palette = &state.palettes[idx];
+            if !RowPosition::for_category(palette.category).contains(position) {
+                bail!(
+                    "{} palette {} can't be placed at {} in area {}",
+                    palette.category,
+                    palette.name,
+                    position,
+                    area_name
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+type MapIdx = usize; // Vanilla map number of area
+
+// Areas that are shown together during screen transitions, by their vanilla map numbers.
+pub fn get_area_neighbors() -> Vec<(MapIdx, Vec<MapIdx>)> {
+    vec![
+        (0x00, vec![0x02, 0x10, 0x11, 0x80]),
+        (0x02, vec![0x00, 0x0A]),
+        (0x03, vec![0x05]),
+        (0x05, vec![0x03, 0x07]),
+        (0x07, vec![0x05]),
+        (0x0A, vec![0x02, 0x12]),
+        (0x0F, vec![0x81, 0x17]),
+        (0x10, vec![0x00, 0x18]),
+        (0x11, vec![0x00, 0x12, 0x18]),
+        (0x12, vec![0x0A, 0x11, 0x13, 0x1A]),
+        (0x13, vec![0x12, 0x14]),
+        (0x14, vec![0x13, 0x15]),
+        (0x15, vec![0x14, 0x16, 0x1D]),
+        (0x16, vec![0x15, 0x17]),
+        (0x17, vec![0x0F, 0x16]),
+        (0x18, vec![0x10, 0x11, 0x22, 0x29]),
+        (0x1A, vec![0x12, 0x1B]),
+        (0x1B, vec![0x1A, 0x25, 0x2B, 0x2C]),
+        (0x1D, vec![0x15, 0x25]),
+        (0x1E, vec![0x2E, 0x2F]),
+        (0x22, vec![0x18]),
+        (0x25, vec![0x1B, 0x1D, 0x2D]),
+        (0x28, vec![0x29]),
+        (0x29, vec![0x18, 0x28, 0x2A]),
+        (0x2A, vec![0x29, 0x32]),
+        (0x2B, vec![0x1B, 0x2C, 0x33]),
+        (0x2C, vec![0x1B, 0x2B, 0x2D, 0x34]),
+        (0x2D, vec![0x25, 0x2C, 0x2E, 0x35]),
+        (0x2E, vec![0x1E, 0x2D, 0x35]),
+        (0x2F, vec![0x1E]),
+        (0x30, vec![0x3A]),
+        (0x32, vec![0x2A, 0x33]),
+        (0x33, vec![0x2B, 0x32, 0x34, 0x3B]),
+        (0x34, vec![0x2C, 0x33, 0x3C]),
+        (0x35, vec![0x2D, 0x2E, 0x3C, 0x3F]),
+        (0x37, vec![0x3F]),
+        (0x3A, vec![0x30, 0x3B]),
+        (0x3B, vec![0x33, 0x3A, 0x3C]),
+        (0x3C, vec![0x34, 0x3B, 0x35]),
+        (0x3F, vec![0x35, 0x37]),
+        (0x40, vec![0x42, 0x50, 0x51]),
+        (0x42, vec![0x40, 0x4A]),
+        (0x43, vec![0x45]),
+        (0x45, vec![0x43, 0x47]),
+        (0x47, vec![0x45]),
+        (0x4A, vec![0x42, 0x52]),
+        (0x4F, vec![0x57]),
+        (0x50, vec![0x40, 0x58]),
+        (0x51, vec![0x40, 0x52, 0x58]),
+        (0x52, vec![0x4A, 0x51, 0x53, 0x5A]),
+        (0x53, vec![0x52, 0x54]),
+        (0x54, vec![0x53, 0x55]),
+        (0x55, vec![0x54, 0x56, 0x5D]),
+        (0x56, vec![0x55, 0x57]),
+        (0x57, vec![0x4F, 0x56]),
+        (0x58, vec![0x50, 0x51, 0x62, 0x69]),
+        (0x5A, vec![0x52]),
+        (0x5B, vec![0x65, 0x6B, 0x6C]),
+        (0x5D, vec![0x55, 0x65]),
+        (0x5E, vec![0x6E, 0x6F]),
+        (0x62, vec![0x58]),
+        (0x65, vec![0x5B, 0x5D, 0x6D]),
+        (0x68, vec![0x69]),
+        (0x69, vec![0x58, 0x68, 0x6A]),
+        (0x6A, vec![0x69, 0x72]),
+        (0x6B, vec![0x5B, 0x6C, 0x73]),
+        (0x6C, vec![0x5B, 0x6B, 0x6D, 0x74]),
+        (0x6D, vec![0x65, 0x6C, 0x6E, 0x75]),
+        (0x6E, vec![0x5E, 0x6D, 0x75]),
+        (0x6F, vec![0x5E]),
+        (0x70, vec![]),
+        (0x72, vec![0x6A, 0x73]),
+        (0x73, vec![0x6B, 0x72, 0x74, 0x7B]),
+        (0x74, vec![0x6C, 0x73, 0x7C]),
+        (0x75, vec![0x6D, 0x6E, 0x7C, 0x7F]),
+        (0x77, vec![0x7F]),
+        (0x7A, vec![0x7B]),
+        (0x7B, vec![0x73, 0x7A, 0x7C]),
+        (0x7C, vec![0x74, 0x7B, 0x75]),
+        (0x7F, vec![0x75, 0x77]),
+        (0x80, vec![0x00]),
+        (0x81, vec![0x0F]),
+    ]
+}
+
+// The palettes used by an area that need a position, i.e. all but the HUD palettes.
+pub fn area_palettes(state: &EditorState, area: &Area) -> Vec<PaletteId> {
+    area.get_unique_palettes()
+        .into_iter()
+        .filter(|id| {
+            state
+                .palettes_id_idx_map
+                .get(id)
+                .is_some_and(|&idx| state.palettes[idx].category != PaletteCategory::HUD)
+        })
+        .collect()
+}
+
+// Assign positions to the palettes of every area of the theme, also returning the problems
+// found (palettes for which no position is left).
+pub fn solve(state: &EditorState, theme: &ThemeName) -> Result<(PaletteAssignment, Vec<String>)> {
+    let mut areas: Vec<(AreaName, Option<u8>, Vec<PaletteId>)> = vec![];
+    for area_name in &state.area_names {
+        let area_id = AreaId {
+            area: area_name.clone(),
+            theme: theme.clone(),
+        };
+        let area = state.area_copy(&area_id)?;
+        let palettes = area_palettes(state, &area);
+        areas.push((area_name.clone(), area.vanilla_map_id, palettes));
+    }
+
+    // Palettes that must have different positions: those used in the same area, or in
+    // neighboring areas.
+    let mut neighbors: Vec<Vec<usize>> = vec![vec![]; areas.len()];
+    for (map_idx, map_neighbors) in get_area_neighbors() {
+        let find = |m: MapIdx| areas.iter().position(|a| a.1 == Some(m as u8));
+        let Some(i) = find(map_idx) else {
+            continue;
+        };
+        neighbors[i].extend(map_neighbors.into_iter().filter_map(find));
+    }
+    let mut conflicts: BTreeSet<(PaletteId, PaletteId)> = BTreeSet::new();
+    for (i, (_, _, palettes)) in areas.iter().enumerate() {
+        let nearby = neighbors[i].iter().flat_map(|&j| &areas[j].2);
+        for &pal1 in palettes {
+            for &pal2 in palettes.iter().chain(nearby.clone()) {
+                if pal1 != pal2 {
+                    conflicts.insert((pal1.min(pal2), pal1.max(pal2)));
+                }
+            }
+        }
+    }
+
+    // Color each group of positions separately, since palettes in different groups never
+    // share a position:
+    let mut positions: BTreeMap<PaletteId, RowPosition> = BTreeMap::new();
+    let mut problems = vec![];
+    let all_palettes: BTreeSet<PaletteId> = areas.iter().flat_map(|a| a.2.clone()).collect();
+    for group in [
+        PaletteGroup::Main,
+        PaletteGroup::Aux,
+        PaletteGroup::Animated,
+    ] {
+        let group_positions = RowPosition::in_group(group);
+        let palettes: Vec<PaletteId> = all_palettes
+            .iter()
+            .copied()
+            .filter(|id| {
+                let category = state.palettes[state.palettes_id_idx_map[id]].category;
+                category_group(category) == Some(group)
+            })
+            .collect();
+        let mut graph = VecVecGraph::new(palettes.len());
+        for (i, pal1) in palettes.iter().enumerate() {
+            for (j, pal2) in palettes.iter().enumerate().skip(i + 1) {
+                if conflicts.contains(&(*pal1, *pal2)) {
+                    graph.add_edge(i, j);
+                }
+            }
+        }
+        let colors = heuristic_graph_coloring::color_rlf(&graph);
+        for (&id, color) in palettes.iter().zip(colors) {
+            match group_positions.get(color) {
+                Some(&position) => {
+                    positions.insert(id, position);
+                }
+                None => {
+                    let palette = &state.palettes[state.palettes_id_idx_map[&id]];
+                    problems.push(format!(
+                        "No {:?} position left for palette {} (it would need {} positions)",
+                        group,
+                        palette.name,
+                        color + 1
+                    ));
+                }
+            }
+        }
+    }
+
+    let mut assignment = PaletteAssignment::new();
+    for (area_name, _, palettes) in areas {
+        let area_positions: BTreeMap<RowPosition, PaletteId> = palettes
+            .iter()
+            .filter_map(|id| Some((*positions.get(id)?, *id)))
+            .collect();
+        if !area_positions.is_empty() {
+            assignment.insert(area_name, area_positions);
+        }
+    }
+    Ok((assignment, problems))
+}
@@ -0,0 +1,67 @@
+// Three-pane layout: the main area, the side area, and the side panel (e.g. the tileset) side by
+// side, with draggable splitters between them, as an alternative to the side panel showing either
+// the tileset or the side area. The splitter positions are kept in the global config, so the
+// layout is restored at startup.
+use iced::widget::pane_grid::{self, Axis, Configuration, Node};
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Pane {
+    Main,
+    SideArea,
+    SidePanel,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct PaneLayout {
+    // Fraction of the window's width taken by the main area:
+    pub main_ratio: f32,
+    // Fraction of the remaining width taken by the side area (the side panel gets the rest):
+    pub side_ratio: f32,
+}
+
+impl Default for PaneLayout {
+    fn default() -> Self {
+        PaneLayout {
+            main_ratio: 0.45,
+            side_ratio: 0.5,
+        }
+    }
+}
+
+// Panes with the given splitter positions.
+pub fn pane_state(layout: PaneLayout) -> pane_grid::State<Pane> {
+    pane_grid::State::with_configuration(Configuration::Split {
+        axis: Axis::Vertical,
+        ratio: layout.main_ratio,
+        a: Box::new(Configuration::Pane(Pane::Main)),
+        b: Box::new(Configuration::Split {
+            axis: Axis::Vertical,
+            ratio: layout.side_ratio,
+            a: Box::new(Configuration::Pane(Pane::SideArea)),
+            b: Box::new(Configuration::Pane(Pane::SidePanel)),
+        }),
+    })
+}
+
+// The splitter positions of panes made by `pane_state` (after they've been resized).
+pub fn pane_layout(panes: &pane_grid::State<Pane>) -> Option<PaneLayout> {
+    let Node::Split {
+        ratio: main_ratio,
+        b,
+        ..
+    } = panes.layout()
+    else {
+        return None;
+    };
+    let Node::Split {
+        ratio: side_ratio, ..
+    } = b.as_ref()
+    else {
+        return None;
+    };
+    Some(PaneLayout {
+        main_ratio: *main_ratio,
+        side_ratio: *side_ratio,
+    })
+}
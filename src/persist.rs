@@ -1,27 +1,38 @@
 use std::{
+    collections::BTreeSet,
+    ffi::OsStr,
     fs::{self, File},
     io::BufWriter,
     path::{Path, PathBuf},
     sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
 };
 
 use anyhow::{bail, Context, Result};
+use hashbrown::HashMap;
+use itertools::Itertools;
 use json_pretty_compact::PrettyCompactFormatter;
-use log::info;
+use log::{info, warn};
 use notify::{recommended_watcher, EventHandler};
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Serializer;
 
 use crate::{
-    helpers::scale_color,
+    area_diff::DiffBase,
+    dirty,
+    helpers::{content_hash, format_age, scale_color},
+    keymap::{render_cheat_sheet, KeyBinding},
+    palette_sheet::{render_palette_sheet, RgbImage},
     state::{
         ensure_areas_non_empty, ensure_palettes_non_empty, ensure_themes_non_empty, Area, AreaId,
-        AreaPosition, EditorState, Palette,
+        AreaName, AreaPosition, EditorState, Palette, PaletteId, PaletteVersion, ProjectMetadata,
+        RecentWrites, ThemeName, WatcherStatus,
     },
+    tile_table::{tile_table, TileTableFormat},
     update::update_palette_order,
 };
 
-fn save_json<T: Serialize>(path: &Path, data: &T) -> Result<()> {
+pub fn save_json<T: Serialize>(path: &Path, data: &T) -> Result<()> {
     info!("Saving {}", path.display());
     let formatter = PrettyCompactFormatter::new().with_max_line_length(200);
     let mut data_bytes = vec![];
@@ -32,7 +43,7 @@ fn save_json<T: Serialize>(path: &Path, data: &T) -> Result<()> {
     Ok(())
 }
 
-fn load_json<T: DeserializeOwned>(path: &Path) -> Result<T> {
+pub fn load_json<T: DeserializeOwned>(path: &Path) -> Result<T> {
     info!("Loading {}", path.display());
     let data_bytes = std::fs::read(path)?;
     let data: T = serde_json::from_slice(&data_bytes)?;
@@ -46,9 +57,7 @@ pub fn load_global_config(state: &mut EditorState) -> Result<()> {
 
 pub fn save_global_config(state: &mut EditorState) -> Result<()> {
     if state.global_config.modified {
-        state.disable_watch_file_changes()?;
         save_json(&state.global_config_path, &state.global_config)?;
-        state.enable_watch_file_changes()?;
         state.global_config.modified = false;
     }
     Ok(())
@@ -63,6 +72,31 @@ fn get_project_dir(state: &EditorState) -> Result<PathBuf> {
         .to_owned())
 }
 
+fn get_project_metadata_path(state: &EditorState) -> Result<PathBuf> {
+    Ok(get_project_dir(state)?.join("Project.json"))
+}
+
+pub fn save_project_metadata(state: &mut EditorState) -> Result<()> {
+    if state.project_metadata.modified {
+        save_json(&get_project_metadata_path(state)?, &state.project_metadata)?;
+        state.project_metadata.modified = false;
+    }
+    Ok(())
+}
+
+// Write the area name table for inclusion in the hack's ASM.
+pub fn save_area_name_table(state: &EditorState) -> Result<PathBuf> {
+    let font = state
+        .project_metadata
+        .label_font
+        .as_ref()
+        .context("Label font not set.")?;
+    let path = get_project_dir(state)?.join("AreaNames.asm");
+    info!("Saving {}", path.display());
+    fs::write(&path, font.name_table_asm(&state.area_names))?;
+    Ok(path)
+}
+
 fn get_palette_dir(state: &EditorState) -> Result<PathBuf> {
     Ok(get_project_dir(state)?.join("Palettes"))
 }
@@ -96,6 +130,41 @@ fn save_palette_colors_png(png_path: &Path, palette: &Palette) -> Result<()> {
     Ok(())
 }
 
+// Export the palette's colors as a PNG: either plain swatches like the `-colors.png` kept in
+// the project, or a reference sheet labeled with the palette name and color values.
+pub fn export_palette_png(png_path: &Path, palette: &Palette, labeled: bool) -> Result<()> {
+    if !labeled {
+        return save_palette_colors_png(png_path, palette);
+    }
+    save_rgb_image_png(png_path, &render_palette_sheet(palette))
+}
+
+// Export a table of the palette's tile metadata.
+pub fn export_tile_table(path: &Path, palette: &Palette, format: TileTableFormat) -> Result<()> {
+    fs::write(path, tile_table(palette, format))
+        .with_context(|| format!("Unable to write {}", path.display()))?;
+    info!("Exported {}", path.display());
+    Ok(())
+}
+
+// Export a printable sheet of the keyboard shortcuts.
+pub fn export_cheat_sheet_png(png_path: &Path, keymap: &[KeyBinding]) -> Result<()> {
+    save_rgb_image_png(png_path, &render_cheat_sheet(keymap))
+}
+
+fn save_rgb_image_png(png_path: &Path, image: &RgbImage) -> Result<()> {
+    let file = File::create(png_path)
+        .with_context(|| format!("Unable to create {}", png_path.display()))?;
+    let w = BufWriter::new(file);
+    let mut encoder = png::Encoder::new(w, image.width as u32, image.height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&image.data)?;
+    info!("Exported {}", png_path.display());
+    Ok(())
+}
+
 fn save_palette_tiles_png(png_path: &Path, palette: &Palette) -> Result<()> {
     let color_bytes: Vec<[u8; 3]> = palette
         .colors
@@ -144,58 +213,128 @@ fn save_palette_tiles_png(png_path: &Path, palette: &Palette) -> Result<()> {
     Ok(())
 }
 
-fn save_palettes(state: &mut EditorState) -> Result<()> {
-    let pal_dir = get_palette_dir(state)?;
-    state.disable_watch_file_changes()?;
-    for pal in &mut state.palettes {
-        if pal.modified {
-            let pal_json_filename = format!("{}.json", pal.name);
-            let pal_json_path = pal_dir.join(pal_json_filename);
-            save_json(&pal_json_path, pal)?;
+// Number of previous versions to keep for each palette:
+const PALETTE_HISTORY_SIZE: usize = 20;
 
-            let pal_colors_png_filename = format!("{}-colors.png", pal.name);
-            let pal_colors_png_path = pal_dir.join(pal_colors_png_filename);
-            save_palette_colors_png(&pal_colors_png_path, pal)?;
+fn get_palette_history_dir(state: &EditorState, id: PaletteId) -> Result<PathBuf> {
+    Ok(get_project_dir(state)?
+        .join("History")
+        .join("Palettes")
+        .join(id.to_string()))
+}
 
-            let pal_tiles_png_filename = format!("{}-tiles.png", pal.name);
-            let pal_tiles_png_path = pal_dir.join(pal_tiles_png_filename);
-            save_palette_tiles_png(&pal_tiles_png_path, pal)?;
+// Keep a copy of the palette JSON that is about to be overwritten. Versions are
+// content-addressed, so saving the same content twice does not create a duplicate.
+// History is keyed by palette ID rather than name, so that it survives renames.
+fn backup_palette(history_dir: &Path, pal_json_path: &Path) -> Result<()> {
+    if !pal_json_path.exists() {
+        return Ok(());
+    }
+    let data = fs::read(pal_json_path)?;
+    let backup_path = history_dir.join(format!("{:016x}.json", content_hash(&data)));
+    fs::create_dir_all(history_dir)?;
+    // Rewrite even if the version already exists, so that its timestamp is refreshed.
+    fs::write(&backup_path, &data)?;
 
-            pal.modified = false;
-        }
+    let mut versions = list_history_files(history_dir)?;
+    for (path, _) in versions.drain(PALETTE_HISTORY_SIZE.min(versions.len())..) {
+        info!("Pruning {}", path.display());
+        fs::remove_file(path)?;
     }
-    state.enable_watch_file_changes()?;
     Ok(())
 }
 
-fn load_palettes(state: &mut EditorState) -> Result<()> {
-    let pal_dir = get_palette_dir(state)?;
-    let pattern = format!("{}/*.json", pal_dir.display());
-    state.palettes.clear();
+// List the JSON files in a history directory, most recent first.
+fn list_history_files(history_dir: &Path) -> Result<Vec<(PathBuf, SystemTime)>> {
+    let pattern = format!("{}/*.json", history_dir.display());
+    let mut out = vec![];
     for entry in glob::glob(&pattern)? {
         let path = entry?;
-        let name = path
-            .file_stem()
-            .context(format!("bad file name: {}", path.display()))?
-            .to_str()
-            .context("bad file stem")?;
-        let mut pal: Palette = load_json(&path)?;
-        pal.name = name.to_owned();
-        state.palettes.push(pal);
+        let time = fs::metadata(&path)?.modified()?;
+        out.push((path, time));
+    }
+    out.sort_by_key(|&(_, time)| std::cmp::Reverse(time));
+    Ok(out)
+}
+
+// The previous versions of a palette, most recent first, without saving anything. A palette with
+// unsaved changes also lists the version last saved, which only goes into the history once it's
+// overwritten.
+pub fn load_palette_history(state: &EditorState, id: PaletteId) -> Result<Vec<PaletteVersion>> {
+    let history_dir = get_palette_history_dir(state, id)?;
+    let mut versions = vec![];
+    for (path, saved_time) in list_history_files(&history_dir)? {
+        let palette: Palette = load_json(&path)?;
+        versions.push(PaletteVersion {
+            saved_time,
+            palette,
+        });
+    }
+    let Some(current) = state.palettes.iter().find(|p| p.id == id) else {
+        return Ok(versions);
+    };
+    let pal_json_path = get_palette_dir(state)?.join(format!("{}.json", current.name));
+    if current.modified && pal_json_path.exists() {
+        let palette: Palette = load_json(&pal_json_path)?;
+        let in_history = versions
+            .iter()
+            .any(|v| v.palette.colors == palette.colors && v.palette.tiles == palette.tiles);
+        if !in_history {
+            let saved_time = fs::metadata(&pal_json_path)?.modified()?;
+            versions.insert(
+                0,
+                PaletteVersion {
+                    saved_time,
+                    palette,
+                },
+            );
+        }
+    }
+    Ok(versions)
+}
+
+fn save_palettes(state: &mut EditorState) -> Result<()> {
+    let pal_dir = get_palette_dir(state)?;
+    for i in 0..state.palettes.len() {
+        if state.palettes[i].modified {
+            let history_dir = get_palette_history_dir(state, state.palettes[i].id)?;
+            let pal_json_filename = format!("{}.json", state.palettes[i].name);
+            let pal_json_path = pal_dir.join(pal_json_filename);
+            if is_load_failure(state, &pal_json_path) {
+                warn!(
+                    "Not saving over {}, which failed to load",
+                    pal_json_path.display()
+                );
+                continue;
+            }
+            let pal = &mut state.palettes[i];
+            let pal_colors_png_path = pal_dir.join(format!("{}-colors.png", pal.name));
+            let pal_tiles_png_path = pal_dir.join(format!("{}-tiles.png", pal.name));
+            for path in [&pal_json_path, &pal_colors_png_path, &pal_tiles_png_path] {
+                state.recent_writes.record(path);
+            }
+            backup_palette(&history_dir, &pal_json_path)?;
+            save_json(&pal_json_path, pal)?;
+            save_palette_colors_png(&pal_colors_png_path, pal)?;
+            save_palette_tiles_png(&pal_tiles_png_path, pal)?;
+            pal.modified = false;
+            let palette_id = pal.id;
+            dirty::mark_palette_unexported(state, palette_id);
+        }
     }
-    ensure_palettes_non_empty(state);
-    update_palette_order(state);
-    state.palette_idx = 0;
     Ok(())
 }
 
 pub fn delete_palette(state: &mut EditorState, name: &str) -> Result<()> {
     let pal_dir = get_palette_dir(state)?;
     let path = pal_dir.join(format!("{}.json", name));
+    // A palette that was added since the last save has no file yet:
+    if !path.exists() {
+        return Ok(());
+    }
     info!("Deleting {}", path.display());
-    state.disable_watch_file_changes()?;
+    state.recent_writes.record(&path);
     std::fs::remove_file(path)?;
-    state.enable_watch_file_changes()?;
     Ok(())
 }
 
@@ -203,6 +342,10 @@ fn get_area_dir(state: &EditorState) -> Result<PathBuf> {
     Ok(get_project_dir(state)?.join("Areas"))
 }
 
+pub fn area_png_path(state: &EditorState, area_id: &AreaId) -> Result<PathBuf> {
+    Ok(area_png_path_in(&get_project_dir(state)?, area_id))
+}
+
 pub fn load_area_list(state: &mut EditorState) -> Result<()> {
     let area_dir = get_area_dir(state)?;
 
@@ -211,6 +354,9 @@ pub fn load_area_list(state: &mut EditorState) -> Result<()> {
     state.area_names.clear();
     for entry in glob::glob(&pattern)? {
         let path = entry?;
+        if is_load_failure(state, &path) {
+            continue;
+        }
         let theme_name = path.file_stem().unwrap().to_str().unwrap();
         let parent_path = path.parent().unwrap();
         let area_name = parent_path.file_name().unwrap().to_owned();
@@ -226,21 +372,152 @@ pub fn load_area_list(state: &mut EditorState) -> Result<()> {
     Ok(())
 }
 
+fn area_json_path_in(project_dir: &Path, area_id: &AreaId) -> PathBuf {
+    project_dir
+        .join("Areas")
+        .join(&area_id.area)
+        .join(format!("{}.json", area_id.theme))
+}
+
+fn area_png_path_in(project_dir: &Path, area_id: &AreaId) -> PathBuf {
+    area_json_path_in(project_dir, area_id).with_extension("png")
+}
+
+pub fn area_json_path(state: &EditorState, area_id: &AreaId) -> Result<PathBuf> {
+    Ok(area_json_path_in(&get_project_dir(state)?, area_id))
+}
+
 pub fn load_area(state: &EditorState, area_id: &AreaId) -> Result<Area> {
-    let area_path = get_area_dir(state)?
-        .join(area_id.area.clone())
-        .join(format!("{}.json", area_id.theme));
+    load_area_in(&get_project_dir(state)?, area_id)
+}
+
+// Load an area of the project in the given directory, for work done apart from the editor state
+// (e.g. by scripts).
+pub fn load_area_in(project_dir: &Path, area_id: &AreaId) -> Result<Area> {
+    let area_path = area_json_path_in(project_dir, area_id);
     let mut area: Area = load_json(&area_path)?;
     area.name = area_id.area.to_owned();
     area.theme = area_id.theme.to_owned();
     Ok(area)
 }
 
+// Metadata embedded in area PNGs as text chunks, so that external scripts (and imports) can
+// tell which area an image shows, and whether it's from this project.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AreaPngMetadata {
+    pub area: AreaName,
+    pub theme: ThemeName,
+    pub project: String,
+    pub editor_version: String,
+    pub palette_ids: Vec<PaletteId>,
+}
+
+const PNG_KEY_AREA: &str = "Area";
+const PNG_KEY_THEME: &str = "Theme";
+const PNG_KEY_PROJECT: &str = "Project";
+const PNG_KEY_SOFTWARE: &str = "Software";
+const PNG_KEY_PALETTES: &str = "Palette IDs";
+const SOFTWARE_NAME: &str = "Z3OverworldEditor";
+
+impl AreaPngMetadata {
+    fn new(project_dir: &Path, area: &Area) -> Self {
+        AreaPngMetadata {
+            area: area.name.clone(),
+            theme: area.theme.clone(),
+            project: project_dir
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            editor_version: env!("CARGO_PKG_VERSION").to_string(),
+            palette_ids: area.get_unique_palettes(),
+        }
+    }
+
+    fn text_chunks(&self) -> Vec<(&'static str, String)> {
+        vec![
+            (PNG_KEY_AREA, self.area.clone()),
+            (PNG_KEY_THEME, self.theme.clone()),
+            (PNG_KEY_PROJECT, self.project.clone()),
+            (
+                PNG_KEY_SOFTWARE,
+                format!("{} {}", SOFTWARE_NAME, self.editor_version),
+            ),
+            (
+                PNG_KEY_PALETTES,
+                self.palette_ids.iter().map(|id| id.to_string()).join(","),
+            ),
+        ]
+    }
+
+    // Read the metadata of an area PNG, or None if the PNG doesn't have any (e.g. from an older
+    // version of the editor, or not written by the editor at all).
+    pub fn read(path: &Path) -> Result<Option<Self>> {
+        let file =
+            File::open(path).with_context(|| format!("Unable to open {}", path.display()))?;
+        let reader = png::Decoder::new(file).read_info()?;
+        let mut metadata = AreaPngMetadata::default();
+        let mut found_software = false;
+        // The metadata is UTF-8 (iTXt), but older versions wrote it as Latin-1 (tEXt):
+        let info = reader.info();
+        let mut chunks = vec![];
+        for chunk in &info.utf8_text {
+            chunks.push((chunk.keyword.clone(), chunk.get_text()?));
+        }
+        for chunk in &info.uncompressed_latin1_text {
+            chunks.push((chunk.keyword.clone(), chunk.text.clone()));
+        }
+        for (keyword, text) in chunks {
+            match keyword.as_str() {
+                PNG_KEY_AREA => metadata.area = text,
+                PNG_KEY_THEME => metadata.theme = text,
+                PNG_KEY_PROJECT => metadata.project = text,
+                PNG_KEY_SOFTWARE => {
+                    let Some(version) = text.strip_prefix(SOFTWARE_NAME) else {
+                        return Ok(None);
+                    };
+                    metadata.editor_version = version.trim().to_string();
+                    found_software = true;
+                }
+                PNG_KEY_PALETTES => {
+                    metadata.palette_ids = text
+                        .split(',')
+                        .filter(|s| !s.is_empty())
+                        .map(|s| s.parse())
+                        .collect::<Result<_, _>>()?;
+                }
+                _ => {}
+            }
+        }
+        Ok(found_software.then_some(metadata))
+    }
+}
+
 pub fn save_area_png(state: &mut EditorState, area_id: &AreaId) -> Result<()> {
+    let project_dir = get_project_dir(state)?;
+    let area_png_path = area_png_path_in(&project_dir, area_id);
+    state.recent_writes.record(&area_png_path);
+    write_area_png(
+        &area_png_path,
+        &project_dir,
+        &state.areas[area_id],
+        &state.palettes,
+        &state.palettes_id_idx_map,
+    )
+}
+
+// Render an area with the given palettes (indexed by `palettes_id_idx_map`), and write it as a
+// PNG. This doesn't need the editor state, so that PNGs can be rendered in the background.
+fn write_area_png(
+    path: &Path,
+    project_dir: &Path,
+    area: &Area,
+    palettes: &[Palette],
+    palettes_id_idx_map: &HashMap<PaletteId, usize>,
+) -> Result<()> {
     let mut color_bytes: Vec<Vec<[u8; 3]>> = vec![];
-    let area = &state.areas[area_id];
-    for i in 0..state.palettes.len() {
-        let mut colors = state.palettes[i].colors.clone();
+    for palette in palettes {
+        let mut colors = palette.colors;
         colors[0] = area.bg_color;
         let cb = colors
             .iter()
@@ -261,17 +538,17 @@ pub fn save_area_png(state: &mut EditorState, area_id: &AreaId) -> Result<()> {
             for ty in 0..32 {
                 for tx in 0..32 {
                     let palette_id = screen.palettes[ty][tx];
-                    let Some(&palette_idx) = state.palettes_id_idx_map.get(&palette_id) else {
+                    let Some(&palette_idx) = palettes_id_idx_map.get(&palette_id) else {
                         // TODO: draw some indicator of the broken tile (due to invalid palette reference)
                         continue;
                     };
                     let tile_idx = screen.tiles[ty][tx];
-                    if tile_idx as usize >= state.palettes[palette_idx].tiles.len() {
+                    if tile_idx as usize >= palettes[palette_idx].tiles.len() {
                         // TODO: draw some indicator of the broken tile (due to invalid palette reference)
                         continue;
                     }
                     let flip = screen.flips[ty][tx];
-                    let tile = state.palettes[palette_idx].tiles[tile_idx as usize];
+                    let tile = palettes[palette_idx].tiles[tile_idx as usize].exported();
                     let tile = flip.apply_to_tile(tile);
                     let cb = &color_bytes[palette_idx];
                     let mut tile_addr = screen_addr + ty * 8 * row_stride + tx * 8 * col_stride;
@@ -291,35 +568,182 @@ pub fn save_area_png(state: &mut EditorState, area_id: &AreaId) -> Result<()> {
         }
     }
 
-    let area_dir = get_area_dir(state)?;
-    let area_png_filename = format!("{}.png", area.theme);
-    let area_png_path = area_dir.join(&area.name).join(area_png_filename);
-    let file = File::create(&area_png_path).unwrap();
+    let file = File::create(path)?;
     let ref mut w = BufWriter::new(file);
     let mut encoder = png::Encoder::new(w, num_cols as u32, num_rows as u32);
     encoder.set_color(png::ColorType::Rgb);
     encoder.set_depth(png::BitDepth::Eight);
-    let mut writer = encoder.write_header().unwrap();
-    writer.write_image_data(&data).unwrap();
+    for (keyword, text) in AreaPngMetadata::new(project_dir, area).text_chunks() {
+        encoder.add_itxt_chunk(keyword.to_string(), text)?;
+    }
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&data)?;
 
     Ok(())
 }
 
+// Which PNGs to regenerate when rebuilding the project. By default, all area PNGs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RebuildScope {
+    pub area: Option<AreaName>,
+    pub theme: Option<ThemeName>,
+    // Only PNGs that are missing or older than their area's JSON:
+    pub only_stale: bool,
+    // Also the palettes' color and tile PNGs:
+    pub palettes: bool,
+}
+
+fn is_area_png_stale(project_dir: &Path, area_id: &AreaId) -> Result<bool> {
+    let json_path = area_json_path_in(project_dir, area_id);
+    let png_path = area_png_path_in(project_dir, area_id);
+    let Ok(png_metadata) = fs::metadata(&png_path) else {
+        return Ok(true);
+    };
+    let json_modified = fs::metadata(&json_path)?.modified()?;
+    if json_modified > png_metadata.modified()? {
+        return Ok(true);
+    }
+    // A PNG without metadata, or for a different area (e.g. copied from elsewhere):
+    Ok(match AreaPngMetadata::read(&png_path) {
+        Ok(Some(metadata)) => metadata.area != area_id.area || metadata.theme != area_id.theme,
+        _ => true,
+    })
+}
+
+// A copy of what the project's PNGs are rendered from (the palettes, and the open areas with
+// any unsaved changes), so that they can be rebuilt apart from the editor state, e.g. on a
+// background thread (see `project_rebuild`).
+#[derive(Clone)]
+pub struct PngSources {
+    project_dir: PathBuf,
+    palettes: Vec<Palette>,
+    palettes_id_idx_map: HashMap<PaletteId, usize>,
+    open_areas: HashMap<AreaId, Area>,
+    recent_writes: RecentWrites,
+    // Areas within the rebuild's scope:
+    area_ids: Vec<AreaId>,
+    scope: RebuildScope,
+}
+
+impl PngSources {
+    pub fn new(state: &EditorState, scope: &RebuildScope) -> Result<Self> {
+        let mut area_ids = vec![];
+        for theme in &state.theme_names {
+            if scope.theme.as_ref().is_some_and(|t| t != theme) {
+                continue;
+            }
+            for area_name in &state.area_names {
+                if scope.area.as_ref().is_some_and(|a| a != area_name) {
+                    continue;
+                }
+                area_ids.push(AreaId {
+                    theme: theme.clone(),
+                    area: area_name.clone(),
+                });
+            }
+        }
+        Ok(PngSources {
+            project_dir: get_project_dir(state)?,
+            palettes: state.palettes.clone(),
+            palettes_id_idx_map: state.palettes_id_idx_map.clone(),
+            open_areas: state
+                .areas
+                .iter()
+                .map(|(id, area)| (id.clone(), area.clone()))
+                .collect(),
+            recent_writes: state.recent_writes.clone(),
+            area_ids,
+            scope: scope.clone(),
+        })
+    }
+
+    // Number of areas and palettes to rebuild (some of which may be skipped, if up to date).
+    pub fn total(&self) -> usize {
+        self.area_ids.len()
+            + if self.scope.palettes {
+                self.palettes.len()
+            } else {
+                0
+            }
+    }
+
+    // Regenerate the PNGs, returning the number of areas and palettes whose PNGs were written.
+    // `progress` is called with the number of areas and palettes done so far (and can stop the
+    // rebuild by returning an error).
+    pub fn rebuild(&self, mut progress: impl FnMut(usize) -> Result<()>) -> Result<usize> {
+        let mut count = 0;
+        let mut done = 0;
+        for area_id in &self.area_ids {
+            if !self.scope.only_stale || is_area_png_stale(&self.project_dir, area_id)? {
+                let loaded_area;
+                let area = match self.open_areas.get(area_id) {
+                    Some(area) => area,
+                    None => {
+                        loaded_area = load_area_in(&self.project_dir, area_id)?;
+                        &loaded_area
+                    }
+                };
+                let path = area_png_path_in(&self.project_dir, area_id);
+                self.recent_writes.record(&path);
+                write_area_png(
+                    &path,
+                    &self.project_dir,
+                    area,
+                    &self.palettes,
+                    &self.palettes_id_idx_map,
+                )?;
+                count += 1;
+            }
+            done += 1;
+            progress(done)?;
+        }
+        if self.scope.palettes {
+            let pal_dir = self.project_dir.join("Palettes");
+            for palette in &self.palettes {
+                let colors_path = pal_dir.join(format!("{}-colors.png", palette.name));
+                let tiles_path = pal_dir.join(format!("{}-tiles.png", palette.name));
+                self.recent_writes.record(&colors_path);
+                self.recent_writes.record(&tiles_path);
+                save_palette_colors_png(&colors_path, palette)?;
+                save_palette_tiles_png(&tiles_path, palette)?;
+                count += 1;
+                done += 1;
+                progress(done)?;
+            }
+        }
+        info!("Rebuilt the PNGs of {} areas and palettes", count);
+        Ok(count)
+    }
+}
+
+// Regenerate area PNGs (which could be out-of-date, e.g. if a palette were updated or a
+// new theme created), returning the number of PNGs written.
+pub fn rebuild_area_pngs(state: &mut EditorState, scope: &RebuildScope) -> Result<usize> {
+    PngSources::new(state, scope)?.rebuild(|_| Ok(()))
+}
+
 pub fn save_area_json(state: &mut EditorState, area_id: &AreaId) -> Result<()> {
     let area_dir = get_area_dir(state)?;
     let area_json_filename = format!("{}.json", area_id.theme);
     let area_json_path = area_dir.join(&area_id.area).join(area_json_filename);
+    if is_load_failure(state, &area_json_path) {
+        warn!(
+            "Not saving over {}, which failed to load",
+            area_json_path.display()
+        );
+        return Ok(());
+    }
+    state.recent_writes.record(&area_json_path);
     save_json(&area_json_path, &state.areas[area_id])?;
     Ok(())
 }
 
 pub fn save_area(state: &mut EditorState, area_id: &AreaId) -> Result<()> {
     if state.areas[area_id].modified {
-        state.disable_watch_file_changes()?;
         save_area_json(state, area_id)?;
         save_area_png(state, area_id)?;
-        state.enable_watch_file_changes()?;
         state.areas.get_mut(area_id).unwrap().modified = false;
+        dirty::mark_area_unexported(state, area_id);
     }
     Ok(())
 }
@@ -338,9 +762,88 @@ pub fn copy_area_theme(
         old_area_path.display(),
         new_area_path.display()
     );
-    state.disable_watch_file_changes()?;
+    state.recent_writes.record(&new_area_path);
     std::fs::copy(old_area_path, new_area_path)?;
-    state.enable_watch_file_changes()?;
+    Ok(())
+}
+
+// Delays before retrying a rename that failed, e.g. because another program (such as an
+// image viewer on Windows) briefly has one of the files open.
+const RENAME_RETRY_DELAYS: [Duration; 4] = [
+    Duration::from_millis(50),
+    Duration::from_millis(150),
+    Duration::from_millis(400),
+    Duration::from_millis(1000),
+];
+
+// Rename a file or (flat) directory, retrying if it fails, and falling back to copying it
+// and deleting the original. Either everything is renamed or nothing is.
+fn rename_path(old_path: &Path, new_path: &Path) -> Result<()> {
+    let mut result = fs::rename(old_path, new_path);
+    for delay in RENAME_RETRY_DELAYS {
+        if result.is_ok() {
+            return Ok(());
+        }
+        std::thread::sleep(delay);
+        result = fs::rename(old_path, new_path);
+    }
+    let Err(err) = result else {
+        return Ok(());
+    };
+    warn!(
+        "Unable to rename {} to {} ({}), copying instead",
+        old_path.display(),
+        new_path.display(),
+        err
+    );
+    if old_path.is_dir() {
+        copy_dir_and_remove(old_path, new_path)
+    } else {
+        fs::copy(old_path, new_path)
+            .with_context(|| format!("Unable to copy {}", old_path.display()))?;
+        if let Err(e) = fs::remove_file(old_path) {
+            fs::remove_file(new_path)?;
+            bail!(
+                "Unable to rename {}: the file is in use by another program ({}). \
+                 Close it and try again.",
+                old_path.display(),
+                e
+            );
+        }
+        Ok(())
+    }
+}
+
+fn copy_dir_and_remove(old_path: &Path, new_path: &Path) -> Result<()> {
+    fs::create_dir(new_path).with_context(|| format!("Unable to create {}", new_path.display()))?;
+    let mut file_names = vec![];
+    for entry in fs::read_dir(old_path)? {
+        let file_name = entry?.file_name();
+        fs::copy(old_path.join(&file_name), new_path.join(&file_name))
+            .with_context(|| format!("Unable to copy {}", old_path.join(&file_name).display()))?;
+        file_names.push(file_name);
+    }
+    let mut removed = vec![];
+    let mut locked = vec![];
+    for file_name in file_names {
+        match fs::remove_file(old_path.join(&file_name)) {
+            Ok(()) => removed.push(file_name),
+            Err(_) => locked.push(old_path.join(&file_name).display().to_string()),
+        }
+    }
+    if !locked.is_empty() {
+        // Put back what was removed, so the original is left as it was:
+        for file_name in removed {
+            fs::copy(new_path.join(&file_name), old_path.join(&file_name))?;
+        }
+        fs::remove_dir_all(new_path)?;
+        bail!(
+            "Unable to rename {}: in use by another program: {}. Close it and try again.",
+            old_path.display(),
+            locked.join(", ")
+        );
+    }
+    fs::remove_dir(old_path)?;
     Ok(())
 }
 
@@ -352,9 +855,9 @@ pub fn rename_area(state: &mut EditorState, old_name: &str, new_name: &str) -> R
         old_area_path.display(),
         new_area_path.display()
     );
-    state.disable_watch_file_changes()?;
-    std::fs::rename(old_area_path, new_area_path)?;
-    state.enable_watch_file_changes()?;
+    state.recent_writes.record(&old_area_path);
+    state.recent_writes.record(&new_area_path);
+    rename_path(&old_area_path, &new_area_path)?;
     let keys: Vec<AreaId> = state
         .areas
         .keys()
@@ -376,23 +879,38 @@ pub fn rename_area_theme(
     let area_dir = get_area_dir(state)?.join(area_name);
     let old_area_path = area_dir.join(format!("{}.json", old_theme));
     let new_area_path = area_dir.join(format!("{}.json", new_theme));
+    let old_png_path = area_dir.join(format!("{}.png", old_theme));
+    let new_png_path = area_dir.join(format!("{}.png", new_theme));
     info!(
         "Renaming {} to {}",
         old_area_path.display(),
         new_area_path.display()
     );
-    state.disable_watch_file_changes()?;
-    std::fs::rename(old_area_path, new_area_path)?;
-    state.enable_watch_file_changes()?;
-    Ok(())
+    for path in [&old_area_path, &new_area_path, &old_png_path, &new_png_path] {
+        state.recent_writes.record(path);
+    }
+    // The PNG is the file most likely to be held open by another program, so rename it
+    // first, before anything needs to be rolled back:
+    let has_png = old_png_path.exists();
+    let mut result = if has_png {
+        rename_path(&old_png_path, &new_png_path)
+    } else {
+        Ok(())
+    };
+    if result.is_ok() {
+        result = rename_path(&old_area_path, &new_area_path);
+        if result.is_err() && has_png {
+            result = result.and(rename_path(&new_png_path, &old_png_path));
+        }
+    }
+    result
 }
 
 pub fn delete_area(state: &mut EditorState, name: &str) -> Result<()> {
     let area_path = get_area_dir(state)?.join(name);
     info!("Deleting {}", area_path.display());
-    state.disable_watch_file_changes()?;
+    state.recent_writes.record(&area_path);
     std::fs::remove_dir_all(area_path)?;
-    state.enable_watch_file_changes()?;
     let keys: Vec<AreaId> = state
         .areas
         .keys()
@@ -409,9 +927,8 @@ pub fn delete_area_theme(state: &mut EditorState, area_name: &str, theme: &str)
     let area_dir = get_area_dir(state)?.join(area_name);
     let area_path = area_dir.join(format!("{}.json", theme));
     info!("Deleting {}", area_path.display());
-    state.disable_watch_file_changes()?;
+    state.recent_writes.record(&area_path);
     std::fs::remove_file(area_path)?;
-    state.enable_watch_file_changes()?;
     state.areas.remove(&AreaId {
         area: area_name.to_string(),
         theme: theme.to_string(),
@@ -451,18 +968,54 @@ pub fn save_project(state: &mut EditorState) -> Result<()> {
     }
     save_global_config(state)?;
     save_palettes(state)?;
-    save_area(state, &state.main_area_id.clone())?;
-    save_area(state, &state.side_area_id.clone())?;
+    // Besides the main and side areas, this includes modified areas kept loaded with manual saving
+    // (see `cleanup_areas`):
+    let area_ids: Vec<AreaId> = state.areas.keys().cloned().collect();
+    for area_id in area_ids {
+        // Keep the saved version of an area while it's being compared with (the area is saved
+        // once the comparison is closed):
+        let compared = state
+            .area_diff
+            .as_ref()
+            .is_some_and(|d| d.area_id == area_id && d.base == DiffBase::Saved);
+        if !compared {
+            save_area(state, &area_id)?;
+        }
+    }
+    state.cleanup_areas()?;
+    // Saved last, as saving the areas and palettes records them as unexported:
+    save_project_metadata(state)?;
     Ok(())
 }
 
+// File written periodically to check that the watcher is still delivering events.
+const WATCHER_PROBE_NAME: &str = ".watcher-probe";
+
+// If wall-clock time advances by this much more than monotonic time between two
+// health checks, assume the system was asleep in between.
+const SLEEP_DETECTION_SLACK: Duration = Duration::from_secs(5);
+
+// Number of consecutive unseen probes before the watcher is considered stalled.
+// (A single probe event can occasionally be dropped by the OS.)
+const MAX_MISSED_PROBES: u32 = 2;
+
 struct FileModificationHandler {
-    modified: Arc<Mutex<bool>>,
+    changed_files: Arc<Mutex<BTreeSet<PathBuf>>>,
+    probe_seen: Arc<Mutex<bool>>,
+    recent_writes: RecentWrites,
 }
 
 impl FileModificationHandler {
-    fn new(modified: Arc<Mutex<bool>>) -> Self {
-        FileModificationHandler { modified }
+    fn new(
+        changed_files: Arc<Mutex<BTreeSet<PathBuf>>>,
+        probe_seen: Arc<Mutex<bool>>,
+        recent_writes: RecentWrites,
+    ) -> Self {
+        FileModificationHandler {
+            changed_files,
+            probe_seen,
+            recent_writes,
+        }
     }
 }
 
@@ -471,44 +1024,237 @@ impl EventHandler for FileModificationHandler {
         let Ok(e) = event else {
             return;
         };
-        match e.kind {
-            notify::EventKind::Modify(_) => {
-                let mut data = self.modified.lock().unwrap();
-                *data = true;
+        let is_probe = !e.paths.is_empty()
+            && e.paths
+                .iter()
+                .all(|p| p.file_name() == Some(OsStr::new(WATCHER_PROBE_NAME)));
+        if is_probe {
+            *self.probe_seen.lock().unwrap() = true;
+            return;
+        }
+        if let notify::EventKind::Modify(_) = e.kind {
+            // Changes from the editor's own saves are ignored:
+            let mut changed_files = self.changed_files.lock().unwrap();
+            for path in e.paths {
+                if !self.recent_writes.is_own_write(&path) {
+                    changed_files.insert(path);
+                }
             }
-            _ => {}
         }
     }
 }
 
+fn restart_watcher(state: &mut EditorState) -> Result<()> {
+    // Dropping the old watcher unregisters its watches.
+    state.watcher = None;
+    state.watcher = Some(recommended_watcher(FileModificationHandler::new(
+        state.changed_files.clone(),
+        state.watcher_probe_seen.clone(),
+        state.recent_writes.clone(),
+    ))?);
+    state.watch_enabled = false;
+    state.enable_watch_file_changes()?;
+    Ok(())
+}
+
+// Periodic check that the watcher is still working, since after the system sleeps
+// it can stop delivering events without reporting any error. The watcher is restarted
+// after a resume from sleep, or if the probe file written by the previous checks
+// went unnoticed.
+pub fn check_watcher(state: &mut EditorState) -> Result<()> {
+    if state.watcher.is_none() || !state.watch_enabled {
+        return Ok(());
+    }
+    let now = (SystemTime::now(), Instant::now());
+    let status = &mut state.watcher_status;
+    let mut restart_reason = None;
+    if let Some((last_wall_time, last_instant)) = status.last_check {
+        let wall_elapsed = now.0.duration_since(last_wall_time).unwrap_or_default();
+        if wall_elapsed > now.1.duration_since(last_instant) + SLEEP_DETECTION_SLACK {
+            restart_reason = Some("resume from sleep");
+        }
+    }
+    if status.probe_pending && !*state.watcher_probe_seen.lock().unwrap() {
+        status.missed_probes += 1;
+        if status.missed_probes >= MAX_MISSED_PROBES {
+            restart_reason = restart_reason.or(Some("file events stopped arriving"));
+        }
+    } else {
+        status.missed_probes = 0;
+    }
+    status.last_check = Some(now);
+
+    if let Some(reason) = restart_reason {
+        info!("Restarting file watcher after {}", reason);
+        restart_watcher(state)?;
+        state.watcher_status.last_restart = Some((now.0, reason.to_string()));
+        state.watcher_status.missed_probes = 0;
+    }
+
+    // Touch the probe file; the watcher should report it before the next check.
+    *state.watcher_probe_seen.lock().unwrap() = false;
+    let probe_path = get_area_dir(state)?.join(WATCHER_PROBE_NAME);
+    let result = fs::write(&probe_path, format!("{:?}", now.0));
+    if let Err(e) = &result {
+        info!("Unable to write {}: {}", probe_path.display(), e);
+    }
+    state.watcher_status.probe_pending = result.is_ok();
+    Ok(())
+}
+
+// A project's files as read from disk (possibly on a background thread, see `project_load`),
+// before they are applied to the editor state.
+#[derive(Clone)]
+pub struct ProjectFiles {
+    pub dir: PathBuf,
+    metadata: ProjectMetadata,
+    // Each palette, or the error loading it:
+    palettes: Vec<(PathBuf, Result<Palette, String>)>,
+    // Area JSONs that failed to load, which are left out of the area list:
+    area_failures: Vec<(PathBuf, String)>,
+}
+
+impl std::fmt::Debug for ProjectFiles {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Leave out the palette contents, which would flood the session log:
+        f.debug_struct("ProjectFiles")
+            .field("dir", &self.dir)
+            .field("palettes", &self.palettes.len())
+            .field("area_failures", &self.area_failures.len())
+            .finish()
+    }
+}
+
+// Read the project metadata and palettes, and check that each area JSON loads, calling
+// `progress` with the number of palettes and areas read so far (which can stop the load by
+// returning an error).
+pub fn read_project_files(
+    dir: &Path,
+    mut progress: impl FnMut(usize, usize) -> Result<()>,
+) -> Result<ProjectFiles> {
+    if !dir.exists() {
+        bail!("Project directory does not exist: {}", dir.display());
+    }
+    let metadata_path = dir.join("Project.json");
+    let metadata = if metadata_path.exists() {
+        load_json(&metadata_path)?
+    } else {
+        ProjectMetadata::default()
+    };
+
+    let mut palettes = vec![];
+    let pattern = format!("{}/*.json", dir.join("Palettes").display());
+    for entry in glob::glob(&pattern)? {
+        let path = entry?;
+        let name = path
+            .file_stem()
+            .context(format!("bad file name: {}", path.display()))?
+            .to_str()
+            .context("bad file stem")?
+            .to_owned();
+        let palette = load_json::<Palette>(&path)
+            .map(|mut pal| {
+                pal.name = name;
+                pal
+            })
+            .map_err(|e| e.to_string());
+        palettes.push((path, palette));
+        progress(palettes.len(), 0)?;
+    }
+
+    let mut area_failures = vec![];
+    let pattern = format!("{}/*/*.json", dir.join("Areas").display());
+    for (i, entry) in glob::glob(&pattern)?.enumerate() {
+        let path = entry?;
+        if let Err(e) = load_json::<Area>(&path) {
+            area_failures.push((path, e.to_string()));
+        }
+        progress(palettes.len(), i + 1)?;
+    }
+    Ok(ProjectFiles {
+        dir: dir.to_owned(),
+        metadata,
+        palettes,
+        area_failures,
+    })
+}
+
 pub fn load_project(state: &mut EditorState) -> Result<()> {
-    if !state.global_config.project_dir.as_ref().unwrap().exists() {
-        bail!(
-            "Project directory does not exist: {}",
-            state.global_config.project_dir.as_ref().unwrap().display()
-        );
+    let dir = get_project_dir(state)?;
+    let files = read_project_files(&dir, |_, _| Ok(()))?;
+    open_project_files(state, files)
+}
+
+// Discard unsaved changes, reloading the project from disk while keeping the same areas open
+// (where they still exist).
+pub fn revert_project(state: &mut EditorState) -> Result<()> {
+    let area_ids = [
+        (AreaPosition::Main, state.main_area_id.clone()),
+        (AreaPosition::Side, state.side_area_id.clone()),
+    ];
+    // Otherwise the changes would be saved as the areas are unloaded:
+    for area in state.areas.values_mut() {
+        area.modified = false;
     }
+    load_project(state)?;
+    state.areas.clear();
+    for (position, area_id) in area_ids {
+        let area_id = if load_area(state, &area_id).is_ok() {
+            area_id
+        } else {
+            state.area_id(position).clone()
+        };
+        state.switch_area(position, &area_id)?;
+    }
+    Ok(())
+}
 
+// Open a project from its files (read by `read_project_files`), recording the files that failed
+// to load and opening the first area that loads.
+pub fn open_project_files(state: &mut EditorState, files: ProjectFiles) -> Result<()> {
     // Set up watcher on the project directories:
     let watch_locations = ["Areas", "Palettes"];
     state.watch_paths.clear();
     for loc in watch_locations {
-        state
-            .watch_paths
-            .push(state.global_config.project_dir.as_ref().unwrap().join(loc));
+        state.watch_paths.push(files.dir.join(loc));
     }
-    state.watcher = Some(recommended_watcher(FileModificationHandler::new(
-        state.files_modified_notification.clone(),
-    ))?);
-    state.watch_enabled = false;
-    state.enable_watch_file_changes()?;
+    state.watcher_status = WatcherStatus::default();
+    restart_watcher(state)?;
 
-    load_palettes(state)?;
+    state.project_metadata = files.metadata;
+    state.load_failures.clear();
+    state.external_changes.clear();
+    // A palette that fails to load is left out, so the rest of the project stays usable:
+    state.palettes.clear();
+    for (path, palette) in files.palettes {
+        match palette {
+            Ok(pal) => state.palettes.push(pal),
+            Err(e) => record_load_failure::<Palette>(state, &path, e)?,
+        }
+    }
+    ensure_palettes_non_empty(state);
+    // The previous index may be out of range, if fewer palettes loaded:
+    state.palette_idx = 0;
+    update_palette_order(state);
+    for (path, e) in files.area_failures {
+        record_load_failure::<Area>(state, &path, e)?;
+    }
     load_area_list(state)?;
-    let area_id = AreaId {
-        area: state.area_names[0].clone(),
-        theme: state.theme_names[0].clone(),
-    };
+    // Open the first area that loads (an area may still have a broken JSON for some themes):
+    let area_id = state
+        .theme_names
+        .iter()
+        .flat_map(|theme| {
+            state.area_names.iter().map(|area| AreaId {
+                area: area.clone(),
+                theme: theme.clone(),
+            })
+        })
+        .find(|area_id| load_area(state, area_id).is_ok())
+        .unwrap_or(AreaId {
+            area: state.area_names[0].clone(),
+            theme: state.theme_names[0].clone(),
+        });
     state.switch_area(AreaPosition::Main, &area_id)?;
     state.switch_area(AreaPosition::Side, &area_id)?;
     state.palette_idx = 0;
@@ -518,3 +1264,217 @@ pub fn load_project(state: &mut EditorState) -> Result<()> {
     state.redo_stack.clear();
     Ok(())
 }
+
+// A project file that failed to load (e.g. containing merge conflict markers, or truncated),
+// which was left out of the project so that the rest of it could still be used.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoadFailure {
+    pub path: PathBuf,
+    pub error: String,
+    // Ways of replacing the file with a version that loads, in order of preference:
+    pub restore_options: Vec<RestoreOption>,
+    // Dismissed by the user, leaving the file out of the project until it is fixed:
+    pub skipped: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictSide {
+    Ours,
+    Theirs,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RestoreOption {
+    // Resolve every merge conflict in the file by keeping one side:
+    ConflictSide(ConflictSide),
+    // A previous version from the project's History folder:
+    History {
+        path: PathBuf,
+        saved_time: SystemTime,
+    },
+    // A copy in the project's Trash folder (e.g. from a previous recovery):
+    Trash(PathBuf),
+}
+
+impl std::fmt::Display for RestoreOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RestoreOption::ConflictSide(ConflictSide::Ours) => write!(f, "Keep our changes"),
+            RestoreOption::ConflictSide(ConflictSide::Theirs) => write!(f, "Keep their changes"),
+            RestoreOption::History { saved_time, .. } => {
+                write!(f, "Restore version saved {}", format_age(*saved_time))
+            }
+            RestoreOption::Trash(_) => write!(f, "Restore copy from Trash"),
+        }
+    }
+}
+
+// Resolve the merge conflicts in a file (as written by git, optionally with the common
+// ancestor in "diff3" style) by keeping one side of each. Returns None if there are none.
+pub fn resolve_conflicts(text: &str, side: ConflictSide) -> Option<String> {
+    #[derive(PartialEq)]
+    enum Section {
+        Outside,
+        Ours,
+        Base,
+        Theirs,
+    }
+    let mut section = Section::Outside;
+    let mut found = false;
+    let mut out = String::new();
+    for line in text.split_inclusive('\n') {
+        if line.starts_with("<<<<<<<") && section == Section::Outside {
+            section = Section::Ours;
+            found = true;
+        } else if line.starts_with("|||||||") && section == Section::Ours {
+            section = Section::Base;
+        } else if line.starts_with("=======")
+            && (section == Section::Ours || section == Section::Base)
+        {
+            section = Section::Theirs;
+        } else if line.starts_with(">>>>>>>") && section == Section::Theirs {
+            section = Section::Outside;
+        } else {
+            let keep = match section {
+                Section::Outside => true,
+                Section::Ours => side == ConflictSide::Ours,
+                Section::Base => false,
+                Section::Theirs => side == ConflictSide::Theirs,
+            };
+            if keep {
+                out.push_str(line);
+            }
+        }
+    }
+    if !found || section != Section::Outside {
+        return None;
+    }
+    Some(out)
+}
+
+fn get_trash_dir(state: &EditorState) -> Result<PathBuf> {
+    Ok(get_project_dir(state)?.join("Trash"))
+}
+
+fn trash_path(state: &EditorState, path: &Path) -> Result<PathBuf> {
+    let project_dir = get_project_dir(state)?;
+    let relative_path = path
+        .strip_prefix(&project_dir)
+        .with_context(|| format!("{} is outside the project directory", path.display()))?;
+    Ok(get_trash_dir(state)?.join(relative_path))
+}
+
+// The palette ID in a palette JSON that may not otherwise parse, for finding its history.
+fn find_palette_id(text: &str) -> Option<PaletteId> {
+    let start = text.find("\"id\"")? + "\"id\"".len();
+    let rest = text[start..].trim_start().strip_prefix(':')?.trim_start();
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+fn parses_as<T: DeserializeOwned>(data: &[u8]) -> bool {
+    serde_json::from_slice::<T>(data).is_ok()
+}
+
+// Ways of restoring a file which would give a version that loads.
+fn find_restore_options<T: DeserializeOwned>(
+    state: &EditorState,
+    path: &Path,
+) -> Result<Vec<RestoreOption>> {
+    let mut options = vec![];
+    let data = fs::read(path).unwrap_or_default();
+    let text = String::from_utf8_lossy(&data);
+    for side in [ConflictSide::Ours, ConflictSide::Theirs] {
+        if let Some(resolved) = resolve_conflicts(&text, side) {
+            if parses_as::<T>(resolved.as_bytes()) {
+                options.push(RestoreOption::ConflictSide(side));
+            }
+        }
+    }
+    if path.starts_with(get_palette_dir(state)?) {
+        if let Some(id) = find_palette_id(&text) {
+            let history_dir = get_palette_history_dir(state, id)?;
+            let history = list_history_files(&history_dir).unwrap_or_default();
+            // Only the most recent version that loads is offered; others are available
+            // afterward from the palette's history.
+            if let Some((path, saved_time)) = history
+                .into_iter()
+                .find(|(path, _)| fs::read(path).is_ok_and(|d| parses_as::<T>(&d)))
+            {
+                options.push(RestoreOption::History { path, saved_time });
+            }
+        }
+    }
+    let trash_path = trash_path(state, path)?;
+    if fs::read(&trash_path).is_ok_and(|d| parses_as::<T>(&d)) {
+        options.push(RestoreOption::Trash(trash_path));
+    }
+    Ok(options)
+}
+
+// Whether a file failed to load (and hasn't been restored since), in which case it shouldn't be
+// saved over, e.g. by a default palette or area created in place of the missing one.
+fn is_load_failure(state: &EditorState, path: &Path) -> bool {
+    state.load_failures.iter().any(|f| f.path == path)
+}
+
+fn record_load_failure<T: DeserializeOwned>(
+    state: &mut EditorState,
+    path: &Path,
+    error: String,
+) -> Result<()> {
+    warn!("Unable to load {}: {}", path.display(), error);
+    let restore_options = find_restore_options::<T>(state, path)?;
+    state.load_failures.push(LoadFailure {
+        path: path.to_owned(),
+        error,
+        restore_options,
+        skipped: false,
+    });
+    Ok(())
+}
+
+// Replace a file that failed to load, moving the broken version to the project's Trash folder
+// (replacing any earlier copy there) rather than deleting it. The project should be reloaded
+// afterward.
+pub fn restore_file(state: &mut EditorState, path: &Path, option: &RestoreOption) -> Result<()> {
+    let data = fs::read(path).with_context(|| format!("Unable to read {}", path.display()))?;
+    let restored = match option {
+        RestoreOption::ConflictSide(side) => {
+            resolve_conflicts(&String::from_utf8_lossy(&data), *side)
+                .context("File no longer has merge conflicts")?
+                .into_bytes()
+        }
+        RestoreOption::History { path, .. } | RestoreOption::Trash(path) => {
+            fs::read(path).with_context(|| format!("Unable to read {}", path.display()))?
+        }
+    };
+    let trash_path = trash_path(state, path)?;
+    info!(
+        "Restoring {}, moving the broken file to {}",
+        path.display(),
+        trash_path.display()
+    );
+    state.recent_writes.record(path);
+    fs::create_dir_all(trash_path.parent().context("invalid parent directory")?)?;
+    fs::write(&trash_path, &data)?;
+    fs::write(path, &restored)?;
+    Ok(())
+}
+
+// Show the folder containing a file in the system's file manager.
+pub fn open_file_location(path: &Path) -> Result<()> {
+    let dir = path.parent().context("invalid parent directory")?;
+    let program = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    std::process::Command::new(program)
+        .arg(dir)
+        .spawn()
+        .with_context(|| format!("Unable to open {}", dir.display()))?;
+    Ok(())
+}
@@ -0,0 +1,66 @@
+// Opening a project in the background, so that the window stays responsive (showing how many
+// palettes and areas have been read) while a large project loads, and the load can be cancelled.
+// The files are read on a background thread, then applied to the editor state all at once.
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use anyhow::bail;
+use iced::{futures::channel::mpsc, Task};
+
+use crate::{message::Message, persist::read_project_files, state::EditorState};
+
+#[derive(Clone, Debug)]
+pub struct ProjectLoad {
+    pub dir: PathBuf,
+    // Number of palettes and areas read so far:
+    pub palettes: usize,
+    pub areas: usize,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ProjectLoad {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+// Start loading the project at `dir`, reporting progress with `Message::ProjectLoadProgress` and
+// the files with `Message::ProjectLoaded`. The current project stays open until then.
+pub fn start(state: &mut EditorState, dir: PathBuf) -> Task<Message> {
+    if let Some(load) = &state.project_load {
+        load.cancel();
+    }
+    let cancelled = Arc::new(AtomicBool::new(false));
+    state.project_load = Some(ProjectLoad {
+        dir: dir.clone(),
+        palettes: 0,
+        areas: 0,
+        cancelled: cancelled.clone(),
+    });
+    let (sender, receiver) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        let files = read_project_files(&dir, |palettes, areas| {
+            if cancelled.load(Ordering::Relaxed) {
+                bail!("Project load cancelled");
+            }
+            let _ = sender.unbounded_send(Message::ProjectLoadProgress {
+                dir: dir.clone(),
+                palettes,
+                areas,
+            });
+            Ok(())
+        });
+        if !cancelled.load(Ordering::Relaxed) {
+            let _ = sender.unbounded_send(Message::ProjectLoaded {
+                dir,
+                files: files.map(Box::new).map_err(|e| format!("{:#}", e)),
+            });
+        }
+    });
+    Task::run(receiver, |message| message)
+}
@@ -0,0 +1,62 @@
+// Rebuilding the project's PNG files in the background, so that the window stays responsive
+// (showing the progress in the rebuild dialogue) while a large project is rebuilt, and the
+// rebuild can be cancelled. The PNGs are rendered on a background thread from a copy of the
+// palettes and open areas, taken when the rebuild starts.
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use anyhow::{bail, Result};
+use iced::{futures::channel::mpsc, Task};
+
+use crate::{
+    message::Message,
+    persist::{PngSources, RebuildScope},
+    state::EditorState,
+};
+
+#[derive(Clone, Debug)]
+pub struct ProjectRebuild {
+    // Number of areas and palettes done so far, out of the total:
+    pub done: usize,
+    pub total: usize,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ProjectRebuild {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+// Start rebuilding the PNGs in the scope, reporting progress with
+// `Message::RebuildProjectProgress` and the result with `Message::RebuildProjectFinished`.
+pub fn start(state: &mut EditorState, scope: &RebuildScope) -> Result<Task<Message>> {
+    if let Some(rebuild) = &state.project_rebuild {
+        rebuild.cancel();
+    }
+    let sources = PngSources::new(state, scope)?;
+    let cancelled = Arc::new(AtomicBool::new(false));
+    state.project_rebuild = Some(ProjectRebuild {
+        done: 0,
+        total: sources.total(),
+        cancelled: cancelled.clone(),
+    });
+    let (sender, receiver) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        let result = sources.rebuild(|done| {
+            if cancelled.load(Ordering::Relaxed) {
+                bail!("Rebuild cancelled");
+            }
+            let _ = sender.unbounded_send(Message::RebuildProjectProgress(done));
+            Ok(())
+        });
+        if !cancelled.load(Ordering::Relaxed) {
+            let _ = sender.unbounded_send(Message::RebuildProjectFinished(
+                result.map_err(|e| format!("{:#}", e)),
+            ));
+        }
+    });
+    Ok(Task::run(receiver, |message| message))
+}
@@ -0,0 +1,186 @@
+// Statistics of the whole project (tiles of each palette and which are unused, palettes of each
+// area, area sizes, and how often each collision type is placed), exported to the project
+// directory for external tools and for planning a hack's use of ROM space. CSV output is one
+// `category,subject,metric,value` record per line, for loading into scripts and spreadsheets.
+use std::{collections::BTreeMap, path::PathBuf};
+
+use anyhow::{Context, Result};
+use hashbrown::HashSet;
+use itertools::Itertools;
+use log::info;
+use serde::Serialize;
+
+use crate::{
+    persist::{load_area, save_json},
+    state::{AreaId, AreaName, CollisionType, EditorState, PaletteId, ThemeName, Tile, TileIdx},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatsFormat {
+    Csv,
+    Json,
+}
+
+impl StatsFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            StatsFormat::Csv => "csv",
+            StatsFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PaletteStats {
+    pub id: PaletteId,
+    pub name: String,
+    // Tiles with graphics (blank tiles are free for new graphics):
+    pub tile_count: usize,
+    pub used_tile_count: usize,
+    // Tiles with graphics that aren't placed in any area of any theme:
+    pub unused_tiles: Vec<TileIdx>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct AreaStats {
+    pub area: AreaName,
+    pub theme: ThemeName,
+    pub size: (u8, u8),
+    pub palettes: Vec<PaletteId>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CollisionStats {
+    pub collision: CollisionType,
+    // Number of placed 8x8 tiles of the collision type, in all areas:
+    pub tiles: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ProjectStats {
+    pub palettes: Vec<PaletteStats>,
+    pub areas: Vec<AreaStats>,
+    // In order of collision type (leaving out those that aren't placed):
+    pub collision: Vec<CollisionStats>,
+}
+
+fn is_blank(tile: &Tile) -> bool {
+    tile.pixels == [[0; 8]; 8]
+}
+
+// Statistics of all areas in all themes, loading those that aren't loaded (without keeping them).
+pub fn project_stats(state: &EditorState) -> Result<ProjectStats> {
+    let mut stats = ProjectStats::default();
+    let mut collision: BTreeMap<CollisionType, usize> = BTreeMap::new();
+    let mut used: HashSet<(PaletteId, TileIdx)> = HashSet::new();
+    for (area_name, theme) in state
+        .area_names
+        .iter()
+        .cartesian_product(&state.theme_names)
+    {
+        let area_id = AreaId {
+            area: area_name.clone(),
+            theme: theme.clone(),
+        };
+        let loaded;
+        let area = match state.areas.get(&area_id) {
+            Some(area) => area,
+            None => {
+                loaded = load_area(state, &area_id)?;
+                &loaded
+            }
+        };
+        let mut palettes = HashSet::new();
+        for screen in &area.screens {
+            for (y, x) in (0..32).cartesian_product(0..32) {
+                let palette_id = screen.palettes[y][x];
+                let tile_idx = screen.tiles[y][x];
+                palettes.insert(palette_id);
+                used.insert((palette_id, tile_idx));
+                let tile = state
+                    .palettes_id_idx_map
+                    .get(&palette_id)
+                    .and_then(|&idx| state.palettes[idx].tiles.get(tile_idx as usize));
+                if let Some(tile) = tile {
+                    *collision.entry(tile.collision).or_default() += 1;
+                }
+            }
+        }
+        stats.areas.push(AreaStats {
+            area: area.name.clone(),
+            theme: area.theme.clone(),
+            size: area.size,
+            palettes: palettes.into_iter().sorted().collect(),
+        });
+    }
+    stats.collision = collision
+        .into_iter()
+        .map(|(collision, tiles)| CollisionStats { collision, tiles })
+        .collect();
+    for palette in &state.palettes {
+        let tiles = palette
+            .tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| !is_blank(t))
+            .map(|(i, _)| i as TileIdx)
+            .collect_vec();
+        let unused_tiles = tiles
+            .iter()
+            .copied()
+            .filter(|&i| !used.contains(&(palette.id, i)))
+            .collect_vec();
+        stats.palettes.push(PaletteStats {
+            id: palette.id,
+            name: palette.name.clone(),
+            tile_count: tiles.len(),
+            used_tile_count: tiles.len() - unused_tiles.len(),
+            unused_tiles,
+        });
+    }
+    Ok(stats)
+}
+
+pub fn stats_csv(stats: &ProjectStats) -> String {
+    let mut out = "category,subject,metric,value\n".to_string();
+    let list = |ids: &[u16]| ids.iter().join(" ");
+    for p in &stats.palettes {
+        let subject = format!("{} {}", p.id, p.name);
+        out += &format!("palette,{},tiles,{}\n", subject, p.tile_count);
+        out += &format!("palette,{},used_tiles,{}\n", subject, p.used_tile_count);
+        out += &format!(
+            "palette,{},unused_tiles,{}\n",
+            subject,
+            list(&p.unused_tiles)
+        );
+    }
+    for a in &stats.areas {
+        let subject = format!("{}/{}", a.area, a.theme);
+        out += &format!("area,{},width,{}\n", subject, a.size.0);
+        out += &format!("area,{},height,{}\n", subject, a.size.1);
+        out += &format!("area,{},palettes,{}\n", subject, list(&a.palettes));
+    }
+    for c in &stats.collision {
+        out += &format!("collision,{},tiles,{}\n", c.collision, c.tiles);
+    }
+    out
+}
+
+// Write the project's statistics to the project directory, returning the file's path.
+pub fn export_stats(state: &EditorState, format: StatsFormat) -> Result<PathBuf> {
+    let stats = project_stats(state)?;
+    let path = state
+        .global_config
+        .project_dir
+        .as_ref()
+        .context("Project directory not set.")?
+        .join(format!("Statistics.{}", format.extension()));
+    match format {
+        StatsFormat::Csv => {
+            info!("Saving {}", path.display());
+            std::fs::write(&path, stats_csv(&stats))?;
+        }
+        StatsFormat::Json => save_json(&path, &stats)?,
+    }
+    Ok(path)
+}
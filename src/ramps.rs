@@ -0,0 +1,36 @@
+// Color ramps: ordered runs of colors (e.g. the shades of a green) that can be shared by
+// several palettes. Editing a ramp's color updates the corresponding color in every
+// palette subscribed to it, to keep recolorings consistent.
+use serde::{Deserialize, Serialize};
+
+use crate::state::{ColorIdx, ColorRGB, PaletteId};
+
+pub const MIN_RAMP_LEN: u8 = 2;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ColorRamp {
+    pub name: String,
+    pub colors: Vec<ColorRGB>,
+    // Palettes using the ramp, with the color index where the ramp starts in each:
+    #[serde(default)]
+    pub subscribers: Vec<RampSubscriber>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RampSubscriber {
+    pub palette_id: PaletteId,
+    pub start: ColorIdx,
+}
+
+impl ColorRamp {
+    // Whether the ramp fits in a palette starting at the given color index.
+    pub fn fits_at(&self, start: ColorIdx) -> bool {
+        start as usize + self.colors.len() <= 16
+    }
+
+    pub fn is_subscribed(&self, palette_id: PaletteId, start: ColorIdx) -> bool {
+        self.subscribers
+            .iter()
+            .any(|s| s.palette_id == palette_id && s.start == start)
+    }
+}
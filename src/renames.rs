@@ -0,0 +1,83 @@
+// Rename manifest: a log of the areas, themes, and palettes renamed in the editor, kept in the
+// project (when enabled in the settings) as Renames.json. Since a rename moves the files of what
+// was renamed, external scripts and collaborators' tools can follow it with the manifest, instead
+// of seeing one file deleted and another added.
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    persist::{load_json, save_json},
+    state::EditorState,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RenameKind {
+    Area,
+    Theme,
+    Palette,
+}
+
+impl std::fmt::Display for RenameKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenameKind::Area => write!(f, "Area"),
+            RenameKind::Theme => write!(f, "Theme"),
+            RenameKind::Palette => write!(f, "Palette"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenameRecord {
+    pub kind: RenameKind,
+    pub old_name: String,
+    pub new_name: String,
+    // Time of the rename, in seconds since the Unix epoch:
+    pub time: u64,
+}
+
+fn renames_path(state: &EditorState) -> Result<PathBuf> {
+    Ok(state
+        .global_config
+        .project_dir
+        .as_ref()
+        .context("Project directory not set.")?
+        .join("Renames.json"))
+}
+
+// The renames recorded in the project, oldest first.
+pub fn load_renames(state: &EditorState) -> Result<Vec<RenameRecord>> {
+    let path = renames_path(state)?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    load_json(&path).with_context(|| format!("Unable to load {}", path.display()))
+}
+
+// Add a rename to the manifest, if the project records them.
+pub fn record_rename(
+    state: &mut EditorState,
+    kind: RenameKind,
+    old_name: &str,
+    new_name: &str,
+) -> Result<()> {
+    if !state.project_metadata.record_renames {
+        return Ok(());
+    }
+    let mut renames = load_renames(state)?;
+    renames.push(RenameRecord {
+        kind,
+        old_name: old_name.to_string(),
+        new_name: new_name.to_string(),
+        time: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+    });
+    let path = renames_path(state)?;
+    state.recent_writes.record(&path);
+    save_json(&path, &renames)
+}
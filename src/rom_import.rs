@@ -0,0 +1,186 @@
+// Importing a ROM without freezing the window. The ROM is read (and its overworld data
+// decompressed) on a background thread, then imported into the project a step at a time, one
+// message per step, so that the dialogue can show the stage and its progress in between and
+// offer to cancel. A cancelled or failed import is rolled back to the project snapshot taken by
+// its undo action. An import can also be previewed, running it up to the point where the project
+// would be saved to see what it would change, then putting the project back as it was.
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use anyhow::{anyhow, Result};
+use iced::{futures::channel::mpsc, Task};
+use log::{error, info};
+
+use crate::{
+    import::{parse_map_ids, ImportMode, ImportStage, Importer, RomData},
+    message::Message,
+    state::{Dialogue, EditorState},
+    update::restore_snapshot,
+};
+
+pub struct RomImport {
+    // None while the ROM is being read:
+    importer: Option<Importer>,
+    preview: bool,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl RomImport {
+    pub fn is_preview(&self) -> bool {
+        self.preview
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    // The current stage, and the fraction of it done so far.
+    pub fn progress(&self, state: &EditorState) -> (ImportStage, f32) {
+        match &self.importer {
+            Some(importer) => importer.progress(state),
+            None => (ImportStage::ReadingRom, 0.0),
+        }
+    }
+}
+
+// Start importing (or previewing the import of) the ROM at `path`, reading it in the background
+// and continuing with `Message::ImportROMRead`.
+pub fn start(state: &mut EditorState, path: PathBuf, preview: bool) -> Task<Message> {
+    if let Some(import) = &state.rom_import {
+        import.cancel();
+    }
+    info!(
+        "Importing from ROM at {} ({:?})",
+        path.display(),
+        state.import_mode
+    );
+    let cancelled = Arc::new(AtomicBool::new(false));
+    state.rom_import = Some(RomImport {
+        importer: None,
+        preview,
+        cancelled: cancelled.clone(),
+    });
+    let (sender, receiver) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        let data = RomData::read(&path);
+        if !cancelled.load(Ordering::Relaxed) {
+            let _ = sender.unbounded_send(Message::ImportROMRead(
+                data.map(Arc::new).map_err(|e| format!("{:#}", e)),
+            ));
+        }
+    });
+    Task::run(receiver, |message| message)
+}
+
+// Begin importing the ROM's data once it has been read.
+pub fn rom_read(
+    state: &mut EditorState,
+    data: &Result<Arc<RomData>, String>,
+) -> Result<Option<Task<Message>>> {
+    // Ignore the data of an import that was cancelled:
+    if state
+        .rom_import
+        .as_ref()
+        .is_none_or(|import| import.importer.is_some())
+    {
+        return Ok(None);
+    }
+    let importer = match data {
+        Ok(data) => Importer::new(state, data.clone(), state.import_mode),
+        Err(e) => Err(anyhow!("{}", e)),
+    }
+    .and_then(|importer| Ok(importer.only_maps(parse_map_ids(&state.import_maps)?)));
+    match importer {
+        Ok(mut importer) => {
+            if state
+                .rom_import
+                .as_ref()
+                .is_some_and(|import| import.preview)
+            {
+                importer = importer.preview(state);
+            }
+            if let Some(import) = &mut state.rom_import {
+                import.importer = Some(importer);
+            }
+            Ok(Some(Task::done(Message::ImportROMStep)))
+        }
+        Err(e) => {
+            fail(state, e)?;
+            Ok(None)
+        }
+    }
+}
+
+// Do the next step of the import, continuing with another `Message::ImportROMStep` until it's
+// finished.
+pub fn step(state: &mut EditorState) -> Result<Option<Task<Message>>> {
+    let Some(mut importer) = state
+        .rom_import
+        .as_mut()
+        .and_then(|import| import.importer.take())
+    else {
+        return Ok(None);
+    };
+    // Loading the imported project would clear the undo history, but the import itself can be
+    // undone, so keep it:
+    let undo_stack = std::mem::take(&mut state.undo_stack);
+    let redo_stack = std::mem::take(&mut state.redo_stack);
+    let result = importer.step(state);
+    state.undo_stack = undo_stack;
+    state.redo_stack = redo_stack;
+    if let Ok(true) = result {
+        let preview = state.rom_import.take().is_some_and(|import| import.preview);
+        let report = importer.into_report();
+        state.dialogue = if preview {
+            Some(Dialogue::ImportPreview(report))
+        } else if state.import_mode == ImportMode::Full && report.rom_differences.is_empty() {
+            None
+        } else {
+            Some(Dialogue::ImportReport(report))
+        };
+        return Ok(None);
+    }
+    if let Some(import) = &mut state.rom_import {
+        import.importer = Some(importer);
+    }
+    match result {
+        Err(e) => {
+            fail(state, e)?;
+            Ok(None)
+        }
+        _ => Ok(Some(Task::done(Message::ImportROMStep))),
+    }
+}
+
+fn fail(state: &mut EditorState, e: anyhow::Error) -> Result<()> {
+    error!("Error importing ROM: {:#}", e);
+    roll_back(state)?;
+    state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+    Ok(())
+}
+
+// Stop an unfinished import, and put the project back as it was before it started.
+pub fn roll_back(state: &mut EditorState) -> Result<()> {
+    let Some(import) = state.rom_import.take() else {
+        return Ok(());
+    };
+    import.cancel();
+    if let Some(importer) = import.importer {
+        importer.abandon(state);
+    }
+    if import.preview {
+        return Ok(());
+    }
+    if let Some((Message::ImportROM, Message::RestoreSnapshot(_))) = state.undo_stack.last() {
+        if let Some((_, Message::RestoreSnapshot(snapshot))) = state.undo_stack.pop() {
+            restore_snapshot(state, &snapshot)?;
+        }
+    }
+    info!("Import rolled back");
+    Ok(())
+}
@@ -0,0 +1,438 @@
+// Scripting console for bulk edits (e.g. replacing a tile with another in a set of areas), in the
+// Rhai language. Scripts work on copies of the project's areas and palettes, through the functions
+// below; when a script finishes without errors, its changes are sent as one batch of messages, so
+// they're undone together. Areas are named as "Name" (in the main area's theme) or "Name/Theme",
+// and positions in areas are in 8x8 tiles.
+//
+//   areas(), themes()                             names of the areas and themes
+//   area_size(area)                               [width, height] in screens
+//   get_tile(area, x, y), set_tile(area, x, y, tile)
+//   get_palette(area, x, y), set_palette(area, x, y, palette_id)
+//   get_flip(area, x, y), set_flip(area, x, y, flip)   (0: none, 1: horizontal, 2: vertical, 3: both)
+//   for_each_screen(area, |sx, sy| ...)           call a function with the position of each screen
+//   palettes()                                    IDs of the palettes
+//   tile_count(palette_id)
+//   get_color(palette_id, i), set_color(palette_id, i, [r, g, b])   (5-bit components)
+//   get_pixel(palette_id, tile, x, y), set_pixel(palette_id, tile, x, y, color_idx)
+//   print(value)                                  add a line to the console output
+use std::{cell::RefCell, collections::BTreeSet, path::PathBuf, rc::Rc};
+
+use anyhow::{bail, Context, Result};
+use hashbrown::HashMap;
+use iced::Point;
+use itertools::Itertools;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, FnPtr, NativeCallContext, INT};
+
+use crate::{
+    area_shapes::AreaCell,
+    message::Message,
+    persist::load_area_in,
+    state::{
+        Area, AreaId, AreaName, EditorState, Flip, Palette, PaletteId, ThemeName, TileCoord,
+        TileIdx,
+    },
+};
+
+// Limit on the work a script can do, so that a script that doesn't end (e.g. with a mistake in a
+// loop) can't freeze the editor:
+const MAX_OPERATIONS: u64 = 500_000_000;
+
+pub const EXAMPLE_SCRIPT: &str = "\
+// Replace tile 5 with tile 6 (of palette 0) in the main area's theme:
+for area in areas() {
+    for_each_screen(area, |sx, sy| {
+        for y in sy * 32..sy * 32 + 32 {
+            for x in sx * 32..sx * 32 + 32 {
+                if get_palette(area, x, y) == 0 && get_tile(area, x, y) == 5 {
+                    set_tile(area, x, y, 6);
+                }
+            }
+        }
+    });
+}
+";
+
+#[derive(Clone, Debug, Default)]
+pub struct ScriptResult {
+    // Printed lines, followed by the error (if any):
+    pub output: Vec<String>,
+    // The script's changes, which are empty if it failed:
+    pub changes: Vec<Message>,
+}
+
+struct World {
+    project_dir: Option<PathBuf>,
+    theme: ThemeName,
+    area_names: Vec<AreaName>,
+    theme_names: Vec<ThemeName>,
+    areas: HashMap<AreaId, Area>,
+    // Positions that the script has set in each area:
+    changed_cells: HashMap<AreaId, BTreeSet<(TileCoord, TileCoord)>>,
+    palettes: Vec<Palette>,
+    palettes_id_idx_map: HashMap<PaletteId, usize>,
+}
+
+fn coord(value: INT) -> Result<TileCoord> {
+    TileCoord::try_from(value)
+        .ok()
+        .context("coordinate out of range")
+}
+
+fn flip_from_int(value: INT) -> Result<Flip> {
+    Ok(match value {
+        0 => Flip::None,
+        1 => Flip::Horizontal,
+        2 => Flip::Vertical,
+        3 => Flip::Both,
+        _ => bail!("invalid flip {} (expected 0-3)", value),
+    })
+}
+
+impl World {
+    fn area_id(&self, name: &str) -> Result<AreaId> {
+        let (area, theme) = match name.rsplit_once('/') {
+            Some((area, theme)) => (area.to_string(), theme.to_string()),
+            None => (name.to_string(), self.theme.clone()),
+        };
+        if !self.area_names.contains(&area) {
+            bail!("unknown area \"{}\"", area);
+        }
+        if !self.theme_names.contains(&theme) {
+            bail!("unknown theme \"{}\"", theme);
+        }
+        Ok(AreaId { area, theme })
+    }
+
+    // The area, loading it on first use.
+    fn area(&mut self, name: &str) -> Result<(AreaId, &mut Area)> {
+        let area_id = self.area_id(name)?;
+        if !self.areas.contains_key(&area_id) {
+            let project_dir = self
+                .project_dir
+                .as_ref()
+                .context("Project directory not set.")?;
+            let area = load_area_in(project_dir, &area_id)?;
+            self.areas.insert(area_id.clone(), area);
+        }
+        let area = self.areas.get_mut(&area_id).unwrap();
+        Ok((area_id, area))
+    }
+
+    fn set_cell(
+        &mut self,
+        name: &str,
+        x: INT,
+        y: INT,
+        f: impl FnOnce(&mut Area, TileCoord, TileCoord) -> Result<()>,
+    ) -> Result<()> {
+        let (x, y) = (coord(x)?, coord(y)?);
+        let (area_id, area) = self.area(name)?;
+        f(area, x, y)?;
+        self.changed_cells
+            .entry(area_id)
+            .or_default()
+            .insert((x, y));
+        Ok(())
+    }
+
+    fn palette(&mut self, id: INT) -> Result<&mut Palette> {
+        let idx = PaletteId::try_from(id)
+            .ok()
+            .and_then(|id| self.palettes_id_idx_map.get(&id))
+            .with_context(|| format!("unknown palette {}", id))?;
+        Ok(&mut self.palettes[*idx])
+    }
+
+    fn pixel(&mut self, palette_id: INT, tile: INT, x: INT, y: INT) -> Result<&mut u8> {
+        let palette = self.palette(palette_id)?;
+        let tile = usize::try_from(tile)
+            .ok()
+            .and_then(|i| palette.tiles.get_mut(i))
+            .with_context(|| format!("tile {} out of range", tile))?;
+        let (x, y) = (usize::try_from(x), usize::try_from(y));
+        match (x, y) {
+            (Ok(x @ 0..8), Ok(y @ 0..8)) => Ok(&mut tile.pixels[y][x]),
+            _ => bail!("pixel coordinates out of range"),
+        }
+    }
+}
+
+type ScriptError = Box<EvalAltResult>;
+
+fn script_err(e: anyhow::Error) -> ScriptError {
+    format!("{:#}", e).into()
+}
+
+fn register_api(engine: &mut Engine, world: &Rc<RefCell<World>>) {
+    let w = world.clone();
+    engine.register_fn("areas", move || -> Array {
+        w.borrow()
+            .area_names
+            .iter()
+            .cloned()
+            .map(Dynamic::from)
+            .collect()
+    });
+    let w = world.clone();
+    engine.register_fn("themes", move || -> Array {
+        w.borrow()
+            .theme_names
+            .iter()
+            .cloned()
+            .map(Dynamic::from)
+            .collect()
+    });
+    let w = world.clone();
+    engine.register_fn(
+        "area_size",
+        move |area: &str| -> Result<Array, ScriptError> {
+            let mut world = w.borrow_mut();
+            let (_, area) = world.area(area).map_err(script_err)?;
+            Ok(vec![
+                (area.size.0 as INT).into(),
+                (area.size.1 as INT).into(),
+            ])
+        },
+    );
+    let w = world.clone();
+    engine.register_fn(
+        "for_each_screen",
+        move |ctx: NativeCallContext, area: &str, f: FnPtr| -> Result<(), ScriptError> {
+            let size = w.borrow_mut().area(area).map_err(script_err)?.1.size;
+            for sy in 0..size.1 as INT {
+                for sx in 0..size.0 as INT {
+                    let _: Dynamic = f.call_within_context(&ctx, (sx, sy))?;
+                }
+            }
+            Ok(())
+        },
+    );
+
+    let w = world.clone();
+    engine.register_fn(
+        "get_tile",
+        move |area: &str, x: INT, y: INT| -> Result<INT, ScriptError> {
+            let (x, y) = (coord(x).map_err(script_err)?, coord(y).map_err(script_err)?);
+            let mut world = w.borrow_mut();
+            let (_, area) = world.area(area).map_err(script_err)?;
+            Ok(area.get_tile(x, y).map_err(script_err)? as INT)
+        },
+    );
+    let w = world.clone();
+    engine.register_fn(
+        "get_palette",
+        move |area: &str, x: INT, y: INT| -> Result<INT, ScriptError> {
+            let (x, y) = (coord(x).map_err(script_err)?, coord(y).map_err(script_err)?);
+            let mut world = w.borrow_mut();
+            let (_, area) = world.area(area).map_err(script_err)?;
+            Ok(area.get_palette(x, y).map_err(script_err)? as INT)
+        },
+    );
+    let w = world.clone();
+    engine.register_fn(
+        "get_flip",
+        move |area: &str, x: INT, y: INT| -> Result<INT, ScriptError> {
+            let (x, y) = (coord(x).map_err(script_err)?, coord(y).map_err(script_err)?);
+            let mut world = w.borrow_mut();
+            let (_, area) = world.area(area).map_err(script_err)?;
+            Ok(area.get_flip(x, y).map_err(script_err)? as INT)
+        },
+    );
+    let w = world.clone();
+    engine.register_fn(
+        "set_tile",
+        move |area: &str, x: INT, y: INT, tile: INT| -> Result<(), ScriptError> {
+            let tile = TileIdx::try_from(tile)
+                .ok()
+                .context("tile out of range")
+                .map_err(script_err)?;
+            w.borrow_mut()
+                .set_cell(area, x, y, |area, x, y| area.set_tile(x, y, tile))
+                .map_err(script_err)
+        },
+    );
+    let w = world.clone();
+    engine.register_fn(
+        "set_palette",
+        move |area: &str, x: INT, y: INT, palette_id: INT| -> Result<(), ScriptError> {
+            let mut world = w.borrow_mut();
+            let palette_id = world.palette(palette_id).map_err(script_err)?.id;
+            world
+                .set_cell(area, x, y, |area, x, y| area.set_palette(x, y, palette_id))
+                .map_err(script_err)
+        },
+    );
+    let w = world.clone();
+    engine.register_fn(
+        "set_flip",
+        move |area: &str, x: INT, y: INT, flip: INT| -> Result<(), ScriptError> {
+            let flip = flip_from_int(flip).map_err(script_err)?;
+            w.borrow_mut()
+                .set_cell(area, x, y, |area, x, y| area.set_flip(x, y, flip))
+                .map_err(script_err)
+        },
+    );
+
+    let w = world.clone();
+    engine.register_fn("palettes", move || -> Array {
+        w.borrow()
+            .palettes
+            .iter()
+            .map(|p| (p.id as INT).into())
+            .collect()
+    });
+    let w = world.clone();
+    engine.register_fn(
+        "tile_count",
+        move |palette_id: INT| -> Result<INT, ScriptError> {
+            let mut world = w.borrow_mut();
+            let palette = world.palette(palette_id).map_err(script_err)?;
+            Ok(palette.tiles.len() as INT)
+        },
+    );
+    let w = world.clone();
+    engine.register_fn(
+        "get_color",
+        move |palette_id: INT, i: INT| -> Result<Array, ScriptError> {
+            let mut world = w.borrow_mut();
+            let palette = world.palette(palette_id).map_err(script_err)?;
+            let color = usize::try_from(i)
+                .ok()
+                .and_then(|i| palette.colors.get(i))
+                .ok_or_else(|| format!("color index {} out of range", i))?;
+            Ok(color.iter().map(|&c| (c as INT).into()).collect())
+        },
+    );
+    let w = world.clone();
+    engine.register_fn(
+        "set_color",
+        move |palette_id: INT, i: INT, rgb: Array| -> Result<(), ScriptError> {
+            let mut color = [0; 3];
+            if rgb.len() != 3 {
+                return Err("expected a color as [r, g, b]".into());
+            }
+            for (c, value) in color.iter_mut().zip(rgb) {
+                *c = match value.as_int() {
+                    Ok(v @ 0..=31) => v as u8,
+                    _ => return Err("color components must be 0-31".into()),
+                };
+            }
+            let mut world = w.borrow_mut();
+            let palette = world.palette(palette_id).map_err(script_err)?;
+            let slot = usize::try_from(i)
+                .ok()
+                .and_then(|i| palette.colors.get_mut(i))
+                .ok_or_else(|| format!("color index {} out of range", i))?;
+            *slot = color;
+            Ok(())
+        },
+    );
+    let w = world.clone();
+    engine.register_fn(
+        "get_pixel",
+        move |palette_id: INT, tile: INT, x: INT, y: INT| -> Result<INT, ScriptError> {
+            let mut world = w.borrow_mut();
+            let pixel = world.pixel(palette_id, tile, x, y).map_err(script_err)?;
+            Ok(*pixel as INT)
+        },
+    );
+    let w = world.clone();
+    engine.register_fn(
+        "set_pixel",
+        move |palette_id: INT,
+              tile: INT,
+              x: INT,
+              y: INT,
+              color_idx: INT|
+              -> Result<(), ScriptError> {
+            let color_idx = match color_idx {
+                0..=15 => color_idx as u8,
+                _ => return Err("color index must be 0-15".into()),
+            };
+            let mut world = w.borrow_mut();
+            *world.pixel(palette_id, tile, x, y).map_err(script_err)? = color_idx;
+            Ok(())
+        },
+    );
+}
+
+// Messages that make the script's changes to the project.
+fn world_changes(state: &EditorState, world: &World) -> Result<Vec<Message>> {
+    let mut changes = vec![];
+    let changed_areas = world
+        .changed_cells
+        .iter()
+        .sorted_by_key(|(id, _)| (&id.area, &id.theme));
+    for (area_id, positions) in changed_areas {
+        let area = &world.areas[area_id];
+        let cells = positions
+            .iter()
+            .map(|&(x, y)| AreaCell::get(area, x, y))
+            .collect::<Result<_>>()?;
+        changes.push(Message::MirrorAreaCells {
+            area_id: area_id.clone(),
+            cells,
+        });
+    }
+    for (old, new) in state.palettes.iter().zip(&world.palettes) {
+        for (i, (&old_color, &color)) in old.colors.iter().zip(&new.colors).enumerate() {
+            if old_color != color {
+                changes.push(Message::BrushColor {
+                    palette_id: new.id,
+                    color_idx: i as u8,
+                    color,
+                });
+            }
+        }
+        for (i, (old_tile, tile)) in old.tiles.iter().zip(&new.tiles).enumerate() {
+            if old_tile.pixels != tile.pixels {
+                changes.push(Message::TilesetBrush {
+                    palette_id: new.id,
+                    coords: Point::new(i as TileIdx % 16, i as TileIdx / 16),
+                    selected_gfx: vec![vec![*tile]],
+                });
+            }
+        }
+    }
+    Ok(changes)
+}
+
+pub fn run_script(state: &EditorState, source: &str) -> ScriptResult {
+    let world = Rc::new(RefCell::new(World {
+        project_dir: state.global_config.project_dir.clone(),
+        theme: state.main_area_id.theme.clone(),
+        area_names: state.area_names.clone(),
+        theme_names: state.theme_names.clone(),
+        // Loaded areas may have unsaved changes, so the script starts from them:
+        areas: state.areas.clone(),
+        changed_cells: HashMap::new(),
+        palettes: state.palettes.clone(),
+        palettes_id_idx_map: state.palettes_id_idx_map.clone(),
+    }));
+    let output = Rc::new(RefCell::new(vec![]));
+
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    let out = output.clone();
+    engine.on_print(move |s| out.borrow_mut().push(s.to_string()));
+    let out = output.clone();
+    engine.on_debug(move |s, _, pos| out.borrow_mut().push(format!("{:?}: {}", pos, s)));
+    register_api(&mut engine, &world);
+
+    let result = engine.run(source);
+    let mut output = output.take();
+    let changes = match result {
+        Ok(()) => world_changes(state, &world.borrow()),
+        Err(e) => Err(anyhow::anyhow!("{}", e)),
+    };
+    match changes {
+        Ok(changes) => ScriptResult { output, changes },
+        Err(e) => {
+            output.push(format!("Error: {:#}", e));
+            ScriptResult {
+                output,
+                changes: vec![],
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+// Overworld secrets: the items hidden under bushes, rocks, and other liftable objects (or
+// revealed by bombing or dashing), listed per area. They're shown as markers over the area like
+// entrances, and positioned on the 16x16 tile grid that the game's secrets table uses.
+use serde::{Deserialize, Serialize};
+
+use crate::{entrances::MARKER_SIZE, state::TileCoord};
+
+// Names of the secret item IDs, as used in the game's overworld secrets table.
+pub const SECRET_ITEMS: [(u8, &str); 27] = [
+    (0x01, "Green rupee"),
+    (0x02, "Rock crab"),
+    (0x03, "Bee"),
+    (0x04, "Random drop"),
+    (0x05, "Bomb"),
+    (0x06, "Heart"),
+    (0x07, "Blue rupee"),
+    (0x08, "Key"),
+    (0x09, "Arrow"),
+    (0x0A, "Bombs"),
+    (0x0B, "Heart (2)"),
+    (0x0C, "Small magic"),
+    (0x0D, "Full magic"),
+    (0x0E, "Cucco"),
+    (0x0F, "Green soldier"),
+    (0x10, "Bush stal"),
+    (0x11, "Blue soldier"),
+    (0x12, "Landmine"),
+    (0x13, "Heart (3)"),
+    (0x14, "Fairy"),
+    (0x15, "Heart (4)"),
+    (0x16, "Nothing"),
+    (0x80, "Hole"),
+    (0x82, "Warp"),
+    (0x84, "Staircase"),
+    (0x86, "Bombable"),
+    (0x88, "Switch"),
+];
+
+pub fn item_name(item: u8) -> String {
+    match SECRET_ITEMS.iter().find(|&&(id, _)| id == item) {
+        Some((_, name)) => name.to_string(),
+        None => format!("Item {:02X}", item),
+    }
+}
+
+// The item ID with the given name (as listed in `SECRET_ITEMS`).
+pub fn item_id(name: &str) -> Option<u8> {
+    SECRET_ITEMS
+        .iter()
+        .find(|&&(_, n)| n == name)
+        .map(|&(id, _)| id)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Secret {
+    // Position of the 16x16 tile within the area, in 8x8 tiles (so always even):
+    pub position: (TileCoord, TileCoord),
+    pub item: u8,
+}
+
+impl Secret {
+    pub fn contains(&self, x: TileCoord, y: TileCoord) -> bool {
+        let (x0, y0) = self.position;
+        (x0..x0 + MARKER_SIZE).contains(&x) && (y0..y0 + MARKER_SIZE).contains(&y)
+    }
+
+    // Short label drawn on the marker, e.g. "S07" for a blue rupee.
+    pub fn label(&self) -> String {
+        format!("S{:02X}", self.item)
+    }
+}
+
+// Snap a position to the 16x16 tile grid.
+pub fn snap_position(x: TileCoord, y: TileCoord) -> (TileCoord, TileCoord) {
+    (x & !1, y & !1)
+}
+
+// The secret at the given position, if any (the last one drawn, if they overlap).
+pub fn secret_at(secrets: &[Secret], x: TileCoord, y: TileCoord) -> Option<usize> {
+    secrets.iter().rposition(|s| s.contains(x, y))
+}
@@ -0,0 +1,103 @@
+// Preview of a sample sprite (Link) drawn over the area at the cursor, for checking which parts of
+// the area cover sprites: as on the SNES, the sprite is hidden behind the non-transparent pixels
+// of tiles with the priority flag (e.g. tree canopies), and drawn over everything else.
+use hashbrown::HashMap;
+use iced::Point;
+
+use crate::state::{Area, Palette, PaletteId};
+
+pub const SPRITE_SIZE: usize = 16;
+// The raster has a pixel of transparent padding around the sprite, like the screen rasters:
+pub const SPRITE_RASTER_SIZE: usize = SPRITE_SIZE + 2;
+
+// Link facing down. Letters stand for the colors below, '.' for transparent pixels.
+const SAMPLE_SPRITE: [&str; SPRITE_SIZE] = [
+    ".....kkkkkk.....",
+    "....kggggggk....",
+    "...kgggggggk....",
+    "..kgghhhhhhgk...",
+    "..khhsshhsshk...",
+    "..khsksssksh.k..",
+    "..kssksssksk....",
+    "...kssssssk.....",
+    "..kgkssssk.gk...",
+    ".kggykkkkyggk...",
+    ".ksgyyyyyygsk...",
+    ".kssgggggggssk..",
+    "..kkgggggggkk...",
+    "...khhhkhhhk....",
+    "...khhk.khhk....",
+    "....kk...kk.....",
+];
+
+fn sprite_color(c: u8) -> Option<[u8; 3]> {
+    match c {
+        b'k' => Some([24, 24, 24]),
+        b'g' => Some([56, 160, 72]),
+        b'h' => Some([144, 88, 40]),
+        b's' => Some([248, 184, 136]),
+        b'y' => Some([232, 200, 48]),
+        _ => None,
+    }
+}
+
+// Top-left pixel of the sprite centered on the given area pixel, kept within the area.
+pub fn sprite_origin(area: &Area, pixel: Point<u16>) -> Point<u16> {
+    let max_x = area.size.0 as u16 * 256 - SPRITE_SIZE as u16;
+    let max_y = area.size.1 as u16 * 256 - SPRITE_SIZE as u16;
+    Point::new(
+        pixel.x.saturating_sub(SPRITE_SIZE as u16 / 2).min(max_x),
+        pixel.y.saturating_sub(SPRITE_SIZE as u16 / 2).min(max_y),
+    )
+}
+
+// Whether a sprite is hidden at the given area pixel, by a tile with priority.
+pub fn sprite_hidden_at(
+    area: &Area,
+    palettes: &[Palette],
+    palettes_id_idx_map: &HashMap<PaletteId, usize>,
+    x: u16,
+    y: u16,
+) -> bool {
+    let (tx, ty) = (x / 8, y / 8);
+    let (Ok(palette_id), Ok(tile_idx), Ok(flip)) = (
+        area.get_palette(tx, ty),
+        area.get_tile(tx, ty),
+        area.get_flip(tx, ty),
+    ) else {
+        return false;
+    };
+    let Some(&palette_idx) = palettes_id_idx_map.get(&palette_id) else {
+        return false;
+    };
+    let Some(&tile) = palettes[palette_idx].tiles.get(tile_idx as usize) else {
+        return false;
+    };
+    let tile = flip.apply_to_tile(tile);
+    tile.priority && tile.pixels[y as usize % 8][x as usize % 8] != 0
+}
+
+// RGBA pixels of the sprite at the given origin, leaving out the pixels that the area covers.
+pub fn sprite_raster(
+    area: &Area,
+    palettes: &[Palette],
+    palettes_id_idx_map: &HashMap<PaletteId, usize>,
+    origin: Point<u16>,
+) -> Vec<u8> {
+    let row_stride = SPRITE_RASTER_SIZE * 4;
+    let mut data = vec![0; SPRITE_RASTER_SIZE * row_stride];
+    for (py, row) in SAMPLE_SPRITE.iter().enumerate() {
+        for (px, c) in row.bytes().enumerate() {
+            let Some([r, g, b]) = sprite_color(c) else {
+                continue;
+            };
+            let (x, y) = (origin.x + px as u16, origin.y + py as u16);
+            if sprite_hidden_at(area, palettes, palettes_id_idx_map, x, y) {
+                continue;
+            }
+            let addr = (py + 1) * row_stride + (px + 1) * 4;
+            data[addr..addr + 4].copy_from_slice(&[r, g, b, 255]);
+        }
+    }
+    data
+}
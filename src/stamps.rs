@@ -0,0 +1,122 @@
+// Project stamps: named blocks of tiles saved from the brush, for building repeated structures
+// (houses, trees, cliffs). Unlike stamps in the library, they refer directly to the project's
+// palettes and tiles, so loading one only sets the brush. Each is stored as Stamps/<name>.json
+// in the project, with a PNG preview alongside it.
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use iced::widget::image::Handle;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    library::{save_thumbnail, selection_stamp, LibraryAsset},
+    persist::{load_json, save_json},
+    state::{EditorState, TileBlock},
+};
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Stamp {
+    #[serde(skip_serializing, skip_deserializing)]
+    pub name: String,
+    pub block: TileBlock,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub preview: Option<Handle>,
+}
+
+pub fn stamps_dir(state: &EditorState) -> Result<PathBuf> {
+    Ok(state
+        .global_config
+        .project_dir
+        .as_ref()
+        .context("Project directory not set.")?
+        .join("Stamps"))
+}
+
+fn stamp_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.json", name))
+}
+
+pub fn preview_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.png", name))
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        bail!("Stamp name is empty");
+    }
+    if name.contains(['/', '\\', ':']) || name.starts_with('.') {
+        bail!("Invalid stamp name: {}", name);
+    }
+    Ok(())
+}
+
+// Save the brush as a stamp, replacing any stamp of the same name.
+pub fn save_stamp(state: &EditorState, name: &str) -> Result<()> {
+    let name = name.trim();
+    validate_name(name)?;
+    let s = &state.selected_tile_block;
+    if s.size.0 == 0 || s.size.1 == 0 {
+        bail!("Select some tiles to save as a stamp");
+    }
+    let dir = stamps_dir(state)?;
+    std::fs::create_dir_all(&dir)
+        .with_context(|| format!("Unable to create stamp folder {}", dir.display()))?;
+    let stamp = Stamp {
+        name: name.to_string(),
+        block: s.clone(),
+        preview: None,
+    };
+    save_json(&stamp_path(&dir, name), &stamp)?;
+    // The preview is drawn the same way as a library stamp's thumbnail:
+    let (palettes, block) = selection_stamp(state)?;
+    save_thumbnail(
+        &preview_path(&dir, name),
+        &LibraryAsset::Stamp { palettes, block },
+    )?;
+    info!("Saved stamp {}", name);
+    Ok(())
+}
+
+// All of the project's stamps, sorted by name. Stamps that can't be read are skipped.
+pub fn load_stamps(state: &EditorState) -> Result<Vec<Stamp>> {
+    let dir = stamps_dir(state)?;
+    let mut stamps = vec![];
+    if !dir.exists() {
+        return Ok(stamps);
+    }
+    let pattern = format!(
+        "{}/*.json",
+        glob::Pattern::escape(&dir.display().to_string())
+    );
+    for entry in glob::glob(&pattern)? {
+        let path = entry?;
+        let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        match load_json::<Stamp>(&path) {
+            Ok(mut stamp) => {
+                stamp.name = name.to_string();
+                stamp.preview = std::fs::read(preview_path(&dir, name))
+                    .ok()
+                    .map(Handle::from_bytes);
+                stamps.push(stamp);
+            }
+            Err(e) => warn!("Skipping stamp {}: {:#}", path.display(), e),
+        }
+    }
+    stamps.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(stamps)
+}
+
+pub fn delete_stamp(state: &EditorState, name: &str) -> Result<()> {
+    validate_name(name)?;
+    let dir = stamps_dir(state)?;
+    std::fs::remove_file(stamp_path(&dir, name))?;
+    let preview = preview_path(&dir, name);
+    if preview.exists() {
+        std::fs::remove_file(preview)?;
+    }
+    info!("Deleted stamp {}", name);
+    Ok(())
+}
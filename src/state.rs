@@ -1,18 +1,70 @@
 use anyhow::{bail, Context, Result};
 use hashbrown::{HashMap, HashSet};
+use itertools::Itertools;
 use log::info;
 use notify::Watcher;
 use serde_repr::{Deserialize_repr, Serialize_repr};
 use std::{
-    path::PathBuf,
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    path::{Path, PathBuf},
     sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
 };
 
+use iced::{
+    widget::{pane_grid, text_editor},
+    Point, Vector,
+};
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    animated_tiles::AnimatedSlot,
+    annotations::ScreenAnnotation,
+    area_diff::AreaDiff,
+    area_manifest::AreaManifestEntry,
+    area_picker::AreaPicker,
+    area_properties::AreaProperties,
+    auto_pan::AutoPan,
+    bug_report::SessionRecorder,
+    compile_check::CompileReport,
+    entrances::Entrance,
+    export::ExportReport,
+    external_changes::ExternalChange,
+    external_tools::ExternalTool,
+    flip_analysis::FlipSuggestion,
+    heatmap::Heatmap,
+    helpers::content_hash,
+    import::{BorrowGraphics, ImportMode, ImportReport, RomInfo},
+    keymap::KeyBinding,
+    labels::LabelFont,
+    library::LibraryItem,
+    macros::EditMacro,
+    map_compression::AreaCompression,
     message::{Message, SelectionSource},
-    persist::{self, load_area, save_area},
+    metatiles::Metatile,
+    palette_adjust::PaletteAdjust,
+    palette_replace::PaletteReplace,
+    palette_slots::PaletteAssignment,
+    panes::{pane_state, Pane, PaneLayout},
+    persist::{self, load_area, save_area, LoadFailure, RebuildScope},
+    project_load::ProjectLoad,
+    project_rebuild::ProjectRebuild,
+    ramps::ColorRamp,
+    renames::RenameRecord,
+    rom_import::RomImport,
+    scripting::EXAMPLE_SCRIPT,
+    secrets::Secret,
+    stamps::Stamp,
+    test_rom::TestRunStage,
+    tile_dedup::DuplicateTile,
+    tile_graphics::GraphicsFormat,
+    tile_table::TileTableFormat,
+    travel::TravelPoint,
+    usages::UsageSearch,
+    validation::ProjectIssue,
+    vram_usage::ScreenUsage,
+    window_state::WindowGeometry,
+    world_map::{WorldMap, WorldPosition},
 };
 
 pub type ColorValue = u8; // Color value (0-31)
@@ -39,9 +91,157 @@ pub struct Tile {
     pub collision: CollisionType,
     pub h_flippable: bool,
     pub v_flippable: bool,
+    // Animated character that the tile is the first frame of (see `animated_tiles`):
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub animated: Option<AnimatedSlot>,
+    // Editor-only helper tile (e.g. an annotation or blocking marker): it's drawn in the editor,
+    // but left out of exported area PNGs and ROM data, as a blank tile.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub editor_only: bool,
     pub pixels: [[ColorIdx; 8]; 8],
 }
 
+// The flags of a tile (everything but its pixels), which can be edited for several tiles at once.
+#[derive(Copy, Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct TileProperties {
+    pub priority: bool,
+    pub collision: CollisionType,
+    pub h_flippable: bool,
+    pub v_flippable: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub animated: Option<AnimatedSlot>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub editor_only: bool,
+}
+
+// Change of one property, applied to all the selected tiles.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TilePropertyChange {
+    Priority(bool),
+    Collision(CollisionType),
+    HFlippable(bool),
+    VFlippable(bool),
+    Animated(Option<AnimatedSlot>),
+    EditorOnly(bool),
+}
+
+impl TilePropertyChange {
+    // New properties of each of the selected tiles. Consecutive tiles get consecutive animated
+    // slots, as the frames of animated graphics are laid out.
+    pub fn apply_to(
+        self,
+        selected: &[(TileIdx, TileProperties)],
+    ) -> Result<Vec<(TileIdx, TileProperties)>> {
+        selected
+            .iter()
+            .enumerate()
+            .map(|(i, &(idx, p))| {
+                let change = match self {
+                    TilePropertyChange::Animated(Some(slot)) => {
+                        let slot = slot
+                            .offset(i)
+                            .with_context(|| format!("Not enough animated slots after {}", slot))?;
+                        TilePropertyChange::Animated(Some(slot))
+                    }
+                    change => change,
+                };
+                Ok((idx, p.with_change(change)))
+            })
+            .collect()
+    }
+}
+
+impl TileProperties {
+    pub fn with_change(mut self, change: TilePropertyChange) -> Self {
+        match change {
+            TilePropertyChange::Priority(x) => self.priority = x,
+            TilePropertyChange::Collision(x) => self.collision = x,
+            TilePropertyChange::HFlippable(x) => self.h_flippable = x,
+            TilePropertyChange::VFlippable(x) => self.v_flippable = x,
+            TilePropertyChange::Animated(x) => self.animated = x,
+            TilePropertyChange::EditorOnly(x) => self.editor_only = x,
+        }
+        self
+    }
+}
+
+impl Tile {
+    pub fn properties(&self) -> TileProperties {
+        TileProperties {
+            priority: self.priority,
+            collision: self.collision,
+            h_flippable: self.h_flippable,
+            v_flippable: self.v_flippable,
+            animated: self.animated,
+            editor_only: self.editor_only,
+        }
+    }
+
+    pub fn set_properties(&mut self, properties: TileProperties) {
+        self.priority = properties.priority;
+        self.collision = properties.collision;
+        self.h_flippable = properties.h_flippable;
+        self.v_flippable = properties.v_flippable;
+        self.animated = properties.animated;
+        self.editor_only = properties.editor_only;
+    }
+
+    // The tile as exported: editor-only tiles are left blank.
+    pub fn exported(&self) -> Tile {
+        if self.editor_only {
+            Tile::default()
+        } else {
+            *self
+        }
+    }
+}
+
+// Which of the game's palette groups a palette is used as, which limits how it can be edited:
+// - HUD palettes are shared by all maps (as BG palette rows 0-1), so their tiles are fixed,
+// - Main, Aux and Animated palettes only have 7 colors (besides the transparent color 0),
+// - Custom palettes (not imported from a ROM) aren't restricted.
+#[derive(Clone, Copy, Serialize, Deserialize, Default, Debug, PartialEq, Eq, Hash)]
+pub enum PaletteCategory {
+    HUD,
+    Main,
+    Aux,
+    Animated,
+    #[default]
+    Custom,
+}
+
+impl PaletteCategory {
+    pub const ALL: [PaletteCategory; 5] = [
+        PaletteCategory::HUD,
+        PaletteCategory::Main,
+        PaletteCategory::Aux,
+        PaletteCategory::Animated,
+        PaletteCategory::Custom,
+    ];
+
+    // Number of colors loaded by the game, after the transparent color 0:
+    pub fn num_colors(self) -> usize {
+        match self {
+            PaletteCategory::HUD | PaletteCategory::Custom => 15,
+            PaletteCategory::Main | PaletteCategory::Aux | PaletteCategory::Animated => 7,
+        }
+    }
+
+    pub fn tiles_editable(self) -> bool {
+        self != PaletteCategory::HUD
+    }
+
+    fn is_custom(&self) -> bool {
+        *self == PaletteCategory::Custom
+    }
+}
+
+impl std::fmt::Display for PaletteCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, Default, Debug)]
 pub struct Palette {
     #[serde(skip_serializing, skip_deserializing)]
@@ -49,10 +249,131 @@ pub struct Palette {
     #[serde(skip_serializing, skip_deserializing)]
     pub name: String,
     pub id: PaletteId,
+    #[serde(default, skip_serializing_if = "PaletteCategory::is_custom")]
+    pub category: PaletteCategory,
+    // Group that the palette is listed under (see `palette_groups`):
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
     pub colors: [ColorRGB; 16],
     pub tiles: Vec<Tile>,
 }
 
+impl Palette {
+    // Problems with the palette's contents that the game couldn't represent, given its category.
+    pub fn category_problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+        let num_colors = self.category.num_colors();
+        if self.colors[num_colors + 1..]
+            .iter()
+            .any(|&c| c != [0, 0, 0])
+        {
+            problems.push(format!(
+                "{} palettes only have colors 1-{}; the other colors aren't exported",
+                self.category, num_colors
+            ));
+        }
+        let high_tiles = self
+            .tiles
+            .iter()
+            .filter(|t| t.pixels.iter().flatten().any(|&c| c as usize > num_colors))
+            .count();
+        if high_tiles > 0 {
+            problems.push(format!(
+                "{} tiles use colors above {}, which {} palettes don't have",
+                high_tiles, num_colors, self.category
+            ));
+        }
+        problems
+    }
+}
+
+// A previously saved version of a palette, kept in the project's History folder.
+#[derive(Clone, Debug)]
+pub struct PaletteVersion {
+    pub saved_time: SystemTime,
+    pub palette: Palette,
+}
+
+// A copy of the project's palettes, metadata, and the areas of one theme, for undoing changes
+// that affect all of them at once (such as importing from a ROM).
+#[derive(Clone)]
+pub struct ProjectSnapshot {
+    pub palettes: Vec<Palette>,
+    pub theme: ThemeName,
+    pub areas: Vec<Area>,
+    pub metadata: ProjectMetadata,
+}
+
+impl std::fmt::Debug for ProjectSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProjectSnapshot")
+            .field("palettes", &self.palettes.len())
+            .field("theme", &self.theme)
+            .field("areas", &self.areas.len())
+            .finish_non_exhaustive()
+    }
+}
+
+// Project-wide data that doesn't belong to any individual palette or area.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct ProjectMetadata {
+    #[serde(skip_serializing, skip_deserializing)]
+    pub modified: bool,
+    // Hashes of each area's map data as of the most recent ROM import, keyed by
+    // "area/theme". This is used to tell which areas have been edited since.
+    #[serde(default)]
+    pub imported_area_hashes: BTreeMap<String, u64>,
+    // Group (world or custom folder) that each area is listed under.
+    #[serde(default)]
+    pub area_groups: BTreeMap<AreaName, String>,
+    // Font for previewing in-game area name labels, if the hack displays them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label_font: Option<LabelFont>,
+    // Color ramps shared between palettes.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub color_ramps: Vec<ColorRamp>,
+    // Recorded edit macros.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub macros: Vec<EditMacro>,
+    // External commands run from the Tools panel (e.g. building the hack or launching it).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub external_tools: Vec<ExternalTool>,
+    // Log renames of areas, themes, and palettes in Renames.json, for external tools to follow.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub record_renames: bool,
+    // Position of each area in the world map, in units of screens.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub world_layout: BTreeMap<AreaName, WorldPosition>,
+    // The ROM that the project was most recently imported from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_rom: Option<RomInfo>,
+    // Project palette that each palette row of the ROM was imported as (keyed by theme, and
+    // then by the row's name, e.g. "Main 0-2"), for exporting the colors back.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rom_palettes: BTreeMap<ThemeName, BTreeMap<String, PaletteId>>,
+    // File of rules applied when importing from a ROM (see `import_rules`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub import_rules: Option<PathBuf>,
+    // Palette that each light world palette is replaced with when generating dark world areas
+    // (see `dark_world`), as pairs of light and dark world palette IDs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dark_world_palettes: Vec<(PaletteId, PaletteId)>,
+    // Named 16x16 and 32x32 blocks of tiles (see `metatiles`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub metatiles: Vec<Metatile>,
+    // Order that palette groups are listed in (see `palette_groups`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub palette_group_order: Vec<String>,
+    // Areas (keyed by "area/theme") and palettes with saved changes that haven't yet been
+    // exported to a ROM (see `dirty`):
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub unexported_areas: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub unexported_palettes: BTreeSet<PaletteId>,
+}
+
+pub const UNGROUPED_AREA_GROUP: &str = "Ungrouped";
+
 #[derive(Serialize, Deserialize, Default, Clone)]
 pub struct GlobalConfig {
     #[serde(skip_serializing, skip_deserializing)]
@@ -62,10 +383,87 @@ pub struct GlobalConfig {
     pub pixel_size: f32,
     #[serde(default = "default_grid_alpha")]
     pub grid_alpha: f32,
+    #[serde(default = "default_overlay_opacity")]
+    pub overlay_opacity: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<WindowGeometry>,
+    // Remember a separate zoom level for each display (identified by its scale factor),
+    // switching to it when the window moves to that display:
+    #[serde(default)]
+    pub per_display_zoom: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub display_pixel_sizes: Vec<DisplayPixelSize>,
+    // Log the editor's messages to a file, for bug reports:
+    #[serde(default)]
+    pub record_session: bool,
+    // Folder of palettes, tilesets, and stamps shared between projects:
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub library_dir: Option<PathBuf>,
+    // Whether the guided tour has been shown (configs from before the tour count as seen):
+    #[serde(default = "default_tutorial_seen")]
+    pub tutorial_seen: bool,
+    // Keyboard shortcuts replacing the default ones (see `keymap::active_keymap`):
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub key_bindings: Vec<KeyBinding>,
+    // Emulator that test ROMs are run with, and the ROM they're exported into (see `test_rom`):
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emulator_path: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_base_rom: Option<PathBuf>,
+    // Splitter positions of the three-pane layout, when it's used (see `panes`):
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pane_layout: Option<PaneLayout>,
+    // Save only on request (or when closing), rather than automatically every second:
+    #[serde(default)]
+    pub manual_save: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct DisplayPixelSize {
+    pub scale_factor: f32,
+    pub pixel_size: f32,
+}
+
+impl GlobalConfig {
+    pub fn display_pixel_size(&self, scale_factor: f32) -> Option<f32> {
+        self.display_pixel_sizes
+            .iter()
+            .find(|d| d.scale_factor == scale_factor)
+            .map(|d| d.pixel_size)
+    }
+
+    // Set the zoom level, remembering it for the display if per-display zoom is enabled.
+    pub fn set_pixel_size(&mut self, pixel_size: f32, scale_factor: Option<f32>) {
+        self.pixel_size = pixel_size;
+        self.modified = true;
+        let Some(scale_factor) = scale_factor else {
+            return;
+        };
+        if !self.per_display_zoom {
+            return;
+        }
+        match self
+            .display_pixel_sizes
+            .iter_mut()
+            .find(|d| d.scale_factor == scale_factor)
+        {
+            Some(d) => d.pixel_size = pixel_size,
+            None => self.display_pixel_sizes.push(DisplayPixelSize {
+                scale_factor,
+                pixel_size,
+            }),
+        }
+    }
 }
 
 pub const MIN_PIXEL_SIZE: f32 = 1.0;
 pub const MAX_PIXEL_SIZE: f32 = 8.0;
+// Zoom levels selected with the keys 1 to 5:
+pub const ZOOM_PRESETS: [f32; 5] = [1.0, 2.0, 3.0, 4.0, 6.0];
+
+fn default_tutorial_seen() -> bool {
+    true
+}
 
 fn default_pixel_size() -> f32 {
     3.0
@@ -75,7 +473,11 @@ fn default_grid_alpha() -> f32 {
     0.1
 }
 
-#[derive(Clone, Copy, Serialize_repr, Deserialize_repr, Default, Debug, PartialEq, Eq)]
+fn default_overlay_opacity() -> f32 {
+    0.7
+}
+
+#[derive(Clone, Copy, Serialize_repr, Deserialize_repr, Default, Debug, PartialEq, Eq, Hash)]
 #[repr(u8)]
 pub enum Flip {
     #[default]
@@ -125,6 +527,16 @@ impl Flip {
         pixels
     }
 
+    // Flip equivalent to applying `self` followed by `other`.
+    pub fn compose(self, other: Flip) -> Flip {
+        match other {
+            Flip::None => self,
+            Flip::Horizontal => self.flip_horizontally(),
+            Flip::Vertical => self.flip_vertically(),
+            Flip::Both => self.flip_horizontally().flip_vertically(),
+        }
+    }
+
     pub fn apply_to_tile(self, mut tile: Tile) -> Tile {
         // TODO: also apply flips to slope collisions
         tile.pixels = self.apply_to_pixels(tile.pixels);
@@ -132,7 +544,17 @@ impl Flip {
     }
 }
 
-#[derive(Serialize, Deserialize, Default)]
+// A tile placed at a given position in an area.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TileUsage {
+    pub area_id: AreaId,
+    pub x: TileCoord,
+    pub y: TileCoord,
+    pub tile_idx: TileIdx,
+    pub flip: Flip,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
 pub struct Screen {
     // X and Y position of the screen (256 x 256 block) within the area, in screen counts:
     // The screens are always listed in row-major order, so `position` is
@@ -141,9 +563,35 @@ pub struct Screen {
     pub palettes: [[PaletteId; 32]; 32],
     pub tiles: [[TileIdx; 32]; 32],
     pub flips: [[Flip; 32]; 32],
+    // Screens that the camera stays within (e.g. single-screen rooms like the Master Sword grove):
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub camera_locked: bool,
+    // Tiles of the second background layer (e.g. the Lost Woods canopy, or fog), in row-major
+    // order of their positions:
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overlay: Vec<OverlayTile>,
+}
+
+// A tile placed on a screen's overlay layer, where color 0 is transparent.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct OverlayTile {
+    // Position within the screen, in 8x8 tiles:
+    pub x: u8,
+    pub y: u8,
+    pub palette: PaletteId,
+    pub tile: TileIdx,
+    pub flip: Flip,
 }
 
-#[derive(Serialize, Deserialize, Default)]
+// The layer of an area that brush strokes and shapes are drawn on.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum Layer {
+    #[default]
+    Main,
+    Overlay,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
 pub struct Area {
     #[serde(skip_serializing, skip_deserializing)]
     pub modified: bool,
@@ -158,6 +606,32 @@ pub struct Area {
     // A 'screen' is a 256x256 pixel section, roughly the size that fits on camera at once.
     // Splitting it up like this helps with formatting of the JSON, e.g. for viewing git diffs.
     pub screens: Vec<Screen>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entrances: Vec<Entrance>,
+    // Items hidden under liftable objects:
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secrets: Vec<Secret>,
+    // Flute spots and whirlpool destinations in the area:
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub travel: Vec<TravelPoint>,
+    // Numbers attached to screens (e.g. enemy density), for tools outside the editor:
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<ScreenAnnotation>,
+    // Music, palette set, and flags:
+    #[serde(default, skip_serializing_if = "AreaProperties::is_empty")]
+    pub properties: AreaProperties,
+}
+
+// Areas are included in messages (e.g. for undoing their deletion), which are logged, so only
+// summarize them rather than listing every tile:
+impl std::fmt::Debug for Area {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Area")
+            .field("name", &self.name)
+            .field("theme", &self.theme)
+            .field("size", &self.size)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Area {
@@ -168,6 +642,40 @@ impl Area {
         }
     }
 
+    // A new area of blank screens (tile 0 of palette 0).
+    pub fn empty(
+        name: AreaName,
+        theme: ThemeName,
+        size: (u8, u8),
+        vanilla_map_id: Option<u8>,
+        bg_color: ColorRGB,
+    ) -> Area {
+        Area {
+            modified: true,
+            name,
+            theme,
+            size,
+            vanilla_map_id,
+            bg_color,
+            screens: (0..size.0)
+                .cartesian_product(0..size.1)
+                .map(|(x, y)| Screen {
+                    position: (x, y),
+                    palettes: [[0; 32]; 32],
+                    tiles: [[0; 32]; 32],
+                    flips: [[Flip::None; 32]; 32],
+                    camera_locked: false,
+                    overlay: vec![],
+                })
+                .collect(),
+            entrances: vec![],
+            secrets: vec![],
+            travel: vec![],
+            annotations: vec![],
+            properties: AreaProperties::default(),
+        }
+    }
+
     pub fn get_screen_coords(&self, x: TileCoord, y: TileCoord) -> Result<(usize, usize, usize)> {
         if x >= self.size.0 as TileCoord * 32 || y >= self.size.1 as TileCoord * 32 {
             bail!("out of range");
@@ -178,6 +686,25 @@ impl Area {
         Ok((screen_i, (x % 32) as usize, (y % 32) as usize))
     }
 
+    // Positions where the tile is used in the area, in row-major order.
+    pub fn tile_usages(&self, palette_id: PaletteId, tile_idx: TileIdx) -> Result<Vec<TileUsage>> {
+        let mut out = vec![];
+        for y in 0..self.size.1 as TileCoord * 32 {
+            for x in 0..self.size.0 as TileCoord * 32 {
+                if self.get_palette(x, y)? == palette_id && self.get_tile(x, y)? == tile_idx {
+                    out.push(TileUsage {
+                        area_id: self.id(),
+                        x,
+                        y,
+                        tile_idx,
+                        flip: self.get_flip(x, y)?,
+                    });
+                }
+            }
+        }
+        Ok(out)
+    }
+
     pub fn get_palette(&self, x: TileCoord, y: TileCoord) -> Result<PaletteId> {
         let (i, sx, sy) = self.get_screen_coords(x, y)?;
         Ok(self.screens[i].palettes[sy][sx])
@@ -211,6 +738,74 @@ impl Area {
         Ok(())
     }
 
+    // The overlay tile at a position, if any.
+    pub fn get_overlay(&self, x: TileCoord, y: TileCoord) -> Result<Option<OverlayTile>> {
+        let (i, sx, sy) = self.get_screen_coords(x, y)?;
+        let overlay = &self.screens[i].overlay;
+        Ok(overlay
+            .iter()
+            .find(|t| (t.x as usize, t.y as usize) == (sx, sy))
+            .copied())
+    }
+
+    // Place a tile on the overlay at a position (whose `x` and `y` are ignored), or remove it.
+    pub fn set_overlay(
+        &mut self,
+        x: TileCoord,
+        y: TileCoord,
+        tile: Option<OverlayTile>,
+    ) -> Result<()> {
+        let (i, sx, sy) = self.get_screen_coords(x, y)?;
+        let overlay = &mut self.screens[i].overlay;
+        let key = (sy as u8, sx as u8);
+        let pos = overlay.binary_search_by_key(&key, |t| (t.y, t.x));
+        match (pos, tile) {
+            (Ok(j), Some(tile)) => {
+                overlay[j] = OverlayTile {
+                    x: key.1,
+                    y: key.0,
+                    ..tile
+                }
+            }
+            (Err(j), Some(tile)) => overlay.insert(
+                j,
+                OverlayTile {
+                    x: key.1,
+                    y: key.0,
+                    ..tile
+                },
+            ),
+            (Ok(j), None) => {
+                overlay.remove(j);
+            }
+            (Err(_), None) => {}
+        }
+        Ok(())
+    }
+
+    // Hash of the area's tile layout, ignoring other properties such as the BG color, and
+    // editor-only markings of the screens such as camera locks.
+    pub fn map_hash(&self) -> u64 {
+        let screens: Vec<Screen> = self
+            .screens
+            .iter()
+            .map(|s| Screen {
+                camera_locked: false,
+                ..s.clone()
+            })
+            .collect();
+        // Entrances and secrets are only included when there are any, so that the hashes of
+        // areas from before they were imported stay the same:
+        let data = if !self.secrets.is_empty() {
+            serde_json::to_vec(&(&self.size, &screens, &self.entrances, &self.secrets)).unwrap()
+        } else if !self.entrances.is_empty() {
+            serde_json::to_vec(&(&self.size, &screens, &self.entrances)).unwrap()
+        } else {
+            serde_json::to_vec(&(&self.size, &screens)).unwrap()
+        };
+        content_hash(&data)
+    }
+
     pub fn get_unique_palettes(&self) -> Vec<PaletteId> {
         let mut palettes: HashSet<PaletteId> = HashSet::new();
         for s in &self.screens {
@@ -226,30 +821,250 @@ impl Area {
     }
 }
 
+// Pick lists which have a right-click context menu.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PickListMenu {
+    Area,
+    Theme,
+    Palette,
+}
+
+// What to do once the unsaved changes have been saved or discarded.
+#[derive(Debug, Clone)]
+pub enum UnsavedChangesAction {
+    CloseWindow(iced::window::Id),
+    OpenProject(PathBuf),
+}
+
 pub enum Dialogue {
     Settings,
     ImportROMConfirm,
     ImportROMProgress,
-    AddPalette { name: String, id: PaletteId },
-    RenamePalette { name: String },
+    AddPalette {
+        name: String,
+        id: PaletteId,
+    },
+    RenamePalette {
+        name: String,
+    },
     DeletePalette,
-    AddArea { name: AreaName, size: (u8, u8) },
-    EditArea { name: AreaName },
+    AddArea {
+        name: AreaName,
+        size: (u8, u8),
+    },
+    CreateAreas {
+        entries: Vec<AreaManifestEntry>,
+        // Number of entries processed so far:
+        done: usize,
+        // Entries that weren't created, with the reason:
+        skipped: Vec<String>,
+    },
+    EditArea {
+        name: AreaName,
+    },
     DeleteArea,
-    AddTheme { name: ThemeName },
-    RenameTheme { name: ThemeName },
+    // Properties of a flute spot or whirlpool destination, clicked in an area view:
+    TravelPoint {
+        position: AreaPosition,
+        idx: usize,
+    },
+    DarkWorld {
+        // Area to create, or to update if it already exists:
+        target: AreaName,
+        exists: bool,
+        // Dark world palette for each light world palette:
+        mapping: BTreeMap<PaletteId, PaletteId>,
+    },
+    DuplicateArea {
+        name: AreaName,
+    },
+    BGColors {
+        // BG color of each area (in the order of `area_names`) in each theme:
+        colors: Vec<Vec<ColorRGB>>,
+        // Theme and color being edited:
+        theme: ThemeName,
+        color: ColorRGB,
+    },
+    AddTheme {
+        name: ThemeName,
+    },
+    RenameTheme {
+        name: ThemeName,
+    },
     DeleteTheme,
     Help,
-    RebuildProject,
+    RebuildProject {
+        scope: RebuildScope,
+    },
     ModifiedReload,
+    LoadRecovery,
+    // Asking whether to save or discard changes before closing the window or opening another
+    // project:
+    UnsavedChanges(UnsavedChangesAction),
+    DiscardChanges,
+    AreaList(AreaPosition, String),
+    AreaPicker(AreaPicker),
+    PaletteGroups {
+        // Group to move palettes to, and new names being typed for groups:
+        group: String,
+        renames: BTreeMap<String, String>,
+    },
+    PaletteHistory(Vec<PaletteVersion>),
+    RenameHistory(Vec<RenameRecord>),
+    // Memory maintenance, with the size of the project on disk:
+    Memory {
+        project_bytes: u64,
+    },
+    Script,
+    ImportReport(ImportReport),
+    // What an import would change, before going ahead with it:
+    ImportPreview(ImportReport),
+    ExportROMProgress,
+    TestRun(TestRunStage),
+    ExportReport(ExportReport),
+    BorrowGraphics(BorrowGraphics),
+    AreaLabels(String),
+    FlipSuggestions(Vec<(FlipSuggestion, bool)>),
+    DuplicateTiles {
+        palette_id: PaletteId,
+        duplicates: Vec<DuplicateTile>,
+    },
+    Macros {
+        // Location to replay the macro at, in the main area:
+        x: TileCoord,
+        y: TileCoord,
+    },
+    ColorRamps {
+        start: ColorIdx,
+        len: u8,
+        // Ramp index and shade being edited:
+        shade: Option<(usize, usize)>,
+    },
+    ExportPalette {
+        palette_id: PaletteId,
+        labeled: bool,
+        // Exporting a table of the tiles' metadata instead of a PNG of the colors:
+        tile_table: Option<TileTableFormat>,
+    },
+    TileGraphics {
+        palette_id: PaletteId,
+        format: GraphicsFormat,
+    },
+    AdjustPalette(PaletteAdjust),
+    Library {
+        items: Vec<LibraryItem>,
+        filter: String,
+        // Name and (comma-separated) tags to publish new items with:
+        name: String,
+        tags: String,
+    },
+    CompileCheck(CompileReport),
+    CompressionEstimate(ThemeName, Vec<AreaCompression>),
+    VramUsage {
+        all_areas: bool,
+        screens: Vec<ScreenUsage>,
+    },
+    PaletteSlots {
+        theme: ThemeName,
+        area: AreaName,
+        // Palettes used by the area that need a position:
+        area_palettes: Vec<PaletteId>,
+        assignment: PaletteAssignment,
+        // Palettes that the solver couldn't find a position for:
+        problems: Vec<String>,
+    },
+    ContextMenu(PickListMenu, Point),
+    Error(String),
+}
+
+// Results of the periodic file watcher health checks.
+#[derive(Default, Debug)]
+pub struct WatcherStatus {
+    // Wall-clock and monotonic time of the last check, used to detect sleep/resume:
+    pub last_check: Option<(SystemTime, Instant)>,
+    pub probe_pending: bool,
+    pub missed_probes: u32,
+    pub last_restart: Option<(SystemTime, String)>,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Eq)]
+// How long after the editor writes a file the watcher attributes its events to that write.
+// (External changes to the same file within this window go unnoticed.)
+const OWN_WRITE_WINDOW: Duration = Duration::from_secs(5);
+
+// Files recently written, renamed or deleted by the editor, so that the watcher can ignore the
+// events from them while still watching for external changes.
+#[derive(Clone, Default)]
+pub struct RecentWrites(Arc<Mutex<HashMap<PathBuf, Instant>>>);
+
+impl RecentWrites {
+    // Record a path (file or directory) before writing to it.
+    pub fn record(&self, path: &Path) {
+        let now = Instant::now();
+        let mut paths = self.0.lock().unwrap();
+        paths.retain(|_, &mut t| now.duration_since(t) < OWN_WRITE_WINDOW);
+        paths.insert(normalize_path(path), now);
+    }
+
+    // Whether a changed path is (inside) one recently written by the editor.
+    pub fn is_own_write(&self, path: &Path) -> bool {
+        let now = Instant::now();
+        let paths = self.0.lock().unwrap();
+        normalize_path(path).ancestors().any(|p| {
+            paths
+                .get(p)
+                .is_some_and(|&t| now.duration_since(t) < OWN_WRITE_WINDOW)
+        })
+    }
+}
+
+// Canonical form of a path that may not exist (yet or anymore), so that paths from the editor
+// compare equal to the paths reported by the watcher (which may resolve symlinks).
+fn normalize_path(path: &Path) -> PathBuf {
+    for ancestor in path.ancestors() {
+        if let Ok(canonical) = ancestor.canonicalize() {
+            return canonical.join(path.strip_prefix(ancestor).unwrap());
+        }
+    }
+    path.to_owned()
+}
+
+// How long the update handler took to process a message.
+#[derive(Clone, Debug)]
+pub struct UpdateTiming {
+    pub name: String,
+    pub duration: Duration,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TileBlock {
     pub size: (TileCoord, TileCoord),
     pub palettes: Vec<Vec<PaletteId>>,
     pub tiles: Vec<Vec<TileIdx>>,
     pub flips: Vec<Vec<Flip>>,
+    // Optional brush shape: cells that are `false` are skipped when brushing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mask: Option<Vec<Vec<bool>>>,
+}
+
+impl TileBlock {
+    pub fn covers(&self, x: usize, y: usize) -> bool {
+        match &self.mask {
+            Some(mask) => mask[y][x],
+            None => true,
+        }
+    }
+
+    pub fn toggle_mask(&mut self, x: usize, y: usize) {
+        let (w, h) = (self.size.0 as usize, self.size.1 as usize);
+        if x >= w || y >= h {
+            return;
+        }
+        let mask = self.mask.get_or_insert_with(|| vec![vec![true; w]; h]);
+        mask[y][x] = !mask[y][x];
+        if mask.iter().all(|row| row.iter().all(|&b| b)) {
+            self.mask = None;
+        }
+    }
 }
 
 // At the moment, Iced's support for tracking widget focus is fairly incomplete,
@@ -273,6 +1088,13 @@ pub enum SidePanelView {
     #[default]
     Tileset,
     Area,
+    Usages,
+    Issues,
+    Metatiles,
+    Stamps,
+    Secrets,
+    Annotations,
+    ExternalTools,
 }
 
 #[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
@@ -282,11 +1104,25 @@ pub enum AreaPosition {
     Side,
 }
 
+// Guide line across an area, placed by clicking on a ruler:
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Guide {
+    Vertical(TileCoord),
+    Horizontal(TileCoord),
+}
+
 #[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
 pub enum Tool {
     #[default]
     Select,
     Brush,
+    Rectangle,
+    Line,
+    Fill,
+    // Selects the region of matching tiles clicked on, as a brush masked to the region's shape:
+    MagicWand,
+    // Paints the collision type of the tiles clicked on:
+    Collision,
 }
 
 pub struct EditorState {
@@ -303,20 +1139,65 @@ pub struct EditorState {
     // Undo functionality:
     pub undo_stack: Vec<(Message, Message)>,
     pub redo_stack: Vec<(Message, Message)>,
+    // The last change is temporarily undone, to preview it (and will be redone when the
+    // preview ends, unless the undo is committed):
+    pub undo_preview: bool,
+    // Edits recorded so far, while recording a macro:
+    pub macro_recording: Option<Vec<Message>>,
+    // Scale factor of the display that the window is on, once known:
+    pub scale_factor: Option<f32>,
+
+    pub project_metadata: ProjectMetadata,
 
     // Settings-related data:
     pub rom_path: Option<PathBuf>,
+    pub import_mode: ImportMode,
+    // Parent maps to import, as typed in the import dialogue (empty for all):
+    pub import_maps: String,
+    pub export_path: Option<PathBuf>,
 
     // General editing state:
     pub focus: Focus,
     pub tool: Tool,
     pub palette_only_brush: bool,
     pub side_panel_view: SidePanelView,
+    // Metatile selected for editing in the side panel, and the one being stamped with the brush:
+    pub metatile_idx: Option<usize>,
+    pub stamp_metatile: Option<usize>,
+    // The project's stamps, as listed in the side panel (loaded when it's shown), and the name
+    // to save the brush as:
+    pub stamps: Vec<Stamp>,
+    pub stamp_name: String,
+    pub usage_search: Option<UsageSearch>,
+    // Issues found by the last project validation (none while it's running):
+    pub project_issues: Option<Vec<ProjectIssue>>,
+    // Current step of the guided tour, while it is showing:
+    pub tutorial_step: Option<usize>,
+    // The side panel is hidden to make room for the main area (keeping `side_panel_view`
+    // for when it's shown again):
+    pub side_panel_hidden: bool,
+    // Panes of the three-pane layout (kept even when it isn't used, for when it's turned on):
+    pub panes: pane_grid::State<Pane>,
+    // Showing the world map (instead of the main area):
+    pub world_map: Option<WorldMap>,
+    pub session_recorder: Option<SessionRecorder>,
 
     // Palette editing state:
     pub palette_idx: PaletteIdx,
+    // Filter text and group of the palettes listed in the palette panel:
+    pub palette_filter: String,
+    pub palette_group_filter: Option<String>,
     pub color_idx: Option<ColorIdx>,
     pub selected_color: ColorRGB,
+    // Text being entered as the selected color's SNES word, with the color it was entered for
+    // (so that it's replaced once the color is changed otherwise):
+    pub snes_color_text: Option<(ColorRGB, String)>,
+    pub rgb_hex_text: Option<(ColorRGB, String)>,
+    // Hue, saturation, and value last picked in the color picker, with the color they gave (so
+    // that the hue of grays, and the exact position picked, are kept while it's unchanged):
+    pub picked_hsv: Option<(ColorRGB, (f32, f32, f32))>,
+    // Picking the selected color from the next click on an area:
+    pub eyedropper: bool,
     pub identify_color: bool,
 
     // Tile editing state:
@@ -335,21 +1216,107 @@ pub struct EditorState {
     pub selected_tile_block: TileBlock,
     pub selected_gfx: Vec<Vec<Tile>>,
     pub show_grid: bool,
+    pub show_bg_color_editor: bool,
+    pub heatmap: Option<Heatmap>,
+    // Comparison of the main area with another version, highlighting the changed tiles:
+    pub area_diff: Option<AreaDiff>,
+    // Replacement of one palette with another in the main area, being set up (with the tiles
+    // that it would change highlighted):
+    pub palette_replace: Option<PaletteReplace>,
+    // A project being opened in the background:
+    pub project_load: Option<ProjectLoad>,
+    // PNG files being rebuilt in the background:
+    pub project_rebuild: Option<ProjectRebuild>,
+    // A ROM being imported, a step at a time:
+    pub rom_import: Option<RomImport>,
+    pub collapsed_area_groups: HashSet<String>,
+    pub dragging_area: Option<AreaName>,
+    pub show_rulers: bool,
+    // Showing a minimap in the corner of area views that don't fit the area:
+    pub show_minimap: bool,
+    pub show_collision: bool,
+    pub collision_brush: CollisionType,
+    // Showing markers of the areas' entrances, holes, and exits (which can then be dragged):
+    pub show_entrances: bool,
+    // Showing markers of the areas' secrets (hidden items), which can also be dragged:
+    pub show_secrets: bool,
+    // Showing markers of the areas' flute spots and whirlpool destinations:
+    pub show_travel: bool,
+    // Pair of themes whose areas brush strokes are applied to together (see `theme_sync`):
+    pub theme_sync: Option<(ThemeName, ThemeName)>,
+    // Showing a sample sprite at the cursor, hidden behind tiles with priority:
+    pub show_sprite_preview: bool,
+    pub edit_layer: Layer,
+    // Showing the screens' annotations over the areas:
+    pub show_annotations: bool,
+    // Output of the external tools that have been run, and how many are still running:
+    pub tool_console: Vec<String>,
+    pub running_tools: usize,
+    // Script in the scripting console (kept while the console is closed), and its last output:
+    pub script: text_editor::Content,
+    pub script_output: Vec<String>,
+    // Scroll offset of the area views (in logical pixels), for the rulers to follow:
+    pub main_area_scroll: Vector,
+    pub side_area_scroll: Vector,
+    // Panning an area view while selecting or brushing near its edge:
+    pub auto_pan: Option<AutoPan>,
+    // Zoom levels of the area views from Ctrl+scrolling, overriding the global zoom until it's
+    // next changed:
+    pub main_area_zoom: Option<f32>,
+    pub side_area_zoom: Option<f32>,
+    // Scroll the main and side area views together, e.g. for comparing an area in two themes:
+    pub link_area_scroll: bool,
+    // View being scrolled to follow the other one, whose next scroll event shouldn't be followed
+    // in turn (its offset can differ, if it's clamped to a smaller area):
+    pub linked_scroll_pending: Option<AreaPosition>,
+    // Highlight the tiles that differ between the main and side areas:
+    pub show_side_differences: bool,
+    // Guide lines of each area (shared between its themes), which aren't saved:
+    pub guides: HashMap<AreaName, Vec<Guide>>,
 
     // Filesystem watch (to detect externa modifications)
     pub watcher: Option<notify::RecommendedWatcher>,
     pub watch_paths: Vec<PathBuf>,
     pub watch_enabled: bool,
-    pub files_modified_notification: Arc<Mutex<bool>>,
+    // Files changed outside the editor since they were last checked, and the changes to them
+    // waiting to be resolved (see `external_changes`):
+    pub changed_files: Arc<Mutex<BTreeSet<PathBuf>>>,
+    pub external_changes: Vec<ExternalChange>,
+    pub watcher_probe_seen: Arc<Mutex<bool>>,
+    pub watcher_status: WatcherStatus,
+    pub recent_writes: RecentWrites,
+    // Project files that failed to load when the project was last loaded:
+    pub load_failures: Vec<LoadFailure>,
 
     // Other editor state:
     pub dialogue: Option<Dialogue>,
+    // Last known mouse position in the window, for placing context menus:
+    pub cursor_position: Point,
+
+    // Update handler timing (to detect handlers that block the UI):
+    pub slow_update: Option<UpdateTiming>,
+    pub show_update_timings: bool,
+    pub update_timings: VecDeque<UpdateTiming>,
 
     // Cached data:
     pub palettes_id_idx_map: HashMap<PaletteId, usize>,
 }
 
 impl EditorState {
+    // The metatile being stamped, while it's still the brush (rather than replaced by another
+    // selection, or flipped).
+    pub fn stamp_metatile(&self) -> Option<&Metatile> {
+        let metatile = self.project_metadata.metatiles.get(self.stamp_metatile?)?;
+        (metatile.block == self.selected_tile_block).then_some(metatile)
+    }
+
+    // Whether the side area is showing, either in the side panel or in its own pane.
+    pub fn side_area_shown(&self) -> bool {
+        !self.side_panel_hidden
+            && (self.global_config.pane_layout.is_some()
+                || matches!(self.side_panel_view, SidePanelView::Area))
+    }
+
     pub fn main_area(&self) -> &Area {
         &self.areas[&self.main_area_id]
     }
@@ -376,6 +1343,36 @@ impl EditorState {
         }
     }
 
+    pub fn area_scroll(&self, position: AreaPosition) -> Vector {
+        match position {
+            AreaPosition::Main => self.main_area_scroll,
+            AreaPosition::Side => self.side_area_scroll,
+        }
+    }
+
+    pub fn set_area_scroll(&mut self, position: AreaPosition, offset: Vector) {
+        match position {
+            AreaPosition::Main => self.main_area_scroll = offset,
+            AreaPosition::Side => self.side_area_scroll = offset,
+        }
+    }
+
+    // Pixel size of an area view: its own zoom level if it has one, or else the global one.
+    pub fn area_pixel_size(&self, position: AreaPosition) -> f32 {
+        let zoom = match position {
+            AreaPosition::Main => self.main_area_zoom,
+            AreaPosition::Side => self.side_area_zoom,
+        };
+        zoom.unwrap_or(self.global_config.pixel_size)
+    }
+
+    pub fn set_area_zoom(&mut self, position: AreaPosition, zoom: Option<f32>) {
+        match position {
+            AreaPosition::Main => self.main_area_zoom = zoom,
+            AreaPosition::Side => self.side_area_zoom = zoom,
+        }
+    }
+
     pub fn area(&self, position: AreaPosition) -> &Area {
         &self.areas[self.area_id(position)]
     }
@@ -405,6 +1402,46 @@ impl EditorState {
         Ok(())
     }
 
+    // A copy of an area, including any unsaved changes to it.
+    pub fn area_copy(&self, area_id: &AreaId) -> Result<Area> {
+        match self.areas.get(area_id) {
+            Some(area) => Ok(area.clone()),
+            None => load_area(self, area_id),
+        }
+    }
+
+    // A copy of every area in the given theme, in the order of `area_names`.
+    pub fn theme_areas(&self, theme: &ThemeName) -> Result<Vec<Area>> {
+        self.area_names
+            .iter()
+            .map(|area| {
+                self.area_copy(&AreaId {
+                    area: area.clone(),
+                    theme: theme.clone(),
+                })
+            })
+            .collect()
+    }
+
+    pub fn snapshot(&self) -> Result<ProjectSnapshot> {
+        let theme = self.main_area_id.theme.clone();
+        Ok(ProjectSnapshot {
+            palettes: self.palettes.clone(),
+            areas: self.theme_areas(&theme)?,
+            theme,
+            metadata: self.project_metadata.clone(),
+        })
+    }
+
+    // The dialogue to show after loading the project, if any files failed to load:
+    pub fn load_recovery_dialogue(&self) -> Option<Dialogue> {
+        if self.load_failures.iter().any(|f| !f.skipped) {
+            Some(Dialogue::LoadRecovery)
+        } else {
+            None
+        }
+    }
+
     pub fn switch_area(&mut self, position: AreaPosition, area_id: &AreaId) -> Result<()> {
         if !self.areas.contains_key(area_id) {
             self.load_area(area_id)?;
@@ -414,12 +1451,88 @@ impl EditorState {
         Ok(())
     }
 
+    // Positions where the tile is used, across all areas and themes.
+    pub fn tile_usages(&self, palette_id: PaletteId, tile_idx: TileIdx) -> Result<Vec<TileUsage>> {
+        let mut out = vec![];
+        for theme in &self.theme_names {
+            for area_name in &self.area_names {
+                let area_id = AreaId {
+                    area: area_name.clone(),
+                    theme: theme.clone(),
+                };
+                let loaded_area;
+                let area = match self.areas.get(&area_id) {
+                    Some(area) => area,
+                    None => {
+                        loaded_area = load_area(self, &area_id)?;
+                        &loaded_area
+                    }
+                };
+                out.extend(area.tile_usages(palette_id, tile_idx)?);
+            }
+        }
+        Ok(out)
+    }
+
+    // The tiles selected in the tileset (as a rectangle of more than one tile), with their
+    // properties.
+    pub fn selected_tileset_tiles(&self) -> Option<(PaletteId, Vec<(TileIdx, TileProperties)>)> {
+        let block = &self.selected_tile_block;
+        if self.selection_source != SelectionSource::Tileset || block.size.0 * block.size.1 < 2 {
+            return None;
+        }
+        let palette_id = *block.palettes.first()?.first()?;
+        let &pal_idx = self.palettes_id_idx_map.get(&palette_id)?;
+        let tiles = &self.palettes[pal_idx].tiles;
+        let selected: Vec<_> = block
+            .tiles
+            .iter()
+            .flatten()
+            .filter_map(|&idx| Some((idx, tiles.get(idx as usize)?.properties())))
+            .collect();
+        (!selected.is_empty()).then_some((palette_id, selected))
+    }
+
+    pub fn area_group(&self, area_name: &AreaName) -> &str {
+        self.project_metadata
+            .area_groups
+            .get(area_name)
+            .map(|x| x.as_str())
+            .unwrap_or(UNGROUPED_AREA_GROUP)
+    }
+
+    // Area names organized by group, with groups in sorted order except
+    // that ungrouped areas are listed last.
+    pub fn grouped_area_names(&self) -> Vec<(String, Vec<AreaName>)> {
+        let mut groups: BTreeMap<&str, Vec<AreaName>> = BTreeMap::new();
+        for name in &self.area_names {
+            groups
+                .entry(self.area_group(name))
+                .or_default()
+                .push(name.clone());
+        }
+        let ungrouped = groups.remove(UNGROUPED_AREA_GROUP);
+        let mut out: Vec<(String, Vec<AreaName>)> = groups
+            .into_iter()
+            .map(|(g, names)| (g.to_string(), names))
+            .collect();
+        if let Some(names) = ungrouped {
+            out.push((UNGROUPED_AREA_GROUP.to_string(), names));
+        }
+        out
+    }
+
     pub fn cleanup_areas(&mut self) -> Result<()> {
         // Unload areas that aren't currently in use.
         let mut delete_keys: HashSet<AreaId> = self.areas.keys().cloned().collect();
         delete_keys.remove(&self.main_area_id);
         delete_keys.remove(&self.side_area_id);
         for key in delete_keys {
+            // With manual saving, changes are only written when saved (so that they can still be
+            // discarded), keeping modified areas loaded until then:
+            if self.global_config.manual_save && self.areas[&key].modified {
+                continue;
+            }
             save_area(self, &key)?;
             self.areas.remove(&key);
         }
@@ -439,18 +1552,6 @@ impl EditorState {
         }
         Ok(())
     }
-
-    pub fn disable_watch_file_changes(&mut self) -> Result<()> {
-        if let Some(watcher) = &mut self.watcher {
-            self.watch_enabled = false;
-            for p in &self.watch_paths {
-                if let Err(e) = watcher.unwatch(p) {
-                    info!("Unable to unwatch path {}: {}", p.display(), e);
-                }
-            }
-        }
-        Ok(())
-    }
 }
 
 fn get_global_config_path() -> Result<PathBuf> {
@@ -481,6 +1582,8 @@ pub fn ensure_areas_non_empty(state: &mut EditorState) -> Result<()> {
                     palettes: [[0; 32]; 32],
                     tiles: [[0; 32]; 32],
                     flips: [[Flip::None; 32]; 32],
+                    camera_locked: false,
+                    overlay: vec![],
                 });
             }
         }
@@ -501,6 +1604,8 @@ pub fn ensure_palettes_non_empty(state: &mut EditorState) {
                 collision: 0,
                 h_flippable: true,
                 v_flippable: true,
+                animated: None,
+                editor_only: false,
                 pixels: [[0; 8]; 8]
             };
             16
@@ -509,11 +1614,16 @@ pub fn ensure_palettes_non_empty(state: &mut EditorState) {
     }
 }
 
-pub fn get_initial_state() -> Result<EditorState> {
-    let mut state = EditorState {
-        global_config_path: get_global_config_path()?,
+// Construct an editor state with no project loaded, using the given path for the global config.
+pub fn new_editor_state(global_config_path: PathBuf) -> EditorState {
+    EditorState {
+        global_config_path,
         global_config: GlobalConfig::default(),
+        project_metadata: ProjectMetadata::default(),
         rom_path: None,
+        import_mode: ImportMode::Full,
+        import_maps: String::new(),
+        export_path: None,
         palettes: vec![],
         areas: HashMap::new(),
         main_area_id: AreaId {
@@ -528,13 +1638,33 @@ pub fn get_initial_state() -> Result<EditorState> {
         theme_names: vec![],
         undo_stack: vec![],
         redo_stack: vec![],
+        undo_preview: false,
+        macro_recording: None,
+        scale_factor: None,
         tool: Tool::default(),
         palette_only_brush: false,
         side_panel_view: SidePanelView::default(),
+        metatile_idx: None,
+        stamp_metatile: None,
+        stamps: vec![],
+        stamp_name: String::new(),
+        usage_search: None,
+        project_issues: None,
+        tutorial_step: None,
+        side_panel_hidden: false,
+        panes: pane_state(PaneLayout::default()),
+        world_map: None,
+        session_recorder: None,
         focus: Focus::None,
         palette_idx: 0,
+        palette_filter: String::new(),
+        palette_group_filter: None,
         color_idx: None,
         selected_color: [0, 0, 0],
+        snes_color_text: None,
+        rgb_hex_text: None,
+        picked_hsv: None,
+        eyedropper: false,
         identify_color: false,
         tile_idx: None,
         identify_tile: false,
@@ -544,21 +1674,82 @@ pub fn get_initial_state() -> Result<EditorState> {
         selected_tile_block: TileBlock::default(),
         selected_gfx: vec![],
         show_grid: false,
+        show_bg_color_editor: false,
+        heatmap: None,
+        area_diff: None,
+        palette_replace: None,
+        project_load: None,
+        project_rebuild: None,
+        rom_import: None,
+        collapsed_area_groups: HashSet::new(),
+        dragging_area: None,
+        show_rulers: true,
+        show_minimap: true,
+        show_collision: false,
+        show_entrances: false,
+        show_secrets: false,
+        show_travel: false,
+        show_sprite_preview: false,
+        edit_layer: Layer::Main,
+        theme_sync: None,
+        show_annotations: false,
+        tool_console: vec![],
+        running_tools: 0,
+        script: text_editor::Content::with_text(EXAMPLE_SCRIPT),
+        script_output: vec![],
+        collision_brush: 1,
+        main_area_scroll: Vector::ZERO,
+        side_area_scroll: Vector::ZERO,
+        auto_pan: None,
+        main_area_zoom: None,
+        side_area_zoom: None,
+        link_area_scroll: false,
+        linked_scroll_pending: None,
+        show_side_differences: false,
+        guides: HashMap::new(),
         pixel_coords: None,
         watcher: None,
         watch_enabled: false,
         watch_paths: vec![],
-        files_modified_notification: Arc::new(Mutex::new(false)),
+        changed_files: Arc::new(Mutex::new(BTreeSet::new())),
+        external_changes: vec![],
+        watcher_probe_seen: Arc::new(Mutex::new(false)),
+        watcher_status: WatcherStatus::default(),
+        recent_writes: RecentWrites::default(),
+        load_failures: vec![],
         dialogue: None,
+        cursor_position: Point::ORIGIN,
+        slow_update: None,
+        show_update_timings: false,
+        update_timings: VecDeque::new(),
         palettes_id_idx_map: HashMap::new(),
-    };
+    }
+}
+
+// The state at startup, with the global config loaded but not the project (which the editor
+// loads in the background).
+pub fn get_startup_state() -> Result<EditorState> {
+    let mut state = new_editor_state(get_global_config_path()?);
     if let Err(err) = persist::load_global_config(&mut state) {
         info!("Unable to load global config, using default: {}", err);
     }
+    state.panes = pane_state(state.global_config.pane_layout.unwrap_or_default());
+    if !state.global_config.tutorial_seen {
+        state.tutorial_step = Some(0);
+    }
+    ensure_themes_non_empty(&mut state);
+    ensure_areas_non_empty(&mut state)?;
+    ensure_palettes_non_empty(&mut state);
+    Ok(state)
+}
+
+pub fn get_initial_state() -> Result<EditorState> {
+    let mut state = get_startup_state()?;
     if let Err(err) = persist::load_project(&mut state) {
         info!("Unable to load project: {}", err);
         state.global_config.project_dir = None;
     }
+    state.dialogue = state.load_recovery_dialogue();
     ensure_themes_non_empty(&mut state);
     ensure_areas_non_empty(&mut state)?;
     ensure_palettes_non_empty(&mut state);
@@ -0,0 +1,115 @@
+// Test runs in an emulator: the main area's theme is exported into a test ROM in the project's
+// `build` folder, which is then opened with the emulator set in the settings. Building and
+// launching are separate steps (each sent as a message, so the progress dialogue is shown in
+// between), and any error is shown in a dialogue. The test ROM is only rebuilt when its inputs
+// (the theme's areas and palette positions, the palettes, and the base ROM) changed since it was
+// last built, so running again without edits just relaunches the emulator.
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use anyhow::{Context, Result};
+use iced::{futures::channel::mpsc, Task};
+use log::info;
+
+use crate::{
+    export::{ExportReport, Exporter},
+    helpers::content_hash,
+    message::Message,
+    palette_slots::load_assignment,
+    persist::load_area,
+    state::{AreaId, EditorState, ThemeName},
+};
+
+// The step that a test run is at, shown in its progress dialogue.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TestRunStage {
+    Building,
+    Launching,
+}
+
+pub fn test_rom_path(state: &EditorState, theme: &ThemeName) -> Result<PathBuf> {
+    let project_dir = state
+        .global_config
+        .project_dir
+        .as_ref()
+        .context("No project open")?;
+    Ok(project_dir
+        .join("build")
+        .join(format!("test-{}.sfc", theme)))
+}
+
+// Path of the file holding the hash of the inputs that the test ROM was last built from.
+fn hash_path(rom_path: &Path) -> PathBuf {
+    rom_path.with_extension("hash")
+}
+
+// Hash of everything the test ROM of a theme is built from (using areas in memory when loaded,
+// since they may have unsaved changes).
+pub fn build_hash(state: &EditorState, base_rom: &Path, theme: &ThemeName) -> Result<u64> {
+    let mut data = vec![];
+    data.extend(env!("CARGO_PKG_VERSION").as_bytes());
+    let base = std::fs::read(base_rom)
+        .with_context(|| format!("Unable to read ROM at {}", base_rom.display()))?;
+    data.extend(content_hash(&base).to_le_bytes());
+    for area_name in &state.area_names {
+        let area_id = AreaId {
+            area: area_name.clone(),
+            theme: theme.clone(),
+        };
+        let json = match state.areas.get(&area_id) {
+            Some(area) => serde_json::to_vec(area)?,
+            None => serde_json::to_vec(&load_area(state, &area_id)?)?,
+        };
+        data.extend(area_name.as_bytes());
+        data.extend(json);
+    }
+    data.extend(serde_json::to_vec(&state.palettes)?);
+    data.extend(serde_json::to_vec(&load_assignment(state, theme)?)?);
+    Ok(content_hash(&data))
+}
+
+// Export the theme into its test ROM, unless it's up to date. Returns the export's report, or
+// None if the ROM was up to date.
+pub fn build(
+    state: &EditorState,
+    base_rom: &Path,
+    theme: &ThemeName,
+) -> Result<Option<ExportReport>> {
+    let path = test_rom_path(state, theme)?;
+    let hash = build_hash(state, base_rom, theme)?.to_string();
+    let hash_path = hash_path(&path);
+    if path.exists() && std::fs::read_to_string(&hash_path).ok().as_deref() == Some(&hash) {
+        info!("Test ROM {} is up to date", path.display());
+        return Ok(None);
+    }
+    let dir = path.parent().context("internal error")?;
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Unable to create folder {}", dir.display()))?;
+    // Remove the hash first, so that a failed export isn't taken as up to date:
+    let _ = std::fs::remove_file(&hash_path);
+    let report = Exporter::export(state, base_rom, &path, theme)?;
+    std::fs::write(&hash_path, hash)
+        .with_context(|| format!("Unable to write {}", hash_path.display()))?;
+    Ok(Some(report))
+}
+
+// Start the emulator with the test ROM, reporting its exit with `Message::TestRunFinished`.
+pub fn launch(emulator: &Path, rom: &Path) -> Result<Task<Message>> {
+    info!("Running {} with {}", emulator.display(), rom.display());
+    let mut child = Command::new(emulator)
+        .arg(rom)
+        .stdin(Stdio::null())
+        .spawn()
+        .with_context(|| format!("Unable to run emulator {}", emulator.display()))?;
+    let (sender, receiver) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        let result = child
+            .wait()
+            .map(|status| status.code())
+            .map_err(|e| e.to_string());
+        let _ = sender.unbounded_send(Message::TestRunFinished(result));
+    });
+    Ok(Task::run(receiver, |message| message))
+}
@@ -0,0 +1,64 @@
+// Synced brushing of two themes: while it's on, brush strokes that change the layout of an area in
+// either theme (placing tiles, rather than only recoloring them) are also applied to the same area
+// in the other theme. The two changes are sent as one batch, so that they're undone together.
+use crate::{
+    area_shapes::{brush_cells, shape_cells},
+    message::Message,
+    state::{AreaId, EditorState, ThemeName},
+};
+
+// The theme that changes to areas of the given theme are mirrored into, if any.
+pub fn synced_theme<'a>(state: &'a EditorState, theme: &ThemeName) -> Option<&'a ThemeName> {
+    let (a, b) = state.theme_sync.as_ref()?;
+    let other = if theme == a {
+        b
+    } else if theme == b {
+        a
+    } else {
+        return None;
+    };
+    state.theme_names.contains(other).then_some(other)
+}
+
+// The message to send in place of the given one: a brush stroke along with its mirror in the
+// synced theme, or otherwise the message itself.
+pub fn synced_message(state: &EditorState, message: Message) -> Message {
+    let (area_id, cells) = match &message {
+        Message::AreaBrush {
+            area_id,
+            coords,
+            selection,
+            palette_only: false,
+            ..
+        } => {
+            let Some(area) = state.areas.get(area_id) else {
+                return message;
+            };
+            (area_id, brush_cells(area, *coords, selection, false))
+        }
+        Message::AreaShape {
+            area_id,
+            shape,
+            selection,
+            palette_only: false,
+            ..
+        } => {
+            let Some(area) = state.areas.get(area_id) else {
+                return message;
+            };
+            (area_id, shape_cells(area, *shape, selection, false))
+        }
+        _ => return message,
+    };
+    let Some(theme) = synced_theme(state, &area_id.theme) else {
+        return message;
+    };
+    let mirror = Message::MirrorAreaCells {
+        area_id: AreaId {
+            area: area_id.area.clone(),
+            theme: theme.clone(),
+        },
+        cells,
+    };
+    Message::Batch(vec![message, mirror])
+}
@@ -0,0 +1,100 @@
+// Deduplication of a palette's tiles: tiles that are copies of an earlier tile (possibly flipped,
+// in a way the earlier tile allows) are replaced by it in all areas, and then cleared, which
+// frees them for new graphics.
+use hashbrown::HashMap;
+use iced::Point;
+
+use crate::{
+    animated_tiles::AnimatedSlot,
+    message::Message,
+    state::{CollisionType, ColorIdx, Flip, Palette, PaletteId, Tile, TileIdx},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DuplicateTile {
+    pub tile_idx: TileIdx,
+    pub original: TileIdx,
+    // Flip of the original that gives the duplicate:
+    pub flip: Flip,
+}
+
+pub fn flip_allowed(tile: &Tile, flip: Flip) -> bool {
+    match flip {
+        Flip::None => true,
+        Flip::Horizontal => tile.h_flippable,
+        Flip::Vertical => tile.v_flippable,
+        Flip::Both => tile.h_flippable && tile.v_flippable,
+    }
+}
+
+// The properties that must match for a tile to be a duplicate of another, and its graphics.
+type PropertiesKey = (bool, CollisionType, Option<AnimatedSlot>, bool);
+type TileKey = (PropertiesKey, [[ColorIdx; 8]; 8]);
+
+fn properties_key(tile: &Tile) -> PropertiesKey {
+    (
+        tile.priority,
+        tile.collision,
+        tile.animated,
+        tile.editor_only,
+    )
+}
+
+pub fn find_duplicate_tiles(palette: &Palette) -> Vec<DuplicateTile> {
+    let mut out = vec![];
+    // Originals by the graphics they can be placed with:
+    let mut originals: HashMap<TileKey, Vec<(TileIdx, Flip)>> = HashMap::new();
+    for (i, tile) in palette.tiles.iter().enumerate() {
+        let tile_idx = i as TileIdx;
+        // Blank tiles are left alone, as they're usually just unused:
+        if tile.pixels == [[0; 8]; 8] {
+            continue;
+        }
+        let key = properties_key(tile);
+        // Placements of the duplicate (with any of the flips it allows) become placements of the
+        // original, so the original must allow at least the same flips:
+        let original = originals.get(&(key, tile.pixels)).and_then(|candidates| {
+            candidates.iter().copied().find(|&(idx, _)| {
+                let original = &palette.tiles[idx as usize];
+                (!tile.h_flippable || original.h_flippable)
+                    && (!tile.v_flippable || original.v_flippable)
+            })
+        });
+        if let Some((original, flip)) = original {
+            out.push(DuplicateTile {
+                tile_idx,
+                original,
+                flip,
+            });
+            continue;
+        }
+        for flip in [Flip::None, Flip::Horizontal, Flip::Vertical, Flip::Both] {
+            if flip_allowed(tile, flip) {
+                originals
+                    .entry((key, flip.apply_to_pixels(tile.pixels)))
+                    .or_default()
+                    .push((tile_idx, flip));
+            }
+        }
+    }
+    out
+}
+
+// Messages to merge the duplicates into their originals, and clear them.
+pub fn merge_messages(palette_id: PaletteId, duplicates: &[DuplicateTile]) -> Vec<Message> {
+    let mut out = vec![];
+    for d in duplicates {
+        out.push(Message::MergeFlippedTile {
+            palette_id,
+            tile_idx: d.tile_idx,
+            original: d.original,
+            flip: d.flip,
+        });
+        out.push(Message::TilesetBrush {
+            palette_id,
+            coords: Point::new(d.tile_idx % 16, d.tile_idx / 16),
+            selected_gfx: vec![vec![Tile::default()]],
+        });
+    }
+    out
+}
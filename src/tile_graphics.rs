@@ -0,0 +1,327 @@
+// Import and export of a palette's 8x8 tile graphics, for editing them in external pixel editors
+// or sharing them with other SNES tools: either as an indexed PNG (16 tiles per row, like the
+// tiles PNG kept in the project's Palettes folder, but at 1x scale and with the palette's colors
+// as the PNG's palette), as raw SNES 3bpp/4bpp tile data (as edited in e.g. YY-CHR), or as 3bpp
+// graphics sheets compressed as in the ROM. Importing replaces the pixels of the palette's tiles
+// in order (keeping their other properties), adding tiles if there are more.
+use std::{fmt, fs::File, io::BufWriter, path::Path};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    compression::{compress, decompress_prefix},
+    helpers::scale_color,
+    state::{ColorIdx, ColorRGB, Palette, Tile, TileIdx},
+};
+
+pub type TilePixels = [[ColorIdx; 8]; 8];
+
+// Number of tiles per row in exported PNGs.
+pub const GRAPHICS_PNG_COLS: usize = 16;
+// Number of tiles in a graphics sheet, the unit that the ROM compresses graphics in:
+pub const SHEET_TILES: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphicsFormat {
+    Png,
+    Bpp3,
+    Bpp4,
+    // 3bpp sheets, each compressed separately and stored one after another:
+    CompressedSheets,
+}
+
+impl GraphicsFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            GraphicsFormat::Png => "png",
+            GraphicsFormat::Bpp3 | GraphicsFormat::Bpp4 | GraphicsFormat::CompressedSheets => "bin",
+        }
+    }
+
+    // Size of a tile in the planar formats (4bpp, or otherwise 3bpp), in bytes:
+    fn tile_bytes(self) -> usize {
+        match self {
+            GraphicsFormat::Bpp4 => 32,
+            _ => 24,
+        }
+    }
+
+    fn file_suffix(self) -> &'static str {
+        match self {
+            GraphicsFormat::Png => "gfx",
+            GraphicsFormat::Bpp3 => "gfx-3bpp",
+            GraphicsFormat::Bpp4 => "gfx-4bpp",
+            GraphicsFormat::CompressedSheets => "gfx-sheets",
+        }
+    }
+}
+
+impl fmt::Display for GraphicsFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphicsFormat::Png => write!(f, "PNG"),
+            GraphicsFormat::Bpp3 => write!(f, "3bpp"),
+            GraphicsFormat::Bpp4 => write!(f, "4bpp"),
+            GraphicsFormat::CompressedSheets => write!(f, "Compressed sheets"),
+        }
+    }
+}
+
+// Encode tiles in the SNES planar format: each row of a tile has bitplanes 0 and 1 interleaved
+// in the first 16 bytes, followed by bitplane 2 alone (3bpp) or bitplanes 2 and 3 interleaved
+// (4bpp).
+pub fn encode_planar(tiles: &[TilePixels], format: GraphicsFormat) -> Result<Vec<u8>> {
+    let mut out = vec![];
+    for (i, pixels) in tiles.iter().enumerate() {
+        let mut data = vec![0; format.tile_bytes()];
+        for (y, row) in pixels.iter().enumerate() {
+            for (x, &c) in row.iter().enumerate() {
+                if format != GraphicsFormat::Bpp4 && c >= 8 {
+                    bail!(
+                        "Tile {} uses color {}, which can't be stored in 3bpp (colors 0-7)",
+                        i,
+                        c
+                    );
+                }
+                let bit = 7 - x;
+                data[y * 2] |= (c & 1) << bit;
+                data[y * 2 + 1] |= ((c >> 1) & 1) << bit;
+                match format {
+                    GraphicsFormat::Bpp4 => {
+                        data[16 + y * 2] |= ((c >> 2) & 1) << bit;
+                        data[16 + y * 2 + 1] |= ((c >> 3) & 1) << bit;
+                    }
+                    _ => data[16 + y] |= ((c >> 2) & 1) << bit,
+                }
+            }
+        }
+        out.extend(data);
+    }
+    Ok(out)
+}
+
+pub fn decode_planar(data: &[u8], format: GraphicsFormat) -> Result<Vec<TilePixels>> {
+    let tile_bytes = format.tile_bytes();
+    if data.is_empty() || !data.len().is_multiple_of(tile_bytes) {
+        bail!(
+            "{} bytes is not a whole number of {} tiles ({} bytes each)",
+            data.len(),
+            format,
+            tile_bytes
+        );
+    }
+    let mut tiles = vec![];
+    for tile in data.chunks(tile_bytes) {
+        let mut pixels: TilePixels = [[0; 8]; 8];
+        for (y, row) in pixels.iter_mut().enumerate() {
+            for (x, c) in row.iter_mut().enumerate() {
+                let bit = 7 - x;
+                let c0 = (tile[y * 2] >> bit) & 1;
+                let c1 = (tile[y * 2 + 1] >> bit) & 1;
+                let (c2, c3) = match format {
+                    GraphicsFormat::Bpp4 => (
+                        (tile[16 + y * 2] >> bit) & 1,
+                        (tile[16 + y * 2 + 1] >> bit) & 1,
+                    ),
+                    _ => ((tile[16 + y] >> bit) & 1, 0),
+                };
+                *c = c0 | (c1 << 1) | (c2 << 2) | (c3 << 3);
+            }
+        }
+        tiles.push(pixels);
+    }
+    Ok(tiles)
+}
+
+fn write_graphics_png(path: &Path, palette: &Palette) -> Result<()> {
+    let tiles = &palette.tiles;
+    let num_rows = tiles.len().div_ceil(GRAPHICS_PNG_COLS);
+    let width = GRAPHICS_PNG_COLS * 8;
+    let mut data: Vec<u8> = vec![0; width * num_rows * 8];
+    for (i, tile) in tiles.iter().enumerate() {
+        let x0 = (i % GRAPHICS_PNG_COLS) * 8;
+        let y0 = (i / GRAPHICS_PNG_COLS) * 8;
+        for y in 0..8 {
+            for x in 0..8 {
+                data[(y0 + y) * width + x0 + x] = tile.pixels[y][x];
+            }
+        }
+    }
+    let plte: Vec<u8> = palette
+        .colors
+        .iter()
+        .flat_map(|&[r, g, b]| [scale_color(r), scale_color(g), scale_color(b)])
+        .collect();
+
+    let file =
+        File::create(path).with_context(|| format!("Unable to create {}", path.display()))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width as u32, (num_rows * 8) as u32);
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_palette(plte);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&data)?;
+    Ok(())
+}
+
+// Index of the palette color closest to an RGB color (with 8 bits per channel).
+fn nearest_color(colors: &[ColorRGB; 16], rgb: [u8; 3]) -> ColorIdx {
+    let distance = |&[r, g, b]: &ColorRGB| {
+        [r, g, b]
+            .iter()
+            .zip(rgb)
+            .map(|(&c, p)| (scale_color(c) as i32 - p as i32).pow(2))
+            .sum::<i32>()
+    };
+    (0..16).min_by_key(|&i| distance(&colors[i])).unwrap() as ColorIdx
+}
+
+// Read the tiles of a PNG, 8x8 pixels each, from left to right and top to bottom. The pixels of
+// indexed PNGs keep their index, while other colors are mapped to the closest palette color.
+fn read_graphics_png(path: &Path, colors: &[ColorRGB; 16]) -> Result<Vec<TilePixels>> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("Unable to read {}", path.display()))?;
+    let mut decoder = png::Decoder::new(bytes.as_slice());
+    let indexed = decoder.read_header_info()?.color_type == png::ColorType::Indexed;
+    if !indexed {
+        decoder = png::Decoder::new(bytes.as_slice());
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+    }
+    let mut reader = decoder.read_info()?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buf)?;
+    let (width, height) = (frame.width as usize, frame.height as usize);
+    if width % 8 != 0 || height % 8 != 0 {
+        bail!(
+            "The image's size ({}x{}) is not a whole number of 8x8 tiles",
+            width,
+            height
+        );
+    }
+    let mut indices: Vec<ColorIdx> = Vec::with_capacity(width * height);
+    for (y, line) in buf.chunks(frame.line_size).take(height).enumerate() {
+        for x in 0..width {
+            let idx = if indexed {
+                let depth = frame.bit_depth as usize;
+                let bit = x * depth;
+                let mask = ((1u16 << depth) - 1) as u8;
+                (line[bit / 8] >> (8 - depth - bit % 8)) & mask
+            } else {
+                let channels = frame.color_type.samples();
+                let p = &line[x * channels..];
+                let rgb = if channels >= 3 {
+                    [p[0], p[1], p[2]]
+                } else {
+                    [p[0]; 3]
+                };
+                nearest_color(colors, rgb)
+            };
+            if idx >= 16 {
+                bail!(
+                    "Pixel ({}, {}) uses color {}, beyond the palette's 16 colors",
+                    x,
+                    y,
+                    idx
+                );
+            }
+            indices.push(idx);
+        }
+    }
+    let mut tiles = vec![];
+    for ty in 0..height / 8 {
+        for tx in 0..width / 8 {
+            let mut pixels: TilePixels = [[0; 8]; 8];
+            for (y, row) in pixels.iter_mut().enumerate() {
+                for (x, c) in row.iter_mut().enumerate() {
+                    *c = indices[(ty * 8 + y) * width + tx * 8 + x];
+                }
+            }
+            tiles.push(pixels);
+        }
+    }
+    Ok(tiles)
+}
+
+// Compress tiles as 3bpp sheets (the last one padded with blank tiles).
+pub fn encode_sheets(tiles: &[TilePixels]) -> Result<Vec<u8>> {
+    let mut tiles = tiles.to_vec();
+    tiles.resize(tiles.len().div_ceil(SHEET_TILES) * SHEET_TILES, [[0; 8]; 8]);
+    let mut out = vec![];
+    for sheet in tiles.chunks(SHEET_TILES) {
+        out.extend(compress(&encode_planar(sheet, GraphicsFormat::Bpp3)?));
+    }
+    Ok(out)
+}
+
+pub fn decode_sheets(data: &[u8]) -> Result<Vec<TilePixels>> {
+    let mut planar = vec![];
+    let mut pos = 0;
+    while pos < data.len() {
+        let (sheet, len) = decompress_prefix(&data[pos..], false)
+            .with_context(|| format!("Invalid compressed data at offset {:#X}", pos))?;
+        planar.extend(sheet);
+        pos += len;
+    }
+    decode_planar(&planar, GraphicsFormat::Bpp3)
+}
+
+pub fn export_graphics(path: &Path, palette: &Palette, format: GraphicsFormat) -> Result<()> {
+    if format == GraphicsFormat::Png {
+        return write_graphics_png(path, palette);
+    }
+    let pixels: Vec<TilePixels> = palette.tiles.iter().map(|t| t.pixels).collect();
+    let data = match format {
+        GraphicsFormat::CompressedSheets => encode_sheets(&pixels)?,
+        _ => encode_planar(&pixels, format)?,
+    };
+    std::fs::write(path, data).with_context(|| format!("Unable to write {}", path.display()))
+}
+
+pub fn read_graphics(
+    path: &Path,
+    colors: &[ColorRGB; 16],
+    format: GraphicsFormat,
+) -> Result<Vec<TilePixels>> {
+    match format {
+        GraphicsFormat::Png => read_graphics_png(path, colors),
+        _ => {
+            let data = std::fs::read(path)
+                .with_context(|| format!("Unable to read {}", path.display()))?;
+            match format {
+                GraphicsFormat::CompressedSheets => decode_sheets(&data),
+                _ => decode_planar(&data, format),
+            }
+        }
+    }
+}
+
+// The palette with its tiles' pixels replaced by the imported ones, adding tiles (in complete
+// rows) for any beyond the end of the palette.
+pub fn with_imported_graphics(palette: &Palette, graphics: &[TilePixels]) -> Result<Palette> {
+    if graphics.len() > TileIdx::MAX as usize + 1 {
+        bail!(
+            "{} tiles is more than a palette can hold ({})",
+            graphics.len(),
+            TileIdx::MAX as usize + 1
+        );
+    }
+    let mut palette = palette.clone();
+    if graphics.len() > palette.tiles.len() {
+        let num_rows = graphics.len().div_ceil(16);
+        palette.tiles.resize(num_rows * 16, Tile::default());
+    }
+    for (tile, pixels) in palette.tiles.iter_mut().zip(graphics) {
+        tile.pixels = *pixels;
+    }
+    Ok(palette)
+}
+
+// Default file name for exporting a palette's graphics.
+pub fn graphics_file_name(palette: &Palette, format: GraphicsFormat) -> String {
+    format!(
+        "{}-{}.{}",
+        palette.name,
+        format.file_suffix(),
+        format.extension()
+    )
+}
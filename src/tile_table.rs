@@ -0,0 +1,75 @@
+// Tables of a palette's tile metadata (collision type, priority, and flippable flags), for
+// documentation and review outside the editor: CSV for scripts and spreadsheets, and Markdown
+// for pasting into docs. These complement the tile PNGs kept in the project's Palettes folder.
+use std::fmt;
+
+use crate::state::Palette;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TileTableFormat {
+    Csv,
+    Markdown,
+}
+
+impl TileTableFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            TileTableFormat::Csv => "csv",
+            TileTableFormat::Markdown => "md",
+        }
+    }
+}
+
+impl fmt::Display for TileTableFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TileTableFormat::Csv => write!(f, "CSV"),
+            TileTableFormat::Markdown => write!(f, "Markdown"),
+        }
+    }
+}
+
+const COLUMNS: [&str; 5] = [
+    "index",
+    "collision",
+    "priority",
+    "h_flippable",
+    "v_flippable",
+];
+
+pub fn tile_table(palette: &Palette, format: TileTableFormat) -> String {
+    let mut out = String::new();
+    match format {
+        TileTableFormat::Csv => {
+            out += &COLUMNS.join(",");
+            out += "\n";
+            for (i, tile) in palette.tiles.iter().enumerate() {
+                out += &format!(
+                    "{},{},{},{},{}\n",
+                    i,
+                    tile.collision,
+                    tile.priority as u8,
+                    tile.h_flippable as u8,
+                    tile.v_flippable as u8
+                );
+            }
+        }
+        TileTableFormat::Markdown => {
+            let flag = |b: bool| if b { "yes" } else { "" };
+            out += &format!("# Tiles of palette {}: {}\n\n", palette.id, palette.name);
+            out += &format!("| {} |\n", COLUMNS.join(" | "));
+            out += &format!("|{}\n", "---:|".repeat(COLUMNS.len()));
+            for (i, tile) in palette.tiles.iter().enumerate() {
+                out += &format!(
+                    "| {} | {} | {} | {} | {} |\n",
+                    i,
+                    tile.collision,
+                    flag(tile.priority),
+                    flag(tile.h_flippable),
+                    flag(tile.v_flippable)
+                );
+            }
+        }
+    }
+    out
+}
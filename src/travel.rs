@@ -0,0 +1,64 @@
+// Overworld travel points: the spots where the bird drops the player off when the flute is
+// played, and where the player comes out of each whirlpool. They're listed per area (the one
+// containing the spot) and shown as markers over it like entrances, which can be dragged, or
+// clicked to edit their destination. The game has a fixed number of each, so they're only moved,
+// not added or removed.
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{entrances::MARKER_SIZE, state::TileCoord};
+
+// Number of destinations of each kind in the game's table (flute spots first, then whirlpools):
+pub const FLUTE_SPOT_CNT: u8 = 9;
+pub const WHIRLPOOL_CNT: u8 = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TravelKind {
+    Flute,
+    Whirlpool,
+}
+
+impl fmt::Display for TravelKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TravelKind::Flute => write!(f, "Flute spot"),
+            TravelKind::Whirlpool => write!(f, "Whirlpool"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TravelPoint {
+    pub kind: TravelKind,
+    // Index among the game's destinations of its kind:
+    pub slot: u8,
+    // Where the player lands, as the marker's top-left corner within the area, in 8x8 tiles:
+    pub position: (TileCoord, TileCoord),
+}
+
+impl TravelPoint {
+    pub fn contains(&self, x: TileCoord, y: TileCoord) -> bool {
+        let (x0, y0) = self.position;
+        (x0..x0 + MARKER_SIZE).contains(&x) && (y0..y0 + MARKER_SIZE).contains(&y)
+    }
+
+    // Short label drawn on the marker, e.g. "F3" for flute spot 3.
+    pub fn label(&self) -> String {
+        match self.kind {
+            TravelKind::Flute => format!("F{}", self.slot),
+            TravelKind::Whirlpool => format!("W{}", self.slot),
+        }
+    }
+
+    // The map that the player lands in, for an area with the given parent map.
+    pub fn destination_map(&self, parent: u8) -> u8 {
+        let (x, y) = self.position;
+        parent + (x / 64) as u8 + (y / 64) as u8 * 8
+    }
+}
+
+// The travel point at the given position, if any (the last one drawn, if they overlap).
+pub fn travel_at(points: &[TravelPoint], x: TileCoord, y: TileCoord) -> Option<usize> {
+    points.iter().rposition(|p| p.contains(x, y))
+}
@@ -1,10 +1,23 @@
 use crate::{
+    annotations::annotation,
+    area_shapes::{shape_cells, AreaCell},
+    camera_locks::is_camera_locked,
+    dark_world::dark_world_areas,
     message::Message,
-    state::{EditorState, Flip, PaletteId, Tile, TileBlock, TileCoord, TileIdx},
+    overlays::OverlayCell,
+    palette_replace::palette_cells,
+    palette_slots::load_assignment,
+    persist::load_area,
+    state::{
+        AreaId, ColorIdx, EditorState, Flip, PaletteId, Tile, TileBlock, TileCoord, TileIdx,
+        TileUsage,
+    },
 };
 
 use anyhow::{Context, Result};
 use iced::Point;
+use itertools::Itertools;
+use std::collections::BTreeMap;
 
 #[derive(Debug)]
 pub enum UndoAction {
@@ -19,29 +32,152 @@ pub fn get_undo_action(state: &EditorState, message: &Message) -> Result<UndoAct
     // transient editor state.
     let action = match message {
         Message::Nothing => UndoAction::None,
+        Message::Batch(messages) => {
+            // The messages in a batch are expected to be independent of each other,
+            // so that each can be reversed based on the state before the whole batch.
+            let mut reverse_messages = vec![];
+            for m in messages {
+                match get_undo_action(state, m)? {
+                    UndoAction::None => {}
+                    UndoAction::Irreversible => return Ok(UndoAction::Irreversible),
+                    UndoAction::Ok(r) => reverse_messages.push(r),
+                }
+            }
+            reverse_messages.reverse();
+            UndoAction::Ok(Message::Batch(reverse_messages))
+        }
         Message::Event(_) => UndoAction::None,
+        Message::Undo | Message::Redo | Message::PreviewUndo(_) => UndoAction::None,
         Message::Focus(_) => UndoAction::None,
         Message::WindowClose(_) => UndoAction::None,
+        Message::SetWindowGeometry { .. } => UndoAction::None,
         Message::SaveProject => UndoAction::None,
+        Message::CheckWatcher => UndoAction::None,
         Message::OpenProject => UndoAction::None,
         Message::ModifiedReload => UndoAction::None,
+        Message::SetReloadChoice(..) => UndoAction::None,
+        Message::ToggleReloadDiff(_) => UndoAction::None,
+        Message::ApplyExternalChanges => UndoAction::None,
+        Message::ReloadAreaFromDisk => UndoAction::None,
+        Message::DiscardChangesDialogue => UndoAction::None,
+        Message::DiscardChanges => UndoAction::None,
+        Message::SaveAndContinue(_) => UndoAction::None,
+        Message::DiscardAndContinue(_) => UndoAction::None,
+        Message::RestoreFile { .. } => UndoAction::Irreversible,
+        Message::SkipFile(_) => UndoAction::None,
+        Message::OpenFileLocation(_) => UndoAction::None,
         Message::RebuildProjectDialogue => UndoAction::None,
-        Message::RebuildProject => UndoAction::None,
+        Message::SetRebuildScope(_) => UndoAction::None,
+        Message::StartRebuildProject => UndoAction::None,
+        Message::RebuildProjectProgress(_) => UndoAction::None,
+        Message::RebuildProjectFinished(_) => UndoAction::None,
+        Message::CancelRebuildProject => UndoAction::None,
         Message::ProjectOpened(_) => UndoAction::Irreversible,
+        Message::ProjectLoadProgress { .. } => UndoAction::None,
+        Message::ProjectLoaded { .. } => UndoAction::Irreversible,
+        Message::CancelProjectLoad => UndoAction::None,
         Message::SettingsDialogue => UndoAction::None,
+        Message::MemoryDialogue => UndoAction::None,
+        Message::ClearCaches => UndoAction::None,
+        Message::CompactMemory => UndoAction::None,
+        Message::UnloadUnusedAreas => UndoAction::None,
+        Message::ExportStatistics(_) => UndoAction::None,
+        Message::ScriptDialogue => UndoAction::None,
+        Message::EditScript(_) => UndoAction::None,
+        // The script's changes are sent in a batch of their own:
+        Message::RunScript => UndoAction::None,
         Message::HelpDialogue => UndoAction::None,
+        Message::ExportCheatSheet => UndoAction::None,
+        Message::ExportCheatSheetTo(_) => UndoAction::None,
+        Message::StartTutorial => UndoAction::None,
+        Message::SetTutorialStep(_) => UndoAction::None,
+        Message::CloseTutorial => UndoAction::None,
+        Message::ToggleSidePanel => UndoAction::None,
+        Message::SetThreePaneLayout(_) => UndoAction::None,
+        Message::PaneResized(_) => UndoAction::None,
         Message::SetPixelSize(_) => UndoAction::None,
+        Message::SetPerDisplayZoom(_) => UndoAction::None,
+        Message::SetRecordSession(_) => UndoAction::None,
+        Message::SetManualSave(_) => UndoAction::None,
+        Message::SetRecordRenames(_) => UndoAction::Ok(Message::SetRecordRenames(
+            state.project_metadata.record_renames,
+        )),
+        Message::RenameHistoryDialogue => UndoAction::None,
+        Message::SaveBugReport => UndoAction::None,
+        Message::SaveBugReportTo(_) => UndoAction::None,
+        Message::SetScaleFactor(_) => UndoAction::None,
         Message::SetGridAlpha(_) => UndoAction::None,
+        Message::SetOverlayOpacity(_) => UndoAction::None,
         Message::CloseDialogue => UndoAction::None,
-        Message::ImportDialogue => UndoAction::None,
+        Message::ContextMenu(_) => UndoAction::None,
+        Message::ImportDialogue(_) => UndoAction::None,
+        Message::ImportRomFolderDialogue => UndoAction::None,
         Message::ImportConfirm(_) => UndoAction::None,
+        Message::SetImportMode(_) => UndoAction::None,
+        Message::SetImportMaps(_) => UndoAction::None,
+        Message::PreviewImport => UndoAction::None,
         Message::ImportROMProgress => UndoAction::None,
-        Message::ImportROM => UndoAction::Irreversible,
+        Message::ImportROMRead(_) => UndoAction::None,
+        Message::ImportROMStep => UndoAction::None,
+        Message::CancelImportROM => UndoAction::None,
+        Message::OpenHeatmap => UndoAction::None,
+        Message::HeatmapOpened(_) => UndoAction::None,
+        Message::ClearHeatmap => UndoAction::None,
+        Message::ImportROM | Message::RestoreSnapshot(_) => {
+            UndoAction::Ok(Message::RestoreSnapshot(Box::new(state.snapshot()?)))
+        }
+        Message::SelectImportRules => UndoAction::None,
+        Message::ImportRulesSelected(_) => UndoAction::None,
+        Message::ClearImportRules => UndoAction::None,
+        Message::ExportDialogue => UndoAction::None,
+        Message::ExportBaseROMSelected(_) => UndoAction::None,
+        Message::ExportROMTo(_) => UndoAction::None,
+        Message::ExportROMProgress => UndoAction::None,
+        Message::ExportROM => UndoAction::None,
+        Message::PaletteSlotsDialogue => UndoAction::None,
+        Message::SelectPaletteSlotsArea(_) => UndoAction::None,
+        // Setting a position can displace another palette, so the whole assignment is restored:
+        Message::SolvePaletteSlots(theme)
+        | Message::SetPaletteAssignment { theme, .. }
+        | Message::SetPaletteSlot { theme, .. } => UndoAction::Ok(Message::SetPaletteAssignment {
+            theme: theme.clone(),
+            assignment: load_assignment(state, theme)?,
+        }),
+        Message::BorrowGraphicsDialogue => UndoAction::None,
+        Message::BorrowGraphicsROMOpened(_) => UndoAction::None,
+        Message::ToggleBorrowSheet(_) => UndoAction::None,
+        Message::SetBorrowPalette(_) => UndoAction::None,
+        &Message::ImportTiles { palette_id, .. } => {
+            let idx = *state
+                .palettes_id_idx_map
+                .get(&palette_id)
+                .context("palette not found")?;
+            UndoAction::Ok(Message::ReplacePalette(state.palettes[idx].clone()))
+        }
         Message::SelectPalette(_) => UndoAction::None,
+        Message::SetPaletteFilter(_) => UndoAction::None,
+        Message::SetPaletteGroupFilter(_) => UndoAction::None,
+        Message::PaletteGroupsDialogue => UndoAction::None,
+        Message::SetNewPaletteGroupName(_) => UndoAction::None,
+        Message::EditPaletteGroupName { .. } => UndoAction::None,
+        Message::SetPaletteGroups(changes) => UndoAction::Ok(Message::SetPaletteGroups(
+            changes
+                .iter()
+                .filter_map(|(id, _)| {
+                    let &idx = state.palettes_id_idx_map.get(id)?;
+                    Some((*id, state.palettes[idx].group.clone()))
+                })
+                .collect(),
+        )),
+        Message::MovePaletteGroup { .. } => UndoAction::None,
+        Message::SetPaletteGroupOrder(_) => UndoAction::Ok(Message::SetPaletteGroupOrder(
+            state.project_metadata.palette_group_order.clone(),
+        )),
         Message::AddPaletteDialogue => UndoAction::None,
         Message::SetAddPaletteName(_) => UndoAction::None,
         Message::SetAddPaletteID(_) => UndoAction::None,
         Message::AddPalette { id, .. } => UndoAction::Ok(Message::DeletePalette(*id)),
+        Message::DuplicatePaletteDialogue => UndoAction::None,
         Message::DeletePaletteDialogue => UndoAction::None,
         Message::DeletePalette(id) => {
             if let Some(&palette_idx) = state.palettes_id_idx_map.get(id) {
@@ -52,6 +188,19 @@ pub fn get_undo_action(state: &EditorState, message: &Message) -> Result<UndoAct
             }
         }
         Message::RestorePalette(pal) => UndoAction::Ok(Message::DeletePalette(pal.id)),
+        &Message::SetPaletteCategory {
+            palette_id,
+            category: _,
+        } => {
+            let idx = *state
+                .palettes_id_idx_map
+                .get(&palette_id)
+                .context("palette not found")?;
+            UndoAction::Ok(Message::SetPaletteCategory {
+                palette_id,
+                category: state.palettes[idx].category,
+            })
+        }
         Message::RenamePaletteDialogue => UndoAction::None,
         Message::SetRenamePaletteName(_) => UndoAction::None,
         Message::RenamePalette { id, name: _ } => {
@@ -64,7 +213,16 @@ pub fn get_undo_action(state: &EditorState, message: &Message) -> Result<UndoAct
                 name: state.palettes[idx].name.clone(),
             })
         }
+        Message::PaletteHistoryDialogue => UndoAction::None,
+        Message::ReplacePalette(pal) => {
+            let idx = *state
+                .palettes_id_idx_map
+                .get(&pal.id)
+                .context("palette not found")?;
+            UndoAction::Ok(Message::ReplacePalette(state.palettes[idx].clone()))
+        }
         Message::HideModal => UndoAction::None,
+        Message::DismissSlowUpdateWarning => UndoAction::None,
         Message::SelectColor(_, _) => UndoAction::None,
         &Message::BrushColor {
             palette_id,
@@ -81,9 +239,28 @@ pub fn get_undo_action(state: &EditorState, message: &Message) -> Result<UndoAct
                 color: state.palettes[idx].colors[color_idx as usize],
             })
         }
-        Message::ChangeRed(_) => UndoAction::None,
-        Message::ChangeGreen(_) => UndoAction::None,
-        Message::ChangeBlue(_) => UndoAction::None,
+        // The picked color is applied with `BrushColor`, which is undone:
+        Message::PickColorHsv { .. } => UndoAction::None,
+        Message::SetRgbHexText(_) => UndoAction::None,
+        Message::ToggleEyedropper => UndoAction::None,
+        Message::PickAreaColor { .. } => UndoAction::None,
+        // Swapping is its own inverse:
+        Message::SwapColors { .. } => UndoAction::Ok(message.clone()),
+        Message::AdjustPaletteDialogue => UndoAction::None,
+        Message::SetPaletteAdjust(_) => UndoAction::None,
+        &Message::AdjustPaletteHSV { palette_id, .. }
+        | &Message::CopyPaletteColors { palette_id, .. }
+        | &Message::InterpolatePalettes { palette_id, .. }
+        | &Message::SetPaletteColors { palette_id, .. } => {
+            let idx = *state
+                .palettes_id_idx_map
+                .get(&palette_id)
+                .context("palette not found")?;
+            UndoAction::Ok(Message::SetPaletteColors {
+                palette_id,
+                colors: state.palettes[idx].colors,
+            })
+        }
         &Message::AddTileRow(palette_id) => UndoAction::Ok(Message::DeleteTileRow(palette_id)),
         Message::DeleteTileRow(palette_id) => {
             let idx = *state
@@ -157,6 +334,391 @@ pub fn get_undo_action(state: &EditorState, message: &Message) -> Result<UndoAct
                 v_flippable: state.palettes[idx].tiles[tile_idx as usize].v_flippable,
             })
         }
+        Message::SetSelectedTilesProperty(_) => UndoAction::None,
+        Message::SetTileProperties {
+            palette_id,
+            properties,
+        } => {
+            let idx = *state
+                .palettes_id_idx_map
+                .get(palette_id)
+                .context("palette not found")?;
+            let tiles = &state.palettes[idx].tiles;
+            let mut old_properties = vec![];
+            for &(tile_idx, _) in properties {
+                let tile = tiles.get(tile_idx as usize).context("tile not found")?;
+                old_properties.push((tile_idx, tile.properties()));
+            }
+            UndoAction::Ok(Message::SetTileProperties {
+                palette_id: *palette_id,
+                properties: old_properties,
+            })
+        }
+        Message::FlipSuggestionsDialogue => UndoAction::None,
+        Message::ToggleFlipSuggestion(_) => UndoAction::None,
+        Message::SetAllFlipSuggestions(_) => UndoAction::None,
+        Message::ApplyFlipSuggestions => UndoAction::None,
+        Message::DuplicateTilesDialogue(_) => UndoAction::None,
+        Message::ApplyDuplicateTiles => UndoAction::None,
+        &Message::MergeFlippedTile {
+            palette_id,
+            tile_idx,
+            ..
+        } => UndoAction::Ok(Message::SetTileUsages {
+            palette_id,
+            usages: state.tile_usages(palette_id, tile_idx)?,
+        }),
+        Message::FindTileUsages => UndoAction::None,
+        Message::TileUsagesFound { .. } => UndoAction::None,
+        Message::GoToTileUsage(_) => UndoAction::None,
+        Message::CloseTileUsages => UndoAction::None,
+        Message::ValidateProject => UndoAction::None,
+        Message::ValidationFinished(_) => UndoAction::None,
+        Message::GoToIssue(_) => UndoAction::None,
+        Message::CloseIssues => UndoAction::None,
+        Message::ShowAreaDiff(_) => UndoAction::None,
+        Message::CloseAreaDiff => UndoAction::None,
+        Message::ShowPaletteReplace
+        | Message::SetPaletteReplaceFrom(_)
+        | Message::SetPaletteReplaceTo(_)
+        | Message::ClosePaletteReplace => UndoAction::None,
+        &Message::ReplaceAreaPalette {
+            ref area_id,
+            from,
+            to,
+        } => {
+            let cells = palette_cells(&state.area_copy(area_id)?, from);
+            if from == to || cells.is_empty() {
+                UndoAction::None
+            } else {
+                UndoAction::Ok(Message::SetAreaPalettes {
+                    area_id: area_id.clone(),
+                    palette_id: from,
+                    cells,
+                })
+            }
+        }
+        Message::SetAreaPalettes { area_id, cells, .. } => {
+            // Restore each cell's palette, with a message for each palette that they had:
+            let area = state.area_copy(area_id)?;
+            let mut old: BTreeMap<PaletteId, Vec<(TileCoord, TileCoord)>> = BTreeMap::new();
+            for &(x, y) in cells {
+                old.entry(area.get_palette(x, y)?).or_default().push((x, y));
+            }
+            UndoAction::Ok(Message::Batch(
+                old.into_iter()
+                    .map(|(palette_id, cells)| Message::SetAreaPalettes {
+                        area_id: area_id.clone(),
+                        palette_id,
+                        cells,
+                    })
+                    .collect(),
+            ))
+        }
+        Message::GoToDiffScreen(_) => UndoAction::None,
+        &Message::SetTileUsages {
+            palette_id,
+            ref usages,
+        } => {
+            let mut old_usages = vec![];
+            for (area_id, area_usages) in &usages.iter().chunk_by(|u| &u.area_id) {
+                let loaded_area;
+                let area = match state.areas.get(area_id) {
+                    Some(area) => area,
+                    None => {
+                        loaded_area = load_area(state, area_id)?;
+                        &loaded_area
+                    }
+                };
+                for u in area_usages {
+                    old_usages.push(TileUsage {
+                        tile_idx: area.get_tile(u.x, u.y)?,
+                        flip: area.get_flip(u.x, u.y)?,
+                        ..u.clone()
+                    });
+                }
+            }
+            UndoAction::Ok(Message::SetTileUsages {
+                palette_id,
+                usages: old_usages,
+            })
+        }
+        Message::ToggleMacroRecording => {
+            if state.macro_recording.is_some() {
+                // Stopping the recording saves the macro:
+                UndoAction::Ok(Message::SetMacros(state.project_metadata.macros.clone()))
+            } else {
+                UndoAction::None
+            }
+        }
+        Message::MacrosDialogue => UndoAction::None,
+        Message::SetMacroReplayCoords(_, _) => UndoAction::None,
+        // The replayed edits are undone as a batch, of their own:
+        Message::ReplayMacro { .. } => UndoAction::None,
+        Message::DeleteMacro(_) | Message::SetMacroName(_, _) | Message::SetMacros(_) => {
+            UndoAction::Ok(Message::SetMacros(state.project_metadata.macros.clone()))
+        }
+        Message::ShowMetatiles(_) | Message::SelectMetatile(_) | Message::StampMetatile(_) => {
+            UndoAction::None
+        }
+        // Stamps are saved to their own files rather than edited in the project:
+        Message::ShowStamps(_)
+        | Message::SetStampName(_)
+        | Message::SaveStamp
+        | Message::LoadStamp(_)
+        | Message::DeleteStamp(_) => UndoAction::None,
+        Message::AddMetatile(_)
+        | Message::SetMetatileName(_, _)
+        | Message::SetMetatileCell { .. }
+        | Message::DeleteMetatile(_)
+        | Message::SetMetatiles(_) => UndoAction::Ok(Message::SetMetatiles(
+            state.project_metadata.metatiles.clone(),
+        )),
+        Message::ColorRampsDialogue => UndoAction::None,
+        Message::ExportPaletteDialogue => UndoAction::None,
+        Message::SetExportPaletteLabeled(_) => UndoAction::None,
+        Message::SetExportPaletteTileTable(_) => UndoAction::None,
+        Message::ExportPalette => UndoAction::None,
+        Message::ExportPaletteTo(_) => UndoAction::None,
+        Message::TileGraphicsDialogue => UndoAction::None,
+        Message::SetTileGraphicsFormat(_) => UndoAction::None,
+        Message::ExportTileGraphics => UndoAction::None,
+        Message::ExportTileGraphicsTo(_) => UndoAction::None,
+        Message::ImportTileGraphics => UndoAction::None,
+        // Undone as the `ReplacePalette` that it sends:
+        Message::ImportTileGraphicsFrom(_) => UndoAction::None,
+        Message::LibraryDialogue => UndoAction::None,
+        Message::PickLibraryDir => UndoAction::None,
+        Message::LibraryDirPicked(_) => UndoAction::None,
+        Message::SetLibraryFilter(_) => UndoAction::None,
+        Message::SetLibraryPublishName(_) => UndoAction::None,
+        Message::SetLibraryPublishTags(_) => UndoAction::None,
+        Message::PublishToLibrary(_) => UndoAction::None,
+        Message::ImportFromLibrary(_) => UndoAction::None,
+        Message::DeleteFromLibrary(_) => UndoAction::None,
+        Message::SetBrush(_) => UndoAction::None,
+        Message::ToggleCollisionOverlay => UndoAction::None,
+        Message::ToggleEntrances => UndoAction::None,
+        Message::MoveEntrance {
+            position,
+            area_id,
+            idx,
+            coords: _,
+        } => {
+            let area = state.area_copy(area_id)?;
+            let entrance = area.entrances.get(*idx).context("entrance not found")?;
+            UndoAction::Ok(Message::MoveEntrance {
+                position: *position,
+                area_id: area_id.clone(),
+                idx: *idx,
+                coords: Point::new(entrance.position.0, entrance.position.1),
+            })
+        }
+        Message::ToggleSecrets => UndoAction::None,
+        Message::ToggleSpritePreview => UndoAction::None,
+        Message::SetEditLayer(_) => UndoAction::None,
+        Message::ShowSecrets(_) => UndoAction::None,
+        Message::InsertSecret {
+            position,
+            area_id,
+            idx,
+            secret: _,
+        } => UndoAction::Ok(Message::RemoveSecret {
+            position: *position,
+            area_id: area_id.clone(),
+            idx: *idx,
+        }),
+        Message::RemoveSecret {
+            position,
+            area_id,
+            idx,
+        } => {
+            let area = state.area_copy(area_id)?;
+            let secret = area.secrets.get(*idx).context("secret not found")?;
+            UndoAction::Ok(Message::InsertSecret {
+                position: *position,
+                area_id: area_id.clone(),
+                idx: *idx,
+                secret: *secret,
+            })
+        }
+        Message::SetSecret {
+            position,
+            area_id,
+            idx,
+            secret: _,
+        } => {
+            let area = state.area_copy(area_id)?;
+            let secret = area.secrets.get(*idx).context("secret not found")?;
+            UndoAction::Ok(Message::SetSecret {
+                position: *position,
+                area_id: area_id.clone(),
+                idx: *idx,
+                secret: *secret,
+            })
+        }
+        Message::ToggleTravel => UndoAction::None,
+        Message::TravelPointDialogue { .. } => UndoAction::None,
+        Message::SetTravelPoint {
+            position,
+            area_id,
+            idx,
+            point: _,
+        } => {
+            let area = &state.areas[area_id];
+            let point = area.travel.get(*idx).context("travel point not found")?;
+            UndoAction::Ok(Message::SetTravelPoint {
+                position: *position,
+                area_id: area_id.clone(),
+                idx: *idx,
+                point: *point,
+            })
+        }
+        Message::EditTravelPosition(..) => UndoAction::None,
+        Message::ToggleAnnotations => UndoAction::None,
+        Message::ShowAnnotations(_) => UndoAction::None,
+        Message::SetScreenAnnotation {
+            position,
+            area_id,
+            screen,
+            value: _,
+        } => {
+            let area = state.area_copy(area_id)?;
+            UndoAction::Ok(Message::SetScreenAnnotation {
+                position: *position,
+                area_id: area_id.clone(),
+                screen: *screen,
+                value: annotation(&area, *screen),
+            })
+        }
+        Message::ExportAnnotations => UndoAction::None,
+        Message::ExportAnnotationsTo(_) => UndoAction::None,
+        Message::SetCameraLocked {
+            position,
+            area_id,
+            screen,
+            locked: _,
+        } => {
+            let area = state.area_copy(area_id)?;
+            UndoAction::Ok(Message::SetCameraLocked {
+                position: *position,
+                area_id: area_id.clone(),
+                screen: *screen,
+                locked: is_camera_locked(&area, *screen),
+            })
+        }
+        Message::ExportCameraLocks => UndoAction::None,
+        Message::ExportCameraLocksTo(_) => UndoAction::None,
+        Message::ShowExternalTools(_) => UndoAction::None,
+        Message::AddExternalTool
+        | Message::SetExternalTool(_, _)
+        | Message::DeleteExternalTool(_)
+        | Message::SetExternalTools(_) => UndoAction::Ok(Message::SetExternalTools(
+            state.project_metadata.external_tools.clone(),
+        )),
+        Message::RunExternalTool(_) => UndoAction::None,
+        Message::ExternalToolOutput(_) => UndoAction::None,
+        Message::ExternalToolFinished { .. } => UndoAction::None,
+        Message::RunTestRom => UndoAction::None,
+        Message::BuildTestRom => UndoAction::None,
+        Message::LaunchTestRom => UndoAction::None,
+        Message::TestRunFinished(_) => UndoAction::None,
+        Message::PickEmulator => UndoAction::None,
+        Message::EmulatorPicked(_) => UndoAction::None,
+        Message::PickTestBaseRom => UndoAction::None,
+        Message::TestBaseRomPicked(_) => UndoAction::None,
+        Message::ClearToolConsole => UndoAction::None,
+        Message::SetCollisionBrush(_) => UndoAction::None,
+        Message::AreaScrolled { .. } => UndoAction::None,
+        Message::SetAutoPan(_) => UndoAction::None,
+        Message::AutoPanTick => UndoAction::None,
+        Message::ScrollAreaTo { .. } => UndoAction::None,
+        Message::ZoomArea { .. } => UndoAction::None,
+        Message::ToggleLinkedScroll => UndoAction::None,
+        Message::ToggleSideDifferences => UndoAction::None,
+        Message::ToggleGuide { .. } => UndoAction::None,
+        Message::CopyTiles => UndoAction::None,
+        Message::PasteTiles => UndoAction::None,
+        // The pasted tiles are added by a batch of undoable edits:
+        Message::PastedTiles(_) => UndoAction::None,
+        Message::CopySelectionJson => UndoAction::None,
+        Message::PasteSelectionJson => UndoAction::None,
+        // The pasted selection only becomes the brush:
+        Message::PastedSelectionJson(_) => UndoAction::None,
+        Message::SetSnesColorText(_)
+        | Message::CopySnesColor
+        | Message::PasteSnesColor
+        | Message::PastedSnesColor(_) => UndoAction::None,
+        Message::SetColorRampRange { .. } => UndoAction::None,
+        Message::SelectRampShade(_) => UndoAction::None,
+        Message::AddColorRamp { .. }
+        | Message::DeleteColorRamp(_)
+        | Message::SetColorRampName(_, _)
+        | Message::SetColorRamps(_) => UndoAction::Ok(Message::SetColorRamps(
+            state.project_metadata.color_ramps.clone(),
+        )),
+        &Message::SetRampColor {
+            ramp_idx, shade, ..
+        } => {
+            let ramp = state
+                .project_metadata
+                .color_ramps
+                .get(ramp_idx)
+                .context("color ramp not found")?;
+            UndoAction::Ok(Message::SetRampColor {
+                ramp_idx,
+                shade,
+                color: *ramp.colors.get(shade).context("shade not found")?,
+            })
+        }
+        &Message::SubscribeColorRamp {
+            ramp_idx,
+            palette_id,
+            start,
+        }
+        | &Message::UnsubscribeColorRamp {
+            ramp_idx,
+            palette_id,
+            start,
+        } => {
+            let ramp = state
+                .project_metadata
+                .color_ramps
+                .get(ramp_idx)
+                .context("color ramp not found")?;
+            let subscribe = matches!(message, Message::SubscribeColorRamp { .. });
+            if subscribe == ramp.is_subscribed(palette_id, start) || !ramp.fits_at(start) {
+                return Ok(UndoAction::None);
+            }
+            let pal_idx = *state
+                .palettes_id_idx_map
+                .get(&palette_id)
+                .context("palette not found")?;
+            let reverse = if subscribe {
+                Message::UnsubscribeColorRamp {
+                    ramp_idx,
+                    palette_id,
+                    start,
+                }
+            } else {
+                Message::SubscribeColorRamp {
+                    ramp_idx,
+                    palette_id,
+                    start,
+                }
+            };
+            // Subscribing overwrites the palette's colors with the ramp's, so restore them:
+            let mut messages = vec![reverse];
+            for i in 0..ramp.colors.len() {
+                let color_idx = start + i as ColorIdx;
+                messages.push(Message::BrushColor {
+                    palette_id,
+                    color_idx,
+                    color: state.palettes[pal_idx].colors[color_idx as usize],
+                });
+            }
+            UndoAction::Ok(Message::Batch(messages))
+        }
         &Message::TilesetBrush {
             palette_id,
             coords: Point { x: x0, y: y0 },
@@ -206,18 +768,59 @@ pub fn get_undo_action(state: &EditorState, message: &Message) -> Result<UndoAct
                 color_idx: c,
             })
         }
+        Message::SetTilePixels { palette_id, pixels } => {
+            let pal_idx = *state
+                .palettes_id_idx_map
+                .get(palette_id)
+                .context("undefined palette")?;
+            let tiles = &state.palettes[pal_idx].tiles;
+            let mut old_pixels = vec![];
+            // Restored in reverse order, so that pixels set more than once end up as before:
+            for &(tile_idx, coords, _) in pixels.iter().rev() {
+                let tile = tiles.get(tile_idx as usize).context("tile not found")?;
+                let c = tile.pixels[coords.y as usize][coords.x as usize];
+                old_pixels.push((tile_idx, coords, c));
+            }
+            UndoAction::Ok(Message::SetTilePixels {
+                palette_id: *palette_id,
+                pixels: old_pixels,
+            })
+        }
         Message::SelectArea(_, _) => UndoAction::None,
         Message::AddAreaDialogue => UndoAction::None,
         Message::SetAddAreaName(_) => UndoAction::None,
         Message::SetAddAreaSizeX(_) => UndoAction::None,
         Message::SetAddAreaSizeY(_) => UndoAction::None,
         Message::AddArea { name, size: _ } => UndoAction::Ok(Message::DeleteArea(name.clone())),
+        Message::OpenAreaManifest => UndoAction::None,
+        Message::AreaManifestOpened(_) => UndoAction::None,
+        Message::CreateManifestArea(entry) => {
+            // Existing areas are skipped rather than replaced.
+            if state.area_names.contains(&entry.name) {
+                UndoAction::None
+            } else {
+                UndoAction::Ok(Message::DeleteArea(entry.name.clone()))
+            }
+        }
         Message::EditAreaDialogue => UndoAction::None,
         Message::SetEditAreaName(_) => UndoAction::None,
         Message::EditArea { old_name, new_name } => UndoAction::Ok(Message::EditArea {
             old_name: new_name.clone(),
             new_name: old_name.clone(),
         }),
+        Message::ToggleBGColorEditor => UndoAction::None,
+        Message::BGColorsDialogue => UndoAction::None,
+        Message::SelectBGColor { .. } => UndoAction::None,
+        Message::SetBGColorsColor(_) => UndoAction::None,
+        Message::SetBGColorColumn => UndoAction::None,
+        Message::CopyBGColorRow(_) => UndoAction::None,
+        Message::SetBGColors(colors) => {
+            let old_colors = colors
+                .iter()
+                .map(|(area_id, _)| Ok((area_id.clone(), state.area_copy(area_id)?.bg_color)))
+                .collect::<Result<_>>()?;
+            UndoAction::Ok(Message::SetBGColors(old_colors))
+        }
         Message::EditAreaBGRed(_) => UndoAction::None,
         Message::EditAreaBGGreen(_) => UndoAction::None,
         Message::EditAreaBGBlue(_) => UndoAction::None,
@@ -228,12 +831,107 @@ pub fn get_undo_action(state: &EditorState, message: &Message) -> Result<UndoAct
             area_id: area_id.clone(),
             color: state.areas[area_id].bg_color,
         }),
+        Message::EditAreaProperty(_) => UndoAction::None,
+        Message::SetAreaProperties { area_id, .. } => UndoAction::Ok(Message::SetAreaProperties {
+            area_id: area_id.clone(),
+            properties: state.areas[area_id].properties.clone(),
+        }),
         Message::DeleteAreaDialogue => UndoAction::None,
-        Message::DeleteArea(_) => UndoAction::Irreversible,
+        Message::DeleteArea(name) => {
+            if state.area_names.len() == 1 {
+                UndoAction::None
+            } else {
+                let mut areas = vec![];
+                for theme in &state.theme_names {
+                    areas.push(state.area_copy(&AreaId {
+                        area: name.clone(),
+                        theme: theme.clone(),
+                    })?);
+                }
+                let metadata = &state.project_metadata;
+                UndoAction::Ok(Message::RestoreArea {
+                    areas,
+                    group: metadata.area_groups.get(name).cloned(),
+                    world_position: metadata.world_layout.get(name).copied(),
+                })
+            }
+        }
+        Message::RestoreArea { areas, .. } => {
+            let area = areas.first().context("internal error")?;
+            UndoAction::Ok(Message::DeleteArea(area.name.clone()))
+        }
+        Message::DarkWorldDialogue => UndoAction::None,
+        Message::SetDarkWorldPalette { .. } => UndoAction::None,
+        Message::GenerateDarkWorldArea {
+            light_area,
+            mapping,
+        } => {
+            // If the areas can't be generated, the message is refused without changes.
+            let Ok(areas) = dark_world_areas(state, light_area, mapping) else {
+                return Ok(UndoAction::None);
+            };
+            let name = &areas.first().context("internal error")?.name;
+            if state.area_names.contains(name) {
+                let old_areas = state.theme_names.iter().map(|theme| {
+                    state.area_copy(&AreaId {
+                        area: name.clone(),
+                        theme: theme.clone(),
+                    })
+                });
+                let metadata = &state.project_metadata;
+                UndoAction::Ok(Message::Batch(vec![
+                    Message::DeleteArea(name.clone()),
+                    Message::RestoreArea {
+                        areas: old_areas.collect::<Result<_>>()?,
+                        group: metadata.area_groups.get(name).cloned(),
+                        world_position: metadata.world_layout.get(name).copied(),
+                    },
+                ]))
+            } else {
+                UndoAction::Ok(Message::DeleteArea(name.clone()))
+            }
+        }
+        Message::DuplicateAreaDialogue => UndoAction::None,
+        Message::SetDuplicateAreaName(_) => UndoAction::None,
+        Message::DuplicateArea { new_name, .. } => {
+            UndoAction::Ok(Message::DeleteArea(new_name.clone()))
+        }
+        Message::CompileCheckArea => UndoAction::None,
+        Message::CompressionEstimate => UndoAction::None,
+        Message::VramUsage(_) => UndoAction::None,
+        Message::GoToAreaScreen(..) => UndoAction::None,
+        Message::AreaLabelsDialogue => UndoAction::None,
+        Message::SetLabelPreviewText(_) => UndoAction::None,
+        Message::SetLabelFont(_) | Message::SetLabelFontField(_) => UndoAction::Ok(
+            Message::SetLabelFont(state.project_metadata.label_font.clone()),
+        ),
+        Message::ExportAreaNameTable => UndoAction::None,
+        Message::AreaPickerDialogue(_) => UndoAction::None,
+        Message::SetAreaPickerQuery(_) => UndoAction::None,
+        Message::PickArea(_) => UndoAction::None,
+        Message::PickSelectedArea => UndoAction::None,
+        Message::AreaListDialogue(_) => UndoAction::None,
+        Message::SetNewAreaGroupName(_) => UndoAction::None,
+        Message::ToggleAreaGroup(_) => UndoAction::None,
+        Message::DragArea(_) => UndoAction::None,
+        Message::ReleaseOnArea(_) => UndoAction::None,
+        Message::ReleaseOnAreaGroup(_) => UndoAction::None,
+        Message::SetAreaGroup { area, group: _ } => UndoAction::Ok(Message::SetAreaGroup {
+            area: area.clone(),
+            group: state.project_metadata.area_groups.get(area).cloned(),
+        }),
+        Message::OpenWorldMap(_) => UndoAction::None,
+        Message::CloseWorldMap => UndoAction::None,
+        Message::SetWorldMapPosition { area, .. } => UndoAction::Ok(Message::SetWorldMapPosition {
+            area: area.clone(),
+            position: state.project_metadata.world_layout.get(area).copied(),
+        }),
+        Message::OpenWorldMapArea(_) => UndoAction::None,
         Message::SelectTheme(_, _) => UndoAction::None,
         Message::AddThemeDialogue => UndoAction::None,
         Message::SetAddThemeName(_) => UndoAction::None,
         Message::AddTheme(theme_name) => UndoAction::Ok(Message::DeleteTheme(theme_name.clone())),
+        Message::DuplicateThemeDialogue => UndoAction::None,
         Message::RenameThemeDialogue => UndoAction::None,
         Message::SetRenameThemeName(_) => UndoAction::None,
         Message::RenameTheme { old_name, new_name } => UndoAction::Ok(Message::RenameTheme {
@@ -241,7 +939,17 @@ pub fn get_undo_action(state: &EditorState, message: &Message) -> Result<UndoAct
             new_name: old_name.clone(),
         }),
         Message::DeleteThemeDialogue => UndoAction::None,
-        Message::DeleteTheme(_) => UndoAction::Irreversible,
+        Message::DeleteTheme(theme) => {
+            if state.theme_names.len() == 1 {
+                UndoAction::None
+            } else {
+                UndoAction::Ok(Message::RestoreTheme {
+                    theme: theme.clone(),
+                    areas: state.theme_areas(theme)?,
+                })
+            }
+        }
+        Message::RestoreTheme { theme, .. } => UndoAction::Ok(Message::DeleteTheme(theme.clone())),
         Message::StartTileSelection(_, _) => UndoAction::None,
         Message::ProgressTileSelection(_) => UndoAction::None,
         Message::EndTileSelection(_) => UndoAction::None,
@@ -279,6 +987,7 @@ pub fn get_undo_action(state: &EditorState, message: &Message) -> Result<UndoAct
                 palettes,
                 tiles,
                 flips,
+                mask: None,
             };
             UndoAction::Ok(Message::AreaBrush {
                 position: *position,
@@ -288,7 +997,80 @@ pub fn get_undo_action(state: &EditorState, message: &Message) -> Result<UndoAct
                 palette_only: *palette_only,
             })
         }
+        Message::AreaShape {
+            position,
+            area_id,
+            shape,
+            selection,
+            palette_only,
+        } => {
+            let area = &state.areas[area_id];
+            let cells = shape_cells(area, *shape, selection, *palette_only)
+                .iter()
+                .map(|c| AreaCell::get(area, c.x, c.y))
+                .collect::<Result<_>>()?;
+            UndoAction::Ok(Message::SetAreaCells {
+                position: *position,
+                area_id: area_id.clone(),
+                cells,
+            })
+        }
+        Message::SetAreaCells {
+            position,
+            area_id,
+            cells,
+        } => {
+            let area = &state.areas[area_id];
+            let cells = cells
+                .iter()
+                .map(|c| AreaCell::get(area, c.x, c.y))
+                .collect::<Result<_>>()?;
+            UndoAction::Ok(Message::SetAreaCells {
+                position: *position,
+                area_id: area_id.clone(),
+                cells,
+            })
+        }
+        Message::SetOverlayCells {
+            position,
+            area_id,
+            cells,
+        } => {
+            let area = &state.areas[area_id];
+            let cells = cells
+                .iter()
+                .map(|c| OverlayCell::get(area, c.x, c.y))
+                .collect::<Result<_>>()?;
+            UndoAction::Ok(Message::SetOverlayCells {
+                position: *position,
+                area_id: area_id.clone(),
+                cells,
+            })
+        }
+        Message::SetThemeSync(_) => UndoAction::None,
+        Message::MirrorAreaCells { area_id, cells } => {
+            let loaded_area;
+            let area = match state.areas.get(area_id) {
+                Some(area) => area,
+                None => {
+                    loaded_area = load_area(state, area_id)?;
+                    &loaded_area
+                }
+            };
+            let cells = cells
+                .iter()
+                .map(|c| AreaCell::get(area, c.x, c.y))
+                .collect::<Result<_>>()?;
+            UndoAction::Ok(Message::MirrorAreaCells {
+                area_id: area_id.clone(),
+                cells,
+            })
+        }
+        Message::SelectRegion(_, _) => UndoAction::None,
+        Message::ToggleBrushMask(_, _) => UndoAction::None,
+        Message::ClearBrushMask => UndoAction::None,
         Message::OpenTile { .. } => UndoAction::None,
+        Message::OpenTileGraphics { .. } => UndoAction::None,
     };
     Ok(action)
 }
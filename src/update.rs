@@ -1,22 +1,91 @@
 use iced::{
     keyboard::{self, key},
-    widget, window, Event, Point, Task,
+    mouse,
+    widget::{self, pane_grid, scrollable, scrollable::AbsoluteOffset},
+    window, Event, Point, Size, Task, Vector,
 };
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::{Duration, Instant},
+};
+
 use itertools::Itertools;
 use log::{error, info, warn};
 
 use crate::{
-    import::Importer,
+    annotations::{export_annotations, set_annotation},
+    area_diff::AreaDiff,
+    area_manifest::load_area_manifest,
+    area_picker::AreaPicker,
+    area_shapes::{fill_region, region_block, shape_cells},
+    auto_pan::{panned_scroll, PanAction},
+    brush_rotation::rotated_block,
+    bug_report::{save_bug_report, SessionRecorder},
+    camera_locks::export_camera_locks,
+    clipboard::{ClipboardTiles, SelectionJson},
+    compile_check::check_area,
+    dark_world::{dark_world_area_name, dark_world_areas, dark_world_map_id, find_area_for_map},
+    dirty,
+    entrances::MARKER_SIZE,
+    export::Exporter,
+    external_changes::{apply_changes, pending_changes, ChangedFile},
+    external_tools::{self, ExternalTool, MAX_CONSOLE_LINES},
+    flip_analysis::find_flip_suggestions,
+    heatmap::Heatmap,
+    helpers::{format_snes_color, parse_rgb_hex, parse_snes_color},
+    import::{load_graphics_sheets, BorrowGraphics},
+    import_rules::{set_rules_path, ImportRules},
+    keymap::{active_keymap, is_bound, key_action, KeyAction},
+    library::{self, load_library, parse_tags},
+    macros::{is_recordable, EditMacro},
+    map_compression::estimate_theme,
+    memory_report,
     message::{Message, SelectionSource},
+    metatiles::Metatile,
+    overlays::layer_message,
+    palette_adjust::{hsv_to_rgb, interpolate, shift_hsv, PaletteAdjust, PaletteColors},
+    palette_groups::{moved_group_order, set_palette_groups, ALL_PALETTE_GROUPS},
+    palette_replace::{palette_cells, PaletteReplace},
+    palette_slots::{area_palettes, check_assignment, load_assignment, save_assignment, solve},
+    panes::pane_layout,
+    persist::RebuildScope,
     persist::{
-        self, copy_area_theme, delete_area, delete_area_theme, delete_palette, load_area_list,
-        rename_area, rename_area_theme, save_area, save_area_png,
+        self, copy_area_theme, delete_area, delete_area_theme, delete_palette, load_area,
+        load_area_list, load_palette_history, rename_area, rename_area_theme, save_area,
     },
+    project_load, project_rebuild, project_stats,
+    ramps::{ColorRamp, RampSubscriber, MIN_RAMP_LEN},
+    renames::{load_renames, record_rename, RenameKind},
+    rom_import,
+    scripting::run_script,
+    secrets::{snap_position, Secret},
+    stamps::{self, load_stamps},
     state::{
-        Area, AreaId, AreaPosition, Dialogue, EditorState, Flip, Focus, PaletteId, Screen, SidePanelView, Tile, TileBlock, TileIdx, Tool, MAX_PIXEL_SIZE, MIN_PIXEL_SIZE
+        Area, AreaId, AreaPosition, ColorRGB, Dialogue, EditorState, Flip, Focus, PaletteId,
+        ProjectSnapshot, SidePanelView, Tile, TileBlock, TileCoord, TileIdx, TileUsage, Tool,
+        UnsavedChangesAction, UpdateTiming, MAX_PIXEL_SIZE, MIN_PIXEL_SIZE, UNGROUPED_AREA_GROUP,
+        ZOOM_PRESETS,
     },
+    test_rom::{self, TestRunStage},
+    theme_sync::synced_message,
+    tile_dedup::{find_duplicate_tiles, merge_messages},
+    tile_graphics::{
+        export_graphics, graphics_file_name, read_graphics, with_imported_graphics, GraphicsFormat,
+    },
+    travel::TravelPoint,
     undo::{get_undo_action, UndoAction},
-    view::{open_project, open_rom},
+    usages::{UsageScan, UsageSearch},
+    validation::{IssueLocation, ValidationScan},
+    view::{
+        area_scrollable_id, area_tile_at, open_area_manifest, open_emulator, open_heatmap,
+        open_import_rules, open_library_dir, open_project, open_rom, open_rom_folder,
+        open_tile_graphics, save_annotations_json, save_bug_report_file, save_camera_locks_asm,
+        save_cheat_sheet_png, save_palette_png, save_rom_file, save_tile_graphics, save_tile_table,
+        TutorialTarget, AREA_SCROLLBAR_PADDING, TUTORIAL_STEPS,
+    },
+    vram_usage::vram_usage,
+    window_state::{primary_monitor_size, WindowGeometry, DEFAULT_WINDOW_SIZE},
+    world_map::WorldMap,
 };
 use anyhow::{bail, Context, Result};
 
@@ -85,6 +154,36 @@ fn should_debounce(message: &Message, last_message: &Message) -> bool {
             }
             _ => false,
         },
+        // A brush stroke mirrored into a synced theme:
+        Message::Batch(messages) => match last_message {
+            Message::Batch(last_messages) => {
+                messages.len() == last_messages.len()
+                    && messages
+                        .iter()
+                        .zip(last_messages)
+                        .all(|(m, last)| should_debounce(m, last))
+            }
+            _ => false,
+        },
+        Message::SetOverlayCells {
+            position,
+            area_id,
+            cells,
+        } => match last_message {
+            Message::SetOverlayCells {
+                position: last_position,
+                area_id: last_area_id,
+                cells: last_cells,
+            } => position == last_position && area_id == last_area_id && cells == last_cells,
+            _ => false,
+        },
+        Message::MirrorAreaCells { area_id, cells } => match last_message {
+            Message::MirrorAreaCells {
+                area_id: last_area_id,
+                cells: last_cells,
+            } => area_id == last_area_id && cells == last_cells,
+            _ => false,
+        },
         Message::AreaBrush {
             position,
             area_id,
@@ -111,15 +210,314 @@ fn should_debounce(message: &Message, last_message: &Message) -> bool {
     }
 }
 
+// Record the window's geometry after it moves or is resized, along with whether it's maximized.
+// Changes while minimized are ignored, since the reported geometry isn't meaningful.
+fn window_geometry_task(position: Option<Point>, size: Option<iced::Size>) -> Task<Message> {
+    window::get_latest().and_then(move |id| {
+        window::get_minimized(id).then(move |minimized| {
+            if minimized == Some(true) {
+                return Task::none();
+            }
+            window::get_maximized(id).map(move |maximized| Message::SetWindowGeometry {
+                position,
+                size,
+                maximized,
+            })
+        })
+    })
+}
+
+fn record_message(state: &mut EditorState, message: &Message) {
+    if state.session_recorder.is_none() {
+        match SessionRecorder::start(state) {
+            Ok(recorder) => state.session_recorder = Some(recorder),
+            Err(e) => {
+                // Don't keep retrying for every message:
+                error!("Unable to start session recording: {:#}", e);
+                state.global_config.record_session = false;
+                return;
+            }
+        }
+    }
+    if let Some(recorder) = &mut state.session_recorder {
+        if let Err(e) = recorder.record(message) {
+            error!("Error recording session: {:#}", e);
+        }
+    }
+}
+
+// Scroll the area picker's list to keep its highlighted area in view.
+fn scroll_area_picker(state: &EditorState) -> Task<Message> {
+    let Some(Dialogue::AreaPicker(picker)) = &state.dialogue else {
+        return Task::none();
+    };
+    let len = picker.matches().len();
+    let y = picker.selected as f32 / len.saturating_sub(1).max(1) as f32;
+    scrollable::snap_to(
+        scrollable::Id::new("AreaPicker"),
+        scrollable::RelativeOffset { x: 0.0, y },
+    )
+}
+
+fn toggle_side_panel(state: &mut EditorState) {
+    state.side_panel_hidden = !state.side_panel_hidden;
+    if state.side_panel_hidden {
+        // Keyboard input shouldn't go to controls that are no longer visible:
+        let side_focus = !matches!(
+            state.focus,
+            Focus::None
+                | Focus::PickArea(AreaPosition::Main)
+                | Focus::PickTheme(AreaPosition::Main)
+                | Focus::Area(AreaPosition::Main)
+        );
+        if side_focus {
+            state.focus = Focus::None;
+        }
+    }
+}
+
+// Perform the action of a keyboard shortcut.
+fn apply_key_action(state: &mut EditorState, action: KeyAction) -> Result<Option<Task<Message>>> {
+    match action {
+        KeyAction::BrushTool => {
+            state.tool = Tool::Brush;
+        }
+        KeyAction::SelectTool => {
+            state.tool = Tool::Select;
+        }
+        KeyAction::RectangleTool => {
+            state.tool = Tool::Rectangle;
+        }
+        KeyAction::LineTool => {
+            state.tool = Tool::Line;
+        }
+        KeyAction::FillTool => {
+            state.tool = Tool::Fill;
+        }
+        KeyAction::MagicWandTool => {
+            state.tool = Tool::MagicWand;
+        }
+        KeyAction::CollisionTool => {
+            state.tool = Tool::Collision;
+            state.show_collision = true;
+        }
+        KeyAction::ToggleCollision => {
+            state.show_collision = !state.show_collision;
+        }
+        KeyAction::ToggleEntrances => {
+            state.show_entrances = !state.show_entrances;
+        }
+        KeyAction::ToggleSecrets => {
+            state.show_secrets = !state.show_secrets;
+        }
+        KeyAction::ToggleSpritePreview => {
+            state.show_sprite_preview = !state.show_sprite_preview;
+        }
+        KeyAction::ToggleGrid => {
+            state.show_grid = !state.show_grid;
+        }
+        KeyAction::ToggleRulers => {
+            state.show_rulers = !state.show_rulers;
+        }
+        KeyAction::ToggleMinimap => {
+            state.show_minimap = !state.show_minimap;
+        }
+        KeyAction::TilesetView => {
+            state.side_panel_view = SidePanelView::Tileset;
+            state.side_panel_hidden = false;
+        }
+        KeyAction::AreaView => {
+            state.side_panel_view = SidePanelView::Area;
+            state.side_panel_hidden = false;
+        }
+        KeyAction::MetatileView => {
+            state.side_panel_view = SidePanelView::Metatiles;
+            state.side_panel_hidden = false;
+        }
+        KeyAction::ToggleSidePanel => {
+            toggle_side_panel(state);
+        }
+        KeyAction::WorldMap => {
+            if state.world_map.is_some() {
+                state.world_map = None;
+            } else {
+                let theme = state.main_area().theme.clone();
+                state.world_map = Some(WorldMap::load(state, &theme)?);
+            }
+        }
+        KeyAction::FlipHorizontal => {
+            for i in 0..state.selected_tile_block.size.1 as usize {
+                state.selected_tile_block.palettes[i].reverse();
+                state.selected_tile_block.tiles[i].reverse();
+                state.selected_tile_block.flips[i].reverse();
+                state.selected_gfx[i].reverse();
+                if let Some(mask) = &mut state.selected_tile_block.mask {
+                    mask[i].reverse();
+                }
+                for j in 0..state.selected_tile_block.size.0 as usize {
+                    state.selected_tile_block.flips[i][j] =
+                        state.selected_tile_block.flips[i][j].flip_horizontally();
+                    state.selected_gfx[i][j] =
+                        Flip::Horizontal.apply_to_tile(state.selected_gfx[i][j]);
+                }
+            }
+        }
+        KeyAction::FlipVertical => {
+            state.selected_tile_block.palettes.reverse();
+            state.selected_tile_block.tiles.reverse();
+            state.selected_tile_block.flips.reverse();
+            state.selected_gfx.reverse();
+            if let Some(mask) = &mut state.selected_tile_block.mask {
+                mask.reverse();
+            }
+            for i in 0..state.selected_tile_block.size.1 as usize {
+                for j in 0..state.selected_tile_block.size.0 as usize {
+                    state.selected_tile_block.flips[i][j] =
+                        state.selected_tile_block.flips[i][j].flip_vertically();
+                    state.selected_gfx[i][j] =
+                        Flip::Vertical.apply_to_tile(state.selected_gfx[i][j]);
+                }
+            }
+        }
+        KeyAction::RotateBrush => {
+            state.selected_tile_block = rotated_block(state, &state.selected_tile_block)?;
+            update_selected_gfx(state);
+        }
+        KeyAction::UndoPreview => {
+            return Ok(Some(Task::done(Message::PreviewUndo(true))));
+        }
+        KeyAction::ZoomOut => {
+            let pixel_size = (state.global_config.pixel_size - 1.0).max(MIN_PIXEL_SIZE);
+            set_global_pixel_size(state, pixel_size);
+        }
+        KeyAction::ZoomIn => {
+            let pixel_size = (state.global_config.pixel_size + 1.0).min(MAX_PIXEL_SIZE);
+            set_global_pixel_size(state, pixel_size);
+        }
+        KeyAction::ZoomPreset(i) => {
+            if let Some(&pixel_size) = ZOOM_PRESETS.get(i) {
+                set_global_pixel_size(state, pixel_size);
+            }
+        }
+        KeyAction::Copy => {
+            return Ok(Some(Task::done(Message::CopyTiles)));
+        }
+        KeyAction::Paste => {
+            return Ok(Some(Task::done(Message::PasteTiles)));
+        }
+        KeyAction::RebuildProject => {
+            return Ok(Some(Task::done(Message::RebuildProjectDialogue)));
+        }
+    }
+    Ok(None)
+}
+
+pub fn scale_factor_task() -> Task<Message> {
+    window::get_latest().and_then(|id| window::get_scale_factor(id).map(Message::SetScaleFactor))
+}
+
+// Close the window, saving the window geometry and (optionally) the project.
+fn close_window(state: &mut EditorState, id: window::Id, save: bool) -> Result<Task<Message>> {
+    if let Some(geometry) = &mut state.global_config.window {
+        if let Some(monitor_size) = primary_monitor_size() {
+            geometry.monitor_width = monitor_size.width;
+            geometry.monitor_height = monitor_size.height;
+        }
+        state.global_config.modified = true;
+    }
+    persist::save_global_config(state)?;
+    if save {
+        persist::save_project(state)?;
+    }
+    Ok(window::close(id))
+}
+
+// Close the window or open another project, once the unsaved changes have been saved or discarded.
+fn continue_unsaved_changes_action(
+    state: &mut EditorState,
+    action: &UnsavedChangesAction,
+) -> Result<Task<Message>> {
+    match action {
+        &UnsavedChangesAction::CloseWindow(id) => close_window(state, id, false),
+        UnsavedChangesAction::OpenProject(path) => {
+            state.dialogue = None;
+            Ok(project_load::start(state, path.clone()))
+        }
+    }
+}
+
+// Messages handled while a project is loading, leaving the current project (if any) as it is.
+fn handled_while_loading(message: &Message) -> bool {
+    matches!(
+        message,
+        Message::ProjectOpened(_)
+            | Message::ProjectLoadProgress { .. }
+            | Message::ProjectLoaded { .. }
+            | Message::CancelProjectLoad
+            | Message::SaveAndContinue(_)
+            | Message::DiscardAndContinue(_)
+            | Message::WindowClose(_)
+    )
+}
+
+// Messages handled while a ROM is being imported, which would otherwise see (or save) the
+// partly imported project.
+fn handled_while_importing(message: &Message) -> bool {
+    matches!(
+        message,
+        Message::ImportROMRead(_)
+            | Message::ImportROMStep
+            | Message::CancelImportROM
+            | Message::WindowClose(_)
+    )
+}
+
+// A secret placed on the 16x16 tile grid, within the area.
+fn clamp_secret(area: &Area, secret: Secret) -> Secret {
+    let limit = (area.size.0 as TileCoord * 32, area.size.1 as TileCoord * 32);
+    let (x, y) = secret.position;
+    Secret {
+        position: snap_position(x.min(limit.0 - 1), y.min(limit.1 - 1)),
+        ..secret
+    }
+}
+
 pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<Task<Message>>> {
-    if state.global_config.project_dir.is_none() {
+    if state.project_load.is_some() {
+        if !handled_while_loading(message) {
+            return Ok(None);
+        }
+    } else if state.rom_import.is_some() {
+        if !handled_while_importing(message) {
+            return Ok(None);
+        }
+    } else if state.global_config.project_dir.is_none() {
         let Message::ProjectOpened(_) = &message else {
             return Ok(None);
         };
     }
     match message {
         Message::Nothing => {}
+        Message::Batch(messages) => {
+            let mut tasks = vec![];
+            for m in messages {
+                if let Some(t) = try_update(state, m)? {
+                    tasks.push(t);
+                }
+            }
+            return Ok(Some(Task::batch(tasks)));
+        }
         Message::Event(event) => match event {
+            &Event::Window(window::Event::Moved(position)) => {
+                // The window may have moved to a display with a different scale factor:
+                return Ok(Some(Task::batch([
+                    window_geometry_task(Some(position), None),
+                    scale_factor_task(),
+                ])));
+            }
+            &Event::Window(window::Event::Resized(size)) => {
+                return Ok(Some(window_geometry_task(None, Some(size))));
+            }
             Event::Keyboard(keyboard::Event::KeyPressed {
                 key: keyboard::Key::Named(key::Named::Tab),
                 modifiers,
@@ -149,11 +547,19 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
                     }
                 }
             }
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key: keyboard::Key::Named(key::Named::F12),
+                ..
+            }) => {
+                state.show_update_timings = !state.show_update_timings;
+                state.update_timings.clear();
+            }
             Event::Keyboard(keyboard::Event::KeyPressed {
                 key: keyboard::Key::Named(key::Named::Escape),
                 ..
             }) => {
                 state.tool = Tool::Select;
+                state.eyedropper = false;
                 state.dialogue = None;
                 state.color_idx = None;
                 state.tile_idx = None;
@@ -249,6 +655,10 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
                 key: keyboard::Key::Named(key::Named::ArrowDown),
                 ..
             }) => {
+                if let Some(Dialogue::AreaPicker(picker)) = &mut state.dialogue {
+                    picker.move_selection(1);
+                    return Ok(Some(scroll_area_picker(state)));
+                }
                 match state.focus {
                     Focus::None => {}
                     Focus::PickArea(position) => {
@@ -316,6 +726,10 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
                 key: keyboard::Key::Named(key::Named::ArrowUp),
                 ..
             }) => {
+                if let Some(Dialogue::AreaPicker(picker)) = &mut state.dialogue {
+                    picker.move_selection(-1);
+                    return Ok(Some(scroll_area_picker(state)));
+                }
                 match state.focus {
                     Focus::None => {}
                     Focus::PickArea(position) => {
@@ -384,116 +798,196 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
                 modifiers,
                 ..
             }) => {
-                if modifiers.control() {
-                    match c.as_str() {
-                        "r" => {
-                            return Ok(Some(Task::done(Message::RebuildProjectDialogue)));
-                        }
-                        _ => {}
-                    }
-                } else {
-                    match c.as_str() {
-                        "b" => {
-                            state.tool = Tool::Brush;
-                        }
-                        "s" => {
-                            state.tool = Tool::Select;
-                        }
-                        "g" => {
-                            state.show_grid = !state.show_grid;
-                        }
-                        "t" => {
-                            state.side_panel_view = SidePanelView::Tileset;
-                        }
-                        "a" => {
-                            state.side_panel_view = SidePanelView::Area;
-                        }
-                        "h" => {
-                            for i in 0..state.selected_tile_block.size.1 as usize {
-                                state.selected_tile_block.palettes[i].reverse();
-                                state.selected_tile_block.tiles[i].reverse();
-                                state.selected_tile_block.flips[i].reverse();
-                                state.selected_gfx[i].reverse();
-                                for j in 0..state.selected_tile_block.size.0 as usize {
-                                    state.selected_tile_block.flips[i][j] =
-                                        state.selected_tile_block.flips[i][j].flip_horizontally();
-                                    state.selected_gfx[i][j] =
-                                        Flip::Horizontal.apply_to_tile(state.selected_gfx[i][j]);
-                                }
-                            }
-                        }
-                        "v" => {
-                            state.selected_tile_block.palettes.reverse();
-                            state.selected_tile_block.tiles.reverse();
-                            state.selected_tile_block.flips.reverse();
-                            state.selected_gfx.reverse();
-                            for i in 0..state.selected_tile_block.size.1 as usize {
-                                for j in 0..state.selected_tile_block.size.0 as usize {
-                                    state.selected_tile_block.flips[i][j] =
-                                        state.selected_tile_block.flips[i][j].flip_vertically();
-                                    state.selected_gfx[i][j] =
-                                        Flip::Vertical.apply_to_tile(state.selected_gfx[i][j]);
-                                }
-                            }
-                        }
-                        "-" => {
-                            state.global_config.pixel_size =
-                                (state.global_config.pixel_size - 1.0).max(MIN_PIXEL_SIZE);
-                        }
-                        "=" => {
-                            state.global_config.pixel_size =
-                                (state.global_config.pixel_size + 1.0).min(MAX_PIXEL_SIZE);
-                        }
-                        _ => {}
-                    }
+                let keymap = active_keymap(&state.global_config);
+                if let Some(action) = key_action(&keymap, c.as_str(), modifiers.control()) {
+                    return apply_key_action(state, action);
                 }
             }
+            Event::Keyboard(keyboard::Event::KeyReleased {
+                key: keyboard::Key::Character(c),
+                ..
+            }) if is_bound(
+                &active_keymap(&state.global_config),
+                c.as_str(),
+                KeyAction::UndoPreview,
+            ) =>
+            {
+                return Ok(Some(Task::done(Message::PreviewUndo(false))));
+            }
+            Event::Mouse(mouse::Event::CursorMoved { position }) => {
+                state.cursor_position = *position;
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                // Releases over an area or group in the area list are captured by them, so this
+                // is a drag dropped anywhere else:
+                state.dragging_area = None;
+            }
             _ => {}
         },
         &Message::Focus(focus) => {
             state.focus = focus;
         }
+        // Handled in `update`, when there is anything to undo or redo:
+        Message::Undo | Message::Redo | Message::PreviewUndo(_) => {}
         Message::SaveProject => {
-            if *state.files_modified_notification.lock().unwrap() {
-                *state.files_modified_notification.lock().unwrap() = false;
-                state.dialogue = Some(Dialogue::ModifiedReload);
-            } else {
+            if state.undo_preview {
+                // Don't save the temporarily undone state.
+                return Ok(None);
+            }
+            let changed_files = std::mem::take(&mut *state.changed_files.lock().unwrap());
+            let found = !changed_files.is_empty();
+            if found {
+                state.external_changes =
+                    pending_changes(state, &state.external_changes, changed_files);
+            }
+            if state.external_changes.is_empty() {
                 persist::save_project(state)?;
+            } else if state.area_diff.is_none() && (found || state.dialogue.is_none()) {
+                // Saving waits until the changes are resolved, so as not to overwrite them
+                // (while comparing an area with the changed file, the dialogue stays hidden):
+                state.dialogue = Some(Dialogue::ModifiedReload);
+            }
+        }
+        &Message::SetReloadChoice(i, choice) => {
+            if let Some(change) = state.external_changes.get_mut(i) {
+                change.choice = choice;
+            }
+        }
+        &Message::ToggleReloadDiff(i) => {
+            if let Some(change) = state.external_changes.get_mut(i) {
+                change.show_diff = !change.show_diff;
+            }
+        }
+        Message::ApplyExternalChanges => {
+            let changes = std::mem::take(&mut state.external_changes);
+            if apply_changes(state, &changes)? {
+                // The undo history may refer to the replaced versions:
+                state.undo_stack.clear();
+                state.redo_stack.clear();
             }
+            state.dialogue = None;
+        }
+        Message::ReloadAreaFromDisk => {
+            let area_id = state.main_area_id.clone();
+            let area = persist::load_area(state, &area_id)?;
+            *state.main_area_mut() = area;
+            state.area_diff = None;
+            state
+                .external_changes
+                .retain(|c| c.file != ChangedFile::Area(area_id.clone()));
+            state.undo_stack.clear();
+            state.redo_stack.clear();
+        }
+        Message::CheckWatcher => {
+            persist::check_watcher(state)?;
         }
         Message::OpenProject => {
             return Ok(Some(Task::perform(open_project(), Message::ProjectOpened)));
         }
         Message::ModifiedReload => {
+            state.area_diff = None;
             persist::load_project(state)?;
-            state.dialogue = None;
+            state.dialogue = state.load_recovery_dialogue();
         }
-        Message::RebuildProjectDialogue => {
-            state.dialogue = Some(Dialogue::RebuildProject);
-            return Ok(Some(Task::done(Message::RebuildProject)));
+        Message::DiscardChangesDialogue => {
+            state.dialogue = Some(Dialogue::DiscardChanges);
         }
-        Message::RebuildProject => {
-            // Save all area PNGs (which could be out-of-date, e.g. if a palette were updated or a new theme created)
-            for theme in &state.theme_names.clone() {
-                for area_name in &state.area_names.clone() {
-                    let area_id = AreaId {
-                        theme: theme.clone(),
-                        area: area_name.clone(),
-                    };
-                    if state.areas.contains_key(&area_id) {
-                        save_area_png(state, &area_id)?;
-                    } else {
-                        state.load_area(&area_id)?;
-                        save_area_png(state, &area_id)?;
-                        state.areas.remove(&area_id);
-                    }
+        Message::DiscardChanges => {
+            state.area_diff = None;
+            persist::revert_project(state)?;
+            state.dialogue = state.load_recovery_dialogue();
+        }
+        Message::RestoreFile { path, option } => {
+            // Save any other changes first, since the project is reloaded afterward:
+            persist::save_project(state)?;
+            if let Err(e) = persist::restore_file(state, path, option) {
+                state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+                return Ok(None);
+            }
+            persist::load_project(state)?;
+            state.dialogue = state.load_recovery_dialogue();
+        }
+        Message::SkipFile(path) => {
+            for failure in &mut state.load_failures {
+                if &failure.path == path {
+                    failure.skipped = true;
                 }
             }
-            state.dialogue = None;
+            state.dialogue = state.load_recovery_dialogue();
+        }
+        Message::OpenFileLocation(path) => {
+            if let Err(e) = persist::open_file_location(path) {
+                warn!("{:#}", e);
+            }
+        }
+        Message::RebuildProjectDialogue => {
+            state.dialogue = Some(Dialogue::RebuildProject {
+                scope: RebuildScope::default(),
+            });
+        }
+        Message::SetRebuildScope(new_scope) => {
+            if let Some(Dialogue::RebuildProject { scope }) = &mut state.dialogue {
+                *scope = new_scope.clone();
+            }
+        }
+        Message::StartRebuildProject => {
+            if let Some(Dialogue::RebuildProject { scope }) = &state.dialogue {
+                let scope = scope.clone();
+                return Ok(Some(project_rebuild::start(state, &scope)?));
+            }
+        }
+        &Message::RebuildProjectProgress(done) => {
+            if let Some(rebuild) = &mut state.project_rebuild {
+                rebuild.done = done;
+            }
+        }
+        Message::RebuildProjectFinished(result) => {
+            state.project_rebuild = None;
+            state.dialogue = match result {
+                Ok(_) => None,
+                Err(e) => Some(Dialogue::Error(e.clone())),
+            };
+        }
+        Message::CancelRebuildProject => {
+            if let Some(rebuild) = state.project_rebuild.take() {
+                info!("Cancelled rebuilding the project");
+                rebuild.cancel();
+            }
         }
         &Message::WindowClose(id) => {
+            if let Some(load) = state.project_load.take() {
+                // The current project was saved when the load started, and the global config
+                // still names the project to open next time:
+                load.cancel();
+                return Ok(Some(window::close(id)));
+            }
+            // An unfinished import isn't kept:
+            rom_import::roll_back(state)?;
+            if state.undo_preview {
+                // Restore the previewed change before saving:
+                return Ok(Some(
+                    Task::done(Message::PreviewUndo(false)).chain(Task::done(message.clone())),
+                ));
+            }
+            if dirty::has_unsaved_changes(state) {
+                state.dialogue = Some(Dialogue::UnsavedChanges(UnsavedChangesAction::CloseWindow(
+                    id,
+                )));
+                return Ok(None);
+            }
+            return Ok(Some(close_window(state, id, true)?));
+        }
+        Message::SaveAndContinue(action) => {
             persist::save_project(state)?;
-            return Ok(Some(window::close(id)));
+            return Ok(Some(continue_unsaved_changes_action(state, action)?));
+        }
+        Message::DiscardAndContinue(action) => {
+            if let UnsavedChangesAction::OpenProject(_) = action {
+                // The project stays open if the other fails to load, so it's reverted now:
+                state.area_diff = None;
+                persist::revert_project(state)?;
+            }
+            return Ok(Some(continue_unsaved_changes_action(state, action)?));
         }
         Message::ProjectOpened(path) => {
             match path {
@@ -501,15 +995,16 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
                     info!("Opening project at {}", p.display());
                     // Ensure that the old project has been persisted before loading the new:
                     if state.global_config.project_dir.is_some() {
+                        if dirty::has_unsaved_changes(state) {
+                            state.dialogue = Some(Dialogue::UnsavedChanges(
+                                UnsavedChangesAction::OpenProject(p.clone()),
+                            ));
+                            return Ok(None);
+                        }
                         persist::save_project(state)?;
                     }
-
-                    // Update the global config to be set to the new project:
-                    state.global_config.project_dir = Some(p.clone());
-                    state.global_config.modified = true;
-                    persist::save_global_config(state)?;
-                    persist::load_project(state)?;
                     state.dialogue = None;
+                    return Ok(Some(project_load::start(state, p.clone())));
                 }
                 None => {
                     if state.global_config.project_dir.is_none() {
@@ -519,53 +1014,562 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
                 }
             }
         }
+        Message::ProjectLoadProgress {
+            dir,
+            palettes,
+            areas,
+        } => {
+            if let Some(load) = &mut state.project_load {
+                if &load.dir == dir {
+                    load.palettes = *palettes;
+                    load.areas = *areas;
+                }
+            }
+        }
+        Message::ProjectLoaded { dir, files } => {
+            // Ignore a load that was cancelled:
+            if state.project_load.as_ref().map(|load| &load.dir) != Some(dir) {
+                return Ok(None);
+            }
+            state.project_load = None;
+            match files {
+                Ok(files) => {
+                    // Update the global config to be set to the new project:
+                    state.global_config.project_dir = Some(dir.clone());
+                    state.global_config.modified = true;
+                    persist::save_global_config(state)?;
+                    state.area_diff = None;
+                    persist::open_project_files(state, (**files).clone())?;
+                    state.dialogue = state.load_recovery_dialogue();
+                }
+                Err(e) => {
+                    if state.global_config.project_dir.is_none() {
+                        // There is no project to go back to, so pick another:
+                        info!("Unable to load project: {}", e);
+                        return Ok(Some(Task::perform(open_project(), Message::ProjectOpened)));
+                    }
+                    state.dialogue = Some(Dialogue::Error(e.clone()));
+                }
+            }
+        }
+        Message::CancelProjectLoad => {
+            if let Some(load) = state.project_load.take() {
+                info!("Cancelled loading project at {}", load.dir.display());
+                load.cancel();
+            }
+            return Ok(Some(Task::perform(open_project(), Message::ProjectOpened)));
+        }
+        &Message::SetWindowGeometry {
+            position,
+            size,
+            maximized,
+        } => {
+            let geometry = state.global_config.window.get_or_insert(WindowGeometry {
+                x: 0.0,
+                y: 0.0,
+                width: DEFAULT_WINDOW_SIZE.width,
+                height: DEFAULT_WINDOW_SIZE.height,
+                maximized: false,
+                monitor_width: 0.0,
+                monitor_height: 0.0,
+            });
+            geometry.maximized = maximized;
+            // Keep the un-maximized geometry, to restore to when the window is un-maximized:
+            if !maximized {
+                if let Some(p) = position {
+                    geometry.x = p.x;
+                    geometry.y = p.y;
+                }
+                if let Some(s) = size {
+                    geometry.width = s.width;
+                    geometry.height = s.height;
+                }
+            }
+        }
         Message::SettingsDialogue => {
             state.dialogue = Some(Dialogue::Settings);
         }
+        Message::MemoryDialogue => {
+            let project_bytes = state
+                .global_config
+                .project_dir
+                .as_deref()
+                .map(memory_report::dir_bytes)
+                .unwrap_or(0);
+            state.dialogue = Some(Dialogue::Memory { project_bytes });
+        }
+        Message::ClearCaches => {
+            memory_report::clear_caches(state);
+        }
+        Message::CompactMemory => {
+            memory_report::compact_memory(state);
+        }
+        Message::UnloadUnusedAreas => {
+            let count = state.areas.len();
+            state.cleanup_areas()?;
+            info!("Unloaded {} areas", count - state.areas.len());
+        }
+        Message::ScriptDialogue => {
+            state.dialogue = Some(Dialogue::Script);
+        }
+        Message::EditScript(action) => {
+            state.script.perform(action.clone());
+        }
+        Message::RunScript => {
+            let result = run_script(state, &state.script.text());
+            info!("Script made {} changes", result.changes.len());
+            state.script_output = result.output;
+            if !result.changes.is_empty() {
+                return Ok(Some(Task::done(Message::Batch(result.changes))));
+            }
+        }
+        &Message::ExportStatistics(format) => {
+            let path = project_stats::export_stats(state, format)?;
+            info!("Exported project statistics to {}", path.display());
+        }
         Message::HelpDialogue => {
             state.dialogue = Some(Dialogue::Help);
         }
-        &Message::SetPixelSize(pixel_size) => {
-            state.global_config.pixel_size = pixel_size;
-            state.global_config.modified = true;
+        Message::ExportCheatSheet => {
+            return Ok(Some(Task::perform(
+                save_cheat_sheet_png("shortcuts.png".to_string()),
+                Message::ExportCheatSheetTo,
+            )));
         }
-        &Message::SetGridAlpha(grid_alpha) => {
-            state.global_config.grid_alpha = grid_alpha;
-            state.global_config.modified = true;
+        Message::ExportCheatSheetTo(path) => {
+            let Some(path) = path else {
+                return Ok(None);
+            };
+            let keymap = active_keymap(&state.global_config);
+            if let Err(e) = persist::export_cheat_sheet_png(path, &keymap) {
+                state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+            }
         }
-        Message::CloseDialogue => {
+        Message::StartTutorial => {
             state.dialogue = None;
+            set_tutorial_step(state, 0);
         }
-        Message::ImportDialogue => {
-            return Ok(Some(Task::perform(open_rom(), Message::ImportConfirm)));
+        &Message::SetTutorialStep(step) => {
+            set_tutorial_step(state, step);
         }
-        Message::ImportConfirm(path) => {
-            if path.is_some() {
-                state.rom_path = path.clone();
-                state.dialogue = Some(Dialogue::ImportROMConfirm);
-            } else {
-                state.dialogue = Some(Dialogue::Settings);
+        Message::CloseTutorial => {
+            state.tutorial_step = None;
+            if !state.global_config.tutorial_seen {
+                state.global_config.tutorial_seen = true;
+                state.global_config.modified = true;
             }
         }
-        Message::ImportROMProgress => {
-            state.dialogue = Some(Dialogue::ImportROMProgress);
-            return Ok(Some(Task::done(Message::ImportROM)));
-        }
-        Message::ImportROM => {
-            let path = state.rom_path.as_ref().context("internal error")?;
-            Importer::import(state, &path.clone())?;
-            state.dialogue = None;
+        Message::ToggleSidePanel => {
+            toggle_side_panel(state);
         }
-        Message::SelectPalette(name) => {
-            for i in 0..state.palettes.len() {
-                if name == &format!("{}: {}", state.palettes[i].id, state.palettes[i].name) {
-                    state.palette_idx = i;
-                    state.color_idx = None;
-                    state.tile_idx = None;
-                    break;
-                }
+        &Message::SetThreePaneLayout(enabled) => {
+            state.global_config.pane_layout = if enabled {
+                Some(pane_layout(&state.panes).unwrap_or_default())
+            } else {
+                None
+            };
+            state.global_config.modified = true;
+            state.side_panel_hidden = false;
+            if enabled && matches!(state.side_panel_view, SidePanelView::Area) {
+                // The side area has its own pane:
+                state.side_panel_view = SidePanelView::Tileset;
             }
         }
+        &Message::PaneResized(pane_grid::ResizeEvent { split, ratio }) => {
+            state.panes.resize(split, ratio);
+            state.global_config.pane_layout = pane_layout(&state.panes);
+            state.global_config.modified = true;
+        }
+        &Message::SetPixelSize(pixel_size) => {
+            set_global_pixel_size(state, pixel_size);
+        }
+        &Message::SetPerDisplayZoom(enabled) => {
+            state.global_config.per_display_zoom = enabled;
+            state.global_config.modified = true;
+            // Start from the current zoom level on the current display:
+            let pixel_size = state.global_config.pixel_size;
+            state
+                .global_config
+                .set_pixel_size(pixel_size, state.scale_factor);
+        }
+        &Message::SetRecordRenames(enabled) => {
+            state.project_metadata.record_renames = enabled;
+            state.project_metadata.modified = true;
+        }
+        Message::RenameHistoryDialogue => {
+            let renames = load_renames(state)?;
+            state.dialogue = Some(Dialogue::RenameHistory(renames));
+        }
+        &Message::SetManualSave(enabled) => {
+            state.global_config.manual_save = enabled;
+            state.global_config.modified = true;
+        }
+        &Message::SetRecordSession(enabled) => {
+            state.global_config.record_session = enabled;
+            state.global_config.modified = true;
+            if !enabled {
+                state.session_recorder = None;
+            }
+        }
+        Message::SaveBugReport => {
+            let file_name = format!(
+                "bug-report-{}.zip",
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)?
+                    .as_secs()
+            );
+            return Ok(Some(Task::perform(
+                save_bug_report_file(file_name),
+                Message::SaveBugReportTo,
+            )));
+        }
+        Message::SaveBugReportTo(path) => {
+            if let Some(path) = path {
+                if let Err(e) = save_bug_report(state, path) {
+                    state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+                }
+            }
+        }
+        &Message::SetScaleFactor(scale_factor) => {
+            if state.scale_factor == Some(scale_factor) {
+                return Ok(None);
+            }
+            state.scale_factor = Some(scale_factor);
+            if state.global_config.per_display_zoom {
+                match state.global_config.display_pixel_size(scale_factor) {
+                    Some(pixel_size) => {
+                        info!(
+                            "Switching to zoom {} for display with scale factor {}",
+                            pixel_size, scale_factor
+                        );
+                        state.global_config.pixel_size = pixel_size;
+                        state.global_config.modified = true;
+                    }
+                    None => {
+                        // First time on this display: keep the current zoom as its default.
+                        let pixel_size = state.global_config.pixel_size;
+                        state
+                            .global_config
+                            .set_pixel_size(pixel_size, Some(scale_factor));
+                    }
+                }
+            }
+        }
+        &Message::SetGridAlpha(grid_alpha) => {
+            state.global_config.grid_alpha = grid_alpha;
+            state.global_config.modified = true;
+        }
+        &Message::SetOverlayOpacity(opacity) => {
+            state.global_config.overlay_opacity = opacity;
+            state.global_config.modified = true;
+        }
+        Message::CloseDialogue => {
+            state.dialogue = None;
+        }
+        &Message::ContextMenu(menu) => {
+            state.dialogue = Some(Dialogue::ContextMenu(menu, state.cursor_position));
+        }
+        &Message::ImportDialogue(mode) => {
+            state.import_mode = mode;
+            return Ok(Some(Task::perform(open_rom(), Message::ImportConfirm)));
+        }
+        Message::ImportRomFolderDialogue => {
+            return Ok(Some(Task::perform(
+                open_rom_folder(),
+                Message::ImportConfirm,
+            )));
+        }
+        Message::ImportConfirm(path) => {
+            if path.is_some() {
+                state.rom_path = path.clone();
+                state.dialogue = Some(Dialogue::ImportROMConfirm);
+            } else {
+                state.dialogue = Some(Dialogue::Settings);
+            }
+        }
+        Message::OpenHeatmap => {
+            return Ok(Some(Task::perform(open_heatmap(), Message::HeatmapOpened)));
+        }
+        Message::HeatmapOpened(path) => {
+            if let Some(path) = path {
+                let heatmap = Heatmap::load(path)?;
+                info!("Loaded playtest heatmap from {}", path.display());
+                state.heatmap = Some(heatmap);
+            }
+        }
+        Message::ClearHeatmap => {
+            state.heatmap = None;
+        }
+        &Message::SetImportMode(mode) => {
+            state.import_mode = mode;
+        }
+        Message::SetImportMaps(maps) => {
+            state.import_maps = maps.clone();
+        }
+        Message::PreviewImport => {
+            let path = state.rom_path.clone().context("internal error")?;
+            state.dialogue = Some(Dialogue::ImportROMProgress);
+            return Ok(Some(rom_import::start(state, path, true)));
+        }
+        Message::ImportROMProgress => {
+            state.dialogue = Some(Dialogue::ImportROMProgress);
+            return Ok(Some(Task::done(Message::ImportROM)));
+        }
+        Message::ImportROM => {
+            let path = state.rom_path.clone().context("internal error")?;
+            return Ok(Some(rom_import::start(state, path, false)));
+        }
+        Message::ImportROMRead(data) => {
+            return rom_import::rom_read(state, data);
+        }
+        Message::ImportROMStep => {
+            return rom_import::step(state);
+        }
+        Message::CancelImportROM => {
+            rom_import::roll_back(state)?;
+            state.dialogue = None;
+        }
+        Message::SelectImportRules => {
+            return Ok(Some(Task::perform(
+                open_import_rules(),
+                Message::ImportRulesSelected,
+            )));
+        }
+        Message::ImportRulesSelected(path) => {
+            if let Some(path) = path {
+                if let Err(e) = ImportRules::read(path) {
+                    state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+                    return Ok(None);
+                }
+                set_rules_path(state, Some(path));
+                persist::save_project_metadata(state)?;
+            }
+            state.dialogue = Some(Dialogue::ImportROMConfirm);
+        }
+        Message::ClearImportRules => {
+            set_rules_path(state, None);
+            persist::save_project_metadata(state)?;
+        }
+        Message::ExportDialogue => {
+            return Ok(Some(Task::perform(
+                open_rom(),
+                Message::ExportBaseROMSelected,
+            )));
+        }
+        Message::ExportBaseROMSelected(path) => {
+            let Some(path) = path else {
+                state.dialogue = Some(Dialogue::Settings);
+                return Ok(None);
+            };
+            state.rom_path = Some(path.clone());
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            let file_name = format!("{}-{}.sfc", stem, state.main_area().theme);
+            return Ok(Some(Task::perform(
+                save_rom_file(file_name),
+                Message::ExportROMTo,
+            )));
+        }
+        Message::ExportROMTo(path) => {
+            let Some(path) = path else {
+                state.dialogue = Some(Dialogue::Settings);
+                return Ok(None);
+            };
+            state.export_path = Some(path.clone());
+            return Ok(Some(Task::done(Message::ExportROMProgress)));
+        }
+        Message::ExportROMProgress => {
+            state.dialogue = Some(Dialogue::ExportROMProgress);
+            return Ok(Some(Task::done(Message::ExportROM)));
+        }
+        Message::ExportROM => {
+            let base_rom_path = state.rom_path.clone().context("internal error")?;
+            let path = state.export_path.clone().context("internal error")?;
+            let theme = state.main_area().theme.clone();
+            state.dialogue = match Exporter::export(state, &base_rom_path, &path, &theme) {
+                Ok(report) => {
+                    dirty::mark_exported(state, &theme);
+                    Some(Dialogue::ExportReport(report))
+                }
+                Err(e) => {
+                    error!("Error exporting ROM: {:#}", e);
+                    Some(Dialogue::Error(format!("{:#}", e)))
+                }
+            };
+        }
+        Message::PaletteSlotsDialogue => {
+            let area_id = state.main_area_id.clone();
+            state.dialogue = Some(Dialogue::PaletteSlots {
+                area_palettes: area_palettes(state, &state.area_copy(&area_id)?),
+                assignment: load_assignment(state, &area_id.theme)?,
+                theme: area_id.theme,
+                area: area_id.area,
+                problems: vec![],
+            });
+        }
+        Message::SelectPaletteSlotsArea(name) => {
+            let Some(Dialogue::PaletteSlots { theme, .. }) = &state.dialogue else {
+                return Ok(None);
+            };
+            let area_id = AreaId {
+                area: name.clone(),
+                theme: theme.clone(),
+            };
+            let palettes = area_palettes(state, &state.area_copy(&area_id)?);
+            if let Some(Dialogue::PaletteSlots {
+                area,
+                area_palettes,
+                ..
+            }) = &mut state.dialogue
+            {
+                *area = name.clone();
+                *area_palettes = palettes;
+            }
+        }
+        Message::SolvePaletteSlots(theme) => {
+            let (assignment, new_problems) = solve(state, theme)?;
+            for p in &new_problems {
+                warn!("{}", p);
+            }
+            save_assignment(state, theme, &assignment)?;
+            refresh_palette_slots(state)?;
+            if let Some(Dialogue::PaletteSlots { problems, .. }) = &mut state.dialogue {
+                *problems = new_problems;
+            }
+        }
+        Message::SetPaletteAssignment { theme, assignment } => {
+            save_assignment(state, theme, assignment)?;
+            refresh_palette_slots(state)?;
+        }
+        Message::SetPaletteSlot {
+            theme,
+            area,
+            palette_id,
+            position,
+        } => {
+            // A palette already at the new position is replaced, leaving it without a position.
+            let mut assignment = load_assignment(state, theme)?;
+            let positions = assignment.entry(area.clone()).or_default();
+            positions.retain(|_, id| id != palette_id);
+            if let Some(p) = position {
+                positions.insert(*p, *palette_id);
+            }
+            if positions.is_empty() {
+                assignment.remove(area);
+            }
+            check_assignment(state, &assignment)?;
+            save_assignment(state, theme, &assignment)?;
+            refresh_palette_slots(state)?;
+        }
+        Message::BorrowGraphicsDialogue => {
+            return Ok(Some(Task::perform(
+                open_rom(),
+                Message::BorrowGraphicsROMOpened,
+            )));
+        }
+        Message::BorrowGraphicsROMOpened(path) => {
+            let Some(path) = path else {
+                state.dialogue = Some(Dialogue::Settings);
+                return Ok(None);
+            };
+            let pal = &state.palettes[state.palette_idx];
+            let mut borrow = BorrowGraphics {
+                path: path.clone(),
+                sheets: load_graphics_sheets(path)?,
+                selected: BTreeSet::new(),
+                palette_id: pal.id,
+                previews: vec![],
+            };
+            borrow.update_previews(&pal.colors);
+            state.dialogue = Some(Dialogue::BorrowGraphics(borrow));
+        }
+        &Message::ToggleBorrowSheet(i) => {
+            if let Some(Dialogue::BorrowGraphics(borrow)) = &mut state.dialogue {
+                if !borrow.selected.remove(&i) {
+                    borrow.selected.insert(i);
+                }
+            }
+        }
+        Message::SetBorrowPalette(name) => {
+            if let Some(Dialogue::BorrowGraphics(borrow)) = &mut state.dialogue {
+                for pal in &state.palettes {
+                    if name == &format!("{}: {}", pal.id, pal.name) {
+                        borrow.palette_id = pal.id;
+                        borrow.update_previews(&pal.colors);
+                        break;
+                    }
+                }
+            }
+        }
+        Message::ImportTiles { palette_id, tiles } => {
+            let idx = *state
+                .palettes_id_idx_map
+                .get(palette_id)
+                .context("palette not found")?;
+            if palette_tiles_locked(state, idx) {
+                return Ok(None);
+            }
+            let pal = &mut state.palettes[idx];
+            if pal.tiles.len() + tiles.len() > TileIdx::MAX as usize + 1 {
+                warn!("Too many tiles for palette {}.", pal.name);
+                return Ok(None);
+            }
+            pal.tiles.extend(tiles);
+            // Keep the tileset in complete rows:
+            let num_rows = pal.tiles.len().div_ceil(16);
+            pal.tiles.resize(num_rows * 16, Tile::default());
+            pal.modified = true;
+            state.palette_idx = idx;
+            state.tile_idx = None;
+            state.color_idx = None;
+            state.dialogue = None;
+        }
+        Message::SelectPalette(name) => {
+            for i in 0..state.palettes.len() {
+                if name == &format!("{}: {}", state.palettes[i].id, state.palettes[i].name) {
+                    state.palette_idx = i;
+                    state.color_idx = None;
+                    state.tile_idx = None;
+                    break;
+                }
+            }
+        }
+        Message::SetPaletteFilter(filter) => {
+            state.palette_filter = filter.clone();
+        }
+        Message::SetPaletteGroupFilter(group) => {
+            state.palette_group_filter = (group != ALL_PALETTE_GROUPS).then(|| group.clone());
+        }
+        Message::PaletteGroupsDialogue => {
+            state.dialogue = Some(Dialogue::PaletteGroups {
+                group: String::new(),
+                renames: BTreeMap::new(),
+            });
+        }
+        Message::SetNewPaletteGroupName(name) => {
+            if let Some(Dialogue::PaletteGroups { group, .. }) = &mut state.dialogue {
+                *group = name.clone();
+            }
+        }
+        Message::EditPaletteGroupName { group, name } => {
+            if let Some(Dialogue::PaletteGroups { renames, .. }) = &mut state.dialogue {
+                renames.insert(group.clone(), name.clone());
+            }
+        }
+        Message::SetPaletteGroups(changes) => {
+            set_palette_groups(state, changes);
+            if let Some(Dialogue::PaletteGroups { group, renames }) = &mut state.dialogue {
+                group.clear();
+                renames.clear();
+            }
+        }
+        Message::MovePaletteGroup { group, up } => {
+            let order = moved_group_order(state, group, *up);
+            return Ok(Some(Task::done(Message::SetPaletteGroupOrder(order))));
+        }
+        Message::SetPaletteGroupOrder(order) => {
+            state.project_metadata.palette_group_order = order.clone();
+            state.project_metadata.modified = true;
+        }
         Message::AddPaletteDialogue => {
             let id = state.palettes.iter().map(|x| x.id).max().unwrap() + 1;
             state.dialogue = Some(Dialogue::AddPalette {
@@ -612,9 +1616,18 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
             update_palette_order(state);
             state.dialogue = None;
         }
+        Message::DuplicatePaletteDialogue => {
+            // Adding a palette copies the currently selected one.
+            let id = state.palettes.iter().map(|x| x.id).max().unwrap() + 1;
+            state.dialogue = Some(Dialogue::AddPalette {
+                name: format!("{} copy", state.palettes[state.palette_idx].name),
+                id,
+            });
+            return Ok(Some(iced::widget::text_input::focus("AddPalette")));
+        }
         Message::RenamePaletteDialogue => {
             state.dialogue = Some(Dialogue::RenamePalette {
-                name: "".to_string(),
+                name: state.palettes[state.palette_idx].name.clone(),
             });
             return Ok(Some(iced::widget::text_input::focus("RenamePalette")));
         }
@@ -645,6 +1658,7 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
             delete_palette(state, &old_name)?;
             update_palette_order(state);
             state.dialogue = None;
+            record_rename(state, RenameKind::Palette, &old_name, &name)?;
         }
         Message::DeletePaletteDialogue => {
             state.dialogue = Some(Dialogue::DeletePalette);
@@ -677,9 +1691,38 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
             state.palette_idx = state.palettes.len() - 1;
             update_palette_order(state);
         }
+        Message::PaletteHistoryDialogue => {
+            let palette_id = state.palettes[state.palette_idx].id;
+            let versions = load_palette_history(state, palette_id)?;
+            state.dialogue = Some(Dialogue::PaletteHistory(versions));
+        }
+        Message::ReplacePalette(palette) => {
+            let idx = *state
+                .palettes_id_idx_map
+                .get(&palette.id)
+                .context("palette not found")?;
+            let pal = &mut state.palettes[idx];
+            pal.colors = palette.colors;
+            pal.tiles = palette.tiles.clone();
+            pal.modified = true;
+            if idx == state.palette_idx {
+                if let Some(tile_idx) = state.tile_idx {
+                    if tile_idx as usize >= pal.tiles.len() {
+                        state.tile_idx = None;
+                    }
+                }
+                if let Some(color_idx) = state.color_idx {
+                    state.selected_color = pal.colors[color_idx as usize];
+                }
+            }
+            state.dialogue = None;
+        }
         Message::HideModal => {
             state.dialogue = None;
         }
+        Message::DismissSlowUpdateWarning => {
+            state.slow_update = None;
+        }
         &Message::SelectColor(pal_idx, color_idx) => {
             if pal_idx != state.palette_idx {
                 state.tile_idx = None;
@@ -700,50 +1743,191 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
                 .palettes_id_idx_map
                 .get(&palette_id)
                 .context("palette not found")?;
+            let pal = &state.palettes[pal_idx];
+            let num_colors = pal.category.num_colors();
+            if color_idx as usize > num_colors {
+                state.dialogue = Some(Dialogue::Error(format!(
+                    "{} is a {} palette, which only has colors 1-{} in the game",
+                    pal.name, pal.category, num_colors
+                )));
+                return Ok(None);
+            }
             state.palettes[pal_idx].colors[color_idx as usize] = color;
             state.palettes[pal_idx].modified = true;
         }
-        &Message::ChangeRed(c) => {
-            if let Some(color_idx) = state.color_idx {
-                let pal_idx = state.palette_idx;
-                let palette_id = state.palettes[pal_idx].id;
-                state.selected_color[0] = c;
-                return Ok(Some(Task::done(Message::BrushColor {
-                    palette_id,
-                    color_idx: color_idx,
-                    color: state.selected_color,
-                })));
+        Message::SetSnesColorText(text) => {
+            if let Some(color) = parse_snes_color(text) {
+                state.selected_color = color;
             }
+            state.snes_color_text = Some((state.selected_color, text.clone()));
+            return Ok(set_selected_color(state));
         }
-        &Message::ChangeGreen(c) => {
-            if let Some(color_idx) = state.color_idx {
-                let pal_idx = state.palette_idx;
-                let palette_id = state.palettes[pal_idx].id;
-                state.selected_color[1] = c;
-                return Ok(Some(Task::done(Message::BrushColor {
-                    palette_id,
-                    color_idx: color_idx,
-                    color: state.selected_color,
-                })));
+        Message::CopySnesColor => {
+            return Ok(Some(iced::clipboard::write(format_snes_color(
+                state.selected_color,
+            ))));
+        }
+        Message::PasteSnesColor => {
+            return Ok(Some(iced::clipboard::read().map(Message::PastedSnesColor)));
+        }
+        Message::PastedSnesColor(text) => {
+            let text = text.as_deref().unwrap_or_default();
+            let Some(color) = parse_snes_color(text) else {
+                state.dialogue = Some(Dialogue::Error(format!(
+                    "The clipboard doesn't hold a SNES color word (such as $7FFF): {:?}",
+                    text
+                )));
+                return Ok(None);
+            };
+            state.selected_color = color;
+            return Ok(set_selected_color(state));
+        }
+        &Message::PickColorHsv {
+            hue,
+            saturation,
+            value,
+        } => {
+            if state.color_idx.is_some() {
+                let color = hsv_to_rgb(hue, saturation, value);
+                state.selected_color = color;
+                state.picked_hsv = Some((color, (hue, saturation, value)));
+                return Ok(set_selected_color(state));
             }
         }
-        &Message::ChangeBlue(c) => {
-            if let Some(color_idx) = state.color_idx {
-                let pal_idx = state.palette_idx;
-                let palette_id = state.palettes[pal_idx].id;
-                state.selected_color[2] = c;
-                return Ok(Some(Task::done(Message::BrushColor {
-                    palette_id,
-                    color_idx: color_idx,
-                    color: state.selected_color,
-                })));
+        Message::SetRgbHexText(text) => {
+            if let Some(color) = parse_rgb_hex(text) {
+                state.selected_color = color;
+            }
+            state.rgb_hex_text = Some((state.selected_color, text.clone()));
+            return Ok(set_selected_color(state));
+        }
+        Message::ToggleEyedropper => {
+            state.eyedropper = !state.eyedropper;
+        }
+        &Message::PickAreaColor { position, pixel } => {
+            state.eyedropper = false;
+            let area = state.area(position);
+            let (x, y) = (pixel.x / 8, pixel.y / 8);
+            let palette_id = area.get_palette(x, y)?;
+            let tile_idx = area.get_tile(x, y)?;
+            let flip = area.get_flip(x, y)?;
+            let pal_idx = *state
+                .palettes_id_idx_map
+                .get(&palette_id)
+                .context("undefined palette")?;
+            let pal = &state.palettes[pal_idx];
+            let tile =
+                flip.apply_to_tile(*pal.tiles.get(tile_idx as usize).context("tile not found")?);
+            let color_idx = tile.pixels[(pixel.y % 8) as usize][(pixel.x % 8) as usize];
+            // Color 0 is transparent, showing the area's background color:
+            state.selected_color = if color_idx == 0 {
+                area.bg_color
+            } else {
+                pal.colors[color_idx as usize]
+            };
+            return Ok(set_selected_color(state));
+        }
+        &Message::SwapColors {
+            palette_id,
+            color_idx_1: a,
+            color_idx_2: b,
+        } => {
+            if a == b || a == 0 || b == 0 || a >= 16 || b >= 16 {
+                warn!("Invalid color indices to swap: {}, {}", a, b);
+                return Ok(None);
+            }
+            let pal_idx = *state
+                .palettes_id_idx_map
+                .get(&palette_id)
+                .context("palette not found")?;
+            let pal = &mut state.palettes[pal_idx];
+            pal.colors.swap(a as usize, b as usize);
+            // Rewrite the pixels so the graphics look the same as before:
+            for tile in &mut pal.tiles {
+                for row in &mut tile.pixels {
+                    for p in row.iter_mut() {
+                        if *p == a {
+                            *p = b;
+                        } else if *p == b {
+                            *p = a;
+                        }
+                    }
+                }
+            }
+            pal.modified = true;
+            if pal_idx == state.palette_idx {
+                // Keep the same color selected, at its new index:
+                if state.color_idx == Some(a) {
+                    state.color_idx = Some(b);
+                } else if state.color_idx == Some(b) {
+                    state.color_idx = Some(a);
+                }
+            }
+        }
+        Message::AdjustPaletteDialogue => {
+            let palette_id = state.palettes[state.palette_idx].id;
+            state.dialogue = Some(Dialogue::AdjustPalette(PaletteAdjust {
+                blend_from: Some(palette_id),
+                ..PaletteAdjust::default()
+            }));
+        }
+        &Message::SetPaletteAdjust(change) => {
+            if let Some(Dialogue::AdjustPalette(adjust)) = &mut state.dialogue {
+                adjust.apply(change);
             }
         }
+        &Message::AdjustPaletteHSV {
+            palette_id,
+            hue,
+            saturation,
+            brightness,
+        } => {
+            let colors = palette_colors(state, palette_id)?;
+            let colors = shift_hsv(&colors, hue, saturation, brightness);
+            set_palette_colors(state, palette_id, colors)?;
+        }
+        &Message::CopyPaletteColors {
+            palette_id,
+            source_id,
+        } => {
+            let colors = palette_colors(state, source_id)?;
+            set_palette_colors(state, palette_id, colors)?;
+        }
+        &Message::InterpolatePalettes {
+            palette_id,
+            from,
+            to,
+            amount,
+        } => {
+            let colors = interpolate(
+                &palette_colors(state, from)?,
+                &palette_colors(state, to)?,
+                amount,
+            );
+            set_palette_colors(state, palette_id, colors)?;
+        }
+        &Message::SetPaletteColors { palette_id, colors } => {
+            set_palette_colors(state, palette_id, colors)?;
+        }
+        &Message::SetPaletteCategory {
+            palette_id,
+            category,
+        } => {
+            let idx = *state
+                .palettes_id_idx_map
+                .get(&palette_id)
+                .context("palette not found")?;
+            state.palettes[idx].category = category;
+            state.palettes[idx].modified = true;
+        }
         Message::AddTileRow(palette_id) => {
             let idx = *state
                 .palettes_id_idx_map
                 .get(palette_id)
                 .context("palette not found")?;
+            if palette_tiles_locked(state, idx) {
+                return Ok(None);
+            }
             state.palettes[idx].tiles.extend(vec![Tile::default(); 16]);
             state.palettes[idx].modified = true;
         }
@@ -752,6 +1936,9 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
                 .palettes_id_idx_map
                 .get(palette_id)
                 .context("palette not found")?;
+            if palette_tiles_locked(state, idx) {
+                return Ok(None);
+            }
             if state.palettes[idx].tiles.len() <= 16 {
                 warn!("Not allowed to delete the last row of tiles.");
                 return Ok(None);
@@ -823,29 +2010,1402 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
             state.palettes[pal_idx].tiles[tile_idx as usize].v_flippable = v_flippable;
             state.palettes[pal_idx].modified = true;
         }
-        &Message::TilesetBrush {
+        &Message::SetSelectedTilesProperty(change) => {
+            if let Some((palette_id, selected)) = state.selected_tileset_tiles() {
+                let properties = change.apply_to(&selected)?;
+                return Ok(Some(Task::done(Message::SetTileProperties {
+                    palette_id,
+                    properties,
+                })));
+            }
+        }
+        Message::SetTileProperties {
             palette_id,
-            coords: Point { x: x0, y: y0 },
-            selected_gfx: ref s,
+            properties,
         } => {
             let pal_idx = *state
                 .palettes_id_idx_map
-                .get(&palette_id)
+                .get(palette_id)
                 .context("undefined palette")?;
-            for y in 0..s.len() {
-                for x in 0..s[0].len() {
-                    let y1 = y + y0 as usize;
-                    let x1 = x + x0 as usize;
-                    let i = y1 * 16 + x1;
-                    if x1 < 16 && i < state.palettes[pal_idx].tiles.len() {
-                        state.palettes[pal_idx].tiles[i] = s[y as usize][x as usize];
-                    }
-                }
+            let pal = &mut state.palettes[pal_idx];
+            if properties
+                .iter()
+                .any(|&(tile_idx, _)| tile_idx as usize >= pal.tiles.len())
+            {
+                bail!("tile not found");
             }
-            state.palettes[pal_idx].modified = true;
+            for &(tile_idx, p) in properties {
+                pal.tiles[tile_idx as usize].set_properties(p);
+            }
+            pal.modified = true;
         }
-        &Message::SelectPixel(x, y) => {
-            state.pixel_coords = Some((x, y));
+        Message::ToggleMacroRecording => match state.macro_recording.take() {
+            None => {
+                info!("Recording macro");
+                state.macro_recording = Some(vec![]);
+            }
+            Some(recorded) => {
+                if recorded.is_empty() {
+                    info!("Macro recording stopped; nothing was recorded");
+                    return Ok(None);
+                }
+                let macros = &mut state.project_metadata.macros;
+                let name = format!("Macro {}", macros.len() + 1);
+                info!("Recorded {} with {} steps", name, recorded.len());
+                macros.push(EditMacro::from_messages(name, &recorded));
+                state.project_metadata.modified = true;
+                return Ok(Some(Task::done(Message::MacrosDialogue)));
+            }
+        },
+        Message::MacrosDialogue => {
+            let (x, y) = match state.selection_source {
+                SelectionSource::Area(AreaPosition::Main) => state.start_coords.unwrap_or((0, 0)),
+                _ => (0, 0),
+            };
+            state.dialogue = Some(Dialogue::Macros { x, y });
+        }
+        &Message::SetMacroReplayCoords(new_x, new_y) => {
+            if let Some(Dialogue::Macros { x, y }) = &mut state.dialogue {
+                *x = new_x;
+                *y = new_y;
+            }
+        }
+        &Message::ReplayMacro {
+            macro_idx,
+            ref area_id,
+            coords,
+        } => {
+            let edit_macro = state
+                .project_metadata
+                .macros
+                .get(macro_idx)
+                .context("macro not found")?;
+            let messages = edit_macro.messages(AreaPosition::Main, area_id, coords);
+            state.dialogue = None;
+            return Ok(Some(Task::done(Message::Batch(messages))));
+        }
+        &Message::DeleteMacro(macro_idx) => {
+            if macro_idx < state.project_metadata.macros.len() {
+                state.project_metadata.macros.remove(macro_idx);
+                state.project_metadata.modified = true;
+            }
+        }
+        Message::SetMacroName(macro_idx, name) => {
+            let edit_macro = state
+                .project_metadata
+                .macros
+                .get_mut(*macro_idx)
+                .context("macro not found")?;
+            edit_macro.name = name.clone();
+            state.project_metadata.modified = true;
+        }
+        Message::SetMacros(macros) => {
+            state.project_metadata.macros = macros.clone();
+            state.project_metadata.modified = true;
+        }
+        Message::ExportPaletteDialogue => {
+            state.dialogue = Some(Dialogue::ExportPalette {
+                palette_id: state.palettes[state.palette_idx].id,
+                labeled: true,
+                tile_table: None,
+            });
+        }
+        &Message::SetExportPaletteLabeled(new_labeled) => {
+            if let Some(Dialogue::ExportPalette { labeled, .. }) = &mut state.dialogue {
+                *labeled = new_labeled;
+            }
+        }
+        &Message::SetExportPaletteTileTable(format) => {
+            if let Some(Dialogue::ExportPalette { tile_table, .. }) = &mut state.dialogue {
+                *tile_table = format;
+            }
+        }
+        Message::ExportPalette => {
+            let Some(Dialogue::ExportPalette {
+                palette_id,
+                tile_table,
+                ..
+            }) = &state.dialogue
+            else {
+                return Ok(None);
+            };
+            let &idx = state
+                .palettes_id_idx_map
+                .get(palette_id)
+                .context("palette not found")?;
+            let name = &state.palettes[idx].name;
+            let task = match tile_table {
+                Some(format) => Task::perform(
+                    save_tile_table(format!("{}-tiles.{}", name, format.extension()), *format),
+                    Message::ExportPaletteTo,
+                ),
+                None => Task::perform(
+                    save_palette_png(format!("{}-sheet.png", name)),
+                    Message::ExportPaletteTo,
+                ),
+            };
+            return Ok(Some(task));
+        }
+        Message::ExportPaletteTo(path) => {
+            let Some(path) = path else {
+                return Ok(None);
+            };
+            let Some(Dialogue::ExportPalette {
+                palette_id,
+                labeled,
+                tile_table,
+            }) = state.dialogue
+            else {
+                return Ok(None);
+            };
+            let &idx = state
+                .palettes_id_idx_map
+                .get(&palette_id)
+                .context("palette not found")?;
+            let palette = &state.palettes[idx];
+            let result = match tile_table {
+                Some(format) => persist::export_tile_table(path, palette, format),
+                None => persist::export_palette_png(path, palette, labeled),
+            };
+            if let Err(e) = result {
+                state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+                return Ok(None);
+            }
+            state.dialogue = None;
+        }
+        Message::TileGraphicsDialogue => {
+            state.dialogue = Some(Dialogue::TileGraphics {
+                palette_id: state.palettes[state.palette_idx].id,
+                format: GraphicsFormat::Png,
+            });
+        }
+        &Message::SetTileGraphicsFormat(new_format) => {
+            if let Some(Dialogue::TileGraphics { format, .. }) = &mut state.dialogue {
+                *format = new_format;
+            }
+        }
+        Message::ExportTileGraphics => {
+            let Some(Dialogue::TileGraphics { palette_id, format }) = state.dialogue else {
+                return Ok(None);
+            };
+            let &idx = state
+                .palettes_id_idx_map
+                .get(&palette_id)
+                .context("palette not found")?;
+            let file_name = graphics_file_name(&state.palettes[idx], format);
+            return Ok(Some(Task::perform(
+                save_tile_graphics(file_name, format),
+                Message::ExportTileGraphicsTo,
+            )));
+        }
+        Message::ExportTileGraphicsTo(path) => {
+            let Some(path) = path else {
+                return Ok(None);
+            };
+            let Some(Dialogue::TileGraphics { palette_id, format }) = state.dialogue else {
+                return Ok(None);
+            };
+            let &idx = state
+                .palettes_id_idx_map
+                .get(&palette_id)
+                .context("palette not found")?;
+            if let Err(e) = export_graphics(path, &state.palettes[idx], format) {
+                state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+                return Ok(None);
+            }
+            info!("Exported tile graphics to {}", path.display());
+            state.dialogue = None;
+        }
+        Message::ImportTileGraphics => {
+            let Some(Dialogue::TileGraphics { format, .. }) = state.dialogue else {
+                return Ok(None);
+            };
+            return Ok(Some(Task::perform(
+                open_tile_graphics(format),
+                Message::ImportTileGraphicsFrom,
+            )));
+        }
+        Message::ImportTileGraphicsFrom(path) => {
+            let Some(path) = path else {
+                return Ok(None);
+            };
+            let Some(Dialogue::TileGraphics { palette_id, format }) = state.dialogue else {
+                return Ok(None);
+            };
+            let &idx = state
+                .palettes_id_idx_map
+                .get(&palette_id)
+                .context("palette not found")?;
+            if palette_tiles_locked(state, idx) {
+                return Ok(None);
+            }
+            let palette = &state.palettes[idx];
+            let result = read_graphics(path, &palette.colors, format)
+                .and_then(|graphics| with_imported_graphics(palette, &graphics));
+            match result {
+                Ok(palette) => return Ok(Some(Task::done(Message::ReplacePalette(palette)))),
+                Err(e) => {
+                    state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+                }
+            }
+        }
+        Message::LibraryDialogue => {
+            if state.global_config.library_dir.is_none() {
+                return Ok(Some(Task::done(Message::PickLibraryDir)));
+            }
+            if let Err(e) = show_library(state) {
+                state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+            }
+        }
+        Message::PickLibraryDir => {
+            return Ok(Some(Task::perform(
+                open_library_dir(),
+                Message::LibraryDirPicked,
+            )));
+        }
+        Message::LibraryDirPicked(path) => {
+            let Some(path) = path else {
+                return Ok(None);
+            };
+            info!("Using library folder {}", path.display());
+            state.global_config.library_dir = Some(path.clone());
+            state.global_config.modified = true;
+            if let Err(e) = show_library(state) {
+                state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+            }
+        }
+        Message::SetLibraryFilter(new_filter) => {
+            if let Some(Dialogue::Library { filter, .. }) = &mut state.dialogue {
+                *filter = new_filter.clone();
+            }
+        }
+        Message::SetLibraryPublishName(new_name) => {
+            if let Some(Dialogue::Library { name, .. }) = &mut state.dialogue {
+                *name = new_name.clone();
+            }
+        }
+        Message::SetLibraryPublishTags(new_tags) => {
+            if let Some(Dialogue::Library { tags, .. }) = &mut state.dialogue {
+                *tags = new_tags.clone();
+            }
+        }
+        &Message::PublishToLibrary(kind) => {
+            let Some(Dialogue::Library { name, tags, .. }) = &state.dialogue else {
+                return Ok(None);
+            };
+            let (name, tags) = (name.clone(), parse_tags(tags));
+            let result = library::new_item(state, kind, &name, tags)
+                .and_then(|item| library::publish(state, &item))
+                .and_then(|_| show_library(state));
+            if let Err(e) = result {
+                state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+            }
+        }
+        Message::ImportFromLibrary(name) => {
+            let Some(Dialogue::Library { items, .. }) = &state.dialogue else {
+                return Ok(None);
+            };
+            let item = items
+                .iter()
+                .find(|item| &item.name == name)
+                .context("library item not found")?;
+            let messages = library::import_messages(state, item)?;
+            info!("Importing {} {} from library", item.kind(), name);
+            state.dialogue = None;
+            return Ok(Some(Task::done(Message::Batch(messages))));
+        }
+        Message::DeleteFromLibrary(name) => {
+            let result = library::delete_item(state, name).and_then(|_| show_library(state));
+            if let Err(e) = result {
+                state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+            }
+        }
+        Message::ToggleCollisionOverlay => {
+            state.show_collision = !state.show_collision;
+        }
+        Message::ToggleEntrances => {
+            state.show_entrances = !state.show_entrances;
+        }
+        &Message::MoveEntrance {
+            position,
+            ref area_id,
+            idx,
+            coords,
+        } => {
+            state.switch_area(position, area_id)?;
+            let area = state.area_mut(position);
+            let limit = (area.size.0 as TileCoord * 32, area.size.1 as TileCoord * 32);
+            let entrance = area.entrances.get_mut(idx).context("entrance not found")?;
+            // Keep the whole marker within the area:
+            entrance.position = (
+                coords.x.min(limit.0 - MARKER_SIZE),
+                coords.y.min(limit.1 - MARKER_SIZE),
+            );
+            area.modified = true;
+        }
+        Message::ToggleSecrets => {
+            state.show_secrets = !state.show_secrets;
+        }
+        Message::ToggleSpritePreview => {
+            state.show_sprite_preview = !state.show_sprite_preview;
+        }
+        &Message::SetEditLayer(layer) => {
+            state.edit_layer = layer;
+        }
+        &Message::ShowSecrets(show) => {
+            state.side_panel_view = if show {
+                SidePanelView::Secrets
+            } else {
+                SidePanelView::Tileset
+            };
+            state.side_panel_hidden = false;
+            if show {
+                state.show_secrets = true;
+            }
+        }
+        &Message::InsertSecret {
+            position,
+            ref area_id,
+            idx,
+            secret,
+        } => {
+            state.switch_area(position, area_id)?;
+            let area = state.area_mut(position);
+            if idx > area.secrets.len() {
+                bail!("secret index {} out of range", idx);
+            }
+            area.secrets.insert(idx, clamp_secret(area, secret));
+            area.modified = true;
+        }
+        &Message::RemoveSecret {
+            position,
+            ref area_id,
+            idx,
+        } => {
+            state.switch_area(position, area_id)?;
+            let area = state.area_mut(position);
+            if idx >= area.secrets.len() {
+                bail!("secret not found");
+            }
+            area.secrets.remove(idx);
+            area.modified = true;
+        }
+        &Message::SetSecret {
+            position,
+            ref area_id,
+            idx,
+            secret,
+        } => {
+            state.switch_area(position, area_id)?;
+            let area = state.area_mut(position);
+            let secret = clamp_secret(area, secret);
+            *area.secrets.get_mut(idx).context("secret not found")? = secret;
+            area.modified = true;
+        }
+        Message::ToggleTravel => {
+            state.show_travel = !state.show_travel;
+        }
+        &Message::TravelPointDialogue { position, idx } => {
+            state.dialogue = Some(Dialogue::TravelPoint { position, idx });
+        }
+        &Message::SetTravelPoint {
+            position,
+            ref area_id,
+            idx,
+            point,
+        } => {
+            state.switch_area(position, area_id)?;
+            let area = state.area_mut(position);
+            let limit = (area.size.0 as TileCoord * 32, area.size.1 as TileCoord * 32);
+            let point = TravelPoint {
+                position: (
+                    point.position.0.min(limit.0 - MARKER_SIZE),
+                    point.position.1.min(limit.1 - MARKER_SIZE),
+                ),
+                ..point
+            };
+            *area.travel.get_mut(idx).context("travel point not found")? = point;
+            area.modified = true;
+        }
+        &Message::EditTravelPosition(x, y) => {
+            let Some(Dialogue::TravelPoint { position, idx }) = state.dialogue else {
+                return Ok(None);
+            };
+            let area = state.area(position);
+            let point = *area.travel.get(idx).context("travel point not found")?;
+            return Ok(Some(Task::done(Message::SetTravelPoint {
+                position,
+                area_id: state.area_id(position).clone(),
+                idx,
+                point: TravelPoint {
+                    position: (x, y),
+                    ..point
+                },
+            })));
+        }
+        Message::ToggleAnnotations => {
+            state.show_annotations = !state.show_annotations;
+        }
+        &Message::ShowAnnotations(show) => {
+            state.side_panel_view = if show {
+                SidePanelView::Annotations
+            } else {
+                SidePanelView::Tileset
+            };
+            state.side_panel_hidden = false;
+            if show {
+                state.show_annotations = true;
+            }
+        }
+        &Message::SetScreenAnnotation {
+            position,
+            ref area_id,
+            screen,
+            value,
+        } => {
+            state.switch_area(position, area_id)?;
+            let area = state.area_mut(position);
+            if screen.0 >= area.size.0 || screen.1 >= area.size.1 {
+                bail!("screen {:?} out of range", screen);
+            }
+            set_annotation(area, screen, value);
+            area.modified = true;
+        }
+        Message::ExportAnnotations => {
+            return Ok(Some(Task::perform(
+                save_annotations_json("annotations.json".to_string()),
+                Message::ExportAnnotationsTo,
+            )));
+        }
+        Message::ExportAnnotationsTo(path) => {
+            let Some(path) = path else {
+                return Ok(None);
+            };
+            if let Err(e) = export_annotations(state, path) {
+                state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+            }
+        }
+        &Message::SetCameraLocked {
+            position,
+            ref area_id,
+            screen,
+            locked,
+        } => {
+            state.switch_area(position, area_id)?;
+            let area = state.area_mut(position);
+            let screen = area
+                .screens
+                .iter_mut()
+                .find(|s| s.position == screen)
+                .context("screen not found")?;
+            screen.camera_locked = locked;
+            area.modified = true;
+        }
+        Message::ExportCameraLocks => {
+            let file_name = format!("camera-locks-{}.asm", state.main_area_id.theme);
+            return Ok(Some(Task::perform(
+                save_camera_locks_asm(file_name),
+                Message::ExportCameraLocksTo,
+            )));
+        }
+        Message::ExportCameraLocksTo(path) => {
+            let Some(path) = path else {
+                return Ok(None);
+            };
+            let theme = state.main_area_id.theme.clone();
+            if let Err(e) = export_camera_locks(state, &theme, path) {
+                state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+            }
+        }
+        &Message::ShowExternalTools(show) => {
+            state.side_panel_view = if show {
+                SidePanelView::ExternalTools
+            } else {
+                SidePanelView::Tileset
+            };
+            state.side_panel_hidden = false;
+        }
+        Message::AddExternalTool => {
+            let tools = &mut state.project_metadata.external_tools;
+            tools.push(ExternalTool {
+                name: format!("Tool {}", tools.len() + 1),
+                ..Default::default()
+            });
+            state.project_metadata.modified = true;
+        }
+        Message::SetExternalTool(idx, tool) => {
+            *state
+                .project_metadata
+                .external_tools
+                .get_mut(*idx)
+                .context("tool not found")? = tool.clone();
+            state.project_metadata.modified = true;
+        }
+        &Message::DeleteExternalTool(idx) => {
+            if idx < state.project_metadata.external_tools.len() {
+                state.project_metadata.external_tools.remove(idx);
+                state.project_metadata.modified = true;
+            }
+        }
+        Message::SetExternalTools(tools) => {
+            state.project_metadata.external_tools = tools.clone();
+            state.project_metadata.modified = true;
+        }
+        &Message::RunExternalTool(idx) => {
+            let tool = state
+                .project_metadata
+                .external_tools
+                .get(idx)
+                .context("tool not found")?
+                .clone();
+            let project_dir = state
+                .global_config
+                .project_dir
+                .clone()
+                .context("no project open")?;
+            push_tool_output(state, format!("> {}: {}", tool.name, tool.command));
+            match external_tools::run(&tool, &project_dir, &state.main_area_id) {
+                Ok(task) => {
+                    state.running_tools += 1;
+                    return Ok(Some(task));
+                }
+                Err(e) => {
+                    error!("{:#}", e);
+                    push_tool_output(state, format!("{:#}", e));
+                }
+            }
+        }
+        Message::ExternalToolOutput(line) => {
+            info!("{}", line);
+            push_tool_output(state, line.clone());
+        }
+        Message::ExternalToolFinished { name, result } => {
+            state.running_tools = state.running_tools.saturating_sub(1);
+            let status = match result {
+                Ok(Some(code)) => format!("{} exited with code {}", name, code),
+                Ok(None) => format!("{} was terminated", name),
+                Err(e) => format!("{} failed: {}", name, e),
+            };
+            info!("{}", status);
+            push_tool_output(state, status);
+        }
+        Message::RunTestRom => {
+            let config = &state.global_config;
+            if config.emulator_path.is_none() || config.test_base_rom.is_none() {
+                state.dialogue = Some(Dialogue::Error(
+                    "To run a test ROM, set the emulator and base ROM in the settings.".to_string(),
+                ));
+                return Ok(None);
+            }
+            state.dialogue = Some(Dialogue::TestRun(TestRunStage::Building));
+            return Ok(Some(Task::done(Message::BuildTestRom)));
+        }
+        Message::BuildTestRom => {
+            let base_rom = state
+                .global_config
+                .test_base_rom
+                .clone()
+                .context("internal error")?;
+            let theme = state.main_area().theme.clone();
+            match test_rom::build(state, &base_rom, &theme) {
+                Ok(report) => {
+                    if let Some(report) = report {
+                        for (area, reason) in &report.skipped {
+                            warn!("Test ROM: skipped area {}: {}", area, reason);
+                        }
+                    }
+                    state.dialogue = Some(Dialogue::TestRun(TestRunStage::Launching));
+                    return Ok(Some(Task::done(Message::LaunchTestRom)));
+                }
+                Err(e) => {
+                    error!("Error building test ROM: {:#}", e);
+                    state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+                }
+            }
+        }
+        Message::LaunchTestRom => {
+            let emulator = state
+                .global_config
+                .emulator_path
+                .clone()
+                .context("internal error")?;
+            let rom = test_rom::test_rom_path(state, &state.main_area().theme)?;
+            match test_rom::launch(&emulator, &rom) {
+                Ok(task) => {
+                    state.dialogue = None;
+                    return Ok(Some(task));
+                }
+                Err(e) => {
+                    error!("Error running emulator: {:#}", e);
+                    state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+                }
+            }
+        }
+        Message::TestRunFinished(result) => match result {
+            Ok(Some(0)) | Ok(None) => info!("Emulator exited"),
+            Ok(Some(code)) => {
+                warn!("Emulator exited with code {}", code);
+                state.dialogue = Some(Dialogue::Error(format!(
+                    "The emulator exited with code {}.",
+                    code
+                )));
+            }
+            Err(e) => {
+                state.dialogue = Some(Dialogue::Error(format!("Emulator failed: {}", e)));
+            }
+        },
+        Message::PickEmulator => {
+            return Ok(Some(Task::perform(
+                open_emulator(),
+                Message::EmulatorPicked,
+            )));
+        }
+        Message::EmulatorPicked(path) => {
+            if let Some(path) = path {
+                state.global_config.emulator_path = Some(path.clone());
+                state.global_config.modified = true;
+            }
+        }
+        Message::PickTestBaseRom => {
+            return Ok(Some(Task::perform(open_rom(), Message::TestBaseRomPicked)));
+        }
+        Message::TestBaseRomPicked(path) => {
+            if let Some(path) = path {
+                state.global_config.test_base_rom = Some(path.clone());
+                state.global_config.modified = true;
+            }
+        }
+        Message::ClearToolConsole => {
+            state.tool_console.clear();
+        }
+        &Message::SetCollisionBrush(collision) => {
+            state.collision_brush = collision;
+            state.tool = Tool::Collision;
+            state.show_collision = true;
+        }
+        &Message::AreaScrolled { position, offset } => {
+            state.set_area_scroll(position, offset);
+            if state.linked_scroll_pending == Some(position) {
+                state.linked_scroll_pending = None;
+                return Ok(None);
+            }
+            let other = match position {
+                AreaPosition::Main => AreaPosition::Side,
+                AreaPosition::Side => AreaPosition::Main,
+            };
+            let offset = linked_offset(state, position, other, offset);
+            if state.link_area_scroll && state.area_scroll(other) != offset {
+                return Ok(Some(scroll_area_to(state, other, offset)));
+            }
+        }
+        &Message::ScrollAreaTo { position, offset } => {
+            state.set_area_scroll(position, offset);
+            return Ok(Some(scrollable::scroll_to(
+                area_scrollable_id(position),
+                AbsoluteOffset {
+                    x: offset.x,
+                    y: offset.y,
+                },
+            )));
+        }
+        &Message::SetAutoPan(pan) => {
+            state.auto_pan = pan;
+        }
+        Message::AutoPanTick => {
+            let Some(pan) = state.auto_pan else {
+                return Ok(None);
+            };
+            let area_size = state.area(pan.position).size;
+            let pixel_size = state.area_pixel_size(pan.position);
+            let content = Size::new(
+                (area_size.0 as f32 * 256.0 + 2.0) * pixel_size + AREA_SCROLLBAR_PADDING,
+                (area_size.1 as f32 * 256.0 + 2.0) * pixel_size + AREA_SCROLLBAR_PADDING,
+            );
+            let scroll = state.area_scroll(pan.position);
+            let offset = panned_scroll(scroll, pan.velocity, content, pan.viewport);
+            if offset == scroll {
+                return Ok(None);
+            }
+            state.set_area_scroll(pan.position, offset);
+            // Continue the selection or brush stroke at the tile that's now under the cursor:
+            let coords = area_tile_at(
+                Point::new(offset.x + pan.cursor.x, offset.y + pan.cursor.y),
+                area_size,
+                pixel_size,
+            );
+            let message = match pan.action {
+                PanAction::Select => Message::ProgressTileSelection(coords),
+                PanAction::Brush => {
+                    let coords = match state.stamp_metatile().map(|m| m.size.tiles()) {
+                        Some(n) => Point::new(coords.x / n * n, coords.y / n * n),
+                        None => coords,
+                    };
+                    Message::AreaBrush {
+                        position: pan.position,
+                        area_id: state.area_id(pan.position).clone(),
+                        coords,
+                        selection: state.selected_tile_block.clone(),
+                        palette_only: state.palette_only_brush,
+                    }
+                }
+            };
+            return Ok(Some(Task::batch([
+                scrollable::scroll_to(
+                    area_scrollable_id(pan.position),
+                    AbsoluteOffset {
+                        x: offset.x,
+                        y: offset.y,
+                    },
+                ),
+                Task::done(message),
+            ])));
+        }
+        &Message::ZoomArea {
+            position,
+            steps,
+            anchor,
+        } => {
+            let old_pixel_size = state.area_pixel_size(position);
+            let pixel_size = (old_pixel_size * WHEEL_ZOOM_FACTOR.powf(steps))
+                .clamp(MIN_PIXEL_SIZE, MAX_PIXEL_SIZE);
+            if pixel_size == old_pixel_size {
+                return Ok(None);
+            }
+            state.set_area_zoom(position, Some(pixel_size));
+            // Scroll so that the point under the cursor stays there:
+            let shift = anchor * (pixel_size / old_pixel_size - 1.0);
+            let scroll = state.area_scroll(position) + shift;
+            let offset = Vector::new(scroll.x.max(0.0), scroll.y.max(0.0));
+            return Ok(Some(scroll_area_to(state, position, offset)));
+        }
+        Message::ToggleLinkedScroll => {
+            state.link_area_scroll = !state.link_area_scroll;
+            if state.link_area_scroll {
+                let offset = linked_offset(
+                    state,
+                    AreaPosition::Main,
+                    AreaPosition::Side,
+                    state.main_area_scroll,
+                );
+                return Ok(Some(scroll_area_to(state, AreaPosition::Side, offset)));
+            }
+        }
+        Message::ToggleSideDifferences => {
+            state.show_side_differences = !state.show_side_differences;
+        }
+        Message::ToggleGuide { area, guide } => {
+            let guides = state.guides.entry(area.clone()).or_default();
+            if let Some(i) = guides.iter().position(|g| g == guide) {
+                guides.remove(i);
+            } else {
+                guides.push(*guide);
+            }
+        }
+        Message::CopyTiles => {
+            match ClipboardTiles::from_selection(state).and_then(|tiles| tiles.to_text()) {
+                Ok(text) => return Ok(Some(iced::clipboard::write(text))),
+                Err(e) => state.dialogue = Some(Dialogue::Error(format!("{:#}", e))),
+            }
+        }
+        Message::PasteTiles => {
+            return Ok(Some(iced::clipboard::read().map(Message::PastedTiles)));
+        }
+        Message::PastedTiles(text) => {
+            let result = ClipboardTiles::parse(text.as_deref().unwrap_or_default())
+                .and_then(|tiles| tiles.paste_messages(state));
+            match result {
+                Ok(messages) => {
+                    info!("Pasting tiles from clipboard");
+                    return Ok(Some(Task::done(Message::Batch(messages))));
+                }
+                Err(e) => state.dialogue = Some(Dialogue::Error(format!("{:#}", e))),
+            }
+        }
+        Message::CopySelectionJson => {
+            match SelectionJson::from_selection(state).and_then(|selection| selection.to_text()) {
+                Ok(text) => {
+                    state.dialogue = None;
+                    return Ok(Some(iced::clipboard::write(text)));
+                }
+                Err(e) => state.dialogue = Some(Dialogue::Error(format!("{:#}", e))),
+            }
+        }
+        Message::PasteSelectionJson => {
+            return Ok(Some(
+                iced::clipboard::read().map(Message::PastedSelectionJson),
+            ));
+        }
+        Message::PastedSelectionJson(text) => {
+            let result = SelectionJson::parse(text.as_deref().unwrap_or_default())
+                .and_then(|selection| selection.to_block(state));
+            match result {
+                Ok(block) => {
+                    info!("Pasting JSON selection from clipboard as the brush");
+                    state.dialogue = None;
+                    return Ok(Some(Task::done(Message::SetBrush(block))));
+                }
+                Err(e) => state.dialogue = Some(Dialogue::Error(format!("{:#}", e))),
+            }
+        }
+        Message::SetBrush(block) => {
+            state.selected_tile_block = block.clone();
+            update_selected_gfx(state);
+            state.tool = Tool::Brush;
+            state.tile_idx = None;
+            state.start_coords = None;
+            state.end_coords = None;
+        }
+        Message::ColorRampsDialogue => {
+            state.dialogue = Some(Dialogue::ColorRamps {
+                start: state.color_idx.unwrap_or(1),
+                len: 4,
+                shade: None,
+            });
+        }
+        &Message::SetColorRampRange { start, len } => {
+            if let Some(Dialogue::ColorRamps {
+                start: s, len: l, ..
+            }) = &mut state.dialogue
+            {
+                *s = start.min(16 - MIN_RAMP_LEN);
+                *l = len.clamp(MIN_RAMP_LEN, 16 - *s);
+            }
+        }
+        &Message::SelectRampShade(selected) => {
+            if let Some(Dialogue::ColorRamps { shade, .. }) = &mut state.dialogue {
+                *shade = selected;
+            }
+        }
+        &Message::AddColorRamp {
+            palette_id,
+            start,
+            len,
+        } => {
+            if len < MIN_RAMP_LEN || start as usize + len as usize > 16 {
+                warn!("Invalid color ramp range: {} colors from {}", len, start);
+                return Ok(None);
+            }
+            let pal_idx = *state
+                .palettes_id_idx_map
+                .get(&palette_id)
+                .context("palette not found")?;
+            let ramps = &mut state.project_metadata.color_ramps;
+            ramps.push(ColorRamp {
+                name: format!("Ramp {}", ramps.len() + 1),
+                colors: state.palettes[pal_idx].colors[start as usize..(start + len) as usize]
+                    .to_vec(),
+                subscribers: vec![RampSubscriber { palette_id, start }],
+            });
+            state.project_metadata.modified = true;
+        }
+        &Message::DeleteColorRamp(ramp_idx) => {
+            if ramp_idx < state.project_metadata.color_ramps.len() {
+                state.project_metadata.color_ramps.remove(ramp_idx);
+                state.project_metadata.modified = true;
+            }
+            if let Some(Dialogue::ColorRamps { shade, .. }) = &mut state.dialogue {
+                *shade = None;
+            }
+        }
+        Message::SetColorRampName(ramp_idx, name) => {
+            let ramp = state
+                .project_metadata
+                .color_ramps
+                .get_mut(*ramp_idx)
+                .context("color ramp not found")?;
+            ramp.name = name.clone();
+            state.project_metadata.modified = true;
+        }
+        Message::SetColorRamps(ramps) => {
+            state.project_metadata.color_ramps = ramps.clone();
+            state.project_metadata.modified = true;
+            if let Some(Dialogue::ColorRamps { shade, .. }) = &mut state.dialogue {
+                *shade = None;
+            }
+        }
+        &Message::ShowMetatiles(show) => {
+            state.side_panel_view = if show {
+                SidePanelView::Metatiles
+            } else {
+                SidePanelView::Tileset
+            };
+            state.side_panel_hidden = false;
+        }
+        &Message::ShowStamps(show) => {
+            state.side_panel_view = if show {
+                SidePanelView::Stamps
+            } else {
+                SidePanelView::Tileset
+            };
+            state.side_panel_hidden = false;
+            if show {
+                match load_stamps(state) {
+                    Ok(stamps) => state.stamps = stamps,
+                    Err(e) => state.dialogue = Some(Dialogue::Error(format!("{:#}", e))),
+                }
+            }
+        }
+        Message::SetStampName(name) => {
+            state.stamp_name = name.clone();
+        }
+        Message::SaveStamp => {
+            let result =
+                stamps::save_stamp(state, &state.stamp_name).and_then(|_| load_stamps(state));
+            match result {
+                Ok(stamps) => state.stamps = stamps,
+                Err(e) => state.dialogue = Some(Dialogue::Error(format!("{:#}", e))),
+            }
+        }
+        Message::LoadStamp(name) => {
+            let stamp = state
+                .stamps
+                .iter()
+                .find(|stamp| &stamp.name == name)
+                .context("stamp not found")?;
+            info!("Loading stamp {} into the brush", name);
+            return Ok(Some(Task::done(Message::SetBrush(stamp.block.clone()))));
+        }
+        Message::DeleteStamp(name) => {
+            let result = stamps::delete_stamp(state, name).and_then(|_| load_stamps(state));
+            match result {
+                Ok(stamps) => state.stamps = stamps,
+                Err(e) => state.dialogue = Some(Dialogue::Error(format!("{:#}", e))),
+            }
+        }
+        &Message::AddMetatile(size) => {
+            let metatiles = &mut state.project_metadata.metatiles;
+            let name = format!("Metatile {}", metatiles.len() + 1);
+            metatiles.push(Metatile::from_brush(
+                name,
+                size,
+                &state.selected_tile_block,
+            )?);
+            state.metatile_idx = Some(metatiles.len() - 1);
+            state.project_metadata.modified = true;
+        }
+        &Message::SelectMetatile(metatile_idx) => {
+            state.metatile_idx = metatile_idx;
+        }
+        Message::SetMetatileName(metatile_idx, name) => {
+            let metatile = state
+                .project_metadata
+                .metatiles
+                .get_mut(*metatile_idx)
+                .context("metatile not found")?;
+            metatile.name = name.clone();
+            state.project_metadata.modified = true;
+        }
+        &Message::SetMetatileCell {
+            metatile_idx,
+            x,
+            y,
+            palette_id,
+            tile_idx,
+            flip,
+        } => {
+            let metatile = state
+                .project_metadata
+                .metatiles
+                .get_mut(metatile_idx)
+                .context("metatile not found")?;
+            let stamping = metatile.block == state.selected_tile_block
+                && state.stamp_metatile == Some(metatile_idx);
+            metatile.set_cell(x, y, palette_id, tile_idx, flip)?;
+            // Keep stamping the edited metatile:
+            if stamping {
+                state.selected_tile_block = metatile.block.clone();
+                update_selected_gfx(state);
+            }
+            state.project_metadata.modified = true;
+        }
+        &Message::DeleteMetatile(metatile_idx) => {
+            if metatile_idx < state.project_metadata.metatiles.len() {
+                state.project_metadata.metatiles.remove(metatile_idx);
+                state.project_metadata.modified = true;
+                state.metatile_idx = None;
+                state.stamp_metatile = None;
+            }
+        }
+        Message::SetMetatiles(metatiles) => {
+            state.project_metadata.metatiles = metatiles.clone();
+            state.project_metadata.modified = true;
+            if state.metatile_idx.is_some_and(|i| i >= metatiles.len()) {
+                state.metatile_idx = None;
+            }
+        }
+        &Message::StampMetatile(metatile_idx) => {
+            let metatile = state
+                .project_metadata
+                .metatiles
+                .get(metatile_idx)
+                .context("metatile not found")?;
+            // Brushing with the metatile's block snaps it to the metatile grid, for as long
+            // as it remains the brush (see `EditorState::stamp_metatile`):
+            state.selected_tile_block = metatile.block.clone();
+            update_selected_gfx(state);
+            state.stamp_metatile = Some(metatile_idx);
+            state.tool = Tool::Brush;
+            state.tile_idx = None;
+            state.start_coords = None;
+            state.end_coords = None;
+        }
+        &Message::SetRampColor {
+            ramp_idx,
+            shade,
+            color,
+        } => {
+            let ramp = state
+                .project_metadata
+                .color_ramps
+                .get_mut(ramp_idx)
+                .context("color ramp not found")?;
+            *ramp.colors.get_mut(shade).context("shade not found")? = color;
+            for s in &ramp.subscribers {
+                let Some(&pal_idx) = state.palettes_id_idx_map.get(&s.palette_id) else {
+                    warn!(
+                        "Color ramp {} uses missing palette {}",
+                        ramp.name, s.palette_id
+                    );
+                    continue;
+                };
+                state.palettes[pal_idx].colors[s.start as usize + shade] = color;
+                state.palettes[pal_idx].modified = true;
+            }
+            state.project_metadata.modified = true;
+        }
+        &Message::SubscribeColorRamp {
+            ramp_idx,
+            palette_id,
+            start,
+        } => {
+            let ramp = state
+                .project_metadata
+                .color_ramps
+                .get_mut(ramp_idx)
+                .context("color ramp not found")?;
+            if !ramp.fits_at(start) {
+                warn!("Color ramp {} does not fit at color {}", ramp.name, start);
+                return Ok(None);
+            }
+            if ramp.is_subscribed(palette_id, start) {
+                return Ok(None);
+            }
+            let pal_idx = *state
+                .palettes_id_idx_map
+                .get(&palette_id)
+                .context("palette not found")?;
+            let palette = &mut state.palettes[pal_idx];
+            palette.colors[start as usize..start as usize + ramp.colors.len()]
+                .copy_from_slice(&ramp.colors);
+            palette.modified = true;
+            ramp.subscribers.push(RampSubscriber { palette_id, start });
+            state.project_metadata.modified = true;
+        }
+        &Message::UnsubscribeColorRamp {
+            ramp_idx,
+            palette_id,
+            start,
+        } => {
+            let ramp = state
+                .project_metadata
+                .color_ramps
+                .get_mut(ramp_idx)
+                .context("color ramp not found")?;
+            ramp.subscribers
+                .retain(|s| s.palette_id != palette_id || s.start != start);
+            state.project_metadata.modified = true;
+        }
+        Message::FlipSuggestionsDialogue => {
+            let suggestions = state
+                .palettes
+                .iter()
+                .flat_map(find_flip_suggestions)
+                .map(|x| (x, true))
+                .collect();
+            state.dialogue = Some(Dialogue::FlipSuggestions(suggestions));
+        }
+        &Message::ToggleFlipSuggestion(i) => {
+            if let Some(Dialogue::FlipSuggestions(suggestions)) = &mut state.dialogue {
+                if let Some((_, checked)) = suggestions.get_mut(i) {
+                    *checked = !*checked;
+                }
+            }
+        }
+        &Message::SetAllFlipSuggestions(value) => {
+            if let Some(Dialogue::FlipSuggestions(suggestions)) = &mut state.dialogue {
+                for (_, checked) in suggestions.iter_mut() {
+                    *checked = value;
+                }
+            }
+        }
+        Message::ApplyFlipSuggestions => {
+            let Some(Dialogue::FlipSuggestions(suggestions)) = &state.dialogue else {
+                return Ok(None);
+            };
+            let mut messages = vec![];
+            for (suggestion, _) in suggestions.iter().filter(|x| x.1) {
+                let idx = *state
+                    .palettes_id_idx_map
+                    .get(&suggestion.palette_id())
+                    .context("palette not found")?;
+                messages.extend(suggestion.messages(&state.palettes[idx]));
+            }
+            state.dialogue = None;
+            if !messages.is_empty() {
+                return Ok(Some(Task::done(Message::Batch(messages))));
+            }
+        }
+        &Message::DuplicateTilesDialogue(palette_id) => {
+            let pal_idx = *state
+                .palettes_id_idx_map
+                .get(&palette_id)
+                .context("undefined palette")?;
+            if palette_tiles_locked(state, pal_idx) {
+                return Ok(None);
+            }
+            state.dialogue = Some(Dialogue::DuplicateTiles {
+                palette_id,
+                duplicates: find_duplicate_tiles(&state.palettes[pal_idx]),
+            });
+        }
+        Message::ApplyDuplicateTiles => {
+            let Some(Dialogue::DuplicateTiles {
+                palette_id,
+                duplicates,
+            }) = &state.dialogue
+            else {
+                return Ok(None);
+            };
+            let messages = merge_messages(*palette_id, duplicates);
+            state.dialogue = None;
+            if !messages.is_empty() {
+                return Ok(Some(Task::done(Message::Batch(messages))));
+            }
+        }
+        &Message::MergeFlippedTile {
+            palette_id,
+            tile_idx,
+            original,
+            flip,
+        } => {
+            for theme in state.theme_names.clone() {
+                for area_name in state.area_names.clone() {
+                    let area_id = AreaId {
+                        area: area_name,
+                        theme: theme.clone(),
+                    };
+                    modify_area(state, &area_id, |area| {
+                        let mut changed = false;
+                        for y in 0..area.size.1 as TileCoord * 32 {
+                            for x in 0..area.size.0 as TileCoord * 32 {
+                                if area.get_palette(x, y)? == palette_id
+                                    && area.get_tile(x, y)? == tile_idx
+                                {
+                                    area.set_tile(x, y, original)?;
+                                    area.set_flip(x, y, flip.compose(area.get_flip(x, y)?))?;
+                                    changed = true;
+                                }
+                            }
+                        }
+                        Ok(changed)
+                    })?;
+                }
+            }
+        }
+        Message::FindTileUsages => {
+            let Some(tile_idx) = state.tile_idx else {
+                return Ok(None);
+            };
+            let palette_id = state.palettes[state.palette_idx].id;
+            let scan = UsageScan::new(state, palette_id, tile_idx)?;
+            state.usage_search = Some(UsageSearch {
+                palette_id,
+                tile_idx,
+                usages: None,
+            });
+            state.side_panel_view = SidePanelView::Usages;
+            state.side_panel_hidden = false;
+            return Ok(Some(scan.spawn()));
+        }
+        Message::TileUsagesFound {
+            palette_id,
+            tile_idx,
+            usages,
+        } => {
+            // Results of an earlier search are dropped if another search has started since.
+            if let Some(search) = &mut state.usage_search {
+                if (search.palette_id, search.tile_idx) == (*palette_id, *tile_idx) {
+                    search.usages = Some(usages.clone());
+                }
+            }
+        }
+        Message::GoToTileUsage(usage) => {
+            state.switch_area(AreaPosition::Main, &usage.area_id)?;
+            return Ok(Some(scroll_to_tile(state, usage.x, usage.y)));
+        }
+        Message::CloseTileUsages => {
+            state.usage_search = None;
+            state.side_panel_view = SidePanelView::Tileset;
+        }
+        Message::ValidateProject => {
+            let scan = ValidationScan::new(state)?;
+            state.project_issues = None;
+            state.side_panel_view = SidePanelView::Issues;
+            state.side_panel_hidden = false;
+            return Ok(Some(scan.spawn()));
+        }
+        Message::ValidationFinished(issues) => {
+            state.project_issues = Some(issues.clone());
+        }
+        Message::GoToIssue(location) => match location {
+            &IssueLocation::Palette(palette_id) => {
+                if let Some(&idx) = state.palettes_id_idx_map.get(&palette_id) {
+                    state.palette_idx = idx;
+                    state.tile_idx = None;
+                }
+            }
+            IssueLocation::Area(area_id) => {
+                state.switch_area(AreaPosition::Main, area_id)?;
+            }
+            IssueLocation::Tile { area_id, x, y } => {
+                state.switch_area(AreaPosition::Main, area_id)?;
+                return Ok(Some(scroll_to_tile(state, *x, *y)));
+            }
+        },
+        Message::CloseIssues => {
+            state.project_issues = None;
+            state.side_panel_view = SidePanelView::Tileset;
+        }
+        Message::ShowAreaDiff(base) => {
+            match AreaDiff::new(state, &state.main_area_id, base.clone()) {
+                Ok(diff) => {
+                    state.area_diff = Some(diff);
+                    state.dialogue = None;
+                }
+                Err(e) => {
+                    state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+                }
+            }
+        }
+        Message::ShowPaletteReplace => {
+            // Start from the selected palette, if the area uses it:
+            let used = state.main_area().get_unique_palettes();
+            let selected = state.palettes[state.palette_idx].id;
+            let from = if used.contains(&selected) {
+                Some(selected)
+            } else {
+                used.first().copied()
+            };
+            state.palette_replace = Some(PaletteReplace {
+                area_id: state.main_area_id.clone(),
+                from,
+                to: None,
+            });
+            state.dialogue = None;
+        }
+        &Message::SetPaletteReplaceFrom(palette_id) => {
+            if let Some(replace) = &mut state.palette_replace {
+                replace.from = Some(palette_id);
+            }
+        }
+        &Message::SetPaletteReplaceTo(palette_id) => {
+            if let Some(replace) = &mut state.palette_replace {
+                replace.to = Some(palette_id);
+            }
+        }
+        Message::ClosePaletteReplace => {
+            state.palette_replace = None;
+        }
+        &Message::ReplaceAreaPalette {
+            ref area_id,
+            from,
+            to,
+        } => {
+            if !state.palettes_id_idx_map.contains_key(&to) {
+                bail!("palette {} not found", to);
+            }
+            state.switch_area(AreaPosition::Main, area_id)?;
+            let area = state.main_area_mut();
+            let cells = palette_cells(area, from);
+            for &(x, y) in &cells {
+                area.set_palette(x, y, to)?;
+            }
+            area.modified = true;
+            info!(
+                "Replaced palette {} with {} in {} tiles of {}",
+                from,
+                to,
+                cells.len(),
+                area_id.area
+            );
+            state.palette_replace = None;
+        }
+        Message::SetAreaPalettes {
+            area_id,
+            palette_id,
+            cells,
+        } => {
+            state.switch_area(AreaPosition::Main, area_id)?;
+            let area = state.main_area_mut();
+            for &(x, y) in cells {
+                area.set_palette(x, y, *palette_id)?;
+            }
+            area.modified = true;
+        }
+        Message::CloseAreaDiff => {
+            state.area_diff = None;
+        }
+        &Message::GoToDiffScreen((x, y)) => {
+            let screen_size = 256.0 * state.area_pixel_size(AreaPosition::Main);
+            let offset = AbsoluteOffset {
+                x: x as f32 * screen_size,
+                y: y as f32 * screen_size,
+            };
+            state.main_area_scroll = Vector::new(offset.x, offset.y);
+            return Ok(Some(scrollable::scroll_to(
+                area_scrollable_id(AreaPosition::Main),
+                offset,
+            )));
+        }
+        Message::SetTileUsages { palette_id, usages } => {
+            for (area_id, area_usages) in &usages.iter().chunk_by(|u| &u.area_id) {
+                let area_usages: Vec<&TileUsage> = area_usages.collect();
+                modify_area(state, &area_id.clone(), |area| {
+                    for u in area_usages {
+                        area.set_palette(u.x, u.y, *palette_id)?;
+                        area.set_tile(u.x, u.y, u.tile_idx)?;
+                        area.set_flip(u.x, u.y, u.flip)?;
+                    }
+                    Ok(true)
+                })?;
+            }
+        }
+        &Message::TilesetBrush {
+            palette_id,
+            coords: Point { x: x0, y: y0 },
+            selected_gfx: ref s,
+        } => {
+            let pal_idx = *state
+                .palettes_id_idx_map
+                .get(&palette_id)
+                .context("undefined palette")?;
+            if palette_tiles_locked(state, pal_idx) {
+                return Ok(None);
+            }
+            for y in 0..s.len() {
+                for x in 0..s[0].len() {
+                    let y1 = y + y0 as usize;
+                    let x1 = x + x0 as usize;
+                    let i = y1 * 16 + x1;
+                    if x1 < 16 && i < state.palettes[pal_idx].tiles.len() {
+                        state.palettes[pal_idx].tiles[i] = s[y as usize][x as usize];
+                    }
+                }
+            }
+            state.palettes[pal_idx].modified = true;
+        }
+        &Message::SelectPixel(x, y) => {
+            state.pixel_coords = Some((x, y));
             if let Some(tile_idx) = state.tile_idx {
                 let pal = &mut state.palettes[state.palette_idx];
                 let color_idx = pal.tiles[tile_idx as usize].pixels[y as usize][x as usize];
@@ -864,10 +3424,35 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
                 .palettes_id_idx_map
                 .get(&palette_id)
                 .context("undefined palette")?;
+            if palette_tiles_locked(state, pal_idx) {
+                return Ok(None);
+            }
             let pal = &mut state.palettes[pal_idx];
             pal.tiles[tile_idx as usize].pixels[coords.y as usize][coords.x as usize] = color_idx;
             pal.modified = true;
         }
+        &Message::SetTilePixels {
+            palette_id,
+            ref pixels,
+        } => {
+            let pal_idx = *state
+                .palettes_id_idx_map
+                .get(&palette_id)
+                .context("undefined palette")?;
+            if palette_tiles_locked(state, pal_idx) {
+                return Ok(None);
+            }
+            let pal = &mut state.palettes[pal_idx];
+            for &(tile_idx, coords, color_idx) in pixels {
+                let tile = pal
+                    .tiles
+                    .get_mut(tile_idx as usize)
+                    .context("tile not found")?;
+                tile.pixels[coords.y as usize][coords.x as usize] = color_idx;
+            }
+            pal.modified = true;
+            update_selected_gfx(state);
+        }
         &Message::SelectArea(position, ref name) => {
             let area_id = &state.main_area_id;
             state.switch_area(
@@ -922,32 +3507,88 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
                 }
             }
             for theme in state.theme_names.clone() {
-                state.set_area(
-                    AreaPosition::Main,
-                    Area {
-                        modified: true,
-                        name: name.clone(),
-                        theme,
-                        size: *size,
-                        vanilla_map_id: state.areas[&state.main_area_id].vanilla_map_id,
-                        bg_color: state.areas[&state.main_area_id].bg_color,
-                        screens: (0..size.0)
-                            .cartesian_product(0..size.1)
-                            .map(|(x, y)| Screen {
-                                position: (x, y),
-                                palettes: [[0; 32]; 32],
-                                tiles: [[0; 32]; 32],
-                                flips: [[Flip::None; 32]; 32],
-                            })
-                            .collect(),
-                    },
-                )?;
+                let main_area = &state.areas[&state.main_area_id];
+                let area = Area::empty(
+                    name.clone(),
+                    theme,
+                    *size,
+                    main_area.vanilla_map_id,
+                    main_area.bg_color,
+                );
+                state.set_area(AreaPosition::Main, area)?;
                 save_area(state, &state.main_area_id.clone())?;
             }
+            // A new area is listed under the same group as the area it was created from.
+            let main_area_name = state.main_area_id.area.clone();
+            if let Some(group) = state.project_metadata.area_groups.get(&main_area_name) {
+                let group = group.clone();
+                state
+                    .project_metadata
+                    .area_groups
+                    .insert(name.clone(), group);
+                state.project_metadata.modified = true;
+            }
             state.dialogue = None;
             state.area_names.push(name.clone());
             state.area_names.sort();
         }
+        Message::OpenAreaManifest => {
+            return Ok(Some(Task::perform(
+                open_area_manifest(),
+                Message::AreaManifestOpened,
+            )));
+        }
+        Message::AreaManifestOpened(path) => {
+            if let Some(path) = path {
+                let entries = load_area_manifest(path)?;
+                info!("Creating {} areas from {}", entries.len(), path.display());
+                let first = entries[0].clone();
+                state.dialogue = Some(Dialogue::CreateAreas {
+                    entries,
+                    done: 0,
+                    skipped: vec![],
+                });
+                return Ok(Some(Task::done(Message::CreateManifestArea(first))));
+            }
+        }
+        Message::CreateManifestArea(entry) => {
+            // Existing areas are skipped rather than replaced:
+            let exists = state.area_names.contains(&entry.name);
+            if !exists {
+                let bg_color = entry
+                    .bg_color
+                    .unwrap_or(state.areas[&state.main_area_id].bg_color);
+                for theme in state.theme_names.clone() {
+                    let area = Area::empty(
+                        entry.name.clone(),
+                        theme,
+                        entry.size,
+                        entry.vanilla_map_id,
+                        bg_color,
+                    );
+                    let area_id = area.id();
+                    state.areas.insert(area_id.clone(), area);
+                    save_area(state, &area_id)?;
+                    state.areas.remove(&area_id);
+                }
+                state.area_names.push(entry.name.clone());
+                state.area_names.sort();
+            }
+            if let Some(Dialogue::CreateAreas {
+                entries,
+                done,
+                skipped,
+            }) = &mut state.dialogue
+            {
+                if exists {
+                    skipped.push(format!("{}: already exists", entry.name));
+                }
+                *done += 1;
+                if let Some(next) = entries.get(*done) {
+                    return Ok(Some(Task::done(Message::CreateManifestArea(next.clone()))));
+                }
+            }
+        }
         Message::EditAreaDialogue => {
             state.dialogue = Some(Dialogue::EditArea {
                 name: state.main_area_id.area.clone(),
@@ -974,8 +3615,21 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
             }
             let area_id = state.main_area_id.clone();
             if new_name != old_name {
-                rename_area(state, old_name, new_name)?;
+                if let Err(e) = rename_area(state, old_name, new_name) {
+                    error!("Error renaming area {}: {:#}", old_name, e);
+                    state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+                    return Ok(None);
+                }
                 load_area_list(state)?;
+                let metadata = &mut state.project_metadata;
+                if let Some(group) = metadata.area_groups.remove(old_name) {
+                    metadata.area_groups.insert(new_name.clone(), group);
+                    metadata.modified = true;
+                }
+                if let Some(p) = metadata.world_layout.remove(old_name) {
+                    metadata.world_layout.insert(new_name.clone(), p);
+                    metadata.modified = true;
+                }
                 if &state.main_area_id.area == old_name {
                     state.switch_area(
                         AreaPosition::Main,
@@ -994,9 +3648,13 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
                         },
                     )?;
                 }
+                record_rename(state, RenameKind::Area, old_name, new_name)?;
             }
             state.dialogue = None;
         }
+        Message::ToggleBGColorEditor => {
+            state.show_bg_color_editor = !state.show_bg_color_editor;
+        }
         &Message::EditAreaBGRed(c) => {
             let mut color = state.main_area().bg_color;
             color[0] = c;
@@ -1005,56 +3663,565 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
                 color,
             })));
         }
-        &Message::EditAreaBGGreen(c) => {
-            let mut color = state.main_area().bg_color;
-            color[1] = c;
-            return Ok(Some(Task::done(Message::EditAreaBGColor {
-                area_id: state.area_id(AreaPosition::Main).clone(),
-                color,
-            })));
+        &Message::EditAreaBGGreen(c) => {
+            let mut color = state.main_area().bg_color;
+            color[1] = c;
+            return Ok(Some(Task::done(Message::EditAreaBGColor {
+                area_id: state.area_id(AreaPosition::Main).clone(),
+                color,
+            })));
+        }
+        &Message::EditAreaBGBlue(c) => {
+            let mut color = state.main_area().bg_color;
+            color[2] = c;
+            return Ok(Some(Task::done(Message::EditAreaBGColor {
+                area_id: state.area_id(AreaPosition::Main).clone(),
+                color,
+            })));
+        }
+        &Message::EditAreaBGColor { ref area_id, color } => {
+            state.switch_area(AreaPosition::Main, area_id)?;
+            state.main_area_mut().bg_color = color;
+        }
+        &Message::EditAreaProperty(edit) => {
+            let area = state.main_area();
+            return Ok(Some(Task::done(Message::SetAreaProperties {
+                area_id: state.area_id(AreaPosition::Main).clone(),
+                properties: area.properties.edited(area.vanilla_map_id, edit),
+            })));
+        }
+        Message::SetAreaProperties {
+            area_id,
+            properties,
+        } => {
+            state.switch_area(AreaPosition::Main, area_id)?;
+            let area = state.main_area_mut();
+            area.properties = properties.clone();
+            area.modified = true;
+        }
+        Message::BGColorsDialogue => {
+            let theme = state.main_area_id.theme.clone();
+            let color = state.main_area().bg_color;
+            match bg_color_table(state) {
+                Ok(colors) => {
+                    state.dialogue = Some(Dialogue::BGColors {
+                        colors,
+                        theme,
+                        color,
+                    });
+                }
+                Err(e) => {
+                    state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+                }
+            }
+        }
+        Message::SelectBGColor {
+            theme: new_theme,
+            color: new_color,
+        } => {
+            if let Some(Dialogue::BGColors { theme, color, .. }) = &mut state.dialogue {
+                *theme = new_theme.clone();
+                *color = *new_color;
+            }
+        }
+        &Message::SetBGColorsColor(new_color) => {
+            if let Some(Dialogue::BGColors { color, .. }) = &mut state.dialogue {
+                *color = new_color;
+            }
+        }
+        Message::SetBGColorColumn => {
+            let Some(Dialogue::BGColors { theme, color, .. }) = &state.dialogue else {
+                return Ok(None);
+            };
+            let colors = state
+                .area_names
+                .iter()
+                .map(|area| {
+                    let area_id = AreaId {
+                        area: area.clone(),
+                        theme: theme.clone(),
+                    };
+                    (area_id, *color)
+                })
+                .collect();
+            return Ok(Some(Task::done(Message::SetBGColors(colors))));
+        }
+        Message::CopyBGColorRow(area) => {
+            let Some(Dialogue::BGColors { colors, theme, .. }) = &state.dialogue else {
+                return Ok(None);
+            };
+            let area_idx = state
+                .area_names
+                .iter()
+                .position(|a| a == area)
+                .context("area not found")?;
+            let theme_idx = state
+                .theme_names
+                .iter()
+                .position(|t| t == theme)
+                .context("theme not found")?;
+            let color = colors[area_idx][theme_idx];
+            let colors = state
+                .theme_names
+                .iter()
+                .filter(|&t| t != theme)
+                .map(|t| {
+                    let area_id = AreaId {
+                        area: area.clone(),
+                        theme: t.clone(),
+                    };
+                    (area_id, color)
+                })
+                .collect();
+            return Ok(Some(Task::done(Message::SetBGColors(colors))));
+        }
+        Message::SetBGColors(colors) => {
+            for (area_id, color) in colors {
+                modify_area(state, area_id, |area| {
+                    let changed = area.bg_color != *color;
+                    area.bg_color = *color;
+                    Ok(changed)
+                })?;
+            }
+            if matches!(state.dialogue, Some(Dialogue::BGColors { .. })) {
+                let table = bg_color_table(state)?;
+                if let Some(Dialogue::BGColors { colors, .. }) = &mut state.dialogue {
+                    *colors = table;
+                }
+            }
+        }
+        Message::DeleteAreaDialogue => {
+            state.dialogue = Some(Dialogue::DeleteArea);
+        }
+        Message::DarkWorldDialogue => {
+            let target = dark_world_map_id(state.main_area()).and_then(|map_id| {
+                Ok(match find_area_for_map(state, map_id)? {
+                    Some(name) => (name, true),
+                    None => (dark_world_area_name(map_id), false),
+                })
+            });
+            match target {
+                Ok((target, exists)) => {
+                    let mapping = state
+                        .project_metadata
+                        .dark_world_palettes
+                        .iter()
+                        .copied()
+                        .collect();
+                    state.dialogue = Some(Dialogue::DarkWorld {
+                        target,
+                        exists,
+                        mapping,
+                    });
+                }
+                Err(e) => {
+                    state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+                }
+            }
+        }
+        &Message::SetDarkWorldPalette { light, dark } => {
+            if let Some(Dialogue::DarkWorld { mapping, .. }) = &mut state.dialogue {
+                if light == dark {
+                    mapping.remove(&light);
+                } else {
+                    mapping.insert(light, dark);
+                }
+            }
+        }
+        Message::GenerateDarkWorldArea {
+            light_area,
+            mapping,
+        } => {
+            let areas = match dark_world_areas(state, light_area, mapping) {
+                Ok(areas) => areas,
+                Err(e) => {
+                    state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+                    return Ok(None);
+                }
+            };
+            let first = areas.first().context("internal error")?;
+            let name = first.name.clone();
+            let map_id = first.vanilla_map_id;
+            let exists = state.area_names.contains(&name);
+            if exists
+                && state
+                    .area_copy(&AreaId {
+                        area: name.clone(),
+                        theme: state.main_area_id.theme.clone(),
+                    })?
+                    .vanilla_map_id
+                    != map_id
+            {
+                state.dialogue = Some(Dialogue::Error(format!(
+                    "Area {} already exists for a different map.",
+                    name
+                )));
+                return Ok(None);
+            }
+            for area in areas {
+                let area_id = area.id();
+                state.areas.insert(area_id.clone(), area);
+                save_area(state, &area_id)?;
+            }
+            let metadata = &mut state.project_metadata;
+            if !exists {
+                state.area_names.push(name.clone());
+                state.area_names.sort();
+                if !metadata.area_groups.contains_key(&name) {
+                    metadata
+                        .area_groups
+                        .insert(name.clone(), "Dark World".to_string());
+                }
+            }
+            // Remember the palettes for generating the other dark world areas:
+            let mut palettes: BTreeMap<PaletteId, PaletteId> =
+                metadata.dark_world_palettes.iter().copied().collect();
+            palettes.extend(mapping);
+            metadata.dark_world_palettes = palettes.into_iter().collect();
+            metadata.modified = true;
+            state.switch_area(
+                AreaPosition::Main,
+                &AreaId {
+                    area: name,
+                    theme: state.main_area_id.theme.clone(),
+                },
+            )?;
+            state.dialogue = None;
+        }
+        Message::DuplicateAreaDialogue => {
+            state.dialogue = Some(Dialogue::DuplicateArea {
+                name: format!("{} copy", state.main_area_id.area),
+            });
+            return Ok(Some(iced::widget::text_input::focus("DuplicateArea")));
+        }
+        Message::SetDuplicateAreaName(new_name) => {
+            if let Some(Dialogue::DuplicateArea { name }) = &mut state.dialogue {
+                *name = new_name.clone();
+            }
+        }
+        Message::DuplicateArea { old_name, new_name } => {
+            if new_name.is_empty() {
+                warn!("Empty area name is invalid.");
+                return Ok(None);
+            }
+            if state.area_names.contains(new_name) {
+                // Don't add a non-unique area name.
+                warn!("Area name {} already exists.", new_name);
+                return Ok(None);
+            }
+            // Copy from the saved files, so make sure they are up-to-date first.
+            persist::save_project(state)?;
+            for theme in state.theme_names.clone() {
+                let mut area = load_area(
+                    state,
+                    &AreaId {
+                        area: old_name.clone(),
+                        theme: theme.clone(),
+                    },
+                )?;
+                area.name = new_name.clone();
+                area.modified = true;
+                let area_id = area.id();
+                state.areas.insert(area_id.clone(), area);
+                save_area(state, &area_id)?;
+            }
+            if let Some(group) = state.project_metadata.area_groups.get(old_name) {
+                let group = group.clone();
+                state
+                    .project_metadata
+                    .area_groups
+                    .insert(new_name.clone(), group);
+                state.project_metadata.modified = true;
+            }
+            state.area_names.push(new_name.clone());
+            state.area_names.sort();
+            state.switch_area(
+                AreaPosition::Main,
+                &AreaId {
+                    area: new_name.clone(),
+                    theme: state.main_area_id.theme.clone(),
+                },
+            )?;
+            state.dialogue = None;
+        }
+        Message::DeleteArea(name) => {
+            if state.area_names.len() == 1 {
+                warn!("Not allowed to delete the last remaining area.");
+                return Ok(None);
+            }
+            let theme = state.main_area().theme.clone();
+            delete_area(state, name)?;
+            load_area_list(state)?;
+            if state.project_metadata.area_groups.remove(name).is_some() {
+                state.project_metadata.modified = true;
+            }
+            if state.project_metadata.world_layout.remove(name).is_some() {
+                state.project_metadata.modified = true;
+            }
+            if &state.main_area_id.area == name {
+                state.switch_area(
+                    AreaPosition::Main,
+                    &AreaId {
+                        area: state.area_names[0].clone(),
+                        theme: theme.clone(),
+                    },
+                )?;
+            }
+            if &state.side_area_id.area == name {
+                state.switch_area(
+                    AreaPosition::Side,
+                    &AreaId {
+                        area: state.area_names[0].clone(),
+                        theme: theme.clone(),
+                    },
+                )?;
+            }
+            state.dialogue = None;
+        }
+        Message::RestoreArea {
+            areas,
+            group,
+            world_position,
+        } => {
+            let name = areas.first().context("internal error")?.name.clone();
+            if state.area_names.contains(&name) {
+                warn!("Area name {} already exists.", name);
+                return Ok(None);
+            }
+            for area in areas {
+                let mut area = area.clone();
+                area.modified = true;
+                let area_id = area.id();
+                state.areas.insert(area_id.clone(), area);
+                save_area(state, &area_id)?;
+            }
+            let metadata = &mut state.project_metadata;
+            if let Some(group) = group {
+                metadata.area_groups.insert(name.clone(), group.clone());
+                metadata.modified = true;
+            }
+            if let Some(p) = world_position {
+                metadata.world_layout.insert(name.clone(), *p);
+                metadata.modified = true;
+            }
+            state.area_names.push(name);
+            state.area_names.sort();
+            state.cleanup_areas()?;
+        }
+        Message::CompileCheckArea => {
+            let report = check_area(state, state.main_area());
+            info!(
+                "Compile check of {}/{}: {} tile16, {} tile32, {} palettes, {} chars, {} errors",
+                report.area_name,
+                report.theme,
+                report.tile16_count,
+                report.tile32_count,
+                report.palette_count,
+                report.char_count,
+                report.error_count
+            );
+            state.dialogue = Some(Dialogue::CompileCheck(report));
+        }
+        Message::CompressionEstimate => {
+            let theme = state.main_area_id.theme.clone();
+            let estimates = estimate_theme(state, &theme)?;
+            for e in estimates.iter().filter(|e| e.over_budget()) {
+                warn!(
+                    "Area {} ({}) may not fit: {} bytes compressed, budget {}",
+                    e.area_name,
+                    theme,
+                    e.compressed_size,
+                    e.budget()
+                );
+            }
+            state.dialogue = Some(Dialogue::CompressionEstimate(theme, estimates));
+        }
+        &Message::VramUsage(all_areas) => {
+            let screens = vram_usage(state, all_areas)?;
+            for s in screens.iter().filter(|s| !s.fits()) {
+                warn!(
+                    "Screen ({}, {}) of {} ({}) exceeds VRAM budgets: {}",
+                    s.screen.0,
+                    s.screen.1,
+                    s.area_id.area,
+                    s.area_id.theme,
+                    s.problems().join(", ")
+                );
+            }
+            state.dialogue = Some(Dialogue::VramUsage { all_areas, screens });
+        }
+        Message::GoToAreaScreen(area_id, (x, y)) => {
+            state.switch_area(AreaPosition::Main, area_id)?;
+            state.dialogue = None;
+            let screen_size = 256.0 * state.area_pixel_size(AreaPosition::Main);
+            let offset = AbsoluteOffset {
+                x: *x as f32 * screen_size,
+                y: *y as f32 * screen_size,
+            };
+            state.main_area_scroll = Vector::new(offset.x, offset.y);
+            return Ok(Some(scrollable::scroll_to(
+                area_scrollable_id(AreaPosition::Main),
+                offset,
+            )));
+        }
+        Message::AreaLabelsDialogue => {
+            state.dialogue = Some(Dialogue::AreaLabels(state.main_area_id.area.clone()));
+        }
+        Message::SetLabelPreviewText(new_text) => {
+            if let Some(Dialogue::AreaLabels(text)) = &mut state.dialogue {
+                *text = new_text.clone();
+            }
+        }
+        Message::SetLabelFont(font) => {
+            state.project_metadata.label_font = font.clone();
+            state.project_metadata.modified = true;
+        }
+        Message::SetLabelFontField(field) => {
+            let font = state
+                .project_metadata
+                .label_font
+                .as_mut()
+                .context("label font not set")?;
+            font.set(field);
+            state.project_metadata.modified = true;
+        }
+        Message::ExportAreaNameTable => {
+            let path = persist::save_area_name_table(state)?;
+            info!("Exported area name table to {}", path.display());
+        }
+        &Message::AreaPickerDialogue(position) => {
+            state.focus = Focus::PickArea(position);
+            state.dialogue = Some(Dialogue::AreaPicker(AreaPicker::load(state, position)?));
+            return Ok(Some(Task::batch([
+                widget::text_input::focus("AreaPicker"),
+                scroll_area_picker(state),
+            ])));
+        }
+        Message::SetAreaPickerQuery(query) => {
+            if let Some(Dialogue::AreaPicker(picker)) = &mut state.dialogue {
+                picker.set_query(query);
+            }
+            return Ok(Some(scroll_area_picker(state)));
+        }
+        Message::PickArea(name) => {
+            let Some(Dialogue::AreaPicker(picker)) = state.dialogue.take() else {
+                return Ok(None);
+            };
+            return Ok(Some(Task::done(Message::SelectArea(
+                picker.position,
+                name.clone(),
+            ))));
+        }
+        Message::PickSelectedArea => {
+            let Some(Dialogue::AreaPicker(picker)) = &state.dialogue else {
+                return Ok(None);
+            };
+            let Some(entry) = picker.selected_entry() else {
+                return Ok(None);
+            };
+            return Ok(Some(Task::done(Message::PickArea(entry.name.clone()))));
+        }
+        &Message::AreaListDialogue(position) => {
+            state.dragging_area = None;
+            state.dialogue = Some(Dialogue::AreaList(position, "".to_string()));
+        }
+        Message::SetNewAreaGroupName(new_name) => {
+            if let Some(Dialogue::AreaList(_, name)) = &mut state.dialogue {
+                *name = new_name.clone();
+            }
         }
-        &Message::EditAreaBGBlue(c) => {
-            let mut color = state.main_area().bg_color;
-            color[2] = c;
-            return Ok(Some(Task::done(Message::EditAreaBGColor {
-                area_id: state.area_id(AreaPosition::Main).clone(),
-                color,
-            })));
+        Message::ToggleAreaGroup(group) => {
+            if !state.collapsed_area_groups.remove(group) {
+                state.collapsed_area_groups.insert(group.clone());
+            }
         }
-        &Message::EditAreaBGColor { ref area_id, color } => {
-            state.switch_area(AreaPosition::Main, area_id)?;
-            state.main_area_mut().bg_color = color;
+        Message::DragArea(name) => {
+            state.dragging_area = Some(name.clone());
         }
-        Message::DeleteAreaDialogue => {
-            state.dialogue = Some(Dialogue::DeleteArea);
+        Message::ReleaseOnArea(name) => {
+            let Some(dragged_name) = state.dragging_area.take() else {
+                return Ok(None);
+            };
+            if &dragged_name == name {
+                // Pressing and releasing on the same area is a click, which selects it.
+                let Some(Dialogue::AreaList(position, _)) = state.dialogue else {
+                    return Ok(None);
+                };
+                state.dialogue = None;
+                return Ok(Some(Task::done(Message::SelectArea(
+                    position,
+                    name.clone(),
+                ))));
+            }
+            // Dropping an area onto another area moves it into that area's group.
+            let group = state.project_metadata.area_groups.get(name).cloned();
+            return Ok(Some(Task::done(Message::SetAreaGroup {
+                area: dragged_name,
+                group,
+            })));
         }
-        Message::DeleteArea(name) => {
-            if state.area_names.len() == 1 {
-                warn!("Not allowed to delete the last remaining area.");
+        Message::ReleaseOnAreaGroup(group) => {
+            let Some(dragged_name) = state.dragging_area.take() else {
+                return Ok(None);
+            };
+            let group = if group == UNGROUPED_AREA_GROUP {
+                None
+            } else {
+                Some(group.clone())
+            };
+            return Ok(Some(Task::done(Message::SetAreaGroup {
+                area: dragged_name,
+                group,
+            })));
+        }
+        Message::SetAreaGroup { area, group } => {
+            if !state.area_names.contains(area) {
+                warn!("Unknown area {}.", area);
                 return Ok(None);
             }
-            let theme = state.main_area().theme.clone();
-            delete_area(state, name)?;
-            load_area_list(state)?;
-            if &state.main_area_id.area == name {
-                state.switch_area(
-                    AreaPosition::Main,
-                    &AreaId {
-                        area: state.area_names[0].clone(),
-                        theme: theme.clone(),
-                    },
-                )?;
+            let metadata = &mut state.project_metadata;
+            match group {
+                Some(g) if !g.is_empty() && g != UNGROUPED_AREA_GROUP => {
+                    metadata.area_groups.insert(area.clone(), g.clone());
+                }
+                _ => {
+                    metadata.area_groups.remove(area);
+                }
             }
-            if &state.side_area_id.area == name {
-                state.switch_area(
-                    AreaPosition::Side,
-                    &AreaId {
-                        area: state.area_names[0].clone(),
-                        theme: theme.clone(),
-                    },
-                )?;
+            metadata.modified = true;
+            if let Some(Dialogue::AreaList(_, name)) = &mut state.dialogue {
+                name.clear();
             }
-            state.dialogue = None;
+        }
+        Message::OpenWorldMap(theme) => {
+            state.world_map = Some(WorldMap::load(state, theme)?);
+        }
+        Message::CloseWorldMap => {
+            state.world_map = None;
+        }
+        Message::SetWorldMapPosition { area, position } => {
+            let layout = &mut state.project_metadata.world_layout;
+            match position {
+                Some(p) => {
+                    layout.insert(area.clone(), *p);
+                }
+                None => {
+                    layout.remove(area);
+                }
+            }
+            state.project_metadata.modified = true;
+        }
+        Message::OpenWorldMapArea(area) => {
+            let Some(world_map) = state.world_map.take() else {
+                return Ok(None);
+            };
+            state.switch_area(
+                AreaPosition::Main,
+                &AreaId {
+                    area: area.clone(),
+                    theme: world_map.theme,
+                },
+            )?;
         }
         &Message::SelectTheme(position, ref theme) => {
             state.switch_area(
@@ -1104,6 +4271,13 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
             state.theme_names.sort();
             state.dialogue = None;
         }
+        Message::DuplicateThemeDialogue => {
+            // Adding a theme copies the current theme of each area.
+            state.dialogue = Some(Dialogue::AddTheme {
+                name: format!("{} copy", state.main_area().theme),
+            });
+            return Ok(Some(iced::widget::text_input::focus("AddTheme")));
+        }
         Message::RenameThemeDialogue => {
             state.dialogue = Some(Dialogue::RenameTheme {
                 name: state.main_area().theme.clone(),
@@ -1128,8 +4302,17 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
                     return Ok(None);
                 }
             }
-            for area_name in &state.area_names.clone() {
-                rename_area_theme(state, area_name, old_name, new_name)?;
+            let area_names = state.area_names.clone();
+            for (i, area_name) in area_names.iter().enumerate() {
+                if let Err(e) = rename_area_theme(state, area_name, old_name, new_name) {
+                    // Put back the areas already renamed, so the theme isn't left split:
+                    for a in &area_names[..i] {
+                        rename_area_theme(state, a, new_name, old_name)?;
+                    }
+                    error!("Error renaming theme {}: {:#}", old_name, e);
+                    state.dialogue = Some(Dialogue::Error(format!("{:#}", e)));
+                    return Ok(None);
+                }
             }
             load_area_list(state)?;
             if &state.main_area_id.theme == old_name {
@@ -1150,7 +4333,15 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
                     },
                 )?;
             }
+            if let Some((a, b)) = &mut state.theme_sync {
+                for t in [a, b] {
+                    if t == old_name {
+                        *t = new_name.clone();
+                    }
+                }
+            }
             state.dialogue = None;
+            record_rename(state, RenameKind::Theme, old_name, new_name)?;
         }
         Message::DeleteThemeDialogue => {
             state.dialogue = Some(Dialogue::DeleteTheme);
@@ -1165,6 +4356,11 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
                 delete_area_theme(state, area_name, theme_name)?;
             }
             load_area_list(state)?;
+            if let Some((a, b)) = &state.theme_sync {
+                if a == theme_name || b == theme_name {
+                    state.theme_sync = None;
+                }
+            }
             if &state.main_area_id.theme == theme_name {
                 state.switch_area(
                     AreaPosition::Main,
@@ -1185,6 +4381,25 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
             }
             state.dialogue = None;
         }
+        Message::RestoreTheme { theme, areas } => {
+            if state.theme_names.contains(theme) {
+                warn!("Theme name {} already exists.", theme);
+                return Ok(None);
+            }
+            for area in areas {
+                let mut area = area.clone();
+                area.modified = true;
+                let area_id = area.id();
+                state.areas.insert(area_id.clone(), area);
+                save_area(state, &area_id)?;
+            }
+            state.theme_names.push(theme.clone());
+            state.theme_names.sort();
+            state.cleanup_areas()?;
+        }
+        Message::RestoreSnapshot(snapshot) => {
+            restore_snapshot(state, snapshot)?;
+        }
         &Message::StartTileSelection(p, source) => {
             state.selection_source = source;
             state.start_coords = Some((p.x, p.y));
@@ -1194,6 +4409,7 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
             state.end_coords = Some((p.x, p.y));
         }
         Message::EndTileSelection(p1) => {
+            state.auto_pan = None;
             let p1 = (p1.x, p1.y);
             let Some(p0) = state.start_coords else {
                 return Ok(None);
@@ -1243,25 +4459,11 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
                 palettes,
                 tiles,
                 flips,
+                mask: None,
             };
+            update_selected_gfx(state);
             let s = &state.selected_tile_block;
 
-            state.selected_gfx.clear();
-            for y in 0..s.size.1 {
-                let mut gfx_row: Vec<Tile> = vec![];
-                for x in 0..s.size.0 {
-                    let palette_id = s.palettes[y as usize][x as usize];
-                    let tile_idx = s.tiles[y as usize][x as usize];
-                    let tile = if let Some(&idx) = state.palettes_id_idx_map.get(&palette_id) {
-                        state.palettes[idx as usize].tiles[tile_idx as usize]
-                    } else {
-                        Tile::default()
-                    };
-                    gfx_row.push(tile);
-                }
-                state.selected_gfx.push(gfx_row);
-            }
-
             state.start_coords = None;
             state.end_coords = None;
             if left == right && top == bottom {
@@ -1286,6 +4488,9 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
             let area = state.area_mut(position);
             for y in 0..s.size.1 {
                 for x in 0..s.size.0 {
+                    if !s.covers(x as usize, y as usize) {
+                        continue;
+                    }
                     let _ = area.set_palette(p.x + x, p.y + y, s.palettes[y as usize][x as usize]);
                     if !palette_only {
                         let _ = area.set_tile(p.x + x, p.y + y, s.tiles[y as usize][x as usize]);
@@ -1295,6 +4500,79 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
             }
             area.modified = true;
         }
+        &Message::AreaShape {
+            position,
+            ref area_id,
+            shape,
+            ref selection,
+            palette_only,
+        } => {
+            state.switch_area(position, area_id)?;
+            let area = state.area_mut(position);
+            for cell in shape_cells(area, shape, selection, palette_only) {
+                cell.set(area)?;
+            }
+            area.modified = true;
+        }
+        &Message::SetAreaCells {
+            position,
+            ref area_id,
+            ref cells,
+        } => {
+            state.switch_area(position, area_id)?;
+            let area = state.area_mut(position);
+            for cell in cells {
+                cell.set(area)?;
+            }
+            area.modified = true;
+        }
+        &Message::SetOverlayCells {
+            position,
+            ref area_id,
+            ref cells,
+        } => {
+            state.switch_area(position, area_id)?;
+            let area = state.area_mut(position);
+            for cell in cells {
+                cell.set(area)?;
+            }
+            area.modified = true;
+        }
+        Message::SetThemeSync(theme) => {
+            let main_theme = &state.main_area_id.theme;
+            state.theme_sync = theme
+                .as_ref()
+                .filter(|&t| t != main_theme)
+                .map(|t| (main_theme.clone(), t.clone()));
+        }
+        Message::MirrorAreaCells { area_id, cells } => {
+            modify_area(state, area_id, |area| {
+                for cell in cells {
+                    cell.set(area)?;
+                }
+                Ok(true)
+            })?;
+        }
+        &Message::SelectRegion(position, p) => {
+            let area = state.area(position);
+            let block = region_block(area, &fill_region(area, p, false))?;
+            if block.size == (0, 0) {
+                return Ok(None);
+            }
+            state.selected_tile_block = block;
+            update_selected_gfx(state);
+            state.selection_source = SelectionSource::Area(position);
+            state.focus = Focus::Area(position);
+            state.tile_idx = None;
+        }
+        &Message::ToggleBrushMask(x, y) => {
+            state
+                .selected_tile_block
+                .toggle_mask(x as usize, y as usize);
+        }
+        Message::ClearBrushMask => {
+            state.selected_tile_block.mask = None;
+        }
         &Message::OpenTile {
             palette_id,
             tile_idx,
@@ -1304,37 +4582,165 @@ pub fn try_update(state: &mut EditorState, message: &Message) -> Result<Option<T
                 state.tile_idx = Some(tile_idx);
             }
         }
+        &Message::OpenTileGraphics {
+            palette_id,
+            tile_idx,
+        } => {
+            let palette_idx = *state
+                .palettes_id_idx_map
+                .get(&palette_id)
+                .context("palette not found")?;
+            if tile_idx as usize >= state.palettes[palette_idx].tiles.len() {
+                warn!("Tile index {} out of range.", tile_idx);
+                return Ok(None);
+            }
+            state.palette_idx = palette_idx;
+            state.tile_idx = Some(tile_idx);
+            state.start_coords = None;
+            state.end_coords = None;
+            state.side_panel_view = SidePanelView::Tileset;
+            state.side_panel_hidden = false;
+            state.pixel_coords.get_or_insert((0, 0));
+            state.focus = Focus::GraphicsPixel;
+        }
     }
     Ok(Some(Task::none()))
 }
 
+// Updates taking longer than this freeze the UI noticeably.
+// Number of tiles shown above and to the left of a tile usage when jumping to it:
+const USAGE_SCROLL_MARGIN: f32 = 8.0;
+// Zoom factor of an area view per step of the mouse wheel (with Ctrl held):
+const WHEEL_ZOOM_FACTOR: f32 = 1.15;
+const SLOW_UPDATE_THRESHOLD: Duration = Duration::from_millis(500);
+const UPDATE_TIMINGS_SIZE: usize = 20;
+
+// Scroll the main area to show the tile near the top-left corner of the view, with a few tiles of
+// its surroundings.
+fn scroll_to_tile(state: &mut EditorState, x: TileCoord, y: TileCoord) -> Task<Message> {
+    let pixel_size = state.area_pixel_size(AreaPosition::Main);
+    let offset =
+        |coord: TileCoord| ((coord as f32 - USAGE_SCROLL_MARGIN) * 8.0 * pixel_size).max(0.0);
+    let offset = AbsoluteOffset {
+        x: offset(x),
+        y: offset(y),
+    };
+    state.main_area_scroll = Vector::new(offset.x, offset.y);
+    scrollable::scroll_to(area_scrollable_id(AreaPosition::Main), offset)
+}
+
+fn message_name(message: &Message) -> String {
+    let s = format!("{:?}", message);
+    let end = s
+        .find(|c: char| !c.is_alphanumeric() && c != '_')
+        .unwrap_or(s.len());
+    s[..end].to_string()
+}
+
+fn record_update_timing(state: &mut EditorState, message: &Message, duration: Duration) {
+    if duration >= SLOW_UPDATE_THRESHOLD {
+        let name = message_name(message);
+        warn!(
+            "Slow update handler: {} took {:.2}s",
+            name,
+            duration.as_secs_f32()
+        );
+        state.slow_update = Some(UpdateTiming { name, duration });
+    }
+    if state.show_update_timings {
+        if let Message::Event(_) = message {
+            // Raw events are frequent and cheap; skip them so they don't crowd out the rest.
+            return;
+        }
+        state.update_timings.push_front(UpdateTiming {
+            name: message_name(message),
+            duration,
+        });
+        state.update_timings.truncate(UPDATE_TIMINGS_SIZE);
+    }
+}
+
+fn record_macro_steps(recorded: &mut Vec<Message>, message: &Message) {
+    if let Message::Batch(messages) = message {
+        for m in messages {
+            record_macro_steps(recorded, m);
+        }
+    } else if is_recordable(message) {
+        recorded.push(message.clone());
+    }
+}
+
+enum HistoryStep {
+    Undo,
+    Redo,
+}
+
 pub fn update(state: &mut EditorState, mut message: Message) -> Task<Message> {
+    if state.global_config.record_session {
+        record_message(state, &message);
+    }
     // Handle undo/redo controls:
     let mut undo = false;
-    match &message {
+    let mut step = match &message {
         Message::Event(Event::Keyboard(keyboard::Event::KeyPressed {
             key: keyboard::Key::Character(c),
             modifiers,
             ..
-        })) if modifiers.control() && c == "z" => {
-            if modifiers.shift() {
-                // Redo:
-                if let Some((msg, rev_msg)) = state.redo_stack.pop() {
-                    state.undo_stack.push((msg.clone(), rev_msg));
-                    message = msg;
-                    undo = true;
-                }
-            } else {
-                // Undo:
-                if let Some((msg, rev_msg)) = state.undo_stack.pop() {
-                    state.redo_stack.push((msg, rev_msg.clone()));
-                    message = rev_msg;
-                    undo = true;
-                }
+        })) if modifiers.control() && c == "z" => Some(if modifiers.shift() {
+            HistoryStep::Redo
+        } else {
+            HistoryStep::Undo
+        }),
+        Message::Undo => Some(HistoryStep::Undo),
+        Message::Redo => Some(HistoryStep::Redo),
+        _ => None,
+    };
+    if state.undo_preview {
+        match step {
+            // The previewed undo is already applied, so keep it:
+            Some(HistoryStep::Undo) => {
+                state.undo_preview = false;
+                return Task::none();
             }
+            // Redoing ends the preview, restoring the change:
+            Some(HistoryStep::Redo) => state.undo_preview = false,
+            None => {}
+        }
+    }
+    match message {
+        Message::PreviewUndo(true) if !state.undo_preview && !state.undo_stack.is_empty() => {
+            state.undo_preview = true;
+            step = Some(HistoryStep::Undo);
+        }
+        Message::PreviewUndo(false) if state.undo_preview => {
+            state.undo_preview = false;
+            step = Some(HistoryStep::Redo);
         }
+        Message::PreviewUndo(_) => return Task::none(),
         _ => {}
     }
+    match step {
+        Some(HistoryStep::Redo) => {
+            if let Some((msg, rev_msg)) = state.redo_stack.pop() {
+                state.undo_stack.push((msg.clone(), rev_msg));
+                message = msg;
+                undo = true;
+            }
+        }
+        Some(HistoryStep::Undo) => {
+            if let Some((msg, rev_msg)) = state.undo_stack.pop() {
+                state.redo_stack.push((msg, rev_msg.clone()));
+                message = rev_msg;
+                undo = true;
+            }
+        }
+        None => {}
+    }
+
+    if !undo {
+        message = layer_message(state, message);
+        message = synced_message(state, message);
+    }
 
     if let Some((last_message, _)) = state.undo_stack.last() {
         if !undo && should_debounce(&message, last_message) {
@@ -1355,7 +4761,11 @@ pub fn update(state: &mut EditorState, mut message: Message) -> Task<Message> {
         }
     };
 
-    match try_update(state, &message) {
+    let start_time = Instant::now();
+    let result = try_update(state, &message);
+    record_update_timing(state, &message, start_time.elapsed());
+
+    match result {
         Ok(Some(t)) => {
             // The update was successful, so update the undo stack if applicable:
             match undo_action {
@@ -1363,10 +4773,16 @@ pub fn update(state: &mut EditorState, mut message: Message) -> Task<Message> {
                 UndoAction::Irreversible => {
                     state.undo_stack.clear();
                     state.redo_stack.clear();
+                    state.undo_preview = false;
                 }
                 UndoAction::Ok(reverse_message) => {
+                    if let Some(recorded) = &mut state.macro_recording {
+                        record_macro_steps(recorded, &message);
+                    }
+                    // A new change during a preview commits the previewed undo:
                     state.undo_stack.push((message, reverse_message));
                     state.redo_stack.clear();
+                    state.undo_preview = false;
                 }
             }
             t
@@ -1380,17 +4796,268 @@ pub fn update(state: &mut EditorState, mut message: Message) -> Task<Message> {
             // The update failed for an abnormal reason, so skip pushing
             // onto the undo stack, and log the error and backtrace:
             error!("Error processing {:?}: {}\n{}", message, e, e.backtrace());
-
-            // Make sure file watcher is re-enabled, since an error could easily
-            // have occurred between disabling and re-enabling:
-            if let Err(e) = state.enable_watch_file_changes() {
-                error!("Error re-enabling watcher: {}\n{}", e, e.backtrace());
-            }
             return Task::none();
         }
     }
 }
 
+// Whether the palette's tiles can't be edited because of its category (in which case an error
+// is shown).
+fn palette_tiles_locked(state: &mut EditorState, pal_idx: usize) -> bool {
+    let pal = &state.palettes[pal_idx];
+    if pal.category.tiles_editable() {
+        return false;
+    }
+    state.dialogue = Some(Dialogue::Error(format!(
+        "{} is a {} palette, shared by all maps, so its tiles can't be edited",
+        pal.name, pal.category
+    )));
+    true
+}
+
+// Set the global zoom level, which the area views then both follow.
+fn set_global_pixel_size(state: &mut EditorState, pixel_size: f32) {
+    state
+        .global_config
+        .set_pixel_size(pixel_size, state.scale_factor);
+    state.main_area_zoom = None;
+    state.side_area_zoom = None;
+}
+
+// Scroll offset for a linked view to show the same part of the area as the other one, which
+// can be zoomed differently.
+fn linked_offset(
+    state: &EditorState,
+    from: AreaPosition,
+    to: AreaPosition,
+    offset: Vector,
+) -> Vector {
+    let (from_size, to_size) = (state.area_pixel_size(from), state.area_pixel_size(to));
+    if from_size == to_size {
+        return offset;
+    }
+    offset * (to_size / from_size)
+}
+
+// Apply a change to an area, whether or not it is currently loaded. The closure
+// returns whether the area was changed.
+// Scroll an area view (without the other one following it), e.g. to follow the other one.
+fn scroll_area_to(
+    state: &mut EditorState,
+    position: AreaPosition,
+    offset: Vector,
+) -> Task<Message> {
+    state.set_area_scroll(position, offset);
+    state.linked_scroll_pending = Some(position);
+    scrollable::scroll_to(
+        area_scrollable_id(position),
+        AbsoluteOffset {
+            x: offset.x,
+            y: offset.y,
+        },
+    )
+}
+
+// The BG color of each area (in the order of `area_names`) in each theme.
+// Add a line to the tool console, dropping the oldest once it's full.
+fn push_tool_output(state: &mut EditorState, line: String) {
+    state.tool_console.push(line);
+    if state.tool_console.len() > MAX_CONSOLE_LINES {
+        let excess = state.tool_console.len() - MAX_CONSOLE_LINES;
+        state.tool_console.drain(..excess);
+    }
+}
+
+fn bg_color_table(state: &EditorState) -> Result<Vec<Vec<ColorRGB>>> {
+    let mut table = vec![];
+    for area in &state.area_names {
+        let mut row = vec![];
+        for theme in &state.theme_names {
+            let area_id = AreaId {
+                area: area.clone(),
+                theme: theme.clone(),
+            };
+            row.push(state.area_copy(&area_id)?.bg_color);
+        }
+        table.push(row);
+    }
+    Ok(table)
+}
+
+fn modify_area(
+    state: &mut EditorState,
+    area_id: &AreaId,
+    f: impl FnOnce(&mut Area) -> Result<bool>,
+) -> Result<()> {
+    if let Some(area) = state.areas.get_mut(area_id) {
+        if f(area)? {
+            area.modified = true;
+        }
+        return Ok(());
+    }
+    let mut area = load_area(state, area_id)?;
+    if f(&mut area)? {
+        area.modified = true;
+        state.areas.insert(area_id.clone(), area);
+        save_area(state, area_id)?;
+        state.areas.remove(area_id);
+    }
+    Ok(())
+}
+
+// Apply the selected color to the selected palette color, if any.
+fn set_selected_color(state: &EditorState) -> Option<Task<Message>> {
+    let color_idx = state.color_idx?;
+    Some(Task::done(Message::BrushColor {
+        palette_id: state.palettes[state.palette_idx].id,
+        color_idx,
+        color: state.selected_color,
+    }))
+}
+
+// Update the graphics of the brush, to match the selected tile block.
+fn update_selected_gfx(state: &mut EditorState) {
+    let s = &state.selected_tile_block;
+    state.selected_gfx.clear();
+    for y in 0..s.size.1 {
+        let mut gfx_row: Vec<Tile> = vec![];
+        for x in 0..s.size.0 {
+            let palette_id = s.palettes[y as usize][x as usize];
+            let tile_idx = s.tiles[y as usize][x as usize];
+            // Metatiles can refer to tiles that have since been removed:
+            let tile = state
+                .palettes_id_idx_map
+                .get(&palette_id)
+                .and_then(|&idx| state.palettes[idx].tiles.get(tile_idx as usize))
+                .copied()
+                .unwrap_or_default();
+            gfx_row.push(tile);
+        }
+        state.selected_gfx.push(gfx_row);
+    }
+}
+
+// Reload the assignment shown in the palette slots dialogue, if open, after it's changed.
+fn set_tutorial_step(state: &mut EditorState, step: usize) {
+    let Some(s) = TUTORIAL_STEPS.get(step) else {
+        return;
+    };
+    state.tutorial_step = Some(step);
+    // Make sure the part of the editor that the step is about is showing:
+    if s.target.in_side_panel() {
+        state.side_panel_hidden = false;
+        state.side_panel_view = SidePanelView::Tileset;
+    }
+    if s.target == TutorialTarget::AreaCanvas {
+        state.world_map = None;
+    }
+}
+
+fn palette_colors(state: &EditorState, palette_id: PaletteId) -> Result<PaletteColors> {
+    let idx = *state
+        .palettes_id_idx_map
+        .get(&palette_id)
+        .context("palette not found")?;
+    Ok(state.palettes[idx].colors)
+}
+
+fn set_palette_colors(
+    state: &mut EditorState,
+    palette_id: PaletteId,
+    colors: PaletteColors,
+) -> Result<()> {
+    let idx = *state
+        .palettes_id_idx_map
+        .get(&palette_id)
+        .context("palette not found")?;
+    state.palettes[idx].colors = colors;
+    state.palettes[idx].modified = true;
+    if idx == state.palette_idx {
+        if let Some(color_idx) = state.color_idx {
+            state.selected_color = colors[color_idx as usize];
+        }
+    }
+    Ok(())
+}
+
+fn refresh_palette_slots(state: &mut EditorState) -> Result<()> {
+    let Some(Dialogue::PaletteSlots { theme, .. }) = &state.dialogue else {
+        return Ok(());
+    };
+    let new_assignment = load_assignment(state, theme)?;
+    if let Some(Dialogue::PaletteSlots { assignment, .. }) = &mut state.dialogue {
+        *assignment = new_assignment;
+    }
+    Ok(())
+}
+
+// Put the project back as it was when the snapshot was taken, removing any palettes and areas
+// that have been added since.
+pub fn restore_snapshot(state: &mut EditorState, snapshot: &ProjectSnapshot) -> Result<()> {
+    for pal in state.palettes.clone() {
+        if !snapshot.palettes.iter().any(|p| p.name == pal.name) {
+            delete_palette(state, &pal.name)?;
+        }
+    }
+    state.palettes = snapshot.palettes.clone();
+    for pal in &mut state.palettes {
+        pal.modified = true;
+    }
+    state.palette_idx = 0;
+    state.tile_idx = None;
+    state.color_idx = None;
+    update_palette_order(state);
+
+    for name in state.area_names.clone() {
+        if !snapshot.areas.iter().any(|a| a.name == name) {
+            delete_area(state, &name)?;
+        }
+    }
+    for area in &snapshot.areas {
+        let mut area = area.clone();
+        area.modified = true;
+        let area_id = area.id();
+        state.areas.insert(area_id.clone(), area);
+        save_area(state, &area_id)?;
+    }
+    state.project_metadata = snapshot.metadata.clone();
+    state.project_metadata.modified = true;
+    persist::save_project(state)?;
+    load_area_list(state)?;
+
+    for position in [AreaPosition::Main, AreaPosition::Side] {
+        let area_id = state.area_id(position).clone();
+        if !state.area_names.contains(&area_id.area) {
+            let area_id = AreaId {
+                area: state.area_names[0].clone(),
+                theme: area_id.theme,
+            };
+            state.switch_area(position, &area_id)?;
+        }
+    }
+    state.cleanup_areas()?;
+    Ok(())
+}
+
+// Open the library dialogue, or refresh its list of items if it's already open.
+fn show_library(state: &mut EditorState) -> Result<()> {
+    let items = load_library(state)?;
+    match &mut state.dialogue {
+        Some(Dialogue::Library {
+            items: old_items, ..
+        }) => *old_items = items,
+        _ => {
+            state.dialogue = Some(Dialogue::Library {
+                items,
+                filter: String::new(),
+                name: state.palettes[state.palette_idx].name.clone(),
+                tags: String::new(),
+            });
+        }
+    }
+    Ok(())
+}
+
 pub fn update_palette_order(state: &mut EditorState) {
     let id = state.palettes[state.palette_idx].id;
     state.palettes.sort_by(|x, y| x.id.cmp(&y.id));
@@ -0,0 +1,106 @@
+// Search for the places where a tile is used, across all areas and themes of the project, for
+// browsing them in the side panel. The areas are read from disk on a background thread, so that
+// the editor stays responsive in large projects. Areas loaded in the editor are searched as they
+// are in memory instead, since they may have unsaved changes.
+use std::path::PathBuf;
+
+use anyhow::Result;
+use iced::{futures::channel::oneshot, Task};
+use log::warn;
+
+use crate::{
+    message::Message,
+    persist::{area_json_path, load_json},
+    state::{Area, AreaId, EditorState, PaletteId, TileIdx, TileUsage},
+};
+
+// A search, with its results once it has finished.
+#[derive(Clone, Debug)]
+pub struct UsageSearch {
+    pub palette_id: PaletteId,
+    pub tile_idx: TileIdx,
+    pub usages: Option<Vec<TileUsage>>,
+}
+
+enum AreaSource {
+    Loaded(Area),
+    File(PathBuf),
+}
+
+// The areas to search, gathered from the editor state before the search starts.
+pub struct UsageScan {
+    palette_id: PaletteId,
+    tile_idx: TileIdx,
+    areas: Vec<(AreaId, AreaSource)>,
+}
+
+impl UsageScan {
+    pub fn new(state: &EditorState, palette_id: PaletteId, tile_idx: TileIdx) -> Result<Self> {
+        let mut areas = vec![];
+        for theme in &state.theme_names {
+            for area_name in &state.area_names {
+                let area_id = AreaId {
+                    area: area_name.clone(),
+                    theme: theme.clone(),
+                };
+                let source = match state.areas.get(&area_id) {
+                    Some(area) => AreaSource::Loaded(area.clone()),
+                    None => AreaSource::File(area_json_path(state, &area_id)?),
+                };
+                areas.push((area_id, source));
+            }
+        }
+        Ok(UsageScan {
+            palette_id,
+            tile_idx,
+            areas,
+        })
+    }
+
+    // Search the areas, skipping (with a warning) any that can't be read.
+    pub fn run(self) -> Vec<TileUsage> {
+        let mut out = vec![];
+        for (area_id, source) in self.areas {
+            let area = match source {
+                AreaSource::Loaded(area) => area,
+                AreaSource::File(path) => match load_json::<Area>(&path) {
+                    Ok(mut area) => {
+                        area.name = area_id.area.clone();
+                        area.theme = area_id.theme.clone();
+                        area
+                    }
+                    Err(e) => {
+                        warn!("Skipping area {} in search: {:#}", path.display(), e);
+                        continue;
+                    }
+                },
+            };
+            match area.tile_usages(self.palette_id, self.tile_idx) {
+                Ok(usages) => out.extend(usages),
+                Err(e) => warn!(
+                    "Skipping area {}/{} in search: {:#}",
+                    area_id.area, area_id.theme, e
+                ),
+            }
+        }
+        out
+    }
+
+    // Run the search on a background thread, reporting the results with
+    // `Message::TileUsagesFound`.
+    pub fn spawn(self) -> Task<Message> {
+        let (palette_id, tile_idx) = (self.palette_id, self.tile_idx);
+        let (sender, receiver) = oneshot::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(self.run());
+        });
+        Task::perform(
+            async move { receiver.await.unwrap_or_default() },
+            move |usages| Message::TileUsagesFound {
+                palette_id,
+                tile_idx,
+                usages,
+            },
+        )
+    }
+}
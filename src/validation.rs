@@ -0,0 +1,250 @@
+// Validation of the whole project: scan all palettes and areas (of every theme) for broken
+// references that would otherwise only show up as blank tiles, e.g. tiles of palettes that don't
+// exist, tile indexes beyond the end of their palette, flips of tiles that aren't flippable, or a
+// list of screens that doesn't match the area's size. The issues are listed in the side panel,
+// where each can be clicked to jump to it. As with tile usage searches, areas are read on a
+// background thread, using the in-memory version of areas that are loaded.
+use std::path::PathBuf;
+
+use anyhow::Result;
+use hashbrown::HashMap;
+use iced::{futures::channel::oneshot, Task};
+use itertools::Itertools;
+
+use crate::{
+    message::Message,
+    persist::{area_json_path, load_json},
+    state::{Area, AreaId, EditorState, Flip, OverlayTile, PaletteId, TileCoord, TileIdx},
+};
+
+// Number of tile issues listed for each area (the rest are only counted):
+pub const MAX_LISTED_PER_AREA: usize = 100;
+
+// Where an issue is, for jumping to it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IssueLocation {
+    Palette(PaletteId),
+    Area(AreaId),
+    Tile {
+        area_id: AreaId,
+        x: TileCoord,
+        y: TileCoord,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProjectIssue {
+    pub location: IssueLocation,
+    pub description: String,
+}
+
+enum AreaSource {
+    Loaded(Area),
+    File(PathBuf),
+}
+
+// What's known about a palette's tiles: whether each can be flipped horizontally and vertically
+// (editor-only tiles are exported blank, so they can be flipped either way).
+type PaletteFlips = HashMap<PaletteId, Vec<(bool, bool)>>;
+
+// The palettes and areas to check, gathered from the editor state before the scan starts.
+pub struct ValidationScan {
+    palette_flips: PaletteFlips,
+    palette_issues: Vec<ProjectIssue>,
+    areas: Vec<(AreaId, AreaSource)>,
+}
+
+impl ValidationScan {
+    pub fn new(state: &EditorState) -> Result<Self> {
+        let mut palette_flips = PaletteFlips::new();
+        let mut palette_issues = vec![];
+        for palette in &state.palettes {
+            let flips = palette
+                .tiles
+                .iter()
+                .map(|t| {
+                    (
+                        t.h_flippable || t.editor_only,
+                        t.v_flippable || t.editor_only,
+                    )
+                })
+                .collect();
+            if palette_flips.insert(palette.id, flips).is_some() {
+                palette_issues.push(ProjectIssue {
+                    location: IssueLocation::Palette(palette.id),
+                    description: format!(
+                        "Palette {} has the same ID ({}) as another palette",
+                        palette.name, palette.id
+                    ),
+                });
+            }
+            for problem in palette.category_problems() {
+                palette_issues.push(ProjectIssue {
+                    location: IssueLocation::Palette(palette.id),
+                    description: format!("Palette {}: {}", palette.name, problem),
+                });
+            }
+        }
+        let mut areas = vec![];
+        for theme in &state.theme_names {
+            for area_name in &state.area_names {
+                let area_id = AreaId {
+                    area: area_name.clone(),
+                    theme: theme.clone(),
+                };
+                let source = match state.areas.get(&area_id) {
+                    Some(area) => AreaSource::Loaded(area.clone()),
+                    None => AreaSource::File(area_json_path(state, &area_id)?),
+                };
+                areas.push((area_id, source));
+            }
+        }
+        Ok(ValidationScan {
+            palette_flips,
+            palette_issues,
+            areas,
+        })
+    }
+
+    pub fn run(self) -> Vec<ProjectIssue> {
+        let mut out = self.palette_issues;
+        for (area_id, source) in self.areas {
+            let area = match source {
+                AreaSource::Loaded(area) => area,
+                AreaSource::File(path) => match load_json::<Area>(&path) {
+                    Ok(area) => area,
+                    Err(e) => {
+                        out.push(ProjectIssue {
+                            location: IssueLocation::Area(area_id),
+                            description: format!("Unable to read {}: {:#}", path.display(), e),
+                        });
+                        continue;
+                    }
+                },
+            };
+            out.extend(check_area(&self.palette_flips, &area_id, &area));
+        }
+        out
+    }
+
+    // Run the scan on a background thread, reporting the issues with
+    // `Message::ValidationFinished`.
+    pub fn spawn(self) -> Task<Message> {
+        let (sender, receiver) = oneshot::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(self.run());
+        });
+        Task::perform(
+            async move { receiver.await.unwrap_or_default() },
+            Message::ValidationFinished,
+        )
+    }
+}
+
+// Problem with a tile reference, if any.
+fn tile_problem(
+    palette_flips: &PaletteFlips,
+    palette_id: PaletteId,
+    tile_idx: TileIdx,
+    flip: Flip,
+) -> Option<String> {
+    let Some(flips) = palette_flips.get(&palette_id) else {
+        return Some(format!("palette {} does not exist", palette_id));
+    };
+    let Some(&(h_flippable, v_flippable)) = flips.get(tile_idx as usize) else {
+        return Some(format!(
+            "tile {} is beyond the end of palette {} ({} tiles)",
+            tile_idx,
+            palette_id,
+            flips.len()
+        ));
+    };
+    let legal = match flip {
+        Flip::None => true,
+        Flip::Horizontal => h_flippable,
+        Flip::Vertical => v_flippable,
+        Flip::Both => h_flippable && v_flippable,
+    };
+    (!legal).then(|| {
+        format!(
+            "tile {} of palette {} can't be flipped {:?}",
+            tile_idx, palette_id, flip
+        )
+    })
+}
+
+fn check_area(palette_flips: &PaletteFlips, area_id: &AreaId, area: &Area) -> Vec<ProjectIssue> {
+    let mut out = vec![];
+    let expected = area.size.0 as usize * area.size.1 as usize;
+    if area.screens.len() != expected {
+        out.push(ProjectIssue {
+            location: IssueLocation::Area(area_id.clone()),
+            description: format!(
+                "{} ({}): {} screens, but its size ({}x{}) needs {}",
+                area_id.area,
+                area_id.theme,
+                area.screens.len(),
+                area.size.0,
+                area.size.1,
+                expected
+            ),
+        });
+    }
+
+    let mut tile_issues = vec![];
+    // Screens beyond the area's size (or missing ones) are skipped, having been reported above:
+    let width = area.size.0 as usize;
+    for (i, screen) in area.screens.iter().take(expected).enumerate() {
+        let x0 = (i % width) as TileCoord * 32;
+        let y0 = (i / width) as TileCoord * 32;
+        for (y, x) in (0..32).cartesian_product(0..32) {
+            let problem = tile_problem(
+                palette_flips,
+                screen.palettes[y][x],
+                screen.tiles[y][x],
+                screen.flips[y][x],
+            );
+            if let Some(problem) = problem {
+                tile_issues.push((x0 + x as TileCoord, y0 + y as TileCoord, problem));
+            }
+        }
+        for &OverlayTile {
+            x,
+            y,
+            palette,
+            tile,
+            flip,
+        } in &screen.overlay
+        {
+            if let Some(problem) = tile_problem(palette_flips, palette, tile, flip) {
+                let (x, y) = (x0 + x as TileCoord, y0 + y as TileCoord);
+                tile_issues.push((x, y, format!("overlay {}", problem)));
+            }
+        }
+    }
+
+    let unlisted = tile_issues.len().saturating_sub(MAX_LISTED_PER_AREA);
+    for (x, y, problem) in tile_issues.into_iter().take(MAX_LISTED_PER_AREA) {
+        out.push(ProjectIssue {
+            location: IssueLocation::Tile {
+                area_id: area_id.clone(),
+                x,
+                y,
+            },
+            description: format!(
+                "{} ({}), tile ({}, {}): {}",
+                area_id.area, area_id.theme, x, y, problem
+            ),
+        });
+    }
+    if unlisted > 0 {
+        out.push(ProjectIssue {
+            location: IssueLocation::Area(area_id.clone()),
+            description: format!(
+                "{} ({}): {} more tile issues",
+                area_id.area, area_id.theme, unlisted
+            ),
+        });
+    }
+    out
+}
@@ -1,35 +1,90 @@
+mod annotations;
 mod area;
+mod brush;
+mod color_picker;
+mod external_tools;
 mod graphics;
+mod labels;
+mod library;
+mod macros;
+mod metatiles;
+mod minimap;
 mod palette;
+mod ruler;
+mod scripting;
+mod secrets;
 mod settings;
+mod stamps;
 mod tiles;
+mod travel;
+mod tutorial;
+mod usages;
+mod validation;
+mod world;
 
 use std::path::PathBuf;
 
+use annotations::annotations_view;
 use area::{
-    add_area_view, add_theme_view, area_grid_view, delete_area_view, delete_theme_view,
-    edit_area_view, main_area_controls, rename_theme_view, side_area_controls,
+    add_area_view, add_theme_view, area_diff_view, area_grid_view, area_list_view,
+    area_picker_view, bg_colors_view, compile_check_view, compression_estimate_view,
+    create_areas_view, dark_world_view, delete_area_view, delete_theme_view, duplicate_area_view,
+    edit_area_view, main_area_controls, palette_replace_view, rename_theme_view,
+    side_area_controls, vram_usage_view,
 };
+pub use area::{area_scrollable_id, area_tile_at, AREA_SCROLLBAR_PADDING};
+use external_tools::external_tools_view;
 use graphics::graphics_view;
 use iced::{
     alignment::Vertical,
     widget::{
-        button, center, column, container, horizontal_space, mouse_area, opaque, responsive, row,
-        stack, text, Column, Space,
+        button, center, checkbox, column, container, horizontal_space, mouse_area, opaque,
+        pane_grid, pick_list, progress_bar, responsive, row, scrollable, stack, text, Column, Row,
+        Space,
     },
-    Element, Font, Length, Theme,
+    Element, Font, Length, Padding, Point, Theme,
 };
-use iced_aw::quad;
+use iced_aw::{quad, Spinner};
+use labels::area_labels_view;
+use library::library_view;
+use macros::macros_view;
+use metatiles::metatiles_view;
 use palette::{
-    add_palette_view, delete_palette_view, rename_palette_view, selected_palette_view,
+    add_palette_view, adjust_palette_view, color_ramps_view, color_swatch, delete_palette_view,
+    duplicate_tiles_view, export_palette_view, flip_suggestions_view, palette_groups_view,
+    palette_history_view, rename_palette_view, selected_palette_view, tile_graphics_view,
     used_palettes_view,
 };
-use settings::{import_rom_confirm_view, import_rom_progress_view, settings_view};
+use scripting::script_view;
+use secrets::secrets_view;
+use settings::{
+    borrow_graphics_view, export_report_view, export_rom_progress_view, import_preview_view,
+    import_report_view, import_rom_confirm_view, import_rom_progress_view, memory_view,
+    palette_slots_view, rename_history_view, settings_view, test_run_progress_view,
+};
+use stamps::stamps_view;
 use tiles::tile_view;
+use travel::travel_point_view;
+use tutorial::{highlight, tutorial_view};
+pub use tutorial::{TutorialTarget, TUTORIAL_STEPS};
+use usages::usages_view;
+use validation::issues_view;
+use world::world_map_view;
 
 use crate::{
+    area_diff::DiffBase,
+    dirty,
+    external_changes::{ChangeDiff, ChangedFile, ReloadChoice},
+    keymap::active_keymap,
     message::Message,
-    state::{AreaPosition, Dialogue, EditorState, SidePanelView},
+    panes::Pane,
+    persist::RebuildScope,
+    project_load::ProjectLoad,
+    state::{
+        AreaPosition, Dialogue, EditorState, PickListMenu, SidePanelView, UnsavedChangesAction,
+    },
+    tile_graphics::GraphicsFormat,
+    tile_table::TileTableFormat,
 };
 
 pub async fn open_project() -> Option<PathBuf> {
@@ -49,6 +104,158 @@ pub async fn open_rom() -> Option<PathBuf> {
     picked_dir.map(|x| x.path().to_owned())
 }
 
+pub async fn open_rom_folder() -> Option<PathBuf> {
+    let picked_dir = rfd::AsyncFileDialog::new()
+        .set_title("Select a ROM folder ...")
+        .pick_folder()
+        .await;
+    picked_dir.map(|x| x.path().to_owned())
+}
+
+pub async fn save_rom_file(file_name: String) -> Option<PathBuf> {
+    let picked_file = rfd::AsyncFileDialog::new()
+        .set_title("Export ROM as ...")
+        .add_filter("SNES ROM", &["sfc", "smc"])
+        .set_file_name(file_name)
+        .save_file()
+        .await;
+    picked_file.map(|x| x.path().to_owned())
+}
+
+pub async fn save_palette_png(file_name: String) -> Option<PathBuf> {
+    let picked_file = rfd::AsyncFileDialog::new()
+        .set_title("Export palette as ...")
+        .add_filter("PNG image", &["png"])
+        .set_file_name(file_name)
+        .save_file()
+        .await;
+    picked_file.map(|x| x.path().to_owned())
+}
+
+pub async fn save_tile_graphics(file_name: String, format: GraphicsFormat) -> Option<PathBuf> {
+    let filter = match format {
+        GraphicsFormat::Png => "PNG image",
+        _ => "SNES tile data",
+    };
+    let picked_file = rfd::AsyncFileDialog::new()
+        .set_title("Export tile graphics as ...")
+        .add_filter(filter, &[format.extension()])
+        .set_file_name(file_name)
+        .save_file()
+        .await;
+    picked_file.map(|x| x.path().to_owned())
+}
+
+pub async fn open_tile_graphics(format: GraphicsFormat) -> Option<PathBuf> {
+    let filter = match format {
+        GraphicsFormat::Png => "PNG image",
+        _ => "SNES tile data",
+    };
+    let picked_file = rfd::AsyncFileDialog::new()
+        .set_title("Select tile graphics ...")
+        .add_filter(filter, &[format.extension()])
+        .pick_file()
+        .await;
+    picked_file.map(|x| x.path().to_owned())
+}
+
+pub async fn save_tile_table(file_name: String, format: TileTableFormat) -> Option<PathBuf> {
+    let filter = match format {
+        TileTableFormat::Csv => "CSV table",
+        TileTableFormat::Markdown => "Markdown document",
+    };
+    let picked_file = rfd::AsyncFileDialog::new()
+        .set_title("Export tile table as ...")
+        .add_filter(filter, &[format.extension()])
+        .set_file_name(file_name)
+        .save_file()
+        .await;
+    picked_file.map(|x| x.path().to_owned())
+}
+
+pub async fn save_annotations_json(file_name: String) -> Option<PathBuf> {
+    let picked_file = rfd::AsyncFileDialog::new()
+        .set_title("Export screen annotations as ...")
+        .add_filter("JSON", &["json"])
+        .set_file_name(file_name)
+        .save_file()
+        .await;
+    picked_file.map(|x| x.path().to_owned())
+}
+
+pub async fn save_camera_locks_asm(file_name: String) -> Option<PathBuf> {
+    let picked_file = rfd::AsyncFileDialog::new()
+        .set_title("Export camera-locked screens as ...")
+        .add_filter("Assembly source", &["asm"])
+        .set_file_name(file_name)
+        .save_file()
+        .await;
+    picked_file.map(|x| x.path().to_owned())
+}
+
+pub async fn save_cheat_sheet_png(file_name: String) -> Option<PathBuf> {
+    let picked_file = rfd::AsyncFileDialog::new()
+        .set_title("Export shortcuts cheat sheet as ...")
+        .add_filter("PNG image", &["png"])
+        .set_file_name(file_name)
+        .save_file()
+        .await;
+    picked_file.map(|x| x.path().to_owned())
+}
+
+pub async fn save_bug_report_file(file_name: String) -> Option<PathBuf> {
+    let picked_file = rfd::AsyncFileDialog::new()
+        .set_title("Save bug report as ...")
+        .add_filter("Zip archive", &["zip"])
+        .set_file_name(file_name)
+        .save_file()
+        .await;
+    picked_file.map(|x| x.path().to_owned())
+}
+
+pub async fn open_library_dir() -> Option<PathBuf> {
+    let picked_dir = rfd::AsyncFileDialog::new()
+        .set_title("Select library folder ...")
+        .pick_folder()
+        .await;
+    picked_dir.map(|x| x.path().to_owned())
+}
+
+pub async fn open_emulator() -> Option<PathBuf> {
+    let picked_file = rfd::AsyncFileDialog::new()
+        .set_title("Select emulator ...")
+        .pick_file()
+        .await;
+    picked_file.map(|x| x.path().to_owned())
+}
+
+pub async fn open_import_rules() -> Option<PathBuf> {
+    let picked_file = rfd::AsyncFileDialog::new()
+        .set_title("Select import rules ...")
+        .add_filter("JSON", &["json"])
+        .pick_file()
+        .await;
+    picked_file.map(|x| x.path().to_owned())
+}
+
+pub async fn open_area_manifest() -> Option<PathBuf> {
+    let picked_file = rfd::AsyncFileDialog::new()
+        .set_title("Select an area manifest ...")
+        .add_filter("Area manifest", &["csv", "json"])
+        .pick_file()
+        .await;
+    picked_file.map(|x| x.path().to_owned())
+}
+
+pub async fn open_heatmap() -> Option<PathBuf> {
+    let picked_file = rfd::AsyncFileDialog::new()
+        .set_title("Select a playtest heatmap ...")
+        .add_filter("Visit counts", &["csv", "json"])
+        .pick_file()
+        .await;
+    picked_file.map(|x| x.path().to_owned())
+}
+
 fn modal<'a, Message>(
     base: impl Into<Element<'a, Message>>,
     content: impl Into<Element<'a, Message>>,
@@ -78,6 +285,75 @@ where
     .into()
 }
 
+// Popup menu placed at the given window position, closed by clicking elsewhere.
+fn context_menu<'a>(
+    base: impl Into<Element<'a, Message>>,
+    content: impl Into<Element<'a, Message>>,
+    position: Point,
+) -> Element<'a, Message> {
+    stack![
+        base.into(),
+        opaque(
+            mouse_area(
+                container(opaque(content))
+                    .padding(Padding::ZERO.top(position.y).left(position.x))
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+            )
+            .on_press(Message::HideModal)
+            .on_right_press(Message::HideModal)
+        )
+    ]
+    .into()
+}
+
+fn context_menu_view(menu: PickListMenu) -> Element<'static, Message> {
+    let (rename_msg, duplicate_msg, delete_msg) = match menu {
+        PickListMenu::Area => (
+            Message::EditAreaDialogue,
+            Message::DuplicateAreaDialogue,
+            Message::DeleteAreaDialogue,
+        ),
+        PickListMenu::Theme => (
+            Message::RenameThemeDialogue,
+            Message::DuplicateThemeDialogue,
+            Message::DeleteThemeDialogue,
+        ),
+        PickListMenu::Palette => (
+            Message::RenamePaletteDialogue,
+            Message::DuplicatePaletteDialogue,
+            Message::DeletePaletteDialogue,
+        ),
+    };
+    let item = |label, msg| {
+        button(text(label))
+            .width(Length::Fill)
+            .style(button::text)
+            .on_press(msg)
+    };
+    let mut items = column![
+        item("Rename", rename_msg),
+        item("Duplicate", duplicate_msg),
+        item("Delete", delete_msg),
+    ]
+    .width(120);
+    if menu == PickListMenu::Area {
+        items = items
+            .push(item("Compare", Message::ShowAreaDiff(DiffBase::Saved)))
+            .push(item("Dark world", Message::DarkWorldDialogue))
+            .push(item("Replace palette", Message::ShowPaletteReplace))
+            .push(item("Hidden items", Message::ShowSecrets(true)))
+            .push(item("Screen properties", Message::ShowAnnotations(true)))
+            .push(item("Copy selection as JSON", Message::CopySelectionJson))
+            .push(item("Paste JSON selection", Message::PasteSelectionJson))
+            .width(180);
+    }
+    container(items)
+        .padding(5)
+        .style(modal_background_style)
+        .into()
+}
+
 pub fn modal_background_style(theme: &Theme) -> container::Style {
     let palette = theme.extended_palette();
     container::Style {
@@ -89,6 +365,18 @@ pub fn modal_background_style(theme: &Theme) -> container::Style {
     }
 }
 
+// Panes are outlined (in the color of `vertical_separator`), to show where the splitters are.
+fn pane_style(_theme: &Theme) -> container::Style {
+    container::Style {
+        border: iced::Border {
+            color: iced::Color::from([0.5; 3]),
+            width: 1.0,
+            radius: 0.0.into(),
+        },
+        ..Default::default()
+    }
+}
+
 fn vertical_separator() -> quad::Quad {
     quad::Quad {
         quad_color: iced::Color::from([0.5; 3]).into(),
@@ -102,32 +390,37 @@ fn vertical_separator() -> quad::Quad {
     }
 }
 
-pub fn help_view(_state: &EditorState) -> Element<Message> {
-    let controls = vec![
-        ("s", "Select tool", "copy tiles, colors, pixels"),
-        ("b", "Brush tool", "paste tiles, colors, pixels"),
-        ("g", "Grid toggle", "show/hide 16x16 tile grid"),
-        ("h", "Horizontal flip", "flip selection horizontally"),
-        ("v", "Vertical flip", "flip selection horizontally"),
-        ("t", "Tileset view", "show palettes/tilesets in side panel"),
-        ("a", "Area view", "show secondary area in side panel"),
-        ("-", "Zoom out", "zoom out area views"),
-        ("=", "Zoom in", "zoom in area views"),
-    ];
+pub fn help_view(state: &EditorState) -> Element<Message> {
     let mut col = Column::new();
     col = col.push(text("Essential keyboard controls:"));
-    for (key, name, desc) in controls {
+    for binding in active_keymap(&state.global_config) {
         col = col.push(
             row![
-                text(key).width(20).font(Font {
+                text(binding.label()).width(20).font(Font {
                     weight: iced::font::Weight::ExtraBold,
                     ..Default::default()
                 }),
-                text(format!("{}: {}", name, desc)).width(400),
+                text(format!(
+                    "{}: {}",
+                    binding.action.name(),
+                    binding.action.description()
+                ))
+                .width(400),
             ]
             .align_y(Vertical::Center),
         );
     }
+    col = col.push(
+        row![
+            button(text("Take the guided tour"))
+                .style(button::secondary)
+                .on_press(Message::StartTutorial),
+            button(text("Export cheat sheet"))
+                .style(button::secondary)
+                .on_press(Message::ExportCheatSheet),
+        ]
+        .spacing(10),
+    );
 
     container(col.spacing(10))
         .width(450)
@@ -136,30 +429,488 @@ pub fn help_view(_state: &EditorState) -> Element<Message> {
         .into()
 }
 
-pub fn rebuild_project_view(_state: &EditorState) -> Element<Message> {
-    container(text(
-        "Please wait while the project PNG files are exported.",
-    ))
+// Shown in place of the editor while a project loads.
+fn project_load_view(load: &ProjectLoad) -> Element<'_, Message> {
+    center(
+        column![
+            Spinner::new().width(40).height(40),
+            text(format!("Loading {}", load.dir.display())),
+            text(format!(
+                "{} palettes, {} areas read",
+                load.palettes, load.areas
+            )),
+            button(text("Cancel"))
+                .style(button::secondary)
+                .on_press(Message::CancelProjectLoad),
+        ]
+        .spacing(15)
+        .align_x(iced::alignment::Horizontal::Center),
+    )
+    .into()
+}
+
+fn slow_update_view(state: &EditorState) -> Option<Element<'_, Message>> {
+    let timing = state.slow_update.as_ref()?;
+    Some(
+        container(
+            row![
+                text("\u{F33B}").font(iced_fonts::BOOTSTRAP_FONT),
+                text(format!(
+                    "{} blocked the editor for {:.1} seconds. \
+                    Long-running operations like this should be run as background tasks.",
+                    timing.name,
+                    timing.duration.as_secs_f32()
+                ))
+                .width(Length::Fill),
+                button(text("\u{F659}").font(iced_fonts::BOOTSTRAP_FONT))
+                    .style(button::text)
+                    .on_press(Message::DismissSlowUpdateWarning),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+        )
+        .padding([5, 10])
+        .style(container::rounded_box)
+        .into(),
+    )
+}
+
+// Debug overlay listing the most recent update handler durations (toggled with F12).
+fn update_timings_view(state: &EditorState) -> Element<'_, Message> {
+    let mut col = Column::new().push(text("Update timings (F12 to hide)").size(12));
+    for t in &state.update_timings {
+        col = col.push(
+            row![
+                text(t.name.clone()).size(12).width(200),
+                text(format!("{:.1} ms", t.duration.as_secs_f64() * 1000.0)).size(12),
+            ]
+            .spacing(10),
+        );
+    }
+    container(
+        container(col.spacing(2))
+            .padding(10)
+            .style(container::rounded_box),
+    )
+    .padding(10)
+    .align_right(Length::Fill)
+    .align_bottom(Length::Fill)
+    .into()
+}
+
+pub fn rebuild_project_view<'a>(
+    state: &'a EditorState,
+    scope: &'a RebuildScope,
+) -> Element<'a, Message> {
+    if let Some(rebuild) = &state.project_rebuild {
+        return container(
+            column![
+                text("Exporting the project's PNG files..."),
+                progress_bar(0.0..=rebuild.total.max(1) as f32, rebuild.done as f32).height(10),
+                row![
+                    text(format!("{} of {} done", rebuild.done, rebuild.total)),
+                    horizontal_space(),
+                    button(text("Cancel"))
+                        .style(button::secondary)
+                        .on_press(Message::CancelRebuildProject),
+                ]
+                .align_y(Vertical::Center),
+            ]
+            .spacing(15),
+        )
+        .width(500)
+        .padding(25)
+        .style(modal_background_style)
+        .into();
+    }
+    const ALL_THEMES: &str = "All themes";
+    let theme_options: Vec<String> = std::iter::once(ALL_THEMES.to_string())
+        .chain(state.theme_names.iter().cloned())
+        .collect();
+    let area_name = state.main_area_id.area.clone();
+    container(
+        column![
+            text("Export the project's area PNG files."),
+            checkbox(
+                format!("Only the current area ({})", area_name),
+                scope.area.is_some()
+            )
+            .on_toggle(move |x| Message::SetRebuildScope(RebuildScope {
+                area: x.then(|| area_name.clone()),
+                ..scope.clone()
+            })),
+            row![
+                text("Theme"),
+                pick_list(
+                    theme_options,
+                    Some(scope.theme.clone().unwrap_or(ALL_THEMES.to_string())),
+                    |x| Message::SetRebuildScope(RebuildScope {
+                        theme: (x != ALL_THEMES).then_some(x),
+                        ..scope.clone()
+                    })
+                )
+                .width(200),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            checkbox(
+                "Only files that are missing or older than the area's JSON",
+                scope.only_stale
+            )
+            .on_toggle(|x| Message::SetRebuildScope(RebuildScope {
+                only_stale: x,
+                ..scope.clone()
+            })),
+            checkbox(
+                "Also the palettes' color and tile PNG files",
+                scope.palettes
+            )
+            .on_toggle(|x| Message::SetRebuildScope(RebuildScope {
+                palettes: x,
+                ..scope.clone()
+            })),
+            row![
+                button(text("Cancel"))
+                    .style(button::secondary)
+                    .on_press(Message::CloseDialogue),
+                horizontal_space(),
+                button(text("Rebuild"))
+                    .style(button::success)
+                    .on_press(Message::StartRebuildProject),
+            ]
+        ]
+        .spacing(15),
+    )
     .width(500)
     .padding(25)
     .style(modal_background_style)
     .into()
 }
 
-pub fn modified_reload_view(_state: &EditorState) -> Element<Message> {
+// Number of changed tiles listed by index for a palette, before the rest are counted.
+const MAX_LISTED_TILES: usize = 24;
+
+// How a changed file differs from the editor's copy, shown below it in the reload dialogue.
+fn change_diff_view(diff: &ChangeDiff) -> Element<'_, Message> {
+    let mut col = column![].spacing(4).padding(Padding::ZERO.left(15));
+    match diff {
+        ChangeDiff::Area {
+            changes,
+            size,
+            disk_size,
+        } => {
+            if changes.size_changed {
+                col = col.push(
+                    text(format!(
+                        "Size: {}x{} in the editor, {}x{} on disk",
+                        size.0, size.1, disk_size.0, disk_size.1
+                    ))
+                    .size(12),
+                );
+            }
+            for screen in &changes.screens {
+                col = col.push(
+                    text(format!(
+                        "Screen ({}, {}): {} tiles differ",
+                        screen.position.0, screen.position.1, screen.tiles
+                    ))
+                    .size(12),
+                );
+            }
+            if changes.tiles.is_empty() && !changes.size_changed {
+                col = col.push(text("Only the area's properties differ.").size(12));
+            }
+        }
+        ChangeDiff::Palette {
+            colors,
+            disk_colors,
+            tiles,
+            tile_count,
+            disk_tile_count,
+        } => {
+            let changed = |i: usize| colors.get(i) != disk_colors.get(i);
+            if !colors.is_empty() {
+                let mut editor_row: Row<Message> = Row::new().spacing(2);
+                for (i, &c) in colors.iter().enumerate() {
+                    editor_row = editor_row.push(color_swatch(c, changed(i)));
+                }
+                col = col.push(
+                    row![text("Editor").width(60).size(12), editor_row].align_y(Vertical::Center),
+                );
+            }
+            let mut disk_row: Row<Message> = Row::new().spacing(2);
+            for (i, &c) in disk_colors.iter().enumerate() {
+                disk_row = disk_row.push(color_swatch(c, !colors.is_empty() && changed(i)));
+            }
+            col = col
+                .push(row![text("On disk").width(60).size(12), disk_row].align_y(Vertical::Center));
+            if tile_count != disk_tile_count {
+                col = col.push(
+                    text(format!(
+                        "{} tiles in the editor, {} on disk",
+                        tile_count, disk_tile_count
+                    ))
+                    .size(12),
+                );
+            }
+            if !tiles.is_empty() {
+                let mut listed: Vec<String> = tiles
+                    .iter()
+                    .take(MAX_LISTED_TILES)
+                    .map(|t| t.to_string())
+                    .collect();
+                if tiles.len() > MAX_LISTED_TILES {
+                    listed.push(format!("and {} more", tiles.len() - MAX_LISTED_TILES));
+                }
+                col = col.push(text(format!("Tiles differing: {}", listed.join(", "))).size(12));
+            }
+        }
+    }
+    col.into()
+}
+
+pub fn modified_reload_view(state: &EditorState) -> Element<Message> {
+    let mut list_col = column![].spacing(8);
+    for (i, change) in state.external_changes.iter().enumerate() {
+        let choice_button = |label, choice| {
+            button(text(label).size(12))
+                .padding([2, 8])
+                .style(if change.choice == choice {
+                    button::primary
+                } else {
+                    button::secondary
+                })
+                .on_press_maybe(
+                    (change.loadable || choice == ReloadChoice::KeepMine)
+                        .then_some(Message::SetReloadChoice(i, choice)),
+                )
+        };
+        let details = if change.unsaved {
+            format!("{} (has unsaved changes)", change.summary)
+        } else {
+            change.summary.clone()
+        };
+        // The area diff compares the main area with the file:
+        let comparable =
+            change.loadable && change.file == ChangedFile::Area(state.main_area_id.clone());
+        list_col = list_col.push(
+            row![
+                column![text(change.label()), text(details).size(12)]
+                    .spacing(2)
+                    .width(Length::Fill),
+                choice_button("Reload", ReloadChoice::Reload),
+                choice_button("Keep mine", ReloadChoice::KeepMine),
+            ]
+            .push_maybe(change.diff.is_some().then(|| {
+                button(
+                    text(if change.show_diff {
+                        "Hide diff"
+                    } else {
+                        "Show diff"
+                    })
+                    .size(12),
+                )
+                .padding([2, 8])
+                .style(button::secondary)
+                .on_press(Message::ToggleReloadDiff(i))
+            }))
+            .push_maybe(comparable.then(|| {
+                button(text("Compare").size(12))
+                    .padding([2, 8])
+                    .style(button::secondary)
+                    .on_press(Message::ShowAreaDiff(DiffBase::Saved))
+            }))
+            .spacing(5)
+            .align_y(Vertical::Center),
+        );
+        if let Some(diff) = change.diff.as_ref().filter(|_| change.show_diff) {
+            list_col = list_col.push(change_diff_view(diff));
+        }
+    }
     container(
         column![
-            text("File changes detected on disk."),
-            text("Please click 'Accept' to load these changes, to keep the editor in sync with the project data."),
+            text("Project files were changed outside the editor."),
+            text(
+                "Choose whether to reload each file, or keep the editor's version (saving it over \
+                 the file). Other unsaved changes are kept."
+            )
+            .size(12),
+            container(scrollable(list_col)).max_height(300),
             row![
-                button(text("Accept"))
+                button(text("Apply"))
                     .style(button::success)
+                    .on_press(Message::ApplyExternalChanges),
+                horizontal_space(),
+                button(text("Reload whole project"))
+                    .style(button::danger)
                     .on_press(Message::ModifiedReload),
+            ]
+            .spacing(10)
+        ]
+        .spacing(15),
+    )
+    .width(550)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
+
+fn unsaved_changes_view<'a>(
+    state: &'a EditorState,
+    action: &UnsavedChangesAction,
+) -> Element<'a, Message> {
+    let save_label = match action {
+        UnsavedChangesAction::CloseWindow(_) => "Save all",
+        UnsavedChangesAction::OpenProject(_) => "Save all and open",
+    };
+    container(
+        column![
+            text("The project has unsaved changes."),
+            text(dirty::status_summary(state)).size(12),
+            row![
+                button(text(save_label))
+                    .style(button::success)
+                    .on_press(Message::SaveAndContinue(action.clone())),
+                button(text("Discard changes"))
+                    .style(button::danger)
+                    .on_press(Message::DiscardAndContinue(action.clone())),
                 horizontal_space(),
-                button(text("Reject"))
+                button(text("Cancel"))
+                    .style(button::secondary)
+                    .on_press(Message::CloseDialogue),
+            ]
+            .spacing(10)
+        ]
+        .spacing(15),
+    )
+    .width(450)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
+
+fn discard_changes_view(state: &EditorState) -> Element<'_, Message> {
+    container(
+        column![
+            text("Discard the unsaved changes, reloading the project from disk?"),
+            text(dirty::status_summary(state)).size(12),
+            text("This can't be undone.").size(12),
+            row![
+                button(text("Discard changes"))
                     .style(button::danger)
+                    .on_press(Message::DiscardChanges),
+                horizontal_space(),
+                button(text("Cancel"))
+                    .style(button::secondary)
                     .on_press(Message::CloseDialogue),
             ]
+            .spacing(10)
+        ]
+        .spacing(15),
+    )
+    .width(450)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
+
+// Bar along the bottom of the window, summarizing the unsaved and unexported changes.
+fn status_bar(state: &EditorState) -> Element<'_, Message> {
+    let unsaved = dirty::has_unsaved_changes(state);
+    container(
+        row![
+            text(dirty::status_summary(state)).size(12),
+            horizontal_space(),
+        ]
+        .push_maybe(unsaved.then(|| {
+            button(text("Save").size(12))
+                .padding([2, 8])
+                .style(button::success)
+                .on_press(Message::SaveProject)
+        }))
+        .push_maybe(unsaved.then(|| {
+            button(text("Discard changes").size(12))
+                .padding([2, 8])
+                .style(button::secondary)
+                .on_press(Message::DiscardChangesDialogue)
+        }))
+        .spacing(10)
+        .align_y(Vertical::Center),
+    )
+    .padding([2, 10])
+    .width(Length::Fill)
+    .into()
+}
+
+pub fn load_recovery_view(state: &EditorState) -> Element<'_, Message> {
+    let project_dir = state.global_config.project_dir.clone().unwrap_or_default();
+    let mut col = column![text(
+        "Some project files couldn't be loaded, and have been left out of the project. \
+        They can be restored from another version (moving the broken file to the project's \
+        Trash folder), or skipped for now and fixed by hand."
+    )]
+    .spacing(15);
+    for failure in state.load_failures.iter().filter(|f| !f.skipped) {
+        let path = failure
+            .path
+            .strip_prefix(&project_dir)
+            .unwrap_or(&failure.path);
+        let mut buttons = row![
+            button(text("Open folder"))
+                .style(button::secondary)
+                .on_press(Message::OpenFileLocation(failure.path.clone())),
+            horizontal_space(),
+        ]
+        .spacing(5);
+        for option in &failure.restore_options {
+            buttons = buttons.push(button(text(option.to_string())).on_press(
+                Message::RestoreFile {
+                    path: failure.path.clone(),
+                    option: option.clone(),
+                },
+            ));
+        }
+        buttons = buttons.push(
+            button(text("Skip"))
+                .style(button::secondary)
+                .on_press(Message::SkipFile(failure.path.clone())),
+        );
+        col = col.push(
+            column![
+                row![
+                    text("\u{F33A}")
+                        .font(iced_fonts::BOOTSTRAP_FONT)
+                        .style(text::danger),
+                    text(path.display().to_string()),
+                ]
+                .spacing(10),
+                text(&failure.error).size(12),
+                buttons,
+            ]
+            .spacing(5),
+        );
+    }
+    container(scrollable(col).height(Length::Shrink))
+        .width(700)
+        .max_height(600)
+        .padding(25)
+        .style(modal_background_style)
+        .into()
+}
+
+pub fn error_view(message: &str) -> Element<'_, Message> {
+    container(
+        column![
+            row![
+                text("\u{F33A}")
+                    .font(iced_fonts::BOOTSTRAP_FONT)
+                    .style(text::danger),
+                text(message),
+            ]
+            .spacing(10),
+            button(text("Close"))
+                .style(button::secondary)
+                .on_press(Message::CloseDialogue),
         ]
         .spacing(15),
     )
@@ -190,10 +941,51 @@ pub fn view_dialogue<'a>(
             Dialogue::AddArea { name, size } => {
                 modal(main_view, add_area_view(name, *size), Message::HideModal)
             }
+            Dialogue::CreateAreas {
+                entries,
+                done,
+                skipped,
+            } => modal(
+                main_view,
+                create_areas_view(entries, *done, skipped),
+                if *done < entries.len() {
+                    Message::Nothing
+                } else {
+                    Message::HideModal
+                },
+            ),
             Dialogue::EditArea { name } => {
                 modal(main_view, edit_area_view(state, name), Message::HideModal)
             }
             Dialogue::DeleteArea => modal(main_view, delete_area_view(state), Message::HideModal),
+            &Dialogue::TravelPoint { position, idx } => modal(
+                main_view,
+                travel_point_view(state, position, idx),
+                Message::HideModal,
+            ),
+            Dialogue::DuplicateArea { name } => modal(
+                main_view,
+                duplicate_area_view(state, name),
+                Message::HideModal,
+            ),
+            Dialogue::DarkWorld {
+                target,
+                exists,
+                mapping,
+            } => modal(
+                main_view,
+                dark_world_view(state, target, *exists, mapping),
+                Message::HideModal,
+            ),
+            Dialogue::BGColors {
+                colors,
+                theme,
+                color,
+            } => modal(
+                main_view,
+                bg_colors_view(state, colors, theme, *color),
+                Message::HideModal,
+            ),
             Dialogue::AddTheme { name } => {
                 modal(main_view, add_theme_view(name), Message::HideModal)
             }
@@ -212,67 +1004,300 @@ pub fn view_dialogue<'a>(
                 modal(main_view, import_rom_progress_view(state), Message::Nothing)
             }
             Dialogue::Help => modal(main_view, help_view(state), Message::HideModal),
-            Dialogue::RebuildProject => {
-                modal(main_view, rebuild_project_view(state), Message::Nothing)
-            }
+            Dialogue::RebuildProject { scope } => modal(
+                main_view,
+                rebuild_project_view(state, scope),
+                if state.project_rebuild.is_some() {
+                    Message::Nothing
+                } else {
+                    Message::HideModal
+                },
+            ),
             Dialogue::ModifiedReload => {
                 modal(main_view, modified_reload_view(state), Message::Nothing)
             }
+            Dialogue::UnsavedChanges(action) => modal(
+                main_view,
+                unsaved_changes_view(state, action),
+                Message::HideModal,
+            ),
+            Dialogue::DiscardChanges => {
+                modal(main_view, discard_changes_view(state), Message::HideModal)
+            }
+            Dialogue::LoadRecovery => {
+                modal(main_view, load_recovery_view(state), Message::HideModal)
+            }
+            Dialogue::AreaPicker(picker) => {
+                modal(main_view, area_picker_view(picker), Message::HideModal)
+            }
+            Dialogue::AreaList(position, new_group) => modal(
+                main_view,
+                area_list_view(state, *position, new_group),
+                Message::HideModal,
+            ),
+            Dialogue::PaletteGroups { group, renames } => modal(
+                main_view,
+                palette_groups_view(state, group, renames),
+                Message::HideModal,
+            ),
+            Dialogue::ContextMenu(menu, position) => {
+                context_menu(main_view, context_menu_view(*menu), *position)
+            }
+            Dialogue::CompileCheck(report) => {
+                modal(main_view, compile_check_view(report), Message::HideModal)
+            }
+            Dialogue::CompressionEstimate(theme, estimates) => modal(
+                main_view,
+                compression_estimate_view(theme, estimates),
+                Message::HideModal,
+            ),
+            Dialogue::VramUsage { all_areas, screens } => modal(
+                main_view,
+                vram_usage_view(*all_areas, screens),
+                Message::HideModal,
+            ),
+            Dialogue::PaletteSlots {
+                theme,
+                area,
+                area_palettes,
+                assignment,
+                problems,
+            } => modal(
+                main_view,
+                palette_slots_view(state, theme, area, area_palettes, assignment, problems),
+                Message::HideModal,
+            ),
+            Dialogue::BorrowGraphics(borrow) => modal(
+                main_view,
+                borrow_graphics_view(state, borrow),
+                Message::HideModal,
+            ),
+            Dialogue::AreaLabels(preview_text) => modal(
+                main_view,
+                area_labels_view(state, preview_text),
+                Message::HideModal,
+            ),
+            &Dialogue::Macros { x, y } => {
+                modal(main_view, macros_view(state, x, y), Message::HideModal)
+            }
+            Dialogue::Library {
+                items,
+                filter,
+                name,
+                tags,
+            } => modal(
+                main_view,
+                library_view(state, items, filter, name, tags),
+                Message::HideModal,
+            ),
+            &Dialogue::TileGraphics { palette_id, format } => modal(
+                main_view,
+                tile_graphics_view(state, palette_id, format),
+                Message::HideModal,
+            ),
+            &Dialogue::ExportPalette {
+                palette_id,
+                labeled,
+                tile_table,
+            } => modal(
+                main_view,
+                export_palette_view(state, palette_id, labeled, tile_table),
+                Message::HideModal,
+            ),
+            Dialogue::AdjustPalette(adjust) => modal(
+                main_view,
+                adjust_palette_view(state, adjust),
+                Message::HideModal,
+            ),
+            &Dialogue::ColorRamps { start, len, shade } => modal(
+                main_view,
+                color_ramps_view(state, start, len, shade),
+                Message::HideModal,
+            ),
+            Dialogue::FlipSuggestions(suggestions) => modal(
+                main_view,
+                flip_suggestions_view(state, suggestions),
+                Message::HideModal,
+            ),
+            Dialogue::DuplicateTiles {
+                palette_id,
+                duplicates,
+            } => modal(
+                main_view,
+                duplicate_tiles_view(state, *palette_id, duplicates),
+                Message::HideModal,
+            ),
+            Dialogue::Error(message) => modal(main_view, error_view(message), Message::HideModal),
+            &Dialogue::Memory { project_bytes } => modal(
+                main_view,
+                memory_view(state, project_bytes),
+                Message::HideModal,
+            ),
+            Dialogue::Script => modal(main_view, script_view(state), Message::HideModal),
+            Dialogue::ImportReport(report) => {
+                modal(main_view, import_report_view(report), Message::HideModal)
+            }
+            Dialogue::ImportPreview(report) => modal(
+                main_view,
+                import_preview_view(state, report),
+                Message::HideModal,
+            ),
+            Dialogue::ExportROMProgress => {
+                modal(main_view, export_rom_progress_view(state), Message::Nothing)
+            }
+            &Dialogue::TestRun(stage) => {
+                modal(main_view, test_run_progress_view(stage), Message::Nothing)
+            }
+            Dialogue::ExportReport(report) => {
+                modal(main_view, export_report_view(report), Message::HideModal)
+            }
+            Dialogue::RenameHistory(renames) => {
+                modal(main_view, rename_history_view(renames), Message::HideModal)
+            }
+            Dialogue::PaletteHistory(versions) => modal(
+                main_view,
+                palette_history_view(state, versions),
+                Message::HideModal,
+            ),
         }
     } else {
         main_view
     }
 }
 
-pub fn view(state: &EditorState) -> Element<Message> {
-    if state.global_config.project_dir.is_none() {
-        return Space::new(Length::Fill, Length::Fill).into();
+fn side_panel(state: &EditorState) -> Element<'_, Message> {
+    let in_pane = state.global_config.pane_layout.is_some();
+    match state.side_panel_view {
+        // In the three-pane layout, the side area has its own pane:
+        SidePanelView::Area if !in_pane => side_area_panel(state).width(420).into(),
+        SidePanelView::Tileset | SidePanelView::Area => column![
+            highlight(
+                state,
+                TutorialTarget::PalettePanel,
+                column![used_palettes_view(state), selected_palette_view(state)]
+            ),
+            responsive(move |size| highlight(
+                state,
+                TutorialTarget::Tileset,
+                column![tile_view(state, size, 260.0), graphics_view(state)]
+            ))
+        ]
+        .width(420)
+        .into(),
+        SidePanelView::Usages => usages_view(state),
+        SidePanelView::Issues => issues_view(state),
+        SidePanelView::Metatiles => metatiles_view(state),
+        SidePanelView::Stamps => stamps_view(state),
+        SidePanelView::Secrets => secrets_view(state),
+        SidePanelView::Annotations => annotations_view(state),
+        SidePanelView::ExternalTools => external_tools_view(state),
     }
+}
 
-    let main_panel: Element<Message> = column![
-        row![
-            button(text("\u{F3E2}").font(iced_fonts::BOOTSTRAP_FONT))
-                .style(button::secondary)
-                .on_press(Message::SettingsDialogue),
-            main_area_controls(state),
-            horizontal_space(),
-            button(text("\u{F505}").font(iced_fonts::BOOTSTRAP_FONT))
-                .style(button::secondary)
-                .on_press(Message::HelpDialogue),
-        ]
-        .spacing(10),
-        area_grid_view(state, AreaPosition::Main),
+fn side_area_panel(state: &EditorState) -> Column<'_, Message> {
+    column![
+        side_area_controls(state),
+        area_grid_view(state, AreaPosition::Side),
     ]
     .padding(10)
     .spacing(10)
-    .into();
-
-    let side_panel: Element<Message> = match state.side_panel_view {
-        SidePanelView::Tileset => column![
-            used_palettes_view(state),
-            selected_palette_view(state),
-            responsive(
-                move |size| column![tile_view(state, size, 260.0), graphics_view(state)].into()
+}
+
+fn main_panel(state: &EditorState) -> Element<'_, Message> {
+    if let Some(world_map) = &state.world_map {
+        column![world_map_view(state, world_map)].padding(10).into()
+    } else {
+        Column::new()
+            .push_maybe(slow_update_view(state))
+            .push(
+                row![
+                    button(text("\u{F3E2}").font(iced_fonts::BOOTSTRAP_FONT))
+                        .style(button::secondary)
+                        .on_press(Message::SettingsDialogue),
+                    button(text("\u{F5C3}").font(iced_fonts::BOOTSTRAP_FONT))
+                        .style(button::secondary)
+                        .on_press(Message::ShowExternalTools(true)),
+                    button(text("\u{F2C6}").font(iced_fonts::BOOTSTRAP_FONT))
+                        .style(button::secondary)
+                        .on_press(Message::ScriptDialogue),
+                    button(text("\u{F26F}").font(iced_fonts::BOOTSTRAP_FONT))
+                        .style(button::secondary)
+                        .on_press(Message::ValidateProject),
+                    main_area_controls(state),
+                    horizontal_space(),
+                    button(
+                        text(if state.side_panel_hidden {
+                            "\u{F45E}"
+                        } else {
+                            "\u{F45C}"
+                        })
+                        .font(iced_fonts::BOOTSTRAP_FONT)
+                    )
+                    .style(button::secondary)
+                    .on_press(Message::ToggleSidePanel),
+                    highlight(
+                        state,
+                        TutorialTarget::Shortcuts,
+                        button(text("\u{F505}").font(iced_fonts::BOOTSTRAP_FONT))
+                            .style(button::secondary)
+                            .on_press(Message::HelpDialogue)
+                    ),
+                ]
+                .spacing(10),
             )
-        ]
-        .width(420)
-        .into(),
-        SidePanelView::Area => column![
-            side_area_controls(state),
-            area_grid_view(state, AreaPosition::Side),
-        ]
-        .padding(10)
-        .spacing(10)
-        .width(420)
-        .into(),
-    };
+            .push_maybe(area_diff_view(state))
+            .push_maybe(palette_replace_view(state))
+            .push(highlight(
+                state,
+                TutorialTarget::AreaCanvas,
+                area_grid_view(state, AreaPosition::Main),
+            ))
+            .padding(10)
+            .spacing(10)
+            .into()
+    }
+}
 
-    let mut main_view: Element<Message> = row![main_panel, vertical_separator(), side_panel,]
-        .spacing(0)
-        .width(Length::Fill)
-        .height(Length::Fill)
-        .into();
+pub fn view(state: &EditorState) -> Element<Message> {
+    if let Some(load) = &state.project_load {
+        return project_load_view(load);
+    }
+    if state.global_config.project_dir.is_none() {
+        return Space::new(Length::Fill, Length::Fill).into();
+    }
+
+    let mut main_view: Element<Message> =
+        if state.global_config.pane_layout.is_some() && !state.side_panel_hidden {
+            pane_grid(&state.panes, |_, &pane, _| {
+                pane_grid::Content::new(match pane {
+                    Pane::Main => main_panel(state),
+                    Pane::SideArea => side_area_panel(state).into(),
+                    Pane::SidePanel => side_panel(state),
+                })
+                .style(pane_style)
+            })
+            .on_resize(10, Message::PaneResized)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+        } else {
+            Row::new()
+                .push(main_panel(state))
+                .push_maybe((!state.side_panel_hidden).then(vertical_separator))
+                .push_maybe((!state.side_panel_hidden).then(|| side_panel(state)))
+                .spacing(0)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .into()
+        };
+    main_view = column![main_view, status_bar(state)].into();
 
+    if let Some(tutorial) = tutorial_view(state) {
+        main_view = stack![main_view, tutorial].into();
+    }
     main_view = view_dialogue(state, main_view);
+    if state.show_update_timings {
+        main_view = stack![main_view, update_timings_view(state)].into();
+    }
     main_view
 }
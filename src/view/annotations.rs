@@ -0,0 +1,103 @@
+// Module for editing the properties of the main area's screens in the side panel: their
+// annotations (e.g. enemy density), and whether the camera is locked to them
+use iced::{
+    alignment::Vertical,
+    widget::{
+        button, checkbox, column, horizontal_space, row, scrollable, text, text_input, Column,
+    },
+    Element,
+};
+use iced_fonts::BOOTSTRAP_FONT;
+
+use crate::{
+    annotations::annotation,
+    camera_locks::is_camera_locked,
+    message::Message,
+    state::{AreaPosition, EditorState},
+};
+
+// The annotation typed into a screen's field: its digits, with an empty field clearing it.
+fn parse_value(input: &str) -> Option<u8> {
+    let digits: String = input.chars().filter(char::is_ascii_digit).collect();
+    digits.parse::<u32>().ok().map(|v| v.min(255) as u8)
+}
+
+fn screen_row(state: &EditorState, screen: (u8, u8)) -> Element<'_, Message> {
+    let area_id = state.main_area_id.clone();
+    let value = annotation(state.main_area(), screen);
+    let locked = is_camera_locked(state.main_area(), screen);
+    let lock_area_id = area_id.clone();
+    row![
+        text(format!("Screen {}, {}", screen.0, screen.1)).width(100),
+        text_input("None", &value.map(|v| v.to_string()).unwrap_or_default())
+            .on_input(move |x| Message::SetScreenAnnotation {
+                position: AreaPosition::Main,
+                area_id: area_id.clone(),
+                screen,
+                value: parse_value(&x),
+            })
+            .width(80),
+        checkbox("Camera locked", locked).on_toggle(move |locked| {
+            Message::SetCameraLocked {
+                position: AreaPosition::Main,
+                area_id: lock_area_id.clone(),
+                screen,
+                locked,
+            }
+        }),
+    ]
+    .spacing(10)
+    .align_y(Vertical::Center)
+    .into()
+}
+
+pub fn annotations_view(state: &EditorState) -> Element<'_, Message> {
+    let area = state.main_area();
+    let header = row![
+        text(format!("Screens of {}", area.name)),
+        horizontal_space(),
+        button(text("\u{F62A}").font(BOOTSTRAP_FONT))
+            .style(button::secondary)
+            .on_press(Message::ShowAnnotations(false)),
+    ]
+    .spacing(10)
+    .align_y(Vertical::Center);
+
+    let mut list = Column::new().spacing(5);
+    for y in 0..area.size.1 {
+        for x in 0..area.size.0 {
+            list = list.push(screen_row(state, (x, y)));
+        }
+    }
+    column![
+        header,
+        row![
+            checkbox("Show on the area", state.show_annotations)
+                .on_toggle(|_| Message::ToggleAnnotations),
+            horizontal_space(),
+            button(text("Export JSON")).on_press(Message::ExportAnnotations),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+        text(
+            "A number per screen (0-255), e.g. difficulty or enemy density. Clear a field to \
+            remove its annotation. The export lists the annotated screens of all areas and themes."
+        )
+        .size(12),
+        row![
+            text(
+                "Camera-locked screens (outlined in the area) keep the camera within them. \
+                The export is a table of them for the theme, for the hack's camera code."
+            )
+            .size(12),
+            button(text("Export ASM")).on_press(Message::ExportCameraLocks),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+        scrollable(list),
+    ]
+    .spacing(10)
+    .padding(10)
+    .width(420)
+    .into()
+}
@@ -1,27 +1,66 @@
 // Module for displaying/editing an area
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
 use hashbrown::HashMap;
+use iced::advanced::image::Handle;
 use iced::{
     alignment::Vertical,
-    mouse,
+    keyboard, mouse,
     widget::{
-        button, canvas, column, container, pick_list, row,
+        button, canvas, column, container, horizontal_space, mouse_area, pick_list, progress_bar,
+        responsive, row, scrollable,
         scrollable::{Direction, Scrollbar},
-        stack, text, text_input, Scrollable, Space,
+        stack, text, text_input, Column, Container, Row, Scrollable, Space,
     },
-    Element, Length, Padding, Point, Rectangle, Size,
+    ContentFit, Element, Length, Padding, Point, Rectangle, Size, Vector,
 };
 use iced_aw::number_input;
 
 use crate::{
-    helpers::{alpha_blend, scale_color},
+    animated_tiles::ANIMATED_SLOT_COUNT,
+    annotations::ScreenAnnotation,
+    area_diff::{compare_areas, DiffBase},
+    area_manifest::AreaManifestEntry,
+    area_picker::AreaPicker,
+    area_properties::{music_stage_count, AreaPropertyEdit, MUSIC_STAGES},
+    area_raster::{
+        RasterHighlights, ScreenRasterCache, ScreenRasterizer, RASTER_SIZE, SCREEN_PIXELS,
+    },
+    area_shapes::{shape_cells, AreaCell, AreaShape},
+    auto_pan::{edge_velocity, AutoPan, PanAction},
+    compile_check::{
+        CompileReport, CHAR_BUDGET, HUD_PALETTE_ROW_BUDGET, PALETTE_ROW_BUDGET, TILE16_BUDGET,
+        TILE32_BUDGET,
+    },
+    dirty,
+    entrances::{entrance_at, Entrance, EntranceKind, MARKER_SIZE},
+    heatmap::ScreenHeat,
+    helpers::collision_color,
+    map_compression::{AreaCompression, MAP_DATA_SPACE},
     message::{Message, SelectionSource},
+    palette_replace::{missing_tile_count, palette_cells},
+    palette_slots::area_palettes,
+    secrets::{secret_at, snap_position, Secret},
+    sprite_preview::{sprite_origin, sprite_raster, SPRITE_RASTER_SIZE},
     state::{
-        Area, AreaId, AreaPosition, ColorIdx, EditorState, Focus, Palette, PaletteId, TileBlock,
-        TileCoord, TileIdx, Tool,
+        Area, AreaId, AreaPosition, CollisionType, ColorIdx, ColorRGB, EditorState, Focus, Guide,
+        Layer, Palette, PaletteId, PickListMenu, ThemeName, TileBlock, TileCoord, TileIdx, Tool,
     },
+    travel::{travel_at, TravelPoint},
+    vram_usage::ScreenUsage,
 };
 
-use super::modal_background_style;
+use super::{
+    labels::label_preview,
+    macros::macro_controls,
+    minimap::minimap_view,
+    modal_background_style,
+    ruler::{ruler_view, RulerAxis, GUIDE_COLOR, RULER_SIZE},
+};
 
 // We use two separate canvases: one for drawing the tile raster and one for the tile selection.
 // This is to work around a limitation in Iced's rendering pipeline that does not allow drawing
@@ -38,26 +77,69 @@ struct AreaGrid<'a> {
     // thickness: f32,
     palette_only_brush: bool,
     tile_block: &'a TileBlock,
+    // Grid that the brush snaps to, while stamping a metatile:
+    stamp_grid: Option<TileCoord>,
     identify_tile: bool,
     palette_idx: usize,
     tile_idx: Option<TileIdx>,
     identify_color: bool,
     color_idx: Option<ColorIdx>,
     tool: Tool,
+    // Picking the selected color from the next click:
+    eyedropper: bool,
+    show_collision: bool,
+    collision_brush: CollisionType,
+    sprite_preview: bool,
+    // Opacity of the overlay layer, drawn over the screens:
+    overlay_opacity: f32,
+    // The part of the canvas that is scrolled into view, outside of which screens aren't drawn:
+    visible: Rectangle,
 }
 
+// Screens within this distance (in logical pixels) of the visible part of an area are drawn
+// too, so that they're ready if the scroll position has changed without being reported yet:
+const CULLING_MARGIN: f32 = 256.0;
+
 #[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
 enum InternalStateAction {
     #[default]
     None,
     Selecting,
     Brushing,
+    PaintingCollision,
+    // Dragging out a rectangle or line, from the given position:
+    Drawing(Point<TileCoord>),
 }
 
-#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[derive(Default)]
 struct InternalState {
     action: InternalStateAction,
     coords: Option<Point<TileCoord>>,
+    // Time and position of the last left click, for detecting double-clicks:
+    last_click: Option<(Instant, Point<TileCoord>)>,
+    // Images of the area's screens, kept between frames:
+    rasters: RefCell<ScreenRasterCache>,
+    // Whether Ctrl is held, for zooming with the mouse wheel:
+    zoom_modifier: bool,
+    // Whether the view is being panned, with the cursor near its edge (see `auto_pan`):
+    panning: bool,
+}
+
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+// Scrolling by this many pixels (e.g. on a touchpad) counts as one step of the mouse wheel:
+const WHEEL_PIXELS_PER_STEP: f32 = 50.0;
+
+// Padding around the area canvas in its scrollable, making room for the scrollbars:
+pub const AREA_SCROLLBAR_PADDING: f32 = 16.0;
+
+// The tile of an area under a point of its canvas (clamped to the area).
+pub fn area_tile_at(p: Point, size: (u8, u8), pixel_size: f32) -> Point<TileCoord> {
+    let x = f32::max(p.x - 1.0 - pixel_size / 2.0, 0.0) / (8.0 * pixel_size);
+    let y = f32::max(p.y - 1.0 - pixel_size / 2.0, 0.0) / (8.0 * pixel_size);
+    Point {
+        x: (x as TileCoord).min(size.0 as TileCoord * 32 - 1),
+        y: (y as TileCoord).min(size.1 as TileCoord * 32 - 1),
+    }
 }
 
 fn clamped_position_in(
@@ -66,14 +148,170 @@ fn clamped_position_in(
     size: (u8, u8),
     pixel_size: f32,
 ) -> Point<TileCoord> {
-    let x = f32::max(p.x - bounds.x - 1.0 - pixel_size / 2.0, 0.0) / (8.0 * pixel_size);
-    let y = f32::max(p.y - bounds.y - 1.0 - pixel_size / 2.0, 0.0) / (8.0 * pixel_size);
-    Point {
-        x: (x as TileCoord).min(size.0 as TileCoord * 32 - 1),
-        y: (y as TileCoord).min(size.1 as TileCoord * 32 - 1),
+    area_tile_at(Point::new(p.x - bounds.x, p.y - bounds.y), size, pixel_size)
+}
+
+impl<'a> AreaGrid<'a> {
+    // The area pixel under a point (past the canvas's one-pixel border).
+    fn pixel_at(&self, p: Point, bounds: iced::Rectangle) -> Point<u16> {
+        let x = ((p.x - bounds.x) / self.pixel_size - 1.0).max(0.0) as u16;
+        let y = ((p.y - bounds.y) / self.pixel_size - 1.0).max(0.0) as u16;
+        Point {
+            x: x.min(self.area.size.0 as u16 * 256 - 1),
+            y: y.min(self.area.size.1 as u16 * 256 - 1),
+        }
+    }
+
+    fn drawn_shape(&self, start: Point<TileCoord>, end: Point<TileCoord>) -> AreaShape {
+        match self.tool {
+            Tool::Line => AreaShape::Line { start, end },
+            _ => AreaShape::Rectangle { start, end },
+        }
+    }
+
+    // Set the collision type of the tile at the given position (unless it already has it).
+    fn collision_message(&self, coords: Point<TileCoord>) -> Option<Message> {
+        if self.tile_collision(coords)? == self.collision_brush {
+            return None;
+        }
+        let palette_id = self.area.get_palette(coords.x, coords.y).ok()?;
+        let tile_idx = self.area.get_tile(coords.x, coords.y).ok()?;
+        Some(Message::SetTileCollision {
+            palette_id,
+            tile_idx,
+            collision: self.collision_brush,
+        })
+    }
+
+    fn tile_collision(&self, coords: Point<TileCoord>) -> Option<CollisionType> {
+        let palette_id = self.area.get_palette(coords.x, coords.y).ok()?;
+        let tile_idx = self.area.get_tile(coords.x, coords.y).ok()?;
+        let palette_idx = *self.palettes_id_idx_map.get(&palette_id)?;
+        Some(
+            self.palettes[palette_idx]
+                .tiles
+                .get(tile_idx as usize)?
+                .collision,
+        )
+    }
+
+    // Where brushing at the given position places the brush.
+    fn brush_coords(&self, coords: Point<TileCoord>) -> Point<TileCoord> {
+        match self.stamp_grid {
+            Some(n) => Point::new(coords.x / n * n, coords.y / n * n),
+            None => coords,
+        }
+    }
+
+    // The cells that brushing at the given position would set.
+    fn brush_cells(&self, base: Point<TileCoord>) -> Vec<AreaCell> {
+        let mut cells = vec![];
+        for ty in 0..self.tile_block.size.1 as usize {
+            for tx in 0..self.tile_block.size.0 as usize {
+                let x = base.x + tx as TileCoord;
+                let y = base.y + ty as TileCoord;
+                if x as usize >= self.area.size.0 as usize * 32
+                    || y as usize >= self.area.size.1 as usize * 32
+                {
+                    continue;
+                }
+                if !self.tile_block.covers(tx, ty) {
+                    continue;
+                }
+                let palette = self.tile_block.palettes[ty][tx];
+                // TODO: draw some indicator of the broken tile (due to invalid palette reference)
+                let Some(&palette_idx) = self.palettes_id_idx_map.get(&palette) else {
+                    continue;
+                };
+                let (tile, flip) = if self.palette_only_brush {
+                    // TODO: indicate out-of-bounds tile index with some consistent broken tile indicator
+                    let num_tiles = self.palettes[palette_idx].tiles.len() as TileIdx;
+                    let tile = self.area.get_tile(x, y).unwrap();
+                    (
+                        tile.min(num_tiles.saturating_sub(1)),
+                        self.area.get_flip(x, y).unwrap(),
+                    )
+                } else {
+                    (self.tile_block.tiles[ty][tx], self.tile_block.flips[ty][tx])
+                };
+                cells.push(AreaCell {
+                    x,
+                    y,
+                    palette,
+                    tile,
+                    flip,
+                });
+            }
+        }
+        cells
+    }
+
+    // Start, update, or stop panning the view, if the cursor is (or was) near its edge.
+    fn auto_pan_message(
+        &self,
+        state: &mut InternalState,
+        action: PanAction,
+        cursor: mouse::Cursor,
+        bounds: Rectangle,
+    ) -> Option<Message> {
+        let p = cursor.position()?;
+        let cursor = Point::new(
+            p.x - bounds.x - self.visible.x,
+            p.y - bounds.y - self.visible.y,
+        );
+        let velocity = edge_velocity(cursor, self.visible.size());
+        if velocity == Vector::ZERO && !state.panning {
+            return None;
+        }
+        state.panning = velocity != Vector::ZERO;
+        Some(Message::SetAutoPan(state.panning.then_some(AutoPan {
+            position: self.position,
+            action,
+            velocity,
+            cursor,
+            viewport: self.visible.size(),
+        })))
+    }
+
+    fn shape_message(&self, shape: AreaShape) -> Message {
+        Message::AreaShape {
+            position: self.position,
+            area_id: self.area_id.clone(),
+            shape,
+            selection: self.tile_block.clone(),
+            palette_only: self.palette_only_brush,
+        }
     }
 }
 
+// Draw an image of part of the area, with its top-left pixel (of padding) at the given position
+// in area pixels.
+fn draw_raster(
+    frame: &mut canvas::Frame,
+    handle: Handle,
+    position: Point<usize>,
+    size: Size<usize>,
+    pixel_size: f32,
+    opacity: f32,
+) {
+    let image = iced::advanced::image::Image::new(handle)
+        .filter_method(iced::widget::image::FilterMethod::Nearest)
+        .opacity(opacity);
+    frame.draw_image(
+        Rectangle::new(
+            Point::new(
+                position.x as f32 * pixel_size,
+                position.y as f32 * pixel_size,
+            ),
+            Size::new(
+                size.width as f32 * pixel_size,
+                size.height as f32 * pixel_size,
+            ),
+        ),
+        image,
+    );
+}
+
 impl<'a> canvas::Program<Message> for AreaGrid<'a> {
     type State = InternalState;
 
@@ -95,9 +333,72 @@ impl<'a> canvas::Program<Message> for AreaGrid<'a> {
             state.coords = None;
         }
         match event {
+            canvas::Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                state.zoom_modifier = modifiers.control();
+            }
             canvas::Event::Mouse(mouse_event) => match mouse_event {
+                mouse::Event::WheelScrolled { delta } if state.zoom_modifier => {
+                    let Some(anchor) = cursor.position_in(bounds) else {
+                        return (canvas::event::Status::Ignored, None);
+                    };
+                    let steps = match delta {
+                        mouse::ScrollDelta::Lines { y, .. } => y,
+                        mouse::ScrollDelta::Pixels { y, .. } => y / WHEEL_PIXELS_PER_STEP,
+                    };
+                    return (
+                        canvas::event::Status::Captured,
+                        Some(Message::ZoomArea {
+                            position: self.position,
+                            steps,
+                            anchor: Vector::new(anchor.x, anchor.y),
+                        }),
+                    );
+                }
                 mouse::Event::ButtonPressed(btn @ (mouse::Button::Left | mouse::Button::Right)) => {
                     if let Some(p) = cursor.position_over(bounds) {
+                        if btn == mouse::Button::Left && self.eyedropper {
+                            return (
+                                canvas::event::Status::Captured,
+                                Some(Message::PickAreaColor {
+                                    position: self.position,
+                                    pixel: self.pixel_at(p, bounds),
+                                }),
+                            );
+                        }
+                        let coords =
+                            clamped_position_in(p, bounds, self.area.size, self.pixel_size);
+                        if btn == mouse::Button::Left
+                            && matches!(self.tool, Tool::Rectangle | Tool::Line)
+                        {
+                            state.action = InternalStateAction::Drawing(coords);
+                            return (canvas::event::Status::Captured, None);
+                        }
+                        if self.tool == Tool::Collision {
+                            if btn == mouse::Button::Right {
+                                // Pick up the collision type of the tile:
+                                return (
+                                    canvas::event::Status::Captured,
+                                    self.tile_collision(coords).map(Message::SetCollisionBrush),
+                                );
+                            }
+                            state.action = InternalStateAction::PaintingCollision;
+                            return (
+                                canvas::event::Status::Captured,
+                                self.collision_message(coords),
+                            );
+                        }
+                        if btn == mouse::Button::Left && self.tool == Tool::MagicWand {
+                            return (
+                                canvas::event::Status::Captured,
+                                Some(Message::SelectRegion(self.position, coords)),
+                            );
+                        }
+                        if btn == mouse::Button::Left && self.tool == Tool::Fill {
+                            return (
+                                canvas::event::Status::Captured,
+                                Some(self.shape_message(AreaShape::Fill(coords))),
+                            );
+                        }
                         if self.tool == Tool::Brush && btn == mouse::Button::Left {
                             state.action = InternalStateAction::Brushing;
                             let coords =
@@ -107,12 +408,37 @@ impl<'a> canvas::Program<Message> for AreaGrid<'a> {
                                 Some(Message::AreaBrush {
                                     position: self.position,
                                     area_id: self.area_id.clone(),
-                                    coords,
+                                    coords: self.brush_coords(coords),
                                     selection: self.tile_block.clone(),
                                     palette_only: self.palette_only_brush,
                                 }),
                             );
                         } else {
+                            let coords =
+                                clamped_position_in(p, bounds, self.area.size, self.pixel_size);
+                            if btn == mouse::Button::Left {
+                                let now = Instant::now();
+                                let last_click = state.last_click.replace((now, coords));
+                                if let Some((time, last_coords)) = last_click {
+                                    if last_coords == coords
+                                        && now.duration_since(time) < DOUBLE_CLICK_TIME
+                                    {
+                                        state.last_click = None;
+                                        if let (Ok(palette_id), Ok(tile_idx)) = (
+                                            self.area.get_palette(coords.x, coords.y),
+                                            self.area.get_tile(coords.x, coords.y),
+                                        ) {
+                                            return (
+                                                canvas::event::Status::Captured,
+                                                Some(Message::OpenTileGraphics {
+                                                    palette_id,
+                                                    tile_idx,
+                                                }),
+                                            );
+                                        }
+                                    }
+                                }
+                            }
                             state.action = InternalStateAction::Selecting;
                             return (
                                 canvas::event::Status::Captured,
@@ -125,9 +451,28 @@ impl<'a> canvas::Program<Message> for AreaGrid<'a> {
                     };
                 }
                 mouse::Event::ButtonReleased(mouse::Button::Left | mouse::Button::Right) => {
-                    let state0 = *state;
+                    let action = state.action;
                     state.action = InternalStateAction::None;
-                    if state0.action == InternalStateAction::Selecting {
+                    let panning = std::mem::take(&mut state.panning);
+                    if panning && action == InternalStateAction::Brushing {
+                        // (The end of a selection stops the panning too.)
+                        return (
+                            canvas::event::Status::Captured,
+                            Some(Message::SetAutoPan(None)),
+                        );
+                    }
+                    if let InternalStateAction::Drawing(start) = action {
+                        let Some(end) = state.coords else {
+                            // Released outside of the area: cancel the shape.
+                            return (canvas::event::Status::Captured, None);
+                        };
+                        let shape = self.drawn_shape(start, end);
+                        return (
+                            canvas::event::Status::Captured,
+                            Some(self.shape_message(shape)),
+                        );
+                    }
+                    if action == InternalStateAction::Selecting {
                         let coords = if let Some(p) = cursor.position() {
                             clamped_position_in(p, bounds, self.area.size, self.pixel_size)
                         } else if let Some(c) = self.end_coords {
@@ -143,7 +488,16 @@ impl<'a> canvas::Program<Message> for AreaGrid<'a> {
                 }
                 mouse::Event::CursorMoved { .. } => match state.action {
                     InternalStateAction::None => {}
+                    InternalStateAction::Drawing(_) => {
+                        // Only the preview changes, until the button is released:
+                        return (canvas::event::Status::Captured, None);
+                    }
                     InternalStateAction::Selecting => {
+                        if let Some(message) =
+                            self.auto_pan_message(state, PanAction::Select, cursor, bounds)
+                        {
+                            return (canvas::event::Status::Captured, Some(message));
+                        }
                         if let Some(p) = cursor.position() {
                             return (
                                 canvas::event::Status::Captured,
@@ -156,7 +510,20 @@ impl<'a> canvas::Program<Message> for AreaGrid<'a> {
                             );
                         }
                     }
+                    InternalStateAction::PaintingCollision => {
+                        if let Some(coords) = state.coords {
+                            return (
+                                canvas::event::Status::Captured,
+                                self.collision_message(coords),
+                            );
+                        }
+                    }
                     InternalStateAction::Brushing => {
+                        if let Some(message) =
+                            self.auto_pan_message(state, PanAction::Brush, cursor, bounds)
+                        {
+                            return (canvas::event::Status::Captured, Some(message));
+                        }
                         if let Some(p) = cursor.position() {
                             let coords =
                                 clamped_position_in(p, bounds, self.area.size, self.pixel_size);
@@ -165,7 +532,7 @@ impl<'a> canvas::Program<Message> for AreaGrid<'a> {
                                 Some(Message::AreaBrush {
                                     position: self.position,
                                     area_id: self.area_id.clone(),
-                                    coords,
+                                    coords: self.brush_coords(coords),
                                     selection: self.tile_block.clone(),
                                     palette_only: self.palette_only_brush,
                                 }),
@@ -186,171 +553,98 @@ impl<'a> canvas::Program<Message> for AreaGrid<'a> {
         renderer: &iced::Renderer,
         _theme: &iced::Theme,
         bounds: iced::Rectangle,
-        _cursor: mouse::Cursor,
+        cursor: mouse::Cursor,
     ) -> Vec<canvas::Geometry> {
         let mut frame = canvas::Frame::new(renderer, bounds.size());
-        let mut color_bytes: Vec<Vec<[u8; 3]>> = vec![];
-
-        for i in 0..self.palettes.len() {
-            let mut colors = self.palettes[i].colors.clone();
-            colors[0] = self.area.bg_color;
-            let cb = colors
-                .iter()
-                .map(|&[r, g, b]| [scale_color(r), scale_color(g), scale_color(b)])
-                .collect();
-            color_bytes.push(cb);
-        }
-
-        // Add a pixel of transparent padding around the image, since Iced's
-        // "nearest neighbor" filter results in the edge pixels having the wrong size.
-        let num_cols = self.area.size.1 as usize * 256 + 2;
-        let num_rows = self.area.size.0 as usize * 256 + 2;
-        let mut data: Vec<u8> = vec![0; num_rows * num_cols * 4];
-        let col_stride = 4;
-        let row_stride = num_cols * col_stride;
+        let rasterizer = ScreenRasterizer {
+            area: self.area,
+            palettes: self.palettes,
+            palettes_id_idx_map: self.palettes_id_idx_map,
+            highlights: RasterHighlights {
+                identify_tile: self.identify_tile,
+                identify_color: self.identify_color,
+                palette_idx: self.palette_idx,
+                tile_idx: self.tile_idx,
+                color_idx: self.color_idx,
+                show_collision: self.show_collision,
+            },
+        };
+        let mut rasters = state.rasters.borrow_mut();
+        let visible = self.visible.expand(CULLING_MARGIN);
+        let screen_size = 256.0 * self.pixel_size;
         for sy in 0..self.area.size.1 as usize {
             for sx in 0..self.area.size.0 as usize {
-                let screen = &self.area.screens[sy * self.area.size.0 as usize + sx];
-                let screen_addr = (sy * 256 + 1) * row_stride + (sx * 256 + 1) * col_stride;
-                for ty in 0..32 {
-                    for tx in 0..32 {
-                        let palette_id = screen.palettes[ty][tx];
-                        let Some(&palette_idx) = self.palettes_id_idx_map.get(&palette_id) else {
-                            // TODO: draw some indicator of the broken tile (due to invalid palette reference)
-                            continue;
-                        };
-                        let tile_idx = screen.tiles[ty][tx];
-                        if tile_idx as usize >= self.palettes[palette_idx].tiles.len() {
-                            continue;
-                        }
-                        let flip = screen.flips[ty][tx];
-                        let tile = self.palettes[palette_idx].tiles[tile_idx as usize];
-                        let tile = flip.apply_to_tile(tile);
-                        let cb = &color_bytes[palette_idx];
-                        let mut tile_addr = screen_addr + ty * 8 * row_stride + tx * 8 * col_stride;
-
-                        let illegal_flip = match flip {
-                            crate::state::Flip::None => false,
-                            crate::state::Flip::Horizontal => !tile.h_flippable,
-                            crate::state::Flip::Vertical => !tile.v_flippable,
-                            crate::state::Flip::Both => !tile.h_flippable || !tile.v_flippable,
-                        };
-                        let identify_tile = self.identify_tile
-                            && self.palette_idx == palette_idx
-                            && self.tile_idx == Some(tile_idx);
-                        for py in 0..8 {
-                            let mut addr = tile_addr;
-                            for px in 0..8 {
-                                let color_idx = tile.pixels[py][px];
-                                let mut color = cb[color_idx as usize];
-                                let identify_color = self.identify_color
-                                    && self.color_idx == Some(color_idx)
-                                    && self.palette_idx == palette_idx;
-
-                                if illegal_flip && !self.identify_tile && !self.identify_color {
-                                    let red_highlight = [255, 0, 0];
-                                    let alpha = 0.5;
-                                    color = alpha_blend(color, red_highlight, alpha);
-                                }
-
-                                let pink_highlight = [255, 105, 180];
-                                if identify_tile {
-                                    let alpha = 0.5;
-                                    color = alpha_blend(color, pink_highlight, alpha);
-                                } else if identify_color {
-                                    color = pink_highlight;
-                                }
-                                data[addr..(addr + 3)].copy_from_slice(&color);
-                                data[addr + 3] = 255;
-                                addr += 4;
-                            }
-                            tile_addr += row_stride;
-                        }
-                    }
+                let screen_bounds = Rectangle::new(
+                    Point::new(
+                        (sx as f32 * 256.0 + 1.0) * self.pixel_size,
+                        (sy as f32 * 256.0 + 1.0) * self.pixel_size,
+                    ),
+                    Size::new(screen_size, screen_size),
+                );
+                if !visible.intersects(&screen_bounds) {
+                    continue;
+                }
+                let screen_idx = sy * self.area.size.0 as usize + sx;
+                let handle = rasters.get(&rasterizer, screen_idx);
+                draw_raster(
+                    &mut frame,
+                    handle,
+                    Point::new(sx * SCREEN_PIXELS, sy * SCREEN_PIXELS),
+                    Size::new(RASTER_SIZE, RASTER_SIZE),
+                    self.pixel_size,
+                    1.0,
+                );
+                if let Some(handle) = rasters.get_overlay(&rasterizer, screen_idx) {
+                    draw_raster(
+                        &mut frame,
+                        handle,
+                        Point::new(sx * SCREEN_PIXELS, sy * SCREEN_PIXELS),
+                        Size::new(RASTER_SIZE, RASTER_SIZE),
+                        self.pixel_size,
+                        self.overlay_opacity,
+                    );
                 }
             }
         }
 
+        let mut preview_cells = vec![];
         if self.tool == Tool::Brush && self.end_coords.is_none() {
             // Overlay the block to be pasted/brushed onto the area:
-            if let Some(Point {
-                x: base_x,
-                y: base_y,
-            }) = state.coords
-            {
-                let base_addr =
-                    (base_y * 8 + 1) as usize * row_stride + (base_x * 8 + 1) as usize * col_stride;
-                let alpha = 0.75;
-                for ty in 0..self.tile_block.size.1 as usize {
-                    for tx in 0..self.tile_block.size.0 as usize {
-                        if tx + base_x as usize >= self.area.size.0 as usize * 32
-                            || ty + base_y as usize >= self.area.size.1 as usize * 32
-                        {
-                            continue;
-                        }
-                        let palette_id = self.tile_block.palettes[ty][tx];
-                        if let Some(&palette_idx) = self.palettes_id_idx_map.get(&palette_id) {
-                            let tile = if self.palette_only_brush {
-                                let x1 = base_x + tx as TileCoord;
-                                let y1 = base_y + ty as TileCoord;
-                                let tile_idx = self.area.get_tile(x1, y1).unwrap();
-                                let flip = self.area.get_flip(x1, y1).unwrap();
-                                let clamped_tile_idx = std::cmp::min(
-                                    tile_idx,
-                                    self.palettes[palette_idx].tiles.len() as TileIdx - 1,
-                                );
-                                // TODO: indicate out-of-bounds tile index with some consistent broken tile indicator
-                                let t = *self.palettes[palette_idx]
-                                    .tiles
-                                    .get(clamped_tile_idx as usize)
-                                    .unwrap();
-                                flip.apply_to_tile(t)
-                            } else {
-                                let tile_idx = self.tile_block.tiles[ty][tx];
-                                let flip = self.tile_block.flips[ty][tx];
-                                let t = self.palettes[palette_idx].tiles[tile_idx as usize];
-                                flip.apply_to_tile(t)
-                            };
-                            let cb = &color_bytes[palette_idx];
-                            let mut tile_addr =
-                                base_addr + ty * 8 * row_stride + tx * 8 * col_stride;
-                            for py in 0..8 {
-                                let mut addr = tile_addr;
-                                for px in 0..8 {
-                                    let color_idx = tile.pixels[py][px];
-                                    let old_color = [data[addr], data[addr + 1], data[addr + 2]];
-                                    let new_color = cb[color_idx as usize];
-                                    let blended_color = alpha_blend(old_color, new_color, alpha);
-                                    data[addr..addr + 3].copy_from_slice(&blended_color);
-                                    addr += 4;
-                                }
-                                tile_addr += row_stride;
-                            }
-                        } else {
-                            // TODO: draw some indicator of the broken tile (due to invalid palette reference)
-                        }
-                    }
-                }
+            if let Some(base) = state.coords {
+                preview_cells = self.brush_cells(self.brush_coords(base));
             }
         }
+        if let (InternalStateAction::Drawing(start), Some(end)) = (state.action, state.coords) {
+            // Preview the shape being dragged out:
+            let shape = self.drawn_shape(start, end);
+            preview_cells = shape_cells(self.area, shape, self.tile_block, self.palette_only_brush);
+        }
+        if let Some(preview) = rasterizer.rasterize_cells(&preview_cells) {
+            draw_raster(
+                &mut frame,
+                Handle::from_rgba(preview.width as u32, preview.height as u32, preview.data),
+                Point::new(preview.x as usize * 8, preview.y as usize * 8),
+                Size::new(preview.width, preview.height),
+                self.pixel_size,
+                0.75,
+            );
+        }
 
-        let image = iced::advanced::image::Image::new(iced::advanced::image::Handle::from_rgba(
-            num_cols as u32,
-            num_rows as u32,
-            data,
-        ))
-        .filter_method(iced::widget::image::FilterMethod::Nearest);
-
-        frame.draw_image(
-            Rectangle::new(
-                Point::new(0.0, 0.0),
-                Size {
-                    width: num_cols as f32 * self.pixel_size,
-                    height: num_rows as f32 * self.pixel_size,
-                },
-            ),
-            image,
-        );
+        if self.sprite_preview {
+            if let Some(p) = cursor.position_over(bounds) {
+                let origin = sprite_origin(self.area, self.pixel_at(p, bounds));
+                let data =
+                    sprite_raster(self.area, self.palettes, self.palettes_id_idx_map, origin);
+                draw_raster(
+                    &mut frame,
+                    Handle::from_rgba(SPRITE_RASTER_SIZE as u32, SPRITE_RASTER_SIZE as u32, data),
+                    Point::new(origin.x as usize, origin.y as usize),
+                    Size::new(SPRITE_RASTER_SIZE, SPRITE_RASTER_SIZE),
+                    self.pixel_size,
+                    1.0,
+                );
+            }
+        }
 
         vec![frame.into_geometry()]
     }
@@ -361,7 +655,7 @@ impl<'a> canvas::Program<Message> for AreaGrid<'a> {
         bounds: iced::Rectangle,
         cursor: mouse::Cursor,
     ) -> mouse::Interaction {
-        if self.tool == Tool::Brush && cursor.is_over(bounds) {
+        if (self.tool != Tool::Select || self.eyedropper) && cursor.is_over(bounds) {
             mouse::Interaction::Crosshair
         } else {
             mouse::Interaction::default()
@@ -379,21 +673,203 @@ struct AreaSelect {
     tool: Tool,
     show_grid: bool,
     grid_alpha: f32,
+    heat: Vec<ScreenHeat>,
+    annotations: Vec<ScreenAnnotation>,
+    // Screens that the camera is locked to, which are outlined:
+    locked_screens: Vec<(u8, u8)>,
+    // Tiles changed from the version the area is being compared with:
+    changed_tiles: Vec<(TileCoord, TileCoord)>,
+    guides: Vec<Guide>,
+    // Markers of the area's entrances, secrets, and travel points (when shown), which can be
+    // dragged to move them:
+    position: AreaPosition,
+    area_id: AreaId,
+    area_size: (u8, u8),
+    entrances: Vec<Entrance>,
+    secrets: Vec<Secret>,
+    travel: Vec<TravelPoint>,
+}
+
+const CAMERA_LOCK_COLOR: iced::Color = iced::Color::from_rgb(1.0, 0.5, 0.0);
+const CAMERA_LOCK_WIDTH: f32 = 3.0;
+const ANNOTATION_COLOR: iced::Color = iced::Color::from_rgba(1.0, 1.0, 1.0, 0.4);
+const DIFF_COLOR: iced::Color = iced::Color::from_rgba(1.0, 0.0, 1.0, 0.45);
+
+fn marker_color(kind: EntranceKind) -> iced::Color {
+    match kind {
+        EntranceKind::Entrance => iced::Color::from_rgb(1.0, 0.85, 0.0),
+        EntranceKind::Hole => iced::Color::from_rgb(0.7, 0.3, 1.0),
+        EntranceKind::Exit => iced::Color::from_rgb(0.0, 0.8, 1.0),
+    }
+}
+
+const SECRET_COLOR: iced::Color = iced::Color::from_rgb(0.2, 0.9, 0.3);
+const TRAVEL_COLOR: iced::Color = iced::Color::from_rgb(1.0, 0.4, 0.6);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Marker {
+    Entrance(usize),
+    Secret(usize),
+    Travel(usize),
+}
+
+#[derive(Default)]
+struct MarkerDrag {
+    // The marker being dragged, and its offset (in tiles) from the cursor:
+    marker: Option<(Marker, (i32, i32))>,
+    // Where the marker would be dropped:
+    target: Option<Point<TileCoord>>,
+}
+
+impl AreaSelect {
+    fn coords(&self, bounds: Rectangle, cursor: mouse::Cursor) -> Option<Point<TileCoord>> {
+        let p = cursor.position_over(bounds)?;
+        Some(clamped_position_in(
+            p,
+            bounds,
+            self.area_size,
+            self.pixel_size,
+        ))
+    }
+
+    // The marker at a position (travel points being drawn over entrances, and entrances over
+    // secrets).
+    fn marker_at(&self, x: TileCoord, y: TileCoord) -> Option<Marker> {
+        travel_at(&self.travel, x, y)
+            .map(Marker::Travel)
+            .or_else(|| entrance_at(&self.entrances, x, y).map(Marker::Entrance))
+            .or_else(|| secret_at(&self.secrets, x, y).map(Marker::Secret))
+    }
+
+    fn saved_position(&self, marker: Marker) -> Point<TileCoord> {
+        let (x, y) = match marker {
+            Marker::Entrance(idx) => self.entrances[idx].position,
+            Marker::Secret(idx) => self.secrets[idx].position,
+            Marker::Travel(idx) => self.travel[idx].position,
+        };
+        Point::new(x, y)
+    }
+
+    // Position of a marker, following the cursor while it's being dragged.
+    fn marker_position(&self, drag: &MarkerDrag, marker: Marker) -> Point<TileCoord> {
+        match (drag.marker, drag.target) {
+            (Some((m, _)), Some(target)) if m == marker => target,
+            _ => self.saved_position(marker),
+        }
+    }
+
+    fn move_message(&self, marker: Marker, coords: Point<TileCoord>) -> Message {
+        match marker {
+            Marker::Entrance(idx) => Message::MoveEntrance {
+                position: self.position,
+                area_id: self.area_id.clone(),
+                idx,
+                coords,
+            },
+            Marker::Secret(idx) => Message::SetSecret {
+                position: self.position,
+                area_id: self.area_id.clone(),
+                idx,
+                secret: Secret {
+                    position: (coords.x, coords.y),
+                    ..self.secrets[idx]
+                },
+            },
+            Marker::Travel(idx) => Message::SetTravelPoint {
+                position: self.position,
+                area_id: self.area_id.clone(),
+                idx,
+                point: TravelPoint {
+                    position: (coords.x, coords.y),
+                    ..self.travel[idx]
+                },
+            },
+        }
+    }
 }
 
 impl canvas::Program<Message> for AreaSelect {
-    // No internal state
-    type State = ();
+    type State = MarkerDrag;
+
+    fn update(
+        &self,
+        state: &mut MarkerDrag,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (canvas::event::Status, Option<Message>) {
+        if self.entrances.is_empty() && self.secrets.is_empty() && self.travel.is_empty() {
+            return (canvas::event::Status::Ignored, None);
+        }
+        let coords = self.coords(bounds, cursor);
+        match event {
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let Some(c) = coords else {
+                    return (canvas::event::Status::Ignored, None);
+                };
+                if let Some(marker) = self.marker_at(c.x, c.y) {
+                    let p = self.saved_position(marker);
+                    let offset = (p.x as i32 - c.x as i32, p.y as i32 - c.y as i32);
+                    state.marker = Some((marker, offset));
+                    state.target = Some(p);
+                    return (canvas::event::Status::Captured, None);
+                }
+            }
+            canvas::Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                if let (Some((marker, (dx, dy))), Some(c)) = (state.marker, coords) {
+                    let x = (c.x as i32 + dx).max(0) as TileCoord;
+                    let y = (c.y as i32 + dy).max(0) as TileCoord;
+                    // Secrets are on the 16x16 tile grid:
+                    let (x, y) = match marker {
+                        Marker::Entrance(_) | Marker::Travel(_) => (x, y),
+                        Marker::Secret(_) => snap_position(x, y),
+                    };
+                    state.target = Some(Point::new(x, y));
+                    return (canvas::event::Status::Captured, None);
+                }
+            }
+            canvas::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                let target = state.target.take();
+                if let Some((marker, _)) = state.marker.take() {
+                    let message = match (marker, target) {
+                        // Clicking a travel point (without moving it) shows its properties:
+                        (Marker::Travel(idx), t) if t == Some(self.saved_position(marker)) => {
+                            Some(Message::TravelPointDialogue {
+                                position: self.position,
+                                idx,
+                            })
+                        }
+                        _ => target
+                            .filter(|&t| t != self.saved_position(marker))
+                            .map(|coords| self.move_message(marker, coords)),
+                    };
+                    return (canvas::event::Status::Captured, message);
+                }
+            }
+            _ => {}
+        }
+        (canvas::event::Status::Ignored, None)
+    }
 
     fn draw(
         &self,
-        _state: &(),
+        state: &MarkerDrag,
         renderer: &iced::Renderer,
         _theme: &iced::Theme,
         bounds: iced::Rectangle,
         _cursor: mouse::Cursor,
     ) -> Vec<canvas::Geometry> {
-        if !self.selecting_active && !self.show_grid {
+        if !self.selecting_active
+            && !self.show_grid
+            && self.heat.is_empty()
+            && self.annotations.is_empty()
+            && self.locked_screens.is_empty()
+            && self.changed_tiles.is_empty()
+            && self.guides.is_empty()
+            && self.entrances.is_empty()
+            && self.secrets.is_empty()
+            && self.travel.is_empty()
+        {
             return vec![];
         }
 
@@ -432,6 +908,149 @@ impl canvas::Program<Message> for AreaSelect {
                 },
             );
         }
+        for h in &self.heat {
+            let x0 = h.position.0 as f32 * pixel_size_x * 256.0 + pixel_size_x / 2.0;
+            let y0 = h.position.1 as f32 * pixel_size_y * 256.0 + pixel_size_y / 2.0;
+            let size = Size {
+                width: pixel_size_x * 256.0,
+                height: pixel_size_y * 256.0,
+            };
+            let alpha = 0.15 + 0.45 * h.intensity;
+            frame.fill_rectangle(
+                Point::new(x0, y0),
+                size,
+                iced::Color::from_rgba(1.0, 0.2, 0.0, alpha),
+            );
+            frame.fill_text(canvas::Text {
+                content: h.count.to_string(),
+                position: Point::new(x0 + 4.0, y0 + 4.0),
+                color: iced::Color::WHITE,
+                ..canvas::Text::default()
+            });
+        }
+        for &(sx, sy) in &self.locked_screens {
+            let inset = CAMERA_LOCK_WIDTH / 2.0;
+            let top_left = Point::new(
+                sx as f32 * pixel_size_x * 256.0 + pixel_size_x / 2.0 + inset,
+                sy as f32 * pixel_size_y * 256.0 + pixel_size_y / 2.0 + inset,
+            );
+            let size = Size::new(
+                pixel_size_x * 256.0 - CAMERA_LOCK_WIDTH,
+                pixel_size_y * 256.0 - CAMERA_LOCK_WIDTH,
+            );
+            frame.stroke(
+                &canvas::Path::rectangle(top_left, size),
+                canvas::Stroke {
+                    style: canvas::stroke::Style::Solid(CAMERA_LOCK_COLOR),
+                    width: CAMERA_LOCK_WIDTH,
+                    line_dash: canvas::LineDash {
+                        segments: &[12.0, 6.0],
+                        offset: 0,
+                    },
+                    ..Default::default()
+                },
+            );
+        }
+        for a in &self.annotations {
+            let screen_size = Size::new(pixel_size_x * 256.0, pixel_size_y * 256.0);
+            let center = Point::new(
+                (a.screen.0 as f32 + 0.5) * screen_size.width + pixel_size_x / 2.0,
+                (a.screen.1 as f32 + 0.5) * screen_size.height + pixel_size_y / 2.0,
+            );
+            frame.fill_text(canvas::Text {
+                content: a.value.to_string(),
+                position: center,
+                color: ANNOTATION_COLOR,
+                size: iced::Pixels(screen_size.height / 3.0),
+                horizontal_alignment: iced::alignment::Horizontal::Center,
+                vertical_alignment: iced::alignment::Vertical::Center,
+                ..canvas::Text::default()
+            });
+        }
+        if !self.changed_tiles.is_empty() {
+            let path = canvas::Path::new(|p| {
+                for &(x, y) in &self.changed_tiles {
+                    p.rectangle(
+                        Point::new(
+                            x as f32 * pixel_size_x * 8.0 + pixel_size_x / 2.0,
+                            y as f32 * pixel_size_y * 8.0 + pixel_size_y / 2.0,
+                        ),
+                        Size::new(pixel_size_x * 8.0, pixel_size_y * 8.0),
+                    );
+                }
+            });
+            frame.fill(&path, DIFF_COLOR);
+        }
+        if !self.guides.is_empty() {
+            let path = canvas::Path::new(|p| {
+                for &g in &self.guides {
+                    match g {
+                        Guide::Vertical(t) => {
+                            let x = t as f32 * pixel_size_x * 8.0 + pixel_size_x / 2.0;
+                            p.move_to(Point::new(x, 0.0));
+                            p.line_to(Point::new(x, bounds.height));
+                        }
+                        Guide::Horizontal(t) => {
+                            let y = t as f32 * pixel_size_y * 8.0 + pixel_size_y / 2.0;
+                            p.move_to(Point::new(0.0, y));
+                            p.line_to(Point::new(bounds.width, y));
+                        }
+                    }
+                }
+            });
+            frame.stroke(
+                &path,
+                canvas::Stroke {
+                    style: canvas::stroke::Style::Solid(GUIDE_COLOR),
+                    width: 1.0,
+                    ..Default::default()
+                },
+            );
+        }
+        let markers = self
+            .secrets
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (Marker::Secret(i), SECRET_COLOR, s.label()))
+            .chain(
+                self.entrances
+                    .iter()
+                    .enumerate()
+                    .map(|(i, e)| (Marker::Entrance(i), marker_color(e.kind), e.label())),
+            )
+            .chain(
+                self.travel
+                    .iter()
+                    .enumerate()
+                    .map(|(i, t)| (Marker::Travel(i), TRAVEL_COLOR, t.label())),
+            );
+        for (marker, color, label) in markers {
+            let p = self.marker_position(state, marker);
+            let top_left = Point::new(
+                p.x as f32 * pixel_size_x * 8.0 + pixel_size_x / 2.0,
+                p.y as f32 * pixel_size_y * 8.0 + pixel_size_y / 2.0,
+            );
+            let size = Size::new(
+                MARKER_SIZE as f32 * pixel_size_x * 8.0,
+                MARKER_SIZE as f32 * pixel_size_y * 8.0,
+            );
+            frame.fill_rectangle(top_left, size, iced::Color { a: 0.35, ..color });
+            frame.stroke(
+                &canvas::Path::rectangle(top_left, size),
+                canvas::Stroke {
+                    style: canvas::stroke::Style::Solid(color),
+                    width: 2.0,
+                    ..Default::default()
+                },
+            );
+            frame.fill_text(canvas::Text {
+                content: label,
+                position: Point::new(top_left.x + 2.0, top_left.y + 1.0),
+                color: iced::Color::WHITE,
+                size: iced::Pixels(11.0),
+                ..canvas::Text::default()
+            });
+        }
         if self.selecting_active {
             let x0 = self.left as f32 * pixel_size_x * 8.0 + pixel_size_x / 2.0;
             let x1 = (self.right + 1) as f32 * pixel_size_x * 8.0 + pixel_size_x / 2.0;
@@ -469,14 +1088,27 @@ impl canvas::Program<Message> for AreaSelect {
 
     fn mouse_interaction(
         &self,
-        _interaction: &Self::State,
+        drag: &Self::State,
         bounds: iced::Rectangle,
         cursor: mouse::Cursor,
     ) -> mouse::Interaction {
+        if drag.marker.is_some() {
+            return mouse::Interaction::Grabbing;
+        }
+        if let Some(c) = self.coords(bounds, cursor) {
+            if self.marker_at(c.x, c.y).is_some() {
+                return mouse::Interaction::Grab;
+            }
+        }
         if cursor.is_over(bounds) {
             match self.tool {
                 Tool::Select => mouse::Interaction::default(),
-                Tool::Brush => mouse::Interaction::Crosshair,
+                Tool::Brush
+                | Tool::Rectangle
+                | Tool::Line
+                | Tool::Fill
+                | Tool::MagicWand
+                | Tool::Collision => mouse::Interaction::Crosshair,
             }
         } else {
             mouse::Interaction::default()
@@ -484,11 +1116,55 @@ impl canvas::Program<Message> for AreaSelect {
     }
 }
 
-pub fn area_grid_view(state: &EditorState, position: AreaPosition) -> Element<Message> {
+// The scrollable of an area view, for scrolling it to a given location.
+pub fn area_scrollable_id(position: AreaPosition) -> scrollable::Id {
+    match position {
+        AreaPosition::Main => scrollable::Id::new("main-area"),
+        AreaPosition::Side => scrollable::Id::new("side-area"),
+    }
+}
+
+// Tiles to highlight as changed: from the version the area is being compared with, from the
+// area in the other view (when comparing the two views), and by the palette replacement being
+// set up.
+fn changed_tiles(state: &EditorState, position: AreaPosition) -> Vec<(TileCoord, TileCoord)> {
+    let area = state.area(position);
+    let mut tiles = state
+        .area_diff
+        .as_ref()
+        .filter(|d| &d.area_id == state.area_id(position))
+        .map(|d| compare_areas(area, &d.base_area).tiles)
+        .unwrap_or_default();
+    let comparing = state.show_side_differences && state.side_area_shown();
+    if comparing {
+        let other = match position {
+            AreaPosition::Main => AreaPosition::Side,
+            AreaPosition::Side => AreaPosition::Main,
+        };
+        tiles.extend(compare_areas(area, state.area(other)).tiles);
+    }
+    let replacing = state
+        .palette_replace
+        .as_ref()
+        .filter(|r| &r.area_id == state.area_id(position))
+        .and_then(|r| r.from);
+    if let Some(from) = replacing {
+        tiles.extend(palette_cells(area, from));
+    }
+    tiles
+}
+
+// The scrollable canvases of an area view, drawing only the screens near the visible part.
+fn area_scrollable<'a>(
+    state: &'a EditorState,
+    position: AreaPosition,
+    guides: &[Guide],
+    visible: Rectangle,
+) -> Element<'a, Message> {
     let area = state.area(position);
     let num_cols = area.size.1 * 32;
     let num_rows = area.size.0 * 32;
-    let pixel_size = state.global_config.pixel_size;
+    let pixel_size = state.area_pixel_size(position);
 
     let mut left = 0;
     let mut right = 0;
@@ -518,12 +1194,19 @@ pub fn area_grid_view(state: &EditorState, position: AreaPosition) -> Element<Me
                 // thickness: 1.0,
                 palette_only_brush: state.palette_only_brush,
                 tile_block: &state.selected_tile_block,
+                stamp_grid: state.stamp_metatile().map(|m| m.size.tiles()),
                 identify_tile: state.identify_tile,
                 palette_idx: state.palette_idx,
                 tile_idx: state.tile_idx,
                 identify_color: state.identify_color,
                 color_idx: state.color_idx,
                 tool: state.tool,
+                eyedropper: state.eyedropper,
+                show_collision: state.show_collision,
+                collision_brush: state.collision_brush,
+                sprite_preview: state.show_sprite_preview,
+                overlay_opacity: state.global_config.overlay_opacity,
+                visible,
             })
             .width((num_cols as f32 * 8.0 + 2.0) * pixel_size)
             .height((num_rows as f32 * 8.0 + 2.0) * pixel_size),
@@ -539,49 +1222,382 @@ pub fn area_grid_view(state: &EditorState, position: AreaPosition) -> Element<Me
                 tool: state.tool,
                 show_grid: state.show_grid,
                 grid_alpha: state.global_config.grid_alpha,
+                heat: state
+                    .heatmap
+                    .as_ref()
+                    .map(|h| h.area_heat(state.area(position)))
+                    .unwrap_or_default(),
+                annotations: if state.show_annotations {
+                    area.annotations.clone()
+                } else {
+                    vec![]
+                },
+                locked_screens: area
+                    .screens
+                    .iter()
+                    .filter(|s| s.camera_locked)
+                    .map(|s| s.position)
+                    .collect(),
+                changed_tiles: changed_tiles(state, position),
+                guides: guides.to_vec(),
+                position,
+                area_id: state.area_id(position).clone(),
+                area_size: area.size,
+                entrances: if state.show_entrances {
+                    area.entrances.clone()
+                } else {
+                    vec![]
+                },
+                secrets: if state.show_secrets {
+                    area.secrets.clone()
+                } else {
+                    vec![]
+                },
+                travel: if state.show_travel {
+                    area.travel.clone()
+                } else {
+                    vec![]
+                },
             })
             .width((num_cols as f32 * 8.0 + 2.0) * pixel_size)
             .height((num_rows as f32 * 8.0 + 2.0) * pixel_size),
         ]]
-        .padding(Padding::new(0.0).right(16.0).bottom(16.0)),
+        .padding(
+            Padding::new(0.0)
+                .right(AREA_SCROLLBAR_PADDING)
+                .bottom(AREA_SCROLLBAR_PADDING),
+        ),
         Direction::Both {
             vertical: Scrollbar::default(),
             horizontal: Scrollbar::default(),
         },
     )
+    .id(area_scrollable_id(position))
+    .on_scroll(move |viewport| {
+        let offset = viewport.absolute_offset();
+        Message::AreaScrolled {
+            position,
+            offset: Vector::new(offset.x, offset.y),
+        }
+    })
     .width(Length::Fill)
     .height(Length::Fill)
     .into()
 }
 
+pub fn area_grid_view(state: &EditorState, position: AreaPosition) -> Element<'_, Message> {
+    let area = state.area(position);
+    let pixel_size = state.area_pixel_size(position);
+    let area_name = &state.area_id(position).area;
+    let guides = state
+        .guides
+        .get(area_name)
+        .map(|g| g.as_slice())
+        .unwrap_or_default();
+    let grid = responsive(move |size| {
+        let scroll = state.area_scroll(position);
+        let visible = Rectangle::new(Point::new(scroll.x, scroll.y), size);
+        let scrollable = area_scrollable(state, position, guides, visible);
+        match minimap_view(state, position, visible) {
+            // In the top-right corner, clear of the vertical scrollbar:
+            Some(minimap) => stack![
+                scrollable,
+                container(minimap)
+                    .align_right(Length::Fill)
+                    .padding(Padding::new(8.0).right(24.0)),
+            ]
+            .into(),
+            None => scrollable,
+        }
+    });
+
+    if !state.show_rulers {
+        return grid.into();
+    }
+    let scroll = state.area_scroll(position);
+    column![
+        row![
+            Space::new(RULER_SIZE, RULER_SIZE),
+            ruler_view(
+                RulerAxis::Horizontal,
+                area_name,
+                area.size.0 as TileCoord * 32,
+                pixel_size,
+                scroll.x,
+                guides,
+            ),
+        ],
+        row![
+            ruler_view(
+                RulerAxis::Vertical,
+                area_name,
+                area.size.1 as TileCoord * 32,
+                pixel_size,
+                scroll.y,
+                guides,
+            ),
+            grid,
+        ],
+    ]
+    .into()
+}
+
+// Summary of the differences between the main area and the version it's being compared with,
+// shown above the area (where the changed tiles are highlighted).
+pub fn area_diff_view(state: &EditorState) -> Option<Element<'_, Message>> {
+    let diff = state.area_diff.as_ref()?;
+    if diff.area_id != state.main_area_id {
+        return None;
+    }
+    let changes = compare_areas(state.main_area(), &diff.base_area);
+    let bases: Vec<DiffBase> = std::iter::once(DiffBase::Saved)
+        .chain(
+            state
+                .theme_names
+                .iter()
+                .filter(|&t| t != &diff.area_id.theme)
+                .map(|t| DiffBase::Theme(t.clone())),
+        )
+        .collect();
+    let summary = if changes.tiles.is_empty() {
+        "No changed tiles".to_string()
+    } else {
+        format!(
+            "{} changed tiles in {} screens",
+            changes.tiles.len(),
+            changes.screens.len()
+        )
+    };
+    let mut header = row![
+        text("Compare with"),
+        pick_list(bases, Some(diff.base.clone()), Message::ShowAreaDiff),
+        text(summary),
+    ]
+    .push_maybe(changes.size_changed.then(|| {
+        text(format!(
+            "(size {}x{} vs. {}x{})",
+            state.main_area().size.0,
+            state.main_area().size.1,
+            diff.base_area.size.0,
+            diff.base_area.size.1
+        ))
+    }))
+    .push(horizontal_space());
+    if diff.base == DiffBase::Saved {
+        header = header.push(
+            button(text("Reload from disk"))
+                .style(button::danger)
+                .on_press(Message::ReloadAreaFromDisk),
+        );
+    }
+    header = header.push(
+        button(text("\u{F62A}").font(iced_fonts::BOOTSTRAP_FONT))
+            .style(button::secondary)
+            .on_press(Message::CloseAreaDiff),
+    );
+    let mut screens = Row::new().spacing(5);
+    for screen in &changes.screens {
+        screens = screens.push(
+            button(
+                text(format!(
+                    "({}, {}): {}",
+                    screen.position.0, screen.position.1, screen.tiles
+                ))
+                .size(12),
+            )
+            .style(button::secondary)
+            .padding([2, 5])
+            .on_press(Message::GoToDiffScreen(screen.position)),
+        );
+    }
+    Some(
+        container(column![header.spacing(10).align_y(Vertical::Center), screens.wrap()].spacing(5))
+            .padding(10)
+            .width(Length::Fill)
+            .style(container::rounded_box)
+            .into(),
+    )
+}
+
+// Setup of replacing one palette with another in the main area, shown above the area (where the
+// tiles that would change are highlighted).
+pub fn palette_replace_view(state: &EditorState) -> Option<Element<'_, Message>> {
+    let replace = state.palette_replace.as_ref()?;
+    if replace.area_id != state.main_area_id {
+        return None;
+    }
+    let area = state.main_area();
+    let name = |id: PaletteId| match state.palettes_id_idx_map.get(&id) {
+        Some(&idx) => format!("{}: {}", id, state.palettes[idx].name),
+        None => format!("{}: (missing)", id),
+    };
+    let used: Vec<(String, PaletteId)> = area
+        .get_unique_palettes()
+        .into_iter()
+        .map(|id| (name(id), id))
+        .collect();
+    let all: Vec<(String, PaletteId)> = state.palettes.iter().map(|p| (name(p.id), p.id)).collect();
+    let pick = |choices: Vec<(String, PaletteId)>,
+                selected: Option<PaletteId>,
+                on_select: fn(PaletteId) -> Message| {
+        let names: Vec<String> = choices.iter().map(|c| c.0.clone()).collect();
+        pick_list(names, selected.map(name), move |n| {
+            let id = choices.iter().find(|c| c.0 == n).map(|c| c.1);
+            on_select(id.unwrap_or_default())
+        })
+        .width(200)
+    };
+
+    let mut summary = String::new();
+    let mut apply = None;
+    if let Some(from) = replace.from {
+        let cells = palette_cells(area, from);
+        summary = format!("{} tiles", cells.len());
+        let target = replace
+            .to
+            .and_then(|to| Some((to, *state.palettes_id_idx_map.get(&to)?)));
+        if let Some((to, idx)) = target {
+            let missing = missing_tile_count(area, &cells, &state.palettes[idx]);
+            if missing > 0 {
+                summary += &format!(" ({} beyond the end of the new palette)", missing);
+            }
+            if to != from && !cells.is_empty() {
+                apply = Some(Message::ReplaceAreaPalette {
+                    area_id: replace.area_id.clone(),
+                    from,
+                    to,
+                });
+            }
+        }
+    }
+    Some(
+        container(
+            row![
+                text("Replace palette"),
+                pick(used, replace.from, Message::SetPaletteReplaceFrom),
+                text("with"),
+                pick(all, replace.to, Message::SetPaletteReplaceTo),
+                text(summary),
+                horizontal_space(),
+                button(text("Replace")).on_press_maybe(apply),
+                button(text("\u{F62A}").font(iced_fonts::BOOTSTRAP_FONT))
+                    .style(button::secondary)
+                    .on_press(Message::ClosePaletteReplace),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+        )
+        .padding(10)
+        .width(Length::Fill)
+        .style(container::rounded_box)
+        .into(),
+    )
+}
+
+// Button showing an area view's area, which opens the area picker.
+fn area_picker_button(state: &EditorState, position: AreaPosition) -> button::Button<'_, Message> {
+    let area_id = state.area_id(position);
+    // Filled circle for unsaved changes, and hollow for unexported:
+    let marker = if dirty::area_unsaved(state, area_id) {
+        "\u{F287}"
+    } else if dirty::area_unexported(state, area_id) {
+        "\u{F28A}"
+    } else {
+        ""
+    };
+    button(
+        row![
+            text(&state.area(position).name),
+            text(marker).font(iced_fonts::BOOTSTRAP_FONT).size(8),
+            horizontal_space(),
+            text("\u{F282}").font(iced_fonts::BOOTSTRAP_FONT).size(12),
+        ]
+        .align_y(Vertical::Center),
+    )
+    .style(button::secondary)
+    .on_press(Message::AreaPickerDialogue(position))
+}
+
 pub fn main_area_controls(state: &EditorState) -> Element<Message> {
     row![
         text("Area"),
-        pick_list(
-            state.area_names.clone(),
-            Some(state.main_area().name.clone()),
-            |x| Message::SelectArea(AreaPosition::Main, x)
-        )
-        .on_open(Message::Focus(Focus::PickArea(AreaPosition::Main)))
-        .width(200),
+        mouse_area(area_picker_button(state, AreaPosition::Main).width(200))
+            .on_right_press(Message::ContextMenu(PickListMenu::Area)),
+        button(text("\u{F478}").font(iced_fonts::BOOTSTRAP_FONT))
+            .on_press(Message::AreaListDialogue(AreaPosition::Main)),
+        button(text("\u{F47F}").font(iced_fonts::BOOTSTRAP_FONT))
+            .style(button::secondary)
+            .on_press(Message::OpenWorldMap(state.main_area().theme.clone())),
         button(text("\u{F64D}").font(iced_fonts::BOOTSTRAP_FONT))
             .style(button::success)
             .on_press(Message::AddAreaDialogue),
         button(text("\u{F4CB}").font(iced_fonts::BOOTSTRAP_FONT))
             .on_press(Message::EditAreaDialogue),
+        button(text("\u{F28B}").font(iced_fonts::BOOTSTRAP_FONT))
+            .style(button::secondary)
+            .on_press(Message::CompileCheckArea),
+        button(text("\u{F4F4}").font(iced_fonts::BOOTSTRAP_FONT))
+            .style(button::secondary)
+            .on_press(Message::RunTestRom),
+        undo_controls(state),
+        macro_controls(state),
+        bg_color_controls(state),
+        collision_controls(state),
+        button(text("Entrances"))
+            .style(if state.show_entrances {
+                button::primary
+            } else {
+                button::secondary
+            })
+            .on_press(Message::ToggleEntrances),
+        button(text("Secrets"))
+            .style(if state.show_secrets {
+                button::primary
+            } else {
+                button::secondary
+            })
+            .on_press(Message::ToggleSecrets),
+        button(text("Travel"))
+            .style(if state.show_travel {
+                button::primary
+            } else {
+                button::secondary
+            })
+            .on_press(Message::ToggleTravel),
+        button(text("Sprite"))
+            .style(if state.show_sprite_preview {
+                button::primary
+            } else {
+                button::secondary
+            })
+            .on_press(Message::ToggleSpritePreview),
+        button(text("Overlay"))
+            .style(if state.edit_layer == Layer::Overlay {
+                button::primary
+            } else {
+                button::secondary
+            })
+            .on_press(Message::SetEditLayer(match state.edit_layer {
+                Layer::Main => Layer::Overlay,
+                Layer::Overlay => Layer::Main,
+            })),
         text("Theme"),
-        pick_list(
-            state.theme_names.clone(),
-            Some(state.main_area().theme.clone()),
-            |x| Message::SelectTheme(AreaPosition::Main, x)
+        mouse_area(
+            pick_list(
+                state.theme_names.clone(),
+                Some(state.main_area().theme.clone()),
+                |x| Message::SelectTheme(AreaPosition::Main, x)
+            )
+            .on_open(Message::Focus(Focus::PickTheme(AreaPosition::Main)))
+            .width(200)
         )
-        .on_open(Message::Focus(Focus::PickTheme(AreaPosition::Main)))
-        .width(200),
+        .on_right_press(Message::ContextMenu(PickListMenu::Theme)),
         button(text("\u{F64D}").font(iced_fonts::BOOTSTRAP_FONT))
             .style(button::success)
             .on_press(Message::AddThemeDialogue),
         button(text("\u{F4CB}").font(iced_fonts::BOOTSTRAP_FONT))
             .on_press(Message::RenameThemeDialogue),
+        theme_sync_controls(state),
     ]
     .spacing(10)
     .clip(true)
@@ -589,22 +1605,236 @@ pub fn main_area_controls(state: &EditorState) -> Element<Message> {
     .into()
 }
 
+// Undo and redo buttons. Hovering over the undo button previews the result of undoing
+// (as does holding Z), and clicking it keeps it.
+fn undo_controls(state: &EditorState) -> Element<'_, Message> {
+    let can_undo = !state.undo_stack.is_empty() || state.undo_preview;
+    let undo_button = button(text("\u{F117}").font(iced_fonts::BOOTSTRAP_FONT))
+        .style(if state.undo_preview {
+            button::primary
+        } else {
+            button::secondary
+        })
+        .on_press_maybe(can_undo.then_some(Message::Undo));
+    row![
+        mouse_area(undo_button)
+            .on_enter(Message::PreviewUndo(true))
+            .on_exit(Message::PreviewUndo(false)),
+        button(text("\u{F116}").font(iced_fonts::BOOTSTRAP_FONT))
+            .style(button::secondary)
+            .on_press_maybe(
+                (!state.redo_stack.is_empty() && !state.undo_preview).then_some(Message::Redo)
+            ),
+    ]
+    .spacing(5)
+    .into()
+}
+
+fn bg_swatch<'a>(color: ColorRGB) -> Container<'a, Message> {
+    container(Space::new(16, 16)).style(move |_theme| {
+        container::Style::default().background(iced::Color::from_rgb(
+            color[0] as f32 / 31.0,
+            color[1] as f32 / 31.0,
+            color[2] as f32 / 31.0,
+        ))
+    })
+}
+
+fn bg_color_controls(state: &EditorState) -> Element<'_, Message> {
+    let color = state.main_area().bg_color;
+    let swatch = button(bg_swatch(color))
+        .padding(4)
+        .style(button::secondary)
+        .on_press(Message::ToggleBGColorEditor);
+    let all_areas = button(text("\u{F5AA}").font(iced_fonts::BOOTSTRAP_FONT))
+        .style(button::secondary)
+        .on_press(Message::BGColorsDialogue);
+    if !state.show_bg_color_editor {
+        return row![text("BG"), swatch, all_areas]
+            .spacing(5)
+            .align_y(Vertical::Center)
+            .into();
+    }
+    let rgb_width = 70;
+    row![
+        text("BG"),
+        swatch,
+        all_areas,
+        text("R"),
+        number_input(&color[0], 0..=31, Message::EditAreaBGRed).width(rgb_width),
+        text("G"),
+        number_input(&color[1], 0..=31, Message::EditAreaBGGreen).width(rgb_width),
+        text("B"),
+        number_input(&color[2], 0..=31, Message::EditAreaBGBlue).width(rgb_width),
+    ]
+    .spacing(5)
+    .align_y(Vertical::Center)
+    .into()
+}
+
+// BG colors of all areas in all themes, for keeping them consistent across themes.
+pub fn bg_colors_view<'a>(
+    state: &'a EditorState,
+    colors: &'a [Vec<ColorRGB>],
+    theme: &'a ThemeName,
+    color: ColorRGB,
+) -> Element<'a, Message> {
+    let name_width = 180;
+    let cell_width = 80;
+    let mut header = row![text("Area").width(name_width)].spacing(5);
+    for t in &state.theme_names {
+        header = header.push(
+            button(text(t.clone()).size(12))
+                .style(if t == theme {
+                    button::primary
+                } else {
+                    button::text
+                })
+                .width(cell_width)
+                .on_press(Message::SelectBGColor {
+                    theme: t.clone(),
+                    color,
+                }),
+        );
+    }
+    let mut rows = Column::new().spacing(2);
+    for (area, area_colors) in state.area_names.iter().zip(colors) {
+        let mut r = row![text(area.clone()).width(name_width)]
+            .spacing(5)
+            .align_y(Vertical::Center);
+        for (t, &c) in state.theme_names.iter().zip(area_colors) {
+            r = r.push(
+                container(
+                    button(bg_swatch(c))
+                        .padding(4)
+                        .style(if t == theme && c == color {
+                            button::primary
+                        } else {
+                            button::secondary
+                        })
+                        .on_press(Message::SelectBGColor {
+                            theme: t.clone(),
+                            color: c,
+                        }),
+                )
+                .width(cell_width),
+            );
+        }
+        r = r.push(
+            button(text(format!("Copy {} to all themes", theme)).size(12))
+                .style(button::text)
+                .on_press_maybe(
+                    (state.theme_names.len() > 1).then(|| Message::CopyBGColorRow(area.clone())),
+                ),
+        );
+        rows = rows.push(r);
+    }
+    let [r, g, b] = color;
+    let rgb_width = 70;
+    let editor = row![
+        text(format!("Color for {}:", theme)),
+        bg_swatch(color),
+        text("R"),
+        number_input(&r, 0..=31, move |r| Message::SetBGColorsColor([r, g, b])).width(rgb_width),
+        text("G"),
+        number_input(&g, 0..=31, move |g| Message::SetBGColorsColor([r, g, b])).width(rgb_width),
+        text("B"),
+        number_input(&b, 0..=31, move |b| Message::SetBGColorsColor([r, g, b])).width(rgb_width),
+        button(text("Set for all areas")).on_press(Message::SetBGColorColumn),
+    ]
+    .spacing(5)
+    .align_y(Vertical::Center);
+    container(
+        column![
+            text("BG colors").size(18),
+            header,
+            scrollable(rows).height(Length::Fixed(400.0)),
+            editor,
+        ]
+        .spacing(10),
+    )
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
+
+// Choosing a theme to brush in together with the main area's theme, or (while synced) showing the
+// pair, which stands out so that edits aren't unknowingly made to both.
+fn theme_sync_controls(state: &EditorState) -> Element<'_, Message> {
+    if let Some((a, b)) = &state.theme_sync {
+        return button(text(format!("Syncing {} + {}", a, b)))
+            .style(button::danger)
+            .on_press(Message::SetThemeSync(None))
+            .into();
+    }
+    let main_theme = &state.main_area_id.theme;
+    let others: Vec<ThemeName> = state
+        .theme_names
+        .iter()
+        .filter(|&t| t != main_theme)
+        .cloned()
+        .collect();
+    if others.is_empty() {
+        return Space::new(0, 0).into();
+    }
+    pick_list(others, None::<ThemeName>, |t| {
+        Message::SetThemeSync(Some(t))
+    })
+    .placeholder("Sync with...")
+    .width(150)
+    .into()
+}
+
+fn collision_controls(state: &EditorState) -> Element<'_, Message> {
+    let toggle = button(text("Collision"))
+        .style(if state.show_collision {
+            button::primary
+        } else {
+            button::secondary
+        })
+        .on_press(Message::ToggleCollisionOverlay);
+    if state.tool != Tool::Collision {
+        return toggle.into();
+    }
+    let [r, g, b] = collision_color(state.collision_brush);
+    row![
+        toggle,
+        container(Space::new(16, 16)).style(move |_theme| {
+            container::Style::default().background(iced::Color::from_rgb8(r, g, b))
+        }),
+        number_input(&state.collision_brush, 0..=255, Message::SetCollisionBrush).width(60),
+    ]
+    .spacing(5)
+    .align_y(Vertical::Center)
+    .into()
+}
+
 pub fn side_area_controls(state: &EditorState) -> Element<Message> {
     row![
-        pick_list(
-            state.area_names.clone(),
-            Some(state.side_area().name.clone()),
-            |x| Message::SelectArea(AreaPosition::Side, x)
-        )
-        .on_open(Message::Focus(Focus::PickArea(AreaPosition::Side)))
-        .width(200),
+        area_picker_button(state, AreaPosition::Side).width(130),
+        button(text("\u{F478}").font(iced_fonts::BOOTSTRAP_FONT))
+            .on_press(Message::AreaListDialogue(AreaPosition::Side)),
         pick_list(
             state.theme_names.clone(),
             Some(state.main_area().theme.clone()),
             |x| Message::SelectTheme(AreaPosition::Side, x)
         )
         .on_open(Message::Focus(Focus::PickTheme(AreaPosition::Side)))
-        .width(200),
+        .width(130),
+        button(text("\u{F471}").font(iced_fonts::BOOTSTRAP_FONT))
+            .style(if state.link_area_scroll {
+                button::primary
+            } else {
+                button::secondary
+            })
+            .on_press(Message::ToggleLinkedScroll),
+        button(text("\u{F33D}").font(iced_fonts::BOOTSTRAP_FONT))
+            .style(if state.show_side_differences {
+                button::primary
+            } else {
+                button::secondary
+            })
+            .on_press(Message::ToggleSideDifferences),
     ]
     .spacing(10)
     .clip(true)
@@ -641,9 +1871,15 @@ pub fn add_area_view(name: &String, size: (u8, u8)) -> Element<Message> {
             ]
             .spacing(10)
             .align_y(Vertical::Center),
-            button(text("Add area"))
-                .style(button::success)
-                .on_press(add_area_msg.clone()),
+            row![
+                button(text("Add area"))
+                    .style(button::success)
+                    .on_press(add_area_msg.clone()),
+                horizontal_space(),
+                button(text("From manifest..."))
+                    .style(button::secondary)
+                    .on_press(Message::OpenAreaManifest),
+            ],
         ]
         .spacing(10),
     )
@@ -653,9 +1889,47 @@ pub fn add_area_view(name: &String, size: (u8, u8)) -> Element<Message> {
     .into()
 }
 
-pub fn edit_area_view(state: &EditorState, name: &String) -> Element<'static, Message> {
+pub fn create_areas_view<'a>(
+    entries: &'a [AreaManifestEntry],
+    done: usize,
+    skipped: &'a [String],
+) -> Element<'a, Message> {
+    let finished = done >= entries.len();
+    let mut col = column![
+        text(if finished {
+            format!(
+                "Created {} of {} areas.",
+                entries.len() - skipped.len(),
+                entries.len()
+            )
+        } else {
+            format!("Creating areas ({} of {}) ...", done, entries.len())
+        }),
+        progress_bar(0.0..=entries.len() as f32, done as f32).height(10),
+    ]
+    .spacing(10);
+    if !skipped.is_empty() {
+        col = col.push(text("Skipped:"));
+        col = col.push(
+            scrollable(Column::with_children(
+                skipped.iter().map(|s| text(s).size(12).into()),
+            ))
+            .height(Length::Shrink),
+        );
+    }
+    if finished {
+        col = col.push(button(text("Close")).on_press(Message::CloseDialogue));
+    }
+    container(col)
+        .width(450)
+        .max_height(400)
+        .padding(25)
+        .style(modal_background_style)
+        .into()
+}
+
+pub fn edit_area_view<'a>(state: &'a EditorState, name: &String) -> Element<'a, Message> {
     let old_name = state.main_area().name.clone();
-    let rgb_width = 80;
     let edit_area_msg = Message::EditArea {
         old_name: old_name.clone(),
         new_name: name.clone(),
@@ -672,43 +1946,103 @@ pub fn edit_area_view(state: &EditorState, name: &String) -> Element<'static, Me
             ]
             .spacing(10)
             .align_y(Vertical::Center),
-            row![text("Background color:")],
+        ]
+        .push_maybe(label_preview(state, name))
+        .push(area_properties_view(state))
+        .push(row![
+            button(text("Edit area")).on_press(edit_area_msg.clone()),
+            Space::with_width(Length::Fill),
+            button(text("Delete area"))
+                .style(button::danger)
+                .on_press(Message::DeleteAreaDialogue),
+        ])
+        .spacing(15),
+    )
+    .width(450)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
+
+// Music, palette set, and flags of the main area, which are changed as soon as they're edited.
+fn area_properties_view(state: &EditorState) -> Element<'_, Message> {
+    let area = state.main_area();
+    let mut col = Column::new().spacing(5);
+    let stage_count = music_stage_count(area.vanilla_map_id);
+    for (stage, &stage_name) in MUSIC_STAGES.iter().enumerate().take(stage_count) {
+        let music = area
+            .properties
+            .music
+            .get(stage)
+            .copied()
+            .unwrap_or_default();
+        let stage_name = if area.vanilla_map_id >= Some(0x40) {
+            "Dark World"
+        } else {
+            stage_name
+        };
+        col = col.push(
             row![
-                text("Red"),
-                number_input(
-                    &state.main_area().bg_color[0],
-                    0..=31,
-                    Message::EditAreaBGRed
-                )
-                .width(rgb_width),
-                iced::widget::Space::with_width(10),
-                text("Green"),
-                number_input(
-                    &state.main_area().bg_color[1],
-                    0..=31,
-                    Message::EditAreaBGGreen
-                )
-                .width(rgb_width),
-                iced::widget::Space::with_width(10),
-                text("Blue"),
-                number_input(
-                    &state.main_area().bg_color[2],
-                    0..=31,
-                    Message::EditAreaBGBlue
-                )
-                .width(rgb_width),
+                text(format!("Music ({}):", stage_name)).width(Length::Fill),
+                text("Track"),
+                number_input(&music.track, 0..=15, move |x| {
+                    Message::EditAreaProperty(AreaPropertyEdit::MusicTrack(stage, x))
+                })
+                .width(60),
+                text("Ambient"),
+                number_input(&music.ambient, 0..=15, move |x| {
+                    Message::EditAreaProperty(AreaPropertyEdit::Ambient(stage, x))
+                })
+                .width(60),
             ]
-            .spacing(5)
-            .align_y(iced::alignment::Vertical::Center),
+            .spacing(10)
+            .align_y(Vertical::Center),
+        );
+    }
+    col.push(
+        row![
+            text("Palette set:").width(Length::Fill),
+            number_input(
+                &area.properties.palette_set.unwrap_or(0),
+                0..=u8::MAX,
+                |x| Message::EditAreaProperty(AreaPropertyEdit::PaletteSet(x))
+            )
+            .width(80),
+            text("Flags:"),
+            number_input(&area.properties.flags, 0..=u8::MAX, |x| {
+                Message::EditAreaProperty(AreaPropertyEdit::Flags(x))
+            })
+            .width(80),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+    )
+    .into()
+}
+
+pub fn duplicate_area_view<'a>(state: &EditorState, name: &str) -> Element<'a, Message> {
+    let old_name = state.main_area().name.clone();
+    let duplicate_area_msg = Message::DuplicateArea {
+        old_name: old_name.clone(),
+        new_name: name.to_string(),
+    };
+    container(
+        column![
+            text(format!("Duplicate area \"{}\" (in all themes).", old_name)),
             row![
-                button(text("Edit area")).on_press(edit_area_msg.clone()),
-                Space::with_width(Length::Fill),
-                button(text("Delete area"))
-                    .style(button::danger)
-                    .on_press(Message::DeleteAreaDialogue),
-            ],
+                text("Name: ").width(70),
+                text_input("", name)
+                    .id("DuplicateArea")
+                    .on_input(Message::SetDuplicateAreaName)
+                    .on_submit(duplicate_area_msg.clone())
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            button(text("Duplicate area"))
+                .style(button::success)
+                .on_press(duplicate_area_msg),
         ]
-        .spacing(15),
+        .spacing(10),
     )
     .width(450)
     .padding(25)
@@ -716,13 +2050,87 @@ pub fn edit_area_view(state: &EditorState, name: &String) -> Element<'static, Me
     .into()
 }
 
+fn palette_label(palette: &Palette) -> String {
+    format!("{}: {}", palette.id, palette.name)
+}
+
+pub fn dark_world_view<'a>(
+    state: &'a EditorState,
+    target: &str,
+    exists: bool,
+    mapping: &BTreeMap<PaletteId, PaletteId>,
+) -> Element<'a, Message> {
+    let light_area = state.main_area();
+    let labels: Vec<String> = state.palettes.iter().map(palette_label).collect();
+    let mut palette_rows = Column::new().spacing(5);
+    for light in area_palettes(state, light_area) {
+        let Some(&idx) = state.palettes_id_idx_map.get(&light) else {
+            continue;
+        };
+        let dark = mapping.get(&light).copied().unwrap_or(light);
+        let selected = state
+            .palettes_id_idx_map
+            .get(&dark)
+            .map(|&i| palette_label(&state.palettes[i]));
+        palette_rows = palette_rows.push(
+            row![
+                text(palette_label(&state.palettes[idx])).width(200),
+                text("\u{F138}").font(iced_fonts::BOOTSTRAP_FONT),
+                pick_list(labels.clone(), selected, move |label: String| {
+                    let dark = label
+                        .split(':')
+                        .next()
+                        .and_then(|id| id.parse().ok())
+                        .unwrap_or(light);
+                    Message::SetDarkWorldPalette { light, dark }
+                })
+                .width(200),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+        );
+    }
+    let action = if exists {
+        format!(
+            "Replace the layout of \"{}\" with a copy of \"{}\" (in all themes)?",
+            target, light_area.name
+        )
+    } else {
+        format!(
+            "Create \"{}\" from a copy of \"{}\" (in all themes).",
+            target, light_area.name
+        )
+    };
+    container(
+        column![
+            text(action),
+            text("Dark world palettes:"),
+            scrollable(palette_rows).height(Length::Shrink),
+            button(text(if exists { "Update area" } else { "Create area" }))
+                .style(if exists {
+                    button::danger
+                } else {
+                    button::success
+                })
+                .on_press(Message::GenerateDarkWorldArea {
+                    light_area: light_area.name.clone(),
+                    mapping: mapping.clone(),
+                }),
+        ]
+        .spacing(10),
+    )
+    .width(500)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
+
 pub fn delete_area_view(state: &EditorState) -> Element<Message> {
     let name = state.main_area().name.clone();
     container(
         column![
             text(format!("Delete area \"{}\"?", name)),
             text("This will delete the area across all themes."),
-            text("This action cannot be undone."),
             button(text("Delete area"))
                 .style(button::danger)
                 .on_press(Message::DeleteArea(name.clone())),
@@ -735,6 +2143,378 @@ pub fn delete_area_view(state: &EditorState) -> Element<Message> {
     .into()
 }
 
+const PICKER_THUMBNAIL_SIZE: f32 = 48.0;
+
+pub fn area_picker_view(picker: &AreaPicker) -> Element<'_, Message> {
+    let mut list_col = column![].spacing(2);
+    let matches = picker.matches();
+    for (i, entry) in matches.iter().enumerate() {
+        let thumbnail: Element<Message> = match &entry.thumbnail {
+            Some(handle) => iced::widget::image(handle.clone())
+                .content_fit(ContentFit::Contain)
+                .width(PICKER_THUMBNAIL_SIZE)
+                .height(PICKER_THUMBNAIL_SIZE)
+                .into(),
+            None => Space::new(PICKER_THUMBNAIL_SIZE, PICKER_THUMBNAIL_SIZE).into(),
+        };
+        let mut details = match entry.size {
+            Some((x, y)) => vec![format!("{}x{} screens", x, y)],
+            None => vec![],
+        };
+        if entry.unsaved {
+            details.push("unsaved changes".to_string());
+        } else if entry.unexported {
+            details.push("not exported".to_string());
+        }
+        let size = details.join(", ");
+        list_col = list_col.push(
+            button(
+                row![
+                    thumbnail,
+                    column![text(&entry.name), text(size).size(12)].spacing(2),
+                ]
+                .spacing(10)
+                .align_y(Vertical::Center),
+            )
+            .width(Length::Fill)
+            .padding(3)
+            .style(if i == picker.selected {
+                button::primary
+            } else {
+                button::text
+            })
+            .on_press(Message::PickArea(entry.name.clone())),
+        );
+    }
+    if matches.is_empty() {
+        list_col = list_col.push(text("No matching areas"));
+    }
+    container(
+        column![
+            text_input("Search areas", &picker.query)
+                .id("AreaPicker")
+                .on_input(Message::SetAreaPickerQuery)
+                .on_submit(Message::PickSelectedArea),
+            scrollable(list_col)
+                .id(scrollable::Id::new("AreaPicker"))
+                .height(400),
+        ]
+        .spacing(10),
+    )
+    .width(350)
+    .padding(15)
+    .style(modal_background_style)
+    .into()
+}
+
+pub fn area_list_view<'a>(
+    state: &'a EditorState,
+    position: AreaPosition,
+    new_group: &'a str,
+) -> Element<'a, Message> {
+    let selected_name = &state.area(position).name;
+    let mut list_col = column![].spacing(2);
+    for (group, names) in state.grouped_area_names() {
+        let collapsed = state.collapsed_area_groups.contains(&group);
+        let chevron = if collapsed { "\u{F285}" } else { "\u{F282}" };
+        // Group headers are drop targets: releasing a dragged area here moves it into the group.
+        list_col = list_col.push(
+            mouse_area(
+                button(
+                    row![
+                        text(chevron).font(iced_fonts::BOOTSTRAP_FONT),
+                        text(format!("{} ({})", group, names.len())),
+                    ]
+                    .spacing(5),
+                )
+                .style(button::text)
+                .width(Length::Fill)
+                .on_press(Message::ToggleAreaGroup(group.clone())),
+            )
+            .on_release(Message::ReleaseOnAreaGroup(group.clone())),
+        );
+        if collapsed {
+            continue;
+        }
+        for name in names {
+            let dragging = state.dragging_area.as_ref() == Some(&name);
+            let selected = &name == selected_name;
+            list_col = list_col.push(
+                mouse_area(
+                    container(text(name.clone()))
+                        .width(Length::Fill)
+                        .padding(Padding::new(3.0).left(30.0))
+                        .style(move |theme: &iced::Theme| {
+                            let palette = theme.extended_palette();
+                            if dragging {
+                                container::Style::default().background(palette.primary.weak.color)
+                            } else if selected {
+                                container::Style::default()
+                                    .background(palette.background.strong.color)
+                            } else {
+                                container::Style::default()
+                            }
+                        }),
+                )
+                .interaction(mouse::Interaction::Grab)
+                .on_press(Message::DragArea(name.clone()))
+                .on_release(Message::ReleaseOnArea(name.clone())),
+            );
+        }
+    }
+
+    let move_msg = Message::SetAreaGroup {
+        area: selected_name.clone(),
+        group: Some(new_group.to_string()),
+    };
+    container(
+        column![
+            text("Click an area to open it. Drag an area onto a group header to move it."),
+            scrollable(list_col).height(400),
+            row![
+                text("Move current area to group:"),
+                text_input("", new_group)
+                    .on_input(Message::SetNewAreaGroupName)
+                    .on_submit(move_msg.clone()),
+                button(text("Move"))
+                    .style(button::success)
+                    .on_press(move_msg),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+        ]
+        .spacing(10),
+    )
+    .width(450)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
+
+pub fn compile_check_view(report: &CompileReport) -> Element<'_, Message> {
+    let budget_row = |label: &str, count: usize, budget: usize| {
+        let (icon, style): (&str, fn(&iced::Theme) -> text::Style) = if count <= budget {
+            ("\u{F26B}", text::success)
+        } else {
+            ("\u{F623}", text::danger)
+        };
+        row![
+            text(icon).font(iced_fonts::BOOTSTRAP_FONT).style(style),
+            text(label.to_string()).width(220),
+            text(format!("{} of {}", count, budget)),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center)
+    };
+    let mut col = column![
+        text(format!(
+            "Compile check of \"{}\" ({})",
+            report.area_name, report.theme
+        )),
+        text(if report.is_ok() {
+            "The area can be represented in the ROM."
+        } else {
+            "The area cannot be represented in the ROM as it is."
+        }),
+        budget_row(
+            "Unique 16x16 blocks (tile16)",
+            report.tile16_count,
+            TILE16_BUDGET
+        ),
+        budget_row(
+            "Unique 32x32 blocks (tile32)",
+            report.tile32_count,
+            TILE32_BUDGET
+        ),
+        budget_row("Palette rows", report.palette_count, PALETTE_ROW_BUDGET),
+        budget_row(
+            "HUD palette rows",
+            report.hud_palette_count,
+            HUD_PALETTE_ROW_BUDGET
+        ),
+        budget_row("8x8 graphics (VRAM chars)", report.char_count, CHAR_BUDGET),
+        budget_row(
+            "Animated slots",
+            report.animated_slot_count,
+            ANIMATED_SLOT_COUNT as usize
+        ),
+        text("Tile16 and tile32 budgets are shared by all areas of a theme.").size(12),
+    ]
+    .spacing(10);
+    if report.illegal_flip_count > 0 {
+        col = col.push(text(format!(
+            "{} tiles use a flip that the tile does not allow.",
+            report.illegal_flip_count
+        )));
+    }
+    if report.animated_problem_count > 0 {
+        col = col.push(text(format!(
+            "{} animated tiles can't be shown by the game here:",
+            report.animated_problem_count
+        )));
+        let mut problems_col = column![].spacing(2);
+        for p in &report.animated_problems {
+            problems_col = problems_col.push(text(p.clone()).size(12));
+        }
+        col = col.push(scrollable(problems_col).height(100));
+    }
+    if report.error_count > 0 {
+        col = col.push(text(format!(
+            "{} tiles reference missing palettes or tiles:",
+            report.error_count
+        )));
+        let mut errors_col = column![].spacing(2);
+        for e in &report.errors {
+            errors_col = errors_col.push(text(e.clone()).size(12));
+        }
+        col = col.push(scrollable(errors_col).height(150));
+    }
+    col = col.push(
+        row![
+            button(text("Close"))
+                .style(button::secondary)
+                .on_press(Message::CloseDialogue),
+            horizontal_space(),
+            button(text("Palette positions")).on_press(Message::PaletteSlotsDialogue),
+            button(text("VRAM usage")).on_press(Message::VramUsage(false)),
+            button(text("Estimate compressed size")).on_press(Message::CompressionEstimate),
+        ]
+        .spacing(10),
+    );
+    container(col)
+        .width(500)
+        .padding(25)
+        .style(modal_background_style)
+        .into()
+}
+
+pub fn compression_estimate_view<'a>(
+    theme: &str,
+    estimates: &'a [AreaCompression],
+) -> Element<'a, Message> {
+    let mut list = column![].spacing(5);
+    for e in estimates {
+        let (icon, style): (&str, fn(&iced::Theme) -> text::Style) = if e.over_budget() {
+            ("\u{F623}", text::danger)
+        } else {
+            ("\u{F26B}", text::success)
+        };
+        list = list.push(
+            row![
+                text(icon).font(iced_fonts::BOOTSTRAP_FONT).style(style),
+                text(e.area_name.clone()).width(200),
+                text(format!("{} of {} bytes", e.compressed_size, e.budget())),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+        );
+    }
+    let total: usize = estimates.iter().map(|e| e.compressed_size).sum();
+    let over_count = estimates.iter().filter(|e| e.over_budget()).count();
+    container(
+        column![
+            text(format!("Compressed map data size ({})", theme)),
+            text(format!(
+                "Total: {} of {} bytes. {} areas exceed their share.",
+                total, MAP_DATA_SPACE, over_count
+            )),
+            scrollable(list).height(300),
+            text(
+                "Each area's share is in proportion to its number of screens. \
+                 Sizes are estimates, with tile32s numbered in order of first use."
+            )
+            .size(12),
+            button(text("Close"))
+                .style(button::secondary)
+                .on_press(Message::CloseDialogue),
+        ]
+        .spacing(10),
+    )
+    .width(500)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
+
+pub fn vram_usage_view(all_areas: bool, screens: &[ScreenUsage]) -> Element<'_, Message> {
+    let scope_button = |label: &'static str, all: bool| {
+        button(text(label))
+            .style(if all == all_areas {
+                button::primary
+            } else {
+                button::secondary
+            })
+            .on_press(Message::VramUsage(all))
+    };
+    let mut list = column![].spacing(5);
+    let mut over_count = 0;
+    for s in screens.iter().filter(|s| !s.fits()) {
+        over_count += 1;
+        list = list.push(
+            row![
+                text("\u{F623}")
+                    .font(iced_fonts::BOOTSTRAP_FONT)
+                    .style(text::danger),
+                button(text(format!(
+                    "{} ({}, {})",
+                    s.area_id.area, s.screen.0, s.screen.1
+                )))
+                .style(button::text)
+                .width(200)
+                .on_press(Message::GoToAreaScreen(s.area_id.clone(), s.screen)),
+                text(s.problems().join(", ")),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+        );
+    }
+    let max = |f: fn(&ScreenUsage) -> usize| screens.iter().map(f).max().unwrap_or(0);
+    container(
+        column![
+            row![
+                text("VRAM usage of"),
+                scope_button("Current area", false),
+                scope_button("All areas", true),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            text(format!(
+                "Most used by a screen: {} of {} 8x8 graphics, {} of {} palette rows, \
+                 {} of {} HUD palette rows.",
+                max(|s| s.char_count),
+                CHAR_BUDGET,
+                max(|s| s.palette_count),
+                PALETTE_ROW_BUDGET,
+                max(|s| s.hud_palette_count),
+                HUD_PALETTE_ROW_BUDGET
+            )),
+            text(if over_count == 0 {
+                format!("All {} screens fit.", screens.len())
+            } else {
+                format!(
+                    "{} of {} screens exceed a budget (click one to go to it):",
+                    over_count,
+                    screens.len()
+                )
+            }),
+            scrollable(list).height(300),
+            text(
+                "Animated tiles use their own VRAM slots, so they aren't counted here. \
+                 Flipped and recolored copies of a graphic share its VRAM."
+            )
+            .size(12),
+            button(text("Close"))
+                .style(button::secondary)
+                .on_press(Message::CloseDialogue),
+        ]
+        .spacing(10),
+    )
+    .width(600)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
+
 pub fn add_theme_view(name: &String) -> Element<Message> {
     container(
         column![
@@ -800,7 +2580,6 @@ pub fn delete_theme_view(state: &EditorState) -> Element<Message> {
         column![
             text(format!("Delete theme \"{}\"?", theme)),
             text("This will delete the theme across all areas."),
-            text("This action cannot be undone."),
             button(text("Delete theme"))
                 .style(button::danger)
                 .on_press(Message::DeleteTheme(theme.clone())),
@@ -0,0 +1,185 @@
+// Module for previewing the brush (the selected tile block) and editing its shape
+use hashbrown::HashMap;
+use iced::{
+    keyboard, mouse,
+    widget::{button, canvas, column, row, text},
+    Element, Point, Rectangle, Size,
+};
+
+use crate::{
+    helpers::{alpha_blend, scale_color},
+    message::Message,
+    state::{EditorState, Palette, PaletteId, Tile, TileBlock, TileCoord},
+};
+
+const MAX_PREVIEW_WIDTH: f32 = 384.0;
+const MAX_PREVIEW_HEIGHT: f32 = 192.0;
+const MAX_PIXEL_SIZE: f32 = 3.0;
+
+struct BrushPreview<'a> {
+    tile_block: &'a TileBlock,
+    gfx: &'a [Vec<Tile>],
+    palettes: &'a [Palette],
+    palettes_id_idx_map: &'a HashMap<PaletteId, usize>,
+    pixel_size: f32,
+}
+
+#[derive(Default)]
+struct InternalState {
+    modifiers: keyboard::Modifiers,
+}
+
+impl<'a> canvas::Program<Message> for BrushPreview<'a> {
+    type State = InternalState;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: canvas::Event,
+        bounds: iced::Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (canvas::event::Status, Option<Message>) {
+        match event {
+            canvas::Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                state.modifiers = modifiers;
+            }
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                if !state.modifiers.control() {
+                    return (canvas::event::Status::Ignored, None);
+                }
+                if let Some(p) = cursor.position_in(bounds) {
+                    let x = (f32::max(p.x - 1.0, 0.0) / (8.0 * self.pixel_size)) as TileCoord;
+                    let y = (f32::max(p.y - 1.0, 0.0) / (8.0 * self.pixel_size)) as TileCoord;
+                    return (
+                        canvas::event::Status::Captured,
+                        Some(Message::ToggleBrushMask(x, y)),
+                    );
+                }
+            }
+            _ => {}
+        }
+        (canvas::event::Status::Ignored, None)
+    }
+
+    fn draw(
+        &self,
+        _state: &InternalState,
+        renderer: &iced::Renderer,
+        _theme: &iced::Theme,
+        bounds: iced::Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let size = self.tile_block.size;
+
+        // Add a pixel of transparent padding around the image, since Iced's
+        // "nearest neighbor" filter results in the edge pixels having the wrong size.
+        let num_cols = size.0 as usize * 8 + 2;
+        let num_rows = size.1 as usize * 8 + 2;
+        let mut data: Vec<u8> = vec![0; num_rows * num_cols * 4];
+        let col_stride = 4;
+        let row_stride = num_cols * col_stride;
+        for ty in 0..size.1 as usize {
+            for tx in 0..size.0 as usize {
+                let palette_id = self.tile_block.palettes[ty][tx];
+                let Some(&palette_idx) = self.palettes_id_idx_map.get(&palette_id) else {
+                    continue;
+                };
+                let Some(tile) = self.gfx.get(ty).and_then(|row| row.get(tx)) else {
+                    continue;
+                };
+                let colors = &self.palettes[palette_idx].colors;
+                // Cells outside the brush shape are shown faded out:
+                let covered = self.tile_block.covers(tx, ty);
+                let mut tile_addr = (ty * 8 + 1) * row_stride + (tx * 8 + 1) * col_stride;
+                for py in 0..8 {
+                    let mut addr = tile_addr;
+                    for px in 0..8 {
+                        let [r, g, b] = colors[tile.pixels[py][px] as usize];
+                        let mut color = [scale_color(r), scale_color(g), scale_color(b)];
+                        if !covered {
+                            color = alpha_blend(color, [64, 64, 64], 0.8);
+                        }
+                        data[addr..(addr + 3)].copy_from_slice(&color);
+                        data[addr + 3] = 255;
+                        addr += 4;
+                    }
+                    tile_addr += row_stride;
+                }
+            }
+        }
+
+        let image = iced::advanced::image::Image::new(iced::advanced::image::Handle::from_rgba(
+            num_cols as u32,
+            num_rows as u32,
+            data,
+        ))
+        .filter_method(iced::widget::image::FilterMethod::Nearest);
+
+        frame.draw_image(
+            Rectangle::new(
+                Point::new(0.0, 0.0),
+                Size {
+                    width: num_cols as f32 * self.pixel_size,
+                    height: num_rows as f32 * self.pixel_size,
+                },
+            ),
+            image,
+        );
+
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &Self::State,
+        bounds: iced::Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        if state.modifiers.control() && cursor.is_over(bounds) {
+            mouse::Interaction::Pointer
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+pub fn brush_preview_view(state: &EditorState) -> Element<'_, Message> {
+    let block = &state.selected_tile_block;
+    let num_cols = block.size.0 as f32 * 8.0 + 2.0;
+    let num_rows = block.size.1 as f32 * 8.0 + 2.0;
+    let pixel_size = MAX_PIXEL_SIZE
+        .min(MAX_PREVIEW_WIDTH / num_cols)
+        .min(MAX_PREVIEW_HEIGHT / num_rows);
+    let mut clear_button = button(text("Reset shape"));
+    if block.mask.is_some() {
+        clear_button = clear_button.on_press(Message::ClearBrushMask);
+    }
+    column![
+        row![
+            text("Brush"),
+            text("(Ctrl-click to toggle cells)").size(12),
+            iced::widget::horizontal_space(),
+            button(text("Metatiles"))
+                .style(button::secondary)
+                .on_press(Message::ShowMetatiles(true)),
+            button(text("Stamps"))
+                .style(button::secondary)
+                .on_press(Message::ShowStamps(true)),
+            clear_button,
+        ]
+        .spacing(10)
+        .align_y(iced::alignment::Vertical::Center),
+        canvas(BrushPreview {
+            tile_block: block,
+            gfx: &state.selected_gfx,
+            palettes: &state.palettes,
+            palettes_id_idx_map: &state.palettes_id_idx_map,
+            pixel_size,
+        })
+        .width(num_cols * pixel_size)
+        .height(num_rows * pixel_size),
+    ]
+    .spacing(10)
+    .into()
+}
@@ -0,0 +1,241 @@
+// Module for the picker of the selected color: a saturation/value square beside a hue bar, drawn
+// in the 5-bit color steps that the SNES can show
+use iced::{
+    mouse,
+    widget::{canvas, column, row, text, text_input},
+    Element, Point, Rectangle, Size,
+};
+
+use crate::{
+    helpers::{format_rgb_hex, format_snes_color, scale_color},
+    message::Message,
+    palette_adjust::{hsv_to_rgb, rgb_to_hsv},
+    state::{ColorRGB, EditorState},
+};
+
+const SQUARE_SIZE: f32 = 128.0;
+const HUE_BAR_WIDTH: f32 = 16.0;
+const HUE_BAR_GAP: f32 = 8.0;
+// Cells of the square along each side, and of the hue bar:
+const SQUARE_STEPS: usize = 32;
+const HUE_STEPS: usize = 64;
+
+fn color(c: ColorRGB) -> iced::Color {
+    iced::Color::from_rgb8(scale_color(c[0]), scale_color(c[1]), scale_color(c[2]))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Part {
+    Square,
+    HueBar,
+}
+
+struct ColorPicker {
+    hue: f32,
+    saturation: f32,
+    value: f32,
+}
+
+#[derive(Default)]
+struct ColorPickerState {
+    dragging: Option<Part>,
+}
+
+impl ColorPicker {
+    fn part_at(p: Point) -> Option<Part> {
+        if p.x < SQUARE_SIZE {
+            Some(Part::Square)
+        } else if p.x >= SQUARE_SIZE + HUE_BAR_GAP {
+            Some(Part::HueBar)
+        } else {
+            None
+        }
+    }
+
+    fn pick(&self, part: Part, p: Point) -> Message {
+        let fraction = |v: f32| (v / SQUARE_SIZE).clamp(0.0, 1.0);
+        match part {
+            Part::Square => Message::PickColorHsv {
+                hue: self.hue,
+                saturation: fraction(p.x),
+                value: 1.0 - fraction(p.y),
+            },
+            Part::HueBar => Message::PickColorHsv {
+                hue: fraction(p.y) * 360.0,
+                saturation: self.saturation,
+                value: self.value,
+            },
+        }
+    }
+}
+
+impl canvas::Program<Message> for ColorPicker {
+    type State = ColorPickerState;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (canvas::event::Status, Option<Message>) {
+        match event {
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                if let Some(p) = cursor.position_in(bounds) {
+                    if let Some(part) = Self::part_at(p) {
+                        state.dragging = Some(part);
+                        return (canvas::event::Status::Captured, Some(self.pick(part, p)));
+                    }
+                }
+            }
+            canvas::Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                if let (Some(part), Some(p)) = (state.dragging, cursor.position()) {
+                    let p = Point::new(p.x - bounds.x, p.y - bounds.y);
+                    return (canvas::event::Status::Captured, Some(self.pick(part, p)));
+                }
+            }
+            canvas::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left))
+                if state.dragging.is_some() =>
+            {
+                state.dragging = None;
+                return (canvas::event::Status::Captured, None);
+            }
+            _ => {}
+        }
+        (canvas::event::Status::Ignored, None)
+    }
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &iced::Renderer,
+        _theme: &iced::Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let cell = SQUARE_SIZE / SQUARE_STEPS as f32;
+        for y in 0..SQUARE_STEPS {
+            for x in 0..SQUARE_STEPS {
+                let saturation = x as f32 / (SQUARE_STEPS - 1) as f32;
+                let value = 1.0 - y as f32 / (SQUARE_STEPS - 1) as f32;
+                frame.fill_rectangle(
+                    Point::new(x as f32 * cell, y as f32 * cell),
+                    Size::new(cell, cell),
+                    color(hsv_to_rgb(self.hue, saturation, value)),
+                );
+            }
+        }
+        let hue_cell = SQUARE_SIZE / HUE_STEPS as f32;
+        let bar_x = SQUARE_SIZE + HUE_BAR_GAP;
+        for i in 0..HUE_STEPS {
+            frame.fill_rectangle(
+                Point::new(bar_x, i as f32 * hue_cell),
+                Size::new(HUE_BAR_WIDTH, hue_cell),
+                color(hsv_to_rgb(i as f32 * 360.0 / HUE_STEPS as f32, 1.0, 1.0)),
+            );
+        }
+
+        // Markers of the picked saturation and value, and hue:
+        let marker = canvas::Stroke {
+            style: canvas::stroke::Style::Solid(if self.value > 0.5 {
+                iced::Color::BLACK
+            } else {
+                iced::Color::WHITE
+            }),
+            width: 1.5,
+            ..Default::default()
+        };
+        frame.stroke(
+            &canvas::Path::circle(
+                Point::new(
+                    self.saturation * SQUARE_SIZE,
+                    (1.0 - self.value) * SQUARE_SIZE,
+                ),
+                4.0,
+            ),
+            marker,
+        );
+        let hue_y = self.hue / 360.0 * SQUARE_SIZE;
+        frame.stroke(
+            &canvas::Path::rectangle(
+                Point::new(bar_x - 1.0, hue_y - 2.0),
+                Size::new(HUE_BAR_WIDTH + 2.0, 4.0),
+            ),
+            canvas::Stroke {
+                style: canvas::stroke::Style::Solid(iced::Color::BLACK),
+                ..marker
+            },
+        );
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &Self::State,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        if state.dragging.is_some() || cursor.position_in(bounds).and_then(Self::part_at).is_some()
+        {
+            mouse::Interaction::Crosshair
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+// The hue, saturation, and value to show the selected color at: as last picked, if it's still
+// the same color.
+fn selected_hsv(state: &EditorState) -> (f32, f32, f32) {
+    match state.picked_hsv {
+        Some((color, hsv)) if color == state.selected_color => hsv,
+        _ => rgb_to_hsv(state.selected_color),
+    }
+}
+
+pub fn color_picker_view(state: &EditorState) -> Element<'_, Message> {
+    let (hue, saturation, value) = selected_hsv(state);
+    let [r, g, b] = state.selected_color;
+    let rgb_text = match &state.rgb_hex_text {
+        Some((color, text)) if *color == state.selected_color => text.clone(),
+        _ => format_rgb_hex(state.selected_color),
+    };
+    let snes_text = match &state.snes_color_text {
+        Some((color, text)) if *color == state.selected_color => text.clone(),
+        _ => format_snes_color(state.selected_color),
+    };
+    row![
+        canvas(ColorPicker {
+            hue,
+            saturation,
+            value,
+        })
+        .width(SQUARE_SIZE + HUE_BAR_GAP + HUE_BAR_WIDTH)
+        .height(SQUARE_SIZE),
+        column![
+            text(format!("Red {}, Green {}, Blue {}", r, g, b)).size(12),
+            row![
+                text("RGB").width(40),
+                text_input("#000000", &rgb_text)
+                    .on_input(Message::SetRgbHexText)
+                    .width(80),
+            ]
+            .spacing(5)
+            .align_y(iced::alignment::Vertical::Center),
+            row![
+                text("SNES").width(40),
+                text_input("$0000", &snes_text)
+                    .on_input(Message::SetSnesColorText)
+                    .width(80),
+            ]
+            .spacing(5)
+            .align_y(iced::alignment::Vertical::Center),
+            text("24-bit colors are snapped to the nearest 5-bit one. The SNES color is a BGR555 word.")
+                .size(12),
+        ]
+        .spacing(8),
+    ]
+    .spacing(10)
+    .into()
+}
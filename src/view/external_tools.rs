@@ -0,0 +1,104 @@
+// Module for the Tools panel: registering external commands, running them, and showing their
+// output
+use iced::{
+    alignment::Vertical,
+    widget::{button, column, horizontal_space, row, scrollable, text, text_input, Column},
+    Element, Font, Length,
+};
+use iced_fonts::BOOTSTRAP_FONT;
+
+use crate::{external_tools::ExternalTool, message::Message, state::EditorState};
+
+fn tool_row(idx: usize, tool: &ExternalTool) -> Element<'_, Message> {
+    let set = move |f: fn(&mut ExternalTool, String)| {
+        let tool = tool.clone();
+        move |x| {
+            let mut tool = tool.clone();
+            f(&mut tool, x);
+            Message::SetExternalTool(idx, tool)
+        }
+    };
+    column![
+        row![
+            text_input("Name", &tool.name)
+                .on_input(set(|t, x| t.name = x))
+                .width(Length::Fill),
+            button(text("Run")).on_press_maybe(
+                (!tool.command.trim().is_empty()).then_some(Message::RunExternalTool(idx))
+            ),
+            button(text("\u{F5DE}").font(BOOTSTRAP_FONT))
+                .style(button::danger)
+                .on_press(Message::DeleteExternalTool(idx)),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+        text_input(
+            "Command, e.g. asar main.asm {project}/hack.sfc",
+            &tool.command
+        )
+        .on_input(set(|t, x| t.command = x)),
+        row![
+            text("Working folder").size(12),
+            text_input("Project folder", &tool.working_dir).on_input(set(|t, x| t.working_dir = x)),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+    ]
+    .spacing(5)
+    .into()
+}
+
+pub fn external_tools_view(state: &EditorState) -> Element<'_, Message> {
+    let header = row![
+        text("Tools"),
+        horizontal_space(),
+        button(text("\u{F62A}").font(BOOTSTRAP_FONT))
+            .style(button::secondary)
+            .on_press(Message::ShowExternalTools(false)),
+    ]
+    .spacing(10)
+    .align_y(Vertical::Center);
+
+    let mut list = Column::new().spacing(15);
+    for (i, tool) in state.project_metadata.external_tools.iter().enumerate() {
+        list = list.push(tool_row(i, tool));
+    }
+
+    let mut console = Column::new();
+    for line in &state.tool_console {
+        console = console.push(text(line).font(Font::MONOSPACE).size(12));
+    }
+    let console_status = if state.running_tools > 0 {
+        format!("Output ({} running)", state.running_tools)
+    } else {
+        "Output".to_string()
+    };
+
+    column![
+        header,
+        text(
+            "Commands run from the project folder, or the working folder within it. \
+            {project}, {area}, and {theme} are replaced by the project folder and the main area."
+        )
+        .size(12),
+        scrollable(list).height(Length::FillPortion(1)),
+        button(text("Add tool")).on_press(Message::AddExternalTool),
+        row![
+            text(console_status),
+            horizontal_space(),
+            button(text("Clear"))
+                .style(button::secondary)
+                .on_press(Message::ClearToolConsole),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+        scrollable(console)
+            .anchor_bottom()
+            .width(Length::Fill)
+            .height(Length::FillPortion(1)),
+    ]
+    .spacing(10)
+    .padding(10)
+    .width(420)
+    .into()
+}
@@ -1,17 +1,24 @@
-// Module for displaying and editing 8x8 graphics pixel-by-pixel
+// Module for displaying and editing 8x8 graphics pixel-by-pixel, one tile at a time or across
+// a block of tiles selected in the tileset
 use iced::{
     alignment::Vertical,
     mouse,
-    widget::{canvas, column, horizontal_space, pick_list, row, text, Column},
+    widget::{canvas, column, container, horizontal_space, pick_list, row, text, Column},
     Element, Point, Size,
 };
 use iced_aw::number_input;
 
 use crate::{
+    animated_tiles::AnimationChoice,
     message::Message,
-    state::{ColorIdx, ColorRGB, EditorState, PaletteId, PixelCoord, Tile, TileIdx, Tool},
+    state::{
+        ColorIdx, ColorRGB, EditorState, PaletteId, PaletteIdx, PixelCoord, Tile, TileIdx,
+        TileProperties, TilePropertyChange, Tool,
+    },
 };
 
+use super::brush::brush_preview_view;
+
 #[derive(Debug)]
 struct GraphicsBox {
     colors: [ColorRGB; 16],
@@ -171,6 +178,242 @@ impl canvas::Program<Message> for GraphicsBox {
     }
 }
 
+// Size of the block graphics editor's longer side, in logical pixels:
+const BLOCK_GRAPHICS_SIZE: f32 = 192.0;
+const MAX_BLOCK_PIXEL_SIZE: f32 = 12.0;
+
+// The graphics of a block of tiles, edited as one picture. A brush stroke is kept here until
+// the mouse is released, and then applied to all of its tiles as one edit.
+struct BlockGraphicsBox {
+    colors: [ColorRGB; 16],
+    palette_id: PaletteId,
+    palette_idx: PaletteIdx,
+    tile_idxs: Vec<Vec<TileIdx>>,
+    tiles: Vec<Vec<Tile>>,
+    color_idx: Option<ColorIdx>,
+    pixel_size: f32,
+    tool: Tool,
+}
+
+#[derive(Default)]
+struct BlockGraphicsState {
+    stroke: Option<Vec<(TileIdx, Point<PixelCoord>, ColorIdx)>>,
+}
+
+impl BlockGraphicsBox {
+    fn size(&self) -> (usize, usize) {
+        (
+            self.tiles.first().map_or(0, |r| r.len()) * 8,
+            self.tiles.len() * 8,
+        )
+    }
+
+    // The tile and coordinates within it, of the pixel at a point in the canvas.
+    fn pixel_at(&self, p: Point) -> Option<(TileIdx, Point<PixelCoord>, ColorIdx)> {
+        let (width, height) = self.size();
+        if p.x < 0.0 || p.y < 0.0 {
+            return None;
+        }
+        let x = (p.x / self.pixel_size) as usize;
+        let y = (p.y / self.pixel_size) as usize;
+        if x >= width || y >= height {
+            return None;
+        }
+        let coords = Point::new((x % 8) as PixelCoord, (y % 8) as PixelCoord);
+        let color_idx = self.tiles[y / 8][x / 8].pixels[y % 8][x % 8];
+        Some((self.tile_idxs[y / 8][x / 8], coords, color_idx))
+    }
+
+    fn pixel_color(&self, color_idx: ColorIdx) -> iced::Color {
+        let color = self.colors[color_idx as usize];
+        iced::Color::from_rgb(
+            color[0] as f32 / 31.0,
+            color[1] as f32 / 31.0,
+            color[2] as f32 / 31.0,
+        )
+    }
+}
+
+impl canvas::Program<Message> for BlockGraphicsBox {
+    type State = BlockGraphicsState;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: canvas::Event,
+        bounds: iced::Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (canvas::event::Status, Option<Message>) {
+        let pixel = cursor.position_in(bounds).and_then(|p| self.pixel_at(p));
+        match event {
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let Some((tile_idx, coords, color_idx)) = pixel else {
+                    return (canvas::event::Status::Ignored, None);
+                };
+                if self.tool != Tool::Brush {
+                    return (
+                        canvas::event::Status::Captured,
+                        Some(Message::SelectColor(self.palette_idx, color_idx)),
+                    );
+                }
+                if let Some(brush_color) = self.color_idx {
+                    state.stroke = Some(vec![(tile_idx, coords, brush_color)]);
+                    return (canvas::event::Status::Captured, None);
+                }
+            }
+            canvas::Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                if let (Some(stroke), Some((tile_idx, coords, _)), Some(brush_color)) =
+                    (&mut state.stroke, pixel, self.color_idx)
+                {
+                    if !stroke.iter().any(|&(t, c, _)| t == tile_idx && c == coords) {
+                        stroke.push((tile_idx, coords, brush_color));
+                    }
+                    return (canvas::event::Status::Captured, None);
+                }
+            }
+            canvas::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                if let Some(pixels) = state.stroke.take() {
+                    return (
+                        canvas::event::Status::Captured,
+                        Some(Message::SetTilePixels {
+                            palette_id: self.palette_id,
+                            pixels,
+                        }),
+                    );
+                }
+            }
+            _ => {}
+        }
+        (canvas::event::Status::Ignored, None)
+    }
+
+    fn draw(
+        &self,
+        state: &Self::State,
+        renderer: &iced::Renderer,
+        theme: &iced::Theme,
+        bounds: iced::Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let pixel = Size::new(self.pixel_size, self.pixel_size);
+        for (ty, tile_row) in self.tiles.iter().enumerate() {
+            for (tx, tile) in tile_row.iter().enumerate() {
+                for y in 0..8 {
+                    for x in 0..8 {
+                        frame.fill_rectangle(
+                            Point::new(
+                                (tx * 8 + x) as f32 * self.pixel_size,
+                                (ty * 8 + y) as f32 * self.pixel_size,
+                            ),
+                            pixel,
+                            self.pixel_color(tile.pixels[y][x]),
+                        );
+                    }
+                }
+            }
+        }
+
+        // The stroke in progress, over the tiles that it's drawn on:
+        for &(tile_idx, coords, color_idx) in state.stroke.iter().flatten() {
+            for (ty, row) in self.tile_idxs.iter().enumerate() {
+                for (tx, _) in row.iter().enumerate().filter(|&(_, &t)| t == tile_idx) {
+                    frame.fill_rectangle(
+                        Point::new(
+                            (tx * 8 + coords.x as usize) as f32 * self.pixel_size,
+                            (ty * 8 + coords.y as usize) as f32 * self.pixel_size,
+                        ),
+                        pixel,
+                        self.pixel_color(color_idx),
+                    );
+                }
+            }
+        }
+
+        // Tile boundaries:
+        let (width, height) = self.size();
+        let mut line_color = theme.extended_palette().background.strong.color;
+        line_color.a = 0.6;
+        let stroke = canvas::Stroke {
+            width: 1.0,
+            style: line_color.into(),
+            ..Default::default()
+        };
+        for tx in 1..width / 8 {
+            let x = (tx * 8) as f32 * self.pixel_size;
+            frame.stroke(
+                &canvas::Path::line(
+                    Point::new(x, 0.0),
+                    Point::new(x, height as f32 * self.pixel_size),
+                ),
+                stroke,
+            );
+        }
+        for ty in 1..height / 8 {
+            let y = (ty * 8) as f32 * self.pixel_size;
+            frame.stroke(
+                &canvas::Path::line(
+                    Point::new(0.0, y),
+                    Point::new(width as f32 * self.pixel_size, y),
+                ),
+                stroke,
+            );
+        }
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        _state: &Self::State,
+        bounds: iced::Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        if self.tool == Tool::Brush && cursor.is_over(bounds) && self.color_idx.is_some() {
+            mouse::Interaction::Crosshair
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+// Editor for the graphics of the block of tiles selected in the tileset.
+fn block_graphics_view(state: &EditorState) -> Option<Element<'_, Message>> {
+    let (palette_id, _) = state.selected_tileset_tiles()?;
+    let &pal_idx = state.palettes_id_idx_map.get(&palette_id)?;
+    let pal = &state.palettes[pal_idx];
+    let block = &state.selected_tile_block;
+    let tiles = block
+        .tiles
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|&idx| pal.tiles.get(idx as usize).copied())
+                .collect::<Option<Vec<Tile>>>()
+        })
+        .collect::<Option<Vec<Vec<Tile>>>>()?;
+    let (cols, rows) = (block.size.0 as f32, block.size.1 as f32);
+    let pixel_size = (BLOCK_GRAPHICS_SIZE / (cols.max(rows) * 8.0)).min(MAX_BLOCK_PIXEL_SIZE);
+    Some(
+        canvas(BlockGraphicsBox {
+            colors: pal.colors,
+            palette_id,
+            palette_idx: pal_idx,
+            tile_idxs: block.tiles.clone(),
+            tiles,
+            color_idx: state.color_idx,
+            pixel_size,
+            tool: if pal.category.tiles_editable() {
+                state.tool
+            } else {
+                Tool::Select
+            },
+        })
+        .width(cols * 8.0 * pixel_size)
+        .height(rows * 8.0 * pixel_size)
+        .into(),
+    )
+}
+
 pub fn graphics_view(state: &EditorState) -> Element<Message> {
     let pal = &state.palettes[state.palette_idx];
     let pal_id = pal.id;
@@ -242,6 +485,44 @@ pub fn graphics_view(state: &EditorState) -> Element<Message> {
                         .text_size(12)
                     ]
                     .align_y(Vertical::Center),
+                    row![
+                        text("Animation").width(label_width),
+                        pick_list(
+                            AnimationChoice::all(),
+                            Some(AnimationChoice(tile.animated)),
+                            move |x| Message::SetTileProperties {
+                                palette_id: pal_id,
+                                properties: vec![(
+                                    idx,
+                                    TileProperties {
+                                        animated: x.0,
+                                        ..tile.properties()
+                                    }
+                                )],
+                            }
+                        )
+                        .text_size(12)
+                    ]
+                    .align_y(Vertical::Center),
+                    row![
+                        text("Editor only").width(label_width),
+                        pick_list(
+                            ["No", "Yes"],
+                            Some(if tile.editor_only { "Yes" } else { "No" }),
+                            move |x| Message::SetTileProperties {
+                                palette_id: pal_id,
+                                properties: vec![(
+                                    idx,
+                                    TileProperties {
+                                        editor_only: x == "Yes",
+                                        ..tile.properties()
+                                    }
+                                )],
+                            }
+                        )
+                        .text_size(12)
+                    ]
+                    .align_y(Vertical::Center),
                 ]
                 .spacing(12)
                 .padding([5, 15]),
@@ -256,12 +537,94 @@ pub fn graphics_view(state: &EditorState) -> Element<Message> {
                     pixel_size: 24.0,
                     thickness: 1.0,
                     color_selected: state.color_idx.is_some(),
-                    tool: state.tool,
+                    tool: if pal.category.tiles_editable() {
+                        state.tool
+                    } else {
+                        Tool::Select
+                    },
                 })
                 .width(24 * 8 + 2)
                 .height(24 * 8 + 4)
             ])
             .padding([10, 0]);
+    } else if !state.selected_gfx.is_empty()
+        && state.selected_tile_block.size.0 as usize * state.selected_tile_block.size.1 as usize > 1
+    {
+        // With a multi-tile selection, show the brush instead, so its shape can be edited.
+        if let Some((_, selected)) = state.selected_tileset_tiles() {
+            col = col.push(
+                row![selected_tiles_view(&selected), horizontal_space()]
+                    .push_maybe(block_graphics_view(state))
+                    .padding([10, 0]),
+            );
+        }
+        col = col.push(container(brush_preview_view(state)).padding([10, 10]));
     }
     col.into()
 }
+
+// Properties of the tiles selected in the tileset, edited for all of them at once. Flags that
+// differ between the tiles are shown blank until set.
+fn selected_tiles_view(selected: &[(TileIdx, TileProperties)]) -> Element<'static, Message> {
+    let first = selected[0].1;
+    let flag = |get: fn(&TileProperties) -> bool, change: fn(bool) -> TilePropertyChange| {
+        let value = get(&first);
+        let same = selected.iter().all(|(_, p)| get(p) == value);
+        pick_list(
+            ["No", "Yes"],
+            same.then_some(if value { "Yes" } else { "No" }),
+            move |x| Message::SetSelectedTilesProperty(change(x == "Yes")),
+        )
+        .placeholder("Mixed")
+        .text_size(12)
+    };
+    let same_collision = selected.iter().all(|(_, p)| p.collision == first.collision);
+    let same_animated = selected.iter().all(|(_, p)| p.animated == first.animated);
+    let label_width = 105;
+    column![
+        text(format!("{} tiles selected", selected.len())),
+        row![
+            text("Priority").width(label_width),
+            flag(|p| p.priority, TilePropertyChange::Priority),
+        ]
+        .align_y(Vertical::Center),
+        row![
+            text("Collision").width(label_width),
+            number_input(&first.collision, 0..=255, |x| {
+                Message::SetSelectedTilesProperty(TilePropertyChange::Collision(x))
+            })
+            .width(60),
+            text(if same_collision { "" } else { " (mixed)" }).size(12),
+        ]
+        .align_y(Vertical::Center),
+        row![
+            text("H-flippable").width(label_width),
+            flag(|p| p.h_flippable, TilePropertyChange::HFlippable),
+        ]
+        .align_y(Vertical::Center),
+        row![
+            text("V-flippable").width(label_width),
+            flag(|p| p.v_flippable, TilePropertyChange::VFlippable),
+        ]
+        .align_y(Vertical::Center),
+        row![
+            text("Animation").width(label_width),
+            pick_list(
+                AnimationChoice::all(),
+                same_animated.then_some(AnimationChoice(first.animated)),
+                |x| Message::SetSelectedTilesProperty(TilePropertyChange::Animated(x.0)),
+            )
+            .placeholder("Mixed")
+            .text_size(12),
+        ]
+        .align_y(Vertical::Center),
+        row![
+            text("Editor only").width(label_width),
+            flag(|p| p.editor_only, TilePropertyChange::EditorOnly),
+        ]
+        .align_y(Vertical::Center),
+    ]
+    .spacing(12)
+    .padding([5, 15])
+    .into()
+}
@@ -0,0 +1,261 @@
+// Module for previewing in-game area name labels
+use iced::{
+    alignment::Vertical,
+    mouse,
+    widget::{
+        button, canvas, checkbox, column, container, horizontal_space, pick_list, row, scrollable,
+        text, text_input, Column,
+    },
+    Element, Length, Point, Rectangle, Size,
+};
+use iced_aw::number_input;
+
+use crate::{
+    helpers::{alpha_blend, scale_color},
+    labels::{LabelFont, LabelFontField},
+    message::Message,
+    state::{EditorState, Palette, TileIdx},
+};
+
+use super::modal_background_style;
+
+const PREVIEW_PIXEL_SIZE: f32 = 2.0;
+
+struct LabelPreview<'a> {
+    font: &'a LabelFont,
+    palette: &'a Palette,
+    tiles: Vec<Option<TileIdx>>,
+}
+
+impl LabelPreview<'_> {
+    fn num_cols(&self) -> usize {
+        self.tiles.len().max(self.font.max_width as usize)
+    }
+
+    fn num_rows(&self) -> usize {
+        if self.font.double_height {
+            2
+        } else {
+            1
+        }
+    }
+}
+
+impl canvas::Program<Message> for LabelPreview<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &iced::Renderer,
+        _theme: &iced::Theme,
+        bounds: iced::Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+
+        // Add a pixel of transparent padding around the image, since Iced's
+        // "nearest neighbor" filter results in the edge pixels having the wrong size.
+        let num_cols = self.num_cols() * 8 + 2;
+        let num_rows = self.num_rows() * 8 + 2;
+        let mut data: Vec<u8> = vec![0; num_rows * num_cols * 4];
+        let col_stride = 4;
+        let row_stride = num_cols * col_stride;
+        let colors = &self.palette.colors;
+        for tx in 0..self.num_cols() {
+            for ty in 0..self.num_rows() {
+                // Characters missing from the font are shown as red boxes, and
+                // characters past the maximum width are tinted red:
+                let tile = self.tiles.get(tx).copied().flatten().map(|t| {
+                    self.palette
+                        .tiles
+                        .get(t as usize + ty * 16)
+                        .copied()
+                        .unwrap_or_default()
+                });
+                let missing = tx < self.tiles.len() && tile.is_none();
+                let overflow = tx >= self.font.max_width as usize;
+                let mut tile_addr = (ty * 8 + 1) * row_stride + (tx * 8 + 1) * col_stride;
+                for py in 0..8 {
+                    let mut addr = tile_addr;
+                    for px in 0..8 {
+                        let mut color = match tile {
+                            Some(tile) => colors[tile.pixels[py][px] as usize],
+                            None if missing => [31, 0, 0],
+                            None => colors[0],
+                        };
+                        if overflow {
+                            color = alpha_blend(color, [31, 0, 0], 0.5);
+                        }
+                        let [r, g, b] = color;
+                        data[addr..(addr + 3)].copy_from_slice(&[
+                            scale_color(r),
+                            scale_color(g),
+                            scale_color(b),
+                        ]);
+                        data[addr + 3] = 255;
+                        addr += 4;
+                    }
+                    tile_addr += row_stride;
+                }
+            }
+        }
+
+        let image = iced::advanced::image::Image::new(iced::advanced::image::Handle::from_rgba(
+            num_cols as u32,
+            num_rows as u32,
+            data,
+        ))
+        .filter_method(iced::widget::image::FilterMethod::Nearest);
+
+        frame.draw_image(
+            Rectangle::new(
+                Point::new(0.0, 0.0),
+                Size {
+                    width: num_cols as f32 * PREVIEW_PIXEL_SIZE,
+                    height: num_rows as f32 * PREVIEW_PIXEL_SIZE,
+                },
+            ),
+            image,
+        );
+
+        vec![frame.into_geometry()]
+    }
+}
+
+// Preview of the label as it would be drawn in-game, if a label font is set up.
+pub fn label_preview<'a>(state: &'a EditorState, label: &str) -> Option<Element<'a, Message>> {
+    let font = state.project_metadata.label_font.as_ref()?;
+    let &palette_idx = state.palettes_id_idx_map.get(&font.palette_id)?;
+    let preview = LabelPreview {
+        font,
+        palette: &state.palettes[palette_idx],
+        tiles: font.label_tiles(label),
+    };
+    let width = (preview.num_cols() * 8 + 2) as f32 * PREVIEW_PIXEL_SIZE;
+    let height = (preview.num_rows() * 8 + 2) as f32 * PREVIEW_PIXEL_SIZE;
+    let problem = font.label_problem(label).unwrap_or_default();
+    Some(
+        column![
+            canvas(preview).width(width).height(height),
+            text(problem).size(12).style(text::danger),
+        ]
+        .spacing(5)
+        .into(),
+    )
+}
+
+pub fn area_labels_view<'a>(state: &'a EditorState, preview_text: &str) -> Element<'a, Message> {
+    let enabled = state.project_metadata.label_font.is_some();
+    let mut col = Column::new().spacing(15).push(
+        checkbox("Preview in-game area name labels", enabled).on_toggle(|x| {
+            Message::SetLabelFont(if x { Some(LabelFont::default()) } else { None })
+        }),
+    );
+
+    if let Some(font) = &state.project_metadata.label_font {
+        let palettes: Vec<(String, _)> = state
+            .palettes
+            .iter()
+            .map(|x| (format!("{}: {}", x.id, x.name), x.id))
+            .collect();
+        let palette_names: Vec<String> = palettes.iter().map(|x| x.0.clone()).collect();
+        let selected_palette_name = palettes
+            .iter()
+            .find(|x| x.1 == font.palette_id)
+            .map(|x| x.0.clone());
+        let max_tile = state
+            .palettes_id_idx_map
+            .get(&font.palette_id)
+            .map(|&i| state.palettes[i].tiles.len().saturating_sub(1) as TileIdx)
+            .unwrap_or(0);
+        col = col
+            .push(
+                row![
+                    text("Palette").width(100),
+                    pick_list(palette_names, selected_palette_name, move |name| {
+                        let palette_id = palettes
+                            .iter()
+                            .find(|x| x.0 == name)
+                            .map(|x| x.1)
+                            .unwrap_or_default();
+                        Message::SetLabelFontField(LabelFontField::Palette(palette_id))
+                    })
+                    .width(250),
+                    text("First tile"),
+                    number_input(&font.start_tile, 0..=max_tile, |x| {
+                        Message::SetLabelFontField(LabelFontField::StartTile(x))
+                    })
+                    .width(80),
+                    checkbox("Two tiles high", font.double_height)
+                        .on_toggle(|x| Message::SetLabelFontField(LabelFontField::DoubleHeight(x))),
+                ]
+                .spacing(10)
+                .align_y(Vertical::Center),
+            )
+            .push(
+                row![
+                    text("Characters").width(100),
+                    text_input("", &font.charset)
+                        .on_input(|x| Message::SetLabelFontField(LabelFontField::Charset(x))),
+                ]
+                .spacing(10)
+                .align_y(Vertical::Center),
+            )
+            .push(
+                row![
+                    text("First code").width(100),
+                    number_input(&font.first_code, 0..=255, |x| {
+                        Message::SetLabelFontField(LabelFontField::FirstCode(x))
+                    })
+                    .width(80),
+                    text("Max width"),
+                    number_input(&font.max_width, 1..=64, |x| Message::SetLabelFontField(
+                        LabelFontField::MaxWidth(x)
+                    ))
+                    .width(80),
+                ]
+                .spacing(10)
+                .align_y(Vertical::Center),
+            )
+            .push(
+                row![
+                    text("Preview").width(100),
+                    text_input("", preview_text).on_input(Message::SetLabelPreviewText),
+                ]
+                .spacing(10)
+                .align_y(Vertical::Center),
+            )
+            .push_maybe(label_preview(state, preview_text));
+
+        let mut problems = Column::new().spacing(5);
+        for name in &state.area_names {
+            if let Some(problem) = font.label_problem(name) {
+                problems = problems.push(text(format!("{}: {}", name, problem)).size(12));
+            }
+        }
+        col = col
+            .push(text("Areas with names that don't fit:"))
+            .push(scrollable(problems).height(Length::Shrink));
+    }
+
+    col = col.push(
+        row![
+            button("Close")
+                .style(button::secondary)
+                .on_press(Message::CloseDialogue),
+            horizontal_space(),
+            button("Export name table")
+                .style(button::success)
+                .on_press_maybe(enabled.then_some(Message::ExportAreaNameTable)),
+        ]
+        .spacing(10),
+    );
+
+    container(col)
+        .width(700)
+        .max_height(800)
+        .padding(25)
+        .style(modal_background_style)
+        .into()
+}
@@ -0,0 +1,131 @@
+use iced::{
+    alignment::Vertical,
+    widget::{
+        button, column, container, horizontal_space, image, image::FilterMethod, row, scrollable,
+        text, text_input, Column,
+    },
+    ContentFit, Element, Length,
+};
+use iced_fonts::BOOTSTRAP_FONT;
+
+use crate::{
+    library::{LibraryItem, LibraryKind},
+    message::Message,
+    state::EditorState,
+};
+
+use super::modal_background_style;
+
+fn library_item_view(item: &LibraryItem) -> Element<'_, Message> {
+    let thumbnail: Element<Message> = match &item.thumbnail {
+        Some(handle) => image(handle.clone())
+            .filter_method(FilterMethod::Nearest)
+            .content_fit(ContentFit::Contain)
+            .width(96)
+            .height(48)
+            .into(),
+        None => horizontal_space().width(96).into(),
+    };
+    let mut details = item.kind().to_string();
+    if !item.tags.is_empty() {
+        details += &format!(" - {}", item.tags.join(", "));
+    }
+    if !item.source.is_empty() {
+        details += &format!(" (from {})", item.source);
+    }
+    row![
+        container(thumbnail).width(96).center_x(96),
+        column![text(&item.name), text(details).size(12)].spacing(2),
+        horizontal_space(),
+        button(text("Import"))
+            .style(button::secondary)
+            .on_press(Message::ImportFromLibrary(item.name.clone())),
+        button(text("\u{F5DE}").font(BOOTSTRAP_FONT))
+            .style(button::danger)
+            .on_press(Message::DeleteFromLibrary(item.name.clone())),
+    ]
+    .spacing(10)
+    .align_y(Vertical::Center)
+    .into()
+}
+
+pub fn library_view<'a>(
+    state: &'a EditorState,
+    items: &'a [LibraryItem],
+    filter: &'a str,
+    name: &'a str,
+    tags: &'a str,
+) -> Element<'a, Message> {
+    let library_dir = state
+        .global_config
+        .library_dir
+        .as_ref()
+        .map(|p| p.display().to_string())
+        .unwrap_or_default();
+    let mut item_list = Column::new().spacing(5);
+    let mut num_shown = 0;
+    for item in items.iter().filter(|item| item.matches(filter)) {
+        item_list = item_list.push(library_item_view(item));
+        num_shown += 1;
+    }
+    if num_shown == 0 {
+        item_list = item_list.push(text(if items.is_empty() {
+            "The library is empty. Publish palettes, tilesets, or stamps below."
+        } else {
+            "No items match the filter."
+        }));
+    }
+    let can_publish = !name.trim().is_empty();
+    let has_selection =
+        state.selected_tile_block.size.0 > 0 && state.selected_tile_block.size.1 > 0;
+    let palette_name = &state.palettes[state.palette_idx].name;
+
+    container(
+        column![
+            row![
+                text("Library"),
+                text(library_dir).size(12),
+                horizontal_space(),
+                button(text("\u{F3D7}").font(BOOTSTRAP_FONT))
+                    .style(button::secondary)
+                    .on_press(Message::PickLibraryDir),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            text_input("Filter by name, kind, or tag", filter).on_input(Message::SetLibraryFilter),
+            scrollable(item_list).height(300),
+            text(format!("Publish from palette {}:", palette_name)),
+            row![
+                text_input("Name", name)
+                    .on_input(Message::SetLibraryPublishName)
+                    .width(Length::FillPortion(1)),
+                text_input("Tags (comma-separated)", tags)
+                    .on_input(Message::SetLibraryPublishTags)
+                    .width(Length::FillPortion(2)),
+            ]
+            .spacing(10),
+            row![
+                button(text("Colors")).on_press_maybe(
+                    can_publish.then_some(Message::PublishToLibrary(LibraryKind::Palette))
+                ),
+                button(text("Tileset")).on_press_maybe(
+                    can_publish.then_some(Message::PublishToLibrary(LibraryKind::Tileset))
+                ),
+                button(text("Selection as stamp")).on_press_maybe(
+                    (can_publish && has_selection)
+                        .then_some(Message::PublishToLibrary(LibraryKind::Stamp))
+                ),
+                horizontal_space(),
+                button(text("Close"))
+                    .style(button::secondary)
+                    .on_press(Message::CloseDialogue),
+            ]
+            .spacing(10),
+        ]
+        .spacing(15),
+    )
+    .width(600)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
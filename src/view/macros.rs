@@ -0,0 +1,98 @@
+// Module for recording and replaying edit macros
+use iced::{
+    alignment::Vertical,
+    widget::{
+        button, column, container, horizontal_space, row, scrollable, text, text_input, Column,
+    },
+    Element, Length, Point,
+};
+use iced_aw::number_input;
+
+use crate::{
+    message::Message,
+    state::{EditorState, TileCoord},
+};
+
+use super::modal_background_style;
+
+// Button to start/stop recording a macro, and to open the list of macros.
+pub fn macro_controls(state: &EditorState) -> Element<'_, Message> {
+    let record_button = match &state.macro_recording {
+        Some(recorded) => button(
+            row![
+                text("\u{F517}").font(iced_fonts::BOOTSTRAP_FONT),
+                text(format!("{}", recorded.len())),
+            ]
+            .spacing(5),
+        )
+        .style(button::danger),
+        None => button(text("\u{F518}").font(iced_fonts::BOOTSTRAP_FONT)).style(button::secondary),
+    };
+    row![
+        record_button.on_press(Message::ToggleMacroRecording),
+        button(text("\u{F2CB}").font(iced_fonts::BOOTSTRAP_FONT))
+            .style(button::secondary)
+            .on_press(Message::MacrosDialogue),
+    ]
+    .spacing(5)
+    .into()
+}
+
+pub fn macros_view(state: &EditorState, x: TileCoord, y: TileCoord) -> Element<'_, Message> {
+    let area = state.main_area();
+    let max_x = area.size.0 as TileCoord * 32 - 1;
+    let max_y = area.size.1 as TileCoord * 32 - 1;
+    let mut list = Column::new().spacing(10);
+    for (i, m) in state.project_metadata.macros.iter().enumerate() {
+        list = list.push(
+            row![
+                text_input("", &m.name)
+                    .on_input(move |name| Message::SetMacroName(i, name))
+                    .width(200),
+                text(format!("{} steps", m.steps.len())).width(80),
+                horizontal_space(),
+                button(text("Replay")).on_press(Message::ReplayMacro {
+                    macro_idx: i,
+                    area_id: state.main_area_id.clone(),
+                    coords: Point::new(x, y),
+                }),
+                button(text("\u{F5DE}").font(iced_fonts::BOOTSTRAP_FONT))
+                    .style(button::danger)
+                    .on_press(Message::DeleteMacro(i)),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+        );
+    }
+    if state.project_metadata.macros.is_empty() {
+        list = list.push(text(
+            "No macros yet. Use the record button to record brush stamps and tile property changes.",
+        ));
+    }
+
+    container(
+        column![
+            text("Macros"),
+            row![
+                text(format!("Replay in {} ({}) at", area.name, area.theme)),
+                text("X"),
+                number_input(&x, 0..=max_x, move |x| Message::SetMacroReplayCoords(x, y)).width(80),
+                text("Y"),
+                number_input(&y, 0..=max_y, move |y| Message::SetMacroReplayCoords(x, y)).width(80),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            text("Brush stamps are placed relative to this location (in 8x8 tiles).").size(12),
+            scrollable(list).height(Length::Shrink),
+            button(text("Close"))
+                .style(button::secondary)
+                .on_press(Message::CloseDialogue),
+        ]
+        .spacing(15),
+    )
+    .width(600)
+    .max_height(600)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
@@ -0,0 +1,280 @@
+// Module for listing and editing metatiles (16x16 and 32x32 blocks of tiles) in the side panel
+use iced::{
+    alignment::Vertical,
+    mouse,
+    widget::{
+        button, canvas, column, horizontal_space, row, scrollable, text, text_input, Column, Row,
+    },
+    Element, Point, Rectangle, Size,
+};
+
+use crate::{
+    area_raster::{RasterHighlights, ScreenRasterizer},
+    area_shapes::AreaCell,
+    message::Message,
+    metatiles::{Metatile, MetatileSize},
+    state::{EditorState, Flip, PaletteId, TileCoord, TileIdx},
+};
+
+const THUMBNAIL_PIXEL_SIZE: f32 = 2.0;
+// Size of the editor's image, in screen pixels (so that either size of metatile fills it):
+const EDITOR_SIZE: f32 = 256.0;
+
+struct MetatileBox<'a> {
+    metatile: &'a Metatile,
+    rasterizer: ScreenRasterizer<'a>,
+    pixel_size: f32,
+    // Index of the metatile being edited, and the tile that clicking a cell sets it to:
+    edit: Option<(usize, Option<(PaletteId, TileIdx)>)>,
+}
+
+impl MetatileBox<'_> {
+    fn cells(&self) -> Vec<AreaCell> {
+        let block = &self.metatile.block;
+        let mut cells = vec![];
+        for y in 0..block.size.1 {
+            for x in 0..block.size.0 {
+                let (tx, ty) = (x as usize, y as usize);
+                cells.push(AreaCell {
+                    x,
+                    y,
+                    palette: block.palettes[ty][tx],
+                    tile: block.tiles[ty][tx],
+                    flip: block.flips[ty][tx],
+                });
+            }
+        }
+        cells
+    }
+}
+
+impl canvas::Program<Message> for MetatileBox<'_> {
+    type State = ();
+
+    fn update(
+        &self,
+        _state: &mut Self::State,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (canvas::event::Status, Option<Message>) {
+        let Some((metatile_idx, selected)) = self.edit else {
+            return (canvas::event::Status::Ignored, None);
+        };
+        let canvas::Event::Mouse(mouse::Event::ButtonPressed(btn)) = event else {
+            return (canvas::event::Status::Ignored, None);
+        };
+        let Some(p) = cursor.position_in(bounds) else {
+            return (canvas::event::Status::Ignored, None);
+        };
+        let n = self.metatile.size.tiles();
+        let cell = |c: f32| {
+            ((f32::max(c - self.pixel_size, 0.0) / (8.0 * self.pixel_size)) as TileCoord).min(n - 1)
+        };
+        let (x, y) = (cell(p.x), cell(p.y));
+        let block = &self.metatile.block;
+        let (palette_id, tile_idx, flip) = match btn {
+            // Set the cell to the selected tile:
+            mouse::Button::Left => match selected {
+                Some((palette_id, tile_idx)) => (palette_id, tile_idx, Flip::None),
+                None => return (canvas::event::Status::Ignored, None),
+            },
+            // Cycle through the flips of the cell's tile:
+            mouse::Button::Right => {
+                let flip = match block.flips[y as usize][x as usize] {
+                    Flip::None => Flip::Horizontal,
+                    Flip::Horizontal => Flip::Vertical,
+                    Flip::Vertical => Flip::Both,
+                    Flip::Both => Flip::None,
+                };
+                (
+                    block.palettes[y as usize][x as usize],
+                    block.tiles[y as usize][x as usize],
+                    flip,
+                )
+            }
+            _ => return (canvas::event::Status::Ignored, None),
+        };
+        (
+            canvas::event::Status::Captured,
+            Some(Message::SetMetatileCell {
+                metatile_idx,
+                x,
+                y,
+                palette_id,
+                tile_idx,
+                flip,
+            }),
+        )
+    }
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &iced::Renderer,
+        _theme: &iced::Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        if let Some(raster) = self.rasterizer.rasterize_cells(&self.cells()) {
+            let image =
+                iced::advanced::image::Image::new(iced::advanced::image::Handle::from_rgba(
+                    raster.width as u32,
+                    raster.height as u32,
+                    raster.data,
+                ))
+                .filter_method(iced::widget::image::FilterMethod::Nearest);
+            frame.draw_image(
+                Rectangle::new(
+                    Point::ORIGIN,
+                    Size::new(
+                        raster.width as f32 * self.pixel_size,
+                        raster.height as f32 * self.pixel_size,
+                    ),
+                ),
+                image,
+            );
+        }
+        if self.edit.is_some() {
+            // Outline the cells, for editing them one by one:
+            let n = self.metatile.size.tiles();
+            let cell_size = 8.0 * self.pixel_size;
+            for y in 0..n {
+                for x in 0..n {
+                    frame.stroke_rectangle(
+                        Point::new(
+                            self.pixel_size + x as f32 * cell_size,
+                            self.pixel_size + y as f32 * cell_size,
+                        ),
+                        Size::new(cell_size, cell_size),
+                        canvas::Stroke {
+                            width: 1.0,
+                            style: iced::Color::from_rgba(0.5, 0.5, 0.5, 0.5).into(),
+                            ..Default::default()
+                        },
+                    );
+                }
+            }
+        }
+        vec![frame.into_geometry()]
+    }
+}
+
+fn metatile_box<'a>(
+    state: &'a EditorState,
+    metatile: &'a Metatile,
+    pixel_size: f32,
+    edit: Option<(usize, Option<(PaletteId, TileIdx)>)>,
+) -> Element<'a, Message> {
+    let size = (metatile.size.tiles() as f32 * 8.0 + 2.0) * pixel_size;
+    canvas(MetatileBox {
+        metatile,
+        rasterizer: ScreenRasterizer {
+            area: state.main_area(),
+            palettes: &state.palettes,
+            palettes_id_idx_map: &state.palettes_id_idx_map,
+            highlights: RasterHighlights::default(),
+        },
+        pixel_size,
+        edit,
+    })
+    .width(size)
+    .height(size)
+    .into()
+}
+
+pub fn metatiles_view(state: &EditorState) -> Element<'_, Message> {
+    let brush_size = state.selected_tile_block.size;
+    let new_button = |size: MetatileSize| {
+        let n = size.tiles();
+        button(text(format!("New {}", size)))
+            .style(button::secondary)
+            .on_press_maybe(
+                (brush_size.0 >= n && brush_size.1 >= n).then_some(Message::AddMetatile(size)),
+            )
+    };
+    let header = row![
+        text("Metatiles"),
+        horizontal_space(),
+        new_button(MetatileSize::Tile16),
+        new_button(MetatileSize::Tile32),
+        button(text("\u{F62A}").font(iced_fonts::BOOTSTRAP_FONT))
+            .style(button::secondary)
+            .on_press(Message::ShowMetatiles(false)),
+    ]
+    .spacing(10)
+    .align_y(Vertical::Center);
+
+    let metatiles = &state.project_metadata.metatiles;
+    let mut col = column![
+        header,
+        text("New metatiles are taken from the top-left of the brush.").size(12),
+    ]
+    .spacing(10)
+    .padding(10)
+    .width(420);
+    if metatiles.is_empty() {
+        return col.push(text("No metatiles yet.")).into();
+    }
+
+    let mut list = Row::new().spacing(10);
+    for (i, metatile) in metatiles.iter().enumerate() {
+        list = list.push(
+            column![
+                button(metatile_box(state, metatile, THUMBNAIL_PIXEL_SIZE, None))
+                    .padding(2)
+                    .style(if state.metatile_idx == Some(i) {
+                        button::primary
+                    } else {
+                        button::secondary
+                    })
+                    .on_press(Message::SelectMetatile(Some(i))),
+                text(metatile.name.clone()).size(12),
+            ]
+            .spacing(2),
+        );
+    }
+    col = col.push(scrollable(list.wrap()).height(200));
+
+    if let Some(stamping) = state.stamp_metatile() {
+        col = col.push(
+            text(format!(
+                "Stamping {}: click in an area to place it on the {} grid.",
+                stamping.name, stamping.size
+            ))
+            .size(12),
+        );
+    }
+
+    if let Some((i, metatile)) = state
+        .metatile_idx
+        .and_then(|i| Some((i, metatiles.get(i)?)))
+    {
+        let selected_tile = state
+            .tile_idx
+            .map(|tile_idx| (state.palettes[state.palette_idx].id, tile_idx));
+        let pixel_size = EDITOR_SIZE / (metatile.size.tiles() as f32 * 8.0 + 2.0);
+        let editor: Column<Message> = column![
+            row![
+                text_input("", &metatile.name)
+                    .on_input(move |name| Message::SetMetatileName(i, name))
+                    .width(200),
+                text(metatile.size.to_string()),
+                horizontal_space(),
+                button(text("Stamp")).on_press(Message::StampMetatile(i)),
+                button(text("\u{F5DE}").font(iced_fonts::BOOTSTRAP_FONT))
+                    .style(button::danger)
+                    .on_press(Message::DeleteMetatile(i)),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            metatile_box(state, metatile, pixel_size, Some((i, selected_tile))),
+            text("Click a cell to set it to the selected tile, or right-click to flip it.")
+                .size(12),
+        ]
+        .spacing(10);
+        col = col.push(editor);
+    }
+    col.into()
+}
@@ -0,0 +1,209 @@
+// Module for the minimap in the corner of an area view: the whole area scaled down, with the
+// part of it that's scrolled into view outlined. Clicking or dragging on it scrolls the view
+// there.
+use std::cell::RefCell;
+
+use hashbrown::HashMap;
+use iced::{
+    mouse,
+    widget::{canvas, image::FilterMethod},
+    Element, Point, Rectangle, Size, Vector,
+};
+
+use crate::{
+    area_raster::{
+        RasterHighlights, ScreenRasterCache, ScreenRasterizer, RASTER_SIZE, SCREEN_PIXELS,
+    },
+    message::Message,
+    state::{Area, AreaPosition, EditorState, Palette, PaletteId},
+};
+
+// Size of the longer side of the minimap, in logical pixels:
+pub const MINIMAP_SIZE: f32 = 160.0;
+
+const VIEWPORT_COLOR: iced::Color = iced::Color::from_rgb(1.0, 1.0, 0.2);
+
+struct Minimap<'a> {
+    position: AreaPosition,
+    area: &'a Area,
+    palettes: &'a [Palette],
+    palettes_id_idx_map: &'a HashMap<PaletteId, usize>,
+    // Logical pixels per area pixel, in the area view and in the minimap:
+    pixel_size: f32,
+    scale: f32,
+    // The part of the area view that is scrolled into view, in logical pixels:
+    viewport: Rectangle,
+}
+
+#[derive(Default)]
+struct MinimapState {
+    dragging: bool,
+    // Images of the area's screens, kept between frames:
+    rasters: RefCell<ScreenRasterCache>,
+}
+
+impl Minimap<'_> {
+    // Message scrolling the area view to center on a point of the minimap.
+    fn scroll_message(&self, p: Point) -> Message {
+        let center = Vector::new(p.x, p.y) * (self.pixel_size / self.scale);
+        let size = Vector::new(self.viewport.width, self.viewport.height);
+        let offset = center - size * 0.5;
+        Message::ScrollAreaTo {
+            position: self.position,
+            offset: Vector::new(offset.x.max(0.0), offset.y.max(0.0)),
+        }
+    }
+}
+
+impl canvas::Program<Message> for Minimap<'_> {
+    type State = MinimapState;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (canvas::event::Status, Option<Message>) {
+        match event {
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                if let Some(p) = cursor.position_in(bounds) {
+                    state.dragging = true;
+                    return (
+                        canvas::event::Status::Captured,
+                        Some(self.scroll_message(p)),
+                    );
+                }
+            }
+            canvas::Event::Mouse(mouse::Event::CursorMoved { .. }) if state.dragging => {
+                if let Some(p) = cursor.position() {
+                    let p = Point::new(
+                        (p.x - bounds.x).clamp(0.0, bounds.width),
+                        (p.y - bounds.y).clamp(0.0, bounds.height),
+                    );
+                    return (
+                        canvas::event::Status::Captured,
+                        Some(self.scroll_message(p)),
+                    );
+                }
+            }
+            canvas::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left))
+                if state.dragging =>
+            {
+                state.dragging = false;
+                return (canvas::event::Status::Captured, None);
+            }
+            _ => {}
+        }
+        (canvas::event::Status::Ignored, None)
+    }
+
+    fn draw(
+        &self,
+        state: &Self::State,
+        renderer: &iced::Renderer,
+        _theme: &iced::Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        frame.fill_rectangle(
+            Point::ORIGIN,
+            bounds.size(),
+            iced::Color::from_rgba(0.0, 0.0, 0.0, 0.6),
+        );
+        let rasterizer = ScreenRasterizer {
+            area: self.area,
+            palettes: self.palettes,
+            palettes_id_idx_map: self.palettes_id_idx_map,
+            highlights: RasterHighlights::default(),
+        };
+        let mut rasters = state.rasters.borrow_mut();
+        for sy in 0..self.area.size.1 as usize {
+            for sx in 0..self.area.size.0 as usize {
+                let handle = rasters.get(&rasterizer, sy * self.area.size.0 as usize + sx);
+                let image =
+                    iced::advanced::image::Image::new(handle).filter_method(FilterMethod::Linear);
+                frame.draw_image(
+                    Rectangle::new(
+                        Point::new(
+                            (sx * SCREEN_PIXELS) as f32 * self.scale,
+                            (sy * SCREEN_PIXELS) as f32 * self.scale,
+                        ),
+                        Size::new(RASTER_SIZE as f32, RASTER_SIZE as f32) * self.scale,
+                    ),
+                    image,
+                );
+            }
+        }
+
+        let view_scale = self.scale / self.pixel_size;
+        let top_left = Point::new(self.viewport.x * view_scale, self.viewport.y * view_scale);
+        let size = Size::new(
+            (self.viewport.width * view_scale).min(bounds.width - top_left.x),
+            (self.viewport.height * view_scale).min(bounds.height - top_left.y),
+        );
+        frame.stroke(
+            &canvas::Path::rectangle(top_left, size),
+            canvas::Stroke {
+                style: canvas::stroke::Style::Solid(VIEWPORT_COLOR),
+                width: 1.5,
+                ..Default::default()
+            },
+        );
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &Self::State,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        if state.dragging {
+            mouse::Interaction::Grabbing
+        } else if cursor.is_over(bounds) {
+            mouse::Interaction::Pointer
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+// The minimap of an area view with the given visible part, if it's shown and the area doesn't
+// fit in the view.
+pub fn minimap_view(
+    state: &EditorState,
+    position: AreaPosition,
+    viewport: Rectangle,
+) -> Option<Element<'_, Message>> {
+    if !state.show_minimap {
+        return None;
+    }
+    let area = state.area(position);
+    let pixel_size = state.area_pixel_size(position);
+    let area_size = Size::new(
+        area.size.0 as f32 * SCREEN_PIXELS as f32,
+        area.size.1 as f32 * SCREEN_PIXELS as f32,
+    );
+    if area_size.width * pixel_size <= viewport.width
+        && area_size.height * pixel_size <= viewport.height
+    {
+        return None;
+    }
+    let scale = MINIMAP_SIZE / area_size.width.max(area_size.height);
+    Some(
+        canvas(Minimap {
+            position,
+            area,
+            palettes: &state.palettes,
+            palettes_id_idx_map: &state.palettes_id_idx_map,
+            pixel_size,
+            scale,
+            viewport,
+        })
+        .width(area_size.width * scale)
+        .height(area_size.height * scale)
+        .into(),
+    )
+}
@@ -1,20 +1,37 @@
 // Module for displaying and editing the 16 colors of palettes
+use std::collections::BTreeMap;
+
 use iced::{
     alignment::Vertical,
     mouse,
     widget::{
-        button, canvas, column, container, pick_list, row, text, text_input, Column, Row, Space,
+        button, canvas, checkbox, column, container, horizontal_space, mouse_area, pick_list, row,
+        scrollable, slider, text, text_input, Column, Row, Space,
     },
     Element, Length, Size,
 };
 use iced_aw::number_input;
 
 use crate::{
+    flip_analysis::FlipSuggestion,
+    helpers::format_age,
     message::Message,
-    state::{ColorIdx, ColorRGB, EditorState, Focus, PaletteId, PaletteIdx, Tool},
+    palette_adjust::{PaletteAdjust, PaletteAdjustChange},
+    palette_groups::{
+        group_size, moved_to_group, normalized_group, palette_group, palette_group_names,
+        shown_palettes, ALL_PALETTE_GROUPS, UNGROUPED_PALETTE_GROUP,
+    },
+    ramps::MIN_RAMP_LEN,
+    state::{
+        ColorIdx, ColorRGB, EditorState, Flip, Focus, PaletteCategory, PaletteId, PaletteIdx,
+        PaletteVersion, PickListMenu, Tool,
+    },
+    tile_dedup::DuplicateTile,
+    tile_graphics::GraphicsFormat,
+    tile_table::TileTableFormat,
 };
 
-use super::modal_background_style;
+use super::{color_picker::color_picker_view, modal_background_style};
 
 #[derive(Debug)]
 struct ColorBox {
@@ -138,11 +155,16 @@ impl canvas::Program<Message> for ColorBox {
 }
 
 pub fn selected_palette_view(state: &EditorState) -> Element<Message> {
-    let palette_names: Vec<String> = state
-        .palettes
+    let palette_names: Vec<String> = shown_palettes(state)
         .iter()
         .map(|x| format!("{}: {}", x.id, x.name))
         .collect();
+    let mut group_choices = vec![ALL_PALETTE_GROUPS.to_string()];
+    group_choices.extend(palette_group_names(state));
+    let group_filter = state
+        .palette_group_filter
+        .clone()
+        .unwrap_or(ALL_PALETTE_GROUPS.to_string());
     let pal = &state.palettes[state.palette_idx];
     let selected_palette_name = format!("{}: {}", pal.id, pal.name);
 
@@ -168,46 +190,159 @@ pub fn selected_palette_view(state: &EditorState) -> Element<Message> {
         );
     }
 
-    let rgb_width = 80;
     let mut col = column![
         row![
             text("Palette"),
-            pick_list(
-                palette_names,
-                Some(selected_palette_name),
-                Message::SelectPalette
+            mouse_area(
+                pick_list(
+                    palette_names,
+                    Some(selected_palette_name),
+                    Message::SelectPalette
+                )
+                .on_open(Message::Focus(Focus::PickPalette))
+                .width(Length::Fill)
             )
-            .on_open(Message::Focus(Focus::PickPalette))
-            .width(Length::Fill),
+            .on_right_press(Message::ContextMenu(PickListMenu::Palette)),
             button(text("\u{F64D}").font(iced_fonts::BOOTSTRAP_FONT))
                 .style(button::success)
                 .on_press(Message::AddPaletteDialogue),
             button(text("\u{F4CB}").font(iced_fonts::BOOTSTRAP_FONT))
                 .on_press(Message::RenamePaletteDialogue),
+            button(text("\u{F292}").font(iced_fonts::BOOTSTRAP_FONT))
+                .style(button::secondary)
+                .on_press(Message::PaletteHistoryDialogue),
+            button(text("\u{F675}").font(iced_fonts::BOOTSTRAP_FONT))
+                .style(button::secondary)
+                .on_press(Message::FlipSuggestionsDialogue),
+            button(text("\u{F4B2}").font(iced_fonts::BOOTSTRAP_FONT))
+                .style(button::secondary)
+                .on_press(Message::ColorRampsDialogue),
+            button(text("\u{F56B}").font(iced_fonts::BOOTSTRAP_FONT))
+                .style(button::secondary)
+                .on_press(Message::AdjustPaletteDialogue),
+            button(text("\u{F36D}").font(iced_fonts::BOOTSTRAP_FONT))
+                .style(button::secondary)
+                .on_press(Message::ExportPaletteDialogue),
+            button(text("\u{F42A}").font(iced_fonts::BOOTSTRAP_FONT))
+                .style(button::secondary)
+                .on_press(Message::TileGraphicsDialogue),
+            button(text("\u{F1A5}").font(iced_fonts::BOOTSTRAP_FONT))
+                .style(button::secondary)
+                .on_press(Message::LibraryDialogue),
         ]
         .spacing(10)
         .align_y(iced::alignment::Vertical::Center),
+        row![
+            text_input("Filter palettes", &state.palette_filter)
+                .on_input(Message::SetPaletteFilter)
+                .size(12)
+                .width(Length::Fill),
+            pick_list(
+                group_choices,
+                Some(group_filter),
+                Message::SetPaletteGroupFilter
+            )
+            .text_size(12),
+            button(text("\u{F5B2}").font(iced_fonts::BOOTSTRAP_FONT).size(12))
+                .style(button::secondary)
+                .on_press(Message::PaletteGroupsDialogue),
+        ]
+        .spacing(5)
+        .align_y(iced::alignment::Vertical::Center),
         colors_row,
     ]
     .spacing(5);
 
-    if state.color_idx.is_some() {
+    let palette_id = pal.id;
+    col = col.push(
+        row![
+            text("Category"),
+            pick_list(PaletteCategory::ALL, Some(pal.category), move |category| {
+                Message::SetPaletteCategory {
+                    palette_id,
+                    category,
+                }
+            })
+            .text_size(12),
+            text(match pal.category {
+                PaletteCategory::HUD => "Shared by all maps: tiles are read-only",
+                PaletteCategory::Custom => "",
+                _ => "Colors 1-7 are used in the game",
+            })
+            .size(12),
+            horizontal_space(),
+            text(if pal.modified {
+                "Unsaved changes"
+            } else if state.project_metadata.unexported_palettes.contains(&pal.id) {
+                "Not exported"
+            } else {
+                ""
+            })
+            .size(12),
+            text(format!("Group: {}", palette_group(pal))).size(12),
+        ]
+        .spacing(5)
+        .align_y(iced::alignment::Vertical::Center),
+    );
+    for problem in pal.category_problems() {
         col = col.push(
             row![
-                text("Red"),
-                number_input(&state.selected_color[0], 0..=31, Message::ChangeRed).width(rgb_width),
-                iced::widget::Space::with_width(10),
-                text("Green"),
-                number_input(&state.selected_color[1], 0..=31, Message::ChangeGreen)
-                    .width(rgb_width),
-                iced::widget::Space::with_width(10),
-                text("Blue"),
-                number_input(&state.selected_color[2], 0..=31, Message::ChangeBlue)
-                    .width(rgb_width),
+                text("\u{F33B}").font(iced_fonts::BOOTSTRAP_FONT).size(12),
+                text(problem).size(12),
+            ]
+            .spacing(5),
+        );
+    }
+
+    if let Some(color_idx) = state.color_idx {
+        let palette_id = pal.id;
+        // Index 0 is the transparent color, so it isn't eligible for swapping.
+        let swap_targets: Vec<ColorIdx> = (1..16).filter(|&i| i != color_idx).collect();
+        col = col.push(color_picker_view(state));
+        col = col.push(
+            row![
+                button(
+                    row![
+                        text("\u{F342}").font(iced_fonts::BOOTSTRAP_FONT),
+                        text("Pick from area"),
+                    ]
+                    .spacing(5)
+                )
+                .style(if state.eyedropper {
+                    button::primary
+                } else {
+                    button::secondary
+                })
+                .on_press(Message::ToggleEyedropper),
+                button(text("Copy SNES"))
+                    .style(button::secondary)
+                    .on_press(Message::CopySnesColor),
+                button(text("Paste SNES"))
+                    .style(button::secondary)
+                    .on_press(Message::PasteSnesColor),
             ]
             .spacing(5)
             .align_y(iced::alignment::Vertical::Center),
         );
+        if color_idx != 0 {
+            col = col.push(
+                row![
+                    text(format!("Swap color {} with", color_idx)),
+                    pick_list(swap_targets, None::<ColorIdx>, move |x| {
+                        Message::SwapColors {
+                            palette_id,
+                            color_idx_1: color_idx,
+                            color_idx_2: x,
+                        }
+                    })
+                    .placeholder("index")
+                    .width(80),
+                    text("(tile pixels are updated to match)").size(12),
+                ]
+                .spacing(5)
+                .align_y(iced::alignment::Vertical::Center),
+            );
+        }
     }
 
     row![col].padding(10).into()
@@ -347,3 +482,756 @@ pub fn used_palettes_view(state: &EditorState) -> Element<Message> {
     }
     row![col].padding(10).into()
 }
+
+// A non-interactive color sample, optionally outlined to mark it as changed.
+pub fn color_swatch<'a>(color: ColorRGB, changed: bool) -> Element<'a, Message> {
+    container(Space::new(14, 14))
+        .style(move |_theme| container::Style {
+            background: Some(
+                iced::Color::from_rgb(
+                    color[0] as f32 / 31.0,
+                    color[1] as f32 / 31.0,
+                    color[2] as f32 / 31.0,
+                )
+                .into(),
+            ),
+            border: if changed {
+                iced::border::color(iced::Color::from_rgb8(255, 105, 180)).width(2.0)
+            } else {
+                iced::Border::default()
+            },
+            ..container::Style::default()
+        })
+        .into()
+}
+
+pub fn palette_history_view<'a>(
+    state: &'a EditorState,
+    versions: &'a [PaletteVersion],
+) -> Element<'a, Message> {
+    let current = &state.palettes[state.palette_idx];
+
+    let mut version_col: Column<Message> = Column::new().spacing(15);
+    if current.modified {
+        let mut current_row: Row<Message> = Row::new().spacing(2);
+        for &c in &current.colors {
+            current_row = current_row.push(color_swatch(c, false));
+        }
+        version_col = version_col.push(
+            column![
+                text("Current (unsaved)"),
+                text(format!("{} tiles", current.tiles.len())).size(12),
+                row![text("Current").width(60).size(12), current_row].align_y(Vertical::Center),
+            ]
+            .spacing(5),
+        );
+    }
+    let mut cnt_shown = 0;
+    for version in versions {
+        let old = &version.palette;
+        if old.colors == current.colors && old.tiles == current.tiles {
+            continue;
+        }
+        cnt_shown += 1;
+        let changed: Vec<bool> = (0..16)
+            .map(|i| old.colors[i] != current.colors[i])
+            .collect();
+        let cnt_changed = changed.iter().filter(|&&x| x).count();
+        let cnt_tiles_changed = old
+            .tiles
+            .iter()
+            .zip(current.tiles.iter())
+            .filter(|(a, b)| a != b)
+            .count();
+        let tile_diff = old.tiles.len() as i64 - current.tiles.len() as i64;
+
+        let mut old_row: Row<Message> = Row::new().spacing(2);
+        let mut new_row: Row<Message> = Row::new().spacing(2);
+        for (i, &c) in changed.iter().enumerate() {
+            old_row = old_row.push(color_swatch(old.colors[i], c));
+            new_row = new_row.push(color_swatch(current.colors[i], c));
+        }
+
+        let mut restored = old.clone();
+        restored.id = current.id;
+        version_col = version_col.push(
+            column![
+                row![
+                    text(format_age(version.saved_time)),
+                    horizontal_space(),
+                    button(text("Restore"))
+                        .style(button::danger)
+                        .on_press(Message::ReplacePalette(restored)),
+                ]
+                .align_y(Vertical::Center),
+                text(format!(
+                    "{} colors changed, {} tiles ({:+}), {} tiles modified",
+                    cnt_changed,
+                    old.tiles.len(),
+                    tile_diff,
+                    cnt_tiles_changed
+                ))
+                .size(12),
+                row![text("Saved").width(60).size(12), old_row].align_y(Vertical::Center),
+                row![text("Current").width(60).size(12), new_row].align_y(Vertical::Center),
+            ]
+            .spacing(5),
+        );
+    }
+    if cnt_shown == 0 {
+        version_col = version_col.push(text("No previous versions of this palette are saved."));
+    }
+
+    container(
+        column![
+            text(format!(
+                "History of palette {}: \"{}\"",
+                current.id, current.name
+            )),
+            scrollable(version_col).height(400),
+            button(text("Close"))
+                .style(button::secondary)
+                .on_press(Message::CloseDialogue),
+        ]
+        .spacing(15),
+    )
+    .width(450)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
+
+pub fn flip_suggestions_view<'a>(
+    state: &'a EditorState,
+    suggestions: &'a [(FlipSuggestion, bool)],
+) -> Element<'a, Message> {
+    let mut suggestion_col: Column<Message> = Column::new().spacing(5);
+    let mut last_palette_id = None;
+    for (i, (suggestion, checked)) in suggestions.iter().enumerate() {
+        let palette_id = suggestion.palette_id();
+        if last_palette_id != Some(palette_id) {
+            last_palette_id = Some(palette_id);
+            let name = state
+                .palettes_id_idx_map
+                .get(&palette_id)
+                .map(|&idx| state.palettes[idx].name.as_str())
+                .unwrap_or_default();
+            suggestion_col = suggestion_col.push(text(format!("Palette {}: {}", palette_id, name)));
+        }
+        suggestion_col = suggestion_col.push(
+            checkbox(suggestion.description(), *checked)
+                .size(14)
+                .text_size(12)
+                .on_toggle(move |_| Message::ToggleFlipSuggestion(i)),
+        );
+    }
+    if suggestions.is_empty() {
+        suggestion_col = suggestion_col.push(text("No flippable tiles were found."));
+    }
+
+    let cnt_checked = suggestions.iter().filter(|x| x.1).count();
+    container(
+        column![
+            text("Flippable tiles"),
+            text(
+                "Symmetric tiles can be allowed to flip. Flipped copies of other tiles can be \
+                 replaced by the original in all areas (the copies are left unused)."
+            )
+            .size(12),
+            scrollable(suggestion_col).height(400),
+            row![
+                button(text("Close"))
+                    .style(button::secondary)
+                    .on_press(Message::CloseDialogue),
+                button(text("All"))
+                    .style(button::secondary)
+                    .on_press(Message::SetAllFlipSuggestions(true)),
+                button(text("None"))
+                    .style(button::secondary)
+                    .on_press(Message::SetAllFlipSuggestions(false)),
+                horizontal_space(),
+                button(text(format!("Apply {} changes", cnt_checked)))
+                    .style(button::success)
+                    .on_press_maybe((cnt_checked > 0).then_some(Message::ApplyFlipSuggestions)),
+            ]
+            .spacing(10),
+        ]
+        .spacing(15),
+    )
+    .width(550)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
+
+pub fn duplicate_tiles_view<'a>(
+    state: &'a EditorState,
+    palette_id: PaletteId,
+    duplicates: &'a [DuplicateTile],
+) -> Element<'a, Message> {
+    let name = state
+        .palettes_id_idx_map
+        .get(&palette_id)
+        .map(|&idx| state.palettes[idx].name.as_str())
+        .unwrap_or_default();
+    let mut duplicate_col: Column<Message> = Column::new().spacing(5);
+    for d in duplicates {
+        duplicate_col = duplicate_col.push(
+            text(if d.flip == Flip::None {
+                format!("Tile {} is a copy of tile {}", d.tile_idx, d.original)
+            } else {
+                format!(
+                    "Tile {} is tile {} flipped ({:?})",
+                    d.tile_idx, d.original, d.flip
+                )
+            })
+            .size(12),
+        );
+    }
+    if duplicates.is_empty() {
+        duplicate_col = duplicate_col.push(text("No duplicate tiles were found."));
+    }
+
+    container(
+        column![
+            text(format!(
+                "Duplicate tiles in palette {}: {}",
+                palette_id, name
+            )),
+            text(
+                "Duplicates are replaced by the original tile in all areas, and then cleared, \
+                 freeing them for new graphics."
+            )
+            .size(12),
+            scrollable(duplicate_col).height(300),
+            row![
+                button(text("Close"))
+                    .style(button::secondary)
+                    .on_press(Message::CloseDialogue),
+                horizontal_space(),
+                button(text(format!("Merge (frees {} tiles)", duplicates.len())))
+                    .style(button::success)
+                    .on_press_maybe(
+                        (!duplicates.is_empty()).then_some(Message::ApplyDuplicateTiles)
+                    ),
+            ]
+            .spacing(10),
+        ]
+        .spacing(15),
+    )
+    .width(550)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
+
+pub fn color_ramps_view(
+    state: &EditorState,
+    start: ColorIdx,
+    len: u8,
+    shade: Option<(usize, usize)>,
+) -> Element<'_, Message> {
+    let pal = &state.palettes[state.palette_idx];
+    let palette_id = pal.id;
+
+    let mut range_swatches = Row::new().spacing(2);
+    for &color in &pal.colors[start as usize..(start + len) as usize] {
+        range_swatches = range_swatches.push(color_swatch(color, false));
+    }
+    let mut col = column![
+        text(format!(
+            "Color ramps, applied to the current palette ({}: {}) starting at color {}.",
+            pal.id, pal.name, start
+        )),
+        row![
+            text("Start at color"),
+            number_input(&start, 0..=(16 - MIN_RAMP_LEN), move |start| {
+                Message::SetColorRampRange { start, len }
+            })
+            .width(70),
+            text("Colors"),
+            number_input(&len, MIN_RAMP_LEN..=(16 - start), move |len| {
+                Message::SetColorRampRange { start, len }
+            })
+            .width(70),
+            range_swatches,
+            horizontal_space(),
+            button(text("New ramp"))
+                .style(button::success)
+                .on_press(Message::AddColorRamp {
+                    palette_id,
+                    start,
+                    len
+                }),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+    ]
+    .spacing(15);
+
+    let mut ramps_col = Column::new().spacing(15);
+    for (i, ramp) in state.project_metadata.color_ramps.iter().enumerate() {
+        let mut shades = Row::new().spacing(2);
+        for (j, &color) in ramp.colors.iter().enumerate() {
+            let selected = shade == Some((i, j));
+            shades = shades.push(
+                button(color_swatch(color, selected))
+                    .padding(2)
+                    .style(button::text)
+                    .on_press(Message::SelectRampShade(if selected {
+                        None
+                    } else {
+                        Some((i, j))
+                    })),
+            );
+        }
+        let link_button = if ramp.is_subscribed(palette_id, start) {
+            button(text("Unlink current palette")).on_press(Message::UnsubscribeColorRamp {
+                ramp_idx: i,
+                palette_id,
+                start,
+            })
+        } else {
+            button(text("Apply to current palette")).on_press_maybe(ramp.fits_at(start).then_some(
+                Message::SubscribeColorRamp {
+                    ramp_idx: i,
+                    palette_id,
+                    start,
+                },
+            ))
+        };
+        let users: Vec<String> = ramp
+            .subscribers
+            .iter()
+            .map(|s| {
+                let name = state
+                    .palettes_id_idx_map
+                    .get(&s.palette_id)
+                    .map(|&idx| state.palettes[idx].name.as_str())
+                    .unwrap_or("(missing)");
+                format!("{}: {} (from color {})", s.palette_id, name, s.start)
+            })
+            .collect();
+        let mut ramp_col = column![
+            row![
+                text_input("", &ramp.name)
+                    .on_input(move |x| Message::SetColorRampName(i, x))
+                    .width(150),
+                shades,
+                horizontal_space(),
+                link_button,
+                button(text("\u{F5DE}").font(iced_fonts::BOOTSTRAP_FONT))
+                    .style(button::danger)
+                    .on_press(Message::DeleteColorRamp(i)),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            text(if users.is_empty() {
+                "Not used by any palette".to_string()
+            } else {
+                format!("Used by {}", users.join(", "))
+            })
+            .size(12),
+        ]
+        .spacing(5);
+        if let Some((ramp_idx, shade)) = shade.filter(|&(r, _)| r == i) {
+            let [r, g, b] = ramp.colors[shade];
+            let set = move |color| Message::SetRampColor {
+                ramp_idx,
+                shade,
+                color,
+            };
+            ramp_col = ramp_col.push(
+                row![
+                    text(format!("Shade {}:", shade + 1)),
+                    text("Red"),
+                    number_input(&r, 0..=31, move |r| set([r, g, b])).width(80),
+                    text("Green"),
+                    number_input(&g, 0..=31, move |g| set([r, g, b])).width(80),
+                    text("Blue"),
+                    number_input(&b, 0..=31, move |b| set([r, g, b])).width(80),
+                ]
+                .spacing(5)
+                .align_y(Vertical::Center),
+            );
+        }
+        ramps_col = ramps_col.push(ramp_col);
+    }
+
+    col = col
+        .push(scrollable(ramps_col).height(Length::Shrink))
+        .push(
+            text("Changing a shade of a ramp changes it in every palette that uses the ramp.")
+                .size(12),
+        )
+        .push(
+            button(text("Close"))
+                .style(button::secondary)
+                .on_press(Message::CloseDialogue),
+        );
+    container(col)
+        .width(800)
+        .max_height(700)
+        .padding(25)
+        .style(modal_background_style)
+        .into()
+}
+
+pub fn export_palette_view(
+    state: &EditorState,
+    palette_id: PaletteId,
+    labeled: bool,
+    tile_table: Option<TileTableFormat>,
+) -> Element<'_, Message> {
+    let name = state
+        .palettes_id_idx_map
+        .get(&palette_id)
+        .map(|&idx| state.palettes[idx].name.as_str())
+        .unwrap_or_default();
+    let format_button = |label, format: Option<TileTableFormat>| {
+        button(text(label))
+            .style(if tile_table == format {
+                button::primary
+            } else {
+                button::secondary
+            })
+            .on_press(Message::SetExportPaletteTileTable(format))
+    };
+    let options: Element<Message> = match tile_table {
+        None => column![
+            checkbox(
+                "Label colors with their index, RGB and SNES values",
+                labeled
+            )
+            .on_toggle(Message::SetExportPaletteLabeled),
+            text(if labeled {
+                "Reference sheet with the palette name and ID, for sharing with artists."
+            } else {
+                "Plain row of 16 swatches, as in the project's Palettes folder."
+            })
+            .size(12),
+        ]
+        .spacing(15)
+        .into(),
+        Some(_) => text(
+            "Table of each tile's index, collision type, priority, and flippable flags, \
+            for documentation and review.",
+        )
+        .size(12)
+        .into(),
+    };
+    container(
+        column![
+            text(format!("Export palette {}: {}", palette_id, name)),
+            row![
+                format_button("PNG colors", None),
+                format_button("CSV tiles", Some(TileTableFormat::Csv)),
+                format_button("Markdown tiles", Some(TileTableFormat::Markdown)),
+            ]
+            .spacing(10),
+            options,
+            row![
+                button(text("Export")).on_press(Message::ExportPalette),
+                button(text("Cancel"))
+                    .style(button::secondary)
+                    .on_press(Message::CloseDialogue),
+            ]
+            .spacing(10),
+        ]
+        .spacing(15),
+    )
+    .width(450)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
+
+pub fn tile_graphics_view(
+    state: &EditorState,
+    palette_id: PaletteId,
+    format: GraphicsFormat,
+) -> Element<'_, Message> {
+    let name = state
+        .palettes_id_idx_map
+        .get(&palette_id)
+        .map(|&idx| state.palettes[idx].name.as_str())
+        .unwrap_or_default();
+    let format_button = |f: GraphicsFormat| {
+        button(text(f.to_string()))
+            .style(if format == f {
+                button::primary
+            } else {
+                button::secondary
+            })
+            .on_press(Message::SetTileGraphicsFormat(f))
+    };
+    container(
+        column![
+            text(format!("Tile graphics of palette {}: {}", palette_id, name)),
+            row![
+                format_button(GraphicsFormat::Png),
+                format_button(GraphicsFormat::Bpp3),
+                format_button(GraphicsFormat::Bpp4),
+                format_button(GraphicsFormat::CompressedSheets),
+            ]
+            .spacing(10),
+            text(match format {
+                GraphicsFormat::Png => {
+                    "Indexed PNG with 16 tiles per row, using the palette's colors. \
+                    Imported pixels keep their color index, or take the closest palette color \
+                    if the PNG isn't indexed."
+                }
+                GraphicsFormat::Bpp3 => "SNES 3bpp tile data (24 bytes per tile, colors 0-7).",
+                GraphicsFormat::Bpp4 => "SNES 4bpp tile data (32 bytes per tile).",
+                GraphicsFormat::CompressedSheets => {
+                    "3bpp graphics sheets of 64 tiles, each compressed as in the ROM, \
+                    one after another."
+                }
+            })
+            .size(12),
+            text(
+                "Importing replaces the pixels of the palette's tiles in order, \
+                adding tiles if there are more."
+            )
+            .size(12),
+            row![
+                button(text("Export")).on_press(Message::ExportTileGraphics),
+                button(text("Import"))
+                    .style(button::success)
+                    .on_press(Message::ImportTileGraphics),
+                button(text("Cancel"))
+                    .style(button::secondary)
+                    .on_press(Message::CloseDialogue),
+            ]
+            .spacing(10),
+        ]
+        .spacing(15),
+    )
+    .width(450)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
+
+fn palette_label(state: &EditorState, palette_id: PaletteId) -> Option<String> {
+    let &idx = state.palettes_id_idx_map.get(&palette_id)?;
+    Some(format!("{}: {}", palette_id, state.palettes[idx].name))
+}
+
+fn palette_id_from_label(label: &str) -> Option<PaletteId> {
+    label.split(':').next()?.parse().ok()
+}
+
+pub fn adjust_palette_view<'a>(
+    state: &'a EditorState,
+    adjust: &'a PaletteAdjust,
+) -> Element<'a, Message> {
+    let pal = &state.palettes[state.palette_idx];
+    let palette_id = pal.id;
+    let labels: Vec<String> = state
+        .palettes
+        .iter()
+        .map(|x| format!("{}: {}", x.id, x.name))
+        .collect();
+    let palette_pick =
+        |selected: Option<PaletteId>, change: fn(Option<PaletteId>) -> PaletteAdjustChange| {
+            pick_list(
+                labels.clone(),
+                selected.and_then(|id| palette_label(state, id)),
+                move |label: String| {
+                    Message::SetPaletteAdjust(change(palette_id_from_label(&label)))
+                },
+            )
+            .width(200)
+        };
+
+    let hsv_msg = Message::AdjustPaletteHSV {
+        palette_id,
+        hue: adjust.hue,
+        saturation: adjust.saturation,
+        brightness: adjust.brightness,
+    };
+    let copy_msg = adjust
+        .copy_source
+        .map(|source_id| Message::CopyPaletteColors {
+            palette_id,
+            source_id,
+        });
+    let blend_msg =
+        adjust
+            .blend_from
+            .zip(adjust.blend_to)
+            .map(|(from, to)| Message::InterpolatePalettes {
+                palette_id,
+                from,
+                to,
+                amount: adjust.blend_amount,
+            });
+    container(
+        column![
+            text(format!(
+                "Adjust all colors of palette {}: {}",
+                pal.id, pal.name
+            )),
+            row![
+                text("Hue").width(100),
+                slider(-180..=180, adjust.hue, |x| Message::SetPaletteAdjust(
+                    PaletteAdjustChange::Hue(x)
+                ))
+                .width(Length::Fill),
+                number_input(&adjust.hue, -180..=180, |x| Message::SetPaletteAdjust(
+                    PaletteAdjustChange::Hue(x)
+                ))
+                .width(80),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text("Saturation %").width(100),
+                number_input(&adjust.saturation, -100..=100, |x| {
+                    Message::SetPaletteAdjust(PaletteAdjustChange::Saturation(x))
+                })
+                .width(80),
+                Space::with_width(10),
+                text("Brightness %").width(100),
+                number_input(&adjust.brightness, -100..=100, |x| {
+                    Message::SetPaletteAdjust(PaletteAdjustChange::Brightness(x))
+                })
+                .width(80),
+                horizontal_space(),
+                button(text("Apply")).on_press(hsv_msg),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text("Copy colors from").width(100),
+                palette_pick(adjust.copy_source, PaletteAdjustChange::CopySource),
+                horizontal_space(),
+                button(text("Copy")).on_press_maybe(copy_msg),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text("Blend from").width(100),
+                palette_pick(adjust.blend_from, PaletteAdjustChange::BlendFrom),
+                text("to"),
+                palette_pick(adjust.blend_to, PaletteAdjustChange::BlendTo),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text("Amount %").width(100),
+                slider(0..=100, adjust.blend_amount, |x| Message::SetPaletteAdjust(
+                    PaletteAdjustChange::BlendAmount(x)
+                ))
+                .width(Length::Fill),
+                number_input(
+                    &adjust.blend_amount,
+                    0..=100,
+                    |x| Message::SetPaletteAdjust(PaletteAdjustChange::BlendAmount(x))
+                )
+                .width(80),
+                button(text("Blend")).on_press_maybe(blend_msg),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            text("Each operation replaces all 16 colors of the palette.").size(12),
+            button(text("Close"))
+                .style(button::secondary)
+                .on_press(Message::CloseDialogue),
+        ]
+        .spacing(15),
+    )
+    .width(600)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
+
+pub fn palette_groups_view<'a>(
+    state: &'a EditorState,
+    new_group: &'a str,
+    renames: &'a BTreeMap<String, String>,
+) -> Element<'a, Message> {
+    let groups = palette_group_names(state);
+    let mut list_col = column![].spacing(5);
+    for (i, group) in groups.iter().enumerate() {
+        let ungrouped = group == UNGROUPED_PALETTE_GROUP;
+        let name = renames.get(group).unwrap_or(group);
+        let rename_msg = (!ungrouped && name != group).then(|| {
+            let members: Vec<_> = state
+                .palettes
+                .iter()
+                .filter(|p| palette_group(p) == group)
+                .collect();
+            Message::SetPaletteGroups(moved_to_group(&members, normalized_group(name)))
+        });
+        let mut name_input = text_input("", name);
+        if !ungrouped {
+            let group = group.clone();
+            name_input = name_input.on_input(move |name| Message::EditPaletteGroupName {
+                group: group.clone(),
+                name,
+            });
+        }
+        list_col = list_col.push(
+            row![
+                name_input.on_submit_maybe(rename_msg.clone()),
+                text(format!("{}", group_size(state, group))).width(40),
+                button(text("\u{F148}").font(iced_fonts::BOOTSTRAP_FONT))
+                    .style(button::secondary)
+                    .on_press_maybe((!ungrouped && i > 0).then(|| Message::MovePaletteGroup {
+                        group: group.clone(),
+                        up: true,
+                    })),
+                button(text("\u{F128}").font(iced_fonts::BOOTSTRAP_FONT))
+                    .style(button::secondary)
+                    .on_press_maybe(
+                        (!ungrouped
+                            && groups
+                                .get(i + 1)
+                                .is_some_and(|g| g != UNGROUPED_PALETTE_GROUP))
+                        .then(|| Message::MovePaletteGroup {
+                            group: group.clone(),
+                            up: false,
+                        }),
+                    ),
+                button(text("Rename")).on_press_maybe(rename_msg),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+        );
+    }
+
+    let target = normalized_group(new_group);
+    let current = &state.palettes[state.palette_idx];
+    let shown = shown_palettes(state);
+    let move_current = Message::SetPaletteGroups(moved_to_group(&[current], target.clone()));
+    let move_shown = Message::SetPaletteGroups(moved_to_group(&shown, target));
+    container(
+        column![
+            text("Palette groups, in the order they're listed:"),
+            scrollable(list_col).height(300),
+            row![
+                text("Move to group:"),
+                text_input(UNGROUPED_PALETTE_GROUP, new_group)
+                    .on_input(Message::SetNewPaletteGroupName)
+                    .on_submit(move_current.clone()),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                button(text(format!("Move {}", current.name))).on_press(move_current),
+                button(text(format!("Move the {} palettes shown", shown.len())))
+                    .style(button::success)
+                    .on_press(move_shown),
+            ]
+            .spacing(10),
+        ]
+        .spacing(10),
+    )
+    .width(500)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
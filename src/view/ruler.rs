@@ -0,0 +1,214 @@
+// Rulers along the edges of an area view, showing tile and screen coordinates. They follow the
+// scroll offset of the area view, and clicking on one places (or removes) a guide line.
+use iced::{
+    alignment::{Horizontal, Vertical},
+    mouse,
+    widget::canvas,
+    Color, Element, Length, Pixels, Point, Rectangle, Size,
+};
+
+use crate::{
+    message::Message,
+    state::{AreaName, Guide, TileCoord},
+};
+
+pub const RULER_SIZE: f32 = 24.0;
+pub const GUIDE_COLOR: Color = Color::from_rgb(0.0, 0.9, 0.9);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RulerAxis {
+    Horizontal,
+    Vertical,
+}
+
+struct Ruler {
+    axis: RulerAxis,
+    area: AreaName,
+    // Length of the area along the ruler, in tiles:
+    num_tiles: TileCoord,
+    pixel_size: f32,
+    // Scroll offset of the area view along the ruler:
+    offset: f32,
+    guides: Vec<TileCoord>,
+}
+
+impl Ruler {
+    // Position along the ruler of the start of a tile (accounting for the pixel of padding
+    // around the area image).
+    fn tile_position(&self, t: TileCoord) -> f32 {
+        (t as f32 * 8.0 + 1.0) * self.pixel_size - self.offset
+    }
+
+    fn tile_at(&self, pos: f32) -> TileCoord {
+        let t = ((pos + self.offset) / self.pixel_size - 1.0).max(0.0) / 8.0;
+        (t as TileCoord).min(self.num_tiles.saturating_sub(1))
+    }
+
+    fn guide(&self, t: TileCoord) -> Guide {
+        match self.axis {
+            RulerAxis::Horizontal => Guide::Vertical(t),
+            RulerAxis::Vertical => Guide::Horizontal(t),
+        }
+    }
+}
+
+impl canvas::Program<Message> for Ruler {
+    type State = ();
+
+    fn update(
+        &self,
+        _state: &mut (),
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (canvas::event::Status, Option<Message>) {
+        let canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) = event else {
+            return (canvas::event::Status::Ignored, None);
+        };
+        let Some(p) = cursor.position_in(bounds) else {
+            return (canvas::event::Status::Ignored, None);
+        };
+        let pos = match self.axis {
+            RulerAxis::Horizontal => p.x,
+            RulerAxis::Vertical => p.y,
+        };
+        let message = Message::ToggleGuide {
+            area: self.area.clone(),
+            guide: self.guide(self.tile_at(pos)),
+        };
+        (canvas::event::Status::Captured, Some(message))
+    }
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &iced::Renderer,
+        theme: &iced::Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let palette = theme.extended_palette();
+        frame.fill_rectangle(Point::ORIGIN, bounds.size(), palette.background.weak.color);
+
+        let (length, depth) = match self.axis {
+            RulerAxis::Horizontal => (bounds.width, bounds.height),
+            RulerAxis::Vertical => (bounds.height, bounds.width),
+        };
+        // Points at a given position along the ruler, and distance from its inner edge:
+        let at = |pos: f32, d: f32| match self.axis {
+            RulerAxis::Horizontal => Point::new(pos, depth - d),
+            RulerAxis::Vertical => Point::new(depth - d, pos),
+        };
+        // Label every 8 tiles if there is room, or otherwise only at screen boundaries:
+        let label_step = if 64.0 * self.pixel_size >= 32.0 {
+            8
+        } else {
+            32
+        };
+        let first = self.tile_at(0.0);
+        let last = self.tile_at(length);
+        let ticks = canvas::Path::new(|p| {
+            for t in first..=last {
+                let tick = if t % 32 == 0 {
+                    depth
+                } else if t % 8 == 0 {
+                    depth / 2.0
+                } else if t % 2 == 0 && self.pixel_size >= 2.0 {
+                    depth / 5.0
+                } else {
+                    continue;
+                };
+                let pos = self.tile_position(t).round() + 0.5;
+                p.move_to(at(pos, 0.0));
+                p.line_to(at(pos, tick));
+            }
+        });
+        frame.stroke(
+            &ticks,
+            canvas::Stroke {
+                style: canvas::stroke::Style::Solid(palette.background.base.text),
+                width: 1.0,
+                ..Default::default()
+            },
+        );
+        for t in (first - first % label_step..=last).step_by(label_step as usize) {
+            let content = if t % 32 == 0 {
+                format!("S{}", t / 32)
+            } else {
+                t.to_string()
+            };
+            let pos = self.tile_position(t) + 3.0;
+            if pos < 0.0 || pos > length - 12.0 {
+                continue;
+            }
+            frame.fill_text(canvas::Text {
+                content,
+                position: at(pos, depth - 2.0),
+                color: if t % 32 == 0 {
+                    palette.primary.base.color
+                } else {
+                    palette.background.base.text
+                },
+                size: Pixels(10.0),
+                horizontal_alignment: Horizontal::Left,
+                vertical_alignment: Vertical::Top,
+                ..canvas::Text::default()
+            });
+        }
+        for &g in &self.guides {
+            let pos = self.tile_position(g);
+            let size = 8.0 * self.pixel_size;
+            let (top_left, size) = match self.axis {
+                RulerAxis::Horizontal => (Point::new(pos, depth - 4.0), Size::new(size, 4.0)),
+                RulerAxis::Vertical => (Point::new(depth - 4.0, pos), Size::new(4.0, size)),
+            };
+            frame.fill_rectangle(top_left, size, GUIDE_COLOR);
+        }
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        _state: &(),
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        if cursor.is_over(bounds) {
+            mouse::Interaction::Pointer
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+pub fn ruler_view<'a>(
+    axis: RulerAxis,
+    area: &AreaName,
+    num_tiles: TileCoord,
+    pixel_size: f32,
+    offset: f32,
+    guides: &[Guide],
+) -> Element<'a, Message> {
+    let guides = guides
+        .iter()
+        .filter_map(|&g| match (axis, g) {
+            (RulerAxis::Horizontal, Guide::Vertical(t)) => Some(t),
+            (RulerAxis::Vertical, Guide::Horizontal(t)) => Some(t),
+            _ => None,
+        })
+        .collect();
+    let ruler = canvas(Ruler {
+        axis,
+        area: area.clone(),
+        num_tiles,
+        pixel_size,
+        offset,
+        guides,
+    });
+    match axis {
+        RulerAxis::Horizontal => ruler.width(Length::Fill).height(RULER_SIZE),
+        RulerAxis::Vertical => ruler.width(RULER_SIZE).height(Length::Fill),
+    }
+    .into()
+}
@@ -0,0 +1,51 @@
+// Module for the scripting console: editing a script, running it, and showing its output
+use iced::{
+    alignment::Vertical,
+    widget::{button, column, container, horizontal_space, row, scrollable, text, text_editor},
+    Element, Font, Length,
+};
+
+use crate::{message::Message, state::EditorState};
+
+use super::modal_background_style;
+
+pub fn script_view(state: &EditorState) -> Element<'_, Message> {
+    let mut output = column![];
+    for line in &state.script_output {
+        output = output.push(text(line).font(Font::MONOSPACE).size(12));
+    }
+    container(
+        column![
+            text("Script console"),
+            text(
+                "Scripts in Rhai can read and change areas and palettes (see the example). \
+                 A script's changes are applied when it finishes, and are undone together."
+            )
+            .size(12),
+            text_editor(&state.script)
+                .on_action(Message::EditScript)
+                .font(Font::MONOSPACE)
+                .size(13)
+                .height(300),
+            row![
+                text("Output"),
+                horizontal_space(),
+                button(text("Close"))
+                    .style(button::secondary)
+                    .on_press(Message::CloseDialogue),
+                button(text("Run")).on_press(Message::RunScript),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            scrollable(output)
+                .anchor_bottom()
+                .width(Length::Fill)
+                .height(150),
+        ]
+        .spacing(10),
+    )
+    .width(700)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
@@ -0,0 +1,97 @@
+// Module for editing the main area's secrets (items hidden under liftable objects) in the side
+// panel
+use iced::{
+    alignment::Vertical,
+    widget::{button, column, horizontal_space, pick_list, row, scrollable, text, Column},
+    Element,
+};
+use iced_fonts::BOOTSTRAP_FONT;
+
+use crate::{
+    message::{Message, SelectionSource},
+    secrets::{item_id, item_name, snap_position, Secret, SECRET_ITEMS},
+    state::{AreaPosition, EditorState, TileCoord},
+};
+
+// Where to add a secret: at the top-left of the selection in the main area, if any.
+fn new_secret_position(state: &EditorState) -> (TileCoord, TileCoord) {
+    match (state.selection_source, state.start_coords, state.end_coords) {
+        (SelectionSource::Area(AreaPosition::Main), Some(p0), Some(p1)) => {
+            snap_position(p0.0.min(p1.0), p0.1.min(p1.1))
+        }
+        _ => (0, 0),
+    }
+}
+
+fn secret_row(state: &EditorState, idx: usize, secret: Secret) -> Element<'_, Message> {
+    let area_id = state.main_area_id.clone();
+    let names: Vec<String> = SECRET_ITEMS.iter().map(|(_, n)| n.to_string()).collect();
+    let remove_area_id = area_id.clone();
+    row![
+        text(format!("{}, {}", secret.position.0, secret.position.1)).width(70),
+        pick_list(names, Some(item_name(secret.item)), move |name| {
+            Message::SetSecret {
+                position: AreaPosition::Main,
+                area_id: area_id.clone(),
+                idx,
+                secret: Secret {
+                    item: item_id(&name).unwrap_or(secret.item),
+                    ..secret
+                },
+            }
+        })
+        .width(180),
+        horizontal_space(),
+        button(text("\u{F5DE}").font(BOOTSTRAP_FONT).size(12))
+            .padding(2)
+            .style(button::danger)
+            .on_press(Message::RemoveSecret {
+                position: AreaPosition::Main,
+                area_id: remove_area_id,
+                idx,
+            }),
+    ]
+    .spacing(10)
+    .align_y(Vertical::Center)
+    .into()
+}
+
+pub fn secrets_view(state: &EditorState) -> Element<'_, Message> {
+    let area = state.main_area();
+    let header = row![
+        text(format!("Hidden items in {}", area.name)),
+        horizontal_space(),
+        button(text("\u{F62A}").font(BOOTSTRAP_FONT))
+            .style(button::secondary)
+            .on_press(Message::ShowSecrets(false)),
+    ]
+    .spacing(10)
+    .align_y(Vertical::Center);
+    let add = button(text("Add at selection")).on_press(Message::InsertSecret {
+        position: AreaPosition::Main,
+        area_id: state.main_area_id.clone(),
+        idx: area.secrets.len(),
+        secret: Secret {
+            position: new_secret_position(state),
+            item: SECRET_ITEMS[0].0,
+        },
+    });
+
+    let mut col = column![
+        header,
+        add,
+        text("Drag the markers in the area to move them.").size(12),
+    ]
+    .spacing(10)
+    .padding(10)
+    .width(420);
+    if area.secrets.is_empty() {
+        return col.push(text("No hidden items.")).into();
+    }
+    let mut list = Column::new().spacing(5);
+    for (idx, &secret) in area.secrets.iter().enumerate() {
+        list = list.push(secret_row(state, idx, secret));
+    }
+    col = col.push(scrollable(list));
+    col.into()
+}
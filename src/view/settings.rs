@@ -1,18 +1,61 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, UNIX_EPOCH},
+};
+
 use iced::{
     alignment::Vertical,
-    widget::{button, column, container, horizontal_space, row, slider, text, text_input},
+    widget::{
+        button, checkbox, column, container, horizontal_space, image, image::FilterMethod,
+        pick_list, progress_bar, row, scrollable, slider, text, text_input, Column, Row,
+    },
     Element, Length,
 };
 use iced_aw::number_input;
 use iced_fonts::BOOTSTRAP_FONT;
 
 use crate::{
+    export::ExportReport,
+    helpers::{format_age, format_bytes},
+    import::{
+        parse_map_ids, BorrowGraphics, ImportMode, ImportReport, ImportStage, GRAPHICS_SHEET_COLS,
+    },
+    memory_report::memory_report,
     message::Message,
-    state::{EditorState, MAX_PIXEL_SIZE, MIN_PIXEL_SIZE},
+    palette_slots::{PaletteAssignment, RowPosition},
+    project_stats::StatsFormat,
+    renames::RenameRecord,
+    state::{AreaName, EditorState, PaletteId, ThemeName, MAX_PIXEL_SIZE, MIN_PIXEL_SIZE},
+    test_rom::TestRunStage,
 };
 
 use super::modal_background_style;
 
+fn watcher_status_text(state: &EditorState) -> String {
+    if state.watcher.is_none() {
+        return "Not running".to_string();
+    }
+    let status = &state.watcher_status;
+    let mut s = match status.last_check {
+        Some((time, _)) if status.missed_probes > 0 => {
+            format!("No events seen at last check ({})", format_age(time))
+        }
+        Some((time, _)) => format!("Active (last checked {})", format_age(time)),
+        None => "Active".to_string(),
+    };
+    if let Some((time, reason)) = &status.last_restart {
+        s += &format!("; restarted {} after {}", format_age(*time), reason);
+    }
+    s
+}
+
+fn path_text(path: &Option<PathBuf>) -> String {
+    match path {
+        Some(path) => path.display().to_string(),
+        None => "Not set up".to_string(),
+    }
+}
+
 pub fn settings_view(state: &EditorState) -> Element<Message> {
     let project_dir = state.global_config.project_dir.as_ref().unwrap();
     let zoom_range = MIN_PIXEL_SIZE..=MAX_PIXEL_SIZE;
@@ -44,6 +87,33 @@ pub fn settings_view(state: &EditorState) -> Element<Message> {
             ]
             .spacing(10)
             .align_y(Vertical::Center),
+            row![
+                horizontal_space().width(100),
+                checkbox(
+                    "Remember zoom separately for each display",
+                    state.global_config.per_display_zoom
+                )
+                .on_toggle(Message::SetPerDisplayZoom),
+            ]
+            .spacing(10),
+            row![
+                text("Saving").width(100),
+                checkbox(
+                    "Save only when asked (otherwise changes are saved every second)",
+                    state.global_config.manual_save
+                )
+                .on_toggle(Message::SetManualSave),
+            ]
+            .spacing(10),
+            row![
+                text("Layout").width(100),
+                checkbox(
+                    "Show the side area and the tileset side by side",
+                    state.global_config.pane_layout.is_some()
+                )
+                .on_toggle(Message::SetThreePaneLayout),
+            ]
+            .spacing(10),
             row![
                 text("Grid alpha").width(100),
                 slider(
@@ -62,34 +132,276 @@ pub fn settings_view(state: &EditorState) -> Element<Message> {
             ]
             .spacing(10)
             .align_y(Vertical::Center),
+            row![
+                text("Overlay opacity").width(100),
+                slider(
+                    0.0..=1.0,
+                    state.global_config.overlay_opacity,
+                    Message::SetOverlayOpacity
+                )
+                .step(0.01)
+                .width(Length::Fill),
+                number_input(
+                    &((state.global_config.overlay_opacity * 100.0).round() as u8),
+                    0..=100,
+                    |x| { Message::SetOverlayOpacity(x as f32 / 100.0) }
+                )
+                .width(60),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text("File watcher").width(100),
+                text(watcher_status_text(state)).width(Length::Fill),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text("Heatmap").width(100),
+                text(match &state.heatmap {
+                    Some(h) => h.path.display().to_string(),
+                    None => "None".to_string(),
+                })
+                .width(Length::Fill),
+                button(text("\u{F3D7}").font(BOOTSTRAP_FONT))
+                    .style(button::secondary)
+                    .on_press(Message::OpenHeatmap),
+                button(text("\u{F5DE}").font(BOOTSTRAP_FONT))
+                    .style(button::secondary)
+                    .on_press_maybe(state.heatmap.as_ref().map(|_| Message::ClearHeatmap)),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text("Area labels").width(100),
+                text(match &state.project_metadata.label_font {
+                    Some(_) => "Previewing with game font",
+                    None => "Not set up",
+                })
+                .width(Length::Fill),
+                button(text("\u{F4CB}").font(BOOTSTRAP_FONT))
+                    .style(button::secondary)
+                    .on_press(Message::AreaLabelsDialogue),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text("Library").width(100),
+                text(match &state.global_config.library_dir {
+                    Some(dir) => dir.display().to_string(),
+                    None => "Not set up".to_string(),
+                })
+                .width(Length::Fill),
+                button(text("\u{F3D7}").font(BOOTSTRAP_FONT))
+                    .style(button::secondary)
+                    .on_press(Message::PickLibraryDir),
+                button(text("\u{F1A5}").font(BOOTSTRAP_FONT))
+                    .style(button::secondary)
+                    .on_press(Message::LibraryDialogue),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text("Emulator").width(100),
+                text(path_text(&state.global_config.emulator_path)).width(Length::Fill),
+                button(text("\u{F3D7}").font(BOOTSTRAP_FONT))
+                    .style(button::secondary)
+                    .on_press(Message::PickEmulator),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text("Test base ROM").width(100),
+                text(path_text(&state.global_config.test_base_rom)).width(Length::Fill),
+                button(text("\u{F3D7}").font(BOOTSTRAP_FONT))
+                    .style(button::secondary)
+                    .on_press(Message::PickTestBaseRom),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text("Renames").width(100),
+                checkbox(
+                    "Record in Renames.json",
+                    state.project_metadata.record_renames
+                )
+                .on_toggle(Message::SetRecordRenames),
+                horizontal_space(),
+                button(text("\u{F292}").font(BOOTSTRAP_FONT))
+                    .style(button::secondary)
+                    .on_press(Message::RenameHistoryDialogue),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text("Memory").width(100),
+                text(format!(
+                    "{} areas loaded, {} undo steps",
+                    state.areas.len(),
+                    state.undo_stack.len()
+                ))
+                .width(Length::Fill),
+                button("Maintenance")
+                    .style(button::secondary)
+                    .on_press(Message::MemoryDialogue),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text("Statistics").width(100),
+                text("Tiles, palettes, areas, and collision types, saved to the project")
+                    .width(Length::Fill),
+                button("Export CSV")
+                    .style(button::secondary)
+                    .on_press(Message::ExportStatistics(StatsFormat::Csv)),
+                button("Export JSON")
+                    .style(button::secondary)
+                    .on_press(Message::ExportStatistics(StatsFormat::Json)),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text("Bug reports").width(100),
+                checkbox("Record session", state.global_config.record_session)
+                    .on_toggle(Message::SetRecordSession),
+                horizontal_space(),
+                button("Save bug report")
+                    .style(button::secondary)
+                    .on_press(Message::SaveBugReport),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
             row![
                 button("Close")
                     .style(button::secondary)
                     .on_press(Message::CloseDialogue),
                 horizontal_space(),
+                button("Export to ROM")
+                    .style(button::secondary)
+                    .on_press(Message::ExportDialogue),
+                button("Borrow graphics from ROM")
+                    .style(button::secondary)
+                    .on_press(Message::BorrowGraphicsDialogue),
+                button("Re-import from ROM")
+                    .style(button::secondary)
+                    .on_press(Message::ImportDialogue(ImportMode::MapsOnly)),
                 button("Import from ROM")
                     .style(button::danger)
-                    .on_press(Message::ImportDialogue)
+                    .on_press(Message::ImportDialogue(ImportMode::Full))
             ]
+            .spacing(10)
         ]
         .spacing(20),
     )
-    .width(600)
+    .width(700)
     .padding(25)
     .style(modal_background_style)
     .into()
 }
 
-pub fn import_rom_confirm_view(_state: &EditorState) -> Element<Message> {
-    container(
-        column![
+pub fn import_rom_confirm_view(state: &EditorState) -> Element<'_, Message> {
+    let description = match state.import_mode {
+        ImportMode::Full => column![
             text("Import project from ROM?"),
             text("This may update existing palettes, tilesets, and areas."),
-            text("This action cannot be undone."),
+        ],
+        ImportMode::MapsOnly => column![
+            text("Re-import map data from ROM?"),
+            text(
+                "Areas that have not been edited since the last import will be \
+                updated to match the ROM. Edited areas will be kept as they are."
+            ),
+        ],
+        ImportMode::PalettesOnly => column![
+            text("Re-import palette colors from ROM?"),
+            text(
+                "The colors of the palettes imported from the ROM's palette rows will be \
+                updated to match the ROM. Tiles and areas will be kept as they are."
+            ),
+        ],
+        ImportMode::GraphicsOnly => column![
+            text("Re-import tile graphics from ROM?"),
+            text(
+                "Tiles used by the ROM's maps that are missing from the palettes will be \
+                added to them. Areas will be kept as they are."
+            ),
+        ],
+    };
+    let source = match &state.rom_path {
+        Some(path) if path.is_dir() => format!("ROM folder: {}", path.display()),
+        Some(path) => format!("ROM: {}", path.display()),
+        None => "No ROM selected".to_string(),
+    };
+    let mut options = column![
+        row![
+            text(source),
+            horizontal_space(),
+            button(text("ROM file"))
+                .style(button::secondary)
+                .on_press(Message::ImportDialogue(state.import_mode)),
+            button(text("ROM folder"))
+                .style(button::secondary)
+                .on_press(Message::ImportRomFolderDialogue),
+        ]
+        .spacing(5)
+        .align_y(Vertical::Center),
+        row![
+            text("What to import").width(120),
+            pick_list(
+                ImportMode::ALL,
+                Some(state.import_mode),
+                Message::SetImportMode
+            ),
+        ]
+        .align_y(Vertical::Center)
+    ]
+    .spacing(5);
+    let map_ids = parse_map_ids(&state.import_maps);
+    if state.import_mode != ImportMode::PalettesOnly {
+        options = options.push(
+            row![
+                text("Maps").width(120),
+                text_input("All (or e.g. 00-07, 1B)", &state.import_maps)
+                    .on_input(Message::SetImportMaps),
+            ]
+            .align_y(Vertical::Center),
+        );
+        if let Err(e) = &map_ids {
+            options = options.push(text(format!("{:#}", e)).style(text::danger));
+        }
+    }
+    let ready = map_ids.is_ok() || state.import_mode == ImportMode::PalettesOnly;
+    let rules = match &state.project_metadata.import_rules {
+        Some(path) => row![
+            text(format!("Import rules: {}", path.display())),
+            horizontal_space(),
+            button(text("Change"))
+                .style(button::secondary)
+                .on_press(Message::SelectImportRules),
+            button(text("Clear"))
+                .style(button::secondary)
+                .on_press(Message::ClearImportRules),
+        ],
+        None => row![
+            text("No import rules"),
+            horizontal_space(),
+            button(text("Choose rules file"))
+                .style(button::secondary)
+                .on_press(Message::SelectImportRules),
+        ],
+    };
+    container(
+        column![
+            description.spacing(15),
+            options,
+            rules.spacing(5).align_y(Vertical::Center),
             row![
                 button(text("Import from ROM"))
                     .style(button::danger)
-                    .on_press(Message::ImportROMProgress),
+                    .on_press_maybe(ready.then_some(Message::ImportROMProgress)),
+                button(text("Preview"))
+                    .style(button::secondary)
+                    .on_press_maybe(ready.then_some(Message::PreviewImport)),
                 horizontal_space(),
                 button(text("Cancel"))
                     .style(button::secondary)
@@ -104,10 +416,492 @@ pub fn import_rom_confirm_view(_state: &EditorState) -> Element<Message> {
     .into()
 }
 
-pub fn import_rom_progress_view(_state: &EditorState) -> Element<Message> {
-    container(text("Please wait while ROM is importing."))
+pub fn import_rom_progress_view(state: &EditorState) -> Element<'_, Message> {
+    let (stage, fraction) = state
+        .rom_import
+        .as_ref()
+        .map(|import| import.progress(state))
+        .unwrap_or((ImportStage::ReadingRom, 0.0));
+    container(
+        column![
+            text(
+                if state
+                    .rom_import
+                    .as_ref()
+                    .is_some_and(|import| import.is_preview())
+                {
+                    "Previewing import..."
+                } else {
+                    "Importing ROM..."
+                }
+            ),
+            progress_bar(0.0..=1.0, fraction).height(10),
+            row![
+                text(format!("{} ({:.0}%)", stage, fraction * 100.0)),
+                horizontal_space(),
+                button(text("Cancel"))
+                    .style(button::secondary)
+                    .on_press(Message::CancelImportROM),
+            ]
+            .align_y(Vertical::Center),
+        ]
+        .spacing(15),
+    )
+    .width(500)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
+
+pub fn test_run_progress_view<'a>(stage: TestRunStage) -> Element<'a, Message> {
+    let status = match stage {
+        TestRunStage::Building => "Building test ROM ...",
+        TestRunStage::Launching => "Starting emulator ...",
+    };
+    container(text(status))
+        .width(350)
+        .padding(25)
+        .style(modal_background_style)
+        .into()
+}
+
+// What happened to the areas and palettes in an import (or would happen, for a preview).
+fn import_report_column(report: &ImportReport) -> Column<'_, Message> {
+    let mut col = Column::new().spacing(5);
+    for (label, areas) in [
+        ("Added", &report.added),
+        ("Updated", &report.updated),
+        ("Unchanged", &report.unchanged),
+        ("Kept (edited in project)", &report.kept),
+        ("Dropped (by import rules)", &report.dropped),
+    ] {
+        col = col.push(text(format!("{}: {}", label, areas.len())));
+    }
+    for name in &report.updated {
+        col = col.push(text(format!("  {}", name)).size(12));
+    }
+    col = col.push(text(format!(
+        "Conflicts (edited in both project and ROM): {}",
+        report.conflicts.len()
+    )));
+    for name in &report.conflicts {
+        col = col.push(text(format!("  {}", name)).size(12));
+    }
+    if !report.rom_differences.is_empty() {
+        col = col.push(
+            text("Warning: the ROM layout differs from the previous import:").style(text::danger),
+        );
+        for d in &report.rom_differences {
+            col = col.push(text(format!("  {}", d)).size(12));
+        }
+    }
+    if !report.palettes_updated.is_empty() {
+        col = col.push(text(format!(
+            "Palettes updated: {}",
+            report.palettes_updated.len()
+        )));
+        for name in &report.palettes_updated {
+            col = col.push(text(format!("  {}", name)).size(12));
+        }
+    }
+    if !report.tiles_added.is_empty() {
+        let total: usize = report.tiles_added.iter().map(|(_, n)| n).sum();
+        col = col.push(text(format!("Tiles added: {}", total)));
+        for (name, n) in &report.tiles_added {
+            col = col.push(text(format!("  {}: {}", name, n)).size(12));
+        }
+    }
+    col
+}
+
+pub fn import_report_view(report: &ImportReport) -> Element<'_, Message> {
+    let col = import_report_column(report);
+    container(
+        column![
+            text("Import complete."),
+            scrollable(col).height(Length::Shrink),
+            button(text("Close"))
+                .style(button::secondary)
+                .on_press(Message::CloseDialogue),
+        ]
+        .spacing(15),
+    )
+    .width(450)
+    .max_height(600)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
+
+pub fn import_preview_view<'a>(
+    state: &'a EditorState,
+    report: &'a ImportReport,
+) -> Element<'a, Message> {
+    container(
+        column![
+            text(format!(
+                "Importing ({}) would make these changes:",
+                state.import_mode.to_string().to_lowercase()
+            )),
+            text("Updated areas and palettes are overwritten with the ROM's versions.").size(12),
+            scrollable(import_report_column(report)).height(Length::Shrink),
+            row![
+                button(text("Import from ROM"))
+                    .style(button::danger)
+                    .on_press(Message::ImportROMProgress),
+                horizontal_space(),
+                button(text("Back"))
+                    .style(button::secondary)
+                    .on_press(Message::ImportConfirm(state.rom_path.clone())),
+            ]
+        ]
+        .spacing(15),
+    )
+    .width(450)
+    .max_height(600)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
+
+pub fn export_rom_progress_view(_state: &EditorState) -> Element<'_, Message> {
+    container(text("Please wait while ROM is exporting."))
         .width(350)
         .padding(25)
         .style(modal_background_style)
         .into()
 }
+
+pub fn export_report_view(report: &ExportReport) -> Element<'_, Message> {
+    let mut col = Column::new().spacing(5);
+    col = col.push(text(format!("Areas exported: {}", report.exported.len())));
+    col = col.push(text(format!(
+        "Palette rows updated: {}",
+        report.palettes_written
+    )));
+    col = col.push(text(format!(
+        "New 16x16 tiles: {}, new 32x32 tiles: {}",
+        report.tiles16_added, report.tiles32_added
+    )));
+    col = col.push(text(format!(
+        "Map data: {} of {} bytes",
+        report.map_data_size, report.map_data_space
+    )));
+    if !report.skipped.is_empty() {
+        col = col.push(
+            text(format!(
+                "Skipped (left as in the base ROM): {}",
+                report.skipped.len()
+            ))
+            .style(text::danger),
+        );
+        for (name, reason) in &report.skipped {
+            col = col.push(text(format!("  {}: {}", name, reason)).size(12));
+        }
+    }
+    if !report.rom_differences.is_empty() {
+        col = col.push(
+            text("Warning: the ROM layout differs from the project's base ROM:")
+                .style(text::danger),
+        );
+        for d in &report.rom_differences {
+            col = col.push(text(format!("  {}", d)).size(12));
+        }
+    }
+    container(
+        column![
+            text(format!("Exported to {}", report.path.display())),
+            scrollable(col).height(Length::Shrink),
+            button(text("Close"))
+                .style(button::secondary)
+                .on_press(Message::CloseDialogue),
+        ]
+        .spacing(15),
+    )
+    .width(450)
+    .max_height(600)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
+
+pub fn palette_slots_view<'a>(
+    state: &'a EditorState,
+    theme: &'a ThemeName,
+    area: &'a AreaName,
+    area_palettes: &'a [PaletteId],
+    assignment: &'a PaletteAssignment,
+    problems: &'a [String],
+) -> Element<'a, Message> {
+    let positions = assignment.get(area);
+    let mut list = Column::new().spacing(5);
+    for &palette_id in area_palettes {
+        let Some(&idx) = state.palettes_id_idx_map.get(&palette_id) else {
+            continue;
+        };
+        let palette = &state.palettes[idx];
+        let position = positions.and_then(|positions| {
+            positions
+                .iter()
+                .find(|(_, &id)| id == palette_id)
+                .map(|(&p, _)| p)
+        });
+        let set_position = move |position| Message::SetPaletteSlot {
+            theme: theme.clone(),
+            area: area.clone(),
+            palette_id,
+            position,
+        };
+        list = list.push(
+            row![
+                text(format!("{}: {}", palette_id, palette.name)).width(200),
+                pick_list(
+                    RowPosition::for_category(palette.category),
+                    position,
+                    move |p| set_position(Some(p))
+                )
+                .placeholder("As in base ROM")
+                .width(150),
+                button(text("Clear"))
+                    .style(button::secondary)
+                    .on_press_maybe(position.map(|_| set_position(None))),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+        );
+    }
+
+    let mut col = column![
+        text(format!("Palette positions ({})", theme)),
+        row![
+            text("Area").width(100),
+            pick_list(
+                state.area_names.clone(),
+                Some(area.clone()),
+                Message::SelectPaletteSlotsArea
+            ),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+        scrollable(list).height(300),
+        text(
+            "Palettes without a position use the rows loaded for the area's map in the base ROM. \
+             Moving a palette to a taken position leaves the palette that was there without one. \
+             Positions are saved in the project and used when exporting."
+        )
+        .size(12),
+    ]
+    .spacing(10);
+    if !problems.is_empty() {
+        col = col.push(text("Not all palettes could be assigned:").style(text::danger));
+        for p in problems {
+            col = col.push(text(format!("  {}", p)).size(12));
+        }
+    }
+    col = col.push(
+        row![
+            button(text("Close"))
+                .style(button::secondary)
+                .on_press(Message::CloseDialogue),
+            horizontal_space(),
+            button(text("Assign all areas")).on_press(Message::SolvePaletteSlots(theme.clone())),
+        ]
+        .spacing(10),
+    );
+    container(scrollable(col))
+        .width(550)
+        .max_height(700)
+        .padding(25)
+        .style(modal_background_style)
+        .into()
+}
+
+pub fn borrow_graphics_view<'a>(
+    state: &'a EditorState,
+    borrow: &'a BorrowGraphics,
+) -> Element<'a, Message> {
+    let palette_names: Vec<String> = state
+        .palettes
+        .iter()
+        .map(|x| format!("{}: {}", x.id, x.name))
+        .collect();
+    let selected_palette_name = state
+        .palettes
+        .iter()
+        .find(|x| x.id == borrow.palette_id)
+        .map(|x| format!("{}: {}", x.id, x.name));
+
+    let sheet_width = (GRAPHICS_SHEET_COLS * 8 * 2) as f32;
+    let mut sheets_col = Column::new().spacing(5);
+    for (row_idx, chunk) in borrow.previews.chunks(3).enumerate() {
+        let mut sheets_row = Row::new().spacing(5);
+        for (j, preview) in chunk.iter().enumerate() {
+            let i = row_idx * 3 + j;
+            let style = if borrow.selected.contains(&i) {
+                button::primary
+            } else {
+                button::secondary
+            };
+            sheets_row = sheets_row.push(
+                button(column![
+                    text(format!("Sheet {:02X}", i)).size(12),
+                    image(preview.clone())
+                        .filter_method(FilterMethod::Nearest)
+                        .width(sheet_width),
+                ])
+                .padding(4)
+                .style(style)
+                .on_press(Message::ToggleBorrowSheet(i)),
+            );
+        }
+        sheets_col = sheets_col.push(sheets_row);
+    }
+
+    let num_tiles = borrow.selected.len() * 64;
+    let import_msg = if num_tiles > 0 {
+        Some(Message::ImportTiles {
+            palette_id: borrow.palette_id,
+            tiles: borrow.selected_tiles(),
+        })
+    } else {
+        None
+    };
+    container(
+        column![
+            text(format!("Borrow graphics from {}", borrow.path.display())),
+            row![
+                text("Add tiles to palette"),
+                pick_list(
+                    palette_names,
+                    selected_palette_name,
+                    Message::SetBorrowPalette
+                )
+                .width(250),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            scrollable(sheets_col).height(500),
+            row![
+                button("Cancel")
+                    .style(button::secondary)
+                    .on_press(Message::CloseDialogue),
+                horizontal_space(),
+                button(text(format!(
+                    "Import {} sheets ({} tiles)",
+                    borrow.selected.len(),
+                    num_tiles
+                )))
+                .style(button::success)
+                .on_press_maybe(import_msg),
+            ]
+            .spacing(10),
+        ]
+        .spacing(15),
+    )
+    .width(900)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
+
+pub fn rename_history_view(renames: &[RenameRecord]) -> Element<'_, Message> {
+    let mut list = Column::new().spacing(5);
+    for r in renames.iter().rev() {
+        list = list.push(
+            row![
+                text(r.kind.to_string()).width(60).size(12),
+                text(format!("{} \u{2192} {}", r.old_name, r.new_name)).width(Length::Fill),
+                text(format_age(UNIX_EPOCH + Duration::from_secs(r.time))).size(12),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+        );
+    }
+    if renames.is_empty() {
+        list = list.push(text(
+            "No renames recorded. Enable recording in the settings to log them in Renames.json.",
+        ));
+    }
+    container(
+        column![
+            text("Rename history"),
+            scrollable(list).height(Length::Shrink),
+            button(text("Close"))
+                .style(button::secondary)
+                .on_press(Message::CloseDialogue),
+        ]
+        .spacing(15),
+    )
+    .width(500)
+    .max_height(500)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
+
+pub fn memory_view(state: &EditorState, project_bytes: u64) -> Element<'_, Message> {
+    let report = memory_report(state, project_bytes);
+    let line = |label: &'static str, value: String| {
+        row![text(label).width(160), text(value)]
+            .spacing(10)
+            .align_y(Vertical::Center)
+    };
+    container(
+        column![
+            text("Memory"),
+            text("Sizes are estimates of the editor's own data.").size(12),
+            line(
+                "Loaded areas",
+                format!(
+                    "{} ({} not in view), {}",
+                    report.loaded_areas,
+                    report.unused_areas,
+                    format_bytes(report.area_bytes)
+                )
+            ),
+            line("Palettes", format_bytes(report.palette_bytes)),
+            line(
+                "Undo history",
+                format!(
+                    "{} undo and {} redo steps, {}",
+                    report.undo_entries,
+                    report.redo_entries,
+                    format_bytes(report.undo_bytes)
+                )
+            ),
+            line(
+                "Cached screen images",
+                format!(
+                    "{}, {}",
+                    report.cached_screens,
+                    format_bytes(report.cached_image_bytes)
+                )
+            ),
+            line("Total", format_bytes(report.total_bytes())),
+            line("Project on disk", format_bytes(report.project_bytes)),
+            row![
+                button(text("Close"))
+                    .style(button::secondary)
+                    .on_press(Message::CloseDialogue),
+                horizontal_space(),
+                button(text("Clear caches"))
+                    .style(button::secondary)
+                    .on_press(Message::ClearCaches),
+                button(text("Compact memory"))
+                    .style(button::secondary)
+                    .on_press(Message::CompactMemory),
+                button(text("Unload unused areas"))
+                    .style(button::secondary)
+                    .on_press_maybe(
+                        (report.unused_areas > 0).then_some(Message::UnloadUnusedAreas)
+                    ),
+            ]
+            .spacing(10),
+        ]
+        .spacing(15),
+    )
+    .width(600)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
@@ -0,0 +1,85 @@
+// Module for browsing the project's stamps in the side panel, and saving the brush as one
+use iced::{
+    alignment::Vertical,
+    widget::{
+        button, column, container, horizontal_space, image, image::FilterMethod, row, scrollable,
+        text, text_input, Row,
+    },
+    ContentFit, Element,
+};
+use iced_fonts::BOOTSTRAP_FONT;
+
+use crate::{message::Message, stamps::Stamp, state::EditorState};
+
+const THUMBNAIL_SIZE: f32 = 96.0;
+
+fn stamp_item_view(stamp: &Stamp) -> Element<'_, Message> {
+    let thumbnail: Element<Message> = match &stamp.preview {
+        Some(handle) => image(handle.clone())
+            .filter_method(FilterMethod::Nearest)
+            .content_fit(ContentFit::Contain)
+            .width(THUMBNAIL_SIZE)
+            .height(THUMBNAIL_SIZE)
+            .into(),
+        None => horizontal_space().width(THUMBNAIL_SIZE).into(),
+    };
+    column![
+        button(container(thumbnail).center(THUMBNAIL_SIZE))
+            .padding(2)
+            .style(button::secondary)
+            .on_press(Message::LoadStamp(stamp.name.clone())),
+        row![
+            text(&stamp.name).size(12),
+            horizontal_space(),
+            button(text("\u{F5DE}").font(BOOTSTRAP_FONT).size(12))
+                .padding(2)
+                .style(button::danger)
+                .on_press(Message::DeleteStamp(stamp.name.clone())),
+        ]
+        .align_y(Vertical::Center)
+        .width(THUMBNAIL_SIZE + 4.0),
+    ]
+    .spacing(2)
+    .into()
+}
+
+pub fn stamps_view(state: &EditorState) -> Element<'_, Message> {
+    let has_selection =
+        state.selected_tile_block.size.0 > 0 && state.selected_tile_block.size.1 > 0;
+    let can_save = has_selection && !state.stamp_name.trim().is_empty();
+    let header = row![
+        text("Stamps"),
+        horizontal_space(),
+        button(text("\u{F62A}").font(BOOTSTRAP_FONT))
+            .style(button::secondary)
+            .on_press(Message::ShowStamps(false)),
+    ]
+    .spacing(10)
+    .align_y(Vertical::Center);
+    let save_row = row![
+        text_input("Name", &state.stamp_name)
+            .on_input(Message::SetStampName)
+            .on_submit_maybe(can_save.then_some(Message::SaveStamp)),
+        button(text("Save brush")).on_press_maybe(can_save.then_some(Message::SaveStamp)),
+    ]
+    .spacing(10)
+    .align_y(Vertical::Center);
+
+    let mut col = column![
+        header,
+        save_row,
+        text("Click a stamp to load it into the brush.").size(12),
+    ]
+    .spacing(10)
+    .padding(10)
+    .width(420);
+    if state.stamps.is_empty() {
+        return col.push(text("No stamps yet.")).into();
+    }
+    let mut list = Row::new().spacing(10);
+    for stamp in &state.stamps {
+        list = list.push(stamp_item_view(stamp));
+    }
+    col = col.push(scrollable(list.wrap()));
+    col.into()
+}
@@ -2,7 +2,7 @@
 use iced::{
     mouse,
     widget::{
-        button, canvas, column, row,
+        button, canvas, column, horizontal_space, row,
         scrollable::{Direction, Scrollbar},
         stack, text, Scrollable,
     },
@@ -364,15 +364,27 @@ pub fn tile_view(state: &EditorState, size: Size, reserved_height: f32) -> Eleme
         }
     }
 
+    // The tiles of HUD palettes can only be selected (for brushing areas), not edited:
+    let palette = &state.palettes[state.palette_idx];
+    let editable = palette.category.tiles_editable();
+    let tool = if editable { state.tool } else { Tool::Select };
+
     let col = column![
         row![
             text("Tiles"),
             button(text("\u{F64D}").font(iced_fonts::BOOTSTRAP_FONT))
                 .style(button::success)
-                .on_press(Message::AddTileRow(state.palettes[state.palette_idx].id)),
+                .on_press_maybe(editable.then_some(Message::AddTileRow(palette.id))),
             button(text("\u{F63B}").font(iced_fonts::BOOTSTRAP_FONT))
                 .style(button::danger)
-                .on_press(Message::DeleteTileRow(state.palettes[state.palette_idx].id)),
+                .on_press_maybe(editable.then_some(Message::DeleteTileRow(palette.id))),
+            button(text("Deduplicate"))
+                .style(button::secondary)
+                .on_press_maybe(editable.then_some(Message::DuplicateTilesDialogue(palette.id))),
+            horizontal_space(),
+            button(text("Find usages"))
+                .style(button::secondary)
+                .on_press_maybe(state.tile_idx.map(|_| Message::FindTileUsages)),
         ]
         .spacing(10)
         .align_y(iced::alignment::Vertical::Center),
@@ -386,7 +398,7 @@ pub fn tile_view(state: &EditorState, size: Size, reserved_height: f32) -> Eleme
                     thickness: 1.0,
                     identify_color: state.identify_color,
                     color_idx: state.color_idx,
-                    tool: state.tool,
+                    tool,
                 })
                 .width(384 + 4)
                 .height((num_rows * 8 * pixel_size + 4) as f32),
@@ -0,0 +1,60 @@
+// Module for the properties of a flute spot or whirlpool destination, shown when its marker is
+// clicked in an area view
+use iced::{
+    alignment::Vertical,
+    widget::{column, container, row, text},
+    Element,
+};
+use iced_aw::number_input;
+
+use crate::{
+    entrances::MARKER_SIZE,
+    message::Message,
+    state::{AreaPosition, EditorState, TileCoord},
+};
+
+use super::modal_background_style;
+
+pub fn travel_point_view(
+    state: &EditorState,
+    position: AreaPosition,
+    idx: usize,
+) -> Element<'_, Message> {
+    let area = state.area(position);
+    let Some(&point) = area.travel.get(idx) else {
+        return text("Travel point not found.").into();
+    };
+    let (x, y) = point.position;
+    let limit = (
+        area.size.0 as TileCoord * 32 - MARKER_SIZE,
+        area.size.1 as TileCoord * 32 - MARKER_SIZE,
+    );
+    let destination = match area.vanilla_map_id {
+        Some(parent) => format!("Map {:02X}", point.destination_map(parent)),
+        None => "None (the area has no vanilla map)".to_string(),
+    };
+    container(
+        column![
+            text(format!("{} {} in {}", point.kind, point.slot, area.name)),
+            row![text("Destination:").width(100), text(destination)]
+                .spacing(10)
+                .align_y(Vertical::Center),
+            row![
+                text("Position:").width(100),
+                text("X"),
+                number_input(&x, 0..=limit.0, move |x| Message::EditTravelPosition(x, y)).width(80),
+                text("Y"),
+                number_input(&y, 0..=limit.1, move |y| Message::EditTravelPosition(x, y)).width(80),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            text("Positions are in 8x8 tiles within the area. Markers can also be dragged.")
+                .size(12),
+        ]
+        .spacing(10),
+    )
+    .width(450)
+    .padding(25)
+    .style(modal_background_style)
+    .into()
+}
@@ -0,0 +1,157 @@
+// Guided tour for new users: a sequence of steps, each explaining one part of the editor while
+// highlighting it. The tour starts on the first run, and can be restarted from the Help dialogue.
+use iced::{
+    widget::{button, column, container, horizontal_space, row, text, Column},
+    Element, Length, Padding, Theme,
+};
+
+use crate::{message::Message, state::EditorState};
+
+use super::modal_background_style;
+
+// The part of the editor that a step is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TutorialTarget {
+    None,
+    PalettePanel,
+    Tileset,
+    AreaCanvas,
+    Shortcuts,
+}
+
+impl TutorialTarget {
+    // Whether the target is in the side panel (which has to be showing the tileset).
+    pub fn in_side_panel(self) -> bool {
+        matches!(self, TutorialTarget::PalettePanel | TutorialTarget::Tileset)
+    }
+}
+
+pub struct TutorialStep {
+    pub title: &'static str,
+    pub text: &'static str,
+    pub target: TutorialTarget,
+}
+
+pub const TUTORIAL_STEPS: &[TutorialStep] = &[
+    TutorialStep {
+        title: "Welcome",
+        text: "This short tour shows the main parts of the editor. \
+            You can leave it at any time, and restart it from the Help dialogue.",
+        target: TutorialTarget::None,
+    },
+    TutorialStep {
+        title: "Palettes",
+        text: "Each palette has 16 colors and its own set of 8x8 tiles. \
+            Pick a palette here, and click a color to edit it with the color picker \
+            (or pick it from the area with the eyedropper). \
+            Right-click the palette list to rename, duplicate or delete a palette.",
+        target: TutorialTarget::PalettePanel,
+    },
+    TutorialStep {
+        title: "Tileset",
+        text: "The tiles of the selected palette. Click a tile to edit its pixels and \
+            properties, or drag over several tiles to pick them up as a brush for the area.",
+        target: TutorialTarget::Tileset,
+    },
+    TutorialStep {
+        title: "Area",
+        text: "The area being edited. With the select tool (s), drag to pick up tiles; \
+            with the brush tool (b), click or drag to paint them. Undo with Ctrl+Z.",
+        target: TutorialTarget::AreaCanvas,
+    },
+    TutorialStep {
+        title: "Keyboard shortcuts",
+        text: "s/b/r/l/f: select, brush, rectangle, line and fill tools\n\
+            h/v: flip the brush\n\
+            t/a/p: tileset, side area, hide the side panel\n\
+            -/=: zoom out and in\n\
+            The Help button lists all the shortcuts.",
+        target: TutorialTarget::Shortcuts,
+    },
+];
+
+pub fn tutorial_target(state: &EditorState) -> TutorialTarget {
+    state
+        .tutorial_step
+        .and_then(|i| TUTORIAL_STEPS.get(i))
+        .map(|step| step.target)
+        .unwrap_or(TutorialTarget::None)
+}
+
+// Draw a border around a part of the editor if the current step is about it.
+pub fn highlight<'a>(
+    state: &EditorState,
+    target: TutorialTarget,
+    content: impl Into<Element<'a, Message>>,
+) -> Element<'a, Message> {
+    if tutorial_target(state) != target {
+        return content.into();
+    }
+    container(content)
+        .style(|theme: &Theme| container::Style {
+            border: iced::border::rounded(4)
+                .color(theme.extended_palette().primary.strong.color)
+                .width(3.0),
+            ..container::Style::default()
+        })
+        .into()
+}
+
+// The card describing the current step, placed next to the part of the editor it is about.
+pub fn tutorial_view(state: &EditorState) -> Option<Element<'_, Message>> {
+    let idx = state.tutorial_step?;
+    let step = TUTORIAL_STEPS.get(idx)?;
+    let last = idx + 1 == TUTORIAL_STEPS.len();
+    let mut body = Column::new().spacing(5);
+    for line in step.text.lines() {
+        body = body.push(text(line));
+    }
+    let card = container(
+        column![
+            row![
+                text(step.title).size(18),
+                horizontal_space(),
+                text(format!("{} of {}", idx + 1, TUTORIAL_STEPS.len())).size(12),
+            ],
+            body,
+            row![
+                button(text("Skip tour"))
+                    .style(button::text)
+                    .on_press(Message::CloseTutorial),
+                horizontal_space(),
+                button(text("Back"))
+                    .style(button::secondary)
+                    .on_press_maybe((idx > 0).then(|| Message::SetTutorialStep(idx - 1))),
+                button(text(if last { "Finish" } else { "Next" })).on_press(if last {
+                    Message::CloseTutorial
+                } else {
+                    Message::SetTutorialStep(idx + 1)
+                }),
+            ]
+            .spacing(10),
+        ]
+        .spacing(15),
+    )
+    .width(380)
+    .padding(20)
+    .style(modal_background_style);
+
+    // Keep the card clear of the highlighted part (the side panel is 420 wide, on the right):
+    let placed = container(card).width(Length::Fill).height(Length::Fill);
+    let placed = match step.target {
+        TutorialTarget::None => placed.center(Length::Fill),
+        TutorialTarget::PalettePanel | TutorialTarget::Tileset => placed
+            .align_right(Length::Fill)
+            .align_top(Length::Fill)
+            .padding(Padding::new(60.0).right(440.0)),
+        TutorialTarget::AreaCanvas => placed
+            .align_right(Length::Fill)
+            .align_bottom(Length::Fill)
+            .padding(30),
+        TutorialTarget::Shortcuts => placed
+            .align_right(Length::Fill)
+            .align_top(Length::Fill)
+            .padding(Padding::new(60.0).right(20.0)),
+    };
+    Some(placed.into())
+}
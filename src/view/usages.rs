@@ -0,0 +1,97 @@
+use iced::{
+    alignment::Vertical,
+    widget::{button, column, horizontal_space, row, scrollable, text, Column},
+    Element, Length,
+};
+use itertools::Itertools;
+
+use crate::{message::Message, state::EditorState, usages::UsageSearch};
+
+// Number of usages listed for each area, to keep the panel manageable for common tiles:
+const MAX_LISTED_PER_AREA: usize = 100;
+
+pub fn usages_view(state: &EditorState) -> Element<'_, Message> {
+    let Some(search) = &state.usage_search else {
+        return text("Select a tile and search for its usages.").into();
+    };
+    let palette_name = state
+        .palettes_id_idx_map
+        .get(&search.palette_id)
+        .map(|&idx| state.palettes[idx].name.as_str())
+        .unwrap_or("?");
+    let header = row![
+        text(format!(
+            "Usages of tile ${:02X} of {}",
+            search.tile_idx, palette_name
+        )),
+        horizontal_space(),
+        button(text("\u{F62A}").font(iced_fonts::BOOTSTRAP_FONT))
+            .style(button::secondary)
+            .on_press(Message::CloseTileUsages),
+    ]
+    .spacing(10)
+    .align_y(Vertical::Center);
+    column![header, usage_list(search)]
+        .spacing(10)
+        .padding(10)
+        .width(420)
+        .into()
+}
+
+fn usage_list(search: &UsageSearch) -> Element<'_, Message> {
+    let Some(usages) = &search.usages else {
+        return text("Searching...").into();
+    };
+    if usages.is_empty() {
+        return text("The tile isn't used in any area.").into();
+    }
+    let groups = usages.iter().chunk_by(|u| &u.area_id);
+    let mut list = Column::new().spacing(2);
+    let mut area_count = 0;
+    for (area_id, area_usages) in &groups {
+        let area_usages: Vec<_> = area_usages.collect();
+        area_count += 1;
+        list = list.push(text(format!(
+            "{} ({}): {}",
+            area_id.area,
+            area_id.theme,
+            area_usages.len()
+        )));
+        for &usage in area_usages.iter().take(MAX_LISTED_PER_AREA) {
+            list = list.push(
+                button(
+                    text(format!(
+                        "Screen ({}, {}), tile ({}, {})",
+                        usage.x / 32,
+                        usage.y / 32,
+                        usage.x % 32,
+                        usage.y % 32
+                    ))
+                    .size(12),
+                )
+                .style(button::text)
+                .padding([0, 20])
+                .on_press(Message::GoToTileUsage(usage.clone())),
+            );
+        }
+        if area_usages.len() > MAX_LISTED_PER_AREA {
+            list = list.push(
+                text(format!(
+                    "and {} more",
+                    area_usages.len() - MAX_LISTED_PER_AREA
+                ))
+                .size(12),
+            );
+        }
+    }
+    column![
+        text(format!(
+            "Used {} times in {} areas",
+            usages.len(),
+            area_count
+        )),
+        scrollable(list).height(Length::Fill),
+    ]
+    .spacing(10)
+    .into()
+}
@@ -0,0 +1,51 @@
+use iced::{
+    alignment::Vertical,
+    widget::{button, column, horizontal_space, row, scrollable, text, Column},
+    Element, Length,
+};
+
+use crate::{message::Message, state::EditorState, validation::ProjectIssue};
+
+pub fn issues_view(state: &EditorState) -> Element<'_, Message> {
+    let header = row![
+        text("Project issues"),
+        horizontal_space(),
+        button(text("\u{F130}").font(iced_fonts::BOOTSTRAP_FONT))
+            .style(button::secondary)
+            .on_press(Message::ValidateProject),
+        button(text("\u{F62A}").font(iced_fonts::BOOTSTRAP_FONT))
+            .style(button::secondary)
+            .on_press(Message::CloseIssues),
+    ]
+    .spacing(10)
+    .align_y(Vertical::Center);
+    column![header, issue_list(state.project_issues.as_deref())]
+        .spacing(10)
+        .padding(10)
+        .width(420)
+        .into()
+}
+
+fn issue_list(issues: Option<&[ProjectIssue]>) -> Element<'_, Message> {
+    let Some(issues) = issues else {
+        return text("Checking the project...").into();
+    };
+    if issues.is_empty() {
+        return text("No issues found.").into();
+    }
+    let mut list = Column::new().spacing(2);
+    for issue in issues {
+        list = list.push(
+            button(text(issue.description.clone()).size(12))
+                .style(button::text)
+                .padding([0, 10])
+                .on_press(Message::GoToIssue(issue.location.clone())),
+        );
+    }
+    column![
+        text(format!("{} issues found", issues.len())),
+        scrollable(list).height(Length::Fill),
+    ]
+    .spacing(10)
+    .into()
+}
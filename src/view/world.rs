@@ -0,0 +1,318 @@
+// Module for the world map: all areas of a theme on one canvas, arranged by dragging them.
+use std::time::{Duration, Instant};
+
+use iced::{
+    alignment::Vertical,
+    mouse,
+    widget::{
+        button, canvas, column, horizontal_space, pick_list, row,
+        scrollable::{Direction, Scrollbar},
+        stack, text, Scrollable,
+    },
+    Color, Element, Length, Padding, Point, Rectangle, Size, Vector,
+};
+
+use crate::{
+    message::Message,
+    state::EditorState,
+    world_map::{overlaps, WorldMap, WorldMapArea, WorldPosition},
+};
+
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+
+// Size of a screen on the world map, relative to the zoom level of the area views:
+fn screen_size(state: &EditorState) -> f32 {
+    16.0 * state.global_config.pixel_size
+}
+
+// We use two canvases, as in the area view: one for the area images, and one on top of it for
+// the labels, outlines, and dragging.
+struct WorldMapImages<'a> {
+    areas: &'a [WorldMapArea],
+    positions: Vec<WorldPosition>,
+    screen_size: f32,
+}
+
+fn area_rect(p: WorldPosition, size: (u8, u8), screen_size: f32) -> Rectangle {
+    Rectangle::new(
+        Point::new(p.0 as f32 * screen_size, p.1 as f32 * screen_size),
+        Size::new(size.0 as f32 * screen_size, size.1 as f32 * screen_size),
+    )
+}
+
+impl<'a> canvas::Program<Message> for WorldMapImages<'a> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &iced::Renderer,
+        _theme: &iced::Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        for (area, &p) in self.areas.iter().zip(&self.positions) {
+            let image = iced::advanced::image::Image::new(
+                iced::advanced::image::Handle::from_path(&area.png_path),
+            )
+            .filter_method(iced::widget::image::FilterMethod::Linear);
+            frame.draw_image(area_rect(p, area.size, self.screen_size), image);
+        }
+        vec![frame.into_geometry()]
+    }
+}
+
+struct WorldMapOverlay<'a> {
+    areas: &'a [WorldMapArea],
+    positions: Vec<WorldPosition>,
+    screen_size: f32,
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+struct Drag {
+    area_idx: usize,
+    // Offset of the cursor from the area's top-left corner:
+    grab: Vector,
+    cursor: Point,
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+struct InternalState {
+    drag: Option<Drag>,
+    // Time and area of the last left click, for detecting double-clicks:
+    last_click: Option<(Instant, usize)>,
+}
+
+impl<'a> WorldMapOverlay<'a> {
+    // Topmost area under the given point (relative to the canvas).
+    fn area_at(&self, p: Point) -> Option<usize> {
+        (0..self.areas.len()).rev().find(|&i| {
+            area_rect(self.positions[i], self.areas[i].size, self.screen_size).contains(p)
+        })
+    }
+
+    // Where a dragged area would be dropped, snapped to screens.
+    fn drop_position(&self, drag: &Drag) -> WorldPosition {
+        let top_left = drag.cursor - drag.grab;
+        (
+            ((top_left.x / self.screen_size).round() as i16).max(0),
+            ((top_left.y / self.screen_size).round() as i16).max(0),
+        )
+    }
+}
+
+impl<'a> canvas::Program<Message> for WorldMapOverlay<'a> {
+    type State = InternalState;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (canvas::event::Status, Option<Message>) {
+        let Some(p) = cursor.position_in(bounds) else {
+            // Dropping outside of the map cancels the drag:
+            if let canvas::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) = event {
+                state.drag = None;
+            }
+            return (canvas::event::Status::Ignored, None);
+        };
+        match event {
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let Some(i) = self.area_at(p) else {
+                    return (canvas::event::Status::Ignored, None);
+                };
+                let now = Instant::now();
+                if let Some((time, last_idx)) = state.last_click {
+                    if last_idx == i && now.duration_since(time) < DOUBLE_CLICK_TIME {
+                        state.last_click = None;
+                        state.drag = None;
+                        return (
+                            canvas::event::Status::Captured,
+                            Some(Message::OpenWorldMapArea(self.areas[i].name.clone())),
+                        );
+                    }
+                }
+                state.last_click = Some((now, i));
+                let rect = area_rect(self.positions[i], self.areas[i].size, self.screen_size);
+                state.drag = Some(Drag {
+                    area_idx: i,
+                    grab: p - rect.position(),
+                    cursor: p,
+                });
+                (canvas::event::Status::Captured, None)
+            }
+            canvas::Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                if let Some(drag) = &mut state.drag {
+                    drag.cursor = p;
+                    return (canvas::event::Status::Captured, None);
+                }
+                (canvas::event::Status::Ignored, None)
+            }
+            canvas::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                let Some(mut drag) = state.drag.take() else {
+                    return (canvas::event::Status::Ignored, None);
+                };
+                drag.cursor = p;
+                let position = self.drop_position(&drag);
+                if position == self.positions[drag.area_idx] {
+                    return (canvas::event::Status::Captured, None);
+                }
+                (
+                    canvas::event::Status::Captured,
+                    Some(Message::SetWorldMapPosition {
+                        area: self.areas[drag.area_idx].name.clone(),
+                        position: Some(position),
+                    }),
+                )
+            }
+            _ => (canvas::event::Status::Ignored, None),
+        }
+    }
+
+    fn draw(
+        &self,
+        state: &Self::State,
+        renderer: &iced::Renderer,
+        _theme: &iced::Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        for (i, area) in self.areas.iter().enumerate() {
+            let p = self.positions[i];
+            // Overlapping areas are outlined in red, to be moved apart:
+            let overlapping =
+                self.areas.iter().enumerate().any(|(j, other)| {
+                    j != i && overlaps(p, area.size, self.positions[j], other.size)
+                });
+            let rect = area_rect(p, area.size, self.screen_size);
+            frame.stroke(
+                &canvas::Path::rectangle(rect.position(), rect.size()),
+                canvas::Stroke {
+                    style: canvas::stroke::Style::Solid(if overlapping {
+                        Color::from_rgb(1.0, 0.2, 0.2)
+                    } else {
+                        Color::from_rgba(1.0, 1.0, 1.0, 0.5)
+                    }),
+                    width: 1.0,
+                    ..Default::default()
+                },
+            );
+            let label_position = rect.position() + Vector::new(4.0, 4.0);
+            for (offset, color) in [(1.0, Color::BLACK), (0.0, Color::WHITE)] {
+                frame.fill_text(canvas::Text {
+                    content: area.name.clone(),
+                    position: label_position + Vector::new(offset, offset),
+                    color,
+                    size: 14.0.into(),
+                    ..canvas::Text::default()
+                });
+            }
+        }
+        if let Some(drag) = &state.drag {
+            let area = &self.areas[drag.area_idx];
+            let rect = area_rect(self.drop_position(drag), area.size, self.screen_size);
+            frame.fill_rectangle(
+                rect.position(),
+                rect.size(),
+                Color::from_rgba(1.0, 1.0, 1.0, 0.25),
+            );
+            frame.stroke(
+                &canvas::Path::rectangle(rect.position(), rect.size()),
+                canvas::Stroke {
+                    style: canvas::stroke::Style::Solid(Color::WHITE),
+                    width: 2.0,
+                    ..Default::default()
+                },
+            );
+        }
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &Self::State,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        if state.drag.is_some() {
+            return mouse::Interaction::Grabbing;
+        }
+        match cursor.position_in(bounds) {
+            Some(p) if self.area_at(p).is_some() => mouse::Interaction::Grab,
+            _ => mouse::Interaction::default(),
+        }
+    }
+}
+
+pub fn world_map_view<'a>(state: &'a EditorState, world_map: &'a WorldMap) -> Element<'a, Message> {
+    let positions = world_map.positions(state);
+    let screen_size = screen_size(state);
+    // Leave a screen of space past the areas, to be able to drag them further:
+    let width = world_map
+        .areas
+        .iter()
+        .zip(&positions)
+        .map(|(a, p)| p.0 + a.size.0 as i16)
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let height = world_map
+        .areas
+        .iter()
+        .zip(&positions)
+        .map(|(a, p)| p.1 + a.size.1 as i16)
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let size = Size::new(width as f32 * screen_size, height as f32 * screen_size);
+
+    column![
+        row![
+            text("World map"),
+            pick_list(
+                state.theme_names.clone(),
+                Some(world_map.theme.clone()),
+                Message::OpenWorldMap
+            )
+            .width(200),
+            text("Drag areas to arrange them; double-click an area to edit it.").size(12),
+            horizontal_space(),
+            button(text("Close"))
+                .style(button::secondary)
+                .on_press(Message::CloseWorldMap),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+        Scrollable::with_direction(
+            column![stack![
+                canvas(WorldMapImages {
+                    areas: &world_map.areas,
+                    positions: positions.clone(),
+                    screen_size,
+                })
+                .width(size.width)
+                .height(size.height),
+                canvas(WorldMapOverlay {
+                    areas: &world_map.areas,
+                    positions,
+                    screen_size,
+                })
+                .width(size.width)
+                .height(size.height),
+            ]]
+            .padding(Padding::new(0.0).right(16.0).bottom(16.0)),
+            Direction::Both {
+                vertical: Scrollbar::default(),
+                horizontal: Scrollbar::default(),
+            },
+        )
+        .width(Length::Fill)
+        .height(Length::Fill),
+    ]
+    .spacing(10)
+    .into()
+}
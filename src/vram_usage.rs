@@ -0,0 +1,123 @@
+// Per-screen check of the graphics that the game must hold at once: the distinct 8x8 graphics
+// (VRAM chars) and palette rows each screen uses, against the space the overworld has for them.
+// An area's total can exceed the budgets when its screens use different graphics, but a screen
+// over a budget can't be shown correctly. Animated tiles have their own slots, so they aren't
+// counted (the compile check covers them).
+use anyhow::Result;
+use hashbrown::HashSet;
+use itertools::Itertools;
+
+use crate::{
+    compile_check::{canonical_pixels, CHAR_BUDGET, HUD_PALETTE_ROW_BUDGET, PALETTE_ROW_BUDGET},
+    persist::load_area,
+    state::{Area, AreaId, EditorState, PaletteCategory, PaletteId, Tile},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScreenUsage {
+    pub area_id: AreaId,
+    // Position of the screen within the area, in screens:
+    pub screen: (u8, u8),
+    pub char_count: usize,
+    pub palette_count: usize,
+    pub hud_palette_count: usize,
+}
+
+impl ScreenUsage {
+    // Descriptions of the budgets that the screen exceeds.
+    pub fn problems(&self) -> Vec<String> {
+        let mut out = vec![];
+        for (label, count, budget) in [
+            ("8x8 graphics", self.char_count, CHAR_BUDGET),
+            ("palette rows", self.palette_count, PALETTE_ROW_BUDGET),
+            (
+                "HUD palette rows",
+                self.hud_palette_count,
+                HUD_PALETTE_ROW_BUDGET,
+            ),
+        ] {
+            if count > budget {
+                out.push(format!("{} {} of {}", count, label, budget));
+            }
+        }
+        out
+    }
+
+    pub fn fits(&self) -> bool {
+        self.problems().is_empty()
+    }
+}
+
+pub fn screen_usage(state: &EditorState, area: &Area, screen: (u8, u8)) -> ScreenUsage {
+    let mut chars = HashSet::new();
+    let mut palettes: HashSet<PaletteId> = HashSet::new();
+    let mut hud_palettes: HashSet<PaletteId> = HashSet::new();
+    let x0 = screen.0 as u16 * 32;
+    let y0 = screen.1 as u16 * 32;
+    for (y, x) in (y0..y0 + 32).cartesian_product(x0..x0 + 32) {
+        let (Ok(palette_id), Ok(tile_idx)) = (area.get_palette(x, y), area.get_tile(x, y)) else {
+            continue;
+        };
+        // Missing palettes and tiles are left to the compile check to report:
+        let Some(palette) = state
+            .palettes_id_idx_map
+            .get(&palette_id)
+            .map(|&idx| &state.palettes[idx])
+        else {
+            continue;
+        };
+        let Some(tile) = palette.tiles.get(tile_idx as usize).map(Tile::exported) else {
+            continue;
+        };
+        if palette.category == PaletteCategory::HUD {
+            hud_palettes.insert(palette_id);
+        } else {
+            palettes.insert(palette_id);
+        }
+        if tile.animated.is_none() {
+            chars.insert(canonical_pixels(tile.pixels));
+        }
+    }
+    ScreenUsage {
+        area_id: AreaId {
+            area: area.name.clone(),
+            theme: area.theme.clone(),
+        },
+        screen,
+        char_count: chars.len(),
+        palette_count: palettes.len(),
+        hud_palette_count: hud_palettes.len(),
+    }
+}
+
+pub fn area_usage(state: &EditorState, area: &Area) -> Vec<ScreenUsage> {
+    (0..area.size.1)
+        .cartesian_product(0..area.size.0)
+        .map(|(y, x)| screen_usage(state, area, (x, y)))
+        .collect()
+}
+
+// Usage of the screens of the main area, or of all areas of its theme (loading those that
+// aren't loaded, without keeping them).
+pub fn vram_usage(state: &EditorState, all_areas: bool) -> Result<Vec<ScreenUsage>> {
+    if !all_areas {
+        return Ok(area_usage(state, state.main_area()));
+    }
+    let mut out = vec![];
+    for area_name in &state.area_names {
+        let area_id = AreaId {
+            area: area_name.clone(),
+            theme: state.main_area_id.theme.clone(),
+        };
+        let loaded;
+        let area = match state.areas.get(&area_id) {
+            Some(area) => area,
+            None => {
+                loaded = load_area(state, &area_id)?;
+                &loaded
+            }
+        };
+        out.extend(area_usage(state, area));
+    }
+    Ok(out)
+}
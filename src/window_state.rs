@@ -0,0 +1,90 @@
+// Persistence of the main window's size, position, and maximized state across sessions.
+//
+// Iced doesn't give us a list of the connected monitors, only the resolution of the primary
+// monitor at the time the window is created (via `Position::SpecificWith`). We record that
+// resolution with the window geometry, and if it changed since the window was last closed,
+// only reuse the saved position if the window would still be reachable on the primary monitor.
+use std::sync::{Mutex, OnceLock};
+
+use iced::{window, Point, Size};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_WINDOW_SIZE: Size = Size {
+    width: 1440.0,
+    height: 960.0,
+};
+
+// Part of the window near its top-left corner that must be on-screen to be able to drag it.
+const TITLE_BAR_SIZE: Size = Size {
+    width: 100.0,
+    height: 30.0,
+};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct WindowGeometry {
+    // Position and size of the window when not maximized:
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub maximized: bool,
+    // Resolution of the primary monitor in the session where the geometry was saved:
+    #[serde(default)]
+    pub monitor_width: f32,
+    #[serde(default)]
+    pub monitor_height: f32,
+}
+
+impl WindowGeometry {
+    pub fn size(&self) -> Size {
+        Size {
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    // Position to restore the window to, or None if it should be centered instead.
+    pub fn restored_position(&self, monitor_size: Size) -> Option<Point> {
+        let same_monitors =
+            self.monitor_width == monitor_size.width && self.monitor_height == monitor_size.height;
+        let on_primary_monitor = self.x >= 0.0
+            && self.y >= 0.0
+            && self.x + TITLE_BAR_SIZE.width <= monitor_size.width
+            && self.y + TITLE_BAR_SIZE.height <= monitor_size.height;
+        if same_monitors || on_primary_monitor {
+            Some(Point::new(self.x, self.y))
+        } else {
+            None
+        }
+    }
+}
+
+static SAVED_GEOMETRY: OnceLock<Option<WindowGeometry>> = OnceLock::new();
+static PRIMARY_MONITOR_SIZE: Mutex<Option<Size>> = Mutex::new(None);
+
+fn restore_position(window_size: Size, monitor_size: Size) -> Point {
+    *PRIMARY_MONITOR_SIZE.lock().unwrap() = Some(monitor_size);
+    let saved = SAVED_GEOMETRY.get().copied().flatten();
+    saved
+        .and_then(|g| g.restored_position(monitor_size))
+        .unwrap_or(Point::new(
+            ((monitor_size.width - window_size.width) / 2.0).max(0.0),
+            ((monitor_size.height - window_size.height) / 2.0).max(0.0),
+        ))
+}
+
+// Resolution of the primary monitor when the window was opened, if known.
+pub fn primary_monitor_size() -> Option<Size> {
+    *PRIMARY_MONITOR_SIZE.lock().unwrap()
+}
+
+// Settings for opening the main window with the given saved geometry.
+pub fn initial_settings(saved: Option<WindowGeometry>) -> window::Settings {
+    let _ = SAVED_GEOMETRY.set(saved);
+    window::Settings {
+        size: saved.map(|g| g.size()).unwrap_or(DEFAULT_WINDOW_SIZE),
+        position: window::Position::SpecificWith(restore_position),
+        exit_on_close_request: false,
+        ..window::Settings::default()
+    }
+}
@@ -0,0 +1,112 @@
+// World map: all areas of a theme laid out together, with their relative positions (in units
+// of screens) stored in the project metadata. Areas that haven't been placed yet default to
+// their position in the vanilla overworld, if they have one.
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::{
+    persist::{area_png_path, load_area},
+    state::{AreaId, AreaName, EditorState, ThemeName},
+};
+
+pub type WorldPosition = (i16, i16);
+
+#[derive(Clone, Debug)]
+pub struct WorldMapArea {
+    pub name: AreaName,
+    // X and Y dimensions, measured in number of screens:
+    pub size: (u8, u8),
+    pub vanilla_map_id: Option<u8>,
+    pub png_path: PathBuf,
+}
+
+#[derive(Clone, Debug)]
+pub struct WorldMap {
+    pub theme: ThemeName,
+    pub areas: Vec<WorldMapArea>,
+}
+
+// Vanilla positions: the Light World and Dark World side by side (each 8x8 maps, which are 2x2
+// screens each), with the special areas below the Light World.
+fn vanilla_position(map_id: u8) -> WorldPosition {
+    let (x0, y0, first_id) = match map_id {
+        0x00..=0x3F => (0, 0, 0x00),
+        0x40..=0x7F => (17, 0, 0x40),
+        _ => (0, 17, 0x80),
+    };
+    let i = (map_id - first_id) as i16;
+    (x0 + 2 * (i % 8), y0 + 2 * (i / 8))
+}
+
+// Row where areas without a vanilla position are placed by default (below the special areas),
+// filling rows as wide as a world.
+const CUSTOM_AREAS_ROW: i16 = 22;
+const CUSTOM_AREAS_WIDTH: i16 = 16;
+
+impl WorldMap {
+    pub fn load(state: &EditorState, theme: &ThemeName) -> Result<Self> {
+        let mut areas = vec![];
+        for name in &state.area_names {
+            let area_id = AreaId {
+                area: name.clone(),
+                theme: theme.clone(),
+            };
+            // Use the area in memory if loaded, since it may have unsaved changes:
+            let (size, vanilla_map_id) = match state.areas.get(&area_id) {
+                Some(area) => (area.size, area.vanilla_map_id),
+                None => {
+                    let area = load_area(state, &area_id)?;
+                    (area.size, area.vanilla_map_id)
+                }
+            };
+            areas.push(WorldMapArea {
+                name: name.clone(),
+                size,
+                vanilla_map_id,
+                png_path: area_png_path(state, &area_id)?,
+            });
+        }
+        Ok(WorldMap {
+            theme: theme.clone(),
+            areas,
+        })
+    }
+
+    // Position of each area, in the same order as `areas`.
+    pub fn positions(&self, state: &EditorState) -> Vec<WorldPosition> {
+        // Where the next custom area goes, and the height of the row so far:
+        let mut next_custom = (0, CUSTOM_AREAS_ROW);
+        let mut row_height = 0;
+        self.areas
+            .iter()
+            .map(|a| {
+                if let Some(&p) = state.project_metadata.world_layout.get(&a.name) {
+                    return p;
+                }
+                match a.vanilla_map_id {
+                    Some(id) => vanilla_position(id),
+                    None => {
+                        let (w, h) = (a.size.0 as i16, a.size.1 as i16);
+                        if next_custom.0 > 0 && next_custom.0 + w > CUSTOM_AREAS_WIDTH {
+                            next_custom = (0, next_custom.1 + row_height + 1);
+                            row_height = 0;
+                        }
+                        let p = next_custom;
+                        next_custom.0 += w + 1;
+                        row_height = row_height.max(h);
+                        p
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+// Whether two areas, at the given positions and sizes, overlap.
+pub fn overlaps(p1: WorldPosition, s1: (u8, u8), p2: WorldPosition, s2: (u8, u8)) -> bool {
+    p1.0 < p2.0 + s2.0 as i16
+        && p2.0 < p1.0 + s1.0 as i16
+        && p1.1 < p2.1 + s2.1 as i16
+        && p2.1 < p1.1 + s1.1 as i16
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use common::{brush_tiles, example_area_id, TestProject};
+use iced::Point;
+use z3_overworld_editor::{
+    animated_tiles::{AnimatedBank, AnimatedSlot},
+    compile_check::check_area,
+    message::{Message, SelectionSource},
+    state::{Flip, TileProperties, TilePropertyChange},
+};
+
+fn slot(bank: AnimatedBank, slot: u8) -> Option<AnimatedSlot> {
+    Some(AnimatedSlot { bank, slot })
+}
+
+fn set_animated(project: &mut TestProject, tile_idx: u16, animated: Option<AnimatedSlot>) {
+    let properties = project.state.palettes[0].tiles[tile_idx as usize].properties();
+    project.send(Message::SetTileProperties {
+        palette_id: 0,
+        properties: vec![(
+            tile_idx,
+            TileProperties {
+                animated,
+                ..properties
+            },
+        )],
+    });
+}
+
+#[test]
+fn selected_tiles_get_consecutive_slots() {
+    let mut project = TestProject::new("animated-select");
+    project.send(Message::StartTileSelection(
+        Point::new(0, 0),
+        SelectionSource::Tileset,
+    ));
+    project.send(Message::EndTileSelection(Point::new(2, 0)));
+    let (palette_id, selected) = project.state.selected_tileset_tiles().unwrap();
+    let change = TilePropertyChange::Animated(slot(AnimatedBank::Default, 4));
+    let properties = change.apply_to(&selected).unwrap();
+    project.send(Message::SetTileProperties {
+        palette_id,
+        properties,
+    });
+    let animated: Vec<_> = project.state.palettes[0].tiles[..4]
+        .iter()
+        .map(|t| t.animated)
+        .collect();
+    assert_eq!(
+        animated,
+        vec![
+            slot(AnimatedBank::Default, 4),
+            slot(AnimatedBank::Default, 5),
+            slot(AnimatedBank::Default, 6),
+            None
+        ]
+    );
+    project.save();
+    assert_eq!(
+        project.saved_palette("Default").tiles[2].animated,
+        slot(AnimatedBank::Default, 6)
+    );
+
+    // The slots run out at the end of the bank:
+    let change = TilePropertyChange::Animated(slot(AnimatedBank::Default, 30));
+    assert!(change.apply_to(&selected).is_err());
+
+    project.undo();
+    assert!(project.state.palettes[0]
+        .tiles
+        .iter()
+        .all(|t| t.animated.is_none()));
+}
+
+#[test]
+fn compile_check_validates_animated_tiles() {
+    let mut project = TestProject::new("animated-check");
+    project.state.palettes[0].tiles[2].pixels[0][0] = 1;
+    set_animated(&mut project, 1, slot(AnimatedBank::Default, 0));
+    set_animated(&mut project, 2, slot(AnimatedBank::Default, 0));
+    set_animated(&mut project, 3, slot(AnimatedBank::DeathMountain, 1));
+    project.send(brush_tiles(
+        example_area_id(),
+        0,
+        0,
+        vec![1, 1, 3],
+        Flip::None,
+    ));
+
+    // Animated tiles don't take up static characters:
+    let state = &project.state;
+    let report = check_area(state, state.main_area());
+    assert_eq!(report.char_count, 1);
+    assert_eq!(report.animated_slot_count, 1);
+    // The area doesn't replace a Death Mountain map, so it can't use that bank:
+    assert_eq!(report.animated_problem_count, 1);
+    assert!(!report.is_ok());
+
+    // Two different graphics can't share a slot:
+    project.send(brush_tiles(
+        example_area_id(),
+        0,
+        0,
+        vec![1, 2, 1],
+        Flip::None,
+    ));
+    let state = &project.state;
+    let report = check_area(state, state.main_area());
+    assert_eq!(report.animated_problem_count, 1);
+    assert!(report.animated_problems[0].contains("different graphic"));
+
+    project.send(brush_tiles(
+        example_area_id(),
+        0,
+        0,
+        vec![1, 1, 1],
+        Flip::None,
+    ));
+    let state = &project.state;
+    assert!(check_area(state, state.main_area()).is_ok());
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::{
+    annotations::{annotation, annotation_records, AnnotationRecord, ScreenAnnotation},
+    message::Message,
+    state::{AreaPosition, SidePanelView},
+};
+
+#[test]
+fn annotations_are_edited_saved_and_undone() {
+    let mut project = TestProject::new("annotations");
+    let area_id = project.state.main_area_id.clone();
+    project.send(Message::ShowAnnotations(true));
+    assert!(matches!(
+        project.state.side_panel_view,
+        SidePanelView::Annotations
+    ));
+    assert!(project.state.show_annotations);
+
+    let set = |screen, value| Message::SetScreenAnnotation {
+        position: AreaPosition::Main,
+        area_id: area_id.clone(),
+        screen,
+        value,
+    };
+    project.send(set((1, 0), Some(7)));
+    project.send(set((0, 0), Some(3)));
+    project.send(set((1, 0), Some(9)));
+    project.save();
+    let saved = project.saved_area(&area_id.area, &area_id.theme);
+    assert_eq!(
+        saved.annotations,
+        vec![
+            ScreenAnnotation {
+                screen: (0, 0),
+                value: 3
+            },
+            ScreenAnnotation {
+                screen: (1, 0),
+                value: 9
+            },
+        ]
+    );
+
+    project.send(set((0, 0), None));
+    assert_eq!(annotation(project.state.main_area(), (0, 0)), None);
+    project.undo();
+    assert_eq!(annotation(project.state.main_area(), (0, 0)), Some(3));
+    project.undo();
+    assert_eq!(annotation(project.state.main_area(), (1, 0)), Some(7));
+
+    // Screens outside the area can't be annotated:
+    let size = project.state.main_area().size;
+    project.send(set((size.0, 0), Some(1)));
+    assert_eq!(project.state.main_area().annotations.len(), 2);
+}
+
+#[test]
+fn annotations_are_exported_with_overworld_screens() {
+    let mut project = TestProject::new("annotations-export");
+    let area_id = project.state.main_area_id.clone();
+    project.state.main_area_mut().vanilla_map_id = Some(0x10);
+    project.send(Message::SetScreenAnnotation {
+        position: AreaPosition::Main,
+        area_id: area_id.clone(),
+        screen: (1, 1),
+        value: Some(5),
+    });
+    assert_eq!(
+        annotation_records(&project.state).unwrap(),
+        vec![AnnotationRecord {
+            area: area_id.area.clone(),
+            theme: area_id.theme.clone(),
+            screen: (1, 1),
+            map_screen: Some(0x10),
+            quadrant: Some((1, 1)),
+            value: 5,
+        }]
+    );
+
+    let path = project.dir.join("annotations.json");
+    project.send(Message::ExportAnnotationsTo(Some(path.clone())));
+    let exported: Vec<AnnotationRecord> =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(exported, annotation_records(&project.state).unwrap());
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::{brush, brush_tiles, example_area_in, TestProject};
+use z3_overworld_editor::{
+    area_diff::{compare_areas, DiffBase, ScreenChanges},
+    message::Message,
+    state::Flip,
+};
+
+fn changed_tiles(project: &TestProject) -> Vec<(u16, u16)> {
+    let diff = project.state.area_diff.as_ref().unwrap();
+    compare_areas(project.state.main_area(), &diff.base_area).tiles
+}
+
+#[test]
+fn diff_against_saved_version() {
+    let mut project = TestProject::new("area-diff-saved");
+    project.send(brush(3, 4, 5));
+    project.send(Message::ShowAreaDiff(DiffBase::Saved));
+    assert_eq!(changed_tiles(&project), vec![(3, 4)]);
+    project.send(brush(40, 35, 6));
+    let diff = project.state.area_diff.as_ref().unwrap();
+    let changes = compare_areas(project.state.main_area(), &diff.base_area);
+    assert_eq!(changes.tiles, vec![(3, 4), (40, 35)]);
+    assert_eq!(
+        changes.screens,
+        vec![
+            ScreenChanges {
+                position: (0, 0),
+                tiles: 1
+            },
+            ScreenChanges {
+                position: (1, 1),
+                tiles: 1
+            },
+        ]
+    );
+    assert!(!changes.size_changed);
+
+    // The saved version is kept while comparing with it:
+    project.save();
+    assert_eq!(
+        project.saved_area("Example", "Base").screens[0].tiles[4][3],
+        0
+    );
+    project.send(Message::CloseAreaDiff);
+    project.save();
+    assert_eq!(
+        project.saved_area("Example", "Base").screens[0].tiles[4][3],
+        5
+    );
+
+    // Reloading from disk ends the comparison:
+    project.send(Message::ShowAreaDiff(DiffBase::Saved));
+    assert!(changed_tiles(&project).is_empty());
+    project.send(Message::ModifiedReload);
+    assert!(project.state.area_diff.is_none());
+}
+
+#[test]
+fn diff_against_other_theme() {
+    let mut project = TestProject::new("area-diff-theme");
+    project.send(Message::AddTheme("Night".to_string()));
+    project.send(brush_tiles(
+        example_area_in("Night"),
+        10,
+        2,
+        vec![7],
+        Flip::None,
+    ));
+    project.send(Message::ShowAreaDiff(DiffBase::Theme("Base".to_string())));
+    assert_eq!(changed_tiles(&project), vec![(10, 2)]);
+
+    // Screens missing from the other version are entirely changed:
+    let diff = project.state.area_diff.as_ref().unwrap();
+    let mut base = diff.base_area.clone();
+    base.screens.truncate(1);
+    let changes = compare_areas(project.state.main_area(), &base);
+    let screen_count = project.state.main_area().screens.len();
+    assert_eq!(changes.screens.len(), screen_count);
+    assert_eq!(changes.tiles.len(), 1 + (screen_count - 1) * 1024);
+
+    // Comparing with a missing theme fails without losing the current comparison:
+    project.send(Message::ShowAreaDiff(DiffBase::Theme(
+        "Missing".to_string(),
+    )));
+    assert!(project.state.dialogue.is_some());
+    assert_eq!(changed_tiles(&project), vec![(10, 2)]);
+}
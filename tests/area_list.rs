@@ -0,0 +1,16 @@
+mod common;
+
+use common::TestProject;
+use iced::mouse;
+use z3_overworld_editor::{message::Message, state::AreaPosition};
+
+#[test]
+fn area_drag_ends_on_release_elsewhere() {
+    let mut project = TestProject::new("area-drag");
+    project.send(Message::AreaListDialogue(AreaPosition::Main));
+    project.send(Message::DragArea("Example".to_string()));
+    project.send(Message::Event(iced::Event::Mouse(
+        mouse::Event::ButtonReleased(mouse::Button::Left),
+    )));
+    assert!(project.state.dragging_area.is_none());
+}
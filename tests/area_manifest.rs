@@ -0,0 +1,87 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::{
+    area_manifest::{parse_area_manifest, AreaManifestEntry},
+    message::Message,
+    state::Dialogue,
+};
+
+#[test]
+fn manifest_formats_parse() {
+    let csv = "name,size,vanilla_map_id,bg_color\n\
+               # Light world\n\
+               Kakariko, 2x2, $18, #488038\n\
+               Lake,2X2,0x35,$1CE9\n\
+               \n\
+               Grove,1x1\n";
+    let entries = parse_area_manifest(csv, false).unwrap();
+    assert_eq!(
+        entries,
+        vec![
+            AreaManifestEntry {
+                name: "Kakariko".to_string(),
+                size: (2, 2),
+                vanilla_map_id: Some(0x18),
+                bg_color: Some([9, 16, 7]),
+            },
+            AreaManifestEntry {
+                name: "Lake".to_string(),
+                size: (2, 2),
+                vanilla_map_id: Some(0x35),
+                bg_color: Some([9, 7, 7]),
+            },
+            AreaManifestEntry {
+                name: "Grove".to_string(),
+                size: (1, 1),
+                vanilla_map_id: None,
+                bg_color: None,
+            },
+        ]
+    );
+
+    let json =
+        r##"[{"name": "Kakariko", "size": [2, 2], "vanilla_map_id": 24, "bg_color": "#488038"}]"##;
+    assert_eq!(parse_area_manifest(json, true).unwrap()[..], entries[..1]);
+
+    assert!(parse_area_manifest("Big,9x1", false).is_err());
+    assert!(parse_area_manifest("Odd,2x2,$100", false).is_err());
+    assert!(parse_area_manifest("# nothing\n", false).is_err());
+}
+
+#[test]
+fn manifest_areas_are_created_in_every_theme() {
+    let mut project = TestProject::new("area-manifest");
+    project.send(Message::AddTheme("Dark".to_string()));
+    let path = project.project_dir().join("areas.csv");
+    std::fs::write(&path, "Castle,2x1,$1B,#FFFFFF\nExample,1x1\nGrove,1x1\n").unwrap();
+    project.send(Message::AreaManifestOpened(Some(path)));
+    let Some(Dialogue::CreateAreas { entries, .. }) = &project.state.dialogue else {
+        panic!("progress not shown");
+    };
+    // Each area is created by its own message, queued by the previous one:
+    let entries = entries.clone();
+    project.send_all(entries.into_iter().map(Message::CreateManifestArea));
+    let Some(Dialogue::CreateAreas { done, skipped, .. }) = &project.state.dialogue else {
+        panic!("progress not shown");
+    };
+    assert_eq!(*done, 3);
+    assert_eq!(skipped, &vec!["Example: already exists".to_string()]);
+    assert_eq!(project.state.area_names, vec!["Castle", "Example", "Grove"]);
+    assert_eq!(project.state.main_area_id.area, "Example");
+
+    for theme in ["Base", "Dark"] {
+        let castle = project.saved_area("Castle", theme);
+        assert_eq!(castle.size, (2, 1));
+        assert_eq!(castle.screens.len(), 2);
+        assert_eq!(castle.vanilla_map_id, Some(0x1B));
+        assert_eq!(castle.bg_color, [31, 31, 31]);
+    }
+    // Without a BG color in the manifest, the current area's is used:
+    let example_bg = project.state.main_area().bg_color;
+    assert_eq!(project.saved_area("Grove", "Base").bg_color, example_bg);
+
+    project.undo();
+    assert_eq!(project.state.area_names, vec!["Castle", "Example"]);
+    assert!(!project.area_path("Grove", "Dark").exists());
+}
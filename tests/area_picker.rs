@@ -0,0 +1,80 @@
+mod common;
+
+use common::TestProject;
+use iced::keyboard::{self, key, Key, Modifiers};
+use z3_overworld_editor::{
+    area_picker::{fuzzy_score, AreaPicker},
+    message::Message,
+    state::{AreaPosition, Dialogue},
+};
+
+fn arrow_down() -> Message {
+    Message::Event(iced::Event::Keyboard(keyboard::Event::KeyPressed {
+        key: Key::Named(key::Named::ArrowDown),
+        modified_key: Key::Named(key::Named::ArrowDown),
+        physical_key: key::Physical::Code(key::Code::ArrowDown),
+        location: keyboard::Location::Standard,
+        modifiers: Modifiers::empty(),
+        text: None,
+    }))
+}
+
+fn picker(project: &TestProject) -> &AreaPicker {
+    match &project.state.dialogue {
+        Some(Dialogue::AreaPicker(picker)) => picker,
+        _ => panic!("area picker not open"),
+    }
+}
+
+fn match_names(project: &TestProject) -> Vec<String> {
+    picker(project)
+        .matches()
+        .iter()
+        .map(|e| e.name.clone())
+        .collect()
+}
+
+#[test]
+fn fuzzy_matches_prefer_word_starts() {
+    assert!(fuzzy_score("lw", "Light World") > fuzzy_score("lw", "Lowlands"));
+    assert!(fuzzy_score("ark", "Dark World") > fuzzy_score("ark", "Kakariko"));
+    assert_eq!(fuzzy_score("xyz", "Light World"), None);
+    assert_eq!(fuzzy_score("", "Light World"), Some(0));
+}
+
+#[test]
+fn areas_are_searched_and_picked_with_the_keyboard() {
+    let mut project = TestProject::new("area-picker");
+    for name in ["Lowlands", "Light World", "Death Mountain"] {
+        project.send(Message::AddArea {
+            name: name.to_string(),
+            size: (1, 1),
+        });
+    }
+    project.send(Message::SelectArea(
+        AreaPosition::Main,
+        "Example".to_string(),
+    ));
+    project.save();
+
+    project.send(Message::AreaPickerDialogue(AreaPosition::Main));
+    let entries = &picker(&project).entries;
+    assert_eq!(entries.len(), 4);
+    assert!(entries.iter().all(|e| e.size.is_some()));
+    assert_eq!(picker(&project).selected_entry().unwrap().name, "Example");
+
+    project.send(Message::SetAreaPickerQuery("LW".to_string()));
+    assert_eq!(match_names(&project), vec!["Light World", "Lowlands"]);
+    assert_eq!(picker(&project).selected, 0);
+    project.send(arrow_down());
+    project.send(arrow_down());
+    assert_eq!(picker(&project).selected_entry().unwrap().name, "Lowlands");
+
+    project.send(Message::SetAreaPickerQuery("zz".to_string()));
+    assert!(match_names(&project).is_empty());
+    project.send(Message::PickSelectedArea);
+    assert!(project.state.dialogue.is_some());
+
+    project.send(Message::PickArea("Lowlands".to_string()));
+    assert!(project.state.dialogue.is_none());
+}
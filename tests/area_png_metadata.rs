@@ -0,0 +1,46 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::{
+    message::Message,
+    persist::{rebuild_area_pngs, AreaPngMetadata, RebuildScope},
+};
+
+#[test]
+fn area_png_metadata_identifies_area() {
+    let mut project = TestProject::new("png-metadata");
+    let png_path = project.area_path("Example", "Base").with_extension("png");
+    let metadata = AreaPngMetadata::read(&png_path).unwrap().unwrap();
+    assert_eq!(metadata.area, "Example");
+    assert_eq!(metadata.theme, "Base");
+    assert_eq!(metadata.project, "Project");
+    assert_eq!(metadata.editor_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(metadata.palette_ids, vec![project.state.palettes[0].id]);
+
+    // Names outside Latin-1 are kept as they are:
+    project.send(Message::AddArea {
+        name: "Höhle 洞窟".to_string(),
+        size: (1, 1),
+    });
+    project.save();
+    let path = project
+        .area_path("Höhle 洞窟", "Base")
+        .with_extension("png");
+    let metadata = AreaPngMetadata::read(&path).unwrap().unwrap();
+    assert_eq!(metadata.area, "Höhle 洞窟");
+
+    // A PNG without the metadata (e.g. from another program) is rebuilt as stale:
+    let file = std::fs::File::create(&png_path).unwrap();
+    let mut encoder = png::Encoder::new(file, 1, 1);
+    encoder.set_color(png::ColorType::Rgb);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&[0, 0, 0]).unwrap();
+    drop(writer);
+    assert_eq!(AreaPngMetadata::read(&png_path).unwrap(), None);
+    let stale = RebuildScope {
+        only_stale: true,
+        ..RebuildScope::default()
+    };
+    assert_eq!(rebuild_area_pngs(&mut project.state, &stale).unwrap(), 1);
+    assert!(AreaPngMetadata::read(&png_path).unwrap().is_some());
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::{
+    area_properties::{
+        read_properties, write_properties, AreaMusic, AreaProperties, AreaPropertyEdit,
+    },
+    import::{Constants, Rom, SnesAddr},
+    message::Message,
+    state::AreaId,
+};
+
+#[test]
+fn properties_are_read_from_and_written_to_the_rom() {
+    let mut rom = Rom::new(vec![0; 0x100000]);
+    // Marker of the US ROM format:
+    rom.write_u16(SnesAddr(0x00E792).into(), 0xCA85).unwrap();
+    let constants = Constants::auto(&rom).unwrap();
+    // Map 0x1A: rain with the Light World theme at the start, and the Light World theme later:
+    rom.write_u8(SnesAddr(0x02C303 + 0x1A).into(), 0x32)
+        .unwrap();
+    for stage in 1..4 {
+        rom.write_u8(SnesAddr(0x02C303 + stage * 0x40 + 0x1A).into(), 0x02)
+            .unwrap();
+    }
+    rom.write_u8(SnesAddr(0x00FD1C + 0x1A).into(), 7).unwrap();
+
+    let properties = read_properties(&rom, &constants, 0x1A).unwrap();
+    let later = AreaMusic {
+        track: 2,
+        ambient: 0,
+    };
+    let start = AreaMusic {
+        track: 2,
+        ambient: 3,
+    };
+    assert_eq!(properties.music, vec![start, later, later, later]);
+    assert_eq!(properties.palette_set, Some(7));
+
+    // The Dark World has a single stage:
+    let dark = AreaProperties {
+        music: vec![AreaMusic {
+            track: 9,
+            ambient: 1,
+        }],
+        palette_set: Some(4),
+        flags: 0,
+    };
+    write_properties(&mut rom, &constants, 0x5A, &dark).unwrap();
+    assert_eq!(rom.read_u8(SnesAddr(0x02C403 + 0x1A).into()).unwrap(), 0x19);
+    assert_eq!(read_properties(&rom, &constants, 0x5A).unwrap(), dark);
+}
+
+#[test]
+fn properties_are_edited_and_undone() {
+    let mut project = TestProject::new("area-properties");
+    let area_id = AreaId {
+        area: "Example".to_string(),
+        theme: "Base".to_string(),
+    };
+    project
+        .state
+        .areas
+        .get_mut(&area_id)
+        .unwrap()
+        .vanilla_map_id = Some(0x40);
+    // (The panel's edits are applied to the area's current properties.)
+    let properties = project
+        .state
+        .main_area()
+        .properties
+        .edited(Some(0x40), AreaPropertyEdit::MusicTrack(0, 9))
+        .edited(Some(0x40), AreaPropertyEdit::Flags(5));
+    project.send(Message::SetAreaProperties {
+        area_id: area_id.clone(),
+        properties,
+    });
+    project.save();
+    let area = project.saved_area("Example", "Base");
+    assert_eq!(
+        area.properties,
+        AreaProperties {
+            music: vec![AreaMusic {
+                track: 9,
+                ambient: 0
+            }],
+            palette_set: None,
+            flags: 5,
+        }
+    );
+
+    project.undo();
+    project.save();
+    assert_eq!(project.saved_area("Example", "Base").properties.flags, 0);
+    assert!(project.state.main_area().properties.is_empty());
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::{
+    area_raster::{RasterHighlights, ScreenRasterCache, ScreenRasterizer},
+    state::{EditorState, TileIdx},
+};
+
+fn screen_key(state: &EditorState, highlights: RasterHighlights) -> u64 {
+    ScreenRasterizer {
+        area: state.main_area(),
+        palettes: &state.palettes,
+        palettes_id_idx_map: &state.palettes_id_idx_map,
+        highlights,
+    }
+    .key(0)
+}
+
+// A project whose first screen uses only tile 0 of the first palette, which has another tile.
+fn raster_project(name: &str) -> TestProject {
+    let mut project = TestProject::new(name);
+    let palette = &mut project.state.palettes[0];
+    if palette.tiles.len() < 2 {
+        let tile = palette.tiles[0];
+        palette.tiles.push(tile);
+    }
+    project
+}
+
+#[test]
+fn screen_key_tracks_used_graphics() {
+    let mut project = raster_project("raster-key");
+    let key = screen_key(&project.state, RasterHighlights::default());
+
+    // Unused tiles don't matter:
+    project.state.palettes[0].tiles[1].pixels[0][0] ^= 1;
+    assert_eq!(screen_key(&project.state, RasterHighlights::default()), key);
+
+    // Neither does the selection, unless it's being identified:
+    let selection = RasterHighlights {
+        tile_idx: Some(1 as TileIdx),
+        ..RasterHighlights::default()
+    };
+    assert_eq!(screen_key(&project.state, selection), key);
+    let identified = RasterHighlights {
+        identify_tile: true,
+        ..selection
+    };
+    assert_ne!(screen_key(&project.state, identified), key);
+
+    project.state.palettes[0].colors[3][0] ^= 1;
+    let recolored = screen_key(&project.state, RasterHighlights::default());
+    assert_ne!(recolored, key);
+    project.state.palettes[0].tiles[0].pixels[0][0] ^= 1;
+    assert_ne!(
+        screen_key(&project.state, RasterHighlights::default()),
+        recolored
+    );
+}
+
+#[test]
+fn cache_reuses_unchanged_screens() {
+    let mut project = raster_project("raster-cache");
+    let mut cache = ScreenRasterCache::default();
+    let state = &project.state;
+    let rasterizer = ScreenRasterizer {
+        area: state.main_area(),
+        palettes: &state.palettes,
+        palettes_id_idx_map: &state.palettes_id_idx_map,
+        highlights: RasterHighlights::default(),
+    };
+    let first = cache.get(&rasterizer, 0);
+    let second = cache.get(&rasterizer, 1);
+    assert_eq!(cache.get(&rasterizer, 0).id(), first.id());
+    assert_eq!(cache.len(), 2);
+
+    // Only the screen that was brushed on is re-rasterized:
+    project.state.main_area_mut().screens[0].tiles[0][0] = 1;
+    let state = &project.state;
+    let rasterizer = ScreenRasterizer {
+        area: state.main_area(),
+        palettes: &state.palettes,
+        palettes_id_idx_map: &state.palettes_id_idx_map,
+        highlights: RasterHighlights::default(),
+    };
+    assert_ne!(cache.get(&rasterizer, 0).id(), first.id());
+    assert_eq!(cache.get(&rasterizer, 1).id(), second.id());
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use common::{shape, TestProject};
+use iced::Point;
+use z3_overworld_editor::area_shapes::AreaShape;
+
+#[test]
+fn rectangle_repeats_brush_and_undoes() {
+    let mut project = TestProject::new("rectangle-tool");
+    project.send(shape(
+        AreaShape::Rectangle {
+            start: Point::new(12, 8),
+            end: Point::new(10, 5),
+        },
+        vec![vec![1, 2]],
+    ));
+    project.save();
+    let area = project.saved_area("Example", "Base");
+    assert_eq!(area.get_tile(10, 5).unwrap(), 1);
+    assert_eq!(area.get_tile(11, 5).unwrap(), 2);
+    assert_eq!(area.get_tile(12, 8).unwrap(), 1);
+    assert_eq!(area.get_tile(13, 8).unwrap(), 0);
+    assert_eq!(area.get_tile(10, 9).unwrap(), 0);
+
+    project.undo();
+    project.save();
+    let area = project.saved_area("Example", "Base");
+    assert_eq!(area.get_tile(10, 5).unwrap(), 0);
+    assert_eq!(area.get_tile(12, 8).unwrap(), 0);
+}
+
+#[test]
+fn line_stamps_brush_along_line() {
+    let mut project = TestProject::new("line-tool");
+    project.send(shape(
+        AreaShape::Line {
+            start: Point::new(0, 0),
+            end: Point::new(3, 3),
+        },
+        vec![vec![7]],
+    ));
+    project.save();
+    let area = project.saved_area("Example", "Base");
+    for i in 0..4 {
+        assert_eq!(area.get_tile(i, i).unwrap(), 7);
+    }
+    assert_eq!(area.get_tile(1, 0).unwrap(), 0);
+}
+
+#[test]
+fn fill_stops_at_different_tiles() {
+    let mut project = TestProject::new("fill-tool");
+    // Wall off the top-left corner:
+    project.send(shape(
+        AreaShape::Line {
+            start: Point::new(0, 4),
+            end: Point::new(4, 0),
+        },
+        vec![vec![3]],
+    ));
+    project.send(shape(AreaShape::Fill(Point::new(0, 0)), vec![vec![8]]));
+    project.save();
+    let area = project.saved_area("Example", "Base");
+    assert_eq!(area.get_tile(0, 0).unwrap(), 8);
+    assert_eq!(area.get_tile(2, 1).unwrap(), 8);
+    assert_eq!(area.get_tile(2, 2).unwrap(), 3);
+    assert_eq!(area.get_tile(3, 3).unwrap(), 0);
+    assert_eq!(area.get_tile(63, 63).unwrap(), 0);
+
+    project.undo();
+    project.save();
+    let area = project.saved_area("Example", "Base");
+    assert_eq!(area.get_tile(0, 0).unwrap(), 0);
+    assert_eq!(area.get_tile(2, 2).unwrap(), 3);
+}
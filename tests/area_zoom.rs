@@ -0,0 +1,56 @@
+mod common;
+
+use common::TestProject;
+use iced::Vector;
+use z3_overworld_editor::{message::Message, state::AreaPosition};
+
+fn zoom(position: AreaPosition, steps: f32, anchor: Vector) -> Message {
+    Message::ZoomArea {
+        position,
+        steps,
+        anchor,
+    }
+}
+
+#[test]
+fn wheel_zoom_keeps_the_point_under_the_cursor() {
+    let mut project = TestProject::new("area-zoom");
+    project.send(Message::SetPixelSize(2.0));
+    project.send(Message::AreaScrolled {
+        position: AreaPosition::Main,
+        offset: Vector::new(100.0, 40.0),
+    });
+
+    // The point under the cursor is 300 pixels into the area, i.e. 200 into the view:
+    project.send(zoom(AreaPosition::Main, 1.0, Vector::new(300.0, 140.0)));
+    let pixel_size = project.state.area_pixel_size(AreaPosition::Main);
+    assert!(pixel_size > 2.0 && pixel_size < 3.0);
+    let ratio = pixel_size / 2.0;
+    let scroll = project.state.main_area_scroll;
+    assert!((scroll.x - (300.0 * ratio - 200.0)).abs() < 0.01);
+    assert!((scroll.y - (140.0 * ratio - 100.0)).abs() < 0.01);
+
+    // Each view has its own zoom level, until the global one changes:
+    assert_eq!(project.state.area_pixel_size(AreaPosition::Side), 2.0);
+    project.send(zoom(AreaPosition::Side, -100.0, Vector::ZERO));
+    assert_eq!(project.state.area_pixel_size(AreaPosition::Side), 1.0);
+    project.send(Message::SetPixelSize(4.0));
+    assert_eq!(project.state.area_pixel_size(AreaPosition::Main), 4.0);
+    assert_eq!(project.state.area_pixel_size(AreaPosition::Side), 4.0);
+}
+
+#[test]
+fn linked_views_follow_the_same_part_of_the_area() {
+    let mut project = TestProject::new("area-zoom-linked");
+    project.send(Message::SetPixelSize(2.0));
+    project.send(zoom(AreaPosition::Side, 100.0, Vector::ZERO));
+    let side_pixel_size = project.state.area_pixel_size(AreaPosition::Side);
+    assert_eq!(side_pixel_size, 8.0);
+
+    project.send(Message::ToggleLinkedScroll);
+    project.send(Message::AreaScrolled {
+        position: AreaPosition::Main,
+        offset: Vector::new(100.0, 50.0),
+    });
+    assert_eq!(project.state.side_area_scroll, Vector::new(400.0, 200.0));
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use common::TestProject;
+use iced::{Point, Size, Vector};
+use z3_overworld_editor::{
+    auto_pan::{edge_velocity, panned_scroll, AutoPan, PanAction},
+    message::{Message, SelectionSource},
+    state::AreaPosition,
+};
+
+#[test]
+fn velocity_grows_towards_the_edges() {
+    let viewport = Size::new(400.0, 300.0);
+    assert_eq!(
+        edge_velocity(Point::new(200.0, 150.0), viewport),
+        Vector::ZERO
+    );
+    let near = edge_velocity(Point::new(380.0, 150.0), viewport);
+    let past = edge_velocity(Point::new(450.0, 150.0), viewport);
+    assert!(near.x > 0.0 && near.x < past.x);
+    assert_eq!(near.y, 0.0);
+    let corner = edge_velocity(Point::new(-10.0, -10.0), viewport);
+    assert!(corner.x < 0.0 && corner.y < 0.0);
+
+    let content = Size::new(1000.0, 1000.0);
+    assert_eq!(
+        panned_scroll(
+            Vector::new(590.0, 5.0),
+            Vector::new(24.0, -24.0),
+            content,
+            viewport
+        ),
+        Vector::new(600.0, 0.0)
+    );
+}
+
+#[test]
+fn selection_pans_the_view_until_it_ends() {
+    let mut project = TestProject::new("auto-pan");
+    project.state.global_config.pixel_size = 2.0;
+    project.send(Message::StartTileSelection(
+        Point::new(1, 1),
+        SelectionSource::Area(AreaPosition::Main),
+    ));
+    project.send(Message::SetAutoPan(Some(AutoPan {
+        position: AreaPosition::Main,
+        action: PanAction::Select,
+        velocity: Vector::new(24.0, 0.0),
+        cursor: Point::new(390.0, 100.0),
+        viewport: Size::new(400.0, 300.0),
+    })));
+    project.send(Message::AutoPanTick);
+    project.send(Message::AutoPanTick);
+    assert_eq!(project.state.main_area_scroll, Vector::new(48.0, 0.0));
+
+    project.send(Message::EndTileSelection(Point::new(60, 10)));
+    assert!(project.state.auto_pan.is_none());
+    project.send(Message::AutoPanTick);
+    assert_eq!(project.state.main_area_scroll, Vector::new(48.0, 0.0));
+}
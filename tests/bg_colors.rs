@@ -0,0 +1,76 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::{
+    message::Message,
+    state::{AreaId, Dialogue},
+};
+
+fn area_id(area: &str, theme: &str) -> AreaId {
+    AreaId {
+        area: area.to_string(),
+        theme: theme.to_string(),
+    }
+}
+
+fn table(project: &TestProject) -> Vec<Vec<[u8; 3]>> {
+    match &project.state.dialogue {
+        Some(Dialogue::BGColors { colors, .. }) => colors.clone(),
+        _ => panic!("BG color dialogue not open"),
+    }
+}
+
+#[test]
+fn bg_colors_are_set_across_themes() {
+    let mut project = TestProject::new("bg-colors");
+    project.send(Message::AddTheme("Night".to_string()));
+    project.send(Message::AddArea {
+        name: "Other".to_string(),
+        size: (1, 1),
+    });
+    project.send(Message::BGColorsDialogue);
+    let old_table = table(&project);
+    assert_eq!(project.state.area_names, vec!["Example", "Other"]);
+    assert_eq!(project.state.theme_names, vec!["Base", "Night"]);
+
+    project.send(Message::SetBGColors(vec![
+        (area_id("Example", "Base"), [1, 2, 3]),
+        (area_id("Example", "Night"), [4, 5, 6]),
+        (area_id("Other", "Base"), [7, 8, 9]),
+    ]));
+    let new_table = table(&project);
+    assert_eq!(new_table[0], vec![[1, 2, 3], [4, 5, 6]]);
+    assert_eq!(new_table[1], vec![[7, 8, 9], old_table[1][1]]);
+    project.save();
+    assert_eq!(project.saved_area("Example", "Base").bg_color, [1, 2, 3]);
+    assert_eq!(project.saved_area("Example", "Night").bg_color, [4, 5, 6]);
+    assert_eq!(project.saved_area("Other", "Base").bg_color, [7, 8, 9]);
+
+    project.undo();
+    assert_eq!(table(&project), old_table);
+    project.save();
+    assert_eq!(
+        project.saved_area("Example", "Base").bg_color,
+        old_table[0][0]
+    );
+    assert_eq!(
+        project.saved_area("Other", "Base").bg_color,
+        old_table[1][0]
+    );
+}
+
+#[test]
+fn bg_color_editor_selects_theme_and_color() {
+    let mut project = TestProject::new("bg-colors-select");
+    project.send(Message::AddTheme("Night".to_string()));
+    project.send(Message::BGColorsDialogue);
+    project.send(Message::SelectBGColor {
+        theme: "Base".to_string(),
+        color: [10, 11, 12],
+    });
+    project.send(Message::SetBGColorsColor([10, 11, 20]));
+    let Some(Dialogue::BGColors { theme, color, .. }) = &project.state.dialogue else {
+        panic!("BG color dialogue not open");
+    };
+    assert_eq!((theme.as_str(), *color), ("Base", [10, 11, 20]));
+}
@@ -0,0 +1,29 @@
+mod common;
+
+use common::TestProject;
+use iced::Point;
+use z3_overworld_editor::message::Message;
+
+#[test]
+fn block_pixel_stroke_is_one_edit() {
+    let mut project = TestProject::new("block-pixel-stroke");
+    // A stroke across tiles 1 and 2, setting one pixel twice:
+    project.send(Message::SetTilePixels {
+        palette_id: 0,
+        pixels: vec![
+            (1, Point::new(7, 3), 4),
+            (2, Point::new(0, 3), 4),
+            (2, Point::new(0, 3), 6),
+        ],
+    });
+    project.save();
+    let pal = project.saved_palette("Default");
+    assert_eq!(pal.tiles[1].pixels[3][7], 4);
+    assert_eq!(pal.tiles[2].pixels[3][0], 6);
+
+    project.undo();
+    project.save();
+    let pal = project.saved_palette("Default");
+    assert_eq!(pal.tiles[1].pixels[3][7], 0);
+    assert_eq!(pal.tiles[2].pixels[3][0], 0);
+}
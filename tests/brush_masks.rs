@@ -0,0 +1,30 @@
+mod common;
+
+use common::{example_area_id, TestProject};
+use iced::Point;
+use z3_overworld_editor::{
+    message::Message,
+    state::{AreaPosition, Flip, TileBlock},
+};
+
+#[test]
+fn masked_brush_skips_cells() {
+    let mut project = TestProject::new("masked-brush");
+    project.send(Message::AreaBrush {
+        position: AreaPosition::Main,
+        area_id: example_area_id(),
+        coords: Point::new(10, 10),
+        selection: TileBlock {
+            size: (2, 1),
+            palettes: vec![vec![0, 0]],
+            tiles: vec![vec![5, 6]],
+            flips: vec![vec![Flip::None, Flip::None]],
+            mask: Some(vec![vec![false, true]]),
+        },
+        palette_only: false,
+    });
+    project.save();
+    let area = project.saved_area("Example", "Base");
+    assert_eq!(area.get_tile(10, 10).unwrap(), 0);
+    assert_eq!(area.get_tile(11, 10).unwrap(), 6);
+}
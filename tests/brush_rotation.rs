@@ -0,0 +1,85 @@
+mod common;
+
+use common::{key_press, TestProject};
+use iced::Point;
+use z3_overworld_editor::{
+    brush_rotation::{rotate_pixels, rotated_block},
+    message::Message,
+    state::{Flip, Tile, TileBlock},
+};
+
+fn block(tiles: Vec<Vec<u16>>) -> TileBlock {
+    let (w, h) = (tiles[0].len(), tiles.len());
+    TileBlock {
+        size: (w as u16, h as u16),
+        palettes: vec![vec![0; w]; h],
+        flips: vec![vec![Flip::None; w]; h],
+        tiles,
+        mask: None,
+    }
+}
+
+// Tile 1 has asymmetric graphics, tile 2 has them rotated, and tile 3 has no rotated counterpart.
+fn rotation_project(name: &str) -> TestProject {
+    let mut project = TestProject::new(name);
+    let mut pixels = [[0; 8]; 8];
+    pixels[0][0] = 3;
+    pixels[0][1] = 5;
+    let tile = Tile {
+        pixels,
+        h_flippable: true,
+        v_flippable: true,
+        ..project.state.palettes[0].tiles[1]
+    };
+    let rotated = Tile {
+        pixels: rotate_pixels(pixels),
+        ..tile
+    };
+    let mut other = tile;
+    other.pixels[7][7] = 1;
+    project.send(Message::TilesetBrush {
+        palette_id: 0,
+        coords: Point::new(1, 0),
+        selected_gfx: vec![vec![tile, rotated, other]],
+    });
+    project
+}
+
+#[test]
+fn brush_rotates_using_tiles_with_rotated_graphics() {
+    let project = rotation_project("brush-rotation");
+    let state = &project.state;
+    let mut b = block(vec![vec![1, 0]]);
+    b.mask = Some(vec![vec![true, false]]);
+
+    let r1 = rotated_block(state, &b).unwrap();
+    assert_eq!(r1.size, (1, 2));
+    assert_eq!(r1.tiles, vec![vec![2], vec![0]]);
+    assert_eq!(r1.mask, Some(vec![vec![true], vec![false]]));
+
+    // Half a turn is the tile flipped both ways, and a full turn is the original:
+    let r2 = rotated_block(state, &r1).unwrap();
+    assert_eq!(r2.tiles[0][1], 1);
+    assert_eq!(r2.flips[0][1], Flip::Both);
+    let r4 = rotated_block(state, &rotated_block(state, &r2).unwrap()).unwrap();
+    assert_eq!(r4, b);
+}
+
+#[test]
+fn brush_without_rotated_graphics_is_kept() {
+    let mut project = rotation_project("brush-rotation-missing");
+    project.state.selected_tile_block = block(vec![vec![1, 3]]);
+    let err = rotated_block(&project.state, &project.state.selected_tile_block).unwrap_err();
+    assert!(err.to_string().contains("palette 0 tile 3"));
+
+    project.send(key_press("x"));
+    assert_eq!(project.state.selected_tile_block, block(vec![vec![1, 3]]));
+
+    project.state.selected_tile_block = block(vec![vec![1, 1]]);
+    project.send(key_press("x"));
+    assert_eq!(
+        project.state.selected_tile_block.tiles,
+        vec![vec![2], vec![2]]
+    );
+    assert_eq!(project.state.selected_gfx.len(), 2);
+}
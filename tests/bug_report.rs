@@ -0,0 +1,51 @@
+mod common;
+
+use common::{brush, TestProject};
+use z3_overworld_editor::message::Message;
+
+// Files in a zip archive written by the editor, by name.
+fn read_zip(path: &std::path::Path) -> std::collections::HashMap<String, Vec<u8>> {
+    use std::io::Read;
+    let data = std::fs::read(path).unwrap();
+    let u16_at = |i: usize| u16::from_le_bytes([data[i], data[i + 1]]) as usize;
+    let u32_at = |i: usize| u32::from_le_bytes(data[i..i + 4].try_into().unwrap()) as usize;
+    let mut files = std::collections::HashMap::new();
+    let mut i = 0;
+    while u32_at(i) == 0x04034b50 {
+        let (compressed_size, size) = (u32_at(i + 18), u32_at(i + 22));
+        let name_len = u16_at(i + 26);
+        let start = i + 30 + name_len + u16_at(i + 28);
+        let name = String::from_utf8(data[i + 30..i + 30 + name_len].to_vec()).unwrap();
+        let mut contents = vec![];
+        flate2::read::DeflateDecoder::new(&data[start..start + compressed_size])
+            .read_to_end(&mut contents)
+            .unwrap();
+        assert_eq!(contents.len(), size);
+        assert_eq!(crc32fast::hash(&contents), u32_at(i + 14) as u32);
+        files.insert(name, contents);
+        i = start + compressed_size;
+    }
+    files
+}
+
+#[test]
+fn bug_report_bundles_session_log() {
+    let mut project = TestProject::new("bug-report");
+    project.send(Message::SetRecordSession(true));
+    project.send(brush(0, 0, 3));
+    project.save();
+    let path = project.dir.join("report.zip");
+    project.send(Message::SaveBugReportTo(Some(path.clone())));
+    assert!(project.state.dialogue.is_none());
+
+    let files = read_zip(&path);
+    let log = String::from_utf8(files["session.log"].clone()).unwrap();
+    assert!(log.contains("AreaBrush"));
+    // Timer ticks aren't recorded:
+    assert!(!log.contains("SaveProject"));
+    let config = String::from_utf8(files["config.json"].clone()).unwrap();
+    assert!(config.contains("<project>"));
+    assert!(!config.contains(&project.project_dir().display().to_string()));
+    let area: serde_json::Value = serde_json::from_slice(&files["Example-Base.json"]).unwrap();
+    assert_eq!(area["size"], serde_json::json!([2, 2]));
+}
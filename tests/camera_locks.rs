@@ -0,0 +1,90 @@
+mod common;
+
+use common::{
+    rom::{blank_rom, write_map_stream},
+    TestProject,
+};
+use z3_overworld_editor::{
+    camera_locks::{camera_lock_table, is_camera_locked},
+    compression::compress_with,
+    import::{ImportMode, Importer},
+    message::Message,
+    state::{AreaId, AreaPosition},
+};
+
+#[test]
+fn camera_locks_are_saved_undone_and_exported() {
+    let mut project = TestProject::new("camera-locks");
+    let area_id = project.state.main_area_id.clone();
+    let lock = |screen, locked| Message::SetCameraLocked {
+        position: AreaPosition::Main,
+        area_id: area_id.clone(),
+        screen,
+        locked,
+    };
+    project.send(lock((1, 0), true));
+    project.send(lock((0, 1), true));
+    project.save();
+    let saved = project.saved_area(&area_id.area, &area_id.theme);
+    assert!(is_camera_locked(&saved, (1, 0)));
+    assert!(!is_camera_locked(&saved, (0, 0)));
+    project.undo();
+    assert!(!is_camera_locked(project.state.main_area(), (0, 1)));
+
+    // Areas that aren't from the ROM are left out of the table:
+    let table = camera_lock_table(&project.state, &area_id.theme).unwrap();
+    assert!(table.bits.iter().all(|&b| b == 0));
+    assert_eq!(table.skipped, vec![area_id.area.clone()]);
+
+    project.state.main_area_mut().vanilla_map_id = Some(0x1A);
+    project.send(lock((1, 1), true));
+    let table = camera_lock_table(&project.state, &area_id.theme).unwrap();
+    assert_eq!(table.bits[0x1A], 0b1010);
+    assert!(table.skipped.is_empty());
+
+    let path = project.dir.join("camera-locks.asm");
+    project.send(Message::ExportCameraLocksTo(Some(path.clone())));
+    let asm = std::fs::read_to_string(&path).unwrap();
+    assert!(asm.contains("CameraLockedScreens:\n"));
+    assert!(asm.contains("    db $00, $00, $00, $00, $00, $00, $00, $00, $00, $00, $0A, $00"));
+}
+
+#[test]
+fn locked_screens_are_updated_by_maps_only_reimport() {
+    let mut project = TestProject::new("camera-locks-reimport");
+    let rom = blank_rom();
+    let path = project.dir.join("base.sfc");
+    std::fs::write(&path, &rom.data).unwrap();
+    Importer::import(&mut project.state, &path, ImportMode::Full).unwrap();
+
+    let area_id = AreaId {
+        area: "00 Light World".to_string(),
+        theme: "Base".to_string(),
+    };
+    project.state.load_area(&area_id).unwrap();
+    project.send(Message::SetCameraLocked {
+        position: AreaPosition::Main,
+        area_id: area_id.clone(),
+        screen: (1, 0),
+        locked: true,
+    });
+    project.save();
+
+    // In the updated ROM, map 00 uses 32x32 tile 1, made of a 16x16 tile with priority:
+    let mut rom = rom;
+    rom.data[0x78008..0x78010].copy_from_slice(&[0, 0x20, 0, 0x20, 0, 0x20, 0, 0x20]);
+    for quadrant in [0x18000, 0x1B400, 0x20000, 0x23400] {
+        rom.data[quadrant + 1] = 1;
+    }
+    let map = compress_with(&[1; 256], true);
+    write_map_stream(&mut rom.data, 0x02FB2D, 0, 0xA0000, &map);
+    std::fs::write(&path, &rom.data).unwrap();
+    let old_tiles = project.saved_area(&area_id.area, &area_id.theme).screens[0].tiles;
+    let report = Importer::import(&mut project.state, &path, ImportMode::MapsOnly).unwrap();
+    assert_eq!(report.updated, vec![area_id.area.clone()]);
+    assert!(report.kept.is_empty() && report.conflicts.is_empty());
+
+    let saved = project.saved_area(&area_id.area, &area_id.theme);
+    assert!(saved.screens[0].tiles != old_tiles);
+    assert!(is_camera_locked(&saved, (1, 0)));
+}
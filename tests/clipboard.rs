@@ -0,0 +1,57 @@
+mod common;
+
+use common::TestProject;
+use iced::Point;
+use z3_overworld_editor::{
+    clipboard::ClipboardTiles,
+    message::{Message, SelectionSource},
+    state::{Dialogue, Tool},
+};
+
+#[test]
+fn copied_tiles_paste_into_another_project() {
+    let mut source = TestProject::new("clipboard-source");
+    source.state.palettes[0].tiles[5].pixels[0][0] = 1;
+    source.send_all([
+        Message::StartTileSelection(Point::new(4, 0), SelectionSource::Tileset),
+        Message::EndTileSelection(Point::new(5, 0)),
+    ]);
+    let text = ClipboardTiles::from_selection(&source.state)
+        .unwrap()
+        .to_text()
+        .unwrap();
+    let source_tiles = source.state.palettes[0].tiles.clone();
+
+    // The colors match the default palette here, so the tiles are merged into it:
+    let mut project = TestProject::new("clipboard-target");
+    let tiles = ClipboardTiles::parse(&text).unwrap();
+    project.send(Message::Batch(
+        tiles.paste_messages(&project.state).unwrap(),
+    ));
+    assert_eq!(project.state.palettes.len(), 1);
+    let pal = &project.state.palettes[0];
+    let brush = &project.state.selected_tile_block;
+    assert_eq!(brush.palettes, vec![vec![pal.id, pal.id]]);
+    assert_eq!(pal.tiles[brush.tiles[0][0] as usize], source_tiles[4]);
+    assert_eq!(pal.tiles[brush.tiles[0][1] as usize], source_tiles[5]);
+    assert_eq!(project.state.tool, Tool::Brush);
+    project.undo();
+    assert_eq!(project.state.palettes[0].tiles.len(), source_tiles.len());
+
+    // With colors that aren't in the project, the tiles bring their own palette:
+    project.send(Message::BrushColor {
+        palette_id: 0,
+        color_idx: 1,
+        color: [0, 31, 0],
+    });
+    project.send(Message::Batch(
+        tiles.paste_messages(&project.state).unwrap(),
+    ));
+    assert_eq!(project.state.palettes.len(), 2);
+    assert_eq!(project.state.palettes[1].name, "Project");
+    assert_eq!(project.state.palettes[1].tiles[1], source_tiles[5]);
+
+    // Anything else on the clipboard is rejected:
+    project.send(Message::PastedTiles(Some("hello".to_string())));
+    assert!(matches!(project.state.dialogue, Some(Dialogue::Error(_))));
+}
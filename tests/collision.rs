@@ -0,0 +1,30 @@
+mod common;
+
+use common::{key_press, TestProject};
+use z3_overworld_editor::{helpers::collision_color, message::Message, state::Tool};
+
+#[test]
+fn collision_brush_paints_and_undoes() {
+    let mut project = TestProject::new("collision-brush");
+    project.send(key_press("c"));
+    assert_eq!(project.state.tool, Tool::Collision);
+    assert!(project.state.show_collision);
+    project.send(key_press("o"));
+    assert!(!project.state.show_collision);
+
+    // Picking a collision type turns the overlay back on:
+    project.send(Message::SetCollisionBrush(9));
+    assert_eq!(project.state.collision_brush, 9);
+    assert!(project.state.show_collision);
+    project.send(Message::SetTileCollision {
+        palette_id: 0,
+        tile_idx: 3,
+        collision: 9,
+    });
+    assert_eq!(project.state.palettes[0].tiles[3].collision, 9);
+    project.undo();
+    assert_eq!(project.state.palettes[0].tiles[3].collision, 0);
+
+    assert_ne!(collision_color(1), collision_color(2));
+    assert_ne!(collision_color(8), collision_color(9));
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::message::Message;
+
+#[test]
+fn color_ramp_updates_subscribed_palettes() {
+    let mut project = TestProject::new("color-ramps");
+    project.send_all([
+        Message::AddPalette {
+            name: "Extra".to_string(),
+            id: 7,
+        },
+        Message::AddColorRamp {
+            palette_id: 0,
+            start: 1,
+            len: 4,
+        },
+    ]);
+    let extra_colors = project.state.palettes[1].colors;
+    project.send_all([
+        Message::SubscribeColorRamp {
+            ramp_idx: 0,
+            palette_id: 7,
+            start: 8,
+        },
+        Message::SetRampColor {
+            ramp_idx: 0,
+            shade: 2,
+            color: [1, 20, 3],
+        },
+    ]);
+    project.save();
+    assert_eq!(project.saved_palette("Default").colors[3], [1, 20, 3]);
+    assert_eq!(project.saved_palette("Extra").colors[10], [1, 20, 3]);
+    assert_eq!(
+        project.saved_palette("Extra").colors[8..12],
+        project.saved_palette("Default").colors[1..5]
+    );
+
+    // Undoing the subscription restores the palette's own colors:
+    project.undo();
+    project.undo();
+    project.save();
+    assert_eq!(
+        project.state.project_metadata.color_ramps[0]
+            .subscribers
+            .len(),
+        1
+    );
+    assert_ne!(project.saved_palette("Default").colors[3], [1, 20, 3]);
+    assert_eq!(project.saved_palette("Extra").colors, extra_colors);
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use common::TestProject;
+use iced::Point;
+use z3_overworld_editor::message::Message;
+
+#[test]
+fn swap_colors_preserves_graphics() {
+    let mut project = TestProject::new("swap-colors");
+    project.send_all([
+        Message::BrushColor {
+            palette_id: 0,
+            color_idx: 3,
+            color: [31, 0, 0],
+        },
+        Message::BrushPixel {
+            palette_id: 0,
+            tile_idx: 1,
+            coords: Point::new(2, 6),
+            color_idx: 3,
+        },
+        Message::SwapColors {
+            palette_id: 0,
+            color_idx_1: 3,
+            color_idx_2: 5,
+        },
+    ]);
+    project.save();
+    let pal = project.saved_palette("Default");
+    assert_eq!(pal.colors[5], [31, 0, 0]);
+    assert_eq!(pal.colors[3], [0, 0, 0]);
+    assert_eq!(pal.tiles[1].pixels[6][2], 5);
+
+    project.undo();
+    project.save();
+    let pal = project.saved_palette("Default");
+    assert_eq!(pal.colors[3], [31, 0, 0]);
+    assert_eq!(pal.tiles[1].pixels[6][2], 3);
+}
@@ -0,0 +1,208 @@
+// Harness for driving the editor's update loop headlessly (without the iced runtime),
+// against a project in a temporary directory.
+#![allow(dead_code)]
+
+pub mod rom;
+
+use std::path::{Path, PathBuf};
+
+use iced::{
+    keyboard::{self, key, Key, Modifiers},
+    Point,
+};
+use serde::de::DeserializeOwned;
+use z3_overworld_editor::{
+    area_shapes::AreaShape,
+    message::Message,
+    persist::read_project_files,
+    state::{new_editor_state, Area, AreaId, AreaPosition, EditorState, Flip, Palette, TileBlock},
+    update::update,
+};
+
+// Open a project as the editor does, reading its files as the background load would (since tasks
+// aren't run in tests).
+pub fn open_project(state: &mut EditorState, project_dir: PathBuf) {
+    let _ = update(state, Message::ProjectOpened(Some(project_dir.clone())));
+    let files = read_project_files(&project_dir, |_, _| Ok(()))
+        .map(Box::new)
+        .map_err(|e| format!("{:#}", e));
+    let _ = update(
+        state,
+        Message::ProjectLoaded {
+            dir: project_dir,
+            files,
+        },
+    );
+}
+
+// The area that a new project opens with, in the given theme.
+pub fn example_area_in(theme: &str) -> AreaId {
+    AreaId {
+        area: "Example".to_string(),
+        theme: theme.to_string(),
+    }
+}
+
+pub fn example_area_id() -> AreaId {
+    example_area_in("Base")
+}
+
+// Brush a row of tiles from palette 0, all with the same flip, onto an area in the main view.
+pub fn brush_tiles(area_id: AreaId, x: u16, y: u16, tiles: Vec<u16>, flip: Flip) -> Message {
+    let w = tiles.len();
+    Message::AreaBrush {
+        position: AreaPosition::Main,
+        area_id,
+        coords: Point::new(x, y),
+        selection: TileBlock {
+            size: (w as u16, 1),
+            palettes: vec![vec![0; w]],
+            tiles: vec![tiles],
+            flips: vec![vec![flip; w]],
+            mask: None,
+        },
+        palette_only: false,
+    }
+}
+
+// Brush a single unflipped tile onto the example area.
+pub fn brush(x: u16, y: u16, tile: u16) -> Message {
+    brush_tiles(example_area_id(), x, y, vec![tile], Flip::None)
+}
+
+// Draw a shape with a block of tiles from palette 0, unflipped, onto the example area.
+pub fn shape(shape: AreaShape, tiles: Vec<Vec<u16>>) -> Message {
+    let (w, h) = (tiles[0].len(), tiles.len());
+    Message::AreaShape {
+        position: AreaPosition::Main,
+        area_id: example_area_id(),
+        shape,
+        selection: TileBlock {
+            size: (w as u16, h as u16),
+            palettes: vec![vec![0; w]; h],
+            flips: vec![vec![Flip::None; w]; h],
+            tiles,
+            mask: None,
+        },
+        palette_only: false,
+    }
+}
+
+pub struct TestProject {
+    pub dir: PathBuf,
+    pub state: EditorState,
+}
+
+impl TestProject {
+    // Create an empty project (with the default "Example" area and "Default" palette)
+    // in a fresh temporary directory, and open it.
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("z3oe-test-{}-{}", name, std::process::id()));
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+        let project_dir = dir.join("Project");
+        std::fs::create_dir_all(&project_dir).unwrap();
+        let mut state = new_editor_state(dir.join("config.json"));
+        open_project(&mut state, project_dir);
+        assert!(
+            state.global_config.project_dir.is_some(),
+            "project failed to open"
+        );
+
+        // The tests check the files written by the editor itself, so the watcher
+        // would only produce spurious reload prompts.
+        state.watcher = None;
+
+        let mut project = TestProject { dir, state };
+        project.save();
+        project
+    }
+
+    pub fn project_dir(&self) -> PathBuf {
+        self.dir.join("Project")
+    }
+
+    // Process a message through the update loop. Any follow-up task is discarded,
+    // since there is no runtime to execute it; tests should send the resulting
+    // messages explicitly instead.
+    pub fn send(&mut self, message: Message) {
+        let _ = update(&mut self.state, message);
+    }
+
+    pub fn send_all(&mut self, messages: impl IntoIterator<Item = Message>) {
+        for message in messages {
+            self.send(message);
+        }
+    }
+
+    pub fn save(&mut self) {
+        self.send(Message::SaveProject);
+    }
+
+    pub fn undo(&mut self) {
+        self.send(ctrl_z(Modifiers::CTRL));
+    }
+
+    pub fn redo(&mut self) {
+        self.send(ctrl_z(Modifiers::CTRL | Modifiers::SHIFT));
+    }
+
+    pub fn area_path(&self, area: &str, theme: &str) -> PathBuf {
+        self.project_dir()
+            .join("Areas")
+            .join(area)
+            .join(format!("{}.json", theme))
+    }
+
+    pub fn palette_path(&self, name: &str) -> PathBuf {
+        self.project_dir()
+            .join("Palettes")
+            .join(format!("{}.json", name))
+    }
+
+    // Read an area as it was last saved to disk.
+    pub fn saved_area(&self, area: &str, theme: &str) -> Area {
+        read_json(&self.area_path(area, theme))
+    }
+
+    // Read a palette as it was last saved to disk.
+    pub fn saved_palette(&self, name: &str) -> Palette {
+        read_json(&self.palette_path(name))
+    }
+}
+
+impl Drop for TestProject {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+// A key press without modifiers, for the character `c`.
+pub fn key_press(c: &str) -> Message {
+    Message::Event(iced::Event::Keyboard(keyboard::Event::KeyPressed {
+        key: Key::Character(c.into()),
+        modified_key: Key::Character(c.into()),
+        physical_key: key::Physical::Unidentified(key::NativeCode::Unidentified),
+        location: keyboard::Location::Standard,
+        modifiers: Modifiers::empty(),
+        text: None,
+    }))
+}
+
+fn ctrl_z(modifiers: Modifiers) -> Message {
+    Message::Event(iced::Event::Keyboard(keyboard::Event::KeyPressed {
+        key: Key::Character("z".into()),
+        modified_key: Key::Character("z".into()),
+        physical_key: key::Physical::Code(key::Code::KeyZ),
+        location: keyboard::Location::Standard,
+        modifiers,
+        text: None,
+    }))
+}
+
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> T {
+    let data = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("unable to read {}: {}", path.display(), e));
+    serde_json::from_str(&data).unwrap()
+}
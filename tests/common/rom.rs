@@ -0,0 +1,60 @@
+// Synthetic ROMs for tests that import or export, with just the tables that are read.
+use z3_overworld_editor::{
+    compression::compress_with,
+    import::{PcAddr, Rom, SnesAddr},
+};
+
+pub fn write_u16(data: &mut [u8], addr: SnesAddr, x: u16) {
+    let pc = PcAddr::from(addr).0 as usize;
+    data[pc..pc + 2].copy_from_slice(&x.to_le_bytes());
+}
+
+// Point entry `i` of a map data table at the given compressed stream, written at `pc`.
+pub fn write_map_stream(data: &mut [u8], table: u32, i: u32, pc: u32, stream: &[u8]) {
+    let addr = SnesAddr::from(PcAddr(pc)).0;
+    let entry = PcAddr::from(SnesAddr(table + i * 3)).0 as usize;
+    data[entry..entry + 3].copy_from_slice(&addr.to_le_bytes()[..3]);
+    data[pc as usize..pc as usize + stream.len()].copy_from_slice(stream);
+}
+
+// A US ROM with blank graphics, palettes, 16x16 and 32x32 tiles and maps, and no secrets or
+// overlays.
+pub fn blank_rom() -> Rom {
+    let mut data = vec![0; 0x200000];
+    write_u16(&mut data, SnesAddr(0x00E792), 0xCA85);
+
+    // Graphics sheets, all sharing one stream at $108000:
+    let sheet = compress_with(&[0; 0x600], false);
+    data[0x80000..0x80000 + sheet.len()].copy_from_slice(&sheet);
+    write_u16(&mut data, SnesAddr(0x00E790), 0x9000);
+    write_u16(&mut data, SnesAddr(0x00E795), 0x9100);
+    write_u16(&mut data, SnesAddr(0x00E79A), 0x9200);
+    for i in 0..113 {
+        data[0x1000 + i] = 0x10;
+        data[0x1100 + i] = 0x80;
+        data[0x1200 + i] = 0x00;
+    }
+
+    // Map data, with a stream of its own for each table entry (so that there's room to repack):
+    let map = compress_with(&[0; 256], true);
+    let mut pc = 0x90000;
+    for table in [0x02F94D, 0x02FB2D] {
+        for i in 0..0x90 {
+            write_map_stream(&mut data, table, i, pc, &map);
+            pc += map.len() as u32;
+        }
+    }
+
+    // Empty lists of secrets, at $1B8000:
+    write_u16(&mut data, SnesAddr(0x1B8000), 0xFFFF);
+    for i in 0..0x80 {
+        write_u16(&mut data, SnesAddr(0x1BC2F9 + i * 2), 0x8000);
+    }
+
+    // Overlay routines that draw nothing (RTS), at $0E9000:
+    data[0x71000] = 0x60;
+    for i in 0..0x80 {
+        write_u16(&mut data, SnesAddr(0x0EF664 + i * 2), 0x9000);
+    }
+    Rom { data }
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::TestProject;
+use iced::Point;
+use z3_overworld_editor::{
+    compile_check::check_area,
+    message::Message,
+    state::{AreaId, AreaPosition, Flip, PaletteCategory, TileBlock},
+};
+
+#[test]
+fn counts_unique_blocks() {
+    let mut project = TestProject::new("compile-check");
+    // Two different tiles within one 16x16 block, and an out-of-range tile:
+    project.send(Message::AreaBrush {
+        position: AreaPosition::Main,
+        area_id: AreaId {
+            area: "Example".to_string(),
+            theme: "Base".to_string(),
+        },
+        coords: Point::new(0, 0),
+        selection: TileBlock {
+            size: (3, 1),
+            palettes: vec![vec![0, 0, 0]],
+            tiles: vec![vec![1, 2, 200]],
+            flips: vec![vec![Flip::None, Flip::Horizontal, Flip::None]],
+            mask: None,
+        },
+        palette_only: false,
+    });
+
+    let state = &project.state;
+    let report = check_area(state, state.main_area());
+    // The block at the top-left, and the blank one (the unconvertible tile is
+    // reported as an error rather than counted):
+    assert_eq!(report.tile16_count, 2);
+    assert_eq!(report.tile32_count, 2);
+    assert_eq!(report.palette_count, 1);
+    // All of the default tiles are blank, so they share one character:
+    assert_eq!(report.char_count, 1);
+    assert_eq!(report.error_count, 1);
+    assert!(!report.is_ok());
+}
+
+#[test]
+fn hud_palettes_have_their_own_budget() {
+    let mut project = TestProject::new("compile-check-hud");
+    project.send(Message::SetPaletteCategory {
+        palette_id: 0,
+        category: PaletteCategory::HUD,
+    });
+    let state = &project.state;
+    let report = check_area(state, state.main_area());
+    assert_eq!(report.palette_count, 0);
+    assert_eq!(report.hud_palette_count, 1);
+    assert!(report.is_ok());
+}
@@ -0,0 +1,126 @@
+use z3_overworld_editor::{
+    compression::{compress, compress_with, decompress, decompress_prefix, decompress_with},
+    import::{decompress_at, PcAddr, Rom},
+    map_compression::estimate_area,
+    state::{Area, Flip, Screen},
+};
+
+#[test]
+fn compress_round_trip() {
+    let mut data: Vec<u8> = vec![];
+    data.extend([7; 40]); // byte fill, extended header
+    data.extend([1, 2].repeat(10)); // word fill
+    data.extend(10..30); // increasing fill
+    data.extend([0x12, 0x34, 0x56, 0x78, 0x9A]); // literals
+    data.extend_from_within(40..70); // repeat
+    data.extend((0..2000).map(|i| (i * 37 % 251) as u8));
+    let compressed = compress(&data);
+    assert_eq!(decompress(&compressed).unwrap(), data);
+    assert!(compress(&[0; 256]).len() <= 4);
+}
+
+// Pseudo-random test data mixing the patterns that each command encodes with noise, so that
+// every command (and literal runs between them) gets used.
+fn mixed_data(seed: u32, len: usize) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(2654435761).wrapping_add(1);
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state
+    };
+    let mut data = vec![];
+    while data.len() < len {
+        let run = (next() % 70) as usize + 1;
+        let b = next() as u8;
+        match next() % 5 {
+            0 => data.extend(std::iter::repeat_n(b, run)),
+            1 => data.extend((0..run).map(|k| if k % 2 == 0 { b } else { !b })),
+            2 => data.extend((0..run).map(|k| b.wrapping_add(k as u8))),
+            3 if !data.is_empty() => {
+                let start = next() as usize % data.len();
+                let end = (start + run).min(data.len());
+                data.extend_from_within(start..end);
+            }
+            _ => data.extend((0..run).map(|_| next() as u8)),
+        }
+    }
+    data.truncate(len);
+    data
+}
+
+#[test]
+fn random_data_round_trips() {
+    for seed in 0..200 {
+        let data = mixed_data(seed, (seed as usize * 97) % 3000);
+        for big_endian in [false, true] {
+            let compressed = compress_with(&data, big_endian);
+            assert_eq!(
+                decompress_with(&compressed, big_endian).unwrap(),
+                data,
+                "seed {}",
+                seed
+            );
+        }
+    }
+}
+
+#[test]
+fn rom_data_is_read_with_the_same_codec() {
+    let data = mixed_data(7, 1500);
+    let compressed = compress_with(&data, true);
+    let mut rom = vec![0xAA; 5];
+    rom.extend(&compressed);
+    rom.extend([0x12; 4]);
+    let (out, end) = decompress_at(&Rom::new(rom), PcAddr(5), true).unwrap();
+    assert_eq!(out, data);
+    assert_eq!(end.0 as usize, 5 + compressed.len());
+}
+
+#[test]
+fn decompressed_prefix_reports_its_length() {
+    let first = compress(&[3; 100]);
+    let mut data = first.clone();
+    data.extend(compress(&[1, 2, 3]));
+    let (out, len) = decompress_prefix(&data, false).unwrap();
+    assert_eq!(out, vec![3; 100]);
+    assert_eq!(len, first.len());
+    assert_eq!(decompress(&data[len..]).unwrap(), vec![1, 2, 3]);
+}
+
+#[test]
+fn big_endian_repeat_offsets() {
+    // A repeat from an offset whose two bytes differ, so that the byte order matters:
+    let mut data: Vec<u8> = (0..=255).map(|i| (i * 37 % 251) as u8).collect();
+    data.extend((0..300).map(|i| (i * 13 % 241) as u8));
+    data.extend_from_within(0x102..0x140);
+    let compressed = compress_with(&data, true);
+    assert_eq!(decompress_with(&compressed, true).unwrap(), data);
+    assert_ne!(compressed, compress(&data));
+    assert_ne!(decompress(&compressed).ok(), Some(data));
+}
+
+#[test]
+fn uniform_area_compresses_small() {
+    let mut area = Area {
+        name: "Field".to_string(),
+        size: (2, 2),
+        ..Area::default()
+    };
+    for y in 0..2 {
+        for x in 0..2 {
+            area.screens.push(Screen {
+                position: (x, y),
+                palettes: [[0; 32]; 32],
+                tiles: [[0; 32]; 32],
+                flips: [[Flip::None; 32]; 32],
+                camera_locked: false,
+                overlay: vec![],
+            });
+        }
+    }
+    let estimate = estimate_area(&area, &mut Default::default());
+    assert_eq!(estimate.screen_count, 1);
+    assert!(estimate.compressed_size <= 8);
+    assert!(!estimate.over_budget());
+}
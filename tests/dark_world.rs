@@ -0,0 +1,135 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::{
+    message::Message,
+    state::{AreaId, AreaPosition, Dialogue, PaletteId},
+};
+
+fn area_id(name: &str) -> AreaId {
+    AreaId {
+        area: name.to_string(),
+        theme: "Base".to_string(),
+    }
+}
+
+// Add a light world area for map 05, using palette 1 in its first tile.
+fn add_light_area(project: &mut TestProject) {
+    for id in [1, 2] {
+        project.send(Message::AddPalette {
+            name: format!("Palette {}", id),
+            id,
+        });
+    }
+    project.send(Message::AddArea {
+        name: "Light".to_string(),
+        size: (1, 1),
+    });
+    let area = project.state.areas.get_mut(&area_id("Light")).unwrap();
+    area.vanilla_map_id = Some(0x05);
+    area.screens[0].palettes[0][0] = 1;
+    area.screens[0].tiles[0][0] = 7;
+    area.modified = true;
+    project.save();
+}
+
+fn generate(project: &mut TestProject, mapping: &[(PaletteId, PaletteId)]) {
+    project.send(Message::DarkWorldDialogue);
+    for &(light, dark) in mapping {
+        project.send(Message::SetDarkWorldPalette { light, dark });
+    }
+    let Some(Dialogue::DarkWorld { mapping, .. }) = &project.state.dialogue else {
+        panic!("dark world dialogue not open");
+    };
+    let mapping = mapping.clone();
+    project.send(Message::GenerateDarkWorldArea {
+        light_area: "Light".to_string(),
+        mapping,
+    });
+}
+
+#[test]
+fn dark_world_area_is_created_and_undone() {
+    let mut project = TestProject::new("dark-world-create");
+    add_light_area(&mut project);
+    generate(&mut project, &[(1, 2)]);
+
+    let name = "45 Dark World".to_string();
+    assert!(project.state.area_names.contains(&name));
+    assert_eq!(project.state.main_area_id, area_id(&name));
+    let area = project.state.main_area();
+    assert_eq!(area.vanilla_map_id, Some(0x45));
+    assert_eq!(area.screens[0].palettes[0][0], 2);
+    assert_eq!(area.screens[0].tiles[0][0], 7);
+    let metadata = &project.state.project_metadata;
+    assert_eq!(metadata.area_groups[&name], "Dark World");
+    assert_eq!(metadata.dark_world_palettes, vec![(1, 2)]);
+    project.save();
+    assert!(project.area_path(&name, "Base").exists());
+
+    // The mapping is remembered for the next time:
+    project
+        .state
+        .switch_area(AreaPosition::Main, &area_id("Light"))
+        .unwrap();
+    project.send(Message::DarkWorldDialogue);
+    let Some(Dialogue::DarkWorld {
+        target,
+        exists,
+        mapping,
+    }) = &project.state.dialogue
+    else {
+        panic!("dark world dialogue not open");
+    };
+    assert_eq!(target, &name);
+    assert!(exists);
+    assert_eq!(mapping.get(&1), Some(&2));
+    project.send(Message::HideModal);
+
+    project.undo();
+    assert!(!project.state.area_names.contains(&name));
+    project.save();
+    assert!(!project.area_path(&name, "Base").exists());
+}
+
+#[test]
+fn existing_dark_world_area_is_updated() {
+    let mut project = TestProject::new("dark-world-update");
+    add_light_area(&mut project);
+    project.send(Message::AddArea {
+        name: "Dark".to_string(),
+        size: (1, 1),
+    });
+    let dark = project.state.areas.get_mut(&area_id("Dark")).unwrap();
+    dark.vanilla_map_id = Some(0x45);
+    dark.bg_color = [1, 2, 3];
+    dark.modified = true;
+    project.save();
+
+    project
+        .state
+        .switch_area(AreaPosition::Main, &area_id("Light"))
+        .unwrap();
+    generate(&mut project, &[]);
+    assert_eq!(project.state.main_area_id, area_id("Dark"));
+    let area = project.state.main_area();
+    assert_eq!(area.bg_color, [1, 2, 3]);
+    assert_eq!(area.screens[0].palettes[0][0], 1);
+    assert_eq!(area.screens[0].tiles[0][0], 7);
+    assert!(!project
+        .state
+        .area_names
+        .contains(&"45 Dark World".to_string()));
+
+    project.undo();
+    let area = project.state.area_copy(&area_id("Dark")).unwrap();
+    assert_eq!(area.screens[0].tiles[0][0], 0);
+    assert_eq!(area.bg_color, [1, 2, 3]);
+}
+
+#[test]
+fn dark_world_areas_need_a_light_world_map() {
+    let mut project = TestProject::new("dark-world-invalid");
+    project.send(Message::DarkWorldDialogue);
+    assert!(matches!(project.state.dialogue, Some(Dialogue::Error(_))));
+}
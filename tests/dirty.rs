@@ -0,0 +1,155 @@
+mod common;
+
+use common::{brush, example_area_id, TestProject};
+use z3_overworld_editor::{
+    area_diff::compare_areas,
+    dirty::{area_unexported, area_unsaved, has_unsaved_changes, mark_exported},
+    message::Message,
+    state::{AreaPosition, Dialogue, UnsavedChangesAction},
+};
+
+#[test]
+fn changes_are_tracked_until_saved_and_exported() {
+    let mut project = TestProject::new("dirty-tracking");
+    mark_exported(&mut project.state, &"Base".to_string());
+    project.save();
+    assert!(!has_unsaved_changes(&project.state));
+    assert!(!area_unexported(&project.state, &example_area_id()));
+
+    project.send(brush(3, 4, 5));
+    assert!(area_unsaved(&project.state, &example_area_id()));
+    assert!(has_unsaved_changes(&project.state));
+
+    // Closing asks whether to save the changes:
+    let id = iced::window::Id::unique();
+    project.send(Message::WindowClose(id));
+    assert!(matches!(
+        project.state.dialogue,
+        Some(Dialogue::UnsavedChanges(UnsavedChangesAction::CloseWindow(i))) if i == id
+    ));
+    project.send(Message::HideModal);
+
+    project.save();
+    assert!(!area_unsaved(&project.state, &example_area_id()));
+    assert!(!has_unsaved_changes(&project.state));
+    assert!(area_unexported(&project.state, &example_area_id()));
+    // The unexported areas are kept in the project metadata:
+    let metadata = std::fs::read_to_string(project.project_dir().join("Project.json")).unwrap();
+    assert!(metadata.contains("Example/Base"));
+
+    mark_exported(&mut project.state, &"Base".to_string());
+    assert!(!area_unexported(&project.state, &example_area_id()));
+}
+
+#[test]
+fn discarding_changes_reverts_to_the_saved_project() {
+    let mut project = TestProject::new("dirty-discard");
+    project.send(brush(3, 4, 5));
+    let saved = project.saved_area("Example", "Base");
+    assert_eq!(
+        compare_areas(project.state.main_area(), &saved).tiles,
+        vec![(3, 4)]
+    );
+    project.send(Message::DiscardChangesDialogue);
+    assert!(matches!(
+        project.state.dialogue,
+        Some(Dialogue::DiscardChanges)
+    ));
+    project.send(Message::DiscardChanges);
+    assert!(project.state.dialogue.is_none());
+    assert!(!has_unsaved_changes(&project.state));
+    assert_eq!(project.state.main_area_id, example_area_id());
+    let saved = project.saved_area("Example", "Base");
+    assert!(compare_areas(project.state.main_area(), &saved)
+        .tiles
+        .is_empty());
+
+    // Nothing was written over the saved area:
+    project.save();
+    let saved = project.saved_area("Example", "Base");
+    assert!(compare_areas(project.state.main_area(), &saved)
+        .tiles
+        .is_empty());
+    assert!(project.state.undo_stack.is_empty());
+}
+
+#[test]
+fn manual_save_keeps_changes_to_unloaded_areas_discardable() {
+    let mut project = TestProject::new("dirty-manual-switch");
+    project.send(Message::SetManualSave(true));
+    project.send(Message::AddArea {
+        name: "Other".to_string(),
+        size: (2, 2),
+    });
+    project.send(Message::SelectArea(
+        AreaPosition::Main,
+        "Example".to_string(),
+    ));
+    project.save();
+    let original = project.saved_area("Example", "Base");
+
+    // Switching both areas away from the edited one keeps it loaded, rather than saving it:
+    project.send(brush(3, 4, 5));
+    project.send(Message::SelectArea(AreaPosition::Main, "Other".to_string()));
+    project.send(Message::SelectArea(AreaPosition::Side, "Other".to_string()));
+    assert!(area_unsaved(&project.state, &example_area_id()));
+    let saved = project.saved_area("Example", "Base");
+    assert!(compare_areas(&original, &saved).tiles.is_empty());
+
+    project.send(Message::DiscardChanges);
+    assert!(!has_unsaved_changes(&project.state));
+    assert!(!project.state.areas.contains_key(&example_area_id()));
+    project.save();
+    let saved = project.saved_area("Example", "Base");
+    assert!(compare_areas(&original, &saved).tiles.is_empty());
+}
+
+#[test]
+fn manual_save_writes_changes_to_unloaded_areas_when_saved() {
+    let mut project = TestProject::new("dirty-manual-save");
+    project.send(Message::SetManualSave(true));
+    project.send(Message::AddArea {
+        name: "Other".to_string(),
+        size: (2, 2),
+    });
+    project.send(Message::SelectArea(
+        AreaPosition::Main,
+        "Example".to_string(),
+    ));
+    project.save();
+    let original = project.saved_area("Example", "Base");
+
+    project.send(brush(3, 4, 5));
+    project.send(Message::SelectArea(AreaPosition::Main, "Other".to_string()));
+    project.send(Message::SelectArea(AreaPosition::Side, "Other".to_string()));
+    project.save();
+    assert!(!has_unsaved_changes(&project.state));
+    // Once saved, the area is no longer kept loaded:
+    assert!(!project.state.areas.contains_key(&example_area_id()));
+    let saved = project.saved_area("Example", "Base");
+    assert_eq!(compare_areas(&original, &saved).tiles, vec![(3, 4)]);
+}
+
+#[test]
+fn opening_another_project_asks_about_unsaved_changes() {
+    let mut project = TestProject::new("dirty-open");
+    let original = project.saved_area("Example", "Base");
+    project.send(brush(3, 4, 5));
+
+    let other_dir = project.dir.join("Other");
+    project.send(Message::ProjectOpened(Some(other_dir.clone())));
+    assert!(project.state.project_load.is_none());
+    let Some(Dialogue::UnsavedChanges(action)) = &project.state.dialogue else {
+        panic!("expected the unsaved changes dialogue");
+    };
+    assert!(matches!(action, UnsavedChangesAction::OpenProject(p) if p == &other_dir));
+    let saved = project.saved_area("Example", "Base");
+    assert!(compare_areas(&original, &saved).tiles.is_empty());
+
+    project.send(Message::DiscardAndContinue(action.clone()));
+    assert!(project.state.dialogue.is_none());
+    assert!(project.state.project_load.is_some());
+    assert!(!has_unsaved_changes(&project.state));
+    let saved = project.saved_area("Example", "Base");
+    assert!(compare_areas(&original, &saved).tiles.is_empty());
+}
@@ -0,0 +1,26 @@
+mod common;
+
+use common::{brush, TestProject};
+use z3_overworld_editor::message::Message;
+
+#[test]
+fn duplicate_area_copies_unsaved_edits() {
+    let mut project = TestProject::new("duplicate-area");
+    project.send_all([
+        brush(3, 4, 5),
+        Message::DuplicateArea {
+            old_name: "Example".to_string(),
+            new_name: "Copy".to_string(),
+        },
+    ]);
+    project.save();
+    assert_eq!(project.state.area_names, vec!["Copy", "Example"]);
+    assert_eq!(project.state.main_area_id.area, "Copy");
+    let area = project.saved_area("Copy", "Base");
+    assert_eq!(area.get_tile(3, 4).unwrap(), 5);
+
+    project.undo();
+    project.save();
+    assert_eq!(project.state.area_names, vec!["Example"]);
+    assert!(!project.area_path("Copy", "Base").exists());
+}
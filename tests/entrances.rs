@@ -0,0 +1,86 @@
+mod common;
+
+use common::TestProject;
+use iced::Point;
+use z3_overworld_editor::{
+    entrances::{entrance_at, Entrance, EntranceKind},
+    message::Message,
+    state::AreaPosition,
+};
+
+fn entrance(kind: EntranceKind, position: (u16, u16), target: u16) -> Entrance {
+    Entrance {
+        kind,
+        position,
+        target,
+    }
+}
+
+#[test]
+fn markers_cover_a_16x16_tile() {
+    let entrances = [
+        entrance(EntranceKind::Entrance, (4, 6), 0x12),
+        entrance(EntranceKind::Exit, (5, 7), 0x104),
+    ];
+    assert_eq!(entrance_at(&entrances, 4, 6), Some(0));
+    // Where markers overlap, the one drawn on top is picked:
+    assert_eq!(entrance_at(&entrances, 5, 7), Some(1));
+    assert_eq!(entrance_at(&entrances, 6, 6), None);
+    assert_eq!(entrances[0].label(), "E12");
+    assert_eq!(entrances[1].label(), "X104");
+}
+
+#[test]
+fn entrances_are_moved_and_saved_with_the_area() {
+    let mut project = TestProject::new("entrances");
+    let area_id = project.state.main_area_id.clone();
+    let unmarked_hash = project.state.main_area().map_hash();
+    project.state.main_area_mut().entrances = vec![
+        entrance(EntranceKind::Entrance, (4, 6), 0x12),
+        entrance(EntranceKind::Hole, (10, 10), 0x3),
+    ];
+    assert_ne!(project.state.main_area().map_hash(), unmarked_hash);
+
+    project.send(Message::MoveEntrance {
+        position: AreaPosition::Main,
+        area_id: area_id.clone(),
+        idx: 1,
+        coords: Point::new(20, 22),
+    });
+    project.save();
+    let saved = project.saved_area(&area_id.area, &area_id.theme);
+    assert_eq!(saved.entrances[1].position, (20, 22));
+    assert_eq!(saved.entrances[1].kind, EntranceKind::Hole);
+    assert_eq!(saved.entrances[1].target, 0x3);
+
+    // Markers are kept within the area:
+    project.send(Message::MoveEntrance {
+        position: AreaPosition::Main,
+        area_id: area_id.clone(),
+        idx: 0,
+        coords: Point::new(1000, 0),
+    });
+    let size = project.state.main_area().size;
+    assert_eq!(
+        project.state.main_area().entrances[0].position,
+        (size.0 as u16 * 32 - 2, 0)
+    );
+
+    project.undo();
+    project.undo();
+    let entrances = &project.state.main_area().entrances;
+    assert_eq!(entrances[0].position, (4, 6));
+    assert_eq!(entrances[1].position, (10, 10));
+}
+
+#[test]
+fn areas_without_entrances_omit_them() {
+    let mut project = TestProject::new("entrances-none");
+    let area_id = project.state.main_area_id.clone();
+    project.send(Message::ToggleEntrances);
+    assert!(project.state.show_entrances);
+    project.state.main_area_mut().modified = true;
+    project.save();
+    let json = std::fs::read_to_string(project.area_path(&area_id.area, &area_id.theme)).unwrap();
+    assert!(!json.contains("entrances"));
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use common::{rom::blank_rom, TestProject};
+use z3_overworld_editor::{
+    export::Exporter,
+    import::{palette_slots, Constants, PcAddr, Rom},
+    message::Message,
+};
+
+// A blank ROM with an overlay drawing 16x16 tile 1 on every parent map.
+fn overlay_rom() -> Rom {
+    let mut rom = blank_rom();
+    // At $0E9000: LDA #$0001; STA $2082; RTS
+    let routine = [0xA9, 0x01, 0x00, 0x8D, 0x82, 0x20, 0x60];
+    rom.data[0x71000..0x71000 + routine.len()].copy_from_slice(&routine);
+    rom
+}
+
+#[test]
+fn overlay_tiles_are_kept() {
+    let mut project = TestProject::new("export-overlay");
+    let rom = overlay_rom();
+    let base_rom_path = project.dir.join("base.sfc");
+    std::fs::write(&base_rom_path, &rom.data).unwrap();
+
+    // An area in place of map 00, using a tile that isn't in the ROM (with priority):
+    project.send(Message::AddArea {
+        name: "Field".to_string(),
+        size: (4, 4),
+    });
+    let main_area_id = project.state.main_area_id.clone();
+    project
+        .state
+        .areas
+        .get_mut(&main_area_id)
+        .unwrap()
+        .vanilla_map_id = Some(0);
+    project.state.palettes[0].tiles[0].priority = true;
+    let constants = Constants::auto(&rom).unwrap();
+    let names = palette_slots(&constants)
+        .into_iter()
+        .map(|(slot, _, _)| (slot.to_string(), 0))
+        .collect();
+    project
+        .state
+        .project_metadata
+        .rom_palettes
+        .insert("Base".to_string(), names);
+
+    let path = project.dir.join("out.sfc");
+    let report =
+        Exporter::export(&project.state, &base_rom_path, &path, &"Base".to_string()).unwrap();
+    assert_eq!(report.exported, vec!["Field".to_string()]);
+    assert_eq!(report.tiles16_added, 1);
+
+    let out = std::fs::read(&path).unwrap();
+    let tiles16: PcAddr = constants.tiles16_addr.into();
+    let overlay_tile = tiles16.0 as usize + 8;
+    assert_eq!(out[overlay_tile..overlay_tile + 8], [0; 8]);
+    // The new tile takes the next free entry instead:
+    assert_ne!(out[overlay_tile + 8..overlay_tile + 16], [0; 8]);
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use std::path::Path;
+
+use common::{read_json, TestProject};
+use z3_overworld_editor::{
+    external_tools::{split_command, tool_args, ExternalTool, MAX_CONSOLE_LINES},
+    message::Message,
+    state::{AreaId, ProjectMetadata},
+};
+
+#[test]
+fn commands_are_split_and_expanded() {
+    assert_eq!(
+        split_command(r#"asar  "my hack.asm" 'a "b"' x""y"#).unwrap(),
+        vec!["asar", "my hack.asm", r#"a "b""#, "xy"]
+    );
+    assert_eq!(split_command(r#"a """#).unwrap(), vec!["a", ""]);
+    assert!(split_command(r#"asar "main.asm"#).is_err());
+
+    let tool = ExternalTool {
+        name: "Build".to_string(),
+        command: r#"build "{project}/out dir" --area={area}/{theme}"#.to_string(),
+        working_dir: String::new(),
+    };
+    let area_id = AreaId {
+        area: "Kakariko".to_string(),
+        theme: "Spring".to_string(),
+    };
+    assert_eq!(
+        tool_args(&tool, Path::new("/hack"), &area_id).unwrap(),
+        vec!["build", "/hack/out dir", "--area=Kakariko/Spring"]
+    );
+    let empty = ExternalTool {
+        command: "  ".to_string(),
+        ..tool
+    };
+    assert!(tool_args(&empty, Path::new("/hack"), &area_id).is_err());
+}
+
+#[test]
+fn tools_are_saved_and_undone() {
+    let mut project = TestProject::new("external-tools");
+    project.send(Message::AddExternalTool);
+    let tool = ExternalTool {
+        name: "Run asar build".to_string(),
+        command: "asar main.asm {project}/hack.sfc".to_string(),
+        working_dir: "asm".to_string(),
+    };
+    project.send(Message::SetExternalTool(0, tool.clone()));
+    project.save();
+    let metadata: ProjectMetadata = read_json(&project.project_dir().join("Project.json"));
+    assert_eq!(metadata.external_tools, vec![tool.clone()]);
+
+    project.send(Message::DeleteExternalTool(0));
+    assert!(project.state.project_metadata.external_tools.is_empty());
+    project.undo();
+    assert_eq!(project.state.project_metadata.external_tools, vec![tool]);
+    project.undo();
+    assert_eq!(
+        project.state.project_metadata.external_tools[0].name,
+        "Tool 1"
+    );
+}
+
+#[test]
+fn tool_output_is_shown_in_the_console() {
+    let mut project = TestProject::new("external-tools-console");
+    project.send(Message::AddExternalTool);
+    // A tool that can't be started reports the error in the console:
+    project.send(Message::SetExternalTool(
+        0,
+        ExternalTool {
+            name: "Missing".to_string(),
+            command: "z3oe-no-such-program".to_string(),
+            working_dir: String::new(),
+        },
+    ));
+    project.send(Message::RunExternalTool(0));
+    assert_eq!(project.state.running_tools, 0);
+    assert_eq!(project.state.tool_console.len(), 2);
+    assert!(project.state.tool_console[1].contains("z3oe-no-such-program"));
+
+    project.send(Message::ClearToolConsole);
+    for i in 0..MAX_CONSOLE_LINES + 5 {
+        project.send(Message::ExternalToolOutput(format!("line {}", i)));
+    }
+    project.send(Message::ExternalToolFinished {
+        name: "Build".to_string(),
+        result: Ok(Some(1)),
+    });
+    let console = &project.state.tool_console;
+    assert_eq!(console.len(), MAX_CONSOLE_LINES);
+    assert_eq!(console[0], "line 6");
+    assert_eq!(console.last().unwrap(), "Build exited with code 1");
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::{brush_tiles, example_area_id, TestProject};
+use iced::Point;
+use z3_overworld_editor::{
+    flip_analysis::{find_flip_suggestions, FlipSuggestion},
+    message::Message,
+    state::Flip,
+};
+
+#[test]
+fn merge_flipped_tile_in_batch() {
+    let mut project = TestProject::new("merge-flipped-tile");
+    let pixel = |tile_idx, x| Message::BrushPixel {
+        palette_id: 0,
+        tile_idx,
+        coords: Point::new(x, 0),
+        color_idx: 3,
+    };
+    // Tile 2 is a horizontally flipped copy of tile 1:
+    project.send_all([
+        pixel(1, 0),
+        pixel(2, 7),
+        brush_tiles(example_area_id(), 5, 5, vec![2], Flip::Horizontal),
+        Message::SetTileHFlippable {
+            palette_id: 0,
+            tile_idx: 1,
+            h_flippable: false,
+        },
+    ]);
+
+    let suggestions = find_flip_suggestions(&project.state.palettes[0]);
+    assert_eq!(
+        suggestions,
+        vec![FlipSuggestion::Duplicate {
+            palette_id: 0,
+            tile_idx: 2,
+            original: 1,
+            flip: Flip::Horizontal,
+        }]
+    );
+    let messages = suggestions[0].messages(&project.state.palettes[0]);
+    project.send(Message::Batch(messages));
+    project.save();
+    let area = project.saved_area("Example", "Base");
+    assert_eq!(area.get_tile(5, 5).unwrap(), 1);
+    // The brush placed it flipped, so the flips cancel out:
+    assert_eq!(area.get_flip(5, 5).unwrap(), Flip::None);
+    assert!(project.saved_palette("Default").tiles[1].h_flippable);
+
+    // The whole batch is undone at once:
+    project.undo();
+    project.save();
+    let area = project.saved_area("Example", "Base");
+    assert_eq!(area.get_tile(5, 5).unwrap(), 2);
+    assert_eq!(area.get_flip(5, 5).unwrap(), Flip::Horizontal);
+    assert!(!project.saved_palette("Default").tiles[1].h_flippable);
+}
@@ -0,0 +1,54 @@
+use z3_overworld_editor::{
+    heatmap::Heatmap,
+    state::{Area, Flip, Screen},
+};
+
+fn area_with_parent(parent: u8) -> Area {
+    let mut area = Area {
+        vanilla_map_id: Some(parent),
+        size: (4, 4),
+        ..Area::default()
+    };
+    for y in 0..4 {
+        for x in 0..4 {
+            area.screens.push(Screen {
+                position: (x, y),
+                palettes: [[0; 32]; 32],
+                tiles: [[0; 32]; 32],
+                flips: [[Flip::None; 32]; 32],
+                camera_locked: false,
+                overlay: vec![],
+            });
+        }
+    }
+    area
+}
+
+#[test]
+fn csv_counts_map_to_area_screens() {
+    let path = std::env::temp_dir().join(format!("z3oe-heatmap-{}.csv", std::process::id()));
+    std::fs::write(
+        &path,
+        "screen,x,y,count\n# comment\n$1B,300,10,4\n0x1B,300,20,4\n28,2\n",
+    )
+    .unwrap();
+    let heatmap = Heatmap::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // Area $1B is a 2x2 (large) area, so $1C is to its right and $23 below it.
+    let heat = heatmap.area_heat(&area_with_parent(0x1B));
+    let mut cells: Vec<((u8, u8), u64)> = heat.iter().map(|h| (h.position, h.count)).collect();
+    cells.sort();
+    assert_eq!(
+        cells,
+        vec![
+            ((1, 0), 8),
+            ((2, 0), 2),
+            ((2, 1), 2),
+            ((3, 0), 2),
+            ((3, 1), 2)
+        ]
+    );
+    let max = heat.iter().map(|h| h.intensity).fold(0.0, f32::max);
+    assert_eq!(max, 1.0);
+}
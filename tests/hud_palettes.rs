@@ -0,0 +1,60 @@
+mod common;
+
+use common::TestProject;
+use iced::Point;
+use z3_overworld_editor::{
+    message::Message,
+    state::{Dialogue, PaletteCategory},
+};
+
+#[test]
+fn palette_category_limits_editing() {
+    let mut project = TestProject::new("palette-category");
+    project.send(Message::SetPaletteCategory {
+        palette_id: 0,
+        category: PaletteCategory::HUD,
+    });
+    project.save();
+    assert_eq!(
+        project.saved_palette("Default").category,
+        PaletteCategory::HUD
+    );
+
+    // The tiles of HUD palettes are shared by all maps, so they can't be edited:
+    project.send(Message::BrushPixel {
+        palette_id: 0,
+        tile_idx: 2,
+        coords: Point::new(1, 1),
+        color_idx: 3,
+    });
+    assert_eq!(project.state.palettes[0].tiles[2].pixels[1][1], 0);
+    assert!(matches!(project.state.dialogue, Some(Dialogue::Error(_))));
+    project.send(Message::CloseDialogue);
+
+    // Main palettes only have 7 colors:
+    project.send(Message::SetPaletteCategory {
+        palette_id: 0,
+        category: PaletteCategory::Main,
+    });
+    project.send(Message::BrushColor {
+        palette_id: 0,
+        color_idx: 9,
+        color: [31, 0, 0],
+    });
+    assert_eq!(project.state.palettes[0].colors[9], [0, 0, 0]);
+    project.send(Message::CloseDialogue);
+    project.send(Message::BrushPixel {
+        palette_id: 0,
+        tile_idx: 2,
+        coords: Point::new(1, 1),
+        color_idx: 12,
+    });
+    assert_eq!(project.state.palettes[0].category_problems().len(), 1);
+
+    project.undo();
+    project.undo();
+    assert_eq!(project.state.palettes[0].category, PaletteCategory::HUD);
+    project.undo();
+    assert_eq!(project.state.palettes[0].category, PaletteCategory::Custom);
+    assert!(project.state.palettes[0].category_problems().is_empty());
+}
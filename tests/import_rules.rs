@@ -0,0 +1,93 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::{
+    import::{PaletteGroup, PaletteSlot},
+    import_rules::{parse_map_id, rules_path, ImportRules},
+    message::Message,
+    state::Dialogue,
+};
+
+fn slot(group: PaletteGroup, set: u8, row: u8) -> PaletteSlot {
+    PaletteSlot { group, set, row }
+}
+
+fn parse(json: &str) -> ImportRules {
+    serde_json::from_str(json).unwrap()
+}
+
+#[test]
+fn rules_look_up_maps_and_palette_rows() {
+    let rules = parse(
+        r#"{
+            "rename_areas": { "1B": "Hyrule Castle", "$00": "Lost Woods" },
+            "merge_palettes": { "Main 1-0": "Main 0-0" },
+            "drop_maps": ["0x80"]
+        }"#,
+    );
+    rules.check().unwrap();
+    assert_eq!(rules.area_name(0x1B).unwrap(), "Hyrule Castle");
+    assert_eq!(rules.area_name(0x00).unwrap(), "Lost Woods");
+    assert_eq!(rules.area_name(0x40), None);
+    assert!(rules.is_dropped(0x80));
+    assert!(!rules.is_dropped(0x81));
+
+    let slots = [
+        slot(PaletteGroup::Main, 0, 0),
+        slot(PaletteGroup::Main, 1, 0),
+        slot(PaletteGroup::Main, 1, 1),
+    ];
+    rules.check_palette_rows(&slots).unwrap();
+    assert_eq!(
+        rules.merge_target(&slots[1], &slots),
+        Some(slot(PaletteGroup::Main, 0, 0))
+    );
+    assert_eq!(rules.merge_target(&slots[2], &slots), None);
+    assert!(rules.check_palette_rows(&slots[1..]).is_err());
+}
+
+#[test]
+fn invalid_rules_are_rejected() {
+    assert_eq!(parse_map_id("2a").unwrap(), 0x2A);
+    assert!(parse_map_id("82").is_err());
+    assert!(parse_map_id("Lost Woods").is_err());
+    for json in [
+        r#"{ "drop_maps": ["XY"] }"#,
+        r#"{ "rename_areas": { "00": "A", "01": "A" } }"#,
+        r#"{ "merge_palettes": { "Main 1-0": "Main 1-0" } }"#,
+        r#"{ "merge_palettes": { "Main 2-0": "Main 1-0", "Main 1-0": "Main 0-0" } }"#,
+    ] {
+        assert!(parse(json).check().is_err(), "{}", json);
+    }
+    // Unknown keys are likely typos, which would otherwise be silently ignored:
+    assert!(serde_json::from_str::<ImportRules>(r#"{ "drop_map": ["80"] }"#).is_err());
+}
+
+#[test]
+fn rules_file_is_stored_relative_to_project() {
+    let mut project = TestProject::new("import-rules");
+    let path = project.project_dir().join("import-rules.json");
+    std::fs::write(&path, r#"{ "drop_maps": ["80"] }"#).unwrap();
+    project.send(Message::ImportRulesSelected(Some(path.clone())));
+    let metadata = &project.state.project_metadata;
+    assert_eq!(
+        metadata.import_rules.as_ref().unwrap().to_str(),
+        Some("import-rules.json")
+    );
+    assert_eq!(rules_path(&project.state), Some(path.clone()));
+    assert!(ImportRules::load(&project.state).unwrap().is_dropped(0x80));
+
+    // Invalid rules aren't selected:
+    let bad_path = project.project_dir().join("bad-rules.json");
+    std::fs::write(&bad_path, r#"{ "drop_maps": ["99"] }"#).unwrap();
+    project.send(Message::ImportRulesSelected(Some(bad_path)));
+    assert!(matches!(project.state.dialogue, Some(Dialogue::Error(_))));
+    assert_eq!(rules_path(&project.state), Some(path));
+
+    project.send(Message::ClearImportRules);
+    assert_eq!(rules_path(&project.state), None);
+    assert_eq!(
+        ImportRules::load(&project.state).unwrap(),
+        ImportRules::default()
+    );
+}
@@ -0,0 +1,27 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::{message::Message, state::Tile};
+
+#[test]
+fn import_tiles_fills_rows() {
+    let mut project = TestProject::new("import-tiles");
+    let num_tiles = project.saved_palette("Default").tiles.len();
+    let tile = Tile {
+        pixels: [[7; 8]; 8],
+        ..Tile::default()
+    };
+    project.send(Message::ImportTiles {
+        palette_id: 0,
+        tiles: vec![tile; 20],
+    });
+    project.save();
+    let pal = project.saved_palette("Default");
+    assert_eq!(pal.tiles.len(), num_tiles + 32);
+    assert_eq!(pal.tiles[num_tiles + 19], tile);
+    assert_eq!(pal.tiles[num_tiles + 20], Tile::default());
+
+    project.undo();
+    project.save();
+    assert_eq!(project.saved_palette("Default").tiles.len(), num_tiles);
+}
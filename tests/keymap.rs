@@ -0,0 +1,87 @@
+mod common;
+
+use common::{key_press, TestProject};
+use z3_overworld_editor::{
+    keymap::{
+        active_keymap, default_keymap, is_bound, key_action, render_cheat_sheet, KeyAction,
+        KeyBinding,
+    },
+    persist,
+    state::{GlobalConfig, Tool},
+};
+
+fn binding(key: &str, action: KeyAction) -> KeyBinding {
+    KeyBinding {
+        key: key.to_string(),
+        ctrl: false,
+        action,
+    }
+}
+
+#[test]
+fn configured_bindings_replace_defaults() {
+    let mut config = GlobalConfig::default();
+    assert_eq!(active_keymap(&config), default_keymap());
+
+    // Brush on "x", fill tool taking "b" (from the brush), and the grid toggle unbound:
+    config.key_bindings = vec![
+        binding("x", KeyAction::BrushTool),
+        binding("b", KeyAction::FillTool),
+        binding("", KeyAction::ToggleGrid),
+    ];
+    let keymap = active_keymap(&config);
+    assert_eq!(key_action(&keymap, "x", false), Some(KeyAction::BrushTool));
+    assert_eq!(key_action(&keymap, "b", false), Some(KeyAction::FillTool));
+    assert_eq!(key_action(&keymap, "f", false), None);
+    assert_eq!(key_action(&keymap, "g", false), None);
+    assert_eq!(key_action(&keymap, "c", true), Some(KeyAction::Copy));
+    // Configured bindings keep their action's place in the list:
+    let position = |action| keymap.iter().position(|b| b.action == action).unwrap();
+    assert!(position(KeyAction::SelectTool) < position(KeyAction::BrushTool));
+    assert!(position(KeyAction::BrushTool) < position(KeyAction::RectangleTool));
+}
+
+#[test]
+fn keys_dispatch_through_the_keymap() {
+    let mut project = TestProject::new("keymap-dispatch");
+    project.send(key_press("f"));
+    assert!(matches!(project.state.tool, Tool::Fill));
+
+    project.state.global_config.key_bindings = vec![
+        binding("x", KeyAction::BrushTool),
+        binding("q", KeyAction::UndoPreview),
+    ];
+    project.send(key_press("x"));
+    assert!(matches!(project.state.tool, Tool::Brush));
+    project.send(key_press("b"));
+    assert!(matches!(project.state.tool, Tool::Brush));
+
+    // The undo preview follows its key:
+    let keymap = active_keymap(&project.state.global_config);
+    assert!(is_bound(&keymap, "q", KeyAction::UndoPreview));
+    assert!(!is_bound(&keymap, "z", KeyAction::UndoPreview));
+}
+
+#[test]
+fn cheat_sheet_lists_bindings() {
+    let project = TestProject::new("keymap-cheat-sheet");
+    let mut config = GlobalConfig::default();
+    let short = render_cheat_sheet(&active_keymap(&config));
+    // Two bindings for zooming in, in place of one:
+    config.key_bindings = vec![
+        binding("9", KeyAction::ZoomIn),
+        binding("+", KeyAction::ZoomIn),
+    ];
+    let long = render_cheat_sheet(&active_keymap(&config));
+    assert_eq!(long.width, short.width);
+    assert!(long.height > short.height);
+
+    let path = project.dir.join("shortcuts.png");
+    persist::export_cheat_sheet_png(&path, &active_keymap(&config)).unwrap();
+    let decoder = png::Decoder::new(std::fs::File::open(&path).unwrap());
+    let info = decoder.read_info().unwrap().info().clone();
+    assert_eq!(
+        (info.width as usize, info.height as usize),
+        (long.width, long.height)
+    );
+}
@@ -0,0 +1,25 @@
+use z3_overworld_editor::labels::LabelFont;
+
+#[test]
+fn name_table_encodes_charset_indices() {
+    let font = LabelFont {
+        charset: "ABC ".to_string(),
+        first_code: 0x10,
+        max_width: 4,
+        ..LabelFont::default()
+    };
+    assert_eq!(font.label_problem("cab"), None);
+    assert_eq!(
+        font.label_problem("ABBA CAB"),
+        Some("Too long: 8 characters (max 4)".to_string())
+    );
+    assert_eq!(
+        font.label_problem("ABD"),
+        Some("Characters not in font: D".to_string())
+    );
+
+    let asm = font.name_table_asm(&["Cab".to_string(), "A B".to_string()]);
+    assert!(asm.contains("    dw AreaName0\n    dw AreaName1\n"));
+    assert!(asm.contains("AreaName0: ; Cab\n    db $12, $10, $11, $FF\n"));
+    assert!(asm.contains("AreaName1: ; A B\n    db $10, $13, $11, $FF\n"));
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use common::TestProject;
+use iced::Point;
+use z3_overworld_editor::{
+    library::{import_messages, LibraryKind},
+    message::{Message, SelectionSource},
+    state::{Dialogue, Tool},
+};
+
+#[test]
+fn library_items_are_shared_between_projects() {
+    let mut source = TestProject::new("library-source");
+    let library_dir = source.dir.join("Library");
+    source.state.global_config.library_dir = Some(library_dir.clone());
+    source.send(Message::BrushColor {
+        palette_id: 0,
+        color_idx: 1,
+        color: [31, 0, 0],
+    });
+    source.state.palettes[0].tiles[5].pixels[0][0] = 1;
+    source.send_all([
+        Message::StartTileSelection(Point::new(5, 0), SelectionSource::Tileset),
+        Message::EndTileSelection(Point::new(6, 0)),
+        Message::LibraryDialogue,
+        Message::SetLibraryPublishName("Red rock".to_string()),
+        Message::SetLibraryPublishTags("rocks, red, rocks".to_string()),
+        Message::PublishToLibrary(LibraryKind::Stamp),
+        Message::SetLibraryPublishName("Red set".to_string()),
+        Message::SetLibraryPublishTags("".to_string()),
+        Message::PublishToLibrary(LibraryKind::Tileset),
+    ]);
+    assert!(library_dir.join("Red rock.json").exists());
+    assert!(library_dir.join("Red rock.png").exists());
+    let source_tiles = source.state.palettes[0].tiles.clone();
+
+    let mut project = TestProject::new("library-target");
+    project.state.global_config.library_dir = Some(library_dir.clone());
+    project.send(Message::LibraryDialogue);
+    let Some(Dialogue::Library { items, .. }) = &project.state.dialogue else {
+        panic!("library dialogue not open");
+    };
+    let items = items.clone();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].name, "Red rock");
+    assert_eq!(items[0].tags, vec!["rocks", "red"]);
+    assert!(items[0].matches("ROCK"));
+    assert!(!items[1].matches("rocks"));
+    assert!(items[1].matches("tileset"));
+
+    // The stamp's colors don't match any palette here, so it brings its own palette, with
+    // just the tiles the stamp uses:
+    project.send(Message::CloseDialogue);
+    project.send(Message::Batch(
+        import_messages(&project.state, &items[0]).unwrap(),
+    ));
+    assert_eq!(project.state.palettes.len(), 2);
+    let pal = &project.state.palettes[1];
+    assert_eq!(pal.name, "Red rock");
+    assert_eq!(pal.colors[1], [31, 0, 0]);
+    assert_eq!(pal.tiles.len(), 16);
+    assert_eq!(pal.tiles[0], source_tiles[5]);
+    let brush = &project.state.selected_tile_block;
+    assert_eq!(brush.palettes, vec![vec![pal.id, pal.id]]);
+    assert_eq!(brush.tiles, vec![vec![0, 1]]);
+    assert_eq!(project.state.tool, Tool::Brush);
+
+    project.undo();
+    assert_eq!(project.state.palettes.len(), 1);
+
+    // A tileset is added as a new palette:
+    project.send(Message::Batch(
+        import_messages(&project.state, &items[1]).unwrap(),
+    ));
+    let pal = &project.state.palettes[1];
+    assert_eq!(pal.name, "Red set");
+    assert_eq!(pal.tiles, source_tiles);
+
+    project.send(Message::LibraryDialogue);
+    project.send(Message::DeleteFromLibrary("Red set".to_string()));
+    assert!(!library_dir.join("Red set.json").exists());
+    assert!(!library_dir.join("Red set.png").exists());
+}
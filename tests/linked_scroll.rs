@@ -0,0 +1,39 @@
+mod common;
+
+use common::TestProject;
+use iced::Vector;
+use z3_overworld_editor::{message::Message, state::AreaPosition};
+
+fn scrolled(position: AreaPosition, x: f32, y: f32) -> Message {
+    Message::AreaScrolled {
+        position,
+        offset: Vector::new(x, y),
+    }
+}
+
+#[test]
+fn views_scroll_together_when_linked() {
+    let mut project = TestProject::new("linked-scroll");
+    project.send(scrolled(AreaPosition::Main, 100.0, 50.0));
+    assert_eq!(project.state.side_area_scroll, Vector::ZERO);
+
+    // Linking brings the side view to the main view's position:
+    project.send(Message::ToggleLinkedScroll);
+    assert!(project.state.link_area_scroll);
+    assert_eq!(project.state.side_area_scroll, Vector::new(100.0, 50.0));
+    project.send(scrolled(AreaPosition::Side, 100.0, 50.0));
+
+    project.send(scrolled(AreaPosition::Main, 300.0, 200.0));
+    assert_eq!(project.state.side_area_scroll, Vector::new(300.0, 200.0));
+    // The side view's own scroll event (here clamped to a smaller area) isn't followed back:
+    project.send(scrolled(AreaPosition::Side, 250.0, 200.0));
+    assert_eq!(project.state.main_area_scroll, Vector::new(300.0, 200.0));
+    assert_eq!(project.state.side_area_scroll, Vector::new(250.0, 200.0));
+
+    project.send(scrolled(AreaPosition::Side, 10.0, 20.0));
+    assert_eq!(project.state.main_area_scroll, Vector::new(10.0, 20.0));
+
+    project.send(Message::ToggleLinkedScroll);
+    project.send(scrolled(AreaPosition::Main, 0.0, 0.0));
+    assert_eq!(project.state.side_area_scroll, Vector::new(10.0, 20.0));
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use common::{brush, TestProject};
+use z3_overworld_editor::{
+    message::Message,
+    persist::{ConflictSide, RestoreOption},
+    state::Dialogue,
+};
+
+#[test]
+fn conflicted_palette_is_restored_from_one_side() {
+    let mut project = TestProject::new("conflicted-palette");
+    project.send(Message::AddPalette {
+        name: "Extra".to_string(),
+        id: 7,
+    });
+    project.save();
+    let path = project.palette_path("Extra");
+    let ours = std::fs::read_to_string(&path).unwrap();
+    let mut pal = project.saved_palette("Extra");
+    pal.colors[1] = [31, 0, 0];
+    let theirs = serde_json::to_string_pretty(&pal).unwrap();
+    let conflicted = format!(
+        "<<<<<<< HEAD\n{}\n=======\n{}\n>>>>>>> branch\n",
+        ours, theirs
+    );
+    std::fs::write(&path, &conflicted).unwrap();
+
+    // The rest of the project still loads, leaving out the broken palette:
+    project.send(Message::ModifiedReload);
+    assert!(matches!(
+        project.state.dialogue,
+        Some(Dialogue::LoadRecovery)
+    ));
+    assert_eq!(project.state.palettes.len(), 1);
+    let failure = &project.state.load_failures[0];
+    assert_eq!(failure.path, path);
+    assert_eq!(
+        failure.restore_options,
+        vec![
+            RestoreOption::ConflictSide(ConflictSide::Ours),
+            RestoreOption::ConflictSide(ConflictSide::Theirs),
+        ]
+    );
+
+    project.send(Message::RestoreFile {
+        path: path.clone(),
+        option: RestoreOption::ConflictSide(ConflictSide::Theirs),
+    });
+    assert!(project.state.dialogue.is_none());
+    assert!(project.state.load_failures.is_empty());
+    assert_eq!(project.state.palettes.len(), 2);
+    assert_eq!(project.saved_palette("Extra").colors[1], [31, 0, 0]);
+    // The broken file is kept in the project's Trash folder:
+    let trash_path = project.project_dir().join("Trash/Palettes/Extra.json");
+    assert_eq!(std::fs::read_to_string(trash_path).unwrap(), conflicted);
+}
+
+#[test]
+fn truncated_area_is_skipped_without_being_overwritten() {
+    let mut project = TestProject::new("truncated-area");
+    let path = project.area_path("Example", "Base");
+    let data = std::fs::read(&path).unwrap();
+    let truncated = &data[..data.len() / 2];
+    std::fs::write(&path, truncated).unwrap();
+
+    project.send(Message::ModifiedReload);
+    assert!(matches!(
+        project.state.dialogue,
+        Some(Dialogue::LoadRecovery)
+    ));
+    let failure = &project.state.load_failures[0];
+    assert_eq!(failure.path, path);
+    assert!(failure.restore_options.is_empty());
+
+    // The default area that takes its place isn't saved over the broken file:
+    project.send(brush(1, 1, 2));
+    project.save();
+    assert_eq!(std::fs::read(&path).unwrap(), truncated);
+
+    project.send(Message::SkipFile(path.clone()));
+    assert!(project.state.dialogue.is_none());
+    assert!(project.state.load_failures[0].skipped);
+}
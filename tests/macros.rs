@@ -0,0 +1,41 @@
+mod common;
+
+use common::{brush, example_area_id, TestProject};
+use iced::Point;
+use z3_overworld_editor::{message::Message, state::AreaPosition};
+
+#[test]
+fn macro_replays_at_new_location() {
+    let mut project = TestProject::new("macros");
+    project.send_all([
+        Message::ToggleMacroRecording,
+        brush(10, 10, 4),
+        brush(12, 11, 5),
+        Message::SetTilePriority {
+            palette_id: 0,
+            tile_idx: 4,
+            priority: true,
+        },
+        Message::ToggleMacroRecording,
+    ]);
+    assert!(project.state.macro_recording.is_none());
+    assert_eq!(project.state.project_metadata.macros[0].steps.len(), 3);
+
+    // Replaying sends the macro's edits as a batch:
+    let messages = project.state.project_metadata.macros[0].messages(
+        AreaPosition::Main,
+        &example_area_id(),
+        Point::new(30, 40),
+    );
+    project.send(Message::Batch(messages));
+    let area = &project.state.areas[&example_area_id()];
+    assert_eq!(area.get_tile(30, 40).unwrap(), 4);
+    assert_eq!(area.get_tile(32, 41).unwrap(), 5);
+
+    // The replay is undone in one step:
+    project.undo();
+    let area = &project.state.areas[&example_area_id()];
+    assert_eq!(area.get_tile(30, 40).unwrap(), 0);
+    assert_eq!(area.get_tile(32, 41).unwrap(), 0);
+    assert_eq!(area.get_tile(12, 11).unwrap(), 5);
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::{example_area_id, shape, TestProject};
+use iced::Point;
+use z3_overworld_editor::{area_shapes::AreaShape, message::Message, state::AreaPosition};
+
+#[test]
+fn magic_wand_selects_region_as_masked_brush() {
+    let mut project = TestProject::new("magic-wand");
+    // An L of tile 4 in the top-left corner:
+    project.send_all([
+        shape(
+            AreaShape::Line {
+                start: Point::new(0, 0),
+                end: Point::new(0, 2),
+            },
+            vec![vec![4]],
+        ),
+        shape(
+            AreaShape::Line {
+                start: Point::new(1, 2),
+                end: Point::new(2, 2),
+            },
+            vec![vec![4]],
+        ),
+    ]);
+    project.send(Message::SelectRegion(AreaPosition::Main, Point::new(0, 1)));
+    let block = &project.state.selected_tile_block;
+    assert_eq!(block.size, (3, 3));
+    assert_eq!(
+        block.mask,
+        Some(vec![
+            vec![true, false, false],
+            vec![true, false, false],
+            vec![true, true, true],
+        ])
+    );
+
+    // Brushing with the selection only places tiles within its shape:
+    let selection = block.clone();
+    project.send(Message::AreaBrush {
+        position: AreaPosition::Main,
+        area_id: example_area_id(),
+        coords: Point::new(20, 20),
+        selection,
+        palette_only: false,
+    });
+    project.save();
+    let area = project.saved_area("Example", "Base");
+    assert_eq!(area.get_tile(20, 22).unwrap(), 4);
+    assert_eq!(area.get_tile(22, 22).unwrap(), 4);
+    assert_eq!(area.get_tile(21, 21).unwrap(), 0);
+}
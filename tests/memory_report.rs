@@ -0,0 +1,91 @@
+mod common;
+
+use common::TestProject;
+use iced::Point;
+use z3_overworld_editor::{
+    area_manifest::AreaManifestEntry,
+    area_raster::{
+        cached_screen_count, clear_screen_raster_caches, RasterHighlights, ScreenRasterCache,
+        ScreenRasterizer,
+    },
+    memory_report::memory_report,
+    message::Message,
+    state::{AreaId, AreaPosition, Dialogue, Flip, TileBlock},
+};
+
+#[test]
+fn memory_report_and_cleanup() {
+    let mut project = TestProject::new("memory-report");
+    project.send(Message::MemoryDialogue);
+    let Some(Dialogue::Memory { project_bytes }) = project.state.dialogue else {
+        panic!("memory dialogue not shown");
+    };
+    assert!(project_bytes > 0);
+
+    let area_id = project.state.main_area_id.clone();
+    project.send(Message::AreaBrush {
+        position: AreaPosition::Main,
+        area_id: area_id.clone(),
+        coords: Point::new(3, 4),
+        selection: TileBlock {
+            size: (1, 1),
+            palettes: vec![vec![0]],
+            tiles: vec![vec![5]],
+            flips: vec![vec![Flip::None]],
+            mask: None,
+        },
+        palette_only: false,
+    });
+    // An area loaded for something other than the area views (e.g. a batch operation):
+    project.send(Message::CreateManifestArea(AreaManifestEntry {
+        name: "Grove".to_string(),
+        size: (1, 1),
+        vanilla_map_id: None,
+        bg_color: None,
+    }));
+    project
+        .state
+        .load_area(&AreaId {
+            area: "Grove".to_string(),
+            theme: "Base".to_string(),
+        })
+        .unwrap();
+    let report = memory_report(&project.state, project_bytes);
+    assert_eq!(report.undo_entries, 2);
+    assert!(report.undo_bytes > 0);
+    assert!(report.area_bytes > 0);
+    let loaded = report.loaded_areas;
+    assert!(report.unused_areas > 0);
+
+    project.send(Message::UnloadUnusedAreas);
+    let report = memory_report(&project.state, project_bytes);
+    assert_eq!(report.unused_areas, 0);
+    assert!(report.loaded_areas < loaded);
+
+    project.send(Message::CompactMemory);
+    assert_eq!(memory_report(&project.state, project_bytes).undo_entries, 2);
+}
+
+#[test]
+fn screen_raster_caches_are_cleared() {
+    let project = TestProject::new("memory-rasters");
+    let state = &project.state;
+    let rasterizer = ScreenRasterizer {
+        area: state.main_area(),
+        palettes: &state.palettes,
+        palettes_id_idx_map: &state.palettes_id_idx_map,
+        highlights: RasterHighlights::default(),
+    };
+    let mut cache = ScreenRasterCache::default();
+    let first = cache.get(&rasterizer, 0);
+    cache.get(&rasterizer, 1);
+    assert_eq!(cached_screen_count(), 2);
+
+    // Caches are cleared when they're next used:
+    clear_screen_raster_caches();
+    assert_ne!(cache.get(&rasterizer, 0).id(), first.id());
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cached_screen_count(), 1);
+    drop(cache);
+    assert_eq!(cached_screen_count(), 0);
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use common::{read_json, TestProject};
+use iced::Point;
+use z3_overworld_editor::{
+    message::{Message, SelectionSource},
+    metatiles::{Metatile, MetatileSize},
+    state::{Flip, ProjectMetadata, SidePanelView},
+};
+
+fn select_tiles(project: &mut TestProject, end: (u16, u16)) {
+    while project.state.palettes[0].tiles.len() < 16 * (end.1 as usize + 1) {
+        project.send(Message::AddTileRow(0));
+    }
+    project.send(Message::StartTileSelection(
+        Point::new(0, 0),
+        SelectionSource::Tileset,
+    ));
+    project.send(Message::EndTileSelection(Point::new(end.0, end.1)));
+}
+
+#[test]
+fn metatiles_are_made_from_the_brush_and_edited() {
+    let mut project = TestProject::new("metatiles");
+    project.send(Message::ShowMetatiles(true));
+    assert!(matches!(
+        project.state.side_panel_view,
+        SidePanelView::Metatiles
+    ));
+
+    // A 3x2 brush is enough for a 16x16 metatile, but not for a 32x32 one:
+    select_tiles(&mut project, (2, 1));
+    project.send(Message::AddMetatile(MetatileSize::Tile32));
+    assert!(project.state.project_metadata.metatiles.is_empty());
+    project.send(Message::AddMetatile(MetatileSize::Tile16));
+    let metatile = &project.state.project_metadata.metatiles[0];
+    assert_eq!(metatile.name, "Metatile 1");
+    assert_eq!(metatile.block.size, (2, 2));
+    assert_eq!(metatile.block.tiles, vec![vec![0, 1], vec![16, 17]]);
+    assert_eq!(project.state.metatile_idx, Some(0));
+
+    project.send(Message::SetMetatileCell {
+        metatile_idx: 0,
+        x: 1,
+        y: 0,
+        palette_id: 0,
+        tile_idx: 5,
+        flip: Flip::Vertical,
+    });
+    project.send(Message::SetMetatileName(0, "Bush".to_string()));
+    project.save();
+    let metadata: ProjectMetadata = read_json(&project.project_dir().join("Project.json"));
+    let saved: &Metatile = &metadata.metatiles[0];
+    assert_eq!(saved.name, "Bush");
+    assert_eq!(saved.block.tiles[0][1], 5);
+    assert_eq!(saved.block.flips[0][1], Flip::Vertical);
+
+    project.undo();
+    project.undo();
+    let metatile = &project.state.project_metadata.metatiles[0];
+    assert_eq!(metatile.block.tiles[0][1], 1);
+    assert_eq!(metatile.block.flips[0][1], Flip::None);
+
+    // Cells outside of the metatile can't be set:
+    project.send(Message::SetMetatileCell {
+        metatile_idx: 0,
+        x: 2,
+        y: 0,
+        palette_id: 0,
+        tile_idx: 5,
+        flip: Flip::None,
+    });
+    let metatile = &project.state.project_metadata.metatiles[0];
+    assert_eq!(metatile.block.tiles, vec![vec![0, 1], vec![16, 17]]);
+}
+
+#[test]
+fn stamping_lasts_while_the_metatile_is_the_brush() {
+    let mut project = TestProject::new("metatiles-stamp");
+    select_tiles(&mut project, (3, 3));
+    project.send(Message::AddMetatile(MetatileSize::Tile32));
+    project.send(Message::StampMetatile(0));
+    let stamping = project.state.stamp_metatile().unwrap();
+    assert_eq!(stamping.size, MetatileSize::Tile32);
+    assert_eq!(project.state.selected_tile_block, stamping.block);
+
+    // Editing the metatile updates the brush:
+    project.send(Message::SetMetatileCell {
+        metatile_idx: 0,
+        x: 0,
+        y: 0,
+        palette_id: 0,
+        tile_idx: 9,
+        flip: Flip::None,
+    });
+    assert_eq!(project.state.selected_tile_block.tiles[0][0], 9);
+    assert!(project.state.stamp_metatile().is_some());
+
+    // Selecting other tiles ends the stamping:
+    select_tiles(&mut project, (1, 1));
+    assert!(project.state.stamp_metatile().is_none());
+
+    project.send(Message::DeleteMetatile(0));
+    assert!(project.state.project_metadata.metatiles.is_empty());
+    project.undo();
+    assert_eq!(project.state.project_metadata.metatiles.len(), 1);
+}
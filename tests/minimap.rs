@@ -0,0 +1,32 @@
+mod common;
+
+use common::TestProject;
+use iced::{
+    keyboard::{self, key, Key, Modifiers},
+    Vector,
+};
+use z3_overworld_editor::{message::Message, state::AreaPosition};
+
+#[test]
+fn minimap_scrolls_the_view_and_is_toggled_by_its_key() {
+    let mut project = TestProject::new("minimap");
+    assert!(project.state.show_minimap);
+    project.send(Message::ScrollAreaTo {
+        position: AreaPosition::Main,
+        offset: Vector::new(320.0, 64.0),
+    });
+    assert_eq!(project.state.main_area_scroll, Vector::new(320.0, 64.0));
+    assert_eq!(project.state.side_area_scroll, Vector::ZERO);
+
+    project.send(Message::Event(iced::Event::Keyboard(
+        keyboard::Event::KeyPressed {
+            key: Key::Character("n".into()),
+            modified_key: Key::Character("n".into()),
+            physical_key: key::Physical::Unidentified(key::NativeCode::Unidentified),
+            location: keyboard::Location::Standard,
+            modifiers: Modifiers::empty(),
+            text: None,
+        },
+    )));
+    assert!(!project.state.show_minimap);
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use common::{brush, brush_tiles, example_area_id, TestProject};
+use iced::Point;
+use z3_overworld_editor::{
+    import::{read_overlay, Rom, SnesAddr},
+    message::Message,
+    state::{Flip, Layer, OverlayTile, Tile},
+};
+
+#[test]
+fn overlay_routines_are_read_from_the_rom() {
+    let mut data = vec![0; 0x80000];
+    // At $0E9000: LDA #$1234; STA $2082; INC A; LDX #$0004; STA $2082,X; RTS
+    let routine = [
+        0xA9, 0x34, 0x12, 0x8D, 0x82, 0x20, 0x1A, 0xA2, 0x04, 0x00, 0x9D, 0x82, 0x20, 0x60,
+    ];
+    data[0x71000..0x71000 + routine.len()].copy_from_slice(&routine);
+    let rom = Rom { data };
+    let tiles = read_overlay(&rom, SnesAddr(0x0E9000)).unwrap();
+    assert_eq!(tiles, vec![((1, 1), 0x1234), ((3, 1), 0x1235)]);
+}
+
+#[test]
+fn brushing_on_the_overlay_layer() {
+    let mut project = TestProject::new("overlays");
+    // Tile 1 has graphics, and tile 2 is transparent:
+    let mut pixels = [[0; 8]; 8];
+    pixels[2][3] = 4;
+    let tile = Tile {
+        pixels,
+        ..project.state.palettes[0].tiles[1]
+    };
+    let transparent = Tile {
+        pixels: [[0; 8]; 8],
+        ..tile
+    };
+    project.send(Message::TilesetBrush {
+        palette_id: 0,
+        coords: Point::new(1, 0),
+        selected_gfx: vec![vec![tile, transparent]],
+    });
+    project.save();
+    let main_tile = project
+        .saved_area("Example", "Base")
+        .get_tile(5, 40)
+        .unwrap();
+
+    project.send(Message::SetEditLayer(Layer::Overlay));
+    project.send(brush_tiles(
+        example_area_id(),
+        5,
+        40,
+        vec![1, 1],
+        Flip::None,
+    ));
+    project.save();
+    let area = project.saved_area("Example", "Base");
+    assert_eq!(area.get_tile(5, 40).unwrap(), main_tile);
+    assert_eq!(
+        area.get_overlay(5, 40).unwrap(),
+        Some(OverlayTile {
+            x: 5,
+            y: 8,
+            palette: 0,
+            tile: 1,
+            flip: Flip::None,
+        })
+    );
+    assert!(area.get_overlay(6, 40).unwrap().is_some());
+    let overlay_len: usize = area.screens.iter().map(|s| s.overlay.len()).sum();
+    assert_eq!(overlay_len, 2);
+
+    // Brushing with transparent graphics erases the overlay:
+    project.send(brush(6, 40, 2));
+    project.save();
+    let area = project.saved_area("Example", "Base");
+    assert!(area.get_overlay(6, 40).unwrap().is_none());
+    assert!(area.get_overlay(5, 40).unwrap().is_some());
+
+    project.undo();
+    project.undo();
+    project.save();
+    let area = project.saved_area("Example", "Base");
+    assert!(area.screens.iter().all(|s| s.overlay.is_empty()));
+
+    // Back on the main layer, brushing changes the tiles themselves:
+    project.send(Message::SetEditLayer(Layer::Main));
+    project.send(brush(5, 40, 1));
+    project.save();
+    assert_eq!(
+        project
+            .saved_area("Example", "Base")
+            .get_tile(5, 40)
+            .unwrap(),
+        1
+    );
+}
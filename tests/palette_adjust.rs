@@ -0,0 +1,104 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::{
+    message::Message,
+    palette_adjust::{interpolate, shift_hsv, PaletteAdjustChange},
+    state::{Dialogue, PaletteId},
+};
+
+fn colors(project: &TestProject, id: PaletteId) -> [[u8; 3]; 16] {
+    let idx = project.state.palettes_id_idx_map[&id];
+    project.state.palettes[idx].colors
+}
+
+fn set_color(project: &mut TestProject, id: PaletteId, color_idx: u8, color: [u8; 3]) {
+    project.send(Message::BrushColor {
+        palette_id: id,
+        color_idx,
+        color,
+    });
+}
+
+#[test]
+fn hsv_shift() {
+    let mut colors = [[0; 3]; 16];
+    colors[1] = [31, 0, 0];
+    colors[2] = [20, 10, 10];
+    let shifted = shift_hsv(&colors, 120, 0, 0);
+    assert_eq!(shifted[0], [0, 0, 0]);
+    assert_eq!(shifted[1], [0, 31, 0]);
+    assert_eq!(shifted[2], [10, 20, 10]);
+    let dimmed = shift_hsv(&colors, 0, 0, -50);
+    assert_eq!(dimmed[1], [16, 0, 0]);
+    let gray = shift_hsv(&colors, 0, -100, 0);
+    assert_eq!(gray[2], [20, 20, 20]);
+    // Values stay in range:
+    assert_eq!(shift_hsv(&colors, 0, 100, 100)[2], [31, 0, 0]);
+    assert_eq!(shift_hsv(&colors, -360, 0, 0), colors);
+}
+
+#[test]
+fn bulk_operations_are_undone() {
+    let mut project = TestProject::new("palette-adjust");
+    project.send(Message::AddPalette {
+        name: "Other".to_string(),
+        id: 1,
+    });
+    set_color(&mut project, 0, 1, [31, 0, 0]);
+    set_color(&mut project, 1, 1, [0, 0, 31]);
+    let original = colors(&project, 0);
+
+    project.send(Message::AdjustPaletteHSV {
+        palette_id: 0,
+        hue: 120,
+        saturation: 0,
+        brightness: 0,
+    });
+    assert_eq!(colors(&project, 0)[1], [0, 31, 0]);
+
+    project.send(Message::InterpolatePalettes {
+        palette_id: 0,
+        from: 0,
+        to: 1,
+        amount: 50,
+    });
+    assert_eq!(colors(&project, 0)[1], [0, 16, 16]);
+
+    project.send(Message::CopyPaletteColors {
+        palette_id: 0,
+        source_id: 1,
+    });
+    assert_eq!(colors(&project, 0), colors(&project, 1));
+
+    project.undo();
+    assert_eq!(colors(&project, 0)[1], [0, 16, 16]);
+    project.undo();
+    project.undo();
+    assert_eq!(colors(&project, 0), original);
+    project.redo();
+    assert_eq!(colors(&project, 0)[1], [0, 31, 0]);
+
+    project.save();
+    assert_eq!(project.saved_palette("Default").colors[1], [0, 31, 0]);
+}
+
+#[test]
+fn dialogue_settings() {
+    let mut project = TestProject::new("palette-adjust-dialogue");
+    project.send(Message::AdjustPaletteDialogue);
+    project.send(Message::SetPaletteAdjust(PaletteAdjustChange::Hue(30)));
+    project.send(Message::SetPaletteAdjust(PaletteAdjustChange::BlendTo(
+        Some(0),
+    )));
+    let Some(Dialogue::AdjustPalette(adjust)) = &project.state.dialogue else {
+        panic!("adjust dialogue not open");
+    };
+    assert_eq!(adjust.hue, 30);
+    assert_eq!(adjust.blend_from, Some(0));
+    assert_eq!(adjust.blend_to, Some(0));
+    assert_eq!(
+        interpolate(&[[0; 3]; 16], &[[31; 3]; 16], 100),
+        [[31; 3]; 16]
+    );
+}
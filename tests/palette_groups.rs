@@ -0,0 +1,100 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::{
+    message::Message,
+    palette_groups::{palette_group_names, shown_palettes, UNGROUPED_PALETTE_GROUP},
+};
+
+fn add_grouped_palettes(project: &mut TestProject) {
+    for (id, name) in [(1, "Grass"), (2, "Water"), (3, "Trees")] {
+        project.send(Message::AddPalette {
+            name: name.to_string(),
+            id,
+        });
+    }
+    project.send(Message::SetPaletteGroups(vec![
+        (1, Some("Outdoors".to_string())),
+        (2, Some("Water".to_string())),
+        (3, Some("Outdoors".to_string())),
+    ]));
+}
+
+fn shown_ids(project: &TestProject) -> Vec<u8> {
+    shown_palettes(&project.state)
+        .iter()
+        .map(|p| p.id as u8)
+        .collect()
+}
+
+#[test]
+fn palettes_are_filtered_by_text_and_group() {
+    let mut project = TestProject::new("palette-groups-filter");
+    add_grouped_palettes(&mut project);
+    assert_eq!(
+        palette_group_names(&project.state),
+        vec!["Outdoors", "Water", UNGROUPED_PALETTE_GROUP]
+    );
+
+    project.send(Message::SetPaletteFilter("TREE".to_string()));
+    assert_eq!(shown_ids(&project), vec![3]);
+    project.send(Message::SetPaletteFilter("outdoors".to_string()));
+    assert_eq!(shown_ids(&project), vec![1, 3]);
+    project.send(Message::SetPaletteFilter(String::new()));
+    project.send(Message::SetPaletteGroupFilter(
+        UNGROUPED_PALETTE_GROUP.to_string(),
+    ));
+    assert_eq!(shown_ids(&project), vec![0]);
+    project.send(Message::SetPaletteGroupFilter("All groups".to_string()));
+    assert_eq!(shown_ids(&project).len(), 4);
+}
+
+#[test]
+fn groups_are_saved_renamed_reordered_and_undone() {
+    let mut project = TestProject::new("palette-groups-edit");
+    add_grouped_palettes(&mut project);
+    project.save();
+    assert_eq!(
+        project.saved_palette("Grass").group.as_deref(),
+        Some("Outdoors")
+    );
+    assert_eq!(project.saved_palette("Default").group, None);
+
+    project.send(Message::SetPaletteGroupOrder(vec![
+        "Water".to_string(),
+        "Outdoors".to_string(),
+    ]));
+    assert_eq!(
+        palette_group_names(&project.state),
+        vec!["Water", "Outdoors", UNGROUPED_PALETTE_GROUP]
+    );
+
+    // Renaming a group (moving all of its palettes) keeps its place in the order:
+    project.send(Message::SetPaletteGroups(vec![(
+        2,
+        Some("Sea".to_string()),
+    )]));
+    assert_eq!(
+        palette_group_names(&project.state),
+        vec!["Sea", "Outdoors", UNGROUPED_PALETTE_GROUP]
+    );
+    project.save();
+    assert_eq!(project.saved_palette("Water").group.as_deref(), Some("Sea"));
+
+    project.undo();
+    assert_eq!(
+        palette_group_names(&project.state),
+        vec!["Water", "Outdoors", UNGROUPED_PALETTE_GROUP]
+    );
+    project.undo();
+    assert_eq!(
+        palette_group_names(&project.state),
+        vec!["Outdoors", "Water", UNGROUPED_PALETTE_GROUP]
+    );
+    assert_eq!(
+        project.state.palettes[project.state.palettes_id_idx_map[&2]]
+            .group
+            .as_deref(),
+        Some("Water")
+    );
+}
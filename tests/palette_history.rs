@@ -0,0 +1,34 @@
+mod common;
+
+use common::{brush, TestProject};
+use z3_overworld_editor::{message::Message, state::Dialogue};
+
+#[test]
+fn palette_history_does_not_save_the_project() {
+    let mut project = TestProject::new("palette-history");
+    let color = |color| Message::BrushColor {
+        palette_id: 0,
+        color_idx: 3,
+        color,
+    };
+    project.send(color([31, 0, 0]));
+    project.save();
+    project.send(color([0, 31, 0]));
+    project.send(brush(5, 5, 1));
+    project.send(Message::PaletteHistoryDialogue);
+
+    let Some(Dialogue::PaletteHistory(versions)) = &project.state.dialogue else {
+        panic!("palette history dialogue not open");
+    };
+    // The version last saved is listed, though not yet in the history:
+    assert_eq!(versions[0].palette.colors[3], [31, 0, 0]);
+    assert!(project.state.palettes[0].modified);
+    assert_eq!(project.saved_palette("Default").colors[3], [31, 0, 0]);
+    assert_ne!(
+        project
+            .saved_area("Example", "Base")
+            .get_tile(5, 5)
+            .unwrap(),
+        1
+    );
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::{
+    message::Message,
+    palette_replace::{missing_tile_count, palette_cells},
+    state::{Palette, PaletteCategory},
+};
+
+// A project with a second palette (ID 1), used by a few tiles of the main area.
+fn two_palette_project(name: &str) -> TestProject {
+    let mut project = TestProject::new(name);
+    let palette = &project.state.palettes[0];
+    project.send(Message::RestorePalette(Palette {
+        modified: true,
+        name: "Other".to_string(),
+        id: 1,
+        category: PaletteCategory::Custom,
+        group: None,
+        colors: palette.colors,
+        tiles: palette.tiles.clone(),
+    }));
+    let area = project.state.main_area_mut();
+    area.set_palette(3, 4, 1).unwrap();
+    area.set_palette(40, 2, 1).unwrap();
+    project
+}
+
+#[test]
+fn palette_is_replaced_in_the_area_with_one_undo() {
+    let mut project = two_palette_project("palette-replace");
+    let area_id = project.state.main_area_id.clone();
+    let uses_of_0 = palette_cells(project.state.main_area(), 0).len();
+
+    // The replacement starts from the selected palette (the one just added):
+    project.send(Message::ShowPaletteReplace);
+    let replace = project.state.palette_replace.as_ref().unwrap();
+    assert_eq!(replace.from, Some(1));
+    assert_eq!(replace.to, None);
+
+    project.send(Message::ReplaceAreaPalette {
+        area_id: area_id.clone(),
+        from: 1,
+        to: 0,
+    });
+    assert!(project.state.palette_replace.is_none());
+    assert!(palette_cells(project.state.main_area(), 1).is_empty());
+    assert_eq!(
+        palette_cells(project.state.main_area(), 0).len(),
+        uses_of_0 + 2
+    );
+
+    // Undoing restores only the tiles that used the replaced palette:
+    project.undo();
+    assert_eq!(
+        palette_cells(project.state.main_area(), 1),
+        vec![(3, 4), (40, 2)]
+    );
+    assert_eq!(palette_cells(project.state.main_area(), 0).len(), uses_of_0);
+    project.redo();
+    assert!(palette_cells(project.state.main_area(), 1).is_empty());
+
+    project.save();
+    let saved = project.saved_area(&area_id.area, &area_id.theme);
+    assert!(palette_cells(&saved, 1).is_empty());
+}
+
+#[test]
+fn tiles_missing_from_the_new_palette_are_counted() {
+    let mut project = two_palette_project("palette-replace-missing");
+    let num_tiles = project.state.palettes[1].tiles.len() as u16;
+    project
+        .state
+        .main_area_mut()
+        .set_tile(40, 2, num_tiles)
+        .unwrap();
+    let area = project.state.main_area();
+    let cells = palette_cells(area, 1);
+    assert_eq!(
+        missing_tile_count(area, &cells, &project.state.palettes[1]),
+        1
+    );
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::{
+    message::Message,
+    palette_sheet::{render_palette_sheet, swatch_position},
+};
+
+#[test]
+fn export_labeled_palette_png() {
+    let mut project = TestProject::new("export-palette");
+    project.send(Message::BrushColor {
+        palette_id: project.state.palettes[0].id,
+        color_idx: 9,
+        color: [31, 0, 0],
+    });
+    project.send(Message::ExportPaletteDialogue);
+    let path = project.dir.join("sheet.png");
+    project.send(Message::ExportPaletteTo(Some(path.clone())));
+    assert!(project.state.dialogue.is_none());
+
+    let decoder = png::Decoder::new(std::fs::File::open(&path).unwrap());
+    let mut reader = decoder.read_info().unwrap();
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data).unwrap();
+    let sheet = render_palette_sheet(&project.state.palettes[0]);
+    assert_eq!(
+        (info.width, info.height),
+        (sheet.width as u32, sheet.height as u32)
+    );
+    assert_eq!(&data[..info.buffer_size()], &sheet.data[..]);
+
+    // The swatch shows the color, with its labels drawn below it:
+    let (x, y) = swatch_position(9);
+    assert_eq!(sheet.pixel(x + 4, y + 4)[0], 255);
+    let label_rows = (y + 48..y + 96).filter(|&y1| sheet.pixel(x + 2, y1) != sheet.pixel(0, 0));
+    assert!(label_rows.count() > 0);
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::{
+    message::Message,
+    palette_slots::{load_assignment, solve, PaletteAssignment, RowPosition},
+    state::{AreaId, PaletteCategory, PaletteId},
+};
+
+// Add an area for the given vanilla map, with a row of tiles in each of the given palettes.
+fn add_area(project: &mut TestProject, name: &str, map_id: u8, palettes: &[PaletteId]) {
+    project.send(Message::AddArea {
+        name: name.to_string(),
+        size: (1, 1),
+    });
+    let area_id = AreaId {
+        area: name.to_string(),
+        theme: "Base".to_string(),
+    };
+    let area = project.state.areas.get_mut(&area_id).unwrap();
+    area.vanilla_map_id = Some(map_id);
+    for (x, &id) in palettes.iter().enumerate() {
+        area.screens[0].palettes[0][x] = id;
+    }
+    area.modified = true;
+    project.save();
+}
+
+fn add_palettes(project: &mut TestProject, ids: &[PaletteId], category: PaletteCategory) {
+    for &id in ids {
+        project.send(Message::AddPalette {
+            name: format!("Palette {}", id),
+            id,
+        });
+        project.send(Message::SetPaletteCategory {
+            palette_id: id,
+            category,
+        });
+    }
+}
+
+#[test]
+fn positions_are_saved_by_name() {
+    let assignment: PaletteAssignment =
+        serde_json::from_str(r#"{ "Lost Woods": { "Main 0": 3, "Aux2 1": 12, "Animated": 20 } }"#)
+            .unwrap();
+    let positions = &assignment["Lost Woods"];
+    let main0 = RowPosition {
+        pal_idx: 2,
+        pal_high: false,
+    };
+    let aux2 = RowPosition {
+        pal_idx: 6,
+        pal_high: true,
+    };
+    let animated = RowPosition {
+        pal_idx: 7,
+        pal_high: false,
+    };
+    assert_eq!(positions[&main0], 3);
+    assert_eq!(positions[&aux2], 12);
+    assert_eq!(positions[&animated], 20);
+    let json = serde_json::to_string(&assignment).unwrap();
+    assert_eq!(
+        serde_json::from_str::<PaletteAssignment>(&json).unwrap(),
+        assignment
+    );
+    assert!(serde_json::from_str::<RowPosition>(r#""Main 5""#).is_err());
+
+    assert_eq!(RowPosition::for_category(PaletteCategory::Main).len(), 5);
+    assert_eq!(RowPosition::for_category(PaletteCategory::Aux).len(), 6);
+    assert_eq!(
+        RowPosition::for_category(PaletteCategory::Animated).len(),
+        1
+    );
+    assert!(RowPosition::for_category(PaletteCategory::HUD).is_empty());
+}
+
+#[test]
+fn solver_separates_palettes_of_neighboring_areas() {
+    let mut project = TestProject::new("palette-slots-solve");
+    add_palettes(&mut project, &[1, 2, 3, 4], PaletteCategory::Main);
+    add_palettes(&mut project, &[5], PaletteCategory::Aux);
+    // Maps 00 and 02 are neighbors; 03 is elsewhere.
+    add_area(&mut project, "A", 0x00, &[1, 2]);
+    add_area(&mut project, "B", 0x02, &[3, 5]);
+    add_area(&mut project, "C", 0x03, &[4]);
+
+    let (assignment, problems) = solve(&project.state, &"Base".to_string()).unwrap();
+    assert!(problems.is_empty(), "{:?}", problems);
+    let position = |area: &str, id: PaletteId| {
+        let positions = &assignment[area];
+        *positions.iter().find(|(_, &p)| p == id).unwrap().0
+    };
+    assert_ne!(position("A", 1), position("A", 2));
+    assert_ne!(position("B", 3), position("A", 1));
+    assert_ne!(position("B", 3), position("A", 2));
+    assert!(position("B", 5).pal_high);
+    assert!(!position("C", 4).pal_high);
+}
+
+#[test]
+fn solver_reports_palettes_that_dont_fit() {
+    let mut project = TestProject::new("palette-slots-full");
+    add_palettes(&mut project, &[1, 2, 3, 4, 5], PaletteCategory::Main);
+    // With the default palette (0), that's six main palettes for five positions:
+    add_area(&mut project, "A", 0x00, &[1, 2, 3, 4, 5]);
+
+    let (assignment, problems) = solve(&project.state, &"Base".to_string()).unwrap();
+    assert_eq!(problems.len(), 1, "{:?}", problems);
+    assert_eq!(assignment["A"].len(), 5);
+}
+
+#[test]
+fn area_positions_are_edited_and_undone() {
+    let mut project = TestProject::new("palette-slots-edit");
+    add_palettes(&mut project, &[1], PaletteCategory::Aux);
+    add_area(&mut project, "A", 0x00, &[1]);
+    let theme = "Base".to_string();
+    let aux2 = RowPosition {
+        pal_idx: 5,
+        pal_high: true,
+    };
+
+    project.send(Message::SolvePaletteSlots(theme.clone()));
+    let solved = load_assignment(&project.state, &theme).unwrap();
+    assert!(solved["A"].values().any(|&id| id == 1));
+
+    project.send(Message::SetPaletteSlot {
+        theme: theme.clone(),
+        area: "A".to_string(),
+        palette_id: 1,
+        position: Some(aux2),
+    });
+    assert_eq!(
+        load_assignment(&project.state, &theme).unwrap()["A"][&aux2],
+        1
+    );
+
+    // Aux palettes can't take a main position:
+    project.send(Message::SetPaletteSlot {
+        theme: theme.clone(),
+        area: "A".to_string(),
+        palette_id: 1,
+        position: Some(RowPosition {
+            pal_idx: 2,
+            pal_high: false,
+        }),
+    });
+    assert_eq!(
+        load_assignment(&project.state, &theme).unwrap()["A"][&aux2],
+        1
+    );
+
+    project.undo();
+    assert_eq!(load_assignment(&project.state, &theme).unwrap(), solved);
+    project.undo();
+    assert!(load_assignment(&project.state, &theme).unwrap().is_empty());
+    project.redo();
+    assert_eq!(load_assignment(&project.state, &theme).unwrap(), solved);
+}
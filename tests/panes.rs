@@ -0,0 +1,54 @@
+mod common;
+
+use common::TestProject;
+use iced::widget::pane_grid::{Node, ResizeEvent};
+use z3_overworld_editor::{
+    message::Message,
+    panes::{pane_layout, pane_state, PaneLayout},
+    state::{GlobalConfig, SidePanelView},
+};
+
+#[test]
+fn layout_round_trips_through_panes() {
+    let layout = PaneLayout {
+        main_ratio: 0.6,
+        side_ratio: 0.3,
+    };
+    assert_eq!(pane_layout(&pane_state(layout)), Some(layout));
+}
+
+#[test]
+fn three_pane_layout_is_toggled_resized_and_saved() {
+    let mut project = TestProject::new("panes");
+    project.state.side_panel_view = SidePanelView::Area;
+    assert!(project.state.side_area_shown());
+
+    project.send(Message::SetThreePaneLayout(true));
+    // The side panel shows the tileset, next to the side area's own pane:
+    assert!(matches!(
+        project.state.side_panel_view,
+        SidePanelView::Tileset
+    ));
+    assert!(project.state.side_area_shown());
+
+    let Node::Split { id, .. } = project.state.panes.layout() else {
+        panic!("panes aren't split");
+    };
+    let split = *id;
+    project.send(Message::PaneResized(ResizeEvent { split, ratio: 0.7 }));
+    project.save();
+    let config: GlobalConfig =
+        serde_json::from_str(&std::fs::read_to_string(project.dir.join("config.json")).unwrap())
+            .unwrap();
+    assert_eq!(
+        config.pane_layout,
+        Some(PaneLayout {
+            main_ratio: 0.7,
+            ..PaneLayout::default()
+        })
+    );
+
+    project.send(Message::SetThreePaneLayout(false));
+    assert!(project.state.global_config.pane_layout.is_none());
+    assert!(!project.state.side_area_shown());
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use common::{open_project, TestProject};
+use z3_overworld_editor::{
+    message::Message,
+    persist::read_project_files,
+    state::{new_editor_state, Dialogue},
+};
+
+#[test]
+fn progress_counts_palettes_and_areas() {
+    let project = TestProject::new("project-load-progress");
+    let mut counts = vec![];
+    read_project_files(&project.project_dir(), |palettes, areas| {
+        counts.push((palettes, areas));
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(counts, vec![(1, 0), (1, 1)]);
+}
+
+#[test]
+fn current_project_is_kept_until_the_new_one_loads() {
+    let mut project = TestProject::new("project-load-keep");
+    let other = TestProject::new("project-load-other");
+    let old_dir = project.state.global_config.project_dir.clone();
+
+    project.send(Message::ProjectOpened(Some(other.project_dir())));
+    let load = project.state.project_load.as_ref().unwrap();
+    assert_eq!(load.dir, other.project_dir());
+    assert_eq!(project.state.global_config.project_dir, old_dir);
+
+    project.send(Message::ProjectLoadProgress {
+        dir: other.project_dir(),
+        palettes: 1,
+        areas: 0,
+    });
+    assert_eq!(project.state.project_load.as_ref().unwrap().palettes, 1);
+    // Other messages are ignored while loading:
+    project.send(Message::AddPaletteDialogue);
+    assert!(project.state.dialogue.is_none());
+
+    // A cancelled load is ignored when it finishes:
+    project.send(Message::CancelProjectLoad);
+    assert!(project.state.project_load.is_none());
+    let files = read_project_files(&other.project_dir(), |_, _| Ok(()));
+    project.send(Message::ProjectLoaded {
+        dir: other.project_dir(),
+        files: files.map(Box::new).map_err(|e| e.to_string()),
+    });
+    assert_eq!(project.state.global_config.project_dir, old_dir);
+}
+
+#[test]
+fn failed_load_shows_an_error() {
+    let mut project = TestProject::new("project-load-error");
+    let missing = project.dir.join("Missing");
+    project.send(Message::ProjectOpened(Some(missing.clone())));
+    let files = read_project_files(&missing, |_, _| Ok(()))
+        .map(Box::new)
+        .map_err(|e| e.to_string());
+    assert!(files.is_err());
+    project.send(Message::ProjectLoaded {
+        dir: missing,
+        files,
+    });
+    assert!(project.state.project_load.is_none());
+    assert!(matches!(project.state.dialogue, Some(Dialogue::Error(_))));
+    assert_eq!(
+        project.state.global_config.project_dir,
+        Some(project.project_dir())
+    );
+}
+
+#[test]
+fn project_opens_once_loaded() {
+    let other = TestProject::new("project-load-open");
+    let mut state = new_editor_state(other.dir.join("config2.json"));
+    open_project(&mut state, other.project_dir());
+    assert!(state.project_load.is_none());
+    assert_eq!(state.global_config.project_dir, Some(other.project_dir()));
+    assert_eq!(state.area_names, other.state.area_names);
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::{
+    message::Message,
+    persist::{rebuild_area_pngs, PngSources, RebuildScope},
+    state::Dialogue,
+};
+
+#[test]
+fn rebuild_only_stale_pngs() {
+    let mut project = TestProject::new("rebuild-stale");
+    let png_path = project.area_path("Example", "Base").with_extension("png");
+    std::fs::remove_file(&png_path).unwrap();
+    let stale = RebuildScope {
+        only_stale: true,
+        ..RebuildScope::default()
+    };
+    assert_eq!(rebuild_area_pngs(&mut project.state, &stale).unwrap(), 1);
+    assert!(png_path.exists());
+    assert_eq!(rebuild_area_pngs(&mut project.state, &stale).unwrap(), 0);
+
+    let other_theme = RebuildScope {
+        theme: Some("Other".to_string()),
+        ..RebuildScope::default()
+    };
+    assert_eq!(
+        rebuild_area_pngs(&mut project.state, &other_theme).unwrap(),
+        0
+    );
+}
+
+#[test]
+fn progress_counts_areas_and_palettes() {
+    let mut project = TestProject::new("project-rebuild-progress");
+    project.send(Message::AddArea {
+        name: "Other".to_string(),
+        size: (1, 1),
+    });
+    project.save();
+    let palette_dir = project.project_dir().join("Palettes");
+    std::fs::remove_file(palette_dir.join("Default-tiles.png")).unwrap();
+
+    let scope = RebuildScope {
+        palettes: true,
+        ..RebuildScope::default()
+    };
+    let sources = PngSources::new(&project.state, &scope).unwrap();
+    assert_eq!(sources.total(), 3);
+    let mut counts = vec![];
+    let written = sources
+        .rebuild(|done| {
+            counts.push(done);
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(written, 3);
+    assert_eq!(counts, vec![1, 2, 3]);
+    assert!(palette_dir.join("Default-tiles.png").exists());
+
+    // The rebuild stops when the progress callback fails (e.g. when cancelled):
+    let result = sources.rebuild(|done| {
+        if done == 1 {
+            anyhow::bail!("cancelled");
+        }
+        Ok(())
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn rebuild_runs_in_the_background_and_can_be_cancelled() {
+    let mut project = TestProject::new("project-rebuild-cancel");
+    project.send(Message::RebuildProjectDialogue);
+    project.send(Message::StartRebuildProject);
+    let rebuild = project.state.project_rebuild.as_ref().unwrap();
+    assert_eq!((rebuild.done, rebuild.total), (0, 1));
+    project.send(Message::RebuildProjectProgress(1));
+    assert_eq!(project.state.project_rebuild.as_ref().unwrap().done, 1);
+
+    // Cancelling goes back to the choice of what to rebuild:
+    project.send(Message::CancelRebuildProject);
+    assert!(project.state.project_rebuild.is_none());
+    assert!(matches!(
+        project.state.dialogue,
+        Some(Dialogue::RebuildProject { .. })
+    ));
+
+    project.send(Message::StartRebuildProject);
+    project.send(Message::RebuildProjectFinished(Ok(1)));
+    assert!(project.state.project_rebuild.is_none());
+    assert!(project.state.dialogue.is_none());
+
+    project.send(Message::RebuildProjectDialogue);
+    project.send(Message::StartRebuildProject);
+    project.send(Message::RebuildProjectFinished(
+        Err("disk full".to_string()),
+    ));
+    assert!(matches!(
+        &project.state.dialogue,
+        Some(Dialogue::Error(e)) if e == "disk full"
+    ));
+}
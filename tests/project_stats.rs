@@ -0,0 +1,72 @@
+mod common;
+
+use common::{read_json, TestProject};
+use iced::Point;
+use z3_overworld_editor::{
+    message::Message,
+    project_stats::{project_stats, CollisionStats, StatsFormat},
+    state::{AreaPosition, Flip, Tile, TileBlock},
+};
+
+#[test]
+fn statistics_are_exported() {
+    let mut project = TestProject::new("project-stats");
+    // Give tiles 1 and 2 graphics, and place only tile 1 (with collision type 3):
+    let mut pixels = [[0; 8]; 8];
+    pixels[4][4] = 1;
+    let tile = Tile {
+        pixels,
+        collision: 3,
+        ..project.state.palettes[0].tiles[1]
+    };
+    let area_id = project.state.main_area_id.clone();
+    project.send_all([
+        Message::TilesetBrush {
+            palette_id: 0,
+            coords: Point::new(1, 0),
+            selected_gfx: vec![vec![tile, tile]],
+        },
+        Message::AreaBrush {
+            position: AreaPosition::Main,
+            area_id,
+            coords: Point::new(3, 4),
+            selection: TileBlock {
+                size: (1, 1),
+                palettes: vec![vec![0]],
+                tiles: vec![vec![1]],
+                flips: vec![vec![Flip::None]],
+                mask: None,
+            },
+            palette_only: false,
+        },
+    ]);
+    project.save();
+    project.send(Message::AddTheme("Dark".to_string()));
+
+    let stats = project_stats(&project.state).unwrap();
+    assert_eq!(stats.areas.len(), 2);
+    assert_eq!(stats.areas[0].size, (2, 2));
+    assert_eq!(stats.areas[0].palettes, vec![0]);
+    let palette = &stats.palettes[0];
+    assert!(palette.tile_count >= 2);
+    assert!(palette.unused_tiles.contains(&2));
+    assert!(!palette.unused_tiles.contains(&1));
+    // The tile is placed in both themes, as the new theme is a copy:
+    assert!(stats.collision.contains(&CollisionStats {
+        collision: 3,
+        tiles: 2
+    }));
+    let placed: usize = stats.collision.iter().map(|c| c.tiles).sum();
+    assert_eq!(placed, 2 * 4 * 32 * 32);
+
+    project.send(Message::ExportStatistics(StatsFormat::Csv));
+    let csv = std::fs::read_to_string(project.project_dir().join("Statistics.csv")).unwrap();
+    assert!(csv.starts_with("category,subject,metric,value\n"));
+    assert!(csv.contains("area,Example/Dark,width,2\n"));
+    assert!(csv.contains("collision,3,tiles,2\n"));
+
+    project.send(Message::ExportStatistics(StatsFormat::Json));
+    let json: serde_json::Value = read_json(&project.project_dir().join("Statistics.json"));
+    assert_eq!(json["areas"][1]["theme"], "Dark");
+    assert_eq!(json["collision"][0]["tiles"], 2 * 4 * 32 * 32 - 2);
+}
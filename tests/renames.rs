@@ -0,0 +1,61 @@
+mod common;
+
+use common::{read_json, TestProject};
+use z3_overworld_editor::{
+    message::Message,
+    renames::{RenameKind, RenameRecord},
+    state::Dialogue,
+};
+
+fn renamed(records: &[RenameRecord]) -> Vec<(RenameKind, &str, &str)> {
+    records
+        .iter()
+        .map(|r| (r.kind, r.old_name.as_str(), r.new_name.as_str()))
+        .collect()
+}
+
+#[test]
+fn renames_are_recorded_when_enabled() {
+    let mut project = TestProject::new("renames");
+    let manifest = project.project_dir().join("Renames.json");
+
+    // Renames aren't recorded by default:
+    project.send(Message::RenamePalette {
+        id: 0,
+        name: "Grass".to_string(),
+    });
+    assert!(!manifest.exists());
+
+    project.send(Message::SetRecordRenames(true));
+    project.send(Message::RenamePalette {
+        id: 0,
+        name: "Forest".to_string(),
+    });
+    project.send(Message::EditArea {
+        old_name: "Example".to_string(),
+        new_name: "Meadow".to_string(),
+    });
+    let records: Vec<RenameRecord> = read_json(&manifest);
+    assert_eq!(
+        renamed(&records),
+        vec![
+            (RenameKind::Palette, "Grass", "Forest"),
+            (RenameKind::Area, "Example", "Meadow"),
+        ]
+    );
+    assert!(records.iter().all(|r| r.time > 0));
+
+    project.send(Message::RenameHistoryDialogue);
+    let Some(Dialogue::RenameHistory(history)) = &project.state.dialogue else {
+        panic!("rename history not shown");
+    };
+    assert_eq!(history, &records);
+
+    // The setting is saved with the project, and undone like other project settings:
+    project.save();
+    let metadata: serde_json::Value = read_json(&project.project_dir().join("Project.json"));
+    assert_eq!(metadata["record_renames"], true);
+    project.send(Message::SetRecordRenames(false));
+    project.undo();
+    assert!(project.state.project_metadata.record_renames);
+}
@@ -0,0 +1,90 @@
+use std::path::PathBuf;
+
+use z3_overworld_editor::{
+    export::Exporter,
+    import::{
+        check_base_rom, palette_slots,
+        rom_folder::{apply_folder_files, find_rom},
+        Constants, PaletteGroup, Rom, RomData,
+    },
+    state::new_editor_state,
+};
+
+fn rom_folder(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("z3oe-test-{}-{}", name, std::process::id()));
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+    std::fs::create_dir_all(dir.join("Palettes")).unwrap();
+    dir
+}
+
+// A blank ROM that is detected as the US version.
+fn write_rom(path: &PathBuf) {
+    let mut rom = vec![0; 0x200000];
+    rom[0x6792..0x6794].copy_from_slice(&0xCA85u16.to_le_bytes());
+    std::fs::write(path, rom).unwrap();
+}
+
+#[test]
+fn folder_rom_is_found() {
+    let dir = rom_folder("rom-folder-find-rom");
+    assert!(find_rom(&dir).is_err());
+    write_rom(&dir.join("Hack.SFC"));
+    assert_eq!(find_rom(&dir).unwrap(), dir.join("Hack.SFC"));
+    write_rom(&dir.join("Other.smc"));
+    let e = find_rom(&dir).unwrap_err();
+    assert!(e.to_string().contains("More than one ROM"));
+}
+
+#[test]
+fn folder_files_of_the_wrong_size_are_rejected() {
+    let dir = rom_folder("rom-folder-bad-files");
+    write_rom(&dir.join("Hack.sfc"));
+    std::fs::write(dir.join("Map16.bin"), vec![0; 12]).unwrap();
+    let e = RomData::read(&dir).unwrap_err();
+    assert!(format!("{:#}", e).contains("Map16.bin has the wrong size"));
+
+    std::fs::write(dir.join("Map16.bin"), vec![0; 16]).unwrap();
+    std::fs::write(dir.join("Palettes").join("Main.bin"), vec![0; 0x1000]).unwrap();
+    let e = RomData::read(&dir).unwrap_err();
+    assert!(format!("{:#}", e).contains("Main.bin has the wrong size"));
+}
+
+#[test]
+fn palette_rows_are_written_at_their_own_addresses() {
+    let dir = rom_folder("rom-folder-palettes");
+    write_rom(&dir.join("Hack.sfc"));
+    // The first two rows of the aux palettes:
+    let colors: Vec<u8> = (1..=28).collect();
+    std::fs::write(dir.join("Palettes").join("Aux.bin"), &colors).unwrap();
+    let mut rom = Rom::new(std::fs::read(dir.join("Hack.sfc")).unwrap());
+    let constants = Constants::auto(&rom).unwrap();
+    apply_folder_files(&mut rom, &constants, &dir).unwrap();
+    let rows: Vec<_> = palette_slots(&constants)
+        .into_iter()
+        .filter(|(slot, _, _)| slot.group == PaletteGroup::Aux)
+        .collect();
+    for (i, &(_, addr, size)) in rows.iter().take(2).enumerate() {
+        let start = addr.0 as usize;
+        assert_eq!(
+            rom.data[start..start + size * 2],
+            colors[i * 14..(i + 1) * 14]
+        );
+    }
+    let (_, addr, _) = rows[2];
+    assert_eq!(rom.data[addr.0 as usize], 0);
+}
+
+#[test]
+fn rom_folders_are_rejected_as_base_roms() {
+    let dir = rom_folder("rom-folder-base-rom");
+    write_rom(&dir.join("Hack.sfc"));
+    let state = new_editor_state(dir.join("config.json"));
+    let e = check_base_rom(&state, &dir).unwrap_err();
+    assert!(e.to_string().contains("is a ROM folder"));
+    let output = dir.join("Out.sfc");
+    let e = Exporter::export(&state, &dir, &output, &"Base".to_string()).unwrap_err();
+    assert!(e.to_string().contains("is a ROM folder"));
+    assert!(!output.exists());
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use common::{rom::blank_rom, TestProject};
+use z3_overworld_editor::{
+    import::{parse_map_ids, ImportMode, ImportStage, Importer, RomData},
+    message::Message,
+    state::Dialogue,
+};
+
+fn select_rom(project: &mut TestProject) {
+    let path = project.project_dir().join("bad.sfc");
+    std::fs::write(&path, vec![0; 0x1000]).unwrap();
+    project.state.rom_path = Some(path);
+}
+
+fn start_import(project: &mut TestProject) {
+    select_rom(project);
+    project.send(Message::ImportROMProgress);
+    project.send(Message::ImportROM);
+}
+
+#[test]
+fn failed_read_is_reported_and_rolled_back() {
+    let mut project = TestProject::new("rom-import-failed");
+    start_import(&mut project);
+    let import = project.state.rom_import.as_ref().unwrap();
+    assert_eq!(
+        import.progress(&project.state),
+        (ImportStage::ReadingRom, 0.0)
+    );
+    assert_eq!(project.state.undo_stack.len(), 1);
+
+    let path = project.state.rom_path.clone().unwrap();
+    let e = RomData::read(&path).unwrap_err();
+    project.send(Message::ImportROMRead(Err(format!("{:#}", e))));
+    assert!(project.state.rom_import.is_none());
+    assert!(matches!(project.state.dialogue, Some(Dialogue::Error(_))));
+    // Nothing was imported, so there's nothing to undo:
+    assert!(project.state.undo_stack.is_empty());
+}
+
+#[test]
+fn import_can_be_cancelled() {
+    let mut project = TestProject::new("rom-import-cancel");
+    start_import(&mut project);
+    // The project can't be changed while the import is running:
+    project.send(Message::AddArea {
+        name: "Other".to_string(),
+        size: (1, 1),
+    });
+    assert_eq!(project.state.area_names, vec!["Example".to_string()]);
+
+    project.send(Message::CancelImportROM);
+    assert!(project.state.rom_import.is_none());
+    assert!(project.state.dialogue.is_none());
+    assert!(project.state.undo_stack.is_empty());
+
+    // The result of reading the ROM is ignored once cancelled:
+    project.send(Message::ImportROMRead(Err("too late".to_string())));
+    assert!(project.state.dialogue.is_none());
+    project.send(Message::ImportROMStep);
+    assert!(project.state.rom_import.is_none());
+}
+
+#[test]
+fn map_ids_are_parsed_as_hex_lists_and_ranges() {
+    assert_eq!(parse_map_ids("").unwrap(), None);
+    assert_eq!(
+        parse_map_ids("00-02, 1B 40").unwrap(),
+        Some([0x00, 0x01, 0x02, 0x1B, 0x40].into())
+    );
+    assert!(parse_map_ids("1G").is_err());
+}
+
+#[test]
+fn preview_leaves_the_undo_history_alone() {
+    let mut project = TestProject::new("rom-import-preview");
+    project.send(Message::AddArea {
+        name: "Other".to_string(),
+        size: (1, 1),
+    });
+    select_rom(&mut project);
+    project.send(Message::SetImportMode(ImportMode::PalettesOnly));
+    project.send(Message::SetImportMaps("1B".to_string()));
+    project.send(Message::PreviewImport);
+    let import = project.state.rom_import.as_ref().unwrap();
+    assert!(import.is_preview());
+    assert!(matches!(
+        project.state.dialogue,
+        Some(Dialogue::ImportROMProgress)
+    ));
+
+    // Cancelling a preview doesn't undo anything:
+    project.send(Message::CancelImportROM);
+    assert!(project.state.rom_import.is_none());
+    assert_eq!(project.state.undo_stack.len(), 1);
+    assert_eq!(project.state.area_names.len(), 2);
+
+    project.send(Message::PreviewImport);
+    project.send(Message::ImportROMRead(Err("bad ROM".to_string())));
+    assert!(matches!(
+        &project.state.dialogue,
+        Some(Dialogue::Error(e)) if e == "bad ROM"
+    ));
+    assert_eq!(project.state.undo_stack.len(), 1);
+    assert_eq!(project.state.import_mode, ImportMode::PalettesOnly);
+    assert_eq!(project.state.import_maps, "1B");
+}
+
+#[test]
+fn unreadable_areas_are_not_overwritten() {
+    let mut project = TestProject::new("rom-import-unreadable");
+    let path = project.dir.join("base.sfc");
+    std::fs::write(&path, blank_rom().data).unwrap();
+    Importer::import(&mut project.state, &path, ImportMode::Full).unwrap();
+
+    let area_path = project.area_path("00 Light World", "Base");
+    std::fs::write(&area_path, "{ corrupt").unwrap();
+    let e = Importer::import(&mut project.state, &path, ImportMode::MapsOnly).unwrap_err();
+    assert!(format!("{:#}", e).contains("Unable to load area 00 Light World/Base"));
+    assert_eq!(std::fs::read_to_string(&area_path).unwrap(), "{ corrupt");
+}
@@ -0,0 +1,74 @@
+use std::path::PathBuf;
+
+use z3_overworld_editor::{
+    import::{PcAddr, Rom, RomInfo, RomVariant, SnesAddr, Tile8},
+    state::Flip,
+};
+
+// A minimal ROM that's detected as the given variant (by the code at the graphics pointers, or
+// for ZScream, the pointer to its expanded 16x16 tiles).
+fn write_rom(name: &str, variant: RomVariant) -> PathBuf {
+    let mut data = vec![0; 0x200000];
+    match variant {
+        RomVariant::JP => data[0x67D2..0x67D4].copy_from_slice(&0xCA85u16.to_le_bytes()),
+        RomVariant::US => data[0x6792..0x6794].copy_from_slice(&0xCA85u16.to_le_bytes()),
+        RomVariant::ZScream => data[0x0865..0x0868].copy_from_slice(&[0x00, 0x80, 0xBD]),
+    }
+    let path =
+        std::env::temp_dir().join(format!("z3-rom-info-{}-{}.sfc", name, std::process::id()));
+    std::fs::write(&path, data).unwrap();
+    path
+}
+
+#[test]
+fn layout_differences_between_variants() {
+    let jp_path = write_rom("jp", RomVariant::JP);
+    let us_path = write_rom("us", RomVariant::US);
+    let jp = RomInfo::read(&jp_path).unwrap();
+    let us = RomInfo::read(&us_path).unwrap();
+    std::fs::remove_file(jp_path).unwrap();
+    std::fs::remove_file(us_path).unwrap();
+
+    assert_eq!(jp.variant, RomVariant::JP);
+    assert_eq!(us.variant, RomVariant::US);
+    assert!(jp.layout_differences(&jp.clone()).is_empty());
+    let differences = jp.layout_differences(&us);
+    assert_eq!(differences[0], "ROM format: JP -> US");
+    assert!(differences.contains(&"tiles32_tr_addr: $03B3C0 -> $03B400".to_string()));
+    assert!(differences.contains(&"tiles32_cnt: 8828 -> 8864".to_string()));
+    assert!(!differences.iter().any(|d| d.starts_with("tiles16_addr")));
+
+    let zscream_path = write_rom("zscream", RomVariant::ZScream);
+    let zscream = RomInfo::read(&zscream_path).unwrap();
+    std::fs::remove_file(zscream_path).unwrap();
+    assert_eq!(zscream.variant, RomVariant::ZScream);
+    let differences = us.layout_differences(&zscream);
+    assert_eq!(differences[0], "ROM format: US -> ZScream");
+    assert!(differences.contains(&"tiles16_addr: $0F8000 -> $BD8000".to_string()));
+}
+
+#[test]
+fn rom_addresses_and_tile_words_round_trip() {
+    for snes in [0x008000, 0x0F8000, 0x02F94D, 0x3FFFFF] {
+        let pc: PcAddr = SnesAddr(snes).into();
+        assert_eq!(SnesAddr::from(pc).0, snes);
+    }
+    let mut rom = Rom::new(vec![0; 0x10000]);
+    rom.write_u24(PcAddr(0x1234), 0x0BC123).unwrap();
+    assert_eq!(rom.read_u24(PcAddr(0x1234)).unwrap(), 0x0BC123);
+    assert!(rom.write_u16(PcAddr(0xFFFF), 0).is_err());
+
+    let word = Tile8 {
+        gfx_char: 0x1A5,
+        pal_idx: 6,
+        priority: true,
+        flip: Flip::Vertical,
+    }
+    .to_vram_tilemap_word();
+    let tile = Tile8::from_vram_tilemap_word(word);
+    assert_eq!(
+        (tile.gfx_char, tile.pal_idx, tile.priority, tile.flip),
+        (0x1A5, 6, true, Flip::Vertical)
+    );
+    assert_eq!(tile.to_vram_tilemap_word(), word);
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use common::{key_press, TestProject};
+use iced::Vector;
+use z3_overworld_editor::{
+    message::Message,
+    state::{AreaPosition, Guide},
+};
+
+#[test]
+fn guides_toggle_per_area() {
+    let mut project = TestProject::new("guides");
+    assert!(project.state.show_rulers);
+    let toggle = |guide| Message::ToggleGuide {
+        area: "Example".to_string(),
+        guide,
+    };
+    project.send_all([
+        toggle(Guide::Vertical(12)),
+        toggle(Guide::Horizontal(40)),
+        toggle(Guide::Vertical(12)),
+    ]);
+    assert_eq!(project.state.guides["Example"], vec![Guide::Horizontal(40)]);
+
+    project.send(Message::AreaScrolled {
+        position: AreaPosition::Side,
+        offset: Vector::new(30.0, 64.0),
+    });
+    assert_eq!(
+        project.state.area_scroll(AreaPosition::Side),
+        Vector::new(30.0, 64.0)
+    );
+    assert_eq!(project.state.area_scroll(AreaPosition::Main), Vector::ZERO);
+
+    project.send(key_press("u"));
+    assert!(!project.state.show_rulers);
+}
@@ -0,0 +1,21 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::message::Message;
+
+#[test]
+fn rename_theme_moves_png() {
+    let mut project = TestProject::new("rename-theme");
+    let area_dir = project.area_path("Example", "Base");
+    let area_dir = area_dir.parent().unwrap().to_owned();
+    assert!(area_dir.join("Base.png").exists());
+    project.send(Message::RenameTheme {
+        old_name: "Base".to_string(),
+        new_name: "Dark".to_string(),
+    });
+    assert!(project.state.dialogue.is_none());
+    assert!(area_dir.join("Dark.json").exists());
+    assert!(area_dir.join("Dark.png").exists());
+    assert!(!area_dir.join("Base.json").exists());
+    assert!(!area_dir.join("Base.png").exists());
+}
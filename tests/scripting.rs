@@ -0,0 +1,81 @@
+mod common;
+
+use common::TestProject;
+use iced::widget::text_editor;
+use z3_overworld_editor::{
+    message::Message,
+    scripting::run_script,
+    state::{AreaPosition, Flip},
+};
+
+#[test]
+fn script_changes_are_applied_and_undone_together() {
+    let mut project = TestProject::new("scripting");
+    project.send(Message::AddTheme("Winter".to_string()));
+    project.send(Message::SelectTheme(AreaPosition::Main, "Base".to_string()));
+    let script = r#"
+        for area in areas() {
+            for_each_screen(area, |sx, sy| {
+                set_tile(area, sx * 32 + 1, sy * 32 + 2, 9);
+            });
+        }
+        set_flip("Example/Winter", 0, 0, 3);
+        set_color(0, 1, [31, 0, 4]);
+        set_pixel(0, 2, 3, 4, 5);
+        print(area_size("Example"));
+        print(get_tile("Example", 33, 2));
+    "#;
+    let result = run_script(&project.state, script);
+    assert_eq!(result.output, vec!["[2, 2]", "9"]);
+    // The two areas, a color, and a tile:
+    assert_eq!(result.changes.len(), 4);
+
+    project.send(Message::Batch(result.changes));
+    project.save();
+    let area = project.saved_area("Example", "Base");
+    for (x, y) in [(1, 2), (33, 2), (1, 34), (33, 34)] {
+        assert_eq!(area.get_tile(x, y).unwrap(), 9);
+    }
+    let winter = project.saved_area("Example", "Winter");
+    assert_ne!(winter.get_tile(1, 2).unwrap(), 9);
+    assert_eq!(winter.get_flip(0, 0).unwrap(), Flip::Both);
+    let palette = project.saved_palette("Default");
+    assert_eq!(palette.colors[1], [31, 0, 4]);
+    assert_eq!(palette.tiles[2].pixels[4][3], 5);
+
+    project.undo();
+    project.save();
+    assert_ne!(
+        project
+            .saved_area("Example", "Base")
+            .get_tile(1, 2)
+            .unwrap(),
+        9
+    );
+    assert_ne!(project.saved_palette("Default").colors[1], [31, 0, 4]);
+}
+
+#[test]
+fn failed_scripts_make_no_changes() {
+    let mut project = TestProject::new("scripting-error");
+    let result = run_script(
+        &project.state,
+        r#"
+            print("start");
+            set_tile("Example", 0, 0, 1);
+            set_tile("Nowhere", 0, 0, 1);
+        "#,
+    );
+    assert!(result.changes.is_empty());
+    assert_eq!(result.output[0], "start");
+    assert!(result.output[1].contains("unknown area \"Nowhere\""));
+
+    project.state.script = text_editor::Content::with_text("set_tile(");
+    project.send(Message::RunScript);
+    assert!(project
+        .state
+        .script_output
+        .last()
+        .unwrap()
+        .starts_with("Error"));
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::{
+    message::Message,
+    secrets::{item_id, item_name, secret_at, Secret},
+    state::{AreaPosition, SidePanelView},
+};
+
+#[test]
+fn items_are_named_and_markers_cover_a_16x16_tile() {
+    assert_eq!(item_name(0x07), "Blue rupee");
+    assert_eq!(item_name(0x42), "Item 42");
+    assert_eq!(item_id("Bee"), Some(0x03));
+    let secrets = [Secret {
+        position: (4, 6),
+        item: 0x07,
+    }];
+    assert_eq!(secrets[0].label(), "S07");
+    assert_eq!(secret_at(&secrets, 5, 7), Some(0));
+    assert_eq!(secret_at(&secrets, 6, 6), None);
+}
+
+#[test]
+fn secrets_are_edited_and_saved_with_the_area() {
+    let mut project = TestProject::new("secrets");
+    let area_id = project.state.main_area_id.clone();
+    project.send(Message::ShowSecrets(true));
+    assert!(matches!(
+        project.state.side_panel_view,
+        SidePanelView::Secrets
+    ));
+    assert!(project.state.show_secrets);
+
+    // Secrets are placed on the 16x16 tile grid:
+    project.send(Message::InsertSecret {
+        position: AreaPosition::Main,
+        area_id: area_id.clone(),
+        idx: 0,
+        secret: Secret {
+            position: (5, 9),
+            item: 0x01,
+        },
+    });
+    assert_eq!(project.state.main_area().secrets[0].position, (4, 8));
+    project.send(Message::SetSecret {
+        position: AreaPosition::Main,
+        area_id: area_id.clone(),
+        idx: 0,
+        secret: Secret {
+            position: (20, 22),
+            item: 0x14,
+        },
+    });
+    project.save();
+    let saved = project.saved_area(&area_id.area, &area_id.theme);
+    assert_eq!(
+        saved.secrets,
+        vec![Secret {
+            position: (20, 22),
+            item: 0x14
+        }]
+    );
+
+    project.send(Message::RemoveSecret {
+        position: AreaPosition::Main,
+        area_id: area_id.clone(),
+        idx: 0,
+    });
+    assert!(project.state.main_area().secrets.is_empty());
+    project.undo();
+    assert_eq!(project.state.main_area().secrets[0].item, 0x14);
+    project.undo();
+    assert_eq!(
+        project.state.main_area().secrets[0],
+        Secret {
+            position: (4, 8),
+            item: 0x01
+        }
+    );
+    project.undo();
+    assert!(project.state.main_area().secrets.is_empty());
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::{
+    clipboard::SelectionJson,
+    message::Message,
+    state::{Dialogue, Flip, TileBlock},
+};
+
+// A brush with a cell outside of its shape, which is left blank since it isn't copied.
+fn sample_block() -> TileBlock {
+    TileBlock {
+        size: (3, 2),
+        palettes: vec![vec![0; 3]; 2],
+        tiles: vec![vec![0x01, 0x02, 0x00], vec![0x11, 0x12, 0x1F]],
+        flips: vec![
+            vec![Flip::None, Flip::Horizontal, Flip::None],
+            vec![Flip::Vertical, Flip::None, Flip::Both],
+        ],
+        mask: Some(vec![vec![true, true, false], vec![true, true, true]]),
+    }
+}
+
+#[test]
+fn selection_json_round_trips() {
+    let mut project = TestProject::new("selection-json");
+    project.state.selected_tile_block = sample_block();
+    let text = SelectionJson::from_selection(&project.state)
+        .and_then(|selection| selection.to_text())
+        .unwrap();
+
+    // Each row is on its own line, for reading and diffing:
+    assert!(text.contains("\"palettes\": {\"0\":\"Default\"}"));
+    assert!(text.contains("    [\"0:$01\",\"0:$02 H\",null],\n"));
+    assert!(text.contains("    [\"0:$11 V\",\"0:$12\",\"0:$1F HV\"]\n"));
+
+    let block = SelectionJson::parse(&text)
+        .and_then(|selection| selection.to_block(&project.state))
+        .unwrap();
+    assert_eq!(block, sample_block());
+}
+
+#[test]
+fn invalid_selection_json_is_reported() {
+    let mut project = TestProject::new("selection-json-errors");
+    let brush = project.state.selected_tile_block.clone();
+
+    // Unknown palettes and malformed cells are rejected:
+    for rows in [r#"[["7:$01"]]"#, r#"[["0:$01 X"]]"#, r#"[["0:$01"], []]"#] {
+        let text = format!(
+            r#"{{"format": "z3-overworld-editor/selection", "version": 1, "rows": {}}}"#,
+            rows
+        );
+        project.send(Message::PastedSelectionJson(Some(text)));
+        assert!(matches!(project.state.dialogue, Some(Dialogue::Error(_))));
+        assert_eq!(project.state.selected_tile_block, brush);
+        project.state.dialogue = None;
+    }
+
+    project.send(Message::PastedSelectionJson(Some("not json".to_string())));
+    assert!(matches!(project.state.dialogue, Some(Dialogue::Error(_))));
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use common::{key_press, TestProject};
+use z3_overworld_editor::{
+    message::Message,
+    state::{AreaPosition, Focus, SidePanelView},
+};
+
+#[test]
+fn side_panel_hides_and_remembers_view() {
+    let mut project = TestProject::new("side-panel");
+    project.send(key_press("a"));
+    project.send(Message::Focus(Focus::Area(AreaPosition::Side)));
+    project.send(key_press("p"));
+    assert!(project.state.side_panel_hidden);
+    assert!(matches!(project.state.focus, Focus::None));
+
+    project.send(Message::ToggleSidePanel);
+    assert!(!project.state.side_panel_hidden);
+    assert!(matches!(project.state.side_panel_view, SidePanelView::Area));
+
+    // Choosing a side panel view shows the panel:
+    project.send(key_press("p"));
+    project.send(key_press("t"));
+    assert!(!project.state.side_panel_hidden);
+    assert!(matches!(
+        project.state.side_panel_view,
+        SidePanelView::Tileset
+    ));
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use common::TestProject;
+use iced::Point;
+use z3_overworld_editor::{
+    helpers::{
+        format_rgb_hex, format_snes_color, parse_rgb_hex, parse_snes_color, snes_color_word,
+    },
+    message::Message,
+    state::{AreaPosition, Dialogue, Flip, TileBlock},
+};
+
+#[test]
+fn snes_color_words_round_trip() {
+    assert_eq!(snes_color_word([31, 31, 31]), 0x7FFF);
+    assert_eq!(snes_color_word([1, 2, 3]), 0x0C41);
+    assert_eq!(format_snes_color([31, 0, 0]), "$001F");
+    assert_eq!(parse_snes_color("$0C41"), Some([1, 2, 3]));
+    assert_eq!(parse_snes_color(" 0x7c00 "), Some([0, 0, 31]));
+    assert_eq!(parse_snes_color("3e0"), Some([0, 31, 0]));
+    // Words with the unused top bit set, or that aren't hex words, are rejected:
+    assert_eq!(parse_snes_color("$8000"), None);
+    assert_eq!(parse_snes_color("$12345"), None);
+    assert_eq!(parse_snes_color("$"), None);
+    assert_eq!(parse_snes_color("red"), None);
+}
+
+#[test]
+fn snes_color_entry_sets_selected_color() {
+    let mut project = TestProject::new("snes-color");
+    project.send(Message::SelectColor(0, 3));
+    project.send(Message::SetSnesColorText("$7C1F".to_string()));
+    assert_eq!(project.state.selected_color, [31, 0, 31]);
+
+    // Partial or invalid text is kept, without changing the color:
+    project.send(Message::SetSnesColorText("$7C1G".to_string()));
+    assert_eq!(project.state.selected_color, [31, 0, 31]);
+    assert_eq!(
+        project.state.snes_color_text,
+        Some(([31, 0, 31], "$7C1G".to_string()))
+    );
+
+    project.send(Message::PastedSnesColor(Some("0x001F".to_string())));
+    assert_eq!(project.state.selected_color, [31, 0, 0]);
+    project.send(Message::PastedSnesColor(Some("not a color".to_string())));
+    assert!(matches!(project.state.dialogue, Some(Dialogue::Error(_))));
+    assert_eq!(project.state.selected_color, [31, 0, 0]);
+}
+
+#[test]
+fn rgb_hex_colors_snap_to_5_bits() {
+    assert_eq!(format_rgb_hex([31, 0, 16]), "#FF0083");
+    assert_eq!(parse_rgb_hex("#FF0083"), Some([31, 0, 16]));
+    assert_eq!(parse_rgb_hex(" f8f8f8 "), Some([30, 30, 30]));
+    assert_eq!(parse_rgb_hex("#070809"), Some([1, 1, 1]));
+    assert_eq!(parse_rgb_hex("#FFF"), None);
+    assert_eq!(parse_rgb_hex("#GG0000"), None);
+}
+
+#[test]
+fn color_picker_and_eyedropper_set_selected_color() {
+    let mut project = TestProject::new("color-picker");
+    project.send(Message::SelectColor(0, 3));
+    project.send(Message::SetRgbHexText("#00FF00".to_string()));
+    assert_eq!(project.state.selected_color, [0, 31, 0]);
+
+    project.send(Message::PickColorHsv {
+        hue: 240.0,
+        saturation: 0.5,
+        value: 1.0,
+    });
+    assert_eq!(project.state.selected_color, [16, 16, 31]);
+    // The picked hue is kept for grays, which don't have one of their own:
+    project.send(Message::PickColorHsv {
+        hue: 240.0,
+        saturation: 0.0,
+        value: 0.5,
+    });
+    assert_eq!(project.state.selected_color, [16, 16, 16]);
+    assert_eq!(
+        project.state.picked_hsv,
+        Some(([16, 16, 16], (240.0, 0.0, 0.5)))
+    );
+
+    // Pick the color of a pixel of the area:
+    let area_id = project.state.main_area_id.clone();
+    project.send_all([
+        Message::BrushColor {
+            palette_id: 0,
+            color_idx: 5,
+            color: [4, 8, 12],
+        },
+        Message::BrushPixel {
+            palette_id: 0,
+            tile_idx: 1,
+            coords: Point::new(2, 3),
+            color_idx: 5,
+        },
+        Message::AreaBrush {
+            position: AreaPosition::Main,
+            area_id,
+            coords: Point::new(10, 4),
+            selection: TileBlock {
+                size: (1, 1),
+                palettes: vec![vec![0]],
+                tiles: vec![vec![1]],
+                flips: vec![vec![Flip::None]],
+                mask: None,
+            },
+            palette_only: false,
+        },
+    ]);
+    project.send(Message::ToggleEyedropper);
+    assert!(project.state.eyedropper);
+    project.send(Message::PickAreaColor {
+        position: AreaPosition::Main,
+        pixel: Point::new(10 * 8 + 2, 4 * 8 + 3),
+    });
+    assert_eq!(project.state.selected_color, [4, 8, 12]);
+    assert!(!project.state.eyedropper);
+}
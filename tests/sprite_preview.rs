@@ -0,0 +1,72 @@
+mod common;
+
+use common::TestProject;
+use iced::Point;
+use z3_overworld_editor::{
+    message::Message,
+    sprite_preview::{sprite_hidden_at, sprite_origin, sprite_raster, SPRITE_RASTER_SIZE},
+    state::{AreaPosition, Flip, Tile, TileBlock},
+};
+
+#[test]
+fn sprite_is_hidden_behind_priority_tiles() {
+    let mut project = TestProject::new("sprite-preview");
+    // A canopy tile with priority, transparent on its left half, next to a tile without:
+    let mut pixels = [[2; 8]; 8];
+    for row in &mut pixels {
+        row[..4].fill(0);
+    }
+    let tile = project.state.palettes[0].tiles[1];
+    let canopy = Tile {
+        pixels,
+        priority: true,
+        ..tile
+    };
+    let ground = Tile {
+        pixels: [[2; 8]; 8],
+        priority: false,
+        ..tile
+    };
+    let area_id = project.state.main_area_id.clone();
+    project.send_all([
+        Message::TilesetBrush {
+            palette_id: 0,
+            coords: Point::new(1, 0),
+            selected_gfx: vec![vec![canopy, ground]],
+        },
+        Message::AreaBrush {
+            position: AreaPosition::Main,
+            area_id,
+            coords: Point::new(3, 4),
+            selection: TileBlock {
+                size: (2, 1),
+                palettes: vec![vec![0, 0]],
+                tiles: vec![vec![1, 2]],
+                flips: vec![vec![Flip::None, Flip::None]],
+                mask: None,
+            },
+            palette_only: false,
+        },
+    ]);
+    let state = &project.state;
+    let (area, palettes, map) = (
+        state.main_area(),
+        &state.palettes,
+        &state.palettes_id_idx_map,
+    );
+    assert!(sprite_hidden_at(area, palettes, map, 29, 32));
+    assert!(!sprite_hidden_at(area, palettes, map, 25, 32));
+    assert!(!sprite_hidden_at(area, palettes, map, 33, 32));
+
+    // The sprite is centered on the cursor, and its outline's top row crosses both tiles:
+    let origin = sprite_origin(area, Point::new(32, 40));
+    assert_eq!(origin, Point::new(24, 32));
+    let data = sprite_raster(area, palettes, map, origin);
+    let alpha = |x: usize, y: usize| data[((y + 1) * SPRITE_RASTER_SIZE + x + 1) * 4 + 3];
+    assert_eq!(alpha(5, 0), 0);
+    assert_eq!(alpha(8, 0), 255);
+    assert_eq!(alpha(0, 0), 0);
+
+    // The sprite stays within the area:
+    assert_eq!(sprite_origin(area, Point::new(0, 511)), Point::new(0, 496));
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::TestProject;
+use iced::Point;
+use z3_overworld_editor::{
+    message::{Message, SelectionSource},
+    stamps::Stamp,
+    state::{Dialogue, SidePanelView},
+};
+
+#[test]
+fn stamps_are_saved_and_loaded_into_the_brush() {
+    let mut project = TestProject::new("stamps");
+    project.send(Message::ShowStamps(true));
+    assert!(matches!(
+        project.state.side_panel_view,
+        SidePanelView::Stamps
+    ));
+    assert!(project.state.stamps.is_empty());
+
+    project.send(Message::StartTileSelection(
+        Point::new(1, 0),
+        SelectionSource::Tileset,
+    ));
+    project.send(Message::EndTileSelection(Point::new(3, 0)));
+    let block = project.state.selected_tile_block.clone();
+    project.send(Message::SetStampName("House".to_string()));
+    project.send(Message::SaveStamp);
+    let dir = project.project_dir().join("Stamps");
+    assert!(dir.join("House.json").exists());
+    assert!(dir.join("House.png").exists());
+    assert_eq!(project.state.stamps.len(), 1);
+    assert!(project.state.stamps[0].preview.is_some());
+
+    // Stamps are read back from the project folder:
+    project.send(Message::ShowStamps(false));
+    project.state.stamps.clear();
+    project.send(Message::ShowStamps(true));
+    let stamp: &Stamp = &project.state.stamps[0];
+    assert_eq!(stamp.name, "House");
+    assert_eq!(stamp.block, block);
+
+    project.send(Message::StartTileSelection(
+        Point::new(0, 0),
+        SelectionSource::Tileset,
+    ));
+    project.send(Message::EndTileSelection(Point::new(0, 0)));
+    assert_ne!(project.state.selected_tile_block, block);
+    project.send(Message::LoadStamp("House".to_string()));
+    // (Tasks aren't run in tests, so the brush is set as loading the stamp would:)
+    project.send(Message::SetBrush(project.state.stamps[0].block.clone()));
+    assert_eq!(project.state.selected_tile_block, block);
+
+    project.send(Message::DeleteStamp("House".to_string()));
+    assert!(project.state.stamps.is_empty());
+    assert!(!dir.join("House.json").exists());
+    assert!(!dir.join("House.png").exists());
+}
+
+#[test]
+fn invalid_stamp_names_are_rejected() {
+    let mut project = TestProject::new("stamps-invalid");
+    project.send(Message::SetStampName("../House".to_string()));
+    project.send(Message::SaveStamp);
+    assert!(matches!(project.state.dialogue, Some(Dialogue::Error(_))));
+    assert!(!project.project_dir().join("Stamps").exists());
+}
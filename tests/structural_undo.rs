@@ -0,0 +1,97 @@
+mod common;
+
+use common::{brush, example_area_id, TestProject};
+use z3_overworld_editor::message::Message;
+
+#[test]
+fn deleted_area_is_restored_by_undo() {
+    let mut project = TestProject::new("undo-delete-area");
+    project.send_all([
+        brush(3, 4, 5),
+        Message::AddArea {
+            name: "Second".to_string(),
+            size: (1, 1),
+        },
+        Message::DeleteArea("Example".to_string()),
+    ]);
+    project.save();
+    assert_eq!(project.state.area_names, vec!["Second"]);
+    assert!(!project.area_path("Example", "Base").exists());
+
+    project.undo();
+    project.save();
+    assert_eq!(project.state.area_names, vec!["Example", "Second"]);
+    assert_eq!(
+        project
+            .saved_area("Example", "Base")
+            .get_tile(3, 4)
+            .unwrap(),
+        5
+    );
+
+    project.redo();
+    assert_eq!(project.state.area_names, vec!["Second"]);
+    assert!(!project.area_path("Example", "Base").exists());
+}
+
+#[test]
+fn deleted_theme_is_restored_by_undo() {
+    let mut project = TestProject::new("undo-delete-theme");
+    project.send_all([
+        Message::AddTheme("Night".to_string()),
+        Message::DeleteTheme("Night".to_string()),
+    ]);
+    assert_eq!(project.state.theme_names, vec!["Base"]);
+    assert!(!project.area_path("Example", "Night").exists());
+
+    project.undo();
+    assert_eq!(project.state.theme_names, vec!["Base", "Night"]);
+    assert!(project.area_path("Example", "Night").exists());
+}
+
+#[test]
+fn snapshot_restores_palettes_and_areas() {
+    // As when undoing a ROM import, which can't be run here without a ROM:
+    let mut project = TestProject::new("restore-snapshot");
+    let snapshot = project.state.snapshot().unwrap();
+    project.send_all([
+        Message::AddPalette {
+            name: "Extra".to_string(),
+            id: 7,
+        },
+        Message::AddArea {
+            name: "Second".to_string(),
+            size: (1, 1),
+        },
+        brush(3, 4, 5),
+    ]);
+    project.save();
+
+    project.send(Message::RestoreSnapshot(Box::new(snapshot)));
+    project.save();
+    assert!(!project.palette_path("Extra").exists());
+    assert_eq!(project.state.palettes.len(), 1);
+    assert_eq!(project.state.area_names, vec!["Example"]);
+    assert!(!project.area_path("Second", "Base").exists());
+    assert_eq!(project.state.main_area_id, example_area_id());
+    assert_eq!(
+        project
+            .saved_area("Example", "Base")
+            .get_tile(3, 4)
+            .unwrap(),
+        0
+    );
+
+    // Restoring a snapshot can itself be undone:
+    project.undo();
+    project.save();
+    assert_eq!(project.saved_palette("Extra").id, 7);
+    assert_eq!(project.state.area_names, vec!["Example", "Second"]);
+    assert_eq!(
+        project
+            .saved_area("Example", "Base")
+            .get_tile(3, 4)
+            .unwrap(),
+        5
+    );
+}
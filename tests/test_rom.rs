@@ -0,0 +1,24 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::{message::Message, state::Dialogue, test_rom::build_hash};
+
+#[test]
+fn build_hash_changes_with_edits() {
+    let mut project = TestProject::new("test-rom-hash");
+    let base_rom = project.project_dir().join("base.sfc");
+    std::fs::write(&base_rom, vec![0u8; 0x1000]).unwrap();
+    let theme = project.state.main_area().theme.clone();
+    let hash = build_hash(&project.state, &base_rom, &theme).unwrap();
+    assert_eq!(build_hash(&project.state, &base_rom, &theme).unwrap(), hash);
+
+    project.state.main_area_mut().bg_color = [1, 2, 3];
+    assert_ne!(build_hash(&project.state, &base_rom, &theme).unwrap(), hash);
+}
+
+#[test]
+fn running_without_an_emulator_shows_an_error() {
+    let mut project = TestProject::new("test-rom-setup");
+    project.send(Message::RunTestRom);
+    assert!(matches!(project.state.dialogue, Some(Dialogue::Error(_))));
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use common::TestProject;
+use iced::Point;
+use z3_overworld_editor::{
+    message::Message,
+    state::{AreaId, AreaPosition, Flip, TileBlock},
+};
+
+fn brush(theme: &str, x: u16, y: u16, tile: u16, palette_only: bool) -> Message {
+    Message::AreaBrush {
+        position: AreaPosition::Main,
+        area_id: AreaId {
+            area: "Example".to_string(),
+            theme: theme.to_string(),
+        },
+        coords: Point::new(x, y),
+        selection: TileBlock {
+            size: (2, 1),
+            palettes: vec![vec![0, 0]],
+            tiles: vec![vec![tile, tile + 1]],
+            flips: vec![vec![Flip::None, Flip::Horizontal]],
+            mask: None,
+        },
+        palette_only,
+    }
+}
+
+#[test]
+fn brush_strokes_are_applied_to_both_themes() {
+    let mut project = TestProject::new("theme-sync");
+    project.send(Message::AddTheme("Winter".to_string()));
+    project.send(Message::SelectTheme(AreaPosition::Main, "Base".to_string()));
+    project.send(Message::SetThemeSync(Some("Winter".to_string())));
+    assert_eq!(
+        project.state.theme_sync,
+        Some(("Base".to_string(), "Winter".to_string()))
+    );
+
+    let undo_len = project.state.undo_stack.len();
+    project.send(brush("Base", 3, 4, 5, false));
+    // Repeats of the same stroke (e.g. while dragging over a tile) are ignored:
+    project.send(brush("Base", 3, 4, 5, false));
+    assert_eq!(project.state.undo_stack.len(), undo_len + 1);
+    project.save();
+    for theme in ["Base", "Winter"] {
+        let area = project.saved_area("Example", theme);
+        assert_eq!(area.get_tile(3, 4).unwrap(), 5);
+        assert_eq!(area.get_tile(4, 4).unwrap(), 6);
+        assert_eq!(area.get_flip(4, 4).unwrap(), Flip::Horizontal);
+    }
+
+    // Strokes in the other theme are mirrored back, but palette-only ones aren't mirrored:
+    project.send(Message::SelectTheme(
+        AreaPosition::Main,
+        "Winter".to_string(),
+    ));
+    project.send(brush("Winter", 10, 10, 7, false));
+    project.send(brush("Winter", 12, 12, 7, true));
+    assert!(matches!(
+        project.state.undo_stack.last().unwrap().0,
+        Message::AreaBrush { .. }
+    ));
+    project.save();
+    assert_eq!(
+        project
+            .saved_area("Example", "Base")
+            .get_tile(10, 10)
+            .unwrap(),
+        7
+    );
+
+    // Undoing a stroke undoes it in both themes:
+    project.undo();
+    project.undo();
+    project.save();
+    for theme in ["Base", "Winter"] {
+        assert_ne!(
+            project
+                .saved_area("Example", theme)
+                .get_tile(10, 10)
+                .unwrap(),
+            7
+        );
+    }
+
+    project.send(Message::SetThemeSync(None));
+    project.send(brush("Winter", 20, 20, 9, false));
+    project.save();
+    assert_ne!(
+        project
+            .saved_area("Example", "Base")
+            .get_tile(20, 20)
+            .unwrap(),
+        9
+    );
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use common::{brush, brush_tiles, example_area_id, TestProject};
+use iced::Point;
+use z3_overworld_editor::{
+    message::Message,
+    state::{Dialogue, Flip, Tile},
+    tile_dedup::{find_duplicate_tiles, merge_messages, DuplicateTile},
+};
+
+#[test]
+fn duplicate_tiles_are_merged_and_cleared() {
+    let mut project = TestProject::new("tile-dedup");
+    let mut pixels = [[0; 8]; 8];
+    pixels[0][0] = 3;
+    pixels[2][1] = 5;
+    let tile = project.state.palettes[0].tiles[1];
+    let tile = Tile { pixels, ..tile };
+    let flipped = Flip::Horizontal.apply_to_tile(tile);
+    // Tiles 2 and 3 are copies of tile 1 (the second flipped), but tile 4 has another collision
+    // type. Tile 5 can't be replaced by tile 4, as it can be flipped vertically while tile 4
+    // can't, but tile 6 can:
+    let tile4 = Tile {
+        collision: 7,
+        v_flippable: false,
+        ..tile
+    };
+    let tile5 = Tile {
+        v_flippable: true,
+        ..tile4
+    };
+    let tile6 = tile4;
+    project.send_all([
+        Message::TilesetBrush {
+            palette_id: 0,
+            coords: Point::new(1, 0),
+            selected_gfx: vec![vec![tile, tile, flipped, tile4, tile5, tile6]],
+        },
+        brush(3, 4, 2),
+        brush_tiles(example_area_id(), 5, 6, vec![3], Flip::Vertical),
+    ]);
+
+    let duplicates = find_duplicate_tiles(&project.state.palettes[0]);
+    assert_eq!(
+        duplicates,
+        vec![
+            DuplicateTile {
+                tile_idx: 2,
+                original: 1,
+                flip: Flip::None,
+            },
+            DuplicateTile {
+                tile_idx: 3,
+                original: 1,
+                flip: Flip::Horizontal,
+            },
+            DuplicateTile {
+                tile_idx: 6,
+                original: 4,
+                flip: Flip::None,
+            },
+        ]
+    );
+    project.send(Message::DuplicateTilesDialogue(0));
+    assert!(matches!(
+        &project.state.dialogue,
+        Some(Dialogue::DuplicateTiles { duplicates: d, .. }) if d == &duplicates
+    ));
+
+    // Applying the merge queues the batch of changes:
+    project.send(Message::Batch(merge_messages(0, &duplicates)));
+    project.save();
+    let area = project.saved_area("Example", "Base");
+    assert_eq!(area.get_tile(3, 4).unwrap(), 1);
+    assert_eq!(area.get_flip(3, 4).unwrap(), Flip::None);
+    assert_eq!(area.get_tile(5, 6).unwrap(), 1);
+    assert_eq!(area.get_flip(5, 6).unwrap(), Flip::Both);
+    let palette = project.saved_palette("Default");
+    for i in [2, 3, 6] {
+        assert_eq!(palette.tiles[i], Tile::default());
+    }
+    assert_eq!(palette.tiles[5], tile5);
+    assert!(find_duplicate_tiles(&project.state.palettes[0]).is_empty());
+
+    project.undo();
+    project.save();
+    let area = project.saved_area("Example", "Base");
+    assert_eq!(area.get_tile(5, 6).unwrap(), 3);
+    assert_eq!(area.get_flip(5, 6).unwrap(), Flip::Vertical);
+    assert_eq!(project.saved_palette("Default").tiles[3], flipped);
+}
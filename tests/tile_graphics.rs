@@ -0,0 +1,118 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::{
+    helpers::scale_color,
+    message::Message,
+    tile_graphics::{
+        decode_planar, decode_sheets, encode_planar, encode_sheets, export_graphics, read_graphics,
+        with_imported_graphics, GraphicsFormat, TilePixels, SHEET_TILES,
+    },
+};
+
+fn gradient_tile(offset: u8, colors: u8) -> TilePixels {
+    let mut pixels: TilePixels = [[0; 8]; 8];
+    for (y, row) in pixels.iter_mut().enumerate() {
+        for (x, c) in row.iter_mut().enumerate() {
+            *c = (x as u8 + y as u8 + offset) % colors;
+        }
+    }
+    pixels
+}
+
+#[test]
+fn planar_tiles_round_trip() {
+    let mut pixels: TilePixels = [[0; 8]; 8];
+    pixels[0][0] = 5;
+    let data = encode_planar(&[pixels], GraphicsFormat::Bpp3).unwrap();
+    assert_eq!(data.len(), 24);
+    assert_eq!((data[0], data[1], data[16]), (0x80, 0x00, 0x80));
+
+    let tiles: Vec<TilePixels> = (0..3).map(|i| gradient_tile(i, 16)).collect();
+    let data = encode_planar(&tiles, GraphicsFormat::Bpp4).unwrap();
+    assert_eq!(data.len(), 3 * 32);
+    assert_eq!(decode_planar(&data, GraphicsFormat::Bpp4).unwrap(), tiles);
+    assert!(encode_planar(&tiles, GraphicsFormat::Bpp3).is_err());
+
+    let tiles: Vec<TilePixels> = (0..3).map(|i| gradient_tile(i, 8)).collect();
+    let data = encode_planar(&tiles, GraphicsFormat::Bpp3).unwrap();
+    assert_eq!(decode_planar(&data, GraphicsFormat::Bpp3).unwrap(), tiles);
+    assert!(decode_planar(&data[1..], GraphicsFormat::Bpp3).is_err());
+}
+
+#[test]
+fn png_graphics_round_trip_and_rgb_colors_are_mapped() {
+    let mut project = TestProject::new("tile-graphics-png");
+    let mut palette = project.state.palettes[0].clone();
+    palette.tiles[1].pixels = gradient_tile(0, 16);
+    let path = project.dir.join("gfx.png");
+    export_graphics(&path, &palette, GraphicsFormat::Png).unwrap();
+    let graphics = read_graphics(&path, &palette.colors, GraphicsFormat::Png).unwrap();
+    let pixels: Vec<TilePixels> = palette.tiles.iter().map(|t| t.pixels).collect();
+    assert_eq!(graphics, pixels);
+
+    // A non-indexed PNG, as saved by some editors, with each pixel in a palette color:
+    let mut colors = palette.colors;
+    for (i, c) in colors.iter_mut().enumerate() {
+        *c = [i as u8 * 2, 31 - i as u8, 0];
+    }
+    let color_idx = 3;
+    let [r, g, b] = colors[color_idx].map(scale_color);
+    let path = project.dir.join("rgb.png");
+    let mut encoder = png::Encoder::new(std::fs::File::create(&path).unwrap(), 8, 8);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&[r, g, b].repeat(64)).unwrap();
+    drop(writer);
+    let graphics = read_graphics(&path, &colors, GraphicsFormat::Png).unwrap();
+    assert_eq!(graphics, vec![[[color_idx as u8; 8]; 8]]);
+
+    project.send(Message::ReplacePalette(
+        with_imported_graphics(&palette, &graphics).unwrap(),
+    ));
+    assert_eq!(project.state.palettes[0].tiles[0].pixels, graphics[0]);
+}
+
+#[test]
+fn imported_bin_adds_tiles_and_is_undone() {
+    let mut project = TestProject::new("tile-graphics-bin");
+    let palette = project.state.palettes[0].clone();
+    let old_len = palette.tiles.len();
+    let tiles: Vec<TilePixels> = (0..old_len as u8 + 4)
+        .map(|i| gradient_tile(i, 8))
+        .collect();
+    let path = project.dir.join("gfx.bin");
+    std::fs::write(&path, encode_planar(&tiles, GraphicsFormat::Bpp3).unwrap()).unwrap();
+
+    let graphics = read_graphics(&path, &palette.colors, GraphicsFormat::Bpp3).unwrap();
+    let imported = with_imported_graphics(&palette, &graphics).unwrap();
+    project.send(Message::ReplacePalette(imported));
+    let tiles_now = &project.state.palettes[0].tiles;
+    assert_eq!(tiles_now.len(), old_len + 16);
+    assert_eq!(tiles_now[old_len + 3].pixels, tiles[old_len + 3]);
+    // Tile properties are kept:
+    assert_eq!(tiles_now[0].h_flippable, palette.tiles[0].h_flippable);
+
+    project.undo();
+    assert_eq!(project.state.palettes[0].tiles, palette.tiles);
+}
+
+#[test]
+fn compressed_sheets_round_trip() {
+    let tiles: Vec<TilePixels> = (0..SHEET_TILES as u8 + 5)
+        .map(|i| gradient_tile(i, 8))
+        .collect();
+    let data = encode_sheets(&tiles).unwrap();
+    assert!(data.len() < 2 * SHEET_TILES * 24);
+    // Two sheets, the second padded with blank tiles:
+    let decoded = decode_sheets(&data).unwrap();
+    assert_eq!(decoded.len(), 2 * SHEET_TILES);
+    assert_eq!(&decoded[..tiles.len()], &tiles[..]);
+    assert!(decoded[tiles.len()..].iter().all(|t| *t == [[0; 8]; 8]));
+
+    assert!(decode_sheets(&data[..data.len() - 1]).is_err());
+    let mut colorful = tiles.clone();
+    colorful[0][0][0] = 9;
+    assert!(encode_sheets(&colorful).is_err());
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use std::path::Path;
+
+use common::TestProject;
+use iced::Point;
+use z3_overworld_editor::{
+    message::{Message, SelectionSource},
+    persist::{rebuild_area_pngs, RebuildScope},
+    state::{AreaPosition, Flip, TileBlock, TileProperties, TilePropertyChange},
+};
+
+fn properties(project: &TestProject, tile_idx: usize) -> TileProperties {
+    project.state.palettes[0].tiles[tile_idx].properties()
+}
+
+#[test]
+fn selected_tiles_are_edited_together() {
+    let mut project = TestProject::new("tile-properties");
+    project.send(Message::AddTileRow(0));
+    project.send(Message::SetTileCollision {
+        palette_id: 0,
+        tile_idx: 1,
+        collision: 9,
+    });
+    project.send(Message::StartTileSelection(
+        Point::new(0, 0),
+        SelectionSource::Tileset,
+    ));
+    project.send(Message::EndTileSelection(Point::new(2, 1)));
+
+    let (palette_id, selected) = project.state.selected_tileset_tiles().unwrap();
+    assert_eq!(palette_id, 0);
+    let idxs: Vec<_> = selected.iter().map(|&(i, _)| i).collect();
+    assert_eq!(idxs, vec![0, 1, 2, 16, 17, 18]);
+    assert_eq!(selected[1].1.collision, 9);
+
+    // The editor turns a change into new properties for each tile:
+    let change = TilePropertyChange::Priority(true);
+    project.send(Message::SetTileProperties {
+        palette_id,
+        properties: selected
+            .iter()
+            .map(|&(i, p)| (i, p.with_change(change)))
+            .collect(),
+    });
+    for i in [0, 1, 2, 16, 17, 18] {
+        assert!(properties(&project, i).priority);
+    }
+    assert!(!properties(&project, 3).priority);
+    assert_eq!(properties(&project, 1).collision, 9);
+
+    project.undo();
+    for i in [0, 1, 2, 16, 17, 18] {
+        assert!(!properties(&project, i).priority);
+    }
+    assert_eq!(properties(&project, 1).collision, 9);
+}
+
+#[test]
+fn single_tile_selection_is_not_batch_edited() {
+    let mut project = TestProject::new("tile-properties-single");
+    project.send(Message::StartTileSelection(
+        Point::new(3, 0),
+        SelectionSource::Tileset,
+    ));
+    project.send(Message::EndTileSelection(Point::new(3, 0)));
+    assert!(project.state.selected_tileset_tiles().is_none());
+}
+
+fn png_pixel(path: &Path, x: usize, y: usize) -> [u8; 3] {
+    let decoder = png::Decoder::new(std::fs::File::open(path).unwrap());
+    let mut reader = decoder.read_info().unwrap();
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data).unwrap();
+    let addr = y * info.line_size + x * 3;
+    [data[addr], data[addr + 1], data[addr + 2]]
+}
+
+#[test]
+fn editor_only_tiles_are_left_out_of_area_png() {
+    let mut project = TestProject::new("tile-editor-only");
+    let area_id = project.state.main_area_id.clone();
+    project.send_all([
+        Message::BrushColor {
+            palette_id: 0,
+            color_idx: 5,
+            color: [31, 0, 0],
+        },
+        Message::BrushPixel {
+            palette_id: 0,
+            tile_idx: 1,
+            coords: Point::new(2, 3),
+            color_idx: 5,
+        },
+        Message::AreaBrush {
+            position: AreaPosition::Main,
+            area_id,
+            coords: Point::new(10, 4),
+            selection: TileBlock {
+                size: (1, 1),
+                palettes: vec![vec![0]],
+                tiles: vec![vec![1]],
+                flips: vec![vec![Flip::None]],
+                mask: None,
+            },
+            palette_only: false,
+        },
+    ]);
+    project.save();
+    let png_path = project.area_path("Example", "Base").with_extension("png");
+    let (x, y) = (10 * 8 + 2, 4 * 8 + 3);
+    assert_eq!(png_pixel(&png_path, x, y), [255, 0, 0]);
+    let bg = png_pixel(&png_path, 0, 0);
+
+    project.send(Message::SetTileProperties {
+        palette_id: 0,
+        properties: vec![(
+            1,
+            properties(&project, 1).with_change(TilePropertyChange::EditorOnly(true)),
+        )],
+    });
+    assert!(project.state.palettes[0].tiles[1].editor_only);
+    // The tile is still drawn in the editor, with its pixels kept:
+    assert_eq!(project.state.palettes[0].tiles[1].pixels[3][2], 5);
+    project.save();
+    assert!(project.saved_palette("Default").tiles[1].editor_only);
+    // Like palette colors, the tile's flag reaches the area's PNG when it's rebuilt:
+    rebuild_area_pngs(&mut project.state, &RebuildScope::default()).unwrap();
+    assert_eq!(png_pixel(&png_path, x, y), bg);
+
+    project.undo();
+    assert!(!properties(&project, 1).editor_only);
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::{
+    message::Message,
+    state::Dialogue,
+    tile_table::{tile_table, TileTableFormat},
+};
+
+#[test]
+fn tile_table_lists_each_tile_flags() {
+    let mut project = TestProject::new("tile-table");
+    let palette = &mut project.state.palettes[0];
+    palette.tiles[1].collision = 3;
+    palette.tiles[1].priority = true;
+    palette.tiles[1].h_flippable = false;
+    palette.tiles[1].v_flippable = true;
+    let palette = &project.state.palettes[0];
+
+    let csv = tile_table(palette, TileTableFormat::Csv);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "index,collision,priority,h_flippable,v_flippable");
+    assert_eq!(lines[2], "1,3,1,0,1");
+    assert_eq!(lines.len(), palette.tiles.len() + 1);
+
+    let markdown = tile_table(palette, TileTableFormat::Markdown);
+    assert!(markdown.starts_with(&format!("# Tiles of palette 0: {}\n", palette.name)));
+    assert!(markdown.contains("| 1 | 3 | yes |  | yes |\n"));
+}
+
+#[test]
+fn tile_table_is_exported_from_the_palette_dialogue() {
+    let mut project = TestProject::new("tile-table-export");
+    project.send(Message::ExportPaletteDialogue);
+    project.send(Message::SetExportPaletteTileTable(Some(
+        TileTableFormat::Csv,
+    )));
+    assert!(matches!(
+        project.state.dialogue,
+        Some(Dialogue::ExportPalette {
+            tile_table: Some(TileTableFormat::Csv),
+            ..
+        })
+    ));
+    let path = project.dir.join("tiles.csv");
+    project.send(Message::ExportPaletteTo(Some(path.clone())));
+    assert!(project.state.dialogue.is_none());
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        tile_table(&project.state.palettes[0], TileTableFormat::Csv)
+    );
+}
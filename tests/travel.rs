@@ -0,0 +1,68 @@
+mod common;
+
+use common::TestProject;
+use z3_overworld_editor::{
+    message::Message,
+    state::{AreaPosition, Dialogue},
+    travel::{travel_at, TravelKind, TravelPoint},
+};
+
+fn point(kind: TravelKind, slot: u8, position: (u16, u16)) -> TravelPoint {
+    TravelPoint {
+        kind,
+        slot,
+        position,
+    }
+}
+
+#[test]
+fn markers_and_destination_maps() {
+    let points = [
+        point(TravelKind::Flute, 3, (4, 6)),
+        point(TravelKind::Whirlpool, 1, (70, 5)),
+    ];
+    assert_eq!(travel_at(&points, 5, 7), Some(0));
+    assert_eq!(travel_at(&points, 6, 6), None);
+    assert_eq!(points[0].label(), "F3");
+    assert_eq!(points[1].label(), "W1");
+    // The second point is in the map to the right of the area's parent map:
+    assert_eq!(points[0].destination_map(0x18), 0x18);
+    assert_eq!(points[1].destination_map(0x18), 0x19);
+}
+
+#[test]
+fn travel_points_are_edited_and_saved_with_the_area() {
+    let mut project = TestProject::new("travel");
+    let area_id = project.state.main_area_id.clone();
+    project.state.main_area_mut().travel = vec![
+        point(TravelKind::Flute, 0, (4, 6)),
+        point(TravelKind::Whirlpool, 2, (10, 10)),
+    ];
+
+    project.send(Message::TravelPointDialogue {
+        position: AreaPosition::Main,
+        idx: 1,
+    });
+    assert!(matches!(
+        project.state.dialogue,
+        Some(Dialogue::TravelPoint { idx: 1, .. })
+    ));
+    project.send(Message::SetTravelPoint {
+        position: AreaPosition::Main,
+        area_id: area_id.clone(),
+        idx: 1,
+        point: point(TravelKind::Whirlpool, 2, (1000, 20)),
+    });
+    project.save();
+    let saved = project.saved_area(&area_id.area, &area_id.theme);
+    // Markers are kept within the area:
+    let width = saved.size.0 as u16 * 32;
+    assert_eq!(
+        saved.travel[1],
+        point(TravelKind::Whirlpool, 2, (width - 2, 20))
+    );
+    assert_eq!(saved.travel[0], point(TravelKind::Flute, 0, (4, 6)));
+
+    project.undo();
+    assert_eq!(project.state.main_area().travel[1].position, (10, 10));
+}
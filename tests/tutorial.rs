@@ -0,0 +1,47 @@
+mod common;
+
+use common::{read_json, TestProject};
+use z3_overworld_editor::{
+    message::Message,
+    state::{GlobalConfig, SidePanelView},
+};
+
+#[test]
+fn tour_steps_show_their_panels() {
+    let mut project = TestProject::new("tutorial-steps");
+    assert_eq!(project.state.tutorial_step, None);
+    project.send(Message::HelpDialogue);
+    project.send(Message::StartTutorial);
+    assert_eq!(project.state.tutorial_step, Some(0));
+    assert!(project.state.dialogue.is_none());
+
+    project.send(Message::ToggleSidePanel);
+    assert!(project.state.side_panel_hidden);
+    project.send(Message::SetTutorialStep(1));
+    assert_eq!(project.state.tutorial_step, Some(1));
+    assert!(!project.state.side_panel_hidden);
+    assert!(matches!(
+        project.state.side_panel_view,
+        SidePanelView::Tileset
+    ));
+
+    // Steps past the end are ignored:
+    project.send(Message::SetTutorialStep(100));
+    assert_eq!(project.state.tutorial_step, Some(1));
+}
+
+#[test]
+fn closing_the_tour_is_remembered() {
+    let mut project = TestProject::new("tutorial-close");
+    project.send(Message::StartTutorial);
+    project.send(Message::CloseTutorial);
+    assert_eq!(project.state.tutorial_step, None);
+    project.save();
+    let config: GlobalConfig = read_json(&project.dir.join("config.json"));
+    assert!(config.tutorial_seen);
+
+    // Configs from before the tour existed don't show it:
+    let old: GlobalConfig = serde_json::from_str(r#"{ "project_dir": null }"#).unwrap();
+    assert!(old.tutorial_seen);
+    assert!(!GlobalConfig::default().tutorial_seen);
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use common::{brush, example_area_id, TestProject};
+use z3_overworld_editor::message::Message;
+
+#[test]
+fn undo_preview_reverts_unless_committed() {
+    let mut project = TestProject::new("undo-preview");
+    project.send(brush(3, 4, 9));
+    project.save();
+    let tile_at = |project: &TestProject| {
+        project.state.areas[&example_area_id()]
+            .get_tile(3, 4)
+            .unwrap()
+    };
+
+    project.send(Message::PreviewUndo(true));
+    assert_eq!(tile_at(&project), 0);
+    // The temporarily undone state isn't saved:
+    project.send(Message::SaveProject);
+    assert_eq!(
+        project
+            .saved_area("Example", "Base")
+            .get_tile(3, 4)
+            .unwrap(),
+        9
+    );
+    project.send(Message::PreviewUndo(false));
+    assert_eq!(tile_at(&project), 9);
+    assert!(project.state.redo_stack.is_empty());
+
+    // Clicking undo while previewing keeps the undone state:
+    project.send_all([
+        Message::PreviewUndo(true),
+        Message::Undo,
+        Message::PreviewUndo(false),
+    ]);
+    assert_eq!(tile_at(&project), 0);
+    assert_eq!(project.state.redo_stack.len(), 1);
+}
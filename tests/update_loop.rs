@@ -0,0 +1,123 @@
+mod common;
+
+use common::{brush, brush_tiles, example_area_id, TestProject};
+use z3_overworld_editor::{message::Message, state::Flip};
+
+#[test]
+fn new_project_is_saved() {
+    let project = TestProject::new("new-project");
+    let area = project.saved_area("Example", "Base");
+    assert_eq!(area.size, (2, 2));
+    assert_eq!(area.screens.len(), 4);
+    assert!(project.palette_path("Default").exists());
+}
+
+#[test]
+fn brush_is_saved() {
+    let mut project = TestProject::new("brush");
+    project.send(brush_tiles(
+        example_area_id(),
+        3,
+        4,
+        vec![5],
+        Flip::Horizontal,
+    ));
+    project.save();
+
+    let area = project.saved_area("Example", "Base");
+    assert_eq!(area.get_tile(3, 4).unwrap(), 5);
+    assert_eq!(area.get_flip(3, 4).unwrap(), Flip::Horizontal);
+    assert_eq!(area.get_tile(4, 4).unwrap(), 0);
+}
+
+#[test]
+fn undo_and_redo_brush() {
+    let mut project = TestProject::new("undo-brush");
+    project.send_all([brush(3, 4, 5), brush(40, 40, 6)]);
+
+    project.undo();
+    project.save();
+    let area = project.saved_area("Example", "Base");
+    assert_eq!(area.get_tile(3, 4).unwrap(), 5);
+    assert_eq!(area.get_tile(40, 40).unwrap(), 0);
+
+    project.undo();
+    project.save();
+    let area = project.saved_area("Example", "Base");
+    assert_eq!(area.get_tile(3, 4).unwrap(), 0);
+    assert_eq!(area.get_flip(3, 4).unwrap(), Flip::None);
+
+    project.redo();
+    project.redo();
+    project.save();
+    let area = project.saved_area("Example", "Base");
+    assert_eq!(area.get_tile(3, 4).unwrap(), 5);
+    assert_eq!(area.get_tile(40, 40).unwrap(), 6);
+}
+
+#[test]
+fn add_and_delete_area() {
+    let mut project = TestProject::new("add-delete-area");
+    project.send(Message::AddArea {
+        name: "Second".to_string(),
+        size: (1, 1),
+    });
+    project.save();
+    assert_eq!(project.state.area_names, vec!["Example", "Second"]);
+    let area = project.saved_area("Second", "Base");
+    assert_eq!(area.size, (1, 1));
+
+    project.send(Message::DeleteArea("Second".to_string()));
+    project.save();
+    assert_eq!(project.state.area_names, vec!["Example"]);
+    assert!(!project.area_path("Second", "Base").exists());
+    assert_eq!(project.state.main_area_id, example_area_id());
+}
+
+#[test]
+fn undo_add_area() {
+    let mut project = TestProject::new("undo-add-area");
+    project.send(Message::AddArea {
+        name: "Second".to_string(),
+        size: (1, 1),
+    });
+    project.undo();
+    project.save();
+    assert_eq!(project.state.area_names, vec!["Example"]);
+    assert!(!project.area_path("Second", "Base").exists());
+}
+
+#[test]
+fn last_area_cannot_be_deleted() {
+    let mut project = TestProject::new("delete-last-area");
+    project.send(Message::DeleteArea("Example".to_string()));
+    project.save();
+    assert_eq!(project.state.area_names, vec!["Example"]);
+    assert!(project.area_path("Example", "Base").exists());
+}
+
+#[test]
+fn add_and_delete_palette() {
+    let mut project = TestProject::new("add-delete-palette");
+    project.send(Message::AddPalette {
+        name: "Extra".to_string(),
+        id: 7,
+    });
+    project.save();
+    let pal = project.saved_palette("Extra");
+    assert_eq!(pal.id, 7);
+    assert_eq!(
+        pal.tiles.len(),
+        project.saved_palette("Default").tiles.len()
+    );
+
+    project.send(Message::DeletePalette(7));
+    project.save();
+    assert!(!project.palette_path("Extra").exists());
+    assert_eq!(project.state.palettes.len(), 1);
+
+    // Deleting a palette can be undone, restoring its file on the next save:
+    project.undo();
+    project.save();
+    assert_eq!(project.saved_palette("Extra").id, 7);
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use common::{brush, example_area_id, TestProject};
+use iced::Vector;
+use z3_overworld_editor::{
+    message::Message,
+    state::{AreaId, AreaPosition, SidePanelView},
+    usages::UsageScan,
+};
+
+#[test]
+fn scan_finds_saved_and_unsaved_usages() {
+    let mut project = TestProject::new("usages-scan");
+    project.send(brush(40, 40, 5));
+    project.save();
+    project.send(brush(3, 4, 5));
+
+    let usages = UsageScan::new(&project.state, 0, 5).unwrap().run();
+    let positions: Vec<_> = usages.iter().map(|u| (u.x, u.y)).collect();
+    assert_eq!(positions, vec![(3, 4), (40, 40)]);
+    assert!(usages.iter().all(|u| u.area_id == example_area_id()));
+
+    // Areas that aren't loaded are read from disk:
+    project.send(Message::AddArea {
+        name: "Other".to_string(),
+        size: (1, 1),
+    });
+    project.save();
+    project
+        .state
+        .switch_area(AreaPosition::Main, &example_area_id())
+        .unwrap();
+    project.state.areas.remove(&AreaId {
+        area: "Other".to_string(),
+        theme: "Base".to_string(),
+    });
+    let usages = UsageScan::new(&project.state, 0, 0).unwrap().run();
+    assert!(usages.iter().any(|u| u.area_id.area == "Other"));
+}
+
+#[test]
+fn results_are_shown_and_jumped_to() {
+    let mut project = TestProject::new("usages-results");
+    project.send(brush(40, 40, 5));
+    project.state.palette_idx = 0;
+    project.state.tile_idx = Some(5);
+    project.send(Message::FindTileUsages);
+    assert!(matches!(
+        project.state.side_panel_view,
+        SidePanelView::Usages
+    ));
+    let search = project.state.usage_search.as_ref().unwrap();
+    assert_eq!((search.palette_id, search.tile_idx), (0, 5));
+    assert!(search.usages.is_none());
+
+    // Results for another tile (from an earlier search) are ignored:
+    project.send(Message::TileUsagesFound {
+        palette_id: 0,
+        tile_idx: 6,
+        usages: vec![],
+    });
+    assert!(project
+        .state
+        .usage_search
+        .as_ref()
+        .unwrap()
+        .usages
+        .is_none());
+
+    let usages = UsageScan::new(&project.state, 0, 5).unwrap().run();
+    project.send(Message::TileUsagesFound {
+        palette_id: 0,
+        tile_idx: 5,
+        usages: usages.clone(),
+    });
+    let found = project.state.usage_search.as_ref().unwrap().usages.clone();
+    assert_eq!(found, Some(usages.clone()));
+
+    project.send(Message::AddArea {
+        name: "Other".to_string(),
+        size: (1, 1),
+    });
+    assert_eq!(project.state.main_area_id.area, "Other");
+    project.send(Message::GoToTileUsage(usages[0].clone()));
+    assert_eq!(project.state.main_area_id, example_area_id());
+    let scroll = (40.0 - 8.0) * 8.0 * project.state.global_config.pixel_size;
+    assert_eq!(project.state.main_area_scroll, Vector::new(scroll, scroll));
+
+    project.send(Message::CloseTileUsages);
+    assert!(project.state.usage_search.is_none());
+    assert!(matches!(
+        project.state.side_panel_view,
+        SidePanelView::Tileset
+    ));
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use common::TestProject;
+use iced::Vector;
+use z3_overworld_editor::{
+    message::Message,
+    state::{AreaId, Flip, SidePanelView},
+    validation::{IssueLocation, ValidationScan},
+};
+
+#[test]
+fn broken_references_are_found() {
+    let mut project = TestProject::new("validation-scan");
+    let area_id = project.state.main_area_id.clone();
+    project.state.palettes[0].tiles[3].h_flippable = false;
+    let area = project.state.main_area_mut();
+    area.set_palette(1, 0, 99).unwrap();
+    area.set_tile(2, 0, 200).unwrap();
+    area.set_tile(3, 0, 3).unwrap();
+    area.set_flip(3, 0, Flip::Horizontal).unwrap();
+    // Flipping vertically is allowed:
+    area.set_tile(4, 0, 3).unwrap();
+    area.set_flip(4, 0, Flip::Vertical).unwrap();
+
+    let issues = ValidationScan::new(&project.state).unwrap().run();
+    let locations: Vec<_> = issues.iter().map(|i| i.location.clone()).collect();
+    let tile = |x, y| IssueLocation::Tile {
+        area_id: area_id.clone(),
+        x,
+        y,
+    };
+    assert_eq!(locations, vec![tile(1, 0), tile(2, 0), tile(3, 0)]);
+    assert!(issues[0].description.contains("palette 99 does not exist"));
+
+    // Screens not matching the area's size are reported (and the extra ones skipped):
+    project.state.main_area_mut().size = (1, 2);
+    let issues = ValidationScan::new(&project.state).unwrap().run();
+    assert_eq!(issues[0].location, IssueLocation::Area(area_id.clone()));
+    assert!(issues[0].description.contains("4 screens"));
+}
+
+#[test]
+fn issues_are_shown_and_jumped_to() {
+    let mut project = TestProject::new("validation-panel");
+    project.send(Message::ValidateProject);
+    assert!(matches!(
+        project.state.side_panel_view,
+        SidePanelView::Issues
+    ));
+    assert!(project.state.project_issues.is_none());
+    let issues = ValidationScan::new(&project.state).unwrap().run();
+    assert!(issues.is_empty());
+    project.send(Message::ValidationFinished(issues));
+    assert_eq!(project.state.project_issues, Some(vec![]));
+
+    project.send(Message::AddArea {
+        name: "Other".to_string(),
+        size: (1, 1),
+    });
+    project.send(Message::GoToIssue(IssueLocation::Tile {
+        area_id: AreaId {
+            area: "Example".to_string(),
+            theme: "Base".to_string(),
+        },
+        x: 40,
+        y: 40,
+    }));
+    assert_eq!(project.state.main_area_id.area, "Example");
+    let scroll = (40.0 - 8.0) * 8.0 * project.state.global_config.pixel_size;
+    assert_eq!(project.state.main_area_scroll, Vector::new(scroll, scroll));
+
+    project.send(Message::CloseIssues);
+    assert!(matches!(
+        project.state.side_panel_view,
+        SidePanelView::Tileset
+    ));
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use common::{example_area_id, TestProject};
+use iced::Vector;
+use z3_overworld_editor::{message::Message, state::Dialogue, vram_usage::vram_usage};
+
+#[test]
+fn screens_over_budget_are_flagged() {
+    let mut project = TestProject::new("vram-usage");
+    for id in 1..=6 {
+        project.send(Message::AddPalette {
+            name: format!("Palette {}", id),
+            id,
+        });
+    }
+    // Seven palette rows on the bottom-right screen, one more than the overworld has:
+    let area = project.state.areas.get_mut(&example_area_id()).unwrap();
+    for id in 0..=6 {
+        area.set_palette(32 + id, 32, id).unwrap();
+        area.set_tile(32 + id, 32, 1).unwrap();
+    }
+    area.modified = true;
+
+    project.send(Message::VramUsage(false));
+    let Some(Dialogue::VramUsage { all_areas, screens }) = &project.state.dialogue else {
+        panic!("VRAM usage not shown");
+    };
+    assert!(!all_areas);
+    assert_eq!(screens.len(), 4);
+    let over: Vec<_> = screens.iter().filter(|s| !s.fits()).collect();
+    assert_eq!(over.len(), 1);
+    assert_eq!(over[0].screen, (1, 1));
+    assert_eq!(over[0].palette_count, 7);
+    assert_eq!(over[0].problems(), vec!["7 palette rows of 6".to_string()]);
+
+    project.send(Message::AddArea {
+        name: "Other".to_string(),
+        size: (1, 1),
+    });
+    assert_eq!(vram_usage(&project.state, true).unwrap().len(), 5);
+    project.send(Message::VramUsage(true));
+    assert!(matches!(
+        project.state.dialogue,
+        Some(Dialogue::VramUsage {
+            all_areas: true,
+            ..
+        })
+    ));
+
+    // Clicking a flagged screen goes to it:
+    project.send(Message::GoToAreaScreen(example_area_id(), (1, 1)));
+    assert_eq!(project.state.main_area_id, example_area_id());
+    assert!(project.state.dialogue.is_none());
+    let scroll = 256.0 * project.state.global_config.pixel_size;
+    assert_eq!(project.state.main_area_scroll, Vector::new(scroll, scroll));
+}
@@ -0,0 +1,190 @@
+mod common;
+
+use std::path::Path;
+
+use common::{brush, TestProject};
+use z3_overworld_editor::{
+    external_changes::{ChangeDiff, ChangedFile, ReloadChoice},
+    message::Message,
+    state::{Dialogue, Tile},
+};
+
+#[test]
+fn saves_are_recorded_as_own_writes() {
+    let mut project = TestProject::new("watcher-own-writes");
+    let recent = project.state.recent_writes.clone();
+    let area_json = project.area_path("Example", "Base");
+    let area_png = area_json.with_extension("png");
+    let palette_json = project.palette_path("Default");
+    assert!(recent.is_own_write(&area_json));
+    assert!(recent.is_own_write(&area_png));
+    assert!(recent.is_own_write(&palette_json));
+    assert!(!recent.is_own_write(&project.project_dir().join("Areas/Other/Base.json")));
+
+    // Files in a renamed area directory are covered by the directory:
+    project.send(Message::AddArea {
+        name: "Old".to_string(),
+        size: (1, 1),
+    });
+    project.save();
+    project.send(Message::EditArea {
+        old_name: "Old".to_string(),
+        new_name: "New".to_string(),
+    });
+    assert!(recent.is_own_write(&project.area_path("New", "Base")));
+    assert!(recent.is_own_write(&project.area_path("Old", "Base")));
+}
+
+// Change a file as another program would, and report it as the watcher would.
+fn change_externally(project: &mut TestProject, path: &Path, contents: String) {
+    std::fs::write(path, contents).unwrap();
+    project
+        .state
+        .changed_files
+        .lock()
+        .unwrap()
+        .insert(path.to_owned());
+}
+
+#[test]
+fn changed_palette_is_reloaded_keeping_other_changes() {
+    let mut project = TestProject::new("watcher-palette-reload");
+    project.send(Message::AddPalette {
+        name: "Extra".to_string(),
+        id: 7,
+    });
+    project.save();
+    project.send(brush(3, 4, 5));
+
+    let path = project.palette_path("Extra");
+    let mut pal = project.saved_palette("Extra");
+    pal.colors[1] = [31, 0, 0];
+    change_externally(
+        &mut project,
+        &path,
+        serde_json::to_string_pretty(&pal).unwrap(),
+    );
+    project.save();
+    assert!(matches!(
+        project.state.dialogue,
+        Some(Dialogue::ModifiedReload)
+    ));
+    let changes = &project.state.external_changes;
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].file, ChangedFile::Palette("Extra".to_string()));
+    assert_eq!(changes[0].summary, "1 colors and 0 tiles differ");
+    assert_eq!(changes[0].choice, ReloadChoice::Reload);
+    // Nothing is saved until the change is resolved:
+    assert_eq!(
+        project.saved_area("Example", "Base").screens[0].tiles[4][3],
+        0
+    );
+
+    project.send(Message::ApplyExternalChanges);
+    assert!(project.state.dialogue.is_none());
+    let idx = project.state.palettes_id_idx_map[&7];
+    assert_eq!(project.state.palettes[idx].colors[1], [31, 0, 0]);
+    assert!(project.state.main_area().modified);
+    project.save();
+    assert_eq!(
+        project.saved_area("Example", "Base").screens[0].tiles[4][3],
+        5
+    );
+    assert_eq!(project.saved_palette("Extra").colors[1], [31, 0, 0]);
+}
+
+#[test]
+fn conflicting_area_change_is_kept_or_reloaded_as_chosen() {
+    let mut project = TestProject::new("watcher-area-conflict");
+    let path = project.area_path("Example", "Base");
+    let mut theirs = project.saved_area("Example", "Base");
+    theirs.screens[0].tiles[1][1] = 9;
+    let theirs_json = serde_json::to_string(&theirs).unwrap();
+
+    // The editor's unsaved version is kept by default, and saved over the file:
+    project.send(brush(3, 4, 5));
+    change_externally(&mut project, &path, theirs_json.clone());
+    project.save();
+    let change = &project.state.external_changes[0];
+    assert!(change.unsaved);
+    assert_eq!(change.summary, "2 tiles differ");
+    assert_eq!(change.choice, ReloadChoice::KeepMine);
+    project.send(Message::ApplyExternalChanges);
+    project.save();
+    let saved = project.saved_area("Example", "Base");
+    assert_eq!(saved.screens[0].tiles[4][3], 5);
+    assert_eq!(saved.screens[0].tiles[1][1], 0);
+
+    // Or replaced by the file's version:
+    project.send(brush(6, 6, 7));
+    change_externally(&mut project, &path, theirs_json);
+    project.save();
+    project.send(Message::SetReloadChoice(0, ReloadChoice::Reload));
+    project.send(Message::ApplyExternalChanges);
+    let area = project.state.main_area();
+    assert!(!area.modified);
+    assert_eq!(area.screens[0].tiles[1][1], 9);
+    assert_eq!(area.screens[0].tiles[6][6], 0);
+    assert!(project.state.undo_stack.is_empty());
+}
+
+#[test]
+fn differences_are_listed_for_each_change() {
+    let mut project = TestProject::new("watcher-diff");
+    project.send(Message::AddPalette {
+        name: "Extra".to_string(),
+        id: 7,
+    });
+    project.save();
+    let pal_path = project.palette_path("Extra");
+    let mut pal = project.saved_palette("Extra");
+    let tile_count = pal.tiles.len();
+    pal.colors[1] = [31, 0, 0];
+    pal.tiles.push(Tile::default());
+    change_externally(
+        &mut project,
+        &pal_path,
+        serde_json::to_string(&pal).unwrap(),
+    );
+    let area_path = project.area_path("Example", "Base");
+    let mut area = project.saved_area("Example", "Base");
+    area.screens[0].tiles[1][1] = 9;
+    change_externally(
+        &mut project,
+        &area_path,
+        serde_json::to_string(&area).unwrap(),
+    );
+    project.save();
+
+    let changes = &project.state.external_changes;
+    assert_eq!(changes.len(), 2);
+    assert!(changes.iter().all(|c| !c.show_diff));
+    let palette_change = changes
+        .iter()
+        .position(|c| c.file == ChangedFile::Palette("Extra".to_string()))
+        .unwrap();
+    let Some(ChangeDiff::Palette {
+        colors,
+        disk_colors,
+        tiles,
+        disk_tile_count,
+        ..
+    }) = &changes[palette_change].diff
+    else {
+        panic!("expected a palette diff");
+    };
+    assert_eq!(colors[1], [0, 0, 0]);
+    assert_eq!(disk_colors[1], [31, 0, 0]);
+    assert_eq!(tiles, &vec![tile_count]);
+    assert_eq!(*disk_tile_count, tile_count + 1);
+    let area_change = 1 - palette_change;
+    let Some(ChangeDiff::Area { changes, .. }) = &changes[area_change].diff else {
+        panic!("expected an area diff");
+    };
+    assert_eq!(changes.tiles, vec![(1, 1)]);
+    assert_eq!(changes.screens.len(), 1);
+
+    project.send(Message::ToggleReloadDiff(palette_change));
+    assert!(project.state.external_changes[palette_change].show_diff);
+    assert!(!project.state.external_changes[area_change].show_diff);
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::TestProject;
+use iced::Point;
+use z3_overworld_editor::{message::Message, window_state::WindowGeometry};
+
+#[test]
+fn maximized_window_keeps_restored_geometry() {
+    let mut project = TestProject::new("window-geometry");
+    project.send_all([
+        Message::SetWindowGeometry {
+            position: Some(Point::new(50.0, 60.0)),
+            size: Some(iced::Size::new(800.0, 600.0)),
+            maximized: false,
+        },
+        Message::SetWindowGeometry {
+            position: Some(Point::new(0.0, 0.0)),
+            size: Some(iced::Size::new(1920.0, 1080.0)),
+            maximized: true,
+        },
+    ]);
+    let geometry = project.state.global_config.window.unwrap();
+    assert!(geometry.maximized);
+    assert_eq!((geometry.x, geometry.y), (50.0, 60.0));
+    assert_eq!((geometry.width, geometry.height), (800.0, 600.0));
+
+    // Saved on a secondary monitor: restored if the primary monitor is unchanged, but
+    // centered if the setup changed and the position is off the primary monitor:
+    let geometry = WindowGeometry {
+        x: 2500.0,
+        monitor_width: 1920.0,
+        monitor_height: 1080.0,
+        ..geometry
+    };
+    assert_eq!(
+        geometry.restored_position(iced::Size::new(1920.0, 1080.0)),
+        Some(Point::new(2500.0, 60.0))
+    );
+    assert_eq!(
+        geometry.restored_position(iced::Size::new(1280.0, 720.0)),
+        None
+    );
+}
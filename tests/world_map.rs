@@ -0,0 +1,81 @@
+mod common;
+
+use common::{read_json, TestProject};
+use z3_overworld_editor::{
+    message::Message,
+    world_map::{overlaps, WorldMapArea},
+};
+
+#[test]
+fn world_map_layout_is_saved() {
+    let mut project = TestProject::new("world-map");
+    project.send(Message::OpenWorldMap("Base".to_string()));
+    let world_map = project.state.world_map.clone().unwrap();
+    assert_eq!(world_map.areas.len(), 1);
+    assert_eq!(world_map.areas[0].size, (2, 2));
+    assert!(world_map.areas[0].png_path.exists());
+    // Areas without a vanilla map default to a row below the vanilla overworld:
+    let default_position = world_map.positions(&project.state)[0];
+
+    project.send(Message::SetWorldMapPosition {
+        area: "Example".to_string(),
+        position: Some((3, 4)),
+    });
+    assert_eq!(world_map.positions(&project.state), vec![(3, 4)]);
+    project.save();
+    let metadata: serde_json::Value = read_json(&project.project_dir().join("Project.json"));
+    assert_eq!(
+        metadata["world_layout"]["Example"],
+        serde_json::json!([3, 4])
+    );
+
+    project.undo();
+    assert_eq!(world_map.positions(&project.state), vec![default_position]);
+    project.redo();
+
+    // Areas are laid out without overlapping, by their sizes in screens:
+    let mut layout = world_map.clone();
+    let area = |name: &str, size, vanilla_map_id| WorldMapArea {
+        name: name.to_string(),
+        size,
+        vanilla_map_id,
+        png_path: Default::default(),
+    };
+    layout.areas = vec![
+        area("A", (2, 2), Some(0x00)),
+        area("B", (2, 2), Some(0x01)),
+        area("C", (2, 2), Some(0x08)),
+        area("D", (2, 2), Some(0x40)),
+        area("E", (2, 2), Some(0x80)),
+        area("F", (10, 2), None),
+        area("G", (8, 2), None),
+    ];
+    let positions = layout.positions(&project.state);
+    for i in 0..layout.areas.len() {
+        for j in 0..i {
+            let (a, b) = (&layout.areas[i], &layout.areas[j]);
+            assert!(
+                !overlaps(positions[i], a.size, positions[j], b.size),
+                "{} overlaps {}",
+                a.name,
+                b.name
+            );
+        }
+    }
+    assert_eq!(positions[1], (2, 0));
+    assert_eq!(positions[3], (17, 0));
+
+    // The layout follows the area when renamed:
+    project.send(Message::EditArea {
+        old_name: "Example".to_string(),
+        new_name: "Renamed".to_string(),
+    });
+    let layout = &project.state.project_metadata.world_layout;
+    assert_eq!(layout.get("Renamed"), Some(&(3, 4)));
+    assert!(!layout.contains_key("Example"));
+
+    // Double-clicking an area opens it and leaves the world map:
+    project.send(Message::OpenWorldMapArea("Renamed".to_string()));
+    assert!(project.state.world_map.is_none());
+    assert_eq!(project.state.main_area_id.area, "Renamed");
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use common::{key_press, TestProject};
+use z3_overworld_editor::message::Message;
+
+#[test]
+fn zoom_presets_are_remembered_per_display() {
+    let mut project = TestProject::new("per-display-zoom");
+    project.send(key_press("4"));
+    assert_eq!(project.state.global_config.pixel_size, 4.0);
+
+    // A laptop panel at 200% and an external monitor at 100%:
+    project.send(Message::SetScaleFactor(2.0));
+    project.send(Message::SetPerDisplayZoom(true));
+    project.send(key_press("2"));
+    project.send(Message::SetScaleFactor(1.0));
+    assert_eq!(project.state.global_config.pixel_size, 2.0);
+    project.send(key_press("5"));
+    assert_eq!(project.state.global_config.pixel_size, 6.0);
+
+    project.send(Message::SetScaleFactor(2.0));
+    assert_eq!(project.state.global_config.pixel_size, 2.0);
+    project.send(Message::SetScaleFactor(1.0));
+    assert_eq!(project.state.global_config.pixel_size, 6.0);
+
+    // Without per-display zoom, moving between displays keeps the zoom level:
+    project.send(Message::SetPerDisplayZoom(false));
+    project.send(Message::SetScaleFactor(2.0));
+    assert_eq!(project.state.global_config.pixel_size, 6.0);
+}